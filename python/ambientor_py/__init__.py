@@ -21,6 +21,9 @@ Typical usage
     eng = engine(sample_rate=48_000, channels=2, gain=0.35)
     block = eng.render_block(1024)       # list[float], interleaved
     eng.render_to_file("demo.wav", 10.0) # offline render
+    eng.render_loop_to_file("loop.wav", 30.0, crossfade=5.0)  # seamless loop
+    for ev in eng.poll_events():         # notes, thunder, sections since the last call
+        print(ev["kind"])
 
 """
 
@@ -49,6 +52,7 @@ def engine(
     sample_rate: float = 48_000.0,
     channels: int = 2,
     gain: float = 0.35,
+    ahead_frames: int | None = None,
 ):
     """
     Convenience factory for :class:`AmbientorEngine`.
@@ -61,6 +65,9 @@ def engine(
         Number of output channels. Default: 2
     gain:
         Linear output gain multiplier. Typical range 0.0–1.0. Default: 0.35
+    ahead_frames:
+        Render this many frames ahead on a worker thread (0 = 4096) for live
+        playback. Default: None (render inline in ``render_block``).
 
     Returns
     -------
@@ -82,4 +89,5 @@ def engine(
         sample_rate=sample_rate,
         channels=channels,
         gain=gain,
+        ahead_frames=ahead_frames,
     )
\ No newline at end of file
@@ -8,7 +8,7 @@
 
 use pyo3::exceptions::PyRuntimeError;
 use pyo3::prelude::*;
-use std::ffi::c_void;
+use std::ffi::{c_char, c_void, CString};
 
 // ----------------------------- FFI bridge ---------------------------------------
 
@@ -20,9 +20,20 @@ pub struct AmbientorEngineOpaque {
 
 type AmbientorEngineHandle = AmbientorEngineOpaque;
 
+// Mirrors `AmbientorEvent` in ambientor.h.
+#[repr(C)]
+#[derive(Default)]
+struct AmbientorEvent {
+    kind: u32,
+    pitch: f32,
+    amp: f32,
+    section: u32,
+}
+
 #[link(name = "ambientor_ffi")]
 extern "C" {
     fn ambientor_create(sample_rate: f32) -> *mut AmbientorEngineHandle;
+    fn ambientor_create_buffered(sample_rate: f32, ahead_frames: u32) -> *mut AmbientorEngineHandle;
     fn ambientor_destroy(engine: *mut AmbientorEngineHandle);
     fn ambientor_scene_set_out_gain(engine: *mut AmbientorEngineHandle, gain: f32);
     fn ambientor_render_interleaved_f32(
@@ -31,46 +42,71 @@ extern "C" {
         frames: u32,
         channels: u32,
     ) -> u32;
+    fn ambientor_set_dither(engine: *mut AmbientorEngineHandle, mode: u8);
+    fn ambientor_render_to_file(
+        engine: *mut AmbientorEngineHandle,
+        path: *const c_char,
+        frames: u64,
+        channels: u32,
+        format: u8,
+        quality: f32,
+    ) -> bool;
+    fn ambientor_render_loop_to_file(
+        engine: *mut AmbientorEngineHandle,
+        path: *const c_char,
+        loop_frames: u64,
+        fade_frames: u64,
+        channels: u32,
+        format: u8,
+        quality: f32,
+    ) -> bool;
+    fn ambientor_export_format_available(format: u8) -> bool;
+    fn ambientor_set_export_description(engine: *mut AmbientorEngineHandle, description: *const c_char);
+    fn ambientor_poll_event(engine: *mut AmbientorEngineHandle, out: *mut AmbientorEvent) -> bool;
 }
 
-// ----------------------------- Helper: WAV writer -------------------------------
-
-fn write_wav_i16(path: &str, sr: u32, channels: u16, data: &[i16]) -> std::io::Result<()> {
-    use std::fs::File;
-    use std::io::Write;
-
-    let mut f = File::create(path)?;
-
-    let bytes_per_sample: u16 = 2;
-    let block_align: u16 = channels * bytes_per_sample;
-    let byte_rate: u32 = sr * block_align as u32;
-    let data_len_bytes: u32 = (data.len() * 2) as u32;
-    let riff_chunk_size: u32 = 36 + data_len_bytes;
-
-    // RIFF header
-    f.write_all(b"RIFF")?;
-    f.write_all(&riff_chunk_size.to_le_bytes())?;
-    f.write_all(b"WAVE")?;
-
-    // fmt chunk
-    f.write_all(b"fmt ")?;
-    f.write_all(&16u32.to_le_bytes())?; // PCM chunk size
-    f.write_all(&1u16.to_le_bytes())?; // PCM format
-    f.write_all(&channels.to_le_bytes())?;
-    f.write_all(&sr.to_le_bytes())?;
-    f.write_all(&byte_rate.to_le_bytes())?;
-    f.write_all(&block_align.to_le_bytes())?;
-    f.write_all(&16u16.to_le_bytes())?; // bits per sample
-
-    // data chunk
-    f.write_all(b"data")?;
-    f.write_all(&data_len_bytes.to_le_bytes())?;
-    for s in data {
-        f.write_all(&s.to_le_bytes())?;
+/// Dither codes understood by `ambientor_set_dither` (see ambientor.h).
+fn dither_code(name: &str) -> PyResult<u8> {
+    match name.to_ascii_lowercase().as_str() {
+        "off" | "none" => Ok(0),
+        "tpdf" => Ok(1),
+        "shaped" => Ok(2),
+        _ => Err(PyRuntimeError::new_err(format!(
+            "unknown dither mode: {name} (expected 'tpdf', 'shaped' or 'off')"
+        ))),
     }
+}
 
-    f.flush()?;
-    Ok(())
+/// `AMBIENTOR_FORMAT_*` code for an explicit `format` name, or guessed from the
+/// path's extension (WAV if unknown). Fails if this build cannot write it.
+fn format_code(path: &str, format: Option<&str>) -> PyResult<u8> {
+    let name = match format {
+        Some(f) => f.to_ascii_lowercase(),
+        None => std::path::Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map_or_else(|| "wav".to_string(), str::to_ascii_lowercase),
+    };
+    let code = match name.as_str() {
+        "wav" | "wave" => 0,
+        "ogg" | "oga" | "vorbis" => 1,
+        _ if format.is_none() => 0,
+        other => {
+            return Err(PyRuntimeError::new_err(format!(
+                "unknown format: {other} (expected 'wav' or 'ogg')"
+            )))
+        }
+    };
+    if !unsafe { ambientor_export_format_available(code) } {
+        return Err(PyRuntimeError::new_err(format!(
+            "format '{name}' is not available in this build (libambientor_ffi needs the `vorbis` feature)"
+        )));
+    }
+    Ok(code)
+}
+
+fn c_string(s: &str, what: &str) -> PyResult<CString> {
+    CString::new(s).map_err(|_| PyRuntimeError::new_err(format!("{what} must not contain NUL bytes")))
 }
 
 // ----------------------------- Python class -------------------------------------
@@ -84,13 +120,16 @@ fn write_wav_i16(path: &str, sr: u32, channels: u16, data: &[i16]) -> std::io::R
 ///
 /// eng = AmbientorEngine(sample_rate=48_000, channels=2, gain=0.35)
 /// block = eng.render_block(1024)            # returns list[float]
+/// live = AmbientorEngine(ahead_frames=4096)  # rendered ahead on a worker thread
 /// eng.render_to_file("test.wav", 10.0)      # offline render
+/// eng.render_loop_to_file("loop.wav", 30.0) # seamless loop
 /// ```
 #[pyclass]
 pub struct AmbientorEngine {
     ptr: *mut AmbientorEngineHandle,
     sample_rate: f32,
     channels: u32,
+    buf: Vec<f32>, // reused by `render_block`
 }
 
 unsafe impl Send for AmbientorEngine {}
@@ -104,14 +143,20 @@ impl AmbientorEngine {
     ///     sample_rate (float): Sample rate in Hz (default 48000.0).
     ///     channels (int): Number of output channels (default 2).
     ///     gain (float): Output gain multiplier (default 0.35).
+    ///     ahead_frames (int | None): Render this many frames ahead on a worker
+    ///         thread (0 = 4096), so `render_block` only copies out and keeps up
+    ///         with a live audio callback. Default: render inline.
     #[new]
-    #[pyo3(signature = (sample_rate = 48_000.0, channels = 2, gain = 0.35))]
-    pub fn new(sample_rate: f32, channels: u32, gain: f32) -> PyResult<Self> {
+    #[pyo3(signature = (sample_rate = 48_000.0, channels = 2, gain = 0.35, ahead_frames = None))]
+    pub fn new(sample_rate: f32, channels: u32, gain: f32, ahead_frames: Option<u32>) -> PyResult<Self> {
         if channels == 0 {
             return Err(PyRuntimeError::new_err("channels must be >= 1"));
         }
 
-        let ptr = unsafe { ambientor_create(sample_rate) };
+        let ptr = match ahead_frames {
+            Some(ahead) => unsafe { ambientor_create_buffered(sample_rate, ahead) },
+            None => unsafe { ambientor_create(sample_rate) },
+        };
         if ptr.is_null() {
             return Err(PyRuntimeError::new_err(
                 "ambientor_create() returned null pointer",
@@ -126,6 +171,7 @@ impl AmbientorEngine {
             ptr,
             sample_rate,
             channels,
+            buf: Vec::new(),
         })
     }
 
@@ -156,12 +202,12 @@ impl AmbientorEngine {
         }
 
         let total_samples = frames * self.channels as usize;
-        let mut buf = vec![0.0f32; total_samples];
+        self.buf.resize(total_samples, 0.0);
 
         let written = unsafe {
             ambientor_render_interleaved_f32(
                 self.ptr,
-                buf.as_mut_ptr(),
+                self.buf.as_mut_ptr(),
                 frames as u32,
                 self.channels,
             )
@@ -169,59 +215,150 @@ impl AmbientorEngine {
 
         let used_frames = (written as usize).min(frames);
         let used_samples = used_frames * self.channels as usize;
-        buf.truncate(used_samples);
 
-        Ok(pyo3::types::PyList::new(py, &buf))
+        Ok(pyo3::types::PyList::new(py, &self.buf[..used_samples]))
     }
 
-    /// Offline render straight to a 16-bit PCM WAV file.
+    /// Offline render straight to a file, written block by block by the engine.
     ///
     /// Args:
-    ///     path (str): Output path for the WAV file.
+    ///     path (str): Output path.
     ///     seconds (float): Duration in seconds (must be > 0).
-    pub fn render_to_file(&mut self, path: &str, seconds: f32) -> PyResult<()> {
+    ///     dither (str): For WAV: "tpdf" (default), "shaped" (TPDF + noise shaping) or "off".
+    ///     format (str | None): "wav" or "ogg"; default: from the extension, else WAV.
+    ///         WAV is 16-bit PCM; Ogg Vorbis needs an FFI library built with `vorbis`.
+    ///     quality (float): Ogg Vorbis quality, 0 (smallest) .. 1 (best). Default 0.6.
+    ///     description (str | None): Makes a WAV a Broadcast Wave file with this
+    ///         description, the scene name and the render time. WAVs past 4 GB
+    ///         are written as RF64 either way.
+    #[pyo3(signature = (path, seconds, dither = "tpdf", format = None, quality = 0.6, description = None))]
+    pub fn render_to_file(
+        &mut self,
+        path: &str,
+        seconds: f32,
+        dither: &str,
+        format: Option<&str>,
+        quality: f32,
+        description: Option<&str>,
+    ) -> PyResult<()> {
         if seconds <= 0.0 {
             return Err(PyRuntimeError::new_err(
                 "seconds must be positive for render_to_file()",
             ));
         }
+        let dither = dither_code(dither)?;
+        let format = format_code(path, format)?;
+        let c_path = c_string(path, "path")?;
+        let frames = (self.sample_rate * seconds).round() as u64;
+        self.set_export_description(description)?;
+
+        let ok = unsafe {
+            ambientor_set_dither(self.ptr, dither);
+            ambientor_render_to_file(self.ptr, c_path.as_ptr(), frames, self.channels, format, quality)
+        };
+        if !ok {
+            return Err(PyRuntimeError::new_err(format!(
+                "ambientor_render_to_file() failed to write {path}"
+            )));
+        }
+        Ok(())
+    }
 
-        let total_frames = (self.sample_rate * seconds).round() as usize;
-        let block_size: usize = 1024;
-        let mut remaining = total_frames;
-
-        let mut tmp = vec![0.0f32; block_size * self.channels as usize];
-        let mut pcm: Vec<i16> = Vec::with_capacity(total_frames * self.channels as usize);
-
-        while remaining > 0 {
-            let frames = remaining.min(block_size);
-            let written = unsafe {
-                ambientor_render_interleaved_f32(
-                    self.ptr,
-                    tmp.as_mut_ptr(),
-                    frames as u32,
-                    self.channels,
-                )
-            } as usize;
-
-            if written == 0 {
-                break;
-            }
-
-            let used_samples = written * self.channels as usize;
+    /// Offline render of a seamlessly loopable file.
+    ///
+    /// Renders `seconds + crossfade` and crossfades the extra tail into the
+    /// beginning, so the file can repeat without a click or a jump in texture.
+    ///
+    /// Args:
+    ///     path (str): Output path.
+    ///     seconds (float): Loop length in seconds (must be > 0).
+    ///     crossfade (float): Crossfade length in seconds (default 5.0, capped at `seconds`).
+    ///     dither, format, quality, description: as for `render_to_file`.
+    #[pyo3(signature = (path, seconds, crossfade = 5.0, dither = "tpdf", format = None, quality = 0.6, description = None))]
+    pub fn render_loop_to_file(
+        &mut self,
+        path: &str,
+        seconds: f32,
+        crossfade: f32,
+        dither: &str,
+        format: Option<&str>,
+        quality: f32,
+        description: Option<&str>,
+    ) -> PyResult<()> {
+        if seconds <= 0.0 || crossfade < 0.0 {
+            return Err(PyRuntimeError::new_err(
+                "render_loop_to_file() needs seconds > 0 and crossfade >= 0",
+            ));
+        }
+        let dither = dither_code(dither)?;
+        let format = format_code(path, format)?;
+        let c_path = c_string(path, "path")?;
+        let loop_frames = (self.sample_rate * seconds).round() as u64;
+        let fade_frames = (self.sample_rate * crossfade).round() as u64;
+        self.set_export_description(description)?;
+
+        let ok = unsafe {
+            ambientor_set_dither(self.ptr, dither);
+            ambientor_render_loop_to_file(
+                self.ptr,
+                c_path.as_ptr(),
+                loop_frames,
+                fade_frames,
+                self.channels,
+                format,
+                quality,
+            )
+        };
+        if !ok {
+            return Err(PyRuntimeError::new_err(format!(
+                "ambientor_render_loop_to_file() failed to write {path}"
+            )));
+        }
+        Ok(())
+    }
 
-            for &s in &tmp[..used_samples] {
-                let x = s.clamp(-1.0, 1.0);
-                let q = (x * i16::MAX as f32) as i16;
-                pcm.push(q);
+    /// Drain the scene events reported since the last call, oldest first.
+    ///
+    /// Each is a dict with a ``"kind"`` of ``"note"`` (with ``"pitch"`` in Hz
+    /// and ``"amp"`` 0..1), ``"thunder"`` (with ``"amp"``) or ``"section"``
+    /// (with ``"section"``, the new section number). Call it after each
+    /// ``render_block`` to flash visuals in time with the music.
+    pub fn poll_events<'py>(&mut self, py: Python<'py>) -> PyResult<&'py PyAny> {
+        let events = pyo3::types::PyList::empty(py);
+        let mut ev = AmbientorEvent::default();
+        while unsafe { ambientor_poll_event(self.ptr, &mut ev) } {
+            let d = pyo3::types::PyDict::new(py);
+            match ev.kind {
+                0 => {
+                    d.set_item("kind", "note")?;
+                    d.set_item("pitch", ev.pitch)?;
+                    d.set_item("amp", ev.amp)?;
+                }
+                1 => {
+                    d.set_item("kind", "thunder")?;
+                    d.set_item("amp", ev.amp)?;
+                }
+                2 => {
+                    d.set_item("kind", "section")?;
+                    d.set_item("section", ev.section)?;
+                }
+                _ => continue,
             }
-
-            remaining -= written;
+            events.append(d)?;
         }
+        Ok(events)
+    }
+}
 
-        write_wav_i16(path, self.sample_rate as u32, self.channels as u16, &pcm)
-            .map_err(|e| PyRuntimeError::new_err(format!("write_wav_i16 failed: {e}")))?;
-
+impl AmbientorEngine {
+    fn set_export_description(&mut self, description: Option<&str>) -> PyResult<()> {
+        let description = description.map(|d| c_string(d, "description")).transpose()?;
+        unsafe {
+            ambientor_set_export_description(
+                self.ptr,
+                description.as_ref().map_or(std::ptr::null(), |d| d.as_ptr()),
+            );
+        }
         Ok(())
     }
 }
@@ -0,0 +1,102 @@
+//! Channel identification for `ambientor-cli test`.
+//!
+//! Every output channel takes its turn: channel N plays N short beeps, at a
+//! pitch a whole tone above the previous channel's, then the next channel
+//! starts. Someone standing among an installation's speakers can tell which
+//! output feeds which speaker by counting, without anyone at the console.
+
+use std::f32::consts::TAU;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Beep and gap between beeps (seconds).
+const BEEP_S: f32 = 0.15;
+const GAP_S: f32 = 0.15;
+/// Silence after a channel's last beep, before the next channel (seconds).
+const PAUSE_S: f32 = 1.0;
+/// Fade in/out of every beep, against clicks (seconds).
+const FADE_S: f32 = 0.005;
+/// Test level (about -12 dBFS) and the first channel's pitch (Hz).
+const LEVEL: f32 = 0.25;
+const BASE_HZ: f32 = 440.0;
+
+/// Audio-thread side: the beep sequence across `channels` outputs.
+pub struct ChannelTest {
+    sr: f32,
+    channels: usize,
+    channel: usize,
+    t: u32, // samples into the current channel's turn
+    phase: f32,
+    current: Arc<AtomicUsize>,
+}
+
+/// A test for `channels` outputs at `sr`, plus the channel (0-based) playing
+/// right now, for the main thread to print.
+pub fn channel_test(sr: f32, channels: usize) -> (ChannelTest, Arc<AtomicUsize>) {
+    let current = Arc::new(AtomicUsize::new(0));
+    let test = ChannelTest { sr, channels: channels.max(1), channel: 0, t: 0, phase: 0.0, current: Arc::clone(&current) };
+    (test, current)
+}
+
+impl ChannelTest {
+    /// Next frame: the channel sounding and its sample (all others are silent).
+    #[inline]
+    pub fn next(&mut self) -> (usize, f32) {
+        let period = ((BEEP_S + GAP_S) * self.sr) as u32;
+        let beep = (BEEP_S * self.sr) as u32;
+        let fade = (FADE_S * self.sr).max(1.0);
+        let turn = period * (self.channel as u32 + 1) + (PAUSE_S * self.sr) as u32;
+
+        let (k, at) = (self.t / period, self.t % period);
+        let y = if k <= self.channel as u32 && at < beep {
+            let env = (at as f32 / fade).min((beep - at) as f32 / fade).min(1.0);
+            let hz = BASE_HZ * 2f32.powf(2.0 * self.channel as f32 / 12.0);
+            self.phase = (self.phase + hz / self.sr).fract();
+            LEVEL * env * (TAU * self.phase).sin()
+        } else {
+            self.phase = 0.0;
+            0.0
+        };
+        let ch = self.channel;
+
+        self.t += 1;
+        if self.t >= turn {
+            self.t = 0;
+            self.channel = (self.channel + 1) % self.channels;
+            self.current.store(self.channel, Ordering::Relaxed);
+        }
+        (ch, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_channel_beeps_its_number_in_turn() {
+        let sr = 48_000.0;
+        let (mut test, current) = channel_test(sr, 3);
+        // (channel, beeps) per turn, over two rounds
+        let mut turns: Vec<(usize, usize)> = Vec::new();
+        let mut quiet = usize::MAX;
+        while turns.len() < 7 {
+            let (ch, y) = test.next();
+            if turns.last().map(|t| t.0) != Some(ch) {
+                // the main thread is told as soon as a turn starts
+                assert_eq!(current.load(Ordering::Relaxed), ch);
+                turns.push((ch, 0));
+            }
+            if y == 0.0 {
+                quiet = quiet.saturating_add(1);
+            } else {
+                // a beep starts after more silence than its own zero crossings
+                if quiet > 100 { turns.last_mut().unwrap().1 += 1; }
+                quiet = 0;
+                assert!(y.abs() <= LEVEL);
+            }
+        }
+        turns.pop(); // the third round has only just started
+        assert_eq!(turns, [(0, 1), (1, 2), (2, 3), (0, 1), (1, 2), (2, 3)]);
+    }
+}
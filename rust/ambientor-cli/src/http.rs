@@ -0,0 +1,288 @@
+//! Tiny REST control server (`--http=ADDR`, `--features http`).
+//!
+//! Plain HTTP/1.1 on std sockets, one request per connection, JSON out and
+//! plain-text bodies in, enough for Home Assistant's `rest` and
+//! `rest_command` integrations or `curl`:
+//!
+//! | Request                       | Body              | Effect / reply                         |
+//! |-------------------------------|-------------------|----------------------------------------|
+//! | `GET /status`                 |                   | scene, state, params and meters        |
+//! | `GET /scene`                  |                   | current scene and the built-in ones    |
+//! | `POST /scene`                 | `tape-loop`       | switch scene                           |
+//! | `GET /params`                 |                   | every parameter of the scene           |
+//! | `GET /params/NAME`            |                   | one parameter                          |
+//! | `POST /params/NAME`           | `1200`            | set it                                 |
+//! | `GET /meters`                 |                   | output peak and RMS over the last second, loudness over ~30 s |
+//! | `GET /transport`              |                   | `playing` or `paused`                  |
+//! | `POST /transport`             | `play`, `pause`, `toggle` | pause or resume                |
+//!
+//! Each connection is served on its own short-lived thread (at most
+//! [`MAX_CONNECTIONS`] at once), so a client that stalls mid-request holds up
+//! only itself. There is no authentication: a bare port listens on loopback
+//! only, and other interfaces take an explicit host (`--http=0.0.0.0:8080`)
+//! that should be a trusted LAN.
+
+use crate::remote::Remote;
+use ambientor_engine::log;
+use ambientor_engine::scenes::SCENE_NAMES;
+use ambientor_engine::ParamId;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Largest request body accepted (bytes).
+const MAX_BODY: usize = 4096;
+/// A client that stalls this long mid-request is dropped.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// Connections served at once; more are closed unanswered.
+pub const MAX_CONNECTIONS: usize = 16;
+
+/// Listen on `addr` (`HOST:PORT`, or a bare port for loopback) and serve
+/// requests on a background thread for the rest of the process.
+pub fn spawn(addr: &str, remote: Remote) -> io::Result<String> {
+    let listener = TcpListener::bind(listen_addr(addr))?;
+    let bound = listener.local_addr()?.to_string();
+    let remote = Arc::new(Mutex::new(remote));
+    let open = Arc::new(AtomicUsize::new(0));
+    std::thread::Builder::new().name("ambientor-http".into()).spawn(move || {
+        for conn in listener.incoming().flatten() {
+            if open.fetch_add(1, Ordering::AcqRel) >= MAX_CONNECTIONS {
+                open.fetch_sub(1, Ordering::AcqRel);
+                log::debug(format_args!("http: {MAX_CONNECTIONS} connections open, dropping one"));
+                continue;
+            }
+            let (remote, done) = (Arc::clone(&remote), Arc::clone(&open));
+            let served = std::thread::Builder::new().name("ambientor-http-conn".into()).spawn(move || {
+                if let Err(e) = handle(conn, &remote) {
+                    log::debug(format_args!("http: {e}"));
+                }
+                done.fetch_sub(1, Ordering::AcqRel);
+            });
+            if let Err(e) = served {
+                open.fetch_sub(1, Ordering::AcqRel);
+                log::warn(format_args!("http: {e}"));
+            }
+        }
+    })?;
+    Ok(bound)
+}
+
+/// `HOST:PORT` as given; a bare port binds to loopback.
+fn listen_addr(addr: &str) -> String {
+    if addr.contains(':') { addr.to_string() } else { format!("127.0.0.1:{addr}") }
+}
+
+struct Reply {
+    code: u16,
+    body: String,
+}
+
+impl Reply {
+    fn ok(body: String) -> Self { Self { code: 200, body } }
+
+    fn error(code: u16, msg: &str) -> Self {
+        Self { code, body: format!(r#"{{"error":"{}"}}"#, msg.replace('\\', "\\\\").replace('"', "\\\"")) }
+    }
+}
+
+fn handle(conn: TcpStream, remote: &Mutex<Remote>) -> io::Result<()> {
+    conn.set_read_timeout(Some(READ_TIMEOUT))?;
+    let reply = respond(&mut BufReader::new(&conn), remote)?;
+    write_reply(&mut &conn, &reply)
+}
+
+/// Read one request from `reader` and act on it. The remote is locked only
+/// once the whole request is in.
+fn respond(reader: &mut impl BufRead, remote: &Mutex<Remote>) -> io::Result<Reply> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+
+    let mut len = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() { break; }
+        if let Some((k, v)) = header.split_once(':') {
+            if k.trim().eq_ignore_ascii_case("content-length") { len = v.trim().parse().unwrap_or(0); }
+        }
+    }
+    if len > MAX_BODY { return Ok(Reply::error(413, "body too large")); }
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body)?;
+    let path = target.split('?').next().unwrap_or("");
+    let mut remote = remote.lock().unwrap_or_else(|e| e.into_inner());
+    Ok(route(&mut remote, method, path, String::from_utf8_lossy(&body).trim()))
+}
+
+fn write_reply(w: &mut impl Write, reply: &Reply) -> io::Result<()> {
+    let reason = match reply.code {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        _ => "Error",
+    };
+    write!(
+        w,
+        "HTTP/1.1 {} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        reply.code,
+        reply.body.len(),
+        reply.body,
+    )?;
+    w.flush()
+}
+
+fn route(remote: &mut Remote, method: &str, path: &str, body: &str) -> Reply {
+    let segs: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match (method, segs.as_slice()) {
+        ("GET", ["status"]) => Reply::ok(remote.status_json()),
+        ("GET", ["scene"]) => {
+            let names: Vec<String> = SCENE_NAMES.iter().map(|n| format!("\"{n}\"")).collect();
+            let scene = remote.status().scene().map_or("null".to_string(), |n| format!("\"{n}\""));
+            Reply::ok(format!(r#"{{"scene":{scene},"available":[{}]}}"#, names.join(",")))
+        }
+        ("POST", ["scene"]) => match remote.set_scene(body) {
+            Ok(()) => Reply::ok(format!(r#"{{"scene":"{body}"}}"#)),
+            Err(e) => Reply::error(400, &e),
+        },
+        ("GET", ["params"]) => Reply::ok(remote.params_json()),
+        ("GET", ["params", name]) => match ParamId::parse(name).and_then(|id| remote.status().param(id)) {
+            Some(v) => Reply::ok(format!(r#"{{"{name}":{v}}}"#)),
+            None => Reply::error(404, &format!("no parameter {name} in this scene")),
+        },
+        ("POST", ["params", name]) => match body.parse::<f32>() {
+            Ok(v) => match remote.set_param(name, v) {
+                Ok(()) => Reply::ok(format!(r#"{{"{name}":{v}}}"#)),
+                Err(e) => Reply::error(400, &e),
+            },
+            Err(_) => Reply::error(400, "body must be a number"),
+        },
+        ("GET", ["meters"]) => {
+            let (peak, rms) = remote.status().levels_db();
+            Reply::ok(format!(r#"{{"peak_db":{peak:.1},"rms_db":{rms:.1},"loudness_db":{:.1}}}"#, remote.status().loudness_db()))
+        }
+        ("GET", ["transport"]) | ("POST", ["transport"]) => {
+            if method == "POST" {
+                match body {
+                    "play" => remote.set_paused(false),
+                    "pause" => remote.set_paused(true),
+                    "toggle" => remote.set_paused(!remote.paused()),
+                    _ => return Reply::error(400, "body must be play, pause or toggle"),
+                }
+            }
+            Reply::ok(format!(r#"{{"state":"{}"}}"#, if remote.paused() { "paused" } else { "playing" }))
+        }
+        (_, ["status" | "scene" | "params" | "meters" | "transport", ..]) => Reply::error(405, "method not allowed"),
+        _ => Reply::error(404, "not found"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SceneReload;
+    use ambientor_engine::command::{Command, CommandReceiver};
+
+    fn player() -> (Mutex<Remote>, CommandReceiver, Arc<SceneReload>) {
+        let (remote, rx, reload) = crate::remote::tests::player();
+        (Mutex::new(remote), rx, reload)
+    }
+
+    fn request(remote: &Mutex<Remote>, raw: &str) -> Reply {
+        respond(&mut raw.as_bytes(), remote).unwrap()
+    }
+
+    fn post(remote: &Mutex<Remote>, path: &str, body: &str) -> Reply {
+        request(remote, &format!("POST {path} HTTP/1.1\r\nHost: x\r\nContent-Length: {}\r\n\r\n{body}", body.len()))
+    }
+
+    #[test]
+    fn bare_ports_listen_on_loopback() {
+        assert_eq!(listen_addr("8080"), "127.0.0.1:8080");
+        assert_eq!(listen_addr("0.0.0.0:8080"), "0.0.0.0:8080");
+        assert_eq!(listen_addr("[::1]:8080"), "[::1]:8080");
+    }
+
+    #[test]
+    fn reads_the_request_line_headers_and_body() {
+        let (remote, mut rx, _) = player();
+        // header names in any case, a query string, a padded body
+        let r = request(&remote, "POST /params/cut_base?x=1 HTTP/1.1\r\ncontent-LENGTH: 6\r\n\r\n 1200 trailing");
+        assert_eq!((r.code, r.body.as_str()), (200, r#"{"cut_base":1200}"#));
+        assert_eq!(rx.pop(), Some(Command::SetHostParam { id: ParamId::CutBase, value: 1200.0 }));
+
+        // no body when there's no Content-Length
+        let r = request(&remote, "GET /transport HTTP/1.1\r\n\r\n");
+        assert_eq!((r.code, r.body.as_str()), (200, r#"{"state":"playing"}"#));
+
+        let r = request(&remote, &format!("POST /scene HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY + 1));
+        assert_eq!(r.code, 413);
+        // a body cut short is an error, not a request
+        assert!(respond(&mut &b"POST /scene HTTP/1.1\r\nContent-Length: 20\r\n\r\nrisset"[..], &remote).is_err());
+    }
+
+    #[test]
+    fn routes_requests() {
+        let (remote, mut rx, reload) = player();
+        let r = request(&remote, "GET /status HTTP/1.1\r\n\r\n");
+        assert!(r.code == 200 && r.body.starts_with(r#"{"scene":"slow-drone","state":"playing","params":{"#), "{}", r.body);
+        let r = request(&remote, "GET /params/cut_base HTTP/1.1\r\n\r\n");
+        assert!(r.code == 200 && r.body.starts_with(r#"{"cut_base":"#), "{}", r.body);
+
+        let r = post(&remote, "/transport", "toggle");
+        assert_eq!((r.code, r.body.as_str()), (200, r#"{"state":"paused"}"#));
+        assert!(remote.lock().unwrap().paused());
+
+        let r = post(&remote, "/scene", "risset");
+        assert_eq!((r.code, r.body.as_str()), (200, r#"{"scene":"risset"}"#));
+        assert!(reload.pending.load(Ordering::Acquire));
+
+        assert_eq!(post(&remote, "/params/cut_base", "800").code, 200);
+        assert_eq!(rx.pop(), Some(Command::SetHostParam { id: ParamId::CutBase, value: 800.0 }));
+    }
+
+    #[test]
+    fn rejects_bad_requests() {
+        let (remote, mut rx, reload) = player();
+        for (method, path, code) in [
+            ("GET", "/nowhere", 404),
+            ("GET", "/", 404),
+            ("GET", "/params/no_such_param", 404),
+            ("DELETE", "/scene", 405),
+            ("POST", "/status", 405),
+        ] {
+            let r = request(&remote, &format!("{method} {path} HTTP/1.1\r\n\r\n"));
+            assert_eq!(r.code, code, "{method} {path}: {}", r.body);
+        }
+        // garbage instead of a request line
+        assert_eq!(request(&remote, "\r\n\r\n").code, 404);
+
+        for (path, body, msg) in [
+            ("/scene", "no-such-scene", "unknown scene: no-such-scene"),
+            ("/params/cut_base", "loud", "body must be a number"),
+            ("/params/cut_base", "NaN", "cut_base: value must be a number"),
+            ("/params/no_such_param", "1", "unknown parameter: no_such_param"),
+            ("/transport", "stop", "body must be play, pause or toggle"),
+        ] {
+            let r = post(&remote, path, body);
+            assert!(r.code == 400 && r.body.starts_with(&format!(r#"{{"error":"{msg}"#)), "{path} {body}: {}", r.body);
+        }
+        assert_eq!(rx.pop(), None);
+        assert!(!reload.pending.load(Ordering::Acquire) && !remote.lock().unwrap().paused());
+    }
+
+    #[test]
+    fn writes_a_complete_reply() {
+        let mut out = Vec::new();
+        write_reply(&mut out, &Reply::error(404, r#"no "x""#)).unwrap();
+        let body = r#"{"error":"no \"x\""}"#;
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("HTTP/1.1 404 Not Found\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len()),
+        );
+    }
+}
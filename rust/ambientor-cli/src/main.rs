@@ -1,37 +1,206 @@
-//! Ambientor CLI — real-time player for evolving ambient scenes.
+//! Ambientor CLI — real-time player and offline renderer for evolving ambient scenes.
 
-use ambientor_engine::graph::Engine;
-use ambientor_engine::scenes::Scene;
+mod channel_test;
+#[cfg(feature = "http")]
+mod http;
+mod meters;
+#[cfg(all(feature = "mpris", target_os = "linux"))]
+mod mpris;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+#[cfg(any(feature = "http", feature = "mqtt"))]
+mod remote;
+
+use ambientor_engine::circadian::Circadian;
+use ambientor_engine::command::{self, CommandReceiver, CommandSender, NodeNames};
+use ambientor_engine::effects::DYN_EQ_THRESHOLD_DB;
+use ambientor_engine::export::{self, AudioFileWriter, BextInfo, DitherMode, ExportFormat, ExportOptions};
+use ambientor_engine::graph::{measure_loudness_db, Engine, Transport};
+use ambientor_engine::log;
+use ambientor_engine::master::{MASTER_HP_HZ, MONO_BASS_HZ, SUB_BED_DEFAULT_HZ};
+use ambientor_engine::profile;
+use ambientor_engine::routing::{ChannelMap, MAX_CHANNELS};
+use ambientor_engine::scenes::{file as scene_file, Scene, SCENE_DESCRIPTIONS, SCENE_NAMES};
+use ambientor_engine::spatial::Binaural;
+use ambientor_engine::stream::{RenderAhead, DEFAULT_AHEAD_FRAMES, DEFAULT_BLOCK_FRAMES};
+use ambientor_engine::{Generator, ParamId, ParamSet, Tuning};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use channel_test::ChannelTest;
+use meters::{MeterMode, MeterTap, METER_REFRESH};
 use std::error::Error;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Length of a `--morph-to` morph without `--morph-minutes`.
+const DEFAULT_MORPH_MIN: f32 = 60.0;
 
 #[derive(Debug, Default)]
 struct Args {
     list_devices: bool,
+    follow_default: bool,
+    render_ahead: Option<usize>, // frames; `--render-ahead` alone is the default depth
+    list_scenes: bool,
+    describe_scene: Option<String>,
+    bench: bool,
+    calibrate: bool,
+    test: bool,
+    render: bool,
+    out_path: Option<String>,
+    dither: Option<String>,
+    loop_render: bool,
+    skip_silence: bool,
+    loop_fade_s: Option<f32>,
+    format: Option<String>,
+    quality: Option<f32>,
+    description: Option<String>,
     device_name: Option<String>,
     sample_rate: Option<u32>,
     channels: Option<u16>,
     duration_sec: Option<u64>,
     scene: Option<String>,
+    scene_file: Option<String>,
+    script: Option<String>,
     gain: Option<f32>,
+    channel_map: Option<String>,
+    binaural: bool,
+    auto_trim: bool,
+    auto_gain: bool,
+    reverb_bypass: bool,
+    binaural_width: Option<f32>,
+    orbit_rate: Option<f32>,
+    width: Option<f32>,
+    tuning_ref: Option<f32>,
+    intensity: Option<f32>,
+    time_warp: Option<f32>,
+    randomize: Option<f32>,
+    seed: Option<u32>,
+    morph_from: Option<String>,
+    morph_to: Option<String>,
+    morph_minutes: Option<f32>,
+    circadian: Option<String>,
+    sleep_timer: Option<String>,
+    sleep_fade: Option<String>,
+    http: Option<String>,
+    mqtt: Option<String>,
+    mqtt_prefix: Option<String>,
+    utc_offset_h: Option<f32>,
+    eq: Option<String>,
+    eq_mid_hz: Option<f32>,
+    tone: Option<f32>,
+    highpass: Option<String>,
+    sub_bed: Option<f32>,
+    sub_bed_hz: Option<f32>,
+    mono_bass: Option<String>,
+    dyn_eq: Option<String>,
+    texture: Option<String>,
+    meters: Option<String>,
+    sets: Vec<String>, // repeatable `--set name=value`, in order
 }
 
 fn parse_args() -> Args {
     let mut a = Args::default();
-    for s in std::env::args().skip(1) {
+    let mut argv = std::env::args().skip(1);
+    while let Some(s) = argv.next() {
+        if s == "bench"          { a.bench        = true; continue; }
+        if s == "calibrate"      { a.calibrate    = true; continue; }
+        if s == "render"         { a.render       = true; continue; }
+        if s == "test"           { a.test         = true; continue; }
         if s == "--list-devices" { a.list_devices = true; continue; }
+        if s == "--list-scenes"  { a.list_scenes  = true; continue; }
+        if s == "--binaural"     { a.binaural     = true; continue; }
+        if s == "--loop"         { a.loop_render  = true; continue; }
+        if s == "--skip-silence" { a.skip_silence = true; continue; }
+        if s == "--auto-trim"    { a.auto_trim    = true; continue; }
+        if s == "--auto-gain"    { a.auto_gain    = true; continue; }
+        if s == "--no-reverb"    { a.reverb_bypass = true; continue; }
+        if s == "--follow-default" { a.follow_default = true; continue; }
+        if s == "--render-ahead" { a.render_ahead = Some(DEFAULT_AHEAD_FRAMES); continue; }
+        if s == "--set" {
+            match argv.next() { Some(kv) => a.sets.push(kv), None => eprintln!("[warn] --set needs name=value"), }
+            continue;
+        }
         if let Some(rest) = s.strip_prefix("--device=")       { a.device_name = Some(rest.to_string()); continue; }
         if let Some(rest) = s.strip_prefix("--sample-rate=")  { a.sample_rate = rest.parse().ok();     continue; }
         if let Some(rest) = s.strip_prefix("--channels=")     { a.channels    = rest.parse().ok();     continue; }
         if let Some(rest) = s.strip_prefix("--duration=")     { a.duration_sec= rest.parse().ok();     continue; }
+        if let Some(rest) = s.strip_prefix("--render-ahead=") { a.render_ahead= rest.parse().ok();     continue; }
         if let Some(rest) = s.strip_prefix("--scene=")        { a.scene       = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--scene-file=")   { a.scene_file  = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--script=")       { a.script      = Some(rest.to_string());continue; }
         if let Some(rest) = s.strip_prefix("--gain=")         { a.gain        = rest.parse().ok();     continue; }
+        if let Some(rest) = s.strip_prefix("--channel-map=")  { a.channel_map = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--binaural-width="){ a.binaural_width = rest.parse().ok();  continue; }
+        if let Some(rest) = s.strip_prefix("--orbit-rate=")   { a.orbit_rate  = rest.parse().ok();     continue; }
+        if let Some(rest) = s.strip_prefix("--width=")        { a.width       = rest.parse().ok();     continue; }
+        if let Some(rest) = s.strip_prefix("--tuning-ref=")   { a.tuning_ref  = rest.parse().ok();     continue; }
+        if let Some(rest) = s.strip_prefix("--intensity=")    { a.intensity   = rest.parse().ok();     continue; }
+        if let Some(rest) = s.strip_prefix("--time-warp=")    { a.time_warp   = rest.parse().ok();     continue; }
+        if let Some(rest) = s.strip_prefix("--randomize=")    { a.randomize   = rest.parse().ok();     continue; }
+        if let Some(rest) = s.strip_prefix("--seed=")         { a.seed        = rest.parse().ok();     continue; }
+        if let Some(rest) = s.strip_prefix("--morph-from=")   { a.morph_from  = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--morph-to=")     { a.morph_to    = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--morph-minutes=") { a.morph_minutes = rest.parse().ok();  continue; }
+        if let Some(rest) = s.strip_prefix("--circadian=")    { a.circadian   = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--sleep-timer=")  { a.sleep_timer = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--sleep-fade=")   { a.sleep_fade  = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--http=")         { a.http        = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--mqtt=")         { a.mqtt        = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--mqtt-prefix=")  { a.mqtt_prefix = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--utc-offset=")   { a.utc_offset_h= rest.parse().ok();     continue; }
+        if let Some(rest) = s.strip_prefix("--eq=")           { a.eq          = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--eq-mid-hz=")    { a.eq_mid_hz   = rest.parse().ok();     continue; }
+        if let Some(rest) = s.strip_prefix("--tone=")         { a.tone        = rest.parse().ok();     continue; }
+        if let Some(rest) = s.strip_prefix("--highpass=")     { a.highpass    = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--sub-bed=")      { a.sub_bed     = rest.parse().ok();     continue; }
+        if let Some(rest) = s.strip_prefix("--sub-bed-hz=")   { a.sub_bed_hz  = rest.parse().ok();     continue; }
+        if let Some(rest) = s.strip_prefix("--mono-bass=")    { a.mono_bass   = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--dyn-eq=")       { a.dyn_eq      = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--texture=")      { a.texture     = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--out=")          { a.out_path    = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--dither=")       { a.dither      = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--loop-fade=")    { a.loop_fade_s = rest.parse().ok();     continue; }
+        if let Some(rest) = s.strip_prefix("--format=")       { a.format      = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--quality=")      { a.quality     = rest.parse().ok();     continue; }
+        if let Some(rest) = s.strip_prefix("--description="){ a.description = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--meters=")       { a.meters      = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--describe-scene="){ a.describe_scene = Some(rest.to_string());continue; }
+        if let Some(rest) = s.strip_prefix("--set=")          { a.sets.push(rest.to_string());         continue; }
         eprintln!("[warn] unknown arg: {s}");
     }
+    // a new variation every run unless a seed is given (it is printed, to get one back)
+    if a.randomize.is_some() && a.seed.is_none() {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        a.seed = Some(now.subsec_nanos() ^ now.as_secs() as u32);
+    }
     a
 }
 
+/// Parse a duration such as `45m`, `1h30m` or `90s` (a bare number is
+/// minutes) into seconds.
+fn parse_duration_s(spec: &str) -> Option<f64> {
+    if let Ok(min) = spec.trim().parse::<f64>() { return (min >= 0.0).then_some(60.0 * min); }
+    let (mut total, mut num) = (0.0, String::new());
+    for c in spec.trim().chars() {
+        let unit = match c {
+            'h' => 3600.0,
+            'm' => 60.0,
+            's' => 1.0,
+            _ => { num.push(c); continue; }
+        };
+        total += unit * num.parse::<f64>().ok().filter(|v| *v >= 0.0)?;
+        num.clear();
+    }
+    num.is_empty().then_some(total)
+}
+
+/// Parse `--eq=LOW,MID,HIGH` band gains in dB.
+fn parse_eq(spec: &str) -> Option<(f32, f32, f32)> {
+    let mut it = spec.split(',').map(|s| s.trim().parse::<f32>().ok().filter(|x| x.is_finite()));
+    let eq = (it.next()??, it.next()??, it.next()??);
+    it.next().is_none().then_some(eq)
+}
+
 fn list_output_devices() -> Result<(), Box<dyn Error>> {
     let host = cpal::default_host();
     println!("Available output devices:");
@@ -41,6 +210,73 @@ fn list_output_devices() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// `--list-scenes`: every built-in scene with a one-line description.
+fn list_scenes() {
+    println!("Built-in scenes (--scene=NAME; --describe-scene=NAME for parameters):");
+    for (name, desc) in SCENE_NAMES.iter().zip(SCENE_DESCRIPTIONS) {
+        println!("  {name:<14} {desc}");
+    }
+}
+
+/// `--describe-scene=NAME`: the scene's parameters and the master ones every
+/// scene shares, with defaults and the ranges `--randomize` draws from
+/// ([`ParamId::range`]; `--set` accepts wider values).
+fn describe_scene(name: &str) -> Result<(), Box<dyn Error>> {
+    let scene = Scene::by_name(name, 48_000.0)
+        .ok_or_else(|| format!("unknown scene: {name} (one of {})", SCENE_NAMES.join(", ")))?;
+    let canonical = scene.name().to_string();
+    let desc = SCENE_NAMES.iter().position(|n| *n == canonical).map_or("", |i| SCENE_DESCRIPTIONS[i]);
+    let params = Engine::new(scene).params();
+    println!("{canonical}: {desc}\n");
+    for (title, master) in [("Scene parameters", false), ("Master parameters (every scene)", true)] {
+        println!("{title}:");
+        println!("  {:<14} {:>9}  {:<17} description", "name", "default", "variation range");
+        for (id, v) in params.iter().filter(|(id, _)| id.is_master() == master) {
+            let (lo, hi) = id.range();
+            println!("  {:<14} {v:>9.3}  {:<17} {}", id.name(), format!("{lo} .. {hi}"), id.description());
+        }
+        println!();
+    }
+    println!("Variation ranges are what --randomize picks from; --set may go beyond them.");
+    println!("Override any of them with --set NAME=VALUE (repeatable). Also accepted:");
+    for (alias, id) in SET_ALIASES { println!("  {alias:<14} = {}", id.name()); }
+    Ok(())
+}
+
+/// Dotted names `--set` takes besides the parameter names.
+const SET_ALIASES: [(&str, ParamId); 2] = [
+    ("reverb.mix", ParamId::ReverbReturn),
+    ("delay.mix", ParamId::DelayReturn),
+];
+
+/// One `--set NAME=VALUE`: a parameter name or one of [`SET_ALIASES`], and a finite number.
+fn parse_set(kv: &str) -> Result<(ParamId, f32), String> {
+    let (name, value) = kv.split_once('=')
+        .ok_or_else(|| format!("invalid --set: {kv} (expected NAME=VALUE, e.g. cut_base=1200)"))?;
+    let name = name.trim();
+    let alias = SET_ALIASES.iter().find(|(a, _)| *a == name).map(|&(_, id)| id);
+    let id = alias.or_else(|| ParamId::parse(name)).ok_or_else(|| {
+        let names: Vec<_> = ParamId::ALL.iter().map(|p| p.name()).collect();
+        format!("invalid --set: unknown parameter {name} (one of {})", names.join(", "))
+    })?;
+    let v: f32 = value.trim().parse().ok().filter(|v: &f32| v.is_finite())
+        .ok_or_else(|| format!("invalid --set: {kv} (value must be a number)"))?;
+    Ok((id, v))
+}
+
+/// Apply the `--set name=value` overrides through the parameter-by-name API,
+/// after every other setting so they win.
+fn apply_sets(engine: &mut Engine<Scene>, sets: &[String]) -> Result<(), Box<dyn Error>> {
+    for kv in sets {
+        let (id, v) = parse_set(kv)?;
+        if !engine.set_param(id, v) {
+            let scene = engine.scene_mut().name().to_string();
+            return Err(format!("invalid --set: {scene} has no parameter {} (see --describe-scene={scene})", id.name()).into());
+        }
+    }
+    Ok(())
+}
+
 fn pick_device(args: &Args) -> Result<cpal::Device, Box<dyn Error>> {
     let host = cpal::default_host();
     if let Some(name) = &args.device_name {
@@ -53,18 +289,58 @@ fn pick_device(args: &Args) -> Result<cpal::Device, Box<dyn Error>> {
         .ok_or_else(|| "no default output device".into())
 }
 
+/// `$build::<T>(args…)` with `T` the sample type of device format `$format`;
+/// an error for formats cpal adds later.
+macro_rules! for_sample_format {
+    ($format:expr, $build:ident($($arg:expr),*)) => {
+        match $format {
+            cpal::SampleFormat::F32 => $build::<f32>($($arg),*),
+            cpal::SampleFormat::I16 => $build::<i16>($($arg),*),
+            cpal::SampleFormat::U16 => $build::<u16>($($arg),*),
+            cpal::SampleFormat::F64 => $build::<f64>($($arg),*),
+            cpal::SampleFormat::I32 => $build::<i32>($($arg),*),
+            cpal::SampleFormat::U32 => $build::<u32>($($arg),*),
+            cpal::SampleFormat::I64 => $build::<i64>($($arg),*),
+            cpal::SampleFormat::U64 => $build::<u64>($($arg),*),
+            cpal::SampleFormat::I8  => $build::<i8>($($arg),*),
+            cpal::SampleFormat::U8  => $build::<u8>($($arg),*),
+            other => Err(format!("unsupported device sample format: {other:?}").into()),
+        }
+    };
+}
+
+/// Preference among device sample formats, higher is better: f32 (the
+/// engine's own format), then f64, then integers by bit depth, signed before
+/// unsigned.
+fn format_rank(f: cpal::SampleFormat) -> (bool, bool, usize, bool) {
+    (f == cpal::SampleFormat::F32, f.is_float(), f.sample_size(), f.is_int())
+}
+
 fn choose_config(
     device: &cpal::Device,
     req_sr: Option<u32>,
     req_ch: Option<u16>,
 ) -> Result<cpal::SupportedStreamConfig, Box<dyn Error>> {
-    // If nothing requested, default is already concrete.
+    // Nothing requested: keep the default's rate and channels, but look for a
+    // better sample format at them (some backends default to i16).
     if req_sr.is_none() && req_ch.is_none() {
-        return Ok(device.default_output_config()?);
+        let default = device.default_output_config()?;
+        let Ok(ranges) = device.supported_output_configs() else { return Ok(default); };
+        let better = ranges
+            .filter(|r| r.channels() == default.channels())
+            .filter(|r| (r.min_sample_rate()..=r.max_sample_rate()).contains(&default.sample_rate()))
+            .max_by_key(|r| format_rank(r.sample_format()));
+        return Ok(match better {
+            Some(r) if format_rank(r.sample_format()) > format_rank(default.sample_format()) => {
+                r.with_sample_rate(default.sample_rate())
+            }
+            _ => default,
+        });
     }
 
-    // Pick a SupportedStreamConfigRange first.
-    let mut best: Option<(u64, cpal::SupportedStreamConfigRange)> = None;
+    // Pick a SupportedStreamConfigRange first; among equally close ones, the
+    // best sample format.
+    let mut best: Option<(_, cpal::SupportedStreamConfigRange)> = None;
     for range in device.supported_output_configs()? {
         let ch     = range.channels();
         let sr_min = range.min_sample_rate().0;
@@ -76,8 +352,8 @@ fn choose_config(
             None => 0,
         };
 
-        let score = sr_pen.saturating_mul(1000) + ch_pen;
-        if best.as_ref().map(|(s, _)| *s).map_or(true, |s| score < s) {
+        let score = (sr_pen.saturating_mul(1000) + ch_pen, std::cmp::Reverse(format_rank(range.sample_format())));
+        if best.as_ref().map_or(true, |(s, _)| score < *s) {
             best = Some((score, range));
         }
     }
@@ -98,16 +374,691 @@ fn choose_config(
 }
 
 fn make_scene(name: Option<&str>, sr: f32) -> Scene {
-    match name.unwrap_or("slow-drone").to_ascii_lowercase().as_str() {
-        "slow-drone" | _ => Scene::slow_drone(sr),
+    let name = name.unwrap_or("slow-drone");
+    Scene::by_name(name, sr).unwrap_or_else(|| {
+        eprintln!("[warn] unknown scene: {name} (available: {}), using slow-drone", SCENE_NAMES.join(", "));
+        Scene::slow_drone(sr)
+    })
+}
+
+/// Scene for `args`: compiled from `--scene-file` if given, else the built-in `--scene`.
+fn initial_scene(args: &Args, sr: f32) -> Result<Scene, Box<dyn Error>> {
+    match &args.scene_file {
+        Some(path) => Ok(scene_file::load(path, sr).map_err(|e| format!("{path}: {e}"))?.into()),
+        None => Ok(make_scene(args.scene.as_deref(), sr)),
     }
 }
 
-fn build_stream<T>(
+/// Handoff for scene-file hot reload: the main thread compiles a new scene into
+/// the preallocated slot and raises `pending`; the audio callback picks it up
+/// with `try_lock` (never blocking) and swaps it in by copy.
+struct SceneReload {
+    pending: AtomicBool,
+    scene: Mutex<Option<Scene>>, // the queued scene, then the one it replaced
+    name: Mutex<String>, // of the scene last published, or the first one
+    sr: AtomicU32, // f32 bits
+}
+
+impl SceneReload {
+    fn new(name: &str, sr: f32) -> Self {
+        Self {
+            pending: AtomicBool::new(false),
+            scene: Mutex::new(None),
+            name: Mutex::new(name.to_string()),
+            sr: AtomicU32::new(sr.to_bits()),
+        }
+    }
+
+    /// Rate the stream runs at, to build scenes for; [`reconnect`] moves it
+    /// when a new device runs at another rate.
+    fn sample_rate(&self) -> f32 { f32::from_bits(self.sr.load(Ordering::Relaxed)) }
+    fn set_sample_rate(&self, sr: f32) { self.sr.store(sr.to_bits(), Ordering::Relaxed); }
+
+    /// Main thread: queue `scene` for the audio thread.
+    fn publish(&self, scene: Scene) {
+        let name = scene.name().to_string();
+        *self.scene.lock().unwrap_or_else(|e| e.into_inner()) = Some(scene);
+        *self.name.lock().unwrap_or_else(|e| e.into_inner()) = name;
+        self.pending.store(true, Ordering::Release);
+    }
+
+    /// Name of the scene playing (or about to, once the audio thread takes it).
+    fn scene_name(&self) -> String { self.name.lock().unwrap_or_else(|e| e.into_inner()).clone() }
+
+    /// Audio thread: swap in a pending scene, keeping the current tuning, intensity, and time warp.
+    /// The replaced scene is parked in the slot, so it is freed by the next
+    /// [`SceneReload::publish`] rather than on the audio thread.
+    fn install(&self, engine: &mut Engine<Scene>) {
+        if !self.pending.load(Ordering::Acquire) { return; }
+        let Ok(mut slot) = self.scene.try_lock() else { return };
+        let Some(mut scene) = slot.take() else { return };
+        self.pending.store(false, Ordering::Release);
+        let cur = engine.scene_mut();
+        scene.set_tuning(*cur.tuning());
+        scene.set_intensity(cur.intensity());
+        scene.set_time_warp(cur.time_warp());
+        *slot = Some(engine.swap_scene(scene));
+    }
+}
+
+/// Node ids declared in a scene file (empty if it can't be read).
+fn scene_node_names(path: &str) -> Vec<String> {
+    scene_file::SceneDesc::read(path).map(|d| d.nodes.into_iter().map(|n| n.id).collect()).unwrap_or_default()
+}
+
+/// Polls a scene file's modification time and recompiles it when it changes.
+struct SceneWatcher {
+    path: String,
+    modified: Option<SystemTime>,
+    reload: Arc<SceneReload>,
+    names: NodeNames,
+}
+
+impl SceneWatcher {
+    fn new(path: &str, reload: Arc<SceneReload>, names: NodeNames) -> Self {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        Self { path: path.to_string(), modified, reload, names }
+    }
+
+    /// Reload if the file changed; a file that fails to load leaves the current scene playing.
+    fn poll(&mut self) {
+        let Ok(modified) = std::fs::metadata(&self.path).and_then(|m| m.modified()) else { return };
+        if self.modified == Some(modified) { return; }
+        self.modified = Some(modified);
+        let desc = scene_file::SceneDesc::read(&self.path);
+        match desc.and_then(|d| Ok((d.compile(self.reload.sample_rate())?, d))) {
+            Ok((g, d)) => {
+                log::info(format_args!("reloaded scene file {} ({})", self.path, g.name()));
+                self.reload.publish(g.into());
+                *self.names.write().unwrap_or_else(|e| e.into_inner()) = d.nodes.into_iter().map(|n| n.id).collect();
+            }
+            Err(e) => log::warn(format_args!("{}: {e} (keeping the current scene)", self.path)),
+        }
+    }
+}
+
+/// `ambientor-cli bench`: render `--duration` seconds (default 10) of the scene
+/// offline as fast as possible and report the speed, plus a per-stage CPU
+/// breakdown when built with `--features profile`.
+fn run_bench(args: &Args) -> Result<(), Box<dyn Error>> {
+    let sr = args.sample_rate.unwrap_or(48_000) as f32;
+    let secs = args.duration_sec.unwrap_or(10);
+    let mut engine = Engine::new(initial_scene(args, sr)?);
+    engine.set_sample_rate(sr);
+    if let Some(x) = args.intensity { engine.scene_mut().set_intensity(x); }
+    if let Some(w) = args.time_warp { engine.scene_mut().set_time_warp(w); }
+    apply_sets(&mut engine, &args.sets)?;
+    let frames = secs * sr as u64;
+
+    println!("Bench: {} for {secs} s at {sr} Hz ({frames} frames)", engine.scene_mut().name());
+    engine.reset_cpu_report();
+    let t0 = Instant::now();
+    let mut sink = 0.0f32;
+    for _ in 0..frames {
+        let (l, r) = engine.next_stereo(sr);
+        sink += l + r;
+    }
+    let wall = t0.elapsed().as_secs_f64();
+    std::hint::black_box(sink);
+
+    println!("Rendered in {:.1} ms: {:.1}× realtime, {:.1} ns/frame",
+        wall * 1e3, secs as f64 / wall.max(1e-9), wall * 1e9 / frames.max(1) as f64);
+    if profile::ENABLED {
+        let report = engine.cpu_report();
+        println!("\nStage     time (ms)   share");
+        for (stage, ns, share) in report.entries() {
+            println!("{:<8} {:>10.1}  {:>5.1}%", stage.name(), ns as f64 * 1e-6, 100.0 * share);
+        }
+        println!("(per-stage timing adds overhead; compare shares, not totals)");
+    } else {
+        println!("(build with `--features profile` for a per-stage breakdown)");
+    }
+    Ok(())
+}
+
+fn build_test_stream<T>(
     device: &cpal::Device,
     cfg: &cpal::StreamConfig,
-    mut engine: Engine<Scene>,
+    mut test: ChannelTest,
+) -> Result<cpal::Stream, Box<dyn Error>>
+where
+    T: cpal::Sample + cpal::FromSample<f32> + cpal::SizedSample + Send + 'static,
+{
+    let channels = cfg.channels as usize;
+    let stream = device.build_output_stream(
+        cfg,
+        move |output: &mut [T], _| {
+            for frame in output.chunks_mut(channels) {
+                let (ch, y) = test.next();
+                for (c, s) in frame.iter_mut().enumerate() {
+                    *s = if c == ch { T::from_sample(y) } else { T::EQUILIBRIUM };
+                }
+            }
+        },
+        |e| eprintln!("[cpal] stream error: {e}"),
+        None,
+    )?;
+    Ok(stream)
+}
+
+/// `ambientor-cli test`: identify every output channel by ear before running an
+/// installation. Channel N beeps N times, each channel a whole tone higher;
+/// runs until Ctrl+C or `--duration`. `--device` and `--channels` pick the
+/// output; `--channel-map` and the rest of the output stage are bypassed.
+fn run_test(args: &Args) -> Result<(), Box<dyn Error>> {
+    let (device, cfg, sample_format) = pick_output(args)?;
+    let channels = cfg.channels as usize;
+    println!("Channel test on {} ({channels} ch at {} Hz, {sample_format:?})", device.name()?, cfg.sample_rate.0);
+    println!("Channel N beeps N times, pitch rising per channel. Press Ctrl+C to stop…\n");
+
+    let (test, current) = channel_test::channel_test(cfg.sample_rate.0 as f32, channels);
+    let stream = for_sample_format!(sample_format, build_test_stream(&device, &cfg, test))?;
+    stream.play()?;
+
+    let started = Instant::now();
+    let mut shown = usize::MAX;
+    loop {
+        std::thread::sleep(Duration::from_millis(50));
+        let ch = current.load(Ordering::Relaxed);
+        if ch != shown {
+            println!("channel {} of {channels}", ch + 1);
+            shown = ch;
+        }
+        if args.duration_sec.is_some_and(|d| started.elapsed() >= Duration::from_secs(d)) {
+            return Ok(());
+        }
+    }
+}
+
+/// `ambientor-cli calibrate`: measure every built-in scene's loudness at default
+/// settings over `--duration` seconds (default 60, after 5 s of settling), next to
+/// the calibration auto-gain currently uses. Paste new values into the scenes'
+/// loudness table after changing their levels.
+fn run_calibrate(args: &Args) -> Result<(), Box<dyn Error>> {
+    let sr = args.sample_rate.unwrap_or(48_000) as f32;
+    let secs = args.duration_sec.unwrap_or(60) as f32;
+    println!("Scene          measured   calibrated   (RMS dBFS, {secs} s at {sr} Hz)");
+    for name in SCENE_NAMES {
+        let scene = Scene::by_name(name, sr).ok_or("unknown scene")?;
+        let calibrated = scene.loudness_db();
+        let mut engine = Engine::new(scene);
+        engine.set_sample_rate(sr);
+        let db = measure_loudness_db(&mut engine, 5.0, secs);
+        let cal = calibrated.map_or("-".to_string(), |c| format!("{c:.1}"));
+        println!("{name:<14} {db:>8.1}   {cal:>10}");
+    }
+    Ok(())
+}
+
+/// Engine for `args`: scene plus the master/scene settings shared by playback and render.
+fn build_engine(args: &Args, sr: f32) -> Result<Engine<Scene>, Box<dyn Error>> {
+    let mut engine = Engine::new(initial_scene(args, sr)?);
+    engine.set_sample_rate(sr);
+    if let Some(pct) = args.width { engine.master_mut().set_width_percent(pct); }
+    if let Some(hz) = args.tuning_ref { engine.scene_mut().set_tuning(Tuning::with_ref_hz(hz)); }
+    if let Some(amount) = args.randomize { engine.scene_mut().randomize(args.seed.unwrap_or(0), amount); }
+    if let Some(x) = args.intensity { engine.scene_mut().set_intensity(x); }
+    if let Some(w) = args.time_warp { engine.scene_mut().set_time_warp(w); }
+    if let Some(spec) = &args.eq {
+        let (low, mid, high) = parse_eq(spec)
+            .ok_or_else(|| format!("invalid --eq: {spec} (expected LOW,MID,HIGH in dB, e.g. -3,0,2)"))?;
+        engine.master_mut().set_eq_db(low, mid, high);
+    }
+    if let Some(hz) = args.eq_mid_hz { engine.master_mut().set_eq_mid_hz(hz); }
+    if let Some(t) = args.tone { engine.master_mut().set_tone(t); }
+    match args.highpass.as_deref() {
+        None => {}
+        Some("off") => engine.master_mut().set_highpass(false, MASTER_HP_HZ),
+        Some(spec) => {
+            let hz: f32 = spec.parse().map_err(|_| format!("invalid --highpass: {spec} (expected Hz or off)"))?;
+            engine.master_mut().set_highpass(true, hz);
+        }
+    }
+    if let Some(level) = args.sub_bed {
+        engine.master_mut().set_sub_bed(level, args.sub_bed_hz.unwrap_or(SUB_BED_DEFAULT_HZ), 0.05);
+    }
+    match args.mono_bass.as_deref() {
+        None => {}
+        Some("on") => engine.master_mut().set_mono_bass(true, MONO_BASS_HZ),
+        Some(spec) => {
+            let hz: f32 = spec.parse().map_err(|_| format!("invalid --mono-bass: {spec} (expected Hz or on)"))?;
+            engine.master_mut().set_mono_bass(true, hz);
+        }
+    }
+    if let Some(spec) = &args.dyn_eq {
+        let mut it = spec.split(',').map(|p| p.trim().parse::<f32>());
+        let (hz, threshold) = match (it.next(), it.next(), it.next()) {
+            (Some(Ok(hz)), None, None) => (hz, DYN_EQ_THRESHOLD_DB),
+            (Some(Ok(hz)), Some(Ok(db)), None) => (hz, db),
+            _ => return Err(format!("invalid --dyn-eq: {spec} (expected HZ[,THRESHOLD_DB], e.g. 1500,-30)").into()),
+        };
+        engine.master_mut().set_return_dyn_eq(true, hz, threshold);
+    }
+    if let Some(spec) = &args.texture {
+        let (low, mid, high) = parse_eq(spec)
+            .ok_or_else(|| format!("invalid --texture: {spec} (expected LOW,MID,HIGH saturation 0..1, e.g. 0.4,0,0.3)"))?;
+        for (band, amount) in [low, mid, high].into_iter().enumerate() { engine.master_mut().set_texture_band(band, amount, 0.0); }
+    }
+    engine.master_mut().set_reverb_bypass(args.reverb_bypass);
+    engine.set_auto_trim(args.auto_trim);
+    engine.set_auto_gain(args.auto_gain);
+    apply_sets(&mut engine, &args.sets)?;
+    if let Some(to) = &args.morph_to {
+        let preset = |spec: &str| ParamSet::parse(spec)
+            .ok_or_else(|| format!("invalid preset: {spec} (expected name=value,… e.g. intensity=0.3,tone=-0.5)"));
+        let from = preset(args.morph_from.as_deref().unwrap_or(""))?;
+        engine.morph(&from, &preset(to)?, 60.0 * args.morph_minutes.unwrap_or(DEFAULT_MORPH_MIN));
+    }
+    Ok(engine)
+}
+
+/// Gain, channel map and optional binaural stage for `channels` outputs.
+fn build_output(args: &Args, sr: f32, channels: u16) -> Result<OutputStage, Box<dyn Error>> {
+    let map = match args.channel_map.as_deref() {
+        Some(spec) => ChannelMap::parse(spec)
+            .ok_or_else(|| format!("invalid --channel-map: {spec} (expected e.g. -,-,L,R)"))?,
+        None => ChannelMap::stereo_pairs(channels as usize)
+            .ok_or_else(|| format!("{channels} channels: at most {MAX_CHANNELS} without --channel-map"))?,
+    };
+    let binaural = args.binaural.then(|| {
+        let mut b = Binaural::new(sr);
+        if let Some(w) = args.binaural_width { b.set_width(w); }
+        if let Some(hz) = args.orbit_rate { b.set_orbit_hz(hz); }
+        b
+    });
+    Ok(OutputStage {
+        gain: args.gain.unwrap_or(0.35),
+        map,
+        binaural,
+        meter: None,
+        paused: false,
+        level: 1.0,
+        level_step: 1.0 / (PAUSE_FADE_S * sr).max(1.0),
+    })
+}
+
+/// Default crossfade for `render --loop`, in seconds.
+const LOOP_FADE_S: f32 = 5.0;
+
+/// `ambientor-cli render --out=FILE`: render `--duration` seconds (default 60)
+/// offline. The format follows `--format=wav|ogg` or the file extension (WAV by
+/// default; Ogg Vorbis needs the `vorbis` feature, quality from `--quality=0..1`).
+/// WAV is 16-bit, dithered per `--dither=tpdf|shaped|off` (default tpdf).
+/// With `--loop` the file loops seamlessly: the last `--loop-fade` seconds
+/// (default 5) are crossfaded into the beginning. `--description=TEXT` makes a
+/// WAV a Broadcast Wave (bext chunk); WAVs past 4 GB are written as RF64.
+/// `--skip-silence` fast-forwards through stretches where the scene and its
+/// echoes are silent instead of rendering them; of the built-in scenes only
+/// cave-drips has such stretches, the others render as usual.
+fn run_render(args: &Args) -> Result<(), Box<dyn Error>> {
+    let sr = args.sample_rate.unwrap_or(48_000);
+    let channels = args.channels.unwrap_or(2).max(1);
+    let secs = args.duration_sec.unwrap_or(60);
+    let path = args.out_path.as_deref().unwrap_or("ambientor.wav");
+    let format = match args.format.as_deref() {
+        Some(s) => ExportFormat::parse(s).ok_or_else(|| format!("invalid --format: {s} (expected wav or ogg)"))?,
+        None => ExportFormat::from_path(path).unwrap_or_default(),
+    };
+    let mut opts = ExportOptions::default();
+    if let Some(s) = args.dither.as_deref() {
+        opts.dither = DitherMode::parse(s).ok_or_else(|| format!("invalid --dither: {s} (expected tpdf, shaped or off)"))?;
+    }
+    if let Some(q) = args.quality { opts.quality = q.clamp(0.0, 1.0); }
+
+    let sr_f32 = sr as f32;
+    let mut engine = build_engine(args, sr_f32)?;
+    engine.set_skip_idle(args.skip_silence);
+    let mut out = build_output(args, sr_f32, channels)?;
+    opts.bext = args.description.as_ref().map(|d| BextInfo {
+        originator_reference: engine.scene_mut().name().to_string(),
+        ..BextInfo::new(d.as_str())
+    });
+    let mut writer = AudioFileWriter::create(path, format, sr, channels, &opts)?;
+    let frames = secs as usize * sr as usize;
+    let ch = channels as usize;
+
+    let detail = match format {
+        ExportFormat::Wav => format!("dither {}", opts.dither.name()),
+        ExportFormat::OggVorbis => format!("quality {:.2}", opts.quality),
+    };
+    println!("Rendering {} for {secs} s at {sr} Hz, {channels} ch, {} ({detail}) → {path}",
+        engine.scene_mut().name(), format.name());
+    if let (ExportFormat::Wav, Some(d)) = (format, &args.description) { println!("Broadcast Wave: \"{d}\""); }
+    if let Some(amount) = args.randomize { println!("Variation: amount {amount:.2}, seed {}", args.seed.unwrap_or(0)); }
+    let t0 = Instant::now();
+    let render = |block: &mut [f32]| {
+        let (mut skip, n) = (0, block.len() / ch);
+        for (i, frame) in block.chunks_exact_mut(ch).enumerate() {
+            if skip == 0 { skip = engine.skip_idle(sr_f32, n - i); }
+            let (l, r) = if skip > 0 {
+                skip -= 1;
+                (0.0, 0.0)
+            } else {
+                engine.next_stereo(sr_f32)
+            };
+            let (l, r) = out.frame(l, r);
+            out.map.write_frame(frame, l, r);
+        }
+    };
+    let sink = |block: &[f32]| writer.write(block);
+    if args.loop_render {
+        let fade_s = args.loop_fade_s.unwrap_or(LOOP_FADE_S).max(0.0);
+        let fade = (fade_s * sr_f32) as usize;
+        println!("Seamless loop: {:.1} s crossfade", fade.min(frames) as f32 / sr_f32);
+        export::render_loop(ch, frames, fade, render, sink)?;
+    } else {
+        export::render_blocks(ch, frames, render, sink)?;
+    }
+    writer.finish()?;
+    println!("Done in {:.1} s", t0.elapsed().as_secs_f64());
+    let clip = engine.clip_guard();
+    if clip.clipped_frames() > 0 {
+        let hint = if args.auto_trim { "" } else { " (try --auto-trim)" };
+        println!("Scene clipped in {} of {} frames{hint}", clip.clipped_frames(), clip.total_frames());
+    }
+    Ok(())
+}
+
+/// Seconds since local midnight, from the system clock (UTC) plus `utc_offset_h`.
+fn seconds_of_day(utc_offset_h: f32) -> f64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |d| d.as_secs_f64());
+    now + f64::from(utc_offset_h) * 3600.0
+}
+
+/// Fade out on pause and back in on resume (seconds).
+const PAUSE_FADE_S: f32 = 0.05;
+
+/// Everything between the engine's stereo frame and the device buffer.
+struct OutputStage {
     gain: f32,
+    map: ChannelMap,
+    binaural: Option<Binaural>,
+    meter: Option<MeterTap>, // feeds the live `--meters` display
+    paused: bool,
+    level: f32,      // pause fade 0..1
+    level_step: f32, // per frame
+}
+
+impl OutputStage {
+    /// Post-process one engine frame: optional binaural, gain, pause fade, hard clamp.
+    #[inline]
+    fn frame(&mut self, l: f32, r: f32) -> (f32, f32) {
+        let (l, r) = match self.binaural.as_mut() {
+            Some(b) => b.process(l, r),
+            None => (l, r),
+        };
+        self.level = if self.paused { (self.level - self.level_step).max(0.0) } else { (self.level + self.level_step).min(1.0) };
+        let g = self.gain * self.level;
+        let (l, r) = ((l * g).clamp(-1.0, 1.0), (r * g).clamp(-1.0, 1.0));
+        if let Some(m) = self.meter.as_mut() { m.push(l, r); }
+        (l, r)
+    }
+
+    /// Paused and faded out: the engine can stop rendering.
+    #[inline]
+    fn is_silent(&self) -> bool { self.paused && self.level == 0.0 }
+}
+
+/// How often the main thread prints callback statistics.
+const XRUN_REPORT_EVERY: Duration = Duration::from_secs(10);
+
+/// Audio callback timing, shared between the callback and the main thread.
+///
+/// - `late`: the gap since the previous callback was well over one buffer
+///   (the device most likely ran dry in between)
+/// - `overruns`: a callback took longer than the audio it produced
+/// - `stream_errors`: reported by the backend's error callback
+#[derive(Debug, Default)]
+struct XrunStats {
+    callbacks: AtomicU64,
+    late: AtomicU64,
+    overruns: AtomicU64,
+    stream_errors: AtomicU64,
+    worst_us: AtomicU64,
+    budget_us: AtomicU64, // duration of the last buffer
+}
+
+impl XrunStats {
+    fn summary(&self) -> String {
+        let worst = self.worst_us.load(Ordering::Relaxed);
+        let budget = self.budget_us.load(Ordering::Relaxed).max(1);
+        format!(
+            "{} callbacks, {} late, {} overruns, {} stream errors; worst callback {:.2} ms ({:.0}% of a {:.2} ms buffer)",
+            self.callbacks.load(Ordering::Relaxed),
+            self.late.load(Ordering::Relaxed),
+            self.overruns.load(Ordering::Relaxed),
+            self.stream_errors.load(Ordering::Relaxed),
+            worst as f64 * 1e-3,
+            100.0 * worst as f64 / budget as f64,
+            budget as f64 * 1e-3,
+        )
+    }
+}
+
+/// Start `--script` on its own thread, pushing commands into `tx`.
+#[cfg(feature = "scripting")]
+fn start_script(path: &str, tx: CommandSender, names: NodeNames) -> Result<impl Drop, Box<dyn Error>> {
+    use ambientor_engine::script::{ScriptHost, DEFAULT_PERIOD};
+    let host = ScriptHost::load(path, tx, names).map_err(|e| format!("{path}: {e}"))?;
+    Ok(host.spawn(DEFAULT_PERIOD)?)
+}
+
+#[cfg(not(feature = "scripting"))]
+fn start_script(_path: &str, _tx: CommandSender, _names: NodeNames) -> Result<(), Box<dyn Error>> {
+    Err("--script needs a build with `--features scripting`".into())
+}
+
+/// `--http`: serve the REST control API (see `http`) on its own thread.
+#[cfg(feature = "http")]
+fn start_http(
+    addr: &str,
+    reload: &Arc<SceneReload>,
+    paused: &Arc<AtomicBool>,
+    controls: &mut Controls,
+) -> Result<(), Box<dyn Error>> {
+    let status = Arc::clone(controls.status.get_or_insert_with(|| Arc::new(remote::Status::new())));
+    let (tx, rx) = command::command_queue(command::DEFAULT_CAPACITY);
+    controls.commands.push(rx);
+    let remote = remote::Remote::new(status, tx, Arc::clone(reload), Arc::clone(paused));
+    let bound = http::spawn(addr, remote).map_err(|e| format!("--http={addr}: {e}"))?;
+    println!("HTTP control: http://{bound}/status");
+    Ok(())
+}
+
+#[cfg(not(feature = "http"))]
+fn start_http(
+    _addr: &str,
+    _reload: &Arc<SceneReload>,
+    _paused: &Arc<AtomicBool>,
+    _controls: &mut Controls,
+) -> Result<(), Box<dyn Error>> {
+    Err("--http needs a build with `--features http`".into())
+}
+
+/// `--mqtt`: connect to the broker (see `mqtt`) on its own thread; an
+/// unreachable broker is retried in the background.
+#[cfg(feature = "mqtt")]
+fn start_mqtt(
+    broker: &str,
+    prefix: &str,
+    reload: &Arc<SceneReload>,
+    paused: &Arc<AtomicBool>,
+    controls: &mut Controls,
+) -> Result<(), Box<dyn Error>> {
+    let status = Arc::clone(controls.status.get_or_insert_with(|| Arc::new(remote::Status::new())));
+    let (tx, rx) = command::command_queue(command::DEFAULT_CAPACITY);
+    controls.commands.push(rx);
+    mqtt::spawn(broker, prefix, remote::Remote::new(status, tx, Arc::clone(reload), Arc::clone(paused)))?;
+    println!("MQTT control: {broker}, topics under {prefix}/");
+    Ok(())
+}
+
+#[cfg(not(feature = "mqtt"))]
+fn start_mqtt(
+    _broker: &str,
+    _prefix: &str,
+    _reload: &Arc<SceneReload>,
+    _paused: &Arc<AtomicBool>,
+    _controls: &mut Controls,
+) -> Result<(), Box<dyn Error>> {
+    Err("--mqtt needs a build with `--features mqtt`".into())
+}
+
+/// Publish the player over MPRIS for media keys and desktop applets; without a
+/// session bus it only warns.
+#[cfg(all(feature = "mpris", target_os = "linux"))]
+fn start_mpris(scene: &str, paused: &Arc<AtomicBool>, quit: &Arc<AtomicBool>) -> Option<mpris::Mpris> {
+    match mpris::serve(scene, Arc::clone(paused), Arc::clone(quit)) {
+        Ok(m) => {
+            println!("MPRIS: on (media keys pause and resume)");
+            Some(m)
+        }
+        Err(e) => {
+            log::warn(format_args!("MPRIS unavailable: {e}"));
+            None
+        }
+    }
+}
+
+/// Stands in for [`mpris::Mpris`] in builds without it.
+#[cfg(not(all(feature = "mpris", target_os = "linux")))]
+struct NoMpris;
+
+#[cfg(not(all(feature = "mpris", target_os = "linux")))]
+impl NoMpris {
+    fn update(&mut self, _scene: &str) {}
+}
+
+#[cfg(not(all(feature = "mpris", target_os = "linux")))]
+fn start_mpris(_scene: &str, _paused: &Arc<AtomicBool>, _quit: &Arc<AtomicBool>) -> Option<NoMpris> {
+    None
+}
+
+/// Fade length of `--sleep-timer` without `--sleep-fade`, in minutes.
+const DEFAULT_SLEEP_FADE_MIN: f64 = 10.0;
+/// Intensity a sleep fade glides down to.
+const SLEEP_INTENSITY: f32 = 0.0;
+/// Time after the sleep fade ends for the reverb tail to die out (seconds).
+const SLEEP_TAIL_S: f64 = 5.0;
+
+/// `--sleep-timer`: over the last `fade_s` before `at_s`, glide the scene
+/// intensity down and its level to silence, on the engine's own ramps. Time
+/// is counted in rendered audio, so the exit lines up with the fade however
+/// late the callbacks run.
+struct SleepTimer {
+    at_s: f64,
+    fade_s: f64,
+    elapsed_s: f64,
+    fading: bool,
+    done: Arc<AtomicBool>, // raised `SLEEP_TAIL_S` after the fade ends
+}
+
+impl SleepTimer {
+    fn new(at_s: f64, fade_s: f64) -> Self {
+        Self { at_s, fade_s: fade_s.min(at_s), elapsed_s: 0.0, fading: false, done: Arc::new(AtomicBool::new(false)) }
+    }
+
+    /// Set once the fade and its tail have played and the player can exit.
+    fn done_flag(&self) -> Arc<AtomicBool> { Arc::clone(&self.done) }
+
+    fn advance(&mut self, dt_s: f64, engine: &mut Engine<Scene>) {
+        self.elapsed_s += dt_s;
+        if self.elapsed_s >= self.at_s + SLEEP_TAIL_S { self.done.store(true, Ordering::Relaxed); }
+        if !self.fading && self.elapsed_s >= self.at_s - self.fade_s {
+            self.fading = true;
+            let secs = (self.at_s - self.elapsed_s).max(0.0) as f32;
+            engine.ramp_param(ParamId::Intensity, SLEEP_INTENSITY, secs);
+            engine.ramp_param(ParamId::SceneGain, 0.0, secs);
+        }
+    }
+}
+
+/// Control-rate inputs applied at the top of every audio callback.
+struct Controls {
+    circadian: Option<Circadian>,
+    sleep: Option<SleepTimer>,
+    reload: Arc<SceneReload>, // scene-file hot reload and remote scene switches
+    commands: Vec<CommandReceiver>, // one per script or remote
+    paused: Arc<AtomicBool>, // set by media keys (MPRIS) and remotes
+    #[cfg(any(feature = "http", feature = "mqtt"))]
+    status: Option<Arc<remote::Status>>, // published for remotes
+}
+
+impl Controls {
+    #[inline]
+    fn paused(&self) -> bool { self.paused.load(Ordering::Relaxed) }
+
+    fn apply(&mut self, engine: &mut Engine<Scene>, dt_s: f64) {
+        engine.set_transport(if self.paused() { Transport::Stopped } else { Transport::Playing });
+        self.reload.install(engine);
+        for rx in &mut self.commands { rx.apply_all(engine); }
+        if let Some(s) = self.sleep.as_mut() { s.advance(dt_s, engine); }
+        // the sleep fade owns intensity once it starts
+        if let Some(c) = self.circadian.as_mut().filter(|_| !self.sleep.as_ref().is_some_and(|s| s.fading)) {
+            c.advance(dt_s);
+            c.apply(engine);
+        }
+        #[cfg(any(feature = "http", feature = "mqtt"))]
+        if let Some(s) = &self.status { s.publish(engine); }
+    }
+}
+
+/// Where the callback's level meter reports to.
+#[derive(Default)]
+struct LevelReport {
+    #[cfg(any(feature = "http", feature = "mqtt"))]
+    status: Option<Arc<remote::Status>>, // published for remotes
+}
+
+impl LevelReport {
+    /// Output peak and RMS of the last second, for remotes.
+    #[inline]
+    fn report(&self, _peak: f32, _rms: f32) {
+        #[cfg(any(feature = "http", feature = "mqtt"))]
+        if let Some(s) = &self.status { s.publish_levels(_peak, _rms); }
+    }
+}
+
+/// How the player renders: in the audio callback, or ahead of it on a
+/// worker (`--render-ahead`), which then applies the controls per block.
+enum Render {
+    Direct(Box<Engine<Scene>>, Box<Controls>),
+    Ahead(RenderAhead<Scene>),
+}
+
+impl Render {
+    #[inline]
+    fn next_stereo(&mut self, sr: f32) -> (f32, f32) {
+        match self {
+            Self::Direct(engine, _) => engine.next_stereo(sr),
+            Self::Ahead(ra) => ra.next_frame(),
+        }
+    }
+
+    fn set_sample_rate(&mut self, sr: f32) {
+        match self {
+            Self::Direct(engine, _) => engine.set_sample_rate(sr),
+            Self::Ahead(ra) => ra.engine().set_sample_rate(sr),
+        };
+    }
+}
+
+/// Everything the audio callback renders with. Shared with the main thread
+/// so a stream rebuilt after the device went away resumes the same engine;
+/// the callback only `try_lock`s it and plays silence while the main thread
+/// holds it.
+struct Player {
+    render: Render,
+    out: OutputStage,
+    paused: Arc<AtomicBool>, // set by media keys (MPRIS) and remotes
+    levels: LevelReport,
+}
+
+fn build_stream<T>(
+    device: &cpal::Device,
+    cfg: &cpal::StreamConfig,
+    player: Arc<Mutex<Player>>,
+    stats: Arc<XrunStats>,
     err_fn: impl Fn(cpal::StreamError) + Send + 'static,
 ) -> Result<cpal::Stream, Box<dyn Error>>
 where
@@ -116,32 +1067,69 @@ where
     let sr = cfg.sample_rate.0 as f32;
     let channels = cfg.channels as usize;
 
-    // ~1 second meter at requested rate
+    // ~1 second peak log at requested rate (the live display replaces it)
     let meter_interval = (cfg.sample_rate.0).max(1) as usize;
     let mut meter_count: usize = 0;
     let mut meter_peak: f32 = 0.0;
+    let mut meter_sq: f32 = 0.0;
+    let mut meter_clipped: usize = 0;
+    let mut last_start: Option<Instant> = None;
 
     let stream = device.build_output_stream(
         cfg,
         move |output: &mut [T], _| {
+            let t0 = Instant::now();
+            let budget = Duration::from_secs_f64((output.len() / channels) as f64 / f64::from(sr));
+            if let Some(prev) = last_start {
+                // generous slack: callback spacing jitters with the backend's scheduling
+                if t0 - prev > budget * 3 / 2 + Duration::from_millis(2) {
+                    stats.late.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            last_start = Some(t0);
+
+            let Ok(mut guard) = player.try_lock() else {
+                output.fill(T::EQUILIBRIUM);
+                return;
+            };
+            let Player { render, out, paused, levels } = &mut *guard;
+            if let Render::Direct(engine, controls) = render {
+                controls.apply(engine, (output.len() / channels) as f64 / f64::from(sr));
+            }
+            out.paused = paused.load(Ordering::Relaxed);
             for frame in output.chunks_mut(channels) {
-                let mut s = engine.next(sr) * gain;
-                if s >  1.0 { s =  1.0; }
-                if s < -1.0 { s = -1.0; }
+                // once faded out, a paused engine holds still
+                let (l, r) = if out.is_silent() { (0.0, 0.0) } else { render.next_stereo(sr) };
+                let (l, r) = out.frame(l, r);
 
-                let v: T = T::from_sample(s);
-                for ch in frame.iter_mut() { *ch = v; }
+                for (c, y) in frame.iter_mut().enumerate() {
+                    *y = T::from_sample(out.map.route(c, l, r));
+                }
 
                 // naive peak meter
-                let a = if s >= 0.0 { s } else { -s };
+                let a = l.abs().max(r.abs());
                 if a > meter_peak { meter_peak = a; }
+                meter_sq += 0.5 * (l * l + r * r);
+                if a >= 1.0 { meter_clipped += 1; } // OutputStage clamps to ±1
                 meter_count += 1;
                 if meter_count >= meter_interval {
-                    eprintln!("[meter] peak ~ {:.3}", meter_peak);
+                    if out.meter.is_none() { log::info(format_args!("[meter] peak ~ {meter_peak:.3}")); }
+                    if meter_clipped > 0 {
+                        log::warn(format_args!("clipping: {meter_clipped} frames hit full scale in the last second (lower --gain)"));
+                    }
+                    levels.report(meter_peak, (meter_sq / meter_count as f32).sqrt());
                     meter_peak = 0.0;
+                    meter_sq = 0.0;
+                    meter_clipped = 0;
                     meter_count = 0;
                 }
             }
+
+            let took = t0.elapsed();
+            if took > budget { stats.overruns.fetch_add(1, Ordering::Relaxed); }
+            stats.worst_us.fetch_max(took.as_micros() as u64, Ordering::Relaxed);
+            stats.budget_us.store(budget.as_micros() as u64, Ordering::Relaxed);
+            stats.callbacks.fetch_add(1, Ordering::Relaxed);
         },
         err_fn,
         None,
@@ -150,6 +1138,86 @@ where
     Ok(stream)
 }
 
+/// Output device and stream config for `args`.
+fn pick_output(args: &Args) -> Result<(cpal::Device, cpal::StreamConfig, cpal::SampleFormat), Box<dyn Error>> {
+    let device  = pick_device(args)?;
+    let sup_cfg = choose_config(&device, args.sample_rate, args.channels)?;
+    let sample_format = sup_cfg.sample_format();
+    let mut cfg = sup_cfg.config();
+
+    if let Some(sr) = args.sample_rate { cfg.sample_rate = cpal::SampleRate(sr); }
+    if let Some(ch) = args.channels    { cfg.channels    = ch; }
+    Ok((device, cfg, sample_format))
+}
+
+/// Build and start a stream on `device` rendering from `player`. Sets `lost`
+/// when the backend reports the device gone.
+fn start_stream(
+    device: &cpal::Device,
+    cfg: &cpal::StreamConfig,
+    sample_format: cpal::SampleFormat,
+    player: &Arc<Mutex<Player>>,
+    stats: &Arc<XrunStats>,
+    lost: &Arc<AtomicBool>,
+) -> Result<cpal::Stream, Box<dyn Error>> {
+    // Underruns often surface as backend errors; count them alongside the timing checks.
+    let (err_stats, err_lost) = (Arc::clone(stats), Arc::clone(lost));
+    let err_fn = move |e: cpal::StreamError| {
+        err_stats.stream_errors.fetch_add(1, Ordering::Relaxed);
+        if matches!(e, cpal::StreamError::DeviceNotAvailable) { err_lost.store(true, Ordering::Relaxed); }
+        eprintln!("[cpal] stream error: {e}");
+    };
+
+    let (p, st) = (Arc::clone(player), Arc::clone(stats));
+    let stream = for_sample_format!(sample_format, build_stream(device, cfg, p, st, err_fn))?;
+
+    stream.play()?;
+    Ok(stream)
+}
+
+/// Time between attempts to reopen a lost output device.
+const RECONNECT_RETRY: Duration = Duration::from_secs(2);
+/// A stream whose callback hasn't run for this long counts as lost: some
+/// backends just stop calling back when a USB interface drops out.
+const STALL_AFTER: Duration = Duration::from_secs(3);
+
+/// How often `--follow-default` checks which device is the system default.
+const DEFAULT_POLL: Duration = Duration::from_secs(2);
+
+/// Name of the system default output device, if there is one.
+fn default_output_name() -> Option<String> {
+    cpal::default_host().default_output_device().and_then(|d| d.name().ok())
+}
+
+/// Reopen the output after the device went away (or the default changed):
+/// `--device` (or the current default) again, at whatever config it offers
+/// now. The engine keeps its state; only a changed rate or channel count
+/// retunes it and the output stage. Returns the stream and the device's name.
+fn reconnect(
+    args: &Args,
+    player: &Arc<Mutex<Player>>,
+    reload: &SceneReload,
+    cfg: &mut cpal::StreamConfig,
+    stats: &Arc<XrunStats>,
+    lost: &Arc<AtomicBool>,
+) -> Result<(cpal::Stream, String), Box<dyn Error>> {
+    let (device, new_cfg, sample_format) = pick_output(args)?;
+    if new_cfg.sample_rate != cfg.sample_rate || new_cfg.channels != cfg.channels {
+        let sr = new_cfg.sample_rate.0 as f32;
+        let mut p = player.lock().unwrap_or_else(|e| e.into_inner());
+        p.render.set_sample_rate(sr);
+        reload.set_sample_rate(sr); // scenes built from now on, by remotes and hot reload
+        let meter = p.out.meter.take();
+        p.out = build_output(args, sr, new_cfg.channels)?;
+        p.out.meter = meter;
+    }
+    let stream = start_stream(&device, &new_cfg, sample_format, player, stats, lost)?;
+    let name = device.name()?;
+    log::info(format_args!("output now on {name} ({new_cfg:?}, {sample_format:?})"));
+    *cfg = new_cfg;
+    Ok((stream, name))
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     let args = parse_args();
 
@@ -157,43 +1225,281 @@ fn main() -> Result<(), Box<dyn Error>> {
         list_output_devices()?;
         return Ok(());
     }
+    if args.list_scenes {
+        list_scenes();
+        return Ok(());
+    }
+    if let Some(name) = &args.describe_scene {
+        return describe_scene(name);
+    }
+    if args.bench {
+        return run_bench(&args);
+    }
+    if args.calibrate {
+        return run_calibrate(&args);
+    }
+    if args.test {
+        return run_test(&args);
+    }
+    if args.render {
+        return run_render(&args);
+    }
 
     println!("ambientor-cli — real-time ambient player\n");
 
-    let device  = pick_device(&args)?;
-    let sup_cfg = choose_config(&device, args.sample_rate, args.channels)?;
-    let sample_format = sup_cfg.sample_format();
-    let mut cfg = sup_cfg.config();
-
-    if let Some(sr) = args.sample_rate { cfg.sample_rate = cpal::SampleRate(sr); }
-    if let Some(ch) = args.channels    { cfg.channels    = ch; }
+    let (device, mut cfg, sample_format) = pick_output(&args)?;
+    let mut device_name = device.name()?;
+    let follow_default = args.follow_default && args.device_name.is_none();
+    if args.follow_default && !follow_default { log::warn(format_args!("--follow-default is ignored with --device")); }
 
     let sr_f32 = cfg.sample_rate.0 as f32;
-    let engine = Engine::new(make_scene(args.scene.as_deref(), sr_f32));
-    let gain   = args.gain.unwrap_or(0.35);
+    let mut engine = build_engine(&args, sr_f32)?;
+    let circadian = match args.circadian.as_deref() {
+        Some("default") => Some(Circadian::default_day()),
+        Some(spec) => Some(Circadian::parse(spec)
+            .ok_or_else(|| format!("invalid --circadian: {spec} (expected e.g. 0=0.2,7:30=0.5,13=0.8/1.3)"))?),
+        None => None,
+    };
+    let circadian = circadian.map(|mut c| {
+        c.set_time_s(seconds_of_day(args.utc_offset_h.unwrap_or(0.0)));
+        c.apply(&mut engine);
+        c
+    });
+    let sleep = match args.sleep_timer.as_deref() {
+        Some(spec) => {
+            let at = parse_duration_s(spec)
+                .ok_or_else(|| format!("invalid --sleep-timer: {spec} (expected e.g. 45m, 1h30m or 90s)"))?;
+            let fade = match args.sleep_fade.as_deref() {
+                Some(f) => parse_duration_s(f).ok_or_else(|| format!("invalid --sleep-fade: {f} (expected e.g. 10m)"))?,
+                None => 60.0 * DEFAULT_SLEEP_FADE_MIN,
+            };
+            Some(SleepTimer::new(at, fade))
+        }
+        None => None,
+    };
+    let sleep_done = sleep.as_ref().map(SleepTimer::done_flag);
+    let mut out = build_output(&args, sr_f32, cfg.channels)?;
+    let meter_mode = match args.meters.as_deref() {
+        Some(spec) => Some(MeterMode::parse(spec).ok_or_else(|| format!("invalid --meters: {spec} (expected vu or spectrum)"))?),
+        None => None,
+    };
+    let mut meter_display = meter_mode.map(|mode| {
+        let (tap, display) = meters::meters(mode, sr_f32);
+        out.meter = Some(tap);
+        display
+    });
+    let reload = Arc::new(SceneReload::new(engine.scene_mut().name(), sr_f32));
+    let names: NodeNames = Arc::new(RwLock::new(args.scene_file.as_deref().map(scene_node_names).unwrap_or_default()));
+    let mut watcher = args.scene_file.as_deref().map(|path| {
+        SceneWatcher::new(path, Arc::clone(&reload), Arc::clone(&names))
+    });
+    // the script thread stops when `_script` drops at the end of main
+    let (_script, commands) = match args.script.as_deref() {
+        Some(path) => {
+            let (tx, rx) = command::command_queue(command::DEFAULT_CAPACITY);
+            (Some(start_script(path, tx, Arc::clone(&names))?), vec![rx])
+        }
+        None => (None, Vec::new()),
+    };
 
-    println!("Using device: {}", device.name()?);
+    println!("Using device: {device_name}{}", if follow_default { " (following the system default)" } else { "" });
     println!("Stream config: {:?} (sample_format: {:?})", cfg, sample_format);
-    println!("Scene: {}  | Gain: {:.2}", args.scene.as_deref().unwrap_or("slow-drone"), gain);
+    println!("Scene: {}  | Gain: {:.2}", engine.scene_mut().name(), out.gain);
+    if let Some(path) = &args.scene_file { println!("Scene file: {path} (hot reload on)"); }
+    if let Some(path) = &args.script { println!("Script: {path}"); }
+    if let Some(spec) = &args.channel_map { println!("Channel map: {spec}"); }
+    if let Some(pct) = args.width { println!("Stereo width: {pct:.0}%"); }
+    if let Some(hz) = args.tuning_ref { println!("Tuning: A4 = {hz} Hz"); }
+    if let Some(x) = args.intensity { println!("Intensity: {x:.2}"); }
+    if let Some(w) = args.time_warp { println!("Time warp: {w:.2}×"); }
+    if !args.sets.is_empty() { println!("Overrides: {}", args.sets.join(", ")); }
+    if let Some(amount) = args.randomize {
+        println!("Variation: amount {amount:.2}, seed {} (--seed= to hear it again)", args.seed.unwrap_or(0));
+    }
+    if let Some(to) = &args.morph_to {
+        let from = args.morph_from.as_deref().unwrap_or("current");
+        println!("Morph: {from} → {to} over {} min", args.morph_minutes.unwrap_or(DEFAULT_MORPH_MIN));
+    }
+    if args.eq.is_some() || args.eq_mid_hz.is_some() {
+        let eq = engine.master_mut().eq();
+        let (low, mid, high) = eq.gains_db();
+        println!("EQ: low {low:+.1} dB, mid {mid:+.1} dB @ {:.0} Hz, high {high:+.1} dB", eq.mid_hz());
+    }
+    if let Some(t) = args.tone { println!("Tone: {t:+.2} (-1 dark .. +1 bright)"); }
+    if args.highpass.is_some() {
+        let m = engine.master_mut();
+        if m.highpass_on() { println!("High-pass: {:.0} Hz", m.highpass_hz()); } else { println!("High-pass: off"); }
+    }
+    if args.sub_bed.is_some() {
+        let m = engine.master_mut();
+        let sub = m.sub_bed();
+        if m.highpass_on() {
+            println!("Sub bed: muted while the high-pass is on (add --highpass=off)");
+        } else {
+            println!("Sub bed: level {:.2} @ {:.0} Hz", sub.level(), sub.freq());
+        }
+    }
+    if args.mono_bass.is_some() { println!("Mono bass: below {:.0} Hz", engine.master_mut().mono_bass_hz()); }
+    if args.dyn_eq.is_some() {
+        let d = engine.master_mut().return_dyn_eq();
+        println!("Dynamic EQ: reverb/delay returns cut at {:.0} Hz above {:.0} dBFS", d.freq_hz(), d.threshold_db());
+    }
+    if args.texture.is_some() {
+        let t = engine.master_mut().texture();
+        let ((lo_hz, hi_hz), [low, mid, high]) = (t.crossovers(), t.bands());
+        println!("Texture: low {:.2}, mid {:.2}, high {:.2} (split at {lo_hz:.0} / {hi_hz:.0} Hz)", low.0, mid.0, high.0);
+    }
+    if let Some(c) = &circadian {
+        let k = c.current();
+        println!("Circadian: {} keys, now {:.2} h → intensity {:.2}, width {:.0}%", c.len(), k.at_s / 3600.0, k.intensity, 100.0 * k.width);
+    }
+    if out.binaural.is_some() { println!("Binaural: on (headphones recommended)"); }
+    if args.auto_trim { println!("Auto-trim: on"); }
+    if args.auto_gain { println!("Auto-gain: {:+.1} dB", engine.auto_gain_db()); }
+    if args.reverb_bypass { println!("Reverb: off"); }
+    if let Some(mode) = meter_mode { println!("Meters: {mode:?}"); }
+    if let Some(n) = args.render_ahead {
+        println!("Render-ahead: {n} frames ({:.0} ms)", 1000.0 * n as f32 / sr_f32);
+    }
     if let Some(d) = args.duration_sec { println!("Auto-stop after {d} seconds"); }
-    println!("Press Ctrl+C to stop…\n");
+    if let Some(s) = &sleep {
+        println!("Sleep timer: {:.0} min, fading over the last {:.0} min", s.at_s / 60.0, s.fade_s / 60.0);
+    }
+    let paused = Arc::new(AtomicBool::new(false));
+    let quit = Arc::new(AtomicBool::new(false)); // MPRIS Quit
+    let mut mpris = start_mpris(engine.scene_mut().name(), &paused, &quit);
 
-    // Provide the missing error callback and pass it in.
-    let err_fn = |e: cpal::StreamError| eprintln!("[cpal] stream error: {e}");
+    let stats = Arc::new(XrunStats::default());
+    let lost = Arc::new(AtomicBool::new(false));
+    let mut controls = Controls {
+        circadian,
+        sleep,
+        reload: Arc::clone(&reload),
+        commands,
+        paused: Arc::clone(&paused),
+        #[cfg(any(feature = "http", feature = "mqtt"))]
+        status: None,
+    };
+    if let Some(addr) = &args.http { start_http(addr, &reload, &paused, &mut controls)?; }
+    if let Some(broker) = &args.mqtt {
+        start_mqtt(broker, args.mqtt_prefix.as_deref().unwrap_or("ambientor"), &reload, &paused, &mut controls)?;
+    }
+    println!("Press Ctrl+C to stop…\n");
 
-    let stream = match sample_format {
-        cpal::SampleFormat::F32 => build_stream::<f32>(&device, &cfg, engine, gain, err_fn)?,
-        cpal::SampleFormat::I16 => build_stream::<i16>(&device, &cfg, engine, gain, err_fn)?,
-        cpal::SampleFormat::U16 => build_stream::<u16>(&device, &cfg, engine, gain, err_fn)?,
-        other => return Err(format!("unsupported device sample format: {other:?}").into()),
+    let levels = LevelReport {
+        #[cfg(any(feature = "http", feature = "mqtt"))]
+        status: controls.status.clone(),
+    };
+    let engine = Box::new(engine);
+    let render = match args.render_ahead {
+        Some(ahead) => Render::Ahead(RenderAhead::with_hook(engine, ahead, DEFAULT_BLOCK_FRAMES, move |e, dt| controls.apply(e, dt))?),
+        None => Render::Direct(engine, Box::new(controls)),
     };
+    let player = Arc::new(Mutex::new(Player { render, out, paused: Arc::clone(&paused), levels }));
+    let mut stream = Some(start_stream(&device, &cfg, sample_format, &player, &stats, &lost)?);
 
-    stream.play()?;
+    let started = Instant::now();
+    let mut last_report = started;
+    let mut last_callbacks = (0, started); // count, when it last moved
+    let mut next_attempt = started;
+    let mut last_default_check = started;
+    let mut was_paused = false;
+    let tick = if meter_display.is_some() { METER_REFRESH } else { Duration::from_millis(500) };
+    loop {
+        std::thread::sleep(tick);
+        if quit.load(Ordering::Relaxed) { break; }
+        let now_paused = paused.load(Ordering::Relaxed);
+        if now_paused != was_paused {
+            log::info(format_args!("{}", if now_paused { "paused" } else { "resumed" }));
+            was_paused = now_paused;
+        }
+        let callbacks = stats.callbacks.load(Ordering::Relaxed);
+        if callbacks != last_callbacks.0 { last_callbacks = (callbacks, Instant::now()); }
+        let stalled = stream.is_some() && last_callbacks.1.elapsed() >= STALL_AFTER;
+        if (lost.swap(false, Ordering::Relaxed) || stalled) && stream.take().is_some() {
+            log::warn(format_args!("output device lost; reconnecting every {} s", RECONNECT_RETRY.as_secs()));
+            next_attempt = Instant::now();
+        }
+        if follow_default && stream.is_some() && last_default_check.elapsed() >= DEFAULT_POLL {
+            last_default_check = Instant::now();
+            if default_output_name().is_some_and(|name| name != device_name) {
+                log::info(format_args!("default output changed; moving from {device_name}"));
+                stream = None;
+                next_attempt = Instant::now();
+            }
+        }
+        if stream.is_none() && Instant::now() >= next_attempt {
+            match reconnect(&args, &player, &reload, &mut cfg, &stats, &lost) {
+                Ok((s, name)) => {
+                    stream = Some(s);
+                    device_name = name;
+                    last_callbacks.1 = Instant::now();
+                }
+                Err(e) => {
+                    log::debug(format_args!("reconnect failed: {e}"));
+                    next_attempt = Instant::now() + RECONNECT_RETRY;
+                }
+            }
+        }
+        if let Some(w) = watcher.as_mut() { w.poll(); }
+        if let Some(m) = mpris.as_mut() { m.update(&reload.scene_name()); }
+        if let Some(d) = meter_display.as_mut() {
+            d.update();
+            d.draw(&mut std::io::stdout().lock())?;
+        }
+        if last_report.elapsed() >= XRUN_REPORT_EVERY {
+            eprintln!("[xrun] {}", stats.summary());
+            last_report = Instant::now();
+        }
+        if args.duration_sec.is_some_and(|d| started.elapsed() >= Duration::from_secs(d)) {
+            break;
+        }
+        if sleep_done.as_ref().is_some_and(|d| d.load(Ordering::Relaxed)) {
+            println!("Sleep timer: done");
+            break;
+        }
+    }
+    println!("Callback stats: {}", stats.summary());
+    Ok(())
+}
 
-    if let Some(d) = args.duration_sec {
-        std::thread::sleep(Duration::from_secs(d));
-        return Ok(());
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sleep_timer_ends_on_rendered_time() {
+        let mut engine = Engine::new(Scene::by_name("slow-drone", 48_000.0).unwrap());
+        let mut timer = SleepTimer::new(60.0, 20.0);
+        let done = timer.done_flag();
+        let mut run = |secs: f64, timer: &mut SleepTimer| for _ in 0..(secs * 100.0).round() as usize { timer.advance(0.01, &mut engine) };
+
+        run(39.9, &mut timer);
+        assert!(!timer.fading);
+        run(0.2, &mut timer);
+        assert!(timer.fading);
+        // done only once the fade and its tail have been rendered
+        run(60.0 + SLEEP_TAIL_S - 40.2, &mut timer);
+        assert!(!done.load(Ordering::Relaxed));
+        run(0.2, &mut timer);
+        assert!(done.load(Ordering::Relaxed));
     }
 
-    loop { std::thread::sleep(Duration::from_millis(500)); }
+    #[test]
+    fn set_takes_names_and_dotted_aliases() {
+        assert_eq!(parse_set("cut_base=1200"), Ok((ParamId::CutBase, 1200.0)));
+        assert_eq!(parse_set(" reverb.mix = 0.4 "), Ok((ParamId::ReverbReturn, 0.4)));
+        assert_eq!(parse_set("delay.mix=0.2"), Ok((ParamId::DelayReturn, 0.2)));
+        for bad in ["cut_base", "nope=1", "reverb.size=1", "cut_base=loud", "cut_base=NaN", "cut_base=inf"] {
+            assert!(parse_set(bad).is_err(), "{bad}");
+        }
+
+        let mut engine = Engine::new(Scene::by_name("slow-drone", 48_000.0).unwrap());
+        apply_sets(&mut engine, &["reverb.mix=0.4".into(), "cut_base=900".into()]).unwrap();
+        assert_eq!(engine.param(ParamId::ReverbReturn), Some(0.4));
+        assert_eq!(engine.param(ParamId::CutBase), Some(900.0));
+        // a parameter this scene doesn't have
+        let err = apply_sets(&mut engine, &["beat_hz=6".into()]).unwrap_err();
+        assert!(err.to_string().contains("slow-drone has no parameter beat_hz"), "{err}");
+    }
 }
@@ -0,0 +1,177 @@
+//! Live terminal meters for the player (`--meters=vu|spectrum`).
+//!
+//! The audio callback only copies output frames into a lock-free ring
+//! ([`MeterTap`]); the main thread drains it a few times per second, runs the
+//! analysis (peak/RMS per side, [`SpectrumAnalyzer`] bands) and redraws its
+//! block of lines in place with ANSI cursor moves.
+
+use ambientor_engine::fft::{SpectrumAnalyzer, SPECTRUM_FFT_SIZE};
+use ambientor_engine::stream::{self, RingConsumer, RingProducer};
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// How often the display is redrawn.
+pub const METER_REFRESH: Duration = Duration::from_millis(125);
+
+/// Audio the ring holds between redraws (seconds); the rest is dropped.
+const RING_S: f32 = 0.5;
+/// Floor of both displays (dBFS).
+const FLOOR_DB: f32 = -60.0;
+/// Width of a VU bar (characters).
+const VU_WIDTH: usize = 48;
+/// Level a VU bar loses per redraw once the signal drops (dB).
+const VU_FALL_DB: f32 = 3.0;
+/// Spectrum columns (log-spaced bands) and rows.
+const SPECTRUM_BANDS: usize = 48;
+const SPECTRUM_ROWS: usize = 8;
+/// Frequency span of the spectrum display (Hz).
+const SPECTRUM_HZ: (f32, f32) = (30.0, 16_000.0);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MeterMode {
+    /// Peak and RMS bars per side.
+    Vu,
+    /// Coarse log-frequency spectrum plus a peak readout.
+    Spectrum,
+}
+
+impl MeterMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "vu" => Some(Self::Vu),
+            "spectrum" => Some(Self::Spectrum),
+            _ => None,
+        }
+    }
+}
+
+/// Audio-thread side: hands output frames to the display.
+pub struct MeterTap {
+    ring: RingProducer,
+}
+
+impl MeterTap {
+    /// Queue one output frame; dropped if the display has fallen behind.
+    #[inline]
+    pub fn push(&mut self, l: f32, r: f32) {
+        self.ring.push_slice(&[l, r]);
+    }
+}
+
+/// Main-thread side: analysis and drawing.
+pub struct MeterDisplay {
+    mode: MeterMode,
+    sr: f32,
+    ring: RingConsumer,
+    buf: Vec<f32>,
+    spectrum: SpectrumAnalyzer,
+    rms_db: [f32; 2],
+    peak_db: [f32; 2],
+    clipped: bool,
+    lines: usize, // lines drawn last time, to move back over
+}
+
+/// A connected tap/display pair for output at `sr`.
+pub fn meters(mode: MeterMode, sr: f32) -> (MeterTap, MeterDisplay) {
+    let cap = ((RING_S * sr) as usize).max(1024) * 2;
+    let (tx, rx) = stream::ring(cap);
+    let display = MeterDisplay {
+        mode,
+        sr,
+        ring: rx,
+        buf: vec![0.0; cap],
+        spectrum: SpectrumAnalyzer::new(SPECTRUM_FFT_SIZE),
+        rms_db: [FLOOR_DB; 2],
+        peak_db: [FLOOR_DB; 2],
+        clipped: false,
+        lines: 0,
+    };
+    (MeterTap { ring: tx }, display)
+}
+
+#[inline]
+fn to_db(x: f32) -> f32 { (20.0 * x.max(1e-6).log10()).max(FLOOR_DB) }
+
+/// `level_db` on the display scale 0..1.
+#[inline]
+fn scale(level_db: f32) -> f32 { (1.0 - level_db / FLOOR_DB).clamp(0.0, 1.0) }
+
+impl MeterDisplay {
+    /// Drain the frames queued since the last call and update the readings.
+    pub fn update(&mut self) {
+        let n = self.ring.pop_slice(&mut self.buf) / 2;
+        if n == 0 { return; }
+        let (mut sum, mut peak) = ([0.0f32; 2], [0.0f32; 2]);
+        for f in self.buf[..2 * n].chunks_exact(2) {
+            for c in 0..2 {
+                sum[c] += f[c] * f[c];
+                peak[c] = peak[c].max(f[c].abs());
+            }
+            self.spectrum.push(0.5 * (f[0] + f[1]));
+        }
+        for c in 0..2 {
+            // bars jump up at once and fall back at a steady rate
+            self.rms_db[c] = to_db((sum[c] / n as f32).sqrt()).max(self.rms_db[c] - VU_FALL_DB);
+            self.peak_db[c] = to_db(peak[c]).max(self.peak_db[c] - VU_FALL_DB);
+        }
+        self.clipped = peak.iter().any(|&p| p >= 1.0);
+        if self.mode == MeterMode::Spectrum { self.spectrum.update(); }
+    }
+
+    /// Redraw over the previous drawing.
+    pub fn draw(&mut self, out: &mut impl Write) -> io::Result<()> {
+        let mut text = String::new();
+        match self.mode {
+            MeterMode::Vu => self.draw_vu(&mut text),
+            MeterMode::Spectrum => self.draw_spectrum(&mut text),
+        }
+        if self.lines > 0 { write!(out, "\x1b[{}A", self.lines)?; }
+        for line in text.lines() { writeln!(out, "\x1b[2K{line}")?; }
+        self.lines = text.lines().count();
+        out.flush()
+    }
+
+    fn draw_vu(&self, text: &mut String) {
+        for (c, side) in ["L", "R"].into_iter().enumerate() {
+            let rms = (scale(self.rms_db[c]) * VU_WIDTH as f32) as usize;
+            let peak = ((scale(self.peak_db[c]) * VU_WIDTH as f32) as usize).min(VU_WIDTH - 1);
+            let bar: String = (0..VU_WIDTH)
+                .map(|i| if i < rms { '#' } else if i == peak { '|' } else { '.' })
+                .collect();
+            text.push_str(&format!(
+                "{side} [{bar}] rms {:6.1} dB  peak {:6.1} dB{}\n",
+                self.rms_db[c],
+                self.peak_db[c],
+                if self.clipped { "  CLIP" } else { "" },
+            ));
+        }
+    }
+
+    fn draw_spectrum(&self, text: &mut String) {
+        let mags = self.spectrum.magnitudes();
+        let bin_hz = self.spectrum.bin_hz(self.sr);
+        let (lo, hi) = SPECTRUM_HZ;
+        let levels: Vec<usize> = (0..SPECTRUM_BANDS)
+            .map(|b| {
+                let f = |k: usize| lo * (hi / lo).powf(k as f32 / SPECTRUM_BANDS as f32);
+                let a = ((f(b) / bin_hz) as usize).min(mags.len() - 1);
+                let z = ((f(b + 1) / bin_hz).ceil() as usize).clamp(a + 1, mags.len());
+                let m = mags[a..z].iter().fold(0.0f32, |x, &y| x.max(y));
+                (scale(to_db(m)) * SPECTRUM_ROWS as f32).round() as usize
+            })
+            .collect();
+        for row in (0..SPECTRUM_ROWS).rev() {
+            let line: String = levels.iter().map(|&l| if l > row { '#' } else { ' ' }).collect();
+            text.push_str(&format!("|{line}|\n"));
+        }
+        let label = format!("{lo:.0} Hz");
+        let end = format!("{:.0} kHz", hi / 1000.0);
+        text.push_str(&format!(
+            " {label}{:>w$}  peak {:6.1} dB{}\n",
+            end,
+            self.peak_db[0].max(self.peak_db[1]),
+            if self.clipped { "  CLIP" } else { "" },
+            w = SPECTRUM_BANDS - label.len(),
+        ));
+    }
+}
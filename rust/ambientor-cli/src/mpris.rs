@@ -0,0 +1,184 @@
+//! MPRIS media-player integration (Linux, `--features mpris`).
+//!
+//! Registers the running player on the session bus as
+//! `org.mpris.MediaPlayer2.ambientor`, so desktop media keys and the GNOME/KDE
+//! media applets can pause and resume it and show the current scene. The
+//! player has no tracks: next/previous/seek are reported as unsupported, and
+//! Stop pauses. zbus serves the bus on its own thread; the audio side only
+//! reads the shared `paused` flag. Scene switches and pauses from elsewhere
+//! (HTTP, MQTT, scene files) reach the applets through [`Mpris::update`],
+//! which the main loop calls on every tick.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use zbus::blocking::{Connection, ConnectionBuilder};
+use zbus::zvariant::{ObjectPath, OwnedValue, Value};
+use zbus::{dbus_interface, SignalContext};
+
+const BUS_NAME: &str = "org.mpris.MediaPlayer2.ambientor";
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+const PLAYER_IFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+/// `org.mpris.MediaPlayer2`: identity, and Quit.
+struct Root {
+    quit: Arc<AtomicBool>,
+}
+
+#[dbus_interface(name = "org.mpris.MediaPlayer2")]
+impl Root {
+    fn raise(&self) {}
+
+    fn quit(&self) { self.quit.store(true, Ordering::Relaxed); }
+
+    #[dbus_interface(property)]
+    fn identity(&self) -> &str { "Ambientor" }
+
+    #[dbus_interface(property)]
+    fn can_quit(&self) -> bool { true }
+
+    #[dbus_interface(property)]
+    fn can_raise(&self) -> bool { false }
+
+    #[dbus_interface(property)]
+    fn has_track_list(&self) -> bool { false }
+
+    #[dbus_interface(property)]
+    fn supported_uri_schemes(&self) -> Vec<String> { Vec::new() }
+
+    #[dbus_interface(property)]
+    fn supported_mime_types(&self) -> Vec<String> { Vec::new() }
+}
+
+/// `org.mpris.MediaPlayer2.Player`: play/pause and the scene as the "track".
+struct Player {
+    scene: Arc<Mutex<String>>,
+    paused: Arc<AtomicBool>,
+}
+
+fn playback_status(paused: bool) -> &'static str { if paused { "Paused" } else { "Playing" } }
+
+fn metadata(scene: &str) -> HashMap<String, OwnedValue> {
+    let track = ObjectPath::from_static_str_unchecked("/org/ambientor/scene");
+    HashMap::from([
+        ("mpris:trackid".to_string(), Value::from(track).into()),
+        ("xesam:title".to_string(), Value::from(scene).into()),
+        ("xesam:artist".to_string(), Value::from(vec!["Ambientor"]).into()),
+    ])
+}
+
+impl Player {
+    async fn set_paused(&self, paused: bool, ctxt: &SignalContext<'_>) {
+        if self.paused.swap(paused, Ordering::Relaxed) != paused {
+            let _ = self.playback_status_changed(ctxt).await;
+        }
+    }
+}
+
+#[dbus_interface(name = "org.mpris.MediaPlayer2.Player")]
+impl Player {
+    async fn play(&self, #[zbus(signal_context)] ctxt: SignalContext<'_>) { self.set_paused(false, &ctxt).await; }
+
+    async fn pause(&self, #[zbus(signal_context)] ctxt: SignalContext<'_>) { self.set_paused(true, &ctxt).await; }
+
+    async fn stop(&self, #[zbus(signal_context)] ctxt: SignalContext<'_>) { self.set_paused(true, &ctxt).await; }
+
+    async fn play_pause(&self, #[zbus(signal_context)] ctxt: SignalContext<'_>) {
+        self.set_paused(!self.paused.load(Ordering::Relaxed), &ctxt).await;
+    }
+
+    fn next(&self) {}
+
+    fn previous(&self) {}
+
+    fn seek(&self, _offset_us: i64) {}
+
+    fn set_position(&self, _track: ObjectPath<'_>, _position_us: i64) {}
+
+    fn open_uri(&self, _uri: &str) {}
+
+    #[dbus_interface(property)]
+    fn playback_status(&self) -> &str { playback_status(self.paused.load(Ordering::Relaxed)) }
+
+    #[dbus_interface(property)]
+    fn metadata(&self) -> HashMap<String, OwnedValue> {
+        metadata(&self.scene.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    #[dbus_interface(property)]
+    fn rate(&self) -> f64 { 1.0 }
+
+    #[dbus_interface(property)]
+    fn minimum_rate(&self) -> f64 { 1.0 }
+
+    #[dbus_interface(property)]
+    fn maximum_rate(&self) -> f64 { 1.0 }
+
+    #[dbus_interface(property)]
+    fn volume(&self) -> f64 { 1.0 }
+
+    #[dbus_interface(property)]
+    fn position(&self) -> i64 { 0 }
+
+    #[dbus_interface(property)]
+    fn can_go_next(&self) -> bool { false }
+
+    #[dbus_interface(property)]
+    fn can_go_previous(&self) -> bool { false }
+
+    #[dbus_interface(property)]
+    fn can_play(&self) -> bool { true }
+
+    #[dbus_interface(property)]
+    fn can_pause(&self) -> bool { true }
+
+    #[dbus_interface(property)]
+    fn can_seek(&self) -> bool { false }
+
+    #[dbus_interface(property)]
+    fn can_control(&self) -> bool { true }
+}
+
+/// The player as published on the session bus; unpublished when dropped.
+pub struct Mpris {
+    conn: Connection,
+    scene: Arc<Mutex<String>>,
+    paused: Arc<AtomicBool>,
+    reported: bool, // playback status last announced
+}
+
+impl Mpris {
+    /// Announce a scene switch or a pause that didn't come through MPRIS;
+    /// a no-op when nothing changed.
+    pub fn update(&mut self, scene: &str) {
+        let mut changed: HashMap<&str, Value<'_>> = HashMap::new();
+        let paused = self.paused.load(Ordering::Relaxed);
+        if paused != self.reported {
+            self.reported = paused;
+            changed.insert("PlaybackStatus", Value::from(playback_status(paused)));
+        }
+        {
+            let mut cur = self.scene.lock().unwrap_or_else(|e| e.into_inner());
+            if *cur != scene {
+                scene.clone_into(&mut cur);
+                changed.insert("Metadata", Value::from(metadata(scene)));
+            }
+        }
+        if changed.is_empty() { return; }
+        let body = (PLAYER_IFACE, changed, Vec::<&str>::new());
+        let _ = self.conn.emit_signal(None::<()>, OBJECT_PATH, "org.freedesktop.DBus.Properties", "PropertiesChanged", &body);
+    }
+}
+
+/// Publish the player on the session bus until the returned [`Mpris`] drops.
+/// Media keys toggle `paused`; Quit sets `quit`.
+pub fn serve(scene: &str, paused: Arc<AtomicBool>, quit: Arc<AtomicBool>) -> zbus::Result<Mpris> {
+    let shared = Arc::new(Mutex::new(scene.to_string()));
+    let conn = ConnectionBuilder::session()?
+        .name(BUS_NAME)?
+        .serve_at(OBJECT_PATH, Root { quit })?
+        .serve_at(OBJECT_PATH, Player { scene: Arc::clone(&shared), paused: Arc::clone(&paused) })?
+        .build()?;
+    let reported = paused.load(Ordering::Relaxed);
+    Ok(Mpris { conn, scene: shared, paused, reported })
+}
@@ -0,0 +1,347 @@
+//! MQTT control client (`--mqtt=HOST[:PORT]`, `--features mqtt`).
+//!
+//! A minimal MQTT 3.1.1 client on std sockets (QoS 0, clean session) for
+//! smart-home brokers such as Mosquitto. With the default `--mqtt-prefix` of
+//! `ambientor` it
+//!
+//! - subscribes to `ambientor/set/#`:
+//!   - `ambientor/set/NAME` with a number sets a parameter (`ambientor/set/cut_base` ← `1200`)
+//!   - `ambientor/set/scene` with a scene name switches scene
+//!   - `ambientor/set/transport` with `play`, `pause` or `toggle`
+//! - publishes every [`TELEMETRY_EVERY`]:
+//!   - `ambientor/status`: scene, state, parameters and meters as JSON (as HTTP `GET /status`)
+//!   - `ambientor/meters/peak_db` and `ambientor/meters/rms_db`: output level over the last second
+//!   - `ambientor/meters/loudness_db`: output RMS averaged over ~30 s, the
+//!     long-term level the scenes are calibrated to
+//! - keeps `ambientor/online` (retained) at `online`, and leaves `offline` as
+//!   its last will when the player goes away.
+//!
+//! A lost broker connection is retried every [`RETRY`] for as long as the
+//! player runs.
+
+use crate::remote::Remote;
+use ambientor_engine::log;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+const DEFAULT_PORT: u16 = 1883;
+/// Keep-alive the broker is told; pings go out at half of it.
+const KEEP_ALIVE_S: u16 = 60;
+/// Time between status publishes.
+pub const TELEMETRY_EVERY: Duration = Duration::from_secs(5);
+/// Time between attempts to reach the broker.
+pub const RETRY: Duration = Duration::from_secs(5);
+/// How long a read waits before the loop goes on to publish or ping.
+const POLL: Duration = Duration::from_millis(200);
+/// Longest packet accepted from the broker (bytes).
+const MAX_PACKET: usize = 16 * 1024;
+
+/// Connect to `broker` and serve topics under `prefix` on a background
+/// thread for the rest of the process.
+pub fn spawn(broker: &str, prefix: &str, mut remote: Remote) -> io::Result<()> {
+    let addr = if broker.contains(':') { broker.to_string() } else { format!("{broker}:{DEFAULT_PORT}") };
+    let prefix = prefix.trim_end_matches('/').to_string();
+    std::thread::Builder::new().name("ambientor-mqtt".into()).spawn(move || {
+        let mut logged = false; // one warning per outage
+        loop {
+            if let Err(e) = session(&addr, &prefix, &mut remote, &mut logged) {
+                if !logged {
+                    log::warn(format_args!("mqtt {addr}: {e} (retrying every {} s)", RETRY.as_secs()));
+                    logged = true;
+                }
+            }
+            std::thread::sleep(RETRY);
+        }
+    })?;
+    Ok(())
+}
+
+/// One broker connection, until it fails.
+fn session(addr: &str, prefix: &str, remote: &mut Remote, logged: &mut bool) -> io::Result<()> {
+    let mut conn = TcpStream::connect(addr)?;
+    conn.set_nodelay(true)?;
+    let online = format!("{prefix}/online");
+    connect(&mut conn, &format!("ambientor-{}", std::process::id()), &online)?;
+    subscribe(&mut conn, &format!("{prefix}/set/#"))?;
+    publish(&mut conn, &online, b"online", true)?;
+    log::info(format_args!("mqtt: connected to {addr} as {prefix}/"));
+    *logged = false;
+
+    let (mut last_telemetry, mut last_sent) = (Instant::now() - TELEMETRY_EVERY, Instant::now());
+    loop {
+        if let Some((topic, payload)) = next_publish(&mut conn)? {
+            if let Err(e) = on_message(remote, prefix, &topic, &payload) {
+                log::warn(format_args!("mqtt {topic}: {e}"));
+            }
+        }
+        if last_telemetry.elapsed() >= TELEMETRY_EVERY {
+            for (topic, payload) in telemetry(remote, prefix) {
+                publish(&mut conn, &topic, payload.as_bytes(), false)?;
+            }
+            last_telemetry = Instant::now();
+            last_sent = last_telemetry;
+        }
+        if last_sent.elapsed() >= Duration::from_secs(u64::from(KEEP_ALIVE_S / 2)) {
+            conn.write_all(&[0xc0, 0x00])?; // PINGREQ
+            last_sent = Instant::now();
+        }
+    }
+}
+
+/// Act on a message the broker passed on; topics outside `{prefix}/set/` are ignored.
+fn on_message(remote: &mut Remote, prefix: &str, topic: &str, payload: &[u8]) -> Result<(), String> {
+    match topic.strip_prefix(prefix).and_then(|t| t.strip_prefix("/set/")) {
+        Some(what) => apply(remote, what, String::from_utf8_lossy(payload).trim()),
+        None => Ok(()),
+    }
+}
+
+/// The `(topic, payload)` pairs published every [`TELEMETRY_EVERY`].
+fn telemetry(remote: &Remote, prefix: &str) -> [(String, String); 4] {
+    let s = remote.status();
+    let (peak, rms) = s.levels_db();
+    [
+        (format!("{prefix}/status"), remote.status_json()),
+        (format!("{prefix}/meters/peak_db"), format!("{peak:.1}")),
+        (format!("{prefix}/meters/rms_db"), format!("{rms:.1}")),
+        (format!("{prefix}/meters/loudness_db"), format!("{:.1}", s.loudness_db())),
+    ]
+}
+
+/// Act on a message to `{prefix}/set/{what}`.
+fn apply(remote: &mut Remote, what: &str, payload: &str) -> Result<(), String> {
+    match what {
+        "scene" => remote.set_scene(payload),
+        "transport" => {
+            match payload {
+                "play" => remote.set_paused(false),
+                "pause" => remote.set_paused(true),
+                "toggle" => remote.set_paused(!remote.paused()),
+                _ => return Err("expected play, pause or toggle".into()),
+            }
+            Ok(())
+        }
+        name => {
+            let v: f32 = payload.parse().map_err(|_| format!("expected a number, got {payload:?}"))?;
+            remote.set_param(name, v)
+        }
+    }
+}
+
+// ---- MQTT 3.1.1 packets ----
+
+fn put_str(buf: &mut Vec<u8>, s: &[u8]) {
+    buf.extend_from_slice(&(s.len() as u16).to_be_bytes());
+    buf.extend_from_slice(s);
+}
+
+/// Write a packet: fixed header `kind`, remaining length, `body`.
+fn send(conn: &mut impl Write, kind: u8, body: &[u8]) -> io::Result<()> {
+    let mut pkt = vec![kind];
+    let mut len = body.len();
+    loop {
+        let byte = (len % 128) as u8;
+        len /= 128;
+        pkt.push(if len > 0 { byte | 0x80 } else { byte });
+        if len == 0 { break; }
+    }
+    pkt.extend_from_slice(body);
+    conn.write_all(&pkt)
+}
+
+/// CONNECT with a retained `offline` last will on `will_topic`; waits for CONNACK.
+fn connect(conn: &mut TcpStream, client_id: &str, will_topic: &str) -> io::Result<()> {
+    let mut body = Vec::new();
+    put_str(&mut body, b"MQTT");
+    body.push(4); // protocol level 3.1.1
+    body.push(0x02 | 0x04 | 0x20); // clean session, will, will retain
+    body.extend_from_slice(&KEEP_ALIVE_S.to_be_bytes());
+    put_str(&mut body, client_id.as_bytes());
+    put_str(&mut body, will_topic.as_bytes());
+    put_str(&mut body, b"offline");
+    send(conn, 0x10, &body)?;
+
+    conn.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let (kind, ack) = read_packet(conn)?;
+    if kind >> 4 != 2 || ack.len() < 2 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "expected CONNACK"));
+    }
+    if ack[1] != 0 {
+        return Err(io::Error::new(io::ErrorKind::ConnectionRefused, format!("broker refused the connection (code {})", ack[1])));
+    }
+    Ok(())
+}
+
+fn subscribe(conn: &mut impl Write, filter: &str) -> io::Result<()> {
+    let mut body = 1u16.to_be_bytes().to_vec(); // packet id
+    put_str(&mut body, filter.as_bytes());
+    body.push(0); // QoS 0
+    send(conn, 0x82, &body)
+}
+
+fn publish(conn: &mut impl Write, topic: &str, payload: &[u8], retain: bool) -> io::Result<()> {
+    let mut body = Vec::with_capacity(topic.len() + payload.len() + 2);
+    put_str(&mut body, topic.as_bytes());
+    body.extend_from_slice(payload);
+    send(conn, 0x30 | u8::from(retain), &body)
+}
+
+/// Read one whole packet: `(fixed header, body)`.
+fn read_packet(conn: &mut impl Read) -> io::Result<(u8, Vec<u8>)> {
+    let mut b = [0u8; 1];
+    conn.read_exact(&mut b)?;
+    let kind = b[0];
+    let (mut len, mut shift) = (0usize, 0);
+    loop {
+        conn.read_exact(&mut b)?;
+        len |= usize::from(b[0] & 0x7f) << shift;
+        if b[0] & 0x80 == 0 { break; }
+        shift += 7;
+        if shift > 21 { return Err(io::Error::new(io::ErrorKind::InvalidData, "bad remaining length")); }
+    }
+    if len > MAX_PACKET { return Err(io::Error::new(io::ErrorKind::InvalidData, "packet too large")); }
+    let mut body = vec![0; len];
+    conn.read_exact(&mut body)?;
+    Ok((kind, body))
+}
+
+/// Wait up to [`POLL`] for a packet; returns an incoming PUBLISH's topic and
+/// payload, `None` for anything else (acks, pings) or nothing.
+fn next_publish(conn: &mut TcpStream) -> io::Result<Option<(String, Vec<u8>)>> {
+    conn.set_read_timeout(Some(POLL))?;
+    let mut b = [0u8; 1];
+    match conn.peek(&mut b) {
+        Ok(0) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "broker closed the connection")),
+        Ok(_) => {}
+        Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    // a packet has started: give the rest time to arrive
+    conn.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let (kind, body) = read_packet(conn)?;
+    let Some(msg) = parse_publish(kind, &body) else { return Ok(None); };
+    if let Some(id) = msg.ack { send(conn, 0x40, &id)?; } // PUBACK
+    Ok(Some((msg.topic, msg.payload)))
+}
+
+struct Publish {
+    topic: String,
+    payload: Vec<u8>,
+    ack: Option<[u8; 2]>, // packet id of a QoS 1 message
+}
+
+/// Decode a PUBLISH packet; `None` for any other packet or a malformed one.
+fn parse_publish(kind: u8, body: &[u8]) -> Option<Publish> {
+    if kind >> 4 != 3 || body.len() < 2 { return None; }
+    let topic_len = usize::from(u16::from_be_bytes([body[0], body[1]]));
+    let topic = body.get(2..2 + topic_len)?;
+    let mut at = 2 + topic_len;
+    let mut ack = None;
+    let qos = (kind >> 1) & 0x03;
+    if qos > 0 {
+        let id = body.get(at..at + 2)?;
+        if qos == 1 { ack = Some([id[0], id[1]]); }
+        at += 2;
+    }
+    Some(Publish { topic: String::from_utf8_lossy(topic).into_owned(), payload: body[at..].to_vec(), ack })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ambientor_engine::command::Command;
+    use ambientor_engine::ParamId;
+    use std::sync::atomic::Ordering;
+
+    /// `send` then `read_packet` back.
+    fn round_trip(kind: u8, body: &[u8]) -> (u8, Vec<u8>) {
+        let mut wire = Vec::new();
+        send(&mut wire, kind, body).unwrap();
+        read_packet(&mut wire.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn packets_round_trip() {
+        // remaining length: one byte up to 127, two up to 16383
+        for len in [0, 1, 127, 128, 300, MAX_PACKET] {
+            let body: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            assert_eq!(round_trip(0x30, &body), (0x30, body), "{len} bytes");
+        }
+        let mut wire = Vec::new();
+        send(&mut wire, 0x30, &[0; 200]).unwrap();
+        assert_eq!(&wire[..3], &[0x30, 0xc8, 0x01]);
+
+        send(&mut wire, 0x30, &vec![0; MAX_PACKET + 1]).unwrap();
+        assert!(read_packet(&mut &wire[203..]).is_err(), "oversized packet accepted");
+        assert!(read_packet(&mut &[0x30, 0xff, 0xff, 0xff, 0xff, 0x01][..]).is_err(), "5-byte length accepted");
+        assert!(read_packet(&mut &[0x30, 0x05, 1, 2][..]).is_err(), "short packet accepted");
+    }
+
+    #[test]
+    fn parses_publish_packets() {
+        let mut wire = Vec::new();
+        publish(&mut wire, "ambientor/set/cut_base", b"1200", false).unwrap();
+        let (kind, body) = read_packet(&mut wire.as_slice()).unwrap();
+        let msg = parse_publish(kind, &body).unwrap();
+        assert_eq!((msg.topic.as_str(), msg.payload.as_slice(), msg.ack), ("ambientor/set/cut_base", &b"1200"[..], None));
+
+        // QoS 1 carries a packet id to acknowledge, QoS 2 one we skip
+        let body = [&[0, 1, b't'][..], &[0x12, 0x34], b"x"].concat();
+        let msg = parse_publish(0x32, &body).unwrap();
+        assert_eq!((msg.topic.as_str(), msg.payload.as_slice(), msg.ack), ("t", &b"x"[..], Some([0x12, 0x34])));
+        assert_eq!(parse_publish(0x34, &body).unwrap().ack, None);
+
+        // not a PUBLISH, or cut short
+        assert!(parse_publish(0x90, &body).is_none());
+        assert!(parse_publish(0x30, &[0]).is_none());
+        assert!(parse_publish(0x30, &[0, 5, b't']).is_none());
+        assert!(parse_publish(0x32, &[0, 1, b't', 0x12]).is_none());
+    }
+
+    #[test]
+    fn acts_on_set_topics() {
+        let (mut remote, mut rx, reload) = crate::remote::tests::player();
+        on_message(&mut remote, "home/amb", "home/amb/set/cut_base", b" 800\n").unwrap();
+        assert_eq!(rx.pop(), Some(Command::SetHostParam { id: ParamId::CutBase, value: 800.0 }));
+        on_message(&mut remote, "home/amb", "home/amb/set/transport", b"toggle").unwrap();
+        assert!(remote.paused());
+        on_message(&mut remote, "home/amb", "home/amb/set/transport", b"play").unwrap();
+        assert!(!remote.paused());
+        on_message(&mut remote, "home/amb", "home/amb/set/scene", b"risset").unwrap();
+        assert!(reload.pending.swap(false, Ordering::AcqRel));
+
+        // other topics are not ours
+        for topic in ["home/amb/status", "home/ambx/set/cut_base", "other/set/cut_base", "home/amb/set"] {
+            on_message(&mut remote, "home/amb", topic, b"1").unwrap();
+        }
+        assert_eq!(rx.pop(), None);
+    }
+
+    #[test]
+    fn rejects_bad_payloads() {
+        let (mut remote, mut rx, reload) = crate::remote::tests::player();
+        for (topic, payload, msg) in [
+            ("amb/set/cut_base", &b"loud"[..], "expected a number, got \"loud\""),
+            ("amb/set/cut_base", b"inf", "cut_base: value must be a number"),
+            ("amb/set/no_such_param", b"1", "unknown parameter: no_such_param"),
+            ("amb/set/transport", b"stop", "expected play, pause or toggle"),
+        ] {
+            assert_eq!(on_message(&mut remote, "amb", topic, payload), Err(msg.to_string()));
+        }
+        let e = on_message(&mut remote, "amb", "amb/set/scene", b"no-such-scene").unwrap_err();
+        assert!(e.starts_with("unknown scene: no-such-scene"), "{e}");
+        assert_eq!(rx.pop(), None);
+        assert!(!reload.pending.load(Ordering::Acquire) && !remote.paused());
+    }
+
+    #[test]
+    fn publishes_meters_and_loudness() {
+        let (remote, _rx, _reload) = crate::remote::tests::player();
+        remote.status().publish_levels(0.5, 0.1);
+        let t = telemetry(&remote, "amb");
+        assert!(t[0].0 == "amb/status" && t[0].1.contains(r#""loudness_db":-20.0"#), "{}", t[0].1);
+        let meters: Vec<(&str, &str)> = t[1..].iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        assert_eq!(meters, [("amb/meters/peak_db", "-6.0"), ("amb/meters/rms_db", "-20.0"), ("amb/meters/loudness_db", "-20.0")]);
+    }
+}
@@ -0,0 +1,191 @@
+//! Plumbing shared by the network front-ends (`--http`, `--mqtt`).
+//!
+//! A front-end runs on its own thread with a [`Remote`]: parameter changes go
+//! to the audio callback through a [`command_queue`](ambientor_engine::command::command_queue),
+//! scene switches through the same handoff as scene-file hot reload, and
+//! pause through the flag media keys use. What it reports back (parameters,
+//! scene, output levels) the callback publishes into a [`Status`] of atomics
+//! once per buffer, so reading it never touches the engine.
+
+use crate::SceneReload;
+use ambientor_engine::command::{Command, CommandSender};
+use ambientor_engine::graph::Engine;
+use ambientor_engine::params::PARAM_COUNT;
+use ambientor_engine::scenes::{Scene, SCENE_NAMES};
+use ambientor_engine::ParamId;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Levels reported before the first second of audio, and for silence (dBFS).
+const FLOOR_DB: f32 = -120.0;
+/// Time constant of the loudness reading (seconds of level reports).
+pub const LOUDNESS_S: f32 = 30.0;
+
+/// What the audio callback last published.
+pub struct Status {
+    params: [AtomicU32; PARAM_COUNT], // f32 bits; NaN: the scene doesn't have it
+    scene: AtomicUsize,               // index into SCENE_NAMES, usize::MAX for a scene file
+    peak_db: AtomicU32,
+    rms_db: AtomicU32,
+    loudness_ms: AtomicU32, // f32 bits: mean square averaged over ~LOUDNESS_S; NaN before the first report
+}
+
+impl Status {
+    pub fn new() -> Self {
+        Self {
+            params: core::array::from_fn(|_| AtomicU32::new(f32::NAN.to_bits())),
+            scene: AtomicUsize::new(usize::MAX),
+            peak_db: AtomicU32::new(FLOOR_DB.to_bits()),
+            rms_db: AtomicU32::new(FLOOR_DB.to_bits()),
+            loudness_ms: AtomicU32::new(f32::NAN.to_bits()),
+        }
+    }
+
+    /// Audio thread: the engine's current parameters and scene.
+    pub fn publish(&self, engine: &mut Engine<Scene>) {
+        for id in ParamId::ALL {
+            let v = engine.param(id).unwrap_or(f32::NAN);
+            self.params[id as usize].store(v.to_bits(), Ordering::Relaxed);
+        }
+        let name = engine.scene_mut().name();
+        let scene = SCENE_NAMES.iter().position(|n| *n == name).unwrap_or(usize::MAX);
+        self.scene.store(scene, Ordering::Relaxed);
+    }
+
+    /// Audio thread: output peak and RMS over the last second (linear).
+    pub fn publish_levels(&self, peak: f32, rms: f32) {
+        self.peak_db.store(level_db(peak).to_bits(), Ordering::Relaxed);
+        self.rms_db.store(level_db(rms).to_bits(), Ordering::Relaxed);
+        let ms = f32::from_bits(self.loudness_ms.load(Ordering::Relaxed));
+        let ms = if ms.is_nan() { rms * rms } else { ms + (rms * rms - ms) / LOUDNESS_S };
+        self.loudness_ms.store(ms.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn param(&self, id: ParamId) -> Option<f32> {
+        Some(f32::from_bits(self.params[id as usize].load(Ordering::Relaxed))).filter(|v| !v.is_nan())
+    }
+
+    /// Built-in scene playing, `None` for a scene file.
+    pub fn scene(&self) -> Option<&'static str> { SCENE_NAMES.get(self.scene.load(Ordering::Relaxed)).copied() }
+
+    /// Output `(peak, rms)` over the last second, dBFS.
+    pub fn levels_db(&self) -> (f32, f32) {
+        let load = |a: &AtomicU32| f32::from_bits(a.load(Ordering::Relaxed));
+        (load(&self.peak_db), load(&self.rms_db))
+    }
+
+    /// Output RMS averaged over about [`LOUDNESS_S`], dBFS: the long-term
+    /// level the scenes' loudness calibrations are measured as.
+    pub fn loudness_db(&self) -> f32 {
+        let ms = f32::from_bits(self.loudness_ms.load(Ordering::Relaxed));
+        if ms.is_nan() { FLOOR_DB } else { level_db(ms.sqrt()) }
+    }
+}
+
+fn level_db(x: f32) -> f32 { if x > 0.0 { (20.0 * x.log10()).max(FLOOR_DB) } else { FLOOR_DB } }
+
+/// One front-end's handle on the player.
+pub struct Remote {
+    status: Arc<Status>,
+    commands: CommandSender,
+    reload: Arc<SceneReload>,
+    paused: Arc<AtomicBool>,
+}
+
+impl Remote {
+    pub fn new(status: Arc<Status>, commands: CommandSender, reload: Arc<SceneReload>, paused: Arc<AtomicBool>) -> Self {
+        Self { status, commands, reload, paused }
+    }
+
+    #[inline] pub fn status(&self) -> &Status { &self.status }
+
+    /// Set parameter `name` (see `--describe-scene`) to `value`.
+    pub fn set_param(&mut self, name: &str, value: f32) -> Result<(), String> {
+        let id = ParamId::parse(name).ok_or_else(|| format!("unknown parameter: {name}"))?;
+        if !value.is_finite() { return Err(format!("{name}: value must be a number")); }
+        if self.status.param(id).is_none() { return Err(format!("the scene has no parameter {name}")); }
+        if !self.commands.push(Command::SetHostParam { id, value }) { return Err("command queue full, try again".into()); }
+        Ok(())
+    }
+
+    /// Switch to built-in scene `name`, built for the stream's current rate.
+    pub fn set_scene(&mut self, name: &str) -> Result<(), String> {
+        let scene = Scene::by_name(name, self.reload.sample_rate())
+            .ok_or_else(|| format!("unknown scene: {name} (one of {})", SCENE_NAMES.join(", ")))?;
+        self.reload.publish(scene);
+        Ok(())
+    }
+
+    #[inline] pub fn paused(&self) -> bool { self.paused.load(Ordering::Relaxed) }
+    #[inline] pub fn set_paused(&self, paused: bool) { self.paused.store(paused, Ordering::Relaxed); }
+
+    /// Scene, transport, parameters and levels as a JSON object.
+    pub fn status_json(&self) -> String {
+        let s = &self.status;
+        let (peak, rms) = s.levels_db();
+        format!(
+            r#"{{"scene":{},"state":"{}","params":{},"meters":{{"peak_db":{peak:.1},"rms_db":{rms:.1},"loudness_db":{:.1}}}}}"#,
+            s.scene().map_or("null".to_string(), |n| format!("\"{n}\"")),
+            if self.paused() { "paused" } else { "playing" },
+            self.params_json(),
+            s.loudness_db(),
+        )
+    }
+
+    /// Every parameter the scene has, as a JSON object.
+    pub fn params_json(&self) -> String {
+        let mut out = String::from("{");
+        for (id, v) in ParamId::ALL.into_iter().filter_map(|id| self.status.param(id).map(|v| (id, v))) {
+            if out.len() > 1 { out.push(','); }
+            let _ = write!(out, "\"{}\":{v}", id.name());
+        }
+        out.push('}');
+        out
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use ambientor_engine::command::{command_queue, CommandReceiver};
+
+    const SR: f32 = 48_000.0;
+
+    /// A remote on a slow-drone player, with the queue and reload it feeds.
+    pub(crate) fn player() -> (Remote, CommandReceiver, Arc<SceneReload>) {
+        let scene = Scene::by_name("slow-drone", SR).unwrap();
+        let status = Arc::new(Status::new());
+        status.publish(&mut Engine::new(scene));
+        let (tx, rx) = command_queue(16);
+        let reload = Arc::new(SceneReload::new("slow-drone", SR));
+        (Remote::new(status, tx, Arc::clone(&reload), Arc::new(AtomicBool::new(false))), rx, reload)
+    }
+
+    #[test]
+    fn scenes_follow_the_stream_rate() {
+        use ambientor_engine::Generator;
+        let (mut remote, _rx, reload) = player();
+        reload.set_sample_rate(44_100.0); // as after a reconnect
+        remote.set_scene("risset").unwrap();
+        let sent = reload.scene.lock().unwrap().take().unwrap();
+        let render = |mut s: Scene| (0..4_800).map(|_| s.next_stereo()).collect::<Vec<_>>();
+        let out = render(sent);
+        assert!(out == render(Scene::by_name("risset", 44_100.0).unwrap()));
+        assert!(out != render(Scene::by_name("risset", SR).unwrap()));
+    }
+
+    #[test]
+    fn loudness_follows_the_long_term_level() {
+        let s = Status::new();
+        assert_eq!(s.loudness_db(), FLOOR_DB);
+        s.publish_levels(1.0, 0.1);
+        assert!((s.loudness_db() + 20.0).abs() < 0.01);
+
+        // one loud second barely moves it, a sustained change gets there
+        s.publish_levels(1.0, 0.3);
+        assert!(s.loudness_db() < -18.0 && (s.levels_db().1 + 10.46).abs() < 0.01);
+        for _ in 0..(10.0 * LOUDNESS_S) as usize { s.publish_levels(0.01, 0.01); }
+        assert!((s.loudness_db() + 40.0).abs() < 0.1, "{}", s.loudness_db());
+    }
+}
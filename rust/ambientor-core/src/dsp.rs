@@ -26,25 +26,28 @@ cfg_if! {
     // micromath preferred if explicitly requested (works in no_std)
     if #[cfg(feature = "micromath")] {
         use micromath::F32Ext as _;
-        #[inline] fn m_sin(x: f32) -> f32 { x.sin() }
-        #[inline] fn m_cos(x: f32) -> f32 { x.cos() }
+        #[inline] pub(crate) fn m_sin(x: f32) -> f32 { x.sin() }
+        #[inline] pub(crate) fn m_cos(x: f32) -> f32 { x.cos() }
         #[inline] fn m_exp(x: f32) -> f32 { x.exp() }
         #[inline] fn m_tanh(x: f32) -> f32 { x.tanh() }
         #[inline] fn m_tan(x: f32) -> f32 { (x.sin()) / (x.cos()) }
+        #[inline] pub(crate) fn m_sqrt(x: f32) -> f32 { x.sqrt() }
     // libm (C math) in no_std
     } else if #[cfg(feature = "no-std")] {
-        #[inline] fn m_sin(x: f32) -> f32 { libm::sinf(x) }
-        #[inline] fn m_cos(x: f32) -> f32 { libm::cosf(x) }
+        #[inline] pub(crate) fn m_sin(x: f32) -> f32 { libm::sinf(x) }
+        #[inline] pub(crate) fn m_cos(x: f32) -> f32 { libm::cosf(x) }
         #[inline] fn m_exp(x: f32) -> f32 { libm::expf(x) }
         #[inline] fn m_tanh(x: f32) -> f32 { libm::tanhf(x) }
         #[inline] fn m_tan(x: f32) -> f32 { libm::tanf(x) }
+        #[inline] pub(crate) fn m_sqrt(x: f32) -> f32 { libm::sqrtf(x) }
     // std backend
     } else {
-        #[inline] fn m_sin(x: f32) -> f32 { x.sin() }
-        #[inline] fn m_cos(x: f32) -> f32 { x.cos() }
+        #[inline] pub(crate) fn m_sin(x: f32) -> f32 { x.sin() }
+        #[inline] pub(crate) fn m_cos(x: f32) -> f32 { x.cos() }
         #[inline] fn m_exp(x: f32) -> f32 { x.exp() }
         #[inline] fn m_tanh(x: f32) -> f32 { x.tanh() }
         #[inline] fn m_tan(x: f32) -> f32 { x.tan() }
+        #[inline] pub(crate) fn m_sqrt(x: f32) -> f32 { x.sqrt() }
     }
 }
 
@@ -262,6 +265,210 @@ impl DcBlock {
     }
 }
 
+// --------------------------------- Scales / pitch --------------------------------
+
+/// A musical scale as pitch classes (semitones above the tonic) repeating every
+/// `period` semitones (12 for octave-repeating scales).
+///
+/// Degrees should be sorted and lie in `[0, period)`. User tables can use
+/// fractional semitones for microtonal steps.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Scale<'a> {
+    degrees: &'a [f32],
+    period:  f32,
+}
+
+impl<'a> Scale<'a> {
+    pub const CHROMATIC: Scale<'static> =
+        Scale::new(&[0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0]);
+    pub const MAJOR: Scale<'static> = Scale::new(&[0.0, 2.0, 4.0, 5.0, 7.0, 9.0, 11.0]);
+    /// Natural minor (aeolian).
+    pub const MINOR: Scale<'static> = Scale::new(&[0.0, 2.0, 3.0, 5.0, 7.0, 8.0, 10.0]);
+    pub const DORIAN: Scale<'static> = Scale::new(&[0.0, 2.0, 3.0, 5.0, 7.0, 9.0, 10.0]);
+    /// Major pentatonic.
+    pub const PENTATONIC: Scale<'static> = Scale::new(&[0.0, 2.0, 4.0, 7.0, 9.0]);
+    pub const MINOR_PENTATONIC: Scale<'static> = Scale::new(&[0.0, 3.0, 5.0, 7.0, 10.0]);
+    pub const WHOLE_TONE: Scale<'static> = Scale::new(&[0.0, 2.0, 4.0, 6.0, 8.0, 10.0]);
+
+    /// Octave-repeating scale from a user table of pitch classes.
+    #[inline]
+    #[must_use]
+    pub const fn new(degrees: &'a [f32]) -> Self { Self { degrees, period: 12.0 } }
+
+    /// Scale repeating every `period` semitones (e.g. 19 for a tritave-free 19-step table).
+    #[inline]
+    #[must_use]
+    pub const fn with_period(degrees: &'a [f32], period: f32) -> Self { Self { degrees, period } }
+
+    /// Look up a built-in scale by name (`major`, `minor`, `dorian`, `pentatonic`,
+    /// `minor-pentatonic`, `whole-tone`, `chromatic`).
+    #[must_use]
+    pub fn by_name(name: &str) -> Option<Scale<'static>> {
+        match name {
+            "major" | "ionian" => Some(Scale::MAJOR),
+            "minor" | "aeolian" => Some(Scale::MINOR),
+            "dorian" => Some(Scale::DORIAN),
+            "pentatonic" | "major-pentatonic" => Some(Scale::PENTATONIC),
+            "minor-pentatonic" => Some(Scale::MINOR_PENTATONIC),
+            "whole-tone" | "wholetone" => Some(Scale::WHOLE_TONE),
+            "chromatic" => Some(Scale::CHROMATIC),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn degrees(&self) -> &'a [f32] { self.degrees }
+
+    #[inline]
+    #[must_use]
+    pub fn period(&self) -> f32 { self.period }
+
+    /// Semitone offset of scale degree `degree` (wrapping into higher/lower periods).
+    #[inline]
+    #[must_use]
+    pub fn degree_to_semitones(&self, degree: i32) -> f32 {
+        if self.degrees.is_empty() {
+            #[allow(clippy::cast_precision_loss)]
+            return degree as f32;
+        }
+        #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+        let n = self.degrees.len() as i32;
+        #[allow(clippy::cast_sign_loss)]
+        let idx = degree.rem_euclid(n) as usize;
+        #[allow(clippy::cast_precision_loss)]
+        let oct = degree.div_euclid(n) as f32;
+        oct * self.period + self.degrees[idx]
+    }
+}
+
+/// Snap a (fractional) MIDI note to the nearest note of `scale`, with the scale's
+/// tonic on MIDI note 0 (C). Transpose around the call for other roots:
+/// `quantize_to_scale(n - root, s) + root`. An empty scale returns `midi_note` unchanged.
+#[inline]
+#[must_use]
+pub fn quantize_to_scale(midi_note: f32, scale: &Scale<'_>) -> f32 {
+    if scale.degrees.is_empty() || scale.period <= 0.0 { return midi_note; }
+    let base = (midi_note / scale.period).floor() * scale.period;
+    let mut best = midi_note;
+    let mut best_d = f32::MAX;
+    // neighbouring periods too, so notes near a period edge snap across it
+    for b in [base - scale.period, base, base + scale.period] {
+        for &deg in scale.degrees {
+            let c = b + deg;
+            let d = (c - midi_note).abs();
+            if d < best_d { best_d = d; best = c; }
+        }
+    }
+    best
+}
+
+/// Maximum number of degrees in a [`Tuning`] ratio table.
+pub const TUNING_MAX_DEGREES: usize = 128;
+
+/// Note → frequency mapping: 12-TET by default, or a Scala-like table of ratios.
+///
+/// - 12-TET: `hz = ref_hz * 2^((note - ref_note) / 12)`.
+/// - Ratio table: consecutive notes step through the table from `ref_note`
+///   (degree 0 = `ref_hz`), repeating every `table.len()` notes at `period`
+///   (2.0 = octave). Fractional notes glide geometrically between degrees.
+///
+/// Fixed-size and `Copy`, so it can live inside scenes and be swapped at runtime.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Tuning {
+    ref_hz:   f32,
+    ref_note: f32,
+    ratios:   [f32; TUNING_MAX_DEGREES],
+    len:      usize, // 0 → 12-TET
+    period:   f32,
+}
+
+impl Default for Tuning {
+    fn default() -> Self { Self::equal() }
+}
+
+impl Tuning {
+    /// 12-TET with A4 (MIDI 69) = 440 Hz.
+    #[inline]
+    #[must_use]
+    pub const fn equal() -> Self {
+        Self { ref_hz: 440.0, ref_note: 69.0, ratios: [1.0; TUNING_MAX_DEGREES], len: 0, period: 2.0 }
+    }
+
+    /// 12-TET with a custom A4 (e.g. 432 Hz).
+    #[inline]
+    #[must_use]
+    pub fn with_ref_hz(hz: f32) -> Self {
+        let mut t = Self::equal();
+        t.set_ref_hz(hz);
+        t
+    }
+
+    /// Frequency of the reference note. Non-positive/invalid values are ignored.
+    #[inline]
+    pub fn set_ref_hz(&mut self, hz: f32) {
+        if hz.is_finite() && hz > 0.0 { self.ref_hz = hz; }
+    }
+
+    /// MIDI note that sounds at `ref_hz` (and is degree 0 of a ratio table).
+    #[inline] pub fn set_ref_note(&mut self, note: f32) { self.ref_note = note; }
+
+    #[inline]
+    #[must_use]
+    pub fn ref_hz(&self) -> f32 { self.ref_hz }
+
+    #[inline]
+    #[must_use]
+    pub fn ref_note(&self) -> f32 { self.ref_note }
+
+    /// `true` when using 12-TET (no ratio table).
+    #[inline]
+    #[must_use]
+    pub fn is_equal(&self) -> bool { self.len == 0 }
+
+    /// Use a ratio table (first entry normally 1.0, ascending, all below `period`).
+    /// Returns `false` (and keeps the current tuning) for empty/oversized/invalid tables.
+    pub fn set_ratios(&mut self, ratios: &[f32], period: f32) -> bool {
+        if ratios.is_empty() || ratios.len() > TUNING_MAX_DEGREES || !(period > 1.0 && period.is_finite()) {
+            return false;
+        }
+        if ratios.iter().any(|r| !(r.is_finite() && *r > 0.0 && *r < period)) {
+            return false;
+        }
+        self.ratios[..ratios.len()].copy_from_slice(ratios);
+        self.len = ratios.len();
+        self.period = period;
+        true
+    }
+
+    /// Back to 12-TET (reference pitch kept).
+    #[inline] pub fn set_equal(&mut self) { self.len = 0; }
+
+    /// Frequency in Hz for a (fractional) MIDI note.
+    #[inline]
+    #[must_use]
+    pub fn note_to_hz(&self, note: f32) -> f32 {
+        let steps = note - self.ref_note;
+        if self.len == 0 {
+            return self.ref_hz * m_exp(core::f32::consts::LN_2 * steps / 12.0);
+        }
+        let k = steps.floor();
+        let frac = steps - k;
+        #[allow(clippy::cast_possible_truncation)]
+        let k = k as i32;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+        let n = self.len as i32;
+        #[allow(clippy::cast_sign_loss)]
+        let idx = k.rem_euclid(n) as usize;
+        #[allow(clippy::cast_precision_loss)]
+        let oct = k.div_euclid(n) as f32;
+        let r0 = self.ratios[idx];
+        let r1 = if idx + 1 < self.len { self.ratios[idx + 1] } else { self.period * self.ratios[0] };
+        let ln = oct * self.period.ln() + r0.ln() + frac * (r1.ln() - r0.ln());
+        self.ref_hz * m_exp(ln)
+    }
+}
+
 // --------------------------------- Mix / sine block (scalar) ---------------------
 
 /// In-place mix: `dst[i] += src[i] * gain` (pure scalar, portable).
@@ -335,6 +542,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn quantize_lands_on_scale() {
+        // C major: E stays, A#+ snaps up to B, B+ wraps to the next C
+        assert!((quantize_to_scale(64.2, &Scale::MAJOR) - 64.0).abs() < 1e-6);
+        assert!((quantize_to_scale(70.6, &Scale::MAJOR) - 71.0).abs() < 1e-6);
+        assert!((quantize_to_scale(71.8, &Scale::MAJOR) - 72.0).abs() < 1e-6);
+        assert!(quantize_to_scale(-1.2, &Scale::PENTATONIC).abs() < 1e-6);
+        assert!((quantize_to_scale(-2.0, &Scale::PENTATONIC) + 3.0).abs() < 1e-6);
+        let user = [0.0, 3.5, 7.0];
+        assert!((quantize_to_scale(63.0, &Scale::new(&user)) - 63.5).abs() < 1e-6);
+        assert!((Scale::WHOLE_TONE.degree_to_semitones(7) - 14.0).abs() < 1e-6);
+        assert!((Scale::MINOR.degree_to_semitones(-1) + 2.0).abs() < 1e-6);
+        assert_eq!(Scale::by_name("dorian"), Some(Scale::DORIAN));
+    }
+
+    #[test]
+    fn tuning_equal_and_table() {
+        let t = Tuning::equal();
+        assert!((t.note_to_hz(69.0) - 440.0).abs() < 1e-3);
+        assert!((t.note_to_hz(57.0) - 220.0).abs() < 1e-3);
+        let t = Tuning::with_ref_hz(432.0);
+        assert!((t.note_to_hz(81.0) - 864.0).abs() < 1e-2);
+
+        // 7-note just major scale on A4
+        let mut j = Tuning::equal();
+        assert!(j.set_ratios(&[1.0, 9.0 / 8.0, 5.0 / 4.0, 4.0 / 3.0, 3.0 / 2.0, 5.0 / 3.0, 15.0 / 8.0], 2.0));
+        assert!((j.note_to_hz(73.0) - 660.0).abs() < 1e-2);
+        assert!((j.note_to_hz(76.0) - 880.0).abs() < 1e-2);
+        assert!((j.note_to_hz(68.0) - 412.5).abs() < 1e-2);
+        assert!(!j.set_ratios(&[], 2.0));
+    }
+
     #[test]
     fn rms_decreases_to_zero() {
         let mut rms = Rms::new(one_pole_coeff_ms(10.0, 48000.0));
@@ -4,6 +4,7 @@
 //! - `AdsrLinear`    : classic ADSR with **linear** segments
 //! - `AdsrExp`       : ADSR with **exponential (RC-like)** segments (more “musical”)
 //! - `ArExp`         : fast AR percussion envelope (exp attack/decay)
+//! - `BreakpointEnv` : multi-segment envelope (time/level/curve points, optional loop)
 //! - `SlewLimiter`   : one-pole slew/smoother for arbitrary control signals
 //!
 //! All envelopes are `no_std` friendly and avoid heap allocations.
@@ -14,6 +15,18 @@ use crate::dsp::{one_pole_coeff_ms, clamp};
 
 // -------------------------------- Linear ADSR ------------------------------------
 
+/// What `gate_on` does while an ADSR is still sounding.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum AdsrTrigger {
+    /// Restart the attack from the current level (default).
+    #[default]
+    Restart,
+    /// Jump to zero and start a fresh attack.
+    FromZero,
+    /// Ignore new gates while already gated (a released envelope re-attacks from its current level).
+    Legato,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 enum AdsrStage {
     Idle,
@@ -24,8 +37,9 @@ enum AdsrStage {
 }
 
 /// Linear ADSR envelope.
-/// Times are specified in milliseconds. Sustain is [0,1].
+/// Times are specified in milliseconds. Sustain is [0,1] (relative to the peak).
 /// Call `set_sr(sample_rate)` once if your `next()` variant uses `sr`.
+/// `gate_on_vel` scales the peak (and so the sustain level) by velocity.
 #[derive(Copy, Clone, Debug)]
 pub struct AdsrLinear {
     atk_ms: f32,
@@ -33,9 +47,11 @@ pub struct AdsrLinear {
     sus:    f32,
     rel_ms: f32,
     sr:     f32,
+    mode:   AdsrTrigger,
 
     // state
     env:   f32,
+    peak:  f32,
     gate:  bool,
     stage: AdsrStage,
     // cached per-sample increments
@@ -53,7 +69,9 @@ impl AdsrLinear {
             sus: clamp(sus, 0.0, 1.0),
             rel_ms,
             sr,
+            mode: AdsrTrigger::Restart,
             env: 0.0,
+            peak: 1.0,
             gate: false,
             stage: AdsrStage::Idle,
             a_inc: 0.0,
@@ -103,8 +121,17 @@ impl AdsrLinear {
         };
     }
 
+    #[inline] pub fn set_trigger_mode(&mut self, mode: AdsrTrigger) { self.mode = mode; }
+
+    #[inline]
+    pub fn gate_on(&mut self)  { self.gate_on_vel(1.0); }
+
+    /// Gate on with velocity in [0,1]: the peak becomes `vel` and sustain `sus * vel`.
     #[inline]
-    pub fn gate_on(&mut self)  {
+    pub fn gate_on_vel(&mut self, vel: f32) {
+        if self.mode == AdsrTrigger::Legato && self.gate { return; }
+        if self.mode == AdsrTrigger::FromZero { self.env = 0.0; }
+        self.peak  = vel.clamp(0.0, 1.0);
         self.gate  = true;
         self.stage = AdsrStage::Attack;
     }
@@ -118,29 +145,35 @@ impl AdsrLinear {
     /// Advance by **one sample** using the configured sample rate.
     #[inline]
     pub fn next(&mut self) -> f32 {
+        let sus = self.sus * self.peak;
         match self.stage {
             AdsrStage::Idle => {
                 self.env = 0.0;
             }
             AdsrStage::Attack => {
-                self.env += self.a_inc;
-                if self.env >= 1.0 {
-                    self.env = 1.0;
+                if self.env >= self.peak {
+                    // already above a (softer) new peak: let decay bring it down
                     self.stage = AdsrStage::Decay;
+                } else {
+                    self.env += self.a_inc * self.peak;
+                    if self.env >= self.peak {
+                        self.env = self.peak;
+                        self.stage = AdsrStage::Decay;
+                    }
                 }
             }
             AdsrStage::Decay => {
                 if !self.gate {
                     // if gate dropped mid-decay, go straight to release
                     self.stage = AdsrStage::Release;
-                } else if self.env > self.sus {
-                    self.env -= self.d_dec;
-                    if self.env <= self.sus {
-                        self.env = self.sus;
+                } else if self.env > sus {
+                    self.env -= self.d_dec * self.peak;
+                    if self.env <= sus {
+                        self.env = sus;
                         self.stage = AdsrStage::Sustain;
                     }
                 } else {
-                    self.env = self.sus;
+                    self.env = sus;
                     self.stage = AdsrStage::Sustain;
                 }
             }
@@ -148,7 +181,7 @@ impl AdsrLinear {
                 if !self.gate {
                     self.stage = AdsrStage::Release;
                 } else {
-                    self.env = self.sus;
+                    self.env = sus;
                 }
             }
             AdsrStage::Release => {
@@ -156,7 +189,7 @@ impl AdsrLinear {
                     self.env = 0.0;
                     self.stage = AdsrStage::Idle;
                 } else if self.env > 0.0 {
-                    self.env -= self.r_dec;
+                    self.env -= self.r_dec * self.peak;
                     if self.env <= 0.0 {
                         self.env = 0.0;
                         self.stage = AdsrStage::Idle;
@@ -177,7 +210,14 @@ impl AdsrLinear {
 
 /// Exponential (RC-like) ADSR envelope.
 /// Attack/Decay/Release are **time constants in ms** controlling the curvature.
-/// Sustain is [0,1]. This is often more “musical” than linear segments.
+/// Sustain is [0,1] (relative to the peak). This is often more “musical” than linear segments.
+///
+/// Like analog ADSRs, the attack can aim *past* the peak (see [`AdsrExp::set_curve`])
+/// and stop when it gets there, which straightens the rise.
+///
+/// In loop mode ([`AdsrExp::set_looping`]) a held gate cycles attack → decay
+/// → attack…, a slow "breathing" pulse between the peak and the sustain level
+/// whose speed follows [`AdsrExp::set_rate`].
 #[derive(Copy, Clone, Debug)]
 pub struct AdsrExp {
     atk_ms: f32,
@@ -185,9 +225,15 @@ pub struct AdsrExp {
     sus:    f32,
     rel_ms: f32,
     sr:     f32,
+    mode:   AdsrTrigger,
+    curve:  f32,
+    looping: bool,
+    rate:   f32,
 
     env:  f32,
+    peak: f32,
     gate: bool,
+    attacking: bool,
     // per-stage coefficients a = exp(-1/(tau*sr))
     a_a: f32,
     a_d: f32,
@@ -200,8 +246,14 @@ impl AdsrExp {
         let mut s = Self {
             atk_ms, dec_ms, sus: clamp(sus, 0.0, 1.0), rel_ms,
             sr,
+            mode: AdsrTrigger::Restart,
+            curve: 0.0,
+            looping: false,
+            rate: 1.0,
             env: 0.0,
+            peak: 1.0,
             gate: false,
+            attacking: false,
             a_a: 0.0,
             a_d: 0.0,
             a_r: 0.0,
@@ -225,29 +277,72 @@ impl AdsrExp {
     #[inline]
     fn recalc_coeffs(&mut self) {
         let sr = self.sr;
-        self.a_a = one_pole_coeff_ms(self.atk_ms, sr);
-        self.a_d = one_pole_coeff_ms(self.dec_ms, sr);
+        self.a_a = one_pole_coeff_ms(self.atk_ms / self.rate, sr);
+        self.a_d = one_pole_coeff_ms(self.dec_ms / self.rate, sr);
         self.a_r = one_pole_coeff_ms(self.rel_ms, sr);
     }
 
-    #[inline] pub fn gate_on(&mut self)  { self.gate = true; }
-    #[inline] pub fn gate_off(&mut self) { self.gate = false; }
+    #[inline] pub fn set_trigger_mode(&mut self, mode: AdsrTrigger) { self.mode = mode; }
+
+    /// Loop mode: while the gate is held, start a new attack each time the
+    /// decay has (nearly) reached the sustain level.
+    #[inline] pub fn set_looping(&mut self, on: bool) { self.looping = on; }
+    #[inline] #[must_use] pub fn is_looping(&self) -> bool { self.looping }
+
+    /// Speed of the attack and decay stages in [0.01, 100] (1 = as set, 2 = twice as
+    /// fast); sets the pulse rate in loop mode. Release keeps its own time.
+    /// Non-finite rates are ignored.
+    #[inline]
+    pub fn set_rate(&mut self, rate: f32) {
+        if !rate.is_finite() { return; }
+        self.rate = rate.clamp(0.01, 100.0);
+        self.recalc_coeffs();
+    }
+
+    /// Attack shape in [0,1]: 0 = pure RC curve towards the peak, 1 = aims at
+    /// 3× the peak and stops there (nearly linear, and a bit faster).
+    #[inline] pub fn set_curve(&mut self, c: f32) { self.curve = c.clamp(0.0, 1.0); }
+
+    #[inline] pub fn gate_on(&mut self)  { self.gate_on_vel(1.0); }
+
+    /// Gate on with velocity in [0,1]: the peak becomes `vel` and sustain `sus * vel`.
+    #[inline]
+    pub fn gate_on_vel(&mut self, vel: f32) {
+        if self.mode == AdsrTrigger::Legato && self.gate { return; }
+        if self.mode == AdsrTrigger::FromZero { self.env = 0.0; }
+        self.peak = vel.clamp(0.0, 1.0);
+        self.gate = true;
+        self.attacking = true;
+    }
+
+    #[inline] pub fn gate_off(&mut self) { self.gate = false; self.attacking = false; }
 
     /// Advance by one sample and return the envelope value.
     ///
     /// Stage equations (exponential towards target):
-    /// - Attack:  env += (1 - env) * (1 - a_a)
-    /// - Decay:   env += (sus - env) * (1 - a_d)
+    /// - Attack:  env += (peak * (1 + 2*curve) - env) * (1 - a_a), until env reaches the peak
+    /// - Decay:   env += (sus * peak - env) * (1 - a_d)
     /// - Release: env += (0   - env) * (1 - a_r)
     #[inline]
     pub fn next(&mut self) -> f32 {
         if self.gate {
-            if self.env < 0.9999 {
-                self.env += (1.0 - self.env) * (1.0 - self.a_a);
-            } else if self.env > self.sus {
-                self.env += (self.sus - self.env) * (1.0 - self.a_d);
+            let sus = self.sus * self.peak;
+            if self.attacking && self.env < self.peak * 0.9999 {
+                let target = self.peak * (1.0 + 2.0 * self.curve);
+                self.env += (target - self.env) * (1.0 - self.a_a);
+                if self.env >= self.peak * 0.9999 {
+                    self.env = self.env.min(self.peak);
+                    self.attacking = false;
+                }
+            } else if self.env > sus {
+                self.attacking = false;
+                self.env += (sus - self.env) * (1.0 - self.a_d);
+                if self.looping && self.env - sus <= LOOP_RESTART * (self.peak - sus) {
+                    self.attacking = true;
+                }
             } else {
-                self.env = self.sus; // hold
+                self.attacking = false;
+                self.env = sus; // hold
             }
         } else {
             self.env += (0.0 - self.env) * (1.0 - self.a_r);
@@ -259,6 +354,10 @@ impl AdsrExp {
     #[inline] pub fn value(&self) -> f32 { self.env }
 }
 
+/// Loop-mode [`AdsrExp`] restarts its attack once the decay is within this share
+/// of the way from the peak down to the sustain level.
+const LOOP_RESTART: f32 = 0.05;
+
 // ------------------------------- AR (percussive) ---------------------------------
 
 /// Exponential AR envelope for percussive sounds.
@@ -316,6 +415,209 @@ impl ArExp {
     }
 
     #[inline] pub fn value(&self) -> f32 { self.env }
+
+    /// `true` once the release has reached zero (and before any new trigger).
+    #[inline] #[must_use] pub fn is_idle(&self) -> bool { !self.rising && self.env <= 0.0 }
+}
+
+// ------------------------------ Breakpoint envelope ------------------------------
+
+/// One point of a [`BreakpointEnv`].
+///
+/// `time_s` is the duration of the segment that **arrives** at this point, from the
+/// previous point's level (or from the value at trigger time for the first point).
+/// `curve` in [-1, 1] bends the segment: 0 = linear, > 0 = fast start/slow finish,
+/// < 0 = slow start/fast finish.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Breakpoint {
+    pub time_s: f32,
+    pub level:  f32,
+    pub curve:  f32,
+}
+
+impl Breakpoint {
+    #[inline]
+    #[must_use]
+    pub const fn new(time_s: f32, level: f32, curve: f32) -> Self { Self { time_s, level, curve } }
+}
+
+/// Rational curve shaping (no transcendental math): `k*t / (1 + (k-1)*t)`.
+#[inline]
+fn bend(t: f32, curve: f32) -> f32 {
+    let c = curve.clamp(-0.99, 0.99);
+    let k = (1.0 + c) / (1.0 - c);
+    k * t / (1.0 + (k - 1.0) * t)
+}
+
+/// Envelope made of up to `N` breakpoints, for long contours an ADSR can't
+/// describe (e.g. a 3-minute rise and fall driving brightness).
+///
+/// With a loop region `(a, b)`, reaching point `b` jumps back to the segment
+/// after `a` while the envelope is held; [`BreakpointEnv::release`] lets it run
+/// on past `b` to the end. Without a region, loop mode
+/// ([`BreakpointEnv::set_looping`]) replays the whole contour from the first
+/// point while held, e.g. a pulsing swell. [`BreakpointEnv::set_rate`] plays it
+/// faster or slower. Segment position is tracked in `f64` so very long
+/// segments stay accurate.
+#[derive(Copy, Clone, Debug)]
+pub struct BreakpointEnv<const N: usize> {
+    pts: [Breakpoint; N],
+    len: usize,
+    loop_region: Option<(usize, usize)>,
+    looping: bool,
+    rate: f64,
+
+    // state
+    seg:     usize, // index of the point we are heading towards
+    pos:     f64,   // [0,1] progress within the current segment
+    from:    f32,   // level at the start of the current segment
+    env:     f32,
+    running: bool,
+    held:    bool,
+}
+
+impl<const N: usize> Default for BreakpointEnv<N> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<const N: usize> BreakpointEnv<N> {
+    /// Empty envelope (outputs 0 until points are added).
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            pts: [Breakpoint::new(0.0, 0.0, 0.0); N],
+            len: 0,
+            loop_region: None,
+            looping: false,
+            rate: 1.0,
+            seg: 0,
+            pos: 0.0,
+            from: 0.0,
+            env: 0.0,
+            running: false,
+            held: false,
+        }
+    }
+
+    /// Build from a slice (extra points beyond `N` are ignored).
+    #[inline]
+    #[must_use]
+    pub fn from_points(points: &[Breakpoint]) -> Self {
+        let mut e = Self::new();
+        for p in points { e.push(*p); }
+        e
+    }
+
+    /// Append a point; returns `false` if the envelope is full.
+    #[inline]
+    pub fn push(&mut self, p: Breakpoint) -> bool {
+        if self.len >= N { return false; }
+        self.pts[self.len] = Breakpoint { time_s: p.time_s.max(0.0), ..p };
+        self.len += 1;
+        true
+    }
+
+    /// Remove all points and stop.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.loop_region = None;
+        self.running = false;
+    }
+
+    #[inline] #[must_use] pub fn len(&self) -> usize { self.len }
+    #[inline] #[must_use] pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Loop between point indices `start < end` while held; `None` disables
+    /// looping, as does a region whose segments all take no time.
+    #[inline]
+    pub fn set_loop(&mut self, region: Option<(usize, usize)>) {
+        self.loop_region = region.filter(|&(a, b)| {
+            a < b && b < self.len && self.pts[a + 1..=b].iter().map(|p| p.time_s).sum::<f32>() > 0.0
+        });
+    }
+
+    /// Loop mode: while held, reaching the last point starts over at the first
+    /// (gliding from the last level). A loop region takes precedence.
+    #[inline] pub fn set_looping(&mut self, on: bool) { self.looping = on; }
+
+    /// Playback speed in [0.01, 100] (1 = segment times as given, 2 = twice as fast).
+    /// Non-finite rates are ignored.
+    #[inline]
+    pub fn set_rate(&mut self, rate: f32) {
+        if rate.is_finite() { self.rate = f64::from(rate.clamp(0.01, 100.0)); }
+    }
+
+    /// Total duration of all segments (ignoring loops), in seconds.
+    #[inline]
+    #[must_use]
+    pub fn duration_s(&self) -> f32 { self.pts[..self.len].iter().map(|p| p.time_s).sum() }
+
+    /// Start from the current value towards the first point (no click on retrigger).
+    #[inline]
+    pub fn trigger(&mut self) {
+        self.seg = 0;
+        self.pos = 0.0;
+        self.from = self.env;
+        self.running = self.len > 0;
+        self.held = true;
+    }
+
+    /// Leave the loop region (if any) and play out to the last point.
+    #[inline] pub fn release(&mut self) { self.held = false; }
+
+    /// Stop and jump to zero.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.env = 0.0;
+        self.running = false;
+        self.held = false;
+    }
+
+    /// `true` while segments are still playing.
+    #[inline] #[must_use] pub fn is_running(&self) -> bool { self.running }
+
+    /// Advance by one sample at sample rate `sr` and return the envelope value.
+    #[inline]
+    pub fn next(&mut self, sr: f32) -> f32 {
+        if !self.running { return self.env; }
+        let dt = self.rate / f64::from(sr.max(1.0));
+
+        loop {
+            let p = self.pts[self.seg];
+            let dur = f64::from(p.time_s);
+            if dur > 0.0 {
+                self.pos += dt / dur;
+            } else {
+                self.pos = 1.0;
+            }
+            if self.pos < 1.0 {
+                #[allow(clippy::cast_possible_truncation)]
+                let t = self.pos as f32;
+                self.env = self.from + (p.level - self.from) * bend(t, p.curve);
+                return self.env;
+            }
+
+            // segment finished
+            self.env = p.level;
+            self.from = p.level;
+            self.pos = 0.0;
+            match self.loop_region {
+                Some((a, b)) if self.held && self.seg == b => self.seg = a + 1,
+                _ if self.seg + 1 < self.len => self.seg += 1,
+                _ if self.looping && self.held && self.duration_s() > 0.0 => self.seg = 0,
+                _ => {
+                    self.running = false;
+                    return self.env;
+                }
+            }
+            // zero-length segments chain through in the same sample
+            if self.pts[self.seg].time_s > 0.0 { return self.env; }
+        }
+    }
+
+    #[inline] #[must_use] pub fn value(&self) -> f32 { self.env }
 }
 
 // -------------------------------- Slew Limiter -----------------------------------
@@ -385,6 +687,73 @@ mod tests {
         assert!(env.value() < 0.05);
     }
 
+    #[test]
+    fn adsr_trigger_modes_and_velocity() {
+        let sr = 48000.0;
+        let mut env = AdsrLinear::new(10.0, 50.0, 0.5, 200.0, sr);
+        env.gate_on_vel(0.5);
+        for _ in 0..24_000 { env.next(); }
+        assert!((env.value() - 0.25).abs() < 0.01, "v={}", env.value());
+
+        // legato: a second gate while held changes nothing
+        env.set_trigger_mode(AdsrTrigger::Legato);
+        env.gate_on();
+        env.next();
+        assert!((env.value() - 0.25).abs() < 0.01);
+
+        // from zero: restarts at silence
+        env.set_trigger_mode(AdsrTrigger::FromZero);
+        env.gate_on();
+        assert!(env.next() < 0.01);
+
+        let mut e = AdsrExp::new(5.0, 100.0, 0.3, 200.0, sr);
+        e.set_curve(1.0);
+        e.gate_on_vel(0.8);
+        let mut maxv: f32 = 0.0;
+        for _ in 0..24_000 { maxv = maxv.max(e.next()); }
+        assert!(maxv <= 0.8 + 1e-4 && maxv > 0.79, "max={maxv}");
+        assert!((e.value() - 0.24).abs() < 0.01, "v={}", e.value());
+    }
+
+    #[test]
+    fn adsr_exp_loops_while_held() {
+        let sr = 48000.0;
+        let mut e = AdsrExp::new(20.0, 100.0, 0.2, 100.0, sr);
+        e.set_looping(true);
+        e.gate_on();
+        let mut peaks = 0;
+        let mut rising = false;
+        let (mut lo, mut prev) = (1.0f32, 0.0f32);
+        for i in 0..96_000 {
+            let v = e.next();
+            if rising && v < prev { peaks += 1; }
+            rising = v > prev;
+            prev = v;
+            if i > 48_000 { lo = lo.min(v); }
+        }
+        assert!(peaks >= 3, "peaks={peaks}");
+        assert!(lo > 0.2 && lo < 0.3, "lo={lo}");
+
+        // twice the rate, about twice the pulses
+        let mut f = AdsrExp::new(20.0, 100.0, 0.2, 100.0, sr);
+        f.set_looping(true);
+        f.set_rate(2.0);
+        f.gate_on();
+        let mut fast = 0;
+        (rising, prev) = (false, 0.0);
+        for _ in 0..96_000 {
+            let v = f.next();
+            if rising && v < prev { fast += 1; }
+            rising = v > prev;
+            prev = v;
+        }
+        assert!(fast >= 2 * peaks - 1, "fast={fast} peaks={peaks}");
+
+        e.gate_off();
+        for _ in 0..48_000 { e.next(); }
+        assert!(e.value() < 0.01);
+    }
+
     #[test]
     fn ar_exp_triggers_and_dies() {
         let sr = 48000.0;
@@ -398,6 +767,98 @@ mod tests {
         assert!(maxv > 0.8 && e.value() < 0.01);
     }
 
+    #[test]
+    fn breakpoint_env_follows_points() {
+        let sr = 1000.0;
+        let mut e: BreakpointEnv<4> = BreakpointEnv::from_points(&[
+            Breakpoint::new(1.0, 1.0, 0.0),
+            Breakpoint::new(2.0, 0.5, 0.5),
+        ]);
+        assert!((e.duration_s() - 3.0).abs() < 1e-6);
+        e.trigger();
+        for _ in 0..500 { e.next(sr); }
+        assert!((e.value() - 0.5).abs() < 0.01, "v={}", e.value());
+        for _ in 0..500 { e.next(sr); }
+        assert!((e.value() - 1.0).abs() < 0.01);
+        for _ in 0..2100 { e.next(sr); }
+        assert!(!e.is_running());
+        assert!((e.value() - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn breakpoint_env_loops_until_release() {
+        let sr = 1000.0;
+        let mut e: BreakpointEnv<4> = BreakpointEnv::from_points(&[
+            Breakpoint::new(0.1, 1.0, 0.0),
+            Breakpoint::new(0.1, 0.2, 0.0),
+            Breakpoint::new(0.1, 0.8, 0.0),
+            Breakpoint::new(0.1, 0.0, 0.0),
+        ]);
+        e.set_loop(Some((0, 2)));
+        e.trigger();
+        for _ in 0..5000 { e.next(sr); }
+        assert!(e.is_running());
+        assert!(e.value() > 0.1);
+        e.release();
+        for _ in 0..1000 { e.next(sr); }
+        assert!(!e.is_running());
+        assert!(e.value().abs() < 1e-6);
+    }
+
+    #[test]
+    fn breakpoint_env_cycles_at_rate() {
+        let sr = 1000.0;
+        let mut e: BreakpointEnv<4> = BreakpointEnv::from_points(&[
+            Breakpoint::new(0.5, 1.0, 0.0),
+            Breakpoint::new(0.5, 0.2, 0.0),
+        ]);
+        e.set_looping(true);
+        e.set_rate(2.0);
+        e.trigger();
+        // a 1 s contour at double speed: back at the top every 0.5 s
+        for _ in 0..750 { e.next(sr); }
+        assert!((e.value() - 1.0).abs() < 0.01, "v={}", e.value());
+        for _ in 0..2000 { e.next(sr); }
+        assert!(e.is_running());
+        e.release();
+        for _ in 0..1000 { e.next(sr); }
+        assert!(!e.is_running());
+        assert!((e.value() - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn set_rate_is_bounded_and_ignores_non_finite() {
+        let mut a = AdsrExp::new(20.0, 100.0, 0.2, 100.0, 48000.0);
+        let mut e: BreakpointEnv<4> = BreakpointEnv::from_points(&[Breakpoint::new(0.5, 1.0, 0.0)]);
+        a.set_rate(1e9);
+        e.set_rate(1e9);
+        assert_eq!((a.rate, e.rate), (100.0, 100.0));
+        a.set_rate(1e-9);
+        e.set_rate(1e-9);
+        assert_eq!((a.rate, e.rate), (0.01, f64::from(0.01f32)));
+        for bad in [f32::NAN, f32::INFINITY] {
+            a.set_rate(bad);
+            e.set_rate(bad);
+            assert_eq!((a.rate, e.rate), (0.01, f64::from(0.01f32)));
+        }
+    }
+
+    #[test]
+    fn breakpoint_env_ignores_zero_length_loop() {
+        let sr = 1000.0;
+        let mut e: BreakpointEnv<4> = BreakpointEnv::from_points(&[
+            Breakpoint::new(0.01, 1.0, 0.0),
+            Breakpoint::new(0.0, 0.5, 0.0),
+            Breakpoint::new(0.1, 0.0, 0.0),
+        ]);
+        // segment 0 -> 1 takes no time: looping on it would never return
+        e.set_loop(Some((0, 1)));
+        e.trigger();
+        for _ in 0..1000 { e.next(sr); }
+        assert!(!e.is_running());
+        assert!(e.value().abs() < 1e-6);
+    }
+
     #[test]
     fn slew_moves_towards_target() {
         let sr = 48000.0;
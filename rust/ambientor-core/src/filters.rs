@@ -11,6 +11,10 @@
 //! - `DcBlock`    : convenience wrapper specialized for DC removal
 //! - `SvfMode`    : LP/HP/BP/Notch modes for the SVF
 //! - `SvfTpt`     : State-Variable Filter via Topology Preserving Transform
+//! - `BiquadKind` : response shapes for the biquad (incl. peaking and shelves)
+//! - `Biquad`     : RBJ "cookbook" biquad, transposed direct form II
+//! - `Crossover`  : 2-way Linkwitz–Riley (LR4) band split; the bands sum back flat
+//! - `Crossover3` : 3-way LR4 split with the low band phase-matched to the other two
 //!
 //! Notes
 //! - `OnePole*` use the inexpensive `y += a * (x - y)` form, where
@@ -18,10 +22,61 @@
 //!   they’re great for parameter smoothing and gentle tonal shaping.
 //! - `SvfTpt` uses the “g = tan(π fc / sr)” formulation with `R = 1/(2Q)`.
 //!   It is robust to high resonance and parameter modulation.
-
-use crate::dsp::{kill_denormals, one_pole_coeff_hz, tpt_g};
+//! - `Biquad` follows Robert Bristow-Johnson's Audio EQ Cookbook. Prefer it for
+//!   static EQ (shelves, bells); prefer `SvfTpt` for fast modulation.
+//! - Cutoff setters skip the `exp`/`tan` when the cutoff moved by less than
+//!   [`CUTOFF_EPSILON`] since the coefficient was last computed, so continuous
+//!   modulation only pays for audible moves. `set_cutoff_smoothed` goes
+//!   further: call it once per control block and the coefficient itself ramps
+//!   linearly to the new value, with no transcendental math per sample.
+//! - Every filter also has `process_block`, which filters a buffer in place in
+//!   a tight loop with the coefficients held in registers (bit-identical to
+//!   calling `process` per sample).
+
+use crate::dsp::{db_to_lin, kill_denormals, m_cos, m_sin, m_sqrt, one_pole_coeff_hz, tpt_g};
 use core::fmt::Debug;
 
+/// Relative cutoff change below which filters keep their current coefficient
+/// (0.1%, under 2 cents).
+pub const CUTOFF_EPSILON: f32 = 1e-3;
+
+/// `true` if `new` is far enough from `old` (the cutoff the coefficient was
+/// computed for) to be worth recomputing.
+#[inline]
+fn cutoff_moved(old: f32, new: f32) -> bool {
+    (new - old).abs() > CUTOFF_EPSILON * old.max(1.0)
+}
+
+/// A coefficient gliding linearly to a target over a fixed number of samples.
+#[derive(Copy, Clone, Debug, Default)]
+struct CoeffSlew {
+    target: f32,
+    step: f32,
+    left: u32,
+}
+
+impl CoeffSlew {
+    /// Start a glide of `c` to `target` over `samples`.
+    #[inline]
+    fn start(&mut self, c: f32, target: f32, samples: u32) {
+        let n = samples.max(1);
+        self.target = target;
+        #[allow(clippy::cast_precision_loss)]
+        let len = n as f32;
+        self.step = (target - c) / len;
+        self.left = n;
+    }
+
+    /// Advance `c` one sample.
+    #[inline]
+    fn tick(&mut self, c: &mut f32) {
+        if self.left > 0 {
+            self.left -= 1;
+            *c = if self.left == 0 { self.target } else { *c + self.step };
+        }
+    }
+}
+
 /// One-pole low-pass `y += a * (x - y)`.
 ///
 /// `a` is derived from cutoff (Hz) and sample rate:
@@ -31,7 +86,8 @@ pub struct OnePoleLP {
     a: f32,
     y: f32,
     sr: f32,
-    fc: f32,
+    fc: f32, // cutoff `a` (or its slew target) was computed for
+    slew: CoeffSlew,
 }
 
 impl OnePoleLP {
@@ -43,6 +99,7 @@ impl OnePoleLP {
             y: 0.0,
             sr: sr.max(1.0),
             fc: cut_hz.max(0.0),
+            slew: CoeffSlew::default(),
         };
         s.update_coeffs();
         s
@@ -54,28 +111,71 @@ impl OnePoleLP {
         self.update_coeffs();
     }
 
+    /// Set the cutoff now (cancels any glide). Moves under [`CUTOFF_EPSILON`]
+    /// keep the current coefficient.
     #[inline]
     pub fn set_cutoff_hz(&mut self, cut_hz: f32) {
-        self.fc = cut_hz.max(0.0);
-        self.update_coeffs();
+        let cut = cut_hz.max(0.0);
+        if cutoff_moved(self.fc, cut) {
+            self.fc = cut;
+            self.update_coeffs();
+        } else if self.slew.left > 0 {
+            self.a = self.slew.target;
+            self.slew.left = 0;
+        }
     }
 
+    /// Glide the coefficient linearly to `cut_hz` over the next `samples`
+    /// calls to [`OnePoleLP::process`]. Meant to be called once per control
+    /// block with the block length.
     #[inline]
-    fn update_coeffs(&mut self) {
+    pub fn set_cutoff_smoothed(&mut self, cut_hz: f32, samples: u32) {
+        let cut = cut_hz.max(0.0);
+        if !cutoff_moved(self.fc, cut) { return; }
+        self.fc = cut;
+        self.slew.start(self.a, Self::coeff(self.fc, self.sr), samples);
+    }
+
+    #[inline]
+    fn coeff(fc: f32, sr: f32) -> f32 {
         // For the “y += a*(x-y)” form, many references set a = 1 - exp(..).
         // We compute `exp(-..)` once and fold to a.
-        let exp_term = one_pole_coeff_hz(self.fc, self.sr); // = exp(-2π fc / sr)
-        self.a = 1.0 - exp_term;
+        1.0 - one_pole_coeff_hz(fc, sr) // exp(-2π fc / sr)
+    }
+
+    #[inline]
+    fn update_coeffs(&mut self) {
+        self.a = Self::coeff(self.fc, self.sr);
+        self.slew.left = 0;
     }
 
     /// Process one sample.
     #[inline]
     pub fn process(&mut self, x: f32) -> f32 {
+        self.slew.tick(&mut self.a);
         self.y += self.a * (x - self.y);
         kill_denormals(self.y)
     }
 
+    /// Filter `io` in place.
+    pub fn process_block(&mut self, io: &mut [f32]) {
+        let gliding = (self.slew.left as usize).min(io.len());
+        let (head, tail) = io.split_at_mut(gliding);
+        for x in head {
+            *x = self.process(*x);
+        }
+        let (a, mut y) = (self.a, self.y);
+        for x in tail {
+            y += a * (*x - y);
+            *x = kill_denormals(y);
+        }
+        self.y = y;
+    }
+
     #[inline] pub fn value(&self) -> f32 { self.y }
+
+    /// Clear the filter state (the cutoff is kept).
+    #[inline] pub fn reset(&mut self) { self.y = 0.0; }
 }
 
 /// One-pole high-pass using the standard “leaky integrator” form:
@@ -106,7 +206,14 @@ impl OnePoleHP {
     }
 
     #[inline] pub fn set_sample_rate(&mut self, sr: f32) { self.sr = sr.max(1.0); self.update_coeffs(); }
-    #[inline] pub fn set_cutoff_hz(&mut self, cut_hz: f32) { self.fc = cut_hz.max(0.0); self.update_coeffs(); }
+    /// Moves under [`CUTOFF_EPSILON`] keep the current coefficient.
+    #[inline]
+    pub fn set_cutoff_hz(&mut self, cut_hz: f32) {
+        let cut = cut_hz.max(0.0);
+        if !cutoff_moved(self.fc, cut) { return; }
+        self.fc = cut;
+        self.update_coeffs();
+    }
 
     #[inline]
     fn update_coeffs(&mut self) {
@@ -122,7 +229,23 @@ impl OnePoleHP {
         kill_denormals(y)
     }
 
+    /// Filter `io` in place.
+    pub fn process_block(&mut self, io: &mut [f32]) {
+        let (b, mut x1, mut y1) = (self.b, self.x1, self.y1);
+        for s in io {
+            let x = *s;
+            y1 = x - x1 + b * y1;
+            x1 = x;
+            *s = kill_denormals(y1);
+        }
+        self.x1 = x1;
+        self.y1 = y1;
+    }
+
     #[inline] pub fn value(&self) -> f32 { self.y1 }
+
+    /// Clear the filter state (the cutoff is kept).
+    #[inline] pub fn reset(&mut self) { self.x1 = 0.0; self.y1 = 0.0; }
 }
 
 /// Convenience DC blocker: a high-pass with a very low cutoff (e.g., 5–30 Hz).
@@ -142,7 +265,10 @@ impl DcBlock {
     #[inline] pub fn set_cutoff_hz(&mut self, hz: f32) { self.hp.set_cutoff_hz(hz); }
 
     #[inline] pub fn process(&mut self, x: f32) -> f32 { self.hp.process(x) }
+    /// Filter `io` in place.
+    #[inline] pub fn process_block(&mut self, io: &mut [f32]) { self.hp.process_block(io); }
     #[inline] pub fn value(&self) -> f32 { self.hp.value() }
+    #[inline] pub fn reset(&mut self) { self.hp.reset(); }
 }
 
 /// SVF output tap selection.
@@ -168,11 +294,12 @@ pub enum SvfMode {
 #[derive(Copy, Clone, Debug)]
 pub struct SvfTpt {
     sr: f32,
-    cut: f32,
+    cut: f32, // cutoff `g` (or its slew target) was computed for
     q: f32,
     // derived
     g: f32,
     r: f32,
+    slew: CoeffSlew, // of `g`
     // states
     ic1eq: f32,
     ic2eq: f32,
@@ -187,6 +314,7 @@ impl SvfTpt {
             q: q.max(1e-4),
             g: 0.0,
             r: 0.0,
+            slew: CoeffSlew::default(),
             ic1eq: 0.0,
             ic2eq: 0.0,
         };
@@ -195,13 +323,38 @@ impl SvfTpt {
     }
 
     #[inline] pub fn set_sample_rate(&mut self, sr: f32) { self.sr = sr.max(1.0); self.recalc(); }
-    #[inline] pub fn set_cutoff_hz(&mut self, cut_hz: f32) { self.cut = cut_hz.max(0.0); self.recalc(); }
-    #[inline] pub fn set_q(&mut self, q: f32) { self.q = q.max(1e-4); self.recalc(); }
+    #[inline] pub fn set_q(&mut self, q: f32) { self.q = q.max(1e-4); self.r = 1.0 / (2.0 * self.q); }
+
+    /// Set the cutoff now (cancels any glide). Moves under [`CUTOFF_EPSILON`]
+    /// keep the current coefficient.
+    #[inline]
+    pub fn set_cutoff_hz(&mut self, cut_hz: f32) {
+        let cut = cut_hz.max(0.0);
+        if cutoff_moved(self.cut, cut) {
+            self.cut = cut;
+            self.recalc();
+        } else if self.slew.left > 0 {
+            self.g = self.slew.target;
+            self.slew.left = 0;
+        }
+    }
+
+    /// Glide `g` linearly to `cut_hz` over the next `samples` processed
+    /// samples (one `tan` per call instead of per sample). Meant to be called
+    /// once per control block with the block length.
+    #[inline]
+    pub fn set_cutoff_smoothed(&mut self, cut_hz: f32, samples: u32) {
+        let cut = cut_hz.max(0.0);
+        if !cutoff_moved(self.cut, cut) { return; }
+        self.cut = cut;
+        self.slew.start(self.g, tpt_g(self.cut, self.sr), samples);
+    }
 
     #[inline]
     fn recalc(&mut self) {
         self.g = tpt_g(self.cut, self.sr);       // tan(π fc / sr)
         self.r = 1.0 / (2.0 * self.q);           // damping
+        self.slew.left = 0;
     }
 
     /// Process one sample, returning the selected mode output.
@@ -215,6 +368,7 @@ impl SvfTpt {
         // v2 = g * v1 + ic2eq
         // ic1eq' = g * v0 + v1
         // ic2eq' = g * v1 + v2
+        self.slew.tick(&mut self.g);
         let v0 = x - self.r * self.ic1eq - self.ic2eq;
         let v1 = self.g * v0 + self.ic1eq;
         let v2 = self.g * v1 + self.ic2eq;
@@ -244,6 +398,40 @@ impl SvfTpt {
         }
     }
 
+    /// Filter `io` in place, keeping the `mode` output.
+    pub fn process_block(&mut self, io: &mut [f32], mode: SvfMode) {
+        let gliding = (self.slew.left as usize).min(io.len());
+        let (head, tail) = io.split_at_mut(gliding);
+        for x in head {
+            *x = self.process(*x, mode);
+        }
+        // one loop per mode keeps the tap choice out of the inner loop
+        match mode {
+            SvfMode::Lowpass => self.run_block(tail, |lp, _, _| lp),
+            SvfMode::Highpass => self.run_block(tail, |_, _, hp| hp),
+            SvfMode::Bandpass => self.run_block(tail, |_, bp, _| bp),
+            SvfMode::Notch => self.run_block(tail, |lp, _, hp| hp + lp),
+        }
+    }
+
+    /// [`SvfTpt::process_all`] over `io` with fixed coefficients; `tap`
+    /// picks the output from `(lp, bp, hp)`.
+    #[inline]
+    fn run_block(&mut self, io: &mut [f32], tap: impl Fn(f32, f32, f32) -> f32) {
+        let (g, r) = (self.g, self.r);
+        let (mut ic1, mut ic2) = (self.ic1eq, self.ic2eq);
+        for x in io {
+            let v0 = *x - r * ic1 - ic2;
+            let v1 = g * v0 + ic1;
+            let v2 = g * v1 + ic2;
+            ic1 = g * v0 + v1;
+            ic2 = g * v1 + v2;
+            *x = tap(v2, v1, v0 - r * v1 - v2);
+        }
+        self.ic1eq = ic1;
+        self.ic2eq = ic2;
+    }
+
     /// Convenience helpers per mode
     #[inline] pub fn process_lp(&mut self, x: f32) -> f32 { self.process(x, SvfMode::Lowpass) }
     #[inline] pub fn process_hp(&mut self, x: f32) -> f32 { self.process(x, SvfMode::Highpass) }
@@ -251,6 +439,260 @@ impl SvfTpt {
     #[inline] pub fn process_notch(&mut self, x: f32) -> f32 { self.process(x, SvfMode::Notch) }
 }
 
+/// Biquad response shape.
+///
+/// `gain_db` only affects `Peak`, `LowShelf`, and `HighShelf`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BiquadKind {
+    Lowpass,
+    Highpass,
+    Bandpass,
+    Notch,
+    Peak,
+    LowShelf,
+    HighShelf,
+}
+
+/// RBJ cookbook biquad in transposed direct form II.
+///
+/// Parameters:
+/// - `freq_hz` : cutoff, center, or shelf midpoint in Hz
+/// - `q`       : quality factor (0.707 = Butterworth / gentle shelf slope)
+/// - `gain_db` : boost/cut for peaking and shelving kinds
+///
+/// Coefficients are recomputed on every setter call; do not sweep them per
+/// sample (use `SvfTpt` for that).
+#[derive(Copy, Clone, Debug)]
+pub struct Biquad {
+    kind: BiquadKind,
+    sr: f32,
+    freq: f32,
+    q: f32,
+    gain_db: f32,
+    // normalized coefficients (a0 = 1)
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    // states
+    z1: f32,
+    z2: f32,
+}
+
+impl Biquad {
+    #[inline]
+    #[must_use]
+    pub fn new(kind: BiquadKind, freq_hz: f32, q: f32, gain_db: f32, sr: f32) -> Self {
+        let mut s = Self {
+            kind,
+            sr: sr.max(1.0),
+            freq: freq_hz.max(0.0),
+            q: q.max(1e-4),
+            gain_db,
+            b0: 1.0,
+            b1: 0.0,
+            b2: 0.0,
+            a1: 0.0,
+            a2: 0.0,
+            z1: 0.0,
+            z2: 0.0,
+        };
+        s.recalc();
+        s
+    }
+
+    #[inline] pub fn set_sample_rate(&mut self, sr: f32) { self.sr = sr.max(1.0); self.recalc(); }
+    #[inline] pub fn set_kind(&mut self, kind: BiquadKind) { self.kind = kind; self.recalc(); }
+    #[inline] pub fn set_freq_hz(&mut self, hz: f32) { self.freq = hz.max(0.0); self.recalc(); }
+    #[inline] pub fn set_q(&mut self, q: f32) { self.q = q.max(1e-4); self.recalc(); }
+    #[inline] pub fn set_gain_db(&mut self, db: f32) { self.gain_db = db; self.recalc(); }
+
+    #[inline] #[must_use] pub fn kind(&self) -> BiquadKind { self.kind }
+    #[inline] #[must_use] pub fn freq_hz(&self) -> f32 { self.freq }
+    #[inline] #[must_use] pub fn gain_db(&self) -> f32 { self.gain_db }
+
+    /// Clear the filter state (coefficients are kept).
+    #[inline]
+    pub fn reset(&mut self) {
+        self.z1 = 0.0;
+        self.z2 = 0.0;
+    }
+
+    fn recalc(&mut self) {
+        // keep the center safely below Nyquist
+        let f = self.freq.clamp(1.0, 0.49 * self.sr);
+        let w0 = core::f32::consts::TAU * f / self.sr;
+        let (sin_w, cos_w) = (m_sin(w0), m_cos(w0));
+        let alpha = sin_w / (2.0 * self.q);
+        let a = m_sqrt(db_to_lin(self.gain_db)); // amplitude for peak/shelves
+
+        let (b0, b1, b2, a0, a1, a2) = match self.kind {
+            BiquadKind::Lowpass => {
+                let b = 0.5 * (1.0 - cos_w);
+                (b, 2.0 * b, b, 1.0 + alpha, -2.0 * cos_w, 1.0 - alpha)
+            }
+            BiquadKind::Highpass => {
+                let b = 0.5 * (1.0 + cos_w);
+                (b, -2.0 * b, b, 1.0 + alpha, -2.0 * cos_w, 1.0 - alpha)
+            }
+            BiquadKind::Bandpass => (alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos_w, 1.0 - alpha),
+            BiquadKind::Notch => (1.0, -2.0 * cos_w, 1.0, 1.0 + alpha, -2.0 * cos_w, 1.0 - alpha),
+            BiquadKind::Peak => (
+                1.0 + alpha * a,
+                -2.0 * cos_w,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos_w,
+                1.0 - alpha / a,
+            ),
+            BiquadKind::LowShelf => {
+                let k = 2.0 * m_sqrt(a) * alpha;
+                (
+                    a * ((a + 1.0) - (a - 1.0) * cos_w + k),
+                    2.0 * a * ((a - 1.0) - (a + 1.0) * cos_w),
+                    a * ((a + 1.0) - (a - 1.0) * cos_w - k),
+                    (a + 1.0) + (a - 1.0) * cos_w + k,
+                    -2.0 * ((a - 1.0) + (a + 1.0) * cos_w),
+                    (a + 1.0) + (a - 1.0) * cos_w - k,
+                )
+            }
+            BiquadKind::HighShelf => {
+                let k = 2.0 * m_sqrt(a) * alpha;
+                (
+                    a * ((a + 1.0) + (a - 1.0) * cos_w + k),
+                    -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w),
+                    a * ((a + 1.0) + (a - 1.0) * cos_w - k),
+                    (a + 1.0) - (a - 1.0) * cos_w + k,
+                    2.0 * ((a - 1.0) - (a + 1.0) * cos_w),
+                    (a + 1.0) - (a - 1.0) * cos_w - k,
+                )
+            }
+        };
+        let inv = 1.0 / a0;
+        self.b0 = b0 * inv;
+        self.b1 = b1 * inv;
+        self.b2 = b2 * inv;
+        self.a1 = a1 * inv;
+        self.a2 = a2 * inv;
+    }
+
+    /// Process one sample.
+    #[inline]
+    pub fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.z1;
+        self.z1 = kill_denormals(self.b1 * x - self.a1 * y + self.z2);
+        self.z2 = kill_denormals(self.b2 * x - self.a2 * y);
+        y
+    }
+}
+
+/// 2-way Linkwitz–Riley crossover (24 dB/oct, LR4).
+///
+/// Each band is two Butterworth biquads in series, so both are -6 dB at the
+/// crossover frequency and in phase with each other there; `low + high` is an
+/// allpass of the input (flat magnitude), which is what lets bands be
+/// processed separately and summed back.
+#[derive(Copy, Clone, Debug)]
+pub struct Crossover {
+    lp: [Biquad; 2],
+    hp: [Biquad; 2],
+}
+
+impl Crossover {
+    #[must_use]
+    pub fn new(freq_hz: f32, sr: f32) -> Self {
+        let q = core::f32::consts::FRAC_1_SQRT_2;
+        Self {
+            lp: [Biquad::new(BiquadKind::Lowpass, freq_hz, q, 0.0, sr); 2],
+            hp: [Biquad::new(BiquadKind::Highpass, freq_hz, q, 0.0, sr); 2],
+        }
+    }
+
+    #[inline]
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        for f in self.lp.iter_mut().chain(&mut self.hp) { f.set_sample_rate(sr); }
+    }
+
+    /// Crossover frequency in Hz. Each call recomputes four biquads, so
+    /// modulate it at control rate.
+    #[inline]
+    pub fn set_freq_hz(&mut self, hz: f32) {
+        if !cutoff_moved(self.freq_hz(), hz.max(0.0)) { return; }
+        for f in self.lp.iter_mut().chain(&mut self.hp) { f.set_freq_hz(hz); }
+    }
+    #[inline] #[must_use] pub fn freq_hz(&self) -> f32 { self.lp[0].freq_hz() }
+
+    #[inline]
+    pub fn reset(&mut self) {
+        for f in self.lp.iter_mut().chain(&mut self.hp) { f.reset(); }
+    }
+
+    /// Split one sample into `(low, high)`.
+    #[inline]
+    pub fn split(&mut self, x: f32) -> (f32, f32) {
+        let [l1, l2] = &mut self.lp;
+        let [h1, h2] = &mut self.hp;
+        (l2.process(l1.process(x)), h2.process(h1.process(x)))
+    }
+}
+
+/// 3-way Linkwitz–Riley crossover: `low | mid | high` at two frequencies.
+///
+/// The input is split at `lo_hz`, then the upper part again at `hi_hz`. The
+/// low band also goes through the allpass of the upper split (a second
+/// crossover at `hi_hz`, summed), so all three bands share one phase response
+/// and still sum back flat.
+#[derive(Copy, Clone, Debug)]
+pub struct Crossover3 {
+    lo: Crossover,
+    hi: Crossover,
+    low_ap: Crossover, // allpass for the low band, matching `hi`
+}
+
+impl Crossover3 {
+    /// `lo_hz` and `hi_hz` are swapped if given backwards.
+    #[must_use]
+    pub fn new(lo_hz: f32, hi_hz: f32, sr: f32) -> Self {
+        let (lo_hz, hi_hz) = (lo_hz.min(hi_hz), lo_hz.max(hi_hz));
+        Self { lo: Crossover::new(lo_hz, sr), hi: Crossover::new(hi_hz, sr), low_ap: Crossover::new(hi_hz, sr) }
+    }
+
+    #[inline]
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        self.lo.set_sample_rate(sr);
+        self.hi.set_sample_rate(sr);
+        self.low_ap.set_sample_rate(sr);
+    }
+
+    /// Both crossover frequencies in Hz (swapped if given backwards).
+    #[inline]
+    pub fn set_freqs_hz(&mut self, lo_hz: f32, hi_hz: f32) {
+        self.lo.set_freq_hz(lo_hz.min(hi_hz));
+        self.hi.set_freq_hz(lo_hz.max(hi_hz));
+        self.low_ap.set_freq_hz(lo_hz.max(hi_hz));
+    }
+
+    /// `(lo_hz, hi_hz)`.
+    #[inline] #[must_use] pub fn freqs_hz(&self) -> (f32, f32) { (self.lo.freq_hz(), self.hi.freq_hz()) }
+
+    #[inline]
+    pub fn reset(&mut self) {
+        self.lo.reset();
+        self.hi.reset();
+        self.low_ap.reset();
+    }
+
+    /// Split one sample into `(low, mid, high)`.
+    #[inline]
+    pub fn split(&mut self, x: f32) -> (f32, f32, f32) {
+        let (low, rest) = self.lo.split(x);
+        let (mid, high) = self.hi.split(rest);
+        let (a, b) = self.low_ap.split(low);
+        (a + b, mid, high)
+    }
+}
+
 // ------------------------------------ Tests --------------------------------------
 
 #[cfg(test)]
@@ -290,4 +732,152 @@ mod tests {
         }
         assert!(acc <= 2.0, "svf runaway? {}", acc);
     }
+
+    #[test]
+    fn tiny_cutoff_moves_keep_the_coefficient() {
+        let mut lp = OnePoleLP::new(1000.0, 48000.0);
+        let a = lp.a;
+        lp.set_cutoff_hz(1000.5);
+        assert_eq!(lp.a.to_bits(), a.to_bits());
+        lp.set_cutoff_hz(1100.0);
+        assert!(lp.a > a);
+        let mut svf = SvfTpt::new(1000.0, 0.707, 48000.0);
+        let g = svf.g;
+        svf.set_cutoff_hz(999.5);
+        assert_eq!(svf.g.to_bits(), g.to_bits());
+        // drift accumulates against the cached cutoff, so it can't creep away
+        let mut cut = 1000.0;
+        for _ in 0..100 {
+            cut += 0.5;
+            svf.set_cutoff_hz(cut);
+        }
+        assert!(svf.g > g);
+    }
+
+    #[test]
+    fn smoothed_cutoff_lands_on_the_target_coefficient() {
+        let sr = 48000.0;
+        let mut lp = OnePoleLP::new(500.0, sr);
+        let mut svf = SvfTpt::new(500.0, 0.707, sr);
+        lp.set_cutoff_smoothed(2000.0, 32);
+        svf.set_cutoff_smoothed(2000.0, 32);
+        let (a0, g0) = (lp.a, svf.g);
+        lp.process(0.0);
+        svf.process_lp(0.0);
+        assert!(lp.a > a0 && svf.g > g0, "glides start at once");
+        for _ in 1..32 {
+            lp.process(0.0);
+            svf.process_lp(0.0);
+        }
+        assert_eq!(lp.a.to_bits(), OnePoleLP::new(2000.0, sr).a.to_bits());
+        assert_eq!(svf.g.to_bits(), SvfTpt::new(2000.0, 0.707, sr).g.to_bits());
+    }
+
+    #[test]
+    fn process_block_matches_per_sample() {
+        let sr = 48000.0;
+        let mut input = [0.25f32; 200];
+        let mut ph = 0.0;
+        for x in &mut input {
+            *x += m_sin(ph);
+            ph += 0.37;
+        }
+        let mut lp = (OnePoleLP::new(300.0, sr), OnePoleLP::new(300.0, sr));
+        let mut hp = (OnePoleHP::new(40.0, sr), OnePoleHP::new(40.0, sr));
+        let mut svf = (SvfTpt::new(800.0, 2.0, sr), SvfTpt::new(800.0, 2.0, sr));
+        // start glides so the block path covers the gliding head too
+        lp.0.set_cutoff_smoothed(2000.0, 48);
+        lp.1.set_cutoff_smoothed(2000.0, 48);
+        svf.0.set_cutoff_smoothed(3000.0, 48);
+        svf.1.set_cutoff_smoothed(3000.0, 48);
+        for mode in [SvfMode::Lowpass, SvfMode::Highpass, SvfMode::Bandpass, SvfMode::Notch] {
+            let (mut a, mut b, mut c) = (input, input, input);
+            lp.0.process_block(&mut a);
+            hp.0.process_block(&mut b);
+            svf.0.process_block(&mut c, mode);
+            for (i, &x) in input.iter().enumerate() {
+                assert_eq!(a[i].to_bits(), lp.1.process(x).to_bits(), "lp sample {i}");
+                assert_eq!(b[i].to_bits(), hp.1.process(x).to_bits(), "hp sample {i}");
+                assert_eq!(c[i].to_bits(), svf.1.process(x, mode).to_bits(), "svf {mode:?} sample {i}");
+            }
+        }
+    }
+
+    /// Steady-state amplitude of a sine at `hz` through `f` (one second at 48 kHz).
+    fn sine_gain(f: &mut Biquad, hz: f32) -> f32 { sine_peak(hz, |x| f.process(x)) }
+
+    /// Steady-state peak of a sine at `hz` through `f` (one second at 48 kHz).
+    fn sine_peak(hz: f32, mut f: impl FnMut(f32) -> f32) -> f32 {
+        let inc = core::f32::consts::TAU * hz / 48000.0;
+        let (mut ph, mut peak) = (0.0f32, 0.0f32);
+        for n in 0..48_000 {
+            let y = f(m_sin(ph));
+            ph = (ph + inc) % core::f32::consts::TAU;
+            if n > 24_000 {
+                peak = peak.max(y.abs());
+            }
+        }
+        peak
+    }
+
+    #[test]
+    fn biquad_peak_boosts_center_only() {
+        let sr = 48000.0;
+        let mut f = Biquad::new(BiquadKind::Peak, 1000.0, 1.0, 6.0, sr);
+        let g = sine_gain(&mut f, 1000.0);
+        assert!((g - db_to_lin(6.0)).abs() < 0.05, "center gain {g}");
+        f.reset();
+        let g = sine_gain(&mut f, 50.0);
+        assert!((g - 1.0).abs() < 0.05, "far gain {g}");
+    }
+
+    #[test]
+    fn crossover_bands_meet_at_minus_6_db_and_sum_flat() {
+        let sr = 48000.0;
+        let mut x = Crossover::new(500.0, sr);
+        let lo = sine_peak(500.0, |s| x.split(s).0);
+        x.reset();
+        let hi = sine_peak(500.0, |s| x.split(s).1);
+        assert!((lo - 0.5).abs() < 0.01 && (hi - 0.5).abs() < 0.01, "lo {lo} hi {hi}");
+        for hz in [50.0, 300.0, 500.0, 800.0, 5000.0] {
+            x.reset();
+            let g = sine_peak(hz, |s| {
+                let (l, h) = x.split(s);
+                l + h
+            });
+            assert!((g - 1.0).abs() < 0.01, "sum at {hz} Hz: {g}");
+        }
+        x.reset();
+        let stop = sine_peak(5000.0, |s| x.split(s).0);
+        assert!(stop < 0.001, "low band at 5 kHz: {stop}");
+    }
+
+    #[test]
+    fn crossover3_sums_flat_and_separates() {
+        let sr = 48000.0;
+        let mut x = Crossover3::new(2000.0, 200.0, sr);
+        assert_eq!(x.freqs_hz(), (200.0, 2000.0));
+        for hz in [40.0, 200.0, 600.0, 2000.0, 7000.0] {
+            x.reset();
+            let g = sine_peak(hz, |s| {
+                let (l, m, h) = x.split(s);
+                l + m + h
+            });
+            assert!((g - 1.0).abs() < 0.01, "sum at {hz} Hz: {g}");
+        }
+        x.reset();
+        let mid = sine_peak(630.0, |s| x.split(s).1);
+        assert!(mid > 0.9, "mid band at 630 Hz: {mid}");
+    }
+
+    #[test]
+    fn biquad_shelves_hit_their_gain() {
+        let sr = 48000.0;
+        let mut lo = Biquad::new(BiquadKind::LowShelf, 200.0, 0.707, -6.0, sr);
+        let g = sine_gain(&mut lo, 30.0);
+        assert!((g - db_to_lin(-6.0)).abs() < 0.05, "low shelf {g}");
+        let mut hi = Biquad::new(BiquadKind::HighShelf, 4000.0, 0.707, 6.0, sr);
+        let g = sine_gain(&mut hi, 15000.0);
+        assert!((g - db_to_lin(6.0)).abs() < 0.1, "high shelf {g}");
+    }
 }
@@ -8,8 +8,8 @@
 //! - `simd`     : enable portable SIMD helper code paths (wide/safe_arch)
 //!
 //! Modules
-//! - [`dsp`]       : math backend, utils (db/lin, smoothing, fast trig, meters)
-//! - [`envelopes`] : ADSR (linear/exp), AR, slew limiter
+//! - [`dsp`]       : math backend, utils (db/lin, smoothing, fast trig, meters, scales, tuning)
+//! - [`envelopes`] : ADSR (linear/exp), AR, breakpoint envelope, slew limiter
 //! - [`filters`]   : one-pole LP/HP/DC blocker, TPT SVF
 //!
 //! Design
@@ -25,10 +25,10 @@ pub mod filters;
 pub mod prelude {
     pub use crate::dsp::{
         clamp, db_to_lin, kill_denormals, lerp, lin_to_db, one_pole_coeff_hz, one_pole_coeff_ms,
-        soft_clip, tpt_g, TAU,
+        quantize_to_scale, soft_clip, tpt_g, Scale, Tuning, TAU,
     };
-    pub use crate::envelopes::{AdsrExp, AdsrLinear, ArExp, SlewLimiter};
-    pub use crate::filters::{DcBlock, OnePoleHP, OnePoleLP, SvfMode, SvfTpt};
+    pub use crate::envelopes::{AdsrExp, AdsrLinear, AdsrTrigger, ArExp, Breakpoint, BreakpointEnv, SlewLimiter};
+    pub use crate::filters::{Biquad, BiquadKind, DcBlock, OnePoleHP, OnePoleLP, SvfMode, SvfTpt};
 }
 
 #[cfg(test)]
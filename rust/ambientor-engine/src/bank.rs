@@ -0,0 +1,141 @@
+//! Several independent engines mixed into one stereo output.
+//!
+//! An [`EngineBank`] suits hosts that run more than one soundscape at a time,
+//! e.g. a game crossfading between the ambiences of neighbouring areas. Each
+//! engine keeps its own scene, master bus and state; the bank adds per-engine
+//! gain (with ramps) and stereo balance, sums them, and runs the sum
+//! through a shared [`Limiter`].
+//!
+//! Engines whose gain has ramped to zero are paused rather than rendered, so
+//! a faded-out area costs nothing until it is faded back in (it resumes where
+//! it stopped).
+
+use crate::effects::Limiter;
+use crate::graph::{Engine, Generator};
+use crate::nodes::{ControlRamp, PanLaw};
+
+/// Most engines one bank holds.
+pub const MAX_BANK_ENGINES: usize = 16;
+
+struct Slot<G: Generator> {
+    engine: Engine<G>,
+    gain: ControlRamp,
+    pan: f32,
+    gl: f32,
+    gr: f32,
+}
+
+impl<G: Generator> Slot<G> {
+    /// Faded out and not heading back up: skip rendering.
+    #[inline]
+    fn paused(&self) -> bool { self.gain.value() <= 0.0 && self.gain.target() <= 0.0 }
+}
+
+/// Owns up to [`MAX_BANK_ENGINES`] engines and renders their mix.
+///
+/// Engines are addressed by the id [`EngineBank::add`] returns; ids stay valid
+/// until the engine is removed, after which the id may be reused.
+pub struct EngineBank<G: Generator> {
+    sr: f32,
+    slots: Vec<Option<Slot<G>>>,
+    limiter: Limiter,
+}
+
+impl<G: Generator> EngineBank<G> {
+    pub fn new(sr: f32) -> Self {
+        let sr = sr.max(1.0);
+        Self { sr, slots: Vec::with_capacity(MAX_BANK_ENGINES), limiter: Limiter::new(sr) }
+    }
+
+    /// Add `engine` at full gain, centered; `None` if the bank is full.
+    pub fn add(&mut self, mut engine: Engine<G>) -> Option<usize> {
+        engine.set_sample_rate(self.sr);
+        let slot = Slot { engine, gain: ControlRamp::new(1.0), pan: 0.0, gl: 1.0, gr: 1.0 };
+        if let Some(id) = self.slots.iter().position(Option::is_none) {
+            self.slots[id] = Some(slot);
+            return Some(id);
+        }
+        if self.slots.len() == MAX_BANK_ENGINES { return None; }
+        self.slots.push(Some(slot));
+        Some(self.slots.len() - 1)
+    }
+
+    /// Take engine `id` out of the bank.
+    pub fn remove(&mut self, id: usize) -> Option<Engine<G>> {
+        let slot = self.slots.get_mut(id)?.take()?;
+        while self.slots.last().is_some_and(Option::is_none) { self.slots.pop(); }
+        Some(slot.engine)
+    }
+
+    /// Engines in the bank.
+    pub fn len(&self) -> usize { self.slots.iter().flatten().count() }
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    pub fn engine_mut(&mut self, id: usize) -> Option<&mut Engine<G>> {
+        self.slots.get_mut(id)?.as_mut().map(|s| &mut s.engine)
+    }
+
+    /// Ramp engine `id` to linear `gain` (0..4) over `ramp_s` seconds (0 jumps).
+    /// Returns `false` for an unknown id.
+    pub fn set_gain(&mut self, id: usize, gain: f32, ramp_s: f32) -> bool {
+        let Some(Some(s)) = self.slots.get_mut(id) else { return false; };
+        let gain = gain.clamp(0.0, 4.0);
+        let samples = (ramp_s.max(0.0) * self.sr) as u32;
+        if samples == 0 { s.gain.snap(gain); } else { s.gain.set_target(gain, samples); }
+        true
+    }
+
+    /// Gain engine `id` is heading for.
+    pub fn gain(&self, id: usize) -> Option<f32> {
+        self.slots.get(id)?.as_ref().map(|s| s.gain.target())
+    }
+
+    /// Constant-power balance of engine `id`'s stereo output: -1 keeps only its
+    /// left channel (+3 dB), 0 passes it unchanged, +1 keeps only the right.
+    /// Returns `false` for an unknown id.
+    pub fn set_pan(&mut self, id: usize, pan: f32) -> bool {
+        let Some(Some(s)) = self.slots.get_mut(id) else { return false; };
+        s.pan = pan.clamp(-1.0, 1.0);
+        // unity at center, so a centered engine passes through unchanged
+        let (l, r) = PanLaw::gains(s.pan);
+        (s.gl, s.gr) = (l * core::f32::consts::SQRT_2, r * core::f32::consts::SQRT_2);
+        true
+    }
+
+    pub fn pan(&self, id: usize) -> Option<f32> {
+        self.slots.get(id)?.as_ref().map(|s| s.pan)
+    }
+
+    /// Limiter on the summed output.
+    pub fn limiter_mut(&mut self) -> &mut Limiter { &mut self.limiter }
+    pub fn limiter(&self) -> &Limiter { &self.limiter }
+
+    /// Change the sample rate of the bank and every engine in it.
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        self.sr = sr.max(1.0);
+        self.limiter.set_sample_rate(self.sr);
+        for s in self.slots.iter_mut().flatten() { s.engine.set_sample_rate(self.sr); }
+    }
+    #[inline] pub fn sample_rate(&self) -> f32 { self.sr }
+
+    /// Next mixed stereo frame.
+    #[inline]
+    pub fn next_stereo(&mut self) -> (f32, f32) {
+        let (mut l, mut r) = (0.0, 0.0);
+        for s in self.slots.iter_mut().flatten() {
+            if s.paused() { continue; }
+            let g = s.gain.next_sample();
+            let (a, b) = s.engine.next_stereo(self.sr);
+            l += a * g * s.gl;
+            r += b * g * s.gr;
+        }
+        self.limiter.process(l, r)
+    }
+
+    /// Render into two planar buffers (as many frames as the shorter holds).
+    pub fn render_stereo(&mut self, left: &mut [f32], right: &mut [f32]) {
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            (*l, *r) = self.next_stereo();
+        }
+    }
+}
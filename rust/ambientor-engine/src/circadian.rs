@@ -0,0 +1,180 @@
+//! Day/night program driver.
+//!
+//! A [`Circadian`] schedule holds keyframes over a repeating period (24 h by
+//! default) and eases between them, so an always-on installation can get
+//! brighter towards noon and darker at night without anyone touching it.
+//!
+//! Keyframes set the scene **intensity** (see [`crate::scenes::intensity`]) and
+//! the master stereo **width**. The schedule is a fixed-size table (no heap);
+//! advance it from the audio or control thread and [`Circadian::apply`] the
+//! result every block or so — scene intensity glides on its own, so coarse
+//! updates are fine.
+
+use crate::graph::Engine;
+use crate::scenes::Scene;
+
+/// Maximum keyframes in a [`Circadian`] schedule.
+pub const CIRCADIAN_MAX_KEYS: usize = 24;
+
+/// Seconds in a day, the default schedule period.
+pub const DAY_S: f32 = 86_400.0;
+
+/// One point of the schedule.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CircadianKey {
+    /// Position within the period, in seconds.
+    pub at_s: f32,
+    /// Scene intensity 0..1 (0.5 = as designed).
+    pub intensity: f32,
+    /// Master stereo width factor 0..2 (1 = unchanged).
+    pub width: f32,
+}
+
+impl CircadianKey {
+    pub const fn new(at_s: f32, intensity: f32, width: f32) -> Self { Self { at_s, intensity, width } }
+
+    /// Keyframe at `hour` (0..24) of a day.
+    pub fn at_hour(hour: f32, intensity: f32, width: f32) -> Self { Self::new(hour * 3600.0, intensity, width) }
+}
+
+/// Looping keyframe schedule with cosine easing between neighbors.
+///
+/// The schedule wraps: between the last key and the first key of the next
+/// period it eases across midnight. With a single key the output is constant.
+#[derive(Copy, Clone, Debug)]
+pub struct Circadian {
+    keys: [CircadianKey; CIRCADIAN_MAX_KEYS],
+    len: usize,
+    period_s: f32,
+    pos_s: f64, // f64: runs for days
+}
+
+impl Circadian {
+    /// Empty schedule repeating every `period_s` seconds (output: intensity 0.5, width 1).
+    pub fn new(period_s: f32) -> Self {
+        Self {
+            keys: [CircadianKey::new(0.0, 0.5, 1.0); CIRCADIAN_MAX_KEYS],
+            len: 0,
+            period_s: period_s.max(1.0),
+            pos_s: 0.0,
+        }
+    }
+
+    /// A gentle default day: quiet and narrow at night, fullest in the early afternoon.
+    pub fn default_day() -> Self {
+        let mut c = Self::new(DAY_S);
+        for (h, i, w) in [
+            (0.0, 0.2, 0.8),
+            (6.0, 0.3, 0.9),
+            (9.0, 0.5, 1.0),
+            (14.0, 0.7, 1.2),
+            (19.0, 0.5, 1.1),
+            (22.0, 0.3, 0.9),
+        ] {
+            c.push(CircadianKey::at_hour(h, i, w));
+        }
+        c
+    }
+
+    /// Parse `H[:MM]=intensity[/width]` entries separated by commas, over a 24 h
+    /// day, e.g. `0=0.2,7:30=0.5,13=0.8/1.3,21=0.3`. Width defaults to 1.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut c = Self::new(DAY_S);
+        for tok in spec.split(',') {
+            let (time, vals) = tok.trim().split_once('=')?;
+            let (h, m) = match time.split_once(':') {
+                Some((h, m)) => (h.trim().parse::<f32>().ok()?, m.trim().parse::<f32>().ok()?),
+                None => (time.trim().parse::<f32>().ok()?, 0.0),
+            };
+            let (i, w) = match vals.split_once('/') {
+                Some((i, w)) => (i.trim().parse::<f32>().ok()?, w.trim().parse::<f32>().ok()?),
+                None => (vals.trim().parse::<f32>().ok()?, 1.0),
+            };
+            if !(0.0..24.0).contains(&h) || !(0.0..60.0).contains(&m) || !i.is_finite() || !w.is_finite() {
+                return None;
+            }
+            if !c.push(CircadianKey::at_hour(h + m / 60.0, i, w)) {
+                return None;
+            }
+        }
+        Some(c)
+    }
+
+    /// Insert a keyframe (kept sorted; a key at the same time is replaced).
+    /// Values are clamped to their ranges. Returns `false` when the table is full.
+    pub fn push(&mut self, key: CircadianKey) -> bool {
+        let key = CircadianKey {
+            at_s: key.at_s.rem_euclid(self.period_s),
+            intensity: key.intensity.clamp(0.0, 1.0),
+            width: key.width.clamp(0.0, 2.0),
+        };
+        let idx = self.keys[..self.len].partition_point(|k| k.at_s < key.at_s);
+        if idx < self.len && self.keys[idx].at_s == key.at_s {
+            self.keys[idx] = key;
+            return true;
+        }
+        if self.len >= CIRCADIAN_MAX_KEYS {
+            return false;
+        }
+        self.keys.copy_within(idx..self.len, idx + 1);
+        self.keys[idx] = key;
+        self.len += 1;
+        true
+    }
+
+    #[inline] pub fn clear(&mut self) { self.len = 0; }
+    #[inline] pub fn len(&self) -> usize { self.len }
+    #[inline] pub fn is_empty(&self) -> bool { self.len == 0 }
+    #[inline] pub fn keys(&self) -> &[CircadianKey] { &self.keys[..self.len] }
+    #[inline] pub fn period_s(&self) -> f32 { self.period_s }
+
+    /// Jump to `t_s` seconds into the period (e.g. seconds since local midnight).
+    #[inline]
+    pub fn set_time_s(&mut self, t_s: f64) {
+        self.pos_s = t_s.rem_euclid(f64::from(self.period_s));
+    }
+
+    /// Current position within the period, in seconds.
+    #[inline] pub fn time_s(&self) -> f64 { self.pos_s }
+
+    /// Move forward by `dt_s` seconds (wraps at the period).
+    #[inline]
+    pub fn advance(&mut self, dt_s: f64) {
+        self.set_time_s(self.pos_s + dt_s.max(0.0));
+    }
+
+    /// Interpolated keyframe at `t_s` (its `at_s` is `t_s` wrapped into the period).
+    pub fn value_at(&self, t_s: f32) -> CircadianKey {
+        let t = t_s.rem_euclid(self.period_s);
+        if self.len == 0 {
+            return CircadianKey::new(t, 0.5, 1.0);
+        }
+        // last key at or before t; before the first key we are still in the
+        // segment that started at the previous period's last key
+        let n = self.keys[..self.len].partition_point(|k| k.at_s <= t);
+        let a = self.keys[if n == 0 { self.len - 1 } else { n - 1 }];
+        let b = self.keys[n % self.len];
+
+        let seg = (b.at_s - a.at_s).rem_euclid(self.period_s);
+        let seg = if seg == 0.0 { self.period_s } else { seg };
+        let frac = ((t - a.at_s).rem_euclid(self.period_s) / seg).clamp(0.0, 1.0);
+        let w = 0.5 - 0.5 * (core::f32::consts::PI * frac).cos();
+        CircadianKey::new(
+            t,
+            a.intensity + (b.intensity - a.intensity) * w,
+            a.width + (b.width - a.width) * w,
+        )
+    }
+
+    /// Interpolated keyframe at the current position.
+    #[inline]
+    pub fn current(&self) -> CircadianKey { self.value_at(self.pos_s as f32) }
+
+    /// Push the current values into an engine (scene intensity + master width).
+    #[inline]
+    pub fn apply(&self, engine: &mut Engine<Scene>) {
+        let k = self.current();
+        engine.scene_mut().set_intensity(k.intensity);
+        engine.master_mut().set_width_percent(100.0 * k.width);
+    }
+}
@@ -0,0 +1,264 @@
+//! Control commands for the audio thread.
+//!
+//! A [`command_queue`] is a lock-free single-producer/single-consumer ring of
+//! [`Command`]s: control code (scripts, remotes, UIs) pushes, and the audio
+//! callback drains it with [`CommandReceiver::apply_all`] before rendering.
+//! Commands travel as atomic words, like the `stream` sample ring, so neither
+//! side locks or allocates.
+
+use crate::graph::Engine;
+use crate::log;
+use crate::params::ParamId;
+use crate::scenes::graph_scene::{NodeId, Param};
+use crate::scenes::Scene;
+use ambientor_core::dsp::Tuning;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// Default queue depth in commands.
+pub const DEFAULT_CAPACITY: usize = 256;
+
+/// Graph node names by id, for resolving `"node.param"` targets. Shared so a
+/// scene reload can update it under a running script.
+pub type NodeNames = Arc<RwLock<Vec<String>>>;
+
+/// One parameter change or event for the engine.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Command {
+    /// Scene intensity 0..1.
+    SetIntensity(f32),
+    /// Master stereo width in percent.
+    SetWidth(f32),
+    /// Master tilt tone -1 (dark) .. +1 (bright).
+    SetTone(f32),
+    SetReverbReturn(f32),
+    SetDelayReturn(f32),
+    /// Switch the shared reverb off (tail fades out) or back on.
+    SetReverbBypass(bool),
+    /// Retune the scene to A4 = this many Hz.
+    SetTuningRef(f32),
+    /// Restart the engine's fade-in over this many seconds.
+    FadeIn(f32),
+    /// Graph scenes: base value of `param` on `node` (ignored by other scenes).
+    SetParam { node: NodeId, param: Param, value: f32 },
+    /// Any host parameter by id (see [`Engine::set_param`]).
+    SetHostParam { id: ParamId, value: f32 },
+}
+
+const WORDS: usize = 3; // tag, node << 8 | param, value bits
+
+impl Command {
+    fn encode(self) -> [u32; WORDS] {
+        let (tag, arg, v) = match self {
+            Command::SetIntensity(v) => (0, 0, v),
+            Command::SetWidth(v) => (1, 0, v),
+            Command::SetTone(v) => (2, 0, v),
+            Command::SetReverbReturn(v) => (3, 0, v),
+            Command::SetDelayReturn(v) => (4, 0, v),
+            Command::SetTuningRef(v) => (5, 0, v),
+            Command::FadeIn(v) => (6, 0, v),
+            Command::SetParam { node, param, value } => (7, (node as u32) << 8 | param as u32, value),
+            Command::SetReverbBypass(on) => (8, u32::from(on), 0.0),
+            Command::SetHostParam { id, value } => (9, id as u32, value),
+        };
+        [tag, arg, v.to_bits()]
+    }
+
+    fn decode(w: [u32; WORDS]) -> Option<Self> {
+        let v = f32::from_bits(w[2]);
+        Some(match w[0] {
+            0 => Command::SetIntensity(v),
+            1 => Command::SetWidth(v),
+            2 => Command::SetTone(v),
+            3 => Command::SetReverbReturn(v),
+            4 => Command::SetDelayReturn(v),
+            5 => Command::SetTuningRef(v),
+            6 => Command::FadeIn(v),
+            7 => Command::SetParam {
+                node: (w[1] >> 8) as NodeId,
+                param: *Param::ALL.get((w[1] & 0xff) as usize)?,
+                value: v,
+            },
+            8 => Command::SetReverbBypass(w[1] != 0),
+            9 => Command::SetHostParam { id: ParamId::from_u32(w[1])?, value: v },
+            _ => return None,
+        })
+    }
+
+    /// Carry out the command on `engine`. Non-finite values are ignored.
+    pub fn apply(self, engine: &mut Engine<Scene>) {
+        let v = match self {
+            Command::SetIntensity(v)
+            | Command::SetWidth(v)
+            | Command::SetTone(v)
+            | Command::SetReverbReturn(v)
+            | Command::SetDelayReturn(v)
+            | Command::SetTuningRef(v)
+            | Command::FadeIn(v)
+            | Command::SetParam { value: v, .. }
+            | Command::SetHostParam { value: v, .. } => v,
+            Command::SetReverbBypass(_) => 0.0,
+        };
+        if !v.is_finite() {
+            log::warn(format_args!("ignoring {self:?}: value is not finite"));
+            return;
+        }
+        match self {
+            Command::SetIntensity(x) => engine.scene_mut().set_intensity(x),
+            Command::SetWidth(pct) => engine.master_mut().set_width_percent(pct),
+            Command::SetTone(t) => engine.master_mut().set_tone(t),
+            Command::SetReverbReturn(g) => engine.master_mut().set_reverb_return(g),
+            Command::SetDelayReturn(g) => engine.master_mut().set_delay_return(g),
+            Command::SetReverbBypass(on) => engine.master_mut().set_reverb_bypass(on),
+            Command::SetTuningRef(hz) => engine.scene_mut().set_tuning(Tuning::with_ref_hz(hz)),
+            Command::FadeIn(s) => engine.fade_in(s),
+            Command::SetParam { node, param, value } => {
+                if let Scene::Graph(g) = engine.scene_mut() {
+                    if let Err(e) = g.set_param(node, param, value) {
+                        log::warn(format_args!("ignoring {self:?}: {e}"));
+                    }
+                }
+            }
+            Command::SetHostParam { id, value } => {
+                if !engine.set_param(id, value) {
+                    log::warn(format_args!("ignoring {self:?}: the scene has no {}", id.name()));
+                }
+            }
+        }
+    }
+}
+
+struct Queue {
+    words: Box<[AtomicU32]>, // WORDS per slot
+    head: AtomicUsize,       // total commands pushed
+    tail: AtomicUsize,       // total commands popped
+}
+
+impl Queue {
+    #[inline] fn capacity(&self) -> usize { self.words.len() / WORDS }
+}
+
+/// Create a queue holding up to `capacity` commands.
+pub fn command_queue(capacity: usize) -> (CommandSender, CommandReceiver) {
+    let q = Arc::new(Queue {
+        words: (0..capacity.max(1) * WORDS).map(|_| AtomicU32::new(0)).collect(),
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0),
+    });
+    (CommandSender { q: Arc::clone(&q) }, CommandReceiver { q })
+}
+
+/// Pushing side of a [`command_queue`]; move it to the control thread.
+pub struct CommandSender {
+    q: Arc<Queue>,
+}
+
+impl CommandSender {
+    #[inline] pub fn capacity(&self) -> usize { self.q.capacity() }
+
+    /// Commands pushed but not yet applied.
+    #[inline]
+    pub fn pending(&self) -> usize {
+        self.q.head.load(Ordering::Relaxed).wrapping_sub(self.q.tail.load(Ordering::Acquire))
+    }
+
+    /// Queue `cmd`; `false` (and nothing queued) if the queue is full.
+    pub fn push(&mut self, cmd: Command) -> bool {
+        if self.pending() >= self.capacity() { return false; }
+        let head = self.q.head.load(Ordering::Relaxed);
+        let base = (head % self.capacity()) * WORDS;
+        for (i, w) in cmd.encode().into_iter().enumerate() {
+            self.q.words[base + i].store(w, Ordering::Relaxed);
+        }
+        self.q.head.store(head.wrapping_add(1), Ordering::Release);
+        true
+    }
+}
+
+/// Draining side of a [`command_queue`]; safe to use from the audio callback.
+pub struct CommandReceiver {
+    q: Arc<Queue>,
+}
+
+impl CommandReceiver {
+    /// Next queued command, oldest first.
+    pub fn pop(&mut self) -> Option<Command> {
+        loop {
+            let tail = self.q.tail.load(Ordering::Relaxed);
+            if self.q.head.load(Ordering::Acquire) == tail { return None; }
+            let base = (tail % self.q.capacity()) * WORDS;
+            let w = core::array::from_fn(|i| self.q.words[base + i].load(Ordering::Relaxed));
+            self.q.tail.store(tail.wrapping_add(1), Ordering::Release);
+            if let Some(cmd) = Command::decode(w) { return Some(cmd); }
+        }
+    }
+
+    /// Apply every queued command to `engine`; returns how many there were.
+    pub fn apply_all(&mut self, engine: &mut Engine<Scene>) -> usize {
+        let mut n = 0;
+        while let Some(cmd) = self.pop() {
+            cmd.apply(engine);
+            n += 1;
+        }
+        n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn every_command() -> Vec<Command> {
+        let mut all = vec![
+            Command::SetIntensity(0.4),
+            Command::SetWidth(140.0),
+            Command::SetTone(-0.3),
+            Command::SetReverbReturn(0.8),
+            Command::SetDelayReturn(0.2),
+            Command::SetReverbBypass(true),
+            Command::SetReverbBypass(false),
+            Command::SetTuningRef(432.0),
+            Command::FadeIn(2.5),
+        ];
+        all.extend(Param::ALL.iter().enumerate().map(|(i, &param)| Command::SetParam { node: 3 * i + 1, param, value: i as f32 }));
+        all.extend(ParamId::ALL.iter().map(|&id| Command::SetHostParam { id, value: -1.5 }));
+        all
+    }
+
+    #[test]
+    fn commands_survive_encoding() {
+        for cmd in every_command() {
+            assert_eq!(Command::decode(cmd.encode()), Some(cmd));
+        }
+        assert_eq!(Command::decode([99, 0, 0]), None);
+        assert_eq!(Command::decode([7, 0xff, 0]), None);
+    }
+
+    #[test]
+    fn commands_arrive_in_order_across_the_wrap() {
+        let (mut tx, mut rx) = command_queue(4);
+        let all = every_command();
+        let mut got = Vec::new();
+        for pair in all.chunks(3) {
+            for &cmd in pair { assert!(tx.push(cmd)); }
+            while let Some(cmd) = rx.pop() { got.push(cmd); }
+        }
+        assert_eq!(got, all);
+        assert_eq!(tx.pending(), 0);
+    }
+
+    #[test]
+    fn a_full_queue_refuses_pushes() {
+        let (mut tx, mut rx) = command_queue(2);
+        assert!(tx.push(Command::SetWidth(1.0)));
+        assert!(tx.push(Command::SetWidth(2.0)));
+        assert!(!tx.push(Command::SetWidth(3.0)));
+        assert_eq!(tx.pending(), 2);
+
+        assert_eq!(rx.pop(), Some(Command::SetWidth(1.0)));
+        assert!(tx.push(Command::SetWidth(4.0)));
+        assert_eq!(rx.pop(), Some(Command::SetWidth(2.0)));
+        assert_eq!(rx.pop(), Some(Command::SetWidth(4.0)));
+        assert_eq!(rx.pop(), None);
+    }
+}
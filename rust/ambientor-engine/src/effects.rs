@@ -0,0 +1,1462 @@
+//! Insert effects for scene layers and the master bus.
+//!
+//! Like the nodes, effects are fixed-size, allocation-free, and process one
+//! sample (or one stereo frame) at a time.
+//!
+//! Contents:
+//! - `StereoWidth` : mid/side width (0–200%) with a short Haas-style decorrelation delay
+//! - `PingPongDelay`: stereo echo bouncing between sides, with a darkening feedback filter
+//! - `Ducker`      : sidechain gain reduction (an event layer pushes a bed layer down)
+//! - `ThreeBandEq` : low shelf, parametric mid, and high shelf for fitting the output to speakers
+//! - `TiltEq`      : one-knob tone control (dark ↔ bright) pivoting around ~800 Hz
+//! - `Limiter`     : stereo-linked peak limiter for sums of several sources
+//! - `Emitter`     : game-style distance rolloff and occlusion low-pass for a whole engine
+//! - `PitchShifter`: delay-based pitch shift (two crossfading grains), ±24 semitones
+//! - `FreqShifter` : single-sideband frequency shift (Hilbert pair), for inharmonic drones
+//! - `AutoWah`     : envelope-swept resonant filter, so percussive layers open up as they hit
+//! - `Exciter`     : saturated highs mixed back in, adding air at a matched level
+//! - `DynamicEq`   : one peaking band that cuts only while its band is too loud (resonance tamer)
+//! - `MultibandTexture`: 3-band split with per-band saturation and level
+
+use crate::nodes::{EnvFollower, OnePoleSmoother, CONTROL_BLOCK};
+use crate::reverb::{DelayLine, SLEEP_LEVEL};
+use ambientor_core::dsp::{db_to_lin, kill_denormals, lin_to_db, one_pole_coeff_ms, soft_clip};
+use ambientor_core::filters::{Biquad, BiquadKind, Crossover3, OnePoleLP, SvfMode, SvfTpt};
+
+const WIDTH_DELAY_MAX: usize = 4096; // ~85 ms @ 48k
+const WIDTH_DELAY_S: f32 = 0.012;
+
+/// Stereo width via mid/side gain.
+///
+/// `width` is 0 (mono) .. 1 (unchanged) .. 2 (200%). Above 100% a short delayed
+/// copy of the mid signal is blended into the side channel, so even mono material
+/// gains some spread instead of only boosting existing side content.
+#[derive(Clone, Debug)]
+pub struct StereoWidth {
+    sr: f32,
+    width: f32,
+    width_sm: OnePoleSmoother,
+    delay: DelayLine<WIDTH_DELAY_MAX>,
+}
+
+impl StereoWidth {
+    pub fn new(sr: f32) -> Self {
+        let mut s = Self {
+            sr: sr.max(1.0),
+            width: 1.0,
+            width_sm: OnePoleSmoother::new_ms(50.0, sr),
+            delay: DelayLine::new(),
+        };
+        s.width_sm.reset(1.0);
+        s.set_sample_rate(sr);
+        s
+    }
+
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        self.sr = sr.max(1.0);
+        self.width_sm.set_time_ms(50.0, self.sr);
+        self.delay.set_len((WIDTH_DELAY_S * self.sr) as usize);
+    }
+
+    /// Width as a factor: 0 = mono, 1 = unchanged, 2 = 200%.
+    #[inline] pub fn set_width(&mut self, w: f32) { self.width = w.clamp(0.0, 2.0); }
+
+    /// Width in percent (0–200).
+    #[inline] pub fn set_width_percent(&mut self, pct: f32) { self.set_width(pct * 0.01); }
+
+    #[inline] pub fn width(&self) -> f32 { self.width }
+
+    /// Flush the decorrelation delay and snap the width smoother.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.delay.clear();
+        self.width_sm.reset(self.width);
+    }
+
+    #[inline]
+    pub fn process(&mut self, l: f32, r: f32) -> (f32, f32) {
+        let w = self.width_sm.process(self.width);
+        let mid = 0.5 * (l + r);
+        let side = 0.5 * (l - r);
+
+        let delayed = self.delay.read();
+        self.delay.write_advance(mid);
+
+        let side = side * w + (w - 1.0).max(0.0) * 0.5 * delayed;
+        (mid + side, mid - side)
+    }
+}
+
+const PING_PONG_MAX: usize = 1 << 15; // ~0.68 s @ 48k
+
+/// Ping-pong echo: the mono sum enters the left line, and each repeat crosses to
+/// the other side through a one-pole low-pass, so echoes bounce and darken.
+///
+/// `process` returns the **wet** signal only; the caller mixes it with the dry.
+#[derive(Clone, Debug)]
+pub struct PingPongDelay {
+    sr: f32,
+    time_s: f32,
+    feedback: f32,
+    dl: DelayLine<PING_PONG_MAX>,
+    dr: DelayLine<PING_PONG_MAX>,
+    tone_l: OnePoleLP,
+    tone_r: OnePoleLP,
+    len: usize,  // samples per bounce
+    quiet: usize, // samples of silent input and echoes so far
+}
+
+impl PingPongDelay {
+    pub fn new(time_s: f32, feedback: f32, sr: f32) -> Self {
+        let mut s = Self {
+            sr: sr.max(1.0),
+            time_s,
+            feedback: 0.0,
+            dl: DelayLine::new(),
+            dr: DelayLine::new(),
+            tone_l: OnePoleLP::new(3000.0, sr),
+            tone_r: OnePoleLP::new(3000.0, sr),
+            len: 1,
+            quiet: usize::MAX, // empty lines
+        };
+        s.set_feedback(feedback);
+        s.set_sample_rate(sr);
+        s
+    }
+
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        self.sr = sr.max(1.0);
+        self.tone_l.set_sample_rate(self.sr);
+        self.tone_r.set_sample_rate(self.sr);
+        self.set_time(self.time_s);
+    }
+
+    /// Time per bounce in seconds (limited by the line length, ~0.68 s at 48 kHz).
+    #[inline]
+    pub fn set_time(&mut self, s: f32) {
+        self.time_s = s.max(0.001);
+        let n = (self.time_s * self.sr) as usize;
+        self.dl.set_len(n);
+        self.dr.set_len(n);
+        self.len = n.clamp(1, PING_PONG_MAX);
+    }
+
+    #[inline] pub fn set_feedback(&mut self, fb: f32) { self.feedback = fb.clamp(0.0, 0.95); }
+
+    /// Cutoff of the low-pass inside the feedback path (lower = darker repeats).
+    #[inline]
+    pub fn set_tone_hz(&mut self, hz: f32) {
+        self.tone_l.set_cutoff_hz(hz);
+        self.tone_r.set_cutoff_hz(hz);
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.dl.clear();
+        self.dr.clear();
+        self.tone_l.reset();
+        self.tone_r.reset();
+        self.quiet = usize::MAX;
+    }
+
+    #[inline]
+    pub fn process(&mut self, l: f32, r: f32) -> (f32, f32) {
+        let wl = self.dl.read();
+        let wr = self.dr.read();
+        self.dl.write_advance(0.5 * (l + r) + self.feedback * self.tone_r.process(wr));
+        self.dr.write_advance(self.feedback * self.tone_l.process(wl));
+        if l.abs().max(r.abs()).max(wl.abs()).max(wr.abs()) < SLEEP_LEVEL {
+            self.quiet = self.quiet.saturating_add(1);
+        } else {
+            self.quiet = 0;
+        }
+        (wl, wr)
+    }
+
+    /// `true` once input and echoes have stayed below [`SLEEP_LEVEL`] for a
+    /// full round trip, i.e. both lines hold nothing audible.
+    #[inline] pub fn is_idle(&self) -> bool { self.quiet > 2 * self.len }
+}
+
+/// Sidechain ducker for layer mixes: an [`EnvFollower`] on the key signal
+/// (e.g. bell or thunder events) lowers the gain of another layer (e.g. the
+/// drone bed), then lets it recover with a slow release.
+///
+/// Reduction grows linearly with the key envelope up to `depth_db` when the key
+/// reaches `full_level`.
+#[derive(Copy, Clone, Debug)]
+pub struct Ducker {
+    follower: EnvFollower,
+    depth_db: f32,
+    floor: f32, // linear gain at full depth
+    full_level: f32,
+}
+
+impl Ducker {
+    /// `depth_db`: maximum reduction; `release_ms`: recovery time after the key stops.
+    pub fn new(depth_db: f32, release_ms: f32, sr: f32) -> Self {
+        let mut s = Self {
+            follower: EnvFollower::new(10.0, release_ms, sr),
+            depth_db: 0.0,
+            floor: 1.0,
+            full_level: 0.3,
+        };
+        s.set_depth_db(depth_db);
+        s
+    }
+
+    #[inline] pub fn set_sample_rate(&mut self, sr: f32) { self.follower.set_sample_rate(sr); }
+
+    /// Maximum gain reduction in dB (0..24).
+    #[inline]
+    pub fn set_depth_db(&mut self, db: f32) {
+        self.depth_db = db.clamp(0.0, 24.0);
+        self.floor = db_to_lin(-self.depth_db);
+    }
+    #[inline] pub fn depth_db(&self) -> f32 { self.depth_db }
+
+    /// Attack/release of the key follower in ms.
+    #[inline] pub fn set_times(&mut self, attack_ms: f32, release_ms: f32, sr: f32) { self.follower.set_times(attack_ms, release_ms, sr); }
+
+    /// Key level that produces the full reduction.
+    #[inline] pub fn set_full_level(&mut self, level: f32) { self.full_level = level.max(1e-4); }
+
+    #[inline] pub fn reset(&mut self) { self.follower.reset(); }
+
+    /// Feed one key sample and return the gain for the ducked layer.
+    #[inline]
+    pub fn gain(&mut self, key: f32) -> f32 {
+        let k = (self.follower.process(key) / self.full_level).min(1.0);
+        1.0 - (1.0 - self.floor) * k
+    }
+
+    /// Duck a stereo frame by the key sample.
+    #[inline]
+    pub fn process(&mut self, l: f32, r: f32, key: f32) -> (f32, f32) {
+        let g = self.gain(key);
+        (l * g, r * g)
+    }
+}
+
+/// Low shelf corner of [`ThreeBandEq`].
+pub const EQ_LOW_HZ: f32 = 150.0;
+/// Default mid band center of [`ThreeBandEq`].
+pub const EQ_MID_HZ: f32 = 1000.0;
+/// High shelf corner of [`ThreeBandEq`].
+pub const EQ_HIGH_HZ: f32 = 6000.0;
+/// Boost/cut range of each band, ±dB.
+pub const EQ_RANGE_DB: f32 = 12.0;
+
+/// Stereo 3-band EQ: low shelf, peaking mid (movable center), high shelf.
+///
+/// All bands start flat, and a flat EQ is skipped entirely. Gain changes
+/// recompute coefficients, so they are meant for occasional user tweaks
+/// rather than per-sample modulation.
+#[derive(Copy, Clone, Debug)]
+pub struct ThreeBandEq {
+    bands_l: [Biquad; 3],
+    bands_r: [Biquad; 3],
+    flat: bool,
+}
+
+impl ThreeBandEq {
+    pub fn new(sr: f32) -> Self {
+        let bands = [
+            Biquad::new(BiquadKind::LowShelf, EQ_LOW_HZ, 0.707, 0.0, sr),
+            Biquad::new(BiquadKind::Peak, EQ_MID_HZ, 0.7, 0.0, sr),
+            Biquad::new(BiquadKind::HighShelf, EQ_HIGH_HZ, 0.707, 0.0, sr),
+        ];
+        Self { bands_l: bands, bands_r: bands, flat: true }
+    }
+
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        for b in self.bands_l.iter_mut().chain(&mut self.bands_r) { b.set_sample_rate(sr); }
+    }
+
+    fn set_band_db(&mut self, band: usize, db: f32) {
+        let db = db.clamp(-EQ_RANGE_DB, EQ_RANGE_DB);
+        self.bands_l[band].set_gain_db(db);
+        self.bands_r[band].set_gain_db(db);
+        self.flat = self.bands_l.iter().all(|b| b.gain_db() == 0.0);
+    }
+
+    /// Low shelf gain in dB (±12).
+    #[inline] pub fn set_low_db(&mut self, db: f32) { self.set_band_db(0, db); }
+    /// Mid band gain in dB (±12).
+    #[inline] pub fn set_mid_db(&mut self, db: f32) { self.set_band_db(1, db); }
+    /// High shelf gain in dB (±12).
+    #[inline] pub fn set_high_db(&mut self, db: f32) { self.set_band_db(2, db); }
+
+    /// Mid band center in Hz (100..10000).
+    #[inline]
+    pub fn set_mid_hz(&mut self, hz: f32) {
+        let hz = hz.clamp(100.0, 10_000.0);
+        self.bands_l[1].set_freq_hz(hz);
+        self.bands_r[1].set_freq_hz(hz);
+    }
+
+    /// Band gains `(low, mid, high)` in dB.
+    #[inline]
+    pub fn gains_db(&self) -> (f32, f32, f32) {
+        (self.bands_l[0].gain_db(), self.bands_l[1].gain_db(), self.bands_l[2].gain_db())
+    }
+
+    #[inline] pub fn mid_hz(&self) -> f32 { self.bands_l[1].freq_hz() }
+
+    #[inline]
+    pub fn reset(&mut self) {
+        for b in self.bands_l.iter_mut().chain(&mut self.bands_r) { b.reset(); }
+    }
+
+    #[inline]
+    pub fn process(&mut self, l: f32, r: f32) -> (f32, f32) {
+        if self.flat { return (l, r); }
+        let l = self.bands_l.iter_mut().fold(l, |x, b| b.process(x));
+        let r = self.bands_r.iter_mut().fold(r, |x, b| b.process(x));
+        (l, r)
+    }
+}
+
+/// Pivot frequency of [`TiltEq`].
+pub const TILT_PIVOT_HZ: f32 = 800.0;
+/// Shelf gain of [`TiltEq`] at full tilt, in dB (so ±12 dB end to end).
+pub const TILT_MAX_DB: f32 = 6.0;
+
+/// One-knob tilt EQ: complementary low and high shelves around
+/// [`TILT_PIVOT_HZ`]. Negative tone darkens (lows up, highs down), positive
+/// brightens; 0 is flat and skipped.
+#[derive(Copy, Clone, Debug)]
+pub struct TiltEq {
+    shelves_l: [Biquad; 2],
+    shelves_r: [Biquad; 2],
+    tone: f32,
+}
+
+impl TiltEq {
+    pub fn new(sr: f32) -> Self {
+        let shelves = [
+            Biquad::new(BiquadKind::LowShelf, TILT_PIVOT_HZ, 0.5, 0.0, sr),
+            Biquad::new(BiquadKind::HighShelf, TILT_PIVOT_HZ, 0.5, 0.0, sr),
+        ];
+        Self { shelves_l: shelves, shelves_r: shelves, tone: 0.0 }
+    }
+
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        for b in self.shelves_l.iter_mut().chain(&mut self.shelves_r) { b.set_sample_rate(sr); }
+    }
+
+    /// Tone -1 (darkest) .. 0 (flat) .. +1 (brightest).
+    pub fn set_tone(&mut self, tone: f32) {
+        self.tone = tone.clamp(-1.0, 1.0);
+        let db = self.tone * TILT_MAX_DB;
+        for s in [&mut self.shelves_l, &mut self.shelves_r] {
+            s[0].set_gain_db(-db);
+            s[1].set_gain_db(db);
+        }
+    }
+    #[inline] pub fn tone(&self) -> f32 { self.tone }
+
+    #[inline]
+    pub fn reset(&mut self) {
+        for b in self.shelves_l.iter_mut().chain(&mut self.shelves_r) { b.reset(); }
+    }
+
+    #[inline]
+    pub fn process(&mut self, l: f32, r: f32) -> (f32, f32) {
+        if self.tone == 0.0 { return (l, r); }
+        let l = self.shelves_l.iter_mut().fold(l, |x, b| b.process(x));
+        let r = self.shelves_r.iter_mut().fold(r, |x, b| b.process(x));
+        (l, r)
+    }
+}
+
+/// Default ceiling of [`Limiter`], dBFS.
+pub const LIMITER_CEILING_DB: f32 = -1.0;
+/// Default release of [`Limiter`], ms.
+pub const LIMITER_RELEASE_MS: f32 = 250.0;
+
+/// Stereo-linked peak limiter without lookahead.
+///
+/// Gain drops instantly to whatever keeps the louder channel at the ceiling,
+/// then recovers with the release time, so the output never exceeds the
+/// ceiling. Meant as a safety stage where several sources are summed, not
+/// as a loudness maximizer.
+#[derive(Copy, Clone, Debug)]
+pub struct Limiter {
+    ceiling_db: f32,
+    ceiling: f32,
+    release_ms: f32,
+    rel: f32,
+    gain: f32,
+}
+
+impl Limiter {
+    pub fn new(sr: f32) -> Self {
+        let mut s = Self { ceiling_db: 0.0, ceiling: 1.0, release_ms: LIMITER_RELEASE_MS, rel: 0.0, gain: 1.0 };
+        s.set_ceiling_db(LIMITER_CEILING_DB);
+        s.set_sample_rate(sr);
+        s
+    }
+
+    #[inline] pub fn set_sample_rate(&mut self, sr: f32) { self.rel = one_pole_coeff_ms(self.release_ms, sr); }
+
+    /// Ceiling in dBFS (-24..0).
+    #[inline]
+    pub fn set_ceiling_db(&mut self, db: f32) {
+        self.ceiling_db = db.clamp(-24.0, 0.0);
+        self.ceiling = db_to_lin(self.ceiling_db);
+    }
+    #[inline] pub fn ceiling_db(&self) -> f32 { self.ceiling_db }
+
+    /// Recovery time after a peak, ms (10..5000).
+    #[inline]
+    pub fn set_release_ms(&mut self, ms: f32, sr: f32) {
+        self.release_ms = ms.clamp(10.0, 5000.0);
+        self.set_sample_rate(sr);
+    }
+
+    /// Current gain reduction in dB (0 = none, negative while limiting).
+    #[inline] pub fn reduction_db(&self) -> f32 { lin_to_db(self.gain) }
+
+    #[inline] pub fn reset(&mut self) { self.gain = 1.0; }
+
+    #[inline]
+    pub fn process(&mut self, l: f32, r: f32) -> (f32, f32) {
+        self.gain = 1.0 + (self.gain - 1.0) * self.rel;
+        let peak = l.abs().max(r.abs()) * self.gain;
+        if peak > self.ceiling { self.gain *= self.ceiling / peak; }
+        (l * self.gain, r * self.gain)
+    }
+}
+
+/// Distance at or below which [`Emitter`] leaves the level alone (m).
+pub const EMITTER_REF_M: f32 = 1.0;
+/// Distance beyond which [`Emitter`] stops attenuating further (m).
+pub const EMITTER_MAX_M: f32 = 100.0;
+/// Low-pass cutoff of [`Emitter`] at full occlusion.
+pub const OCCLUDED_HZ: f32 = 400.0;
+/// Level drop of [`Emitter`] at full occlusion, dB.
+pub const OCCLUSION_DB: f32 = 6.0;
+const OPEN_HZ: f32 = 20_000.0;
+const EMITTER_GLIDE_MS: f32 = 50.0;
+
+/// Cheap spatialization for a whole soundscape placed in a game world.
+///
+/// Distance maps to inverse-distance gain, clamped like common game audio
+/// engines: unity up to the reference distance, `ref / d` beyond it, and no
+/// further drop past the max distance. Occlusion (0..1) closes a low-pass from
+/// fully open down to [`OCCLUDED_HZ`] on an exponential scale and lowers the
+/// level by up to [`OCCLUSION_DB`]. Both glide over ~50 ms, so a host can
+/// update them once per game frame. At distance ≤ ref with no occlusion the
+/// stage is skipped.
+#[derive(Copy, Clone, Debug)]
+pub struct Emitter {
+    sr: f32,
+    distance: f32,
+    ref_m: f32,
+    max_m: f32,
+    occlusion: f32,
+    gain: f32,
+    gain_sm: OnePoleSmoother,
+    lp: [OnePoleLP; 2],
+    opening: u32, // samples left of the glide back to open before the filter is skipped
+}
+
+impl Emitter {
+    pub fn new(sr: f32) -> Self {
+        let sr = sr.max(1.0);
+        let open = Self::open_hz(sr);
+        let mut gain_sm = OnePoleSmoother::new_ms(EMITTER_GLIDE_MS, sr);
+        gain_sm.reset(1.0);
+        Self {
+            sr,
+            distance: 0.0,
+            ref_m: EMITTER_REF_M,
+            max_m: EMITTER_MAX_M,
+            occlusion: 0.0,
+            gain: 1.0,
+            gain_sm,
+            lp: [OnePoleLP::new(open, sr); 2],
+            opening: 0,
+        }
+    }
+
+    #[inline]
+    fn open_hz(sr: f32) -> f32 { OPEN_HZ.min(0.45 * sr) }
+
+    #[inline]
+    fn glide_samples(&self) -> u32 { (EMITTER_GLIDE_MS * 0.001 * self.sr) as u32 }
+
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        self.sr = sr.max(1.0);
+        self.gain_sm.set_time_ms(EMITTER_GLIDE_MS, self.sr);
+        for lp in &mut self.lp {
+            lp.set_sample_rate(self.sr);
+        }
+        self.set_occlusion(self.occlusion);
+    }
+
+    /// Distance from the listener in meters (≥ 0).
+    pub fn set_distance(&mut self, m: f32) {
+        self.distance = m.max(0.0);
+        self.update_gain();
+    }
+    #[inline] pub fn distance(&self) -> f32 { self.distance }
+
+    /// Reference distance (unity gain up to here, ≥ 0.01 m) and max distance
+    /// (attenuation stops here, ≥ ref).
+    pub fn set_rolloff(&mut self, ref_m: f32, max_m: f32) {
+        self.ref_m = ref_m.max(0.01);
+        self.max_m = max_m.max(self.ref_m);
+        self.update_gain();
+    }
+    #[inline] pub fn rolloff(&self) -> (f32, f32) { (self.ref_m, self.max_m) }
+
+    /// Occlusion 0 (clear line of sight) .. 1 (fully behind walls).
+    pub fn set_occlusion(&mut self, x: f32) {
+        let filtering = self.filtering();
+        self.occlusion = x.clamp(0.0, 1.0);
+        let open = Self::open_hz(self.sr);
+        let hz = open * (OCCLUDED_HZ / open).powf(self.occlusion);
+        let n = self.glide_samples();
+        for lp in &mut self.lp {
+            if !filtering {
+                // skipped until now: start from open and clean
+                lp.set_cutoff_hz(open);
+                lp.reset();
+            }
+            lp.set_cutoff_smoothed(hz, n);
+        }
+        // back to clear: keep filtering until the cutoff has opened up again
+        self.opening = if self.occlusion == 0.0 && filtering { n.max(1) } else { 0 };
+        self.update_gain();
+    }
+
+    #[inline]
+    fn filtering(&self) -> bool { self.occlusion > 0.0 || self.opening > 0 }
+    #[inline] pub fn occlusion(&self) -> f32 { self.occlusion }
+
+    fn update_gain(&mut self) {
+        let d = self.distance.clamp(self.ref_m, self.max_m);
+        self.gain = self.ref_m / d * db_to_lin(-OCCLUSION_DB * self.occlusion);
+    }
+
+    /// Current target gain (distance and occlusion combined), linear.
+    #[inline] pub fn gain(&self) -> f32 { self.gain }
+
+    #[inline]
+    pub fn reset(&mut self) {
+        self.gain_sm.reset(self.gain);
+        for lp in &mut self.lp {
+            lp.reset();
+        }
+    }
+
+    #[inline]
+    pub fn process(&mut self, l: f32, r: f32) -> (f32, f32) {
+        let g = self.gain_sm.process(self.gain);
+        if !self.filtering() { return (l * g, r * g); }
+        self.opening = self.opening.saturating_sub(1);
+        (self.lp[0].process(l) * g, self.lp[1].process(r) * g)
+    }
+}
+
+/// Largest shift of a [`PitchShifter`], semitones either way.
+pub const PITCH_SHIFT_MAX_ST: f32 = 24.0;
+/// Default grain window of a [`PitchShifter`].
+pub const PITCH_WINDOW_MS: f32 = 60.0;
+const PITCH_BUF: usize = 1 << 13; // ~170 ms @ 48k, bounds the window
+
+/// Delay-based pitch shifter: two read taps sweep through a short delay at
+/// the rate that transposes them, half a window apart, each faded by a sin²
+/// window that is silent where its tap jumps back. The fades sum to one, so
+/// steady input keeps its level.
+///
+/// Longer windows (10..150 ms, capped by an internal ~170 ms buffer at 48 kHz)
+/// smear transients less audibly on pads and drones; shorter ones suit
+/// percussive material. Used on its own or in [`ReverbLite`](crate::reverb::ReverbLite)'s
+/// shimmer loop.
+#[derive(Copy, Clone, Debug)]
+pub struct PitchShifter {
+    sr: f32,
+    buf: [f32; PITCH_BUF],
+    w: usize,
+    phase: f32,  // tap A position in the window, 0..1 (tap B is half a window on)
+    step: f32,   // phase change per sample
+    window: f32, // samples
+    window_ms: f32,
+    semitones: f32,
+    mix: f32,
+}
+
+impl PitchShifter {
+    /// Up `semitones` (negative = down), fully wet, [`PITCH_WINDOW_MS`] window.
+    pub fn new(semitones: f32, sr: f32) -> Self {
+        let mut p = Self {
+            sr: sr.max(1.0),
+            buf: [0.0; PITCH_BUF],
+            w: 0,
+            phase: 0.0,
+            step: 0.0,
+            window: 1.0,
+            window_ms: PITCH_WINDOW_MS,
+            semitones: 0.0,
+            mix: 1.0,
+        };
+        p.set_semitones(semitones);
+        p
+    }
+
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        self.sr = sr.max(1.0);
+        self.update();
+    }
+
+    /// Shift in semitones, clamped to ±[`PITCH_SHIFT_MAX_ST`].
+    pub fn set_semitones(&mut self, st: f32) {
+        self.semitones = st.clamp(-PITCH_SHIFT_MAX_ST, PITCH_SHIFT_MAX_ST);
+        self.update();
+    }
+    #[inline] pub fn semitones(&self) -> f32 { self.semitones }
+
+    /// Grain window in ms (10..150).
+    pub fn set_window_ms(&mut self, ms: f32) {
+        self.window_ms = ms.clamp(10.0, 150.0);
+        self.update();
+    }
+    #[inline] pub fn window_ms(&self) -> f32 { self.window_ms }
+
+    /// Dry/wet balance 0..1 (default 1 = shifted signal only).
+    #[inline] pub fn set_mix(&mut self, mix: f32) { self.mix = mix.clamp(0.0, 1.0); }
+    #[inline] pub fn mix(&self) -> f32 { self.mix }
+
+    fn update(&mut self) {
+        self.window = (self.window_ms * 0.001 * self.sr).clamp(4.0, (PITCH_BUF - 2) as f32);
+        let ratio = (self.semitones / 12.0).exp2();
+        // delay changes by (1 - ratio) samples per sample
+        self.step = (1.0 - ratio) / self.window;
+    }
+
+    pub fn clear(&mut self) {
+        self.buf.fill(0.0);
+        self.phase = 0.0;
+    }
+
+    /// Linear-interpolated read `delay` samples behind the newest one.
+    #[inline]
+    fn tap(&self, delay: f32) -> f32 {
+        let d = delay as usize;
+        let frac = delay - d as f32;
+        let i = (self.w + PITCH_BUF - d) % PITCH_BUF;
+        let a = self.buf[i];
+        let b = self.buf[(i + PITCH_BUF - 1) % PITCH_BUF];
+        a + (b - a) * frac
+    }
+
+    #[inline]
+    pub fn process(&mut self, x: f32) -> f32 {
+        self.buf[self.w] = x;
+        self.phase += self.step;
+        self.phase -= self.phase.floor();
+        let pb = if self.phase < 0.5 { self.phase + 0.5 } else { self.phase - 0.5 };
+        let s = (core::f32::consts::PI * self.phase).sin();
+        let ga = s * s; // tap B's fade is cos², i.e. 1 - ga
+        let y = ga * self.tap(self.phase * self.window) + (1.0 - ga) * self.tap(pb * self.window);
+        self.w = (self.w + 1) % PITCH_BUF;
+        x + self.mix * (y - x)
+    }
+}
+
+/// Largest shift of a [`FreqShifter`] either way (Hz).
+pub const FREQ_SHIFT_MAX_HZ: f32 = 2000.0;
+
+// Two allpass chains whose outputs stay ~90° apart from ~20 Hz to ~20 kHz at
+// 44.1/48 kHz (Niemitalo's coefficients, given as a² of y = a²(x + y₂) - x₂).
+const HILBERT_A: [f32; 4] = [0.479_401_07, 0.876_219, 0.976_597_6, 0.997_499_2];
+const HILBERT_B: [f32; 4] = [0.161_758_5, 0.733_028_97, 0.945_349_8, 0.990_599_2];
+
+/// Four second-order allpasses in series (`x₁, x₂, y₁, y₂` per stage).
+#[derive(Copy, Clone, Debug, Default)]
+struct AllpassChain {
+    z: [[f32; 4]; 4],
+}
+
+impl AllpassChain {
+    #[inline]
+    fn process(&mut self, mut x: f32, coeffs: &[f32; 4]) -> f32 {
+        for (z, &a) in self.z.iter_mut().zip(coeffs) {
+            let y = kill_denormals(a * (x + z[3]) - z[1]);
+            *z = [x, z[0], y, z[2]];
+            x = y;
+        }
+        x
+    }
+}
+
+/// Single-sideband frequency shifter: every partial moves by the same number
+/// of Hz (unlike [`PitchShifter`], which keeps their ratios), so harmonic
+/// drones turn metallic and bell-like.
+///
+/// The input is split into an analytic pair by an allpass Hilbert network and
+/// rotated by a quadrature carrier. The carrier phase is kept in `f64`, so
+/// shifts of a fraction of a Hertz stay exact for hours; mixed with the dry
+/// signal, such slow shifts give an endless "barberpole" phasing. Feedback
+/// sends the shifted signal round again, stacking further shifts.
+#[derive(Copy, Clone, Debug)]
+pub struct FreqShifter {
+    sr: f32,
+    shift_hz: f32,
+    phase: f64, // carrier phase in cycles, 0..1
+    a: AllpassChain,
+    b: AllpassChain,
+    a_z1: f32, // chain A output, delayed one sample to line up with chain B
+    fb: f32,
+    last: f32,
+    mix: f32,
+}
+
+impl FreqShifter {
+    /// Shift by `shift_hz` (negative = down), fully wet, no feedback.
+    pub fn new(shift_hz: f32, sr: f32) -> Self {
+        let mut f = Self {
+            sr: sr.max(1.0),
+            shift_hz: 0.0,
+            phase: 0.0,
+            a: AllpassChain::default(),
+            b: AllpassChain::default(),
+            a_z1: 0.0,
+            fb: 0.0,
+            last: 0.0,
+            mix: 1.0,
+        };
+        f.set_shift_hz(shift_hz);
+        f
+    }
+
+    #[inline] pub fn set_sample_rate(&mut self, sr: f32) { self.sr = sr.max(1.0); }
+
+    /// Shift in Hz, clamped to ±[`FREQ_SHIFT_MAX_HZ`]; any fraction is kept
+    /// (e.g. 0.05 Hz for a 20 s phasing cycle).
+    #[inline] pub fn set_shift_hz(&mut self, hz: f32) { self.shift_hz = hz.clamp(-FREQ_SHIFT_MAX_HZ, FREQ_SHIFT_MAX_HZ); }
+    #[inline] pub fn shift_hz(&self) -> f32 { self.shift_hz }
+
+    /// Feedback of the shifted signal 0..0.9 (default 0).
+    #[inline] pub fn set_feedback(&mut self, fb: f32) { self.fb = fb.clamp(0.0, 0.9); }
+    #[inline] pub fn feedback(&self) -> f32 { self.fb }
+
+    /// Dry/wet balance 0..1 (default 1 = shifted signal only; 0.5 for phasing).
+    #[inline] pub fn set_mix(&mut self, mix: f32) { self.mix = mix.clamp(0.0, 1.0); }
+    #[inline] pub fn mix(&self) -> f32 { self.mix }
+
+    pub fn clear(&mut self) {
+        self.a = AllpassChain::default();
+        self.b = AllpassChain::default();
+        self.a_z1 = 0.0;
+        self.last = 0.0;
+    }
+
+    #[inline]
+    pub fn process(&mut self, x: f32) -> f32 {
+        let input = x + self.fb * self.last;
+        let re = self.a_z1;
+        self.a_z1 = self.a.process(input, &HILBERT_A);
+        let im = self.b.process(input, &HILBERT_B);
+        let (s, c) = (core::f64::consts::TAU * self.phase).sin_cos();
+        let y = re * c as f32 + im * s as f32;
+        self.phase += self.shift_hz as f64 / self.sr as f64;
+        self.phase -= self.phase.floor();
+        self.last = y;
+        x + self.mix * (y - x)
+    }
+}
+
+/// Default sweep range of an [`AutoWah`] (Hz).
+pub const WAH_RANGE_HZ: (f32, f32) = (300.0, 3000.0);
+
+/// Which way an [`AutoWah`] sweeps as its input gets louder.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WahDirection {
+    /// Rests at the bottom of the range and opens up on hits.
+    Up,
+    /// Rests at the top of the range and closes down on hits.
+    Down,
+}
+
+/// Envelope-controlled filter ("auto-wah"): an [`EnvFollower`] on the input
+/// sweeps a resonant [`SvfTpt`] across its range, so rain, bells and other
+/// percussive layers get movement that follows how hard they hit.
+///
+/// `sensitivity` 0..1 sets the input level that gives a full sweep, from
+/// 0 dBFS down to -40 dBFS; the sweep is even in octaves. The cutoff is
+/// recomputed once per [`CONTROL_BLOCK`] and glides in between. Band-pass
+/// output is scaled to unity at its peak; low-pass keeps its resonant bump.
+/// Stereo input is detected linked, so both sides sweep together.
+#[derive(Copy, Clone, Debug)]
+pub struct AutoWah {
+    follower: EnvFollower,
+    svf: [SvfTpt; 2],
+    mode: SvfMode,
+    direction: WahDirection,
+    lo_hz: f32,
+    hi_hz: f32,
+    cutoff: f32, // sweep target
+    q: f32,
+    sensitivity: f32,
+    full_level: f32, // follower level that reaches the end of the range
+    mix: f32,
+    tick: u32,
+}
+
+impl AutoWah {
+    /// Band-pass sweeping up across [`WAH_RANGE_HZ`] at Q 3, sensitivity 0.5
+    /// (full sweep at -20 dBFS), 5 ms attack / 150 ms release, fully wet.
+    pub fn new(sr: f32) -> Self {
+        let mut w = Self {
+            follower: EnvFollower::new(5.0, 150.0, sr),
+            svf: [SvfTpt::new(WAH_RANGE_HZ.0, 3.0, sr); 2],
+            mode: SvfMode::Bandpass,
+            direction: WahDirection::Up,
+            lo_hz: WAH_RANGE_HZ.0,
+            hi_hz: WAH_RANGE_HZ.1,
+            cutoff: WAH_RANGE_HZ.0,
+            q: 3.0,
+            sensitivity: 0.0,
+            full_level: 1.0,
+            mix: 1.0,
+            tick: 0,
+        };
+        w.set_sensitivity(0.5);
+        w.sweep(0.0, 1);
+        w
+    }
+
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        self.follower.set_sample_rate(sr);
+        for f in &mut self.svf { f.set_sample_rate(sr); }
+    }
+
+    /// 0 (needs a full-scale input to sweep fully) .. 1 (sweeps fully at -40 dBFS).
+    #[inline]
+    pub fn set_sensitivity(&mut self, s: f32) {
+        self.sensitivity = s.clamp(0.0, 1.0);
+        self.full_level = db_to_lin(-40.0 * self.sensitivity);
+    }
+    #[inline] pub fn sensitivity(&self) -> f32 { self.sensitivity }
+
+    /// Sweep range in Hz (each 20..18000; swapped if given backwards).
+    #[inline]
+    pub fn set_range(&mut self, lo_hz: f32, hi_hz: f32) {
+        let (a, b) = (lo_hz.clamp(20.0, 18_000.0), hi_hz.clamp(20.0, 18_000.0));
+        (self.lo_hz, self.hi_hz) = (a.min(b), a.max(b));
+    }
+    #[inline] pub fn range(&self) -> (f32, f32) { (self.lo_hz, self.hi_hz) }
+
+    #[inline] pub fn set_direction(&mut self, d: WahDirection) { self.direction = d; }
+    #[inline] pub fn direction(&self) -> WahDirection { self.direction }
+
+    /// Where the cutoff is heading (Hz), for meters.
+    #[inline] pub fn cutoff_hz(&self) -> f32 { self.cutoff }
+
+    /// Filter response: band-pass (default) or low-pass; other modes are
+    /// taken as band-pass.
+    #[inline]
+    pub fn set_mode(&mut self, mode: SvfMode) {
+        self.mode = if matches!(mode, SvfMode::Lowpass) { mode } else { SvfMode::Bandpass };
+    }
+
+    /// Resonance (0.5..12).
+    #[inline]
+    pub fn set_q(&mut self, q: f32) {
+        self.q = q.clamp(0.5, 12.0);
+        for f in &mut self.svf { f.set_q(self.q); }
+    }
+    #[inline] pub fn q(&self) -> f32 { self.q }
+
+    /// Follower attack/release in ms.
+    #[inline] pub fn set_times(&mut self, attack_ms: f32, release_ms: f32, sr: f32) { self.follower.set_times(attack_ms, release_ms, sr); }
+
+    /// Dry/wet balance 0..1 (default 1).
+    #[inline] pub fn set_mix(&mut self, mix: f32) { self.mix = mix.clamp(0.0, 1.0); }
+    #[inline] pub fn mix(&self) -> f32 { self.mix }
+
+    pub fn reset(&mut self) {
+        self.follower.reset();
+        self.tick = 0;
+        self.sweep(0.0, 1);
+    }
+
+    /// Move the cutoff to sweep position `e` (0..1) over `samples`.
+    fn sweep(&mut self, e: f32, samples: u32) {
+        let e = if self.direction == WahDirection::Up { e } else { 1.0 - e };
+        self.cutoff = self.lo_hz * (self.hi_hz / self.lo_hz).powf(e);
+        for f in &mut self.svf { f.set_cutoff_smoothed(self.cutoff, samples); }
+    }
+
+    #[inline]
+    fn detect(&mut self, level: f32) {
+        let env = self.follower.process(level);
+        if self.tick == 0 { self.sweep((env / self.full_level).min(1.0), CONTROL_BLOCK); }
+        self.tick = (self.tick + 1) % CONTROL_BLOCK;
+    }
+
+    #[inline]
+    fn filter(&mut self, ch: usize, x: f32) -> f32 {
+        let y = self.svf[ch].process(x, self.mode);
+        let y = if matches!(self.mode, SvfMode::Bandpass) { y * 0.5 / self.q } else { y };
+        x + self.mix * (y - x)
+    }
+
+    #[inline]
+    pub fn process(&mut self, x: f32) -> f32 {
+        self.detect(x);
+        self.filter(0, x)
+    }
+
+    #[inline]
+    pub fn process_stereo(&mut self, l: f32, r: f32) -> (f32, f32) {
+        self.detect(l.abs().max(r.abs()));
+        (self.filter(0, l), self.filter(1, r))
+    }
+}
+
+/// Range of an [`Exciter`]'s frequency (Hz).
+pub const EXCITER_HZ: (f32, f32) = (1000.0, 12_000.0);
+/// Range of an [`Exciter`]'s drive.
+pub const EXCITER_DRIVE: (f32, f32) = (1.0, 50.0);
+/// Averaging time of the exciter's level matching.
+const EXCITER_MATCH_MS: f32 = 300.0;
+/// Averaging time of the fit that finds the band's own share of the clipper output.
+const EXCITER_FIT_MS: f32 = 30.0;
+/// Harmonics level at amount 1, relative to the band they come from.
+const EXCITER_GAIN: f32 = 4.0;
+
+/// Harmonic exciter for dull drones: the input above `freq` is driven into a
+/// soft clipper, and only what the clipper adds (the new harmonics, not the
+/// band itself) is high-passed again and mixed back in by `amount`. The band's
+/// share of the clipper output is its least-squares fit to the band, tracked
+/// over ~30 ms, so what is left is what the band can't account for.
+///
+/// The band is scaled to its own envelope before the clipper and back after
+/// it, so `drive` sets how hard it clips whatever the band's level: even
+/// the faint highs of a dark pad get their harmonics.
+///
+/// Dark pads gain air and presence that an EQ can't give them, since an EQ
+/// can only lift highs that are already there. The output is matched to the
+/// input's RMS over ~300 ms (turned down as far as the harmonics added, never
+/// up), so raising `amount` changes the colour, not the master level.
+#[derive(Copy, Clone, Debug)]
+pub struct Exciter {
+    pre: [Biquad; 2],  // band fed to the clipper, per channel
+    post: [Biquad; 2], // strips what the clipper folds back down
+    env: [EnvFollower; 2],
+    freq: f32,
+    drive: f32,
+    clip_norm: f32, // 1 / soft_clip(drive): a band peak stays a peak
+    fit_a: f32,
+    fit: [[f32; 2]; 2], // per channel: mean clipped·band, mean band²
+    amount: f32,
+    match_a: f32,
+    ms_in: f32,
+    ms_out: f32,
+}
+
+impl Exciter {
+    /// At 3 kHz, drive 10, amount 0 (bypassed).
+    pub fn new(sr: f32) -> Self {
+        let hp = Biquad::new(BiquadKind::Highpass, 3000.0, 0.707, 0.0, sr);
+        Self {
+            pre: [hp; 2],
+            post: [hp; 2],
+            env: [EnvFollower::new(1.0, 50.0, sr); 2],
+            freq: 3000.0,
+            drive: 10.0,
+            clip_norm: 1.0 / soft_clip(10.0),
+            fit_a: one_pole_coeff_ms(EXCITER_FIT_MS, sr),
+            fit: [[0.0; 2]; 2],
+            amount: 0.0,
+            match_a: one_pole_coeff_ms(EXCITER_MATCH_MS, sr),
+            ms_in: 0.0,
+            ms_out: 0.0,
+        }
+    }
+
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        for f in self.pre.iter_mut().chain(&mut self.post) { f.set_sample_rate(sr); }
+        for e in &mut self.env { e.set_sample_rate(sr); }
+        self.fit_a = one_pole_coeff_ms(EXCITER_FIT_MS, sr);
+        self.match_a = one_pole_coeff_ms(EXCITER_MATCH_MS, sr);
+    }
+
+    /// Lowest frequency that is excited, clamped to [`EXCITER_HZ`].
+    pub fn set_freq_hz(&mut self, hz: f32) {
+        self.freq = hz.clamp(EXCITER_HZ.0, EXCITER_HZ.1);
+        for f in self.pre.iter_mut().chain(&mut self.post) { f.set_freq_hz(self.freq); }
+    }
+    #[inline] pub fn freq_hz(&self) -> f32 { self.freq }
+
+    /// How hard the band is clipped, clamped to [`EXCITER_DRIVE`]: 1 adds a
+    /// faint sheen, 50 a gritty one.
+    #[inline]
+    pub fn set_drive(&mut self, d: f32) {
+        self.drive = d.clamp(EXCITER_DRIVE.0, EXCITER_DRIVE.1);
+        self.clip_norm = 1.0 / soft_clip(self.drive);
+    }
+    #[inline] pub fn drive(&self) -> f32 { self.drive }
+
+    /// How much of the harmonics is added, 0..1 (0 = bypassed).
+    #[inline] pub fn set_amount(&mut self, a: f32) { self.amount = a.clamp(0.0, 1.0); }
+    #[inline] pub fn amount(&self) -> f32 { self.amount }
+
+    pub fn reset(&mut self) {
+        for f in self.pre.iter_mut().chain(&mut self.post) { f.reset(); }
+        for e in &mut self.env { e.reset(); }
+        self.fit = [[0.0; 2]; 2];
+        self.ms_in = 0.0;
+        self.ms_out = 0.0;
+    }
+
+    /// New harmonics of channel `ch`'s band (the clipper's output minus its
+    /// fit to the band), high-passed.
+    #[inline]
+    fn harmonics(&mut self, ch: usize, x: f32) -> f32 {
+        let h = self.pre[ch].process(x);
+        let env = self.env[ch].process(h).max(1e-6);
+        let u = h / env; // about -1..1
+        let c = env * soft_clip(self.drive * u) * self.clip_norm;
+        let k = 1.0 - self.fit_a;
+        let [cx, xx] = &mut self.fit[ch];
+        *cx = kill_denormals(*cx + (c * h - *cx) * k);
+        *xx = kill_denormals(*xx + (h * h - *xx) * k);
+        let g = if *xx > 1e-12 { *cx / *xx } else { 1.0 };
+        EXCITER_GAIN * self.post[ch].process(c - g * h)
+    }
+
+    /// Gain that brings the excited signal back to the input's level.
+    #[inline]
+    fn level_match(&mut self, p_in: f32, p_out: f32) -> f32 {
+        let k = 1.0 - self.match_a;
+        self.ms_in = kill_denormals(self.ms_in + (p_in - self.ms_in) * k);
+        self.ms_out = kill_denormals(self.ms_out + (p_out - self.ms_out) * k);
+        if self.ms_out <= 1e-12 { return 1.0; }
+        (self.ms_in / self.ms_out).sqrt().min(1.0)
+    }
+
+    #[inline]
+    pub fn process(&mut self, x: f32) -> f32 {
+        if self.amount == 0.0 { return x; }
+        let y = x + self.amount * self.harmonics(0, x);
+        y * self.level_match(x * x, y * y)
+    }
+
+    #[inline]
+    pub fn process_stereo(&mut self, l: f32, r: f32) -> (f32, f32) {
+        if self.amount == 0.0 { return (l, r); }
+        let yl = l + self.amount * self.harmonics(0, l);
+        let yr = r + self.amount * self.harmonics(1, r);
+        let g = self.level_match(l * l + r * r, yl * yl + yr * yr);
+        (yl * g, yr * g)
+    }
+}
+
+/// Default threshold of a [`DynamicEq`] (dBFS, band level).
+pub const DYN_EQ_THRESHOLD_DB: f32 = -30.0;
+/// Deepest cut a [`DynamicEq`] can be set to (dB).
+pub const DYN_EQ_MAX_RANGE_DB: f32 = 24.0;
+
+/// Single-band dynamic EQ, a de-esser for any frequency: a band-pass at
+/// `freq` measures how loud that band is, and a peaking filter at the same
+/// frequency cuts it by the compressor curve (`ratio` above `threshold_db`,
+/// never deeper than `range_db`).
+///
+/// Meant for the resonant build-ups that long reverb and delay feedback pile
+/// up around comb frequencies: the band is left alone while it behaves and
+/// pulled down only while it rings. Detection is stereo-linked; the filter
+/// gain is updated once per [`CONTROL_BLOCK`].
+#[derive(Copy, Clone, Debug)]
+pub struct DynamicEq {
+    detectors: [Biquad; 2], // band-pass, per channel
+    follower: EnvFollower,
+    bands: [Biquad; 2],     // peaking cut, per channel
+    threshold_db: f32,
+    ratio: f32,
+    range_db: f32,
+    cut_db: f32, // current cut, >= 0
+    tick: u32,
+}
+
+impl DynamicEq {
+    /// At `freq_hz` with Q 4: threshold [`DYN_EQ_THRESHOLD_DB`], ratio 4,
+    /// range 12 dB, 5 ms attack / 120 ms release.
+    pub fn new(freq_hz: f32, sr: f32) -> Self {
+        let mut d = Self {
+            detectors: [Biquad::new(BiquadKind::Bandpass, 1000.0, 4.0, 0.0, sr); 2],
+            follower: EnvFollower::new(5.0, 120.0, sr),
+            bands: [Biquad::new(BiquadKind::Peak, 1000.0, 4.0, 0.0, sr); 2],
+            threshold_db: DYN_EQ_THRESHOLD_DB,
+            ratio: 4.0,
+            range_db: 12.0,
+            cut_db: 0.0,
+            tick: 0,
+        };
+        d.set_freq_hz(freq_hz);
+        d
+    }
+
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        self.follower.set_sample_rate(sr);
+        for b in self.detectors.iter_mut().chain(&mut self.bands) { b.set_sample_rate(sr); }
+    }
+
+    /// Band center in Hz (40..16000), for both detector and cut.
+    pub fn set_freq_hz(&mut self, hz: f32) {
+        let hz = hz.clamp(40.0, 16_000.0);
+        for b in self.detectors.iter_mut().chain(&mut self.bands) { b.set_freq_hz(hz); }
+    }
+    #[inline] pub fn freq_hz(&self) -> f32 { self.bands[0].freq_hz() }
+
+    /// Band width as Q (0.5..20; high Q for single comb resonances).
+    pub fn set_q(&mut self, q: f32) {
+        let q = q.clamp(0.5, 20.0);
+        for b in self.detectors.iter_mut().chain(&mut self.bands) { b.set_q(q); }
+    }
+
+    /// Band level above which the cut starts (dBFS, -60..0).
+    #[inline] pub fn set_threshold_db(&mut self, db: f32) { self.threshold_db = db.clamp(-60.0, 0.0); }
+    #[inline] pub fn threshold_db(&self) -> f32 { self.threshold_db }
+
+    /// Compression ratio above the threshold (1..20).
+    #[inline] pub fn set_ratio(&mut self, r: f32) { self.ratio = r.clamp(1.0, 20.0); }
+    #[inline] pub fn ratio(&self) -> f32 { self.ratio }
+
+    /// Deepest cut in dB (0..[`DYN_EQ_MAX_RANGE_DB`]).
+    #[inline] pub fn set_range_db(&mut self, db: f32) { self.range_db = db.clamp(0.0, DYN_EQ_MAX_RANGE_DB); }
+    #[inline] pub fn range_db(&self) -> f32 { self.range_db }
+
+    /// Detector attack/release in ms.
+    #[inline] pub fn set_times(&mut self, attack_ms: f32, release_ms: f32, sr: f32) { self.follower.set_times(attack_ms, release_ms, sr); }
+
+    /// Current cut in dB (0 = none, negative while cutting), for meters.
+    #[inline] pub fn reduction_db(&self) -> f32 { -self.cut_db }
+
+    pub fn reset(&mut self) {
+        for d in &mut self.detectors { d.reset(); }
+        self.follower.reset();
+        for b in &mut self.bands {
+            b.reset();
+            b.set_gain_db(0.0);
+        }
+        self.cut_db = 0.0;
+        self.tick = 0;
+    }
+
+    #[inline]
+    pub fn process(&mut self, l: f32, r: f32) -> (f32, f32) {
+        let [dl, dr] = &mut self.detectors;
+        let env = self.follower.process(dl.process(l).abs().max(dr.process(r).abs()));
+        if self.tick == 0 {
+            let over = lin_to_db(env) - self.threshold_db;
+            let cut = (over * (1.0 - 1.0 / self.ratio)).clamp(0.0, self.range_db);
+            if (cut - self.cut_db).abs() > 0.05 {
+                self.cut_db = cut;
+                for b in &mut self.bands { b.set_gain_db(-cut); }
+            }
+        }
+        self.tick = (self.tick + 1) % CONTROL_BLOCK;
+        (self.bands[0].process(l), self.bands[1].process(r))
+    }
+}
+
+/// Default crossovers of a [`MultibandTexture`] (Hz).
+pub const TEXTURE_XOVER_HZ: (f32, f32) = (200.0, 3000.0);
+/// Largest drive of a [`MultibandTexture`] band (at amount 1).
+const TEXTURE_MAX_DRIVE_DB: f32 = 18.0;
+
+/// Three-band "texture" processor: an LR4 [`Crossover3`] splits the signal
+/// into lows, mids and highs, each band gets its own saturation amount and
+/// level, and the bands are summed back.
+///
+/// The ambient mastering move of thickening the lows and adding grit to the
+/// air without touching the mids. Saturation keeps a band's small-signal gain
+/// at unity and rounds off its peaks (amount 1 drives 18 dB into the
+/// clipper), so more amount means denser, not louder; use the band level to
+/// rebalance. With every band neutral (amount 0, 0 dB) the processor is
+/// bypassed and the crossovers sleep.
+#[derive(Copy, Clone, Debug)]
+pub struct MultibandTexture {
+    xover: [Crossover3; 2],
+    amount: [f32; 3],
+    drive: [f32; 3],
+    level_db: [f32; 3],
+    gain: [f32; 3],
+    active: bool,
+}
+
+impl MultibandTexture {
+    pub fn new(sr: f32) -> Self {
+        Self {
+            xover: [Crossover3::new(TEXTURE_XOVER_HZ.0, TEXTURE_XOVER_HZ.1, sr); 2],
+            amount: [0.0; 3],
+            drive: [1.0; 3],
+            level_db: [0.0; 3],
+            gain: [1.0; 3],
+            active: false,
+        }
+    }
+
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        for x in &mut self.xover { x.set_sample_rate(sr); }
+    }
+
+    /// Crossover frequencies between low/mid and mid/high, each 40..16000 Hz.
+    pub fn set_crossovers(&mut self, lo_hz: f32, hi_hz: f32) {
+        let (lo, hi) = (lo_hz.clamp(40.0, 16_000.0), hi_hz.clamp(40.0, 16_000.0));
+        for x in &mut self.xover { x.set_freqs_hz(lo, hi); }
+    }
+    #[inline] pub fn crossovers(&self) -> (f32, f32) { self.xover[0].freqs_hz() }
+
+    /// Saturation `amount` (0..1) and `level_db` (±12) of band 0 (low), 1
+    /// (mid) or 2 (high); other bands are ignored.
+    pub fn set_band(&mut self, band: usize, amount: f32, level_db: f32) {
+        if band >= 3 { return; }
+        self.amount[band] = amount.clamp(0.0, 1.0);
+        self.drive[band] = db_to_lin(TEXTURE_MAX_DRIVE_DB * self.amount[band]);
+        self.level_db[band] = level_db.clamp(-12.0, 12.0);
+        self.gain[band] = db_to_lin(self.level_db[band]);
+        let active = self.amount.iter().chain(&self.level_db).any(|&v| v != 0.0);
+        if active && !self.active { self.reset(); }
+        self.active = active;
+    }
+
+    /// `(amount, level_db)` of `band` (0 = low, 1 = mid, 2 = high); `None`
+    /// for any other band, like [`MultibandTexture::set_band`] ignores them.
+    #[inline]
+    pub fn band(&self, band: usize) -> Option<(f32, f32)> {
+        Some((*self.amount.get(band)?, self.level_db[band]))
+    }
+    /// `(amount, level_db)` of the low, mid and high bands.
+    #[inline] pub fn bands(&self) -> [(f32, f32); 3] { core::array::from_fn(|b| (self.amount[b], self.level_db[b])) }
+    /// `true` unless every band is neutral.
+    #[inline] pub fn is_active(&self) -> bool { self.active }
+
+    pub fn reset(&mut self) {
+        for x in &mut self.xover { x.reset(); }
+    }
+
+    #[inline]
+    fn shape(&self, band: usize, x: f32) -> f32 {
+        let a = self.amount[band];
+        let y = if a == 0.0 { x } else {
+            let d = self.drive[band];
+            x + a * (soft_clip(d * x) / d - x)
+        };
+        self.gain[band] * y
+    }
+
+    #[inline]
+    fn channel(&mut self, ch: usize, x: f32) -> f32 {
+        let (lo, mid, hi) = self.xover[ch].split(x);
+        self.shape(0, lo) + self.shape(1, mid) + self.shape(2, hi)
+    }
+
+    #[inline]
+    pub fn process(&mut self, l: f32, r: f32) -> (f32, f32) {
+        if !self.active { return (l, r); }
+        (self.channel(0, l), self.channel(1, r))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f32::consts::TAU;
+
+    const SR: f32 = 48_000.0;
+
+    fn sine(hz: f32, amp: f32, n: usize) -> impl Iterator<Item = f32> {
+        (0..n).map(move |i| amp * (TAU * hz * i as f32 / SR).sin())
+    }
+
+    /// Amplitude of the `hz` component of `x` (one DFT bin).
+    fn tone_level(x: &[f32], hz: f32) -> f32 {
+        let (mut re, mut im) = (0.0f64, 0.0f64);
+        for (i, &v) in x.iter().enumerate() {
+            let w = core::f64::consts::TAU * f64::from(hz) * i as f64 / f64::from(SR);
+            re += f64::from(v) * w.cos();
+            im += f64::from(v) * w.sin();
+        }
+        (2.0 * (re * re + im * im).sqrt() / x.len() as f64) as f32
+    }
+
+    fn rms(x: &[f32]) -> f32 { (x.iter().map(|v| v * v).sum::<f32>() / x.len() as f32).sqrt() }
+
+    #[test]
+    fn freq_shifter_moves_1k_to_1k1() {
+        let mut f = FreqShifter::new(100.0, SR);
+        let y: Vec<f32> = sine(1000.0, 0.5, 48_000).map(|x| f.process(x)).skip(4_800).collect();
+        let (moved, left, mirror) = (tone_level(&y, 1100.0), tone_level(&y, 1000.0), tone_level(&y, 900.0));
+        assert!((moved - 0.5).abs() < 0.05, "1.1 kHz at {moved}");
+        assert!(left < 0.01 && mirror < 0.01, "1 kHz at {left}, 900 Hz at {mirror}");
+
+        // and down
+        f.clear();
+        f.set_shift_hz(-100.0);
+        let y: Vec<f32> = sine(1000.0, 0.5, 48_000).map(|x| f.process(x)).skip(4_800).collect();
+        assert!(tone_level(&y, 900.0) > 0.45 && tone_level(&y, 1100.0) < 0.01);
+    }
+
+    /// Cutoff of `w` after a steady input at `level` has settled.
+    fn wah_cutoff(w: &mut AutoWah, level: f32) -> f32 {
+        for _ in 0..24_000 { w.process(level); }
+        w.cutoff_hz()
+    }
+
+    #[test]
+    fn auto_wah_tracks_level_across_range() {
+        let (lo, hi) = WAH_RANGE_HZ;
+        for direction in [WahDirection::Up, WahDirection::Down] {
+            let mut w = AutoWah::new(SR);
+            w.set_direction(direction);
+            // sensitivity 0.5: a full sweep at -20 dBFS, even in octaves
+            let levels = [0.0, 0.02, 0.05, 0.08, 0.1, 0.5];
+            let cuts: Vec<f32> = levels.iter().map(|&l| wah_cutoff(&mut w, l)).collect();
+            let (rest, hit) = if direction == WahDirection::Up { (lo, hi) } else { (hi, lo) };
+            assert!((cuts[0] - rest).abs() < 1.0, "{direction:?} at rest: {}", cuts[0]);
+            assert!((cuts[4] - hit).abs() < 0.01 * hit && (cuts[5] - hit).abs() < 1.0, "{direction:?}: {cuts:?}");
+            // half the full level is halfway through the range in octaves
+            assert!((cuts[2] / (lo * hi).sqrt() - 1.0).abs() < 0.02, "{direction:?} midpoint: {}", cuts[2]);
+            for pair in cuts.windows(2) {
+                match direction {
+                    WahDirection::Up => assert!(pair[1] >= pair[0], "{cuts:?}"),
+                    WahDirection::Down => assert!(pair[1] <= pair[0], "{cuts:?}"),
+                }
+            }
+            // and back once the input has stopped for a while (150 ms release)
+            for _ in 0..3 { wah_cutoff(&mut w, 0.0); }
+            assert!((wah_cutoff(&mut w, 0.0) - rest).abs() < 1.0);
+        }
+
+        // sensitivity 1: a full sweep already at -40 dBFS
+        let mut w = AutoWah::new(SR);
+        w.set_sensitivity(1.0);
+        assert!((wah_cutoff(&mut w, 0.01) - hi).abs() < 0.01 * hi);
+        let mut w = AutoWah::new(SR);
+        w.set_sensitivity(1.0);
+        w.set_range(200.0, 800.0);
+        assert!((wah_cutoff(&mut w, 0.005) - 400.0).abs() < 8.0);
+    }
+
+    /// A dark pad: a loud 200 Hz fundamental and a faint 4 kHz partial.
+    fn dark_pad(n: usize) -> Vec<f32> {
+        sine(200.0, 0.4, n).zip(sine(4000.0, 0.05, n)).map(|(a, b)| a + b).collect()
+    }
+
+    #[test]
+    fn exciter_adds_harmonics_above_freq() {
+        let x = dark_pad(48_000);
+        let mut e = Exciter::new(SR);
+        assert!(x.iter().all(|&v| e.process(v) == v), "amount 0 must be a bypass");
+
+        e.set_amount(1.0);
+        e.set_drive(20.0);
+        let y: Vec<f32> = x.iter().map(|&v| e.process(v)).collect();
+        let (x, y) = (&x[24_000..], &y[24_000..]);
+        // odd harmonics of the 4 kHz partial; nothing new from the 200 Hz below `freq`
+        assert!(tone_level(x, 12_000.0) < 1e-4);
+        assert!(tone_level(y, 12_000.0) > 2e-3, "12 kHz at {}", tone_level(y, 12_000.0));
+        assert!(tone_level(y, 600.0) < 1e-3);
+    }
+
+    #[test]
+    fn exciter_harmonics_leave_the_band_out() {
+        for drive in [1.0, 10.0, 50.0] {
+            let mut e = Exciter::new(SR);
+            e.set_drive(drive);
+            // 3.5 kHz: none of the clipper's low harmonics alias back onto it
+            let h: Vec<f32> = sine(3500.0, 0.5, 48_000).map(|v| e.harmonics(0, v)).collect();
+            let h = &h[24_000..];
+            let (band, third) = (tone_level(h, 3500.0), tone_level(h, 10_500.0));
+            assert!(band < 0.05 * third, "drive {drive}: 3.5 kHz {band} vs 10.5 kHz {third}");
+        }
+    }
+
+    #[test]
+    fn exciter_matches_input_level() {
+        for (drive, amount) in [(1.0, 0.3), (10.0, 1.0), (50.0, 1.0)] {
+            let mut e = Exciter::new(SR);
+            e.set_drive(drive);
+            e.set_amount(amount);
+            e.set_freq_hz(1000.0);
+            // bright input: the band is most of the signal, the worst case
+            let x: Vec<f32> = sine(3000.0, 0.5, 48_000).zip(sine(5000.0, 0.3, 48_000)).map(|(a, b)| a + b).collect();
+            let y: Vec<f32> = x.iter().map(|&v| e.process(v)).collect();
+            for w in (24_000..48_000).step_by(4_800) {
+                let ratio = rms(&y[w..w + 4_800]) / rms(&x[w..w + 4_800]);
+                // within ~0.5 dB of the input, never above it
+                assert!((0.94..=1.01).contains(&ratio), "drive {drive} amount {amount}: ratio {ratio}");
+            }
+        }
+
+        // stereo is matched on the pair
+        let mut e = Exciter::new(SR);
+        e.set_amount(1.0);
+        let x = dark_pad(48_000);
+        let (mut sx, mut sy) = (0.0, 0.0);
+        for (i, &v) in x.iter().enumerate() {
+            let (l, r) = e.process_stereo(v, 0.5 * v);
+            if i >= 24_000 {
+                sx += 1.25 * v * v;
+                sy += l * l + r * r;
+            }
+        }
+        assert!(sy <= sx * 1.02, "stereo turned up: {}", (sy / sx).sqrt());
+    }
+
+    /// Gain in dB `d` gives a steady `hz` tone of amplitude `amp`, once settled.
+    fn dyn_eq_gain_db(d: &mut DynamicEq, hz: f32, amp: f32) -> f32 {
+        let y: Vec<f32> = sine(hz, amp, 48_000).map(|x| d.process(x, x).0).collect();
+        lin_to_db(tone_level(&y[24_000..], hz) / amp)
+    }
+
+    #[test]
+    fn dynamic_eq_cuts_only_a_loud_band() {
+        // -20 dBFS at the band: 10 dB over the threshold at ratio 4 is ~7.5 dB
+        let mut d = DynamicEq::new(1000.0, SR);
+        let g = dyn_eq_gain_db(&mut d, 1000.0, 0.1);
+        assert!((-8.5..=-6.5).contains(&g), "cut {g} dB");
+        assert!((d.reduction_db() - g).abs() < 0.1, "meter {} dB", d.reduction_db());
+
+        // -6 dBFS would want 18 dB: held at the 12 dB range
+        let mut d = DynamicEq::new(1000.0, SR);
+        assert!((dyn_eq_gain_db(&mut d, 1000.0, 0.5) + 12.0).abs() < 0.1);
+
+        // quiet, or loud away from the band: untouched
+        for (hz, amp) in [(1000.0, 0.02), (100.0, 0.5), (8000.0, 0.5)] {
+            let mut d = DynamicEq::new(1000.0, SR);
+            let g = dyn_eq_gain_db(&mut d, hz, amp);
+            assert!(g.abs() < 0.01 && d.reduction_db() == 0.0, "{hz} Hz at {amp}: {g} dB");
+        }
+    }
+
+    #[test]
+    fn dynamic_eq_releases_when_the_band_calms_down() {
+        let mut d = DynamicEq::new(1000.0, SR);
+        dyn_eq_gain_db(&mut d, 1000.0, 0.1);
+        assert!(d.reduction_db() < -6.0);
+        for _ in 0..24_000 { d.process(0.0, 0.0); }
+        assert!(d.reduction_db() > -0.05, "still cutting {} dB", d.reduction_db());
+
+        // a range of 0 never cuts
+        d.set_range_db(0.0);
+        assert!(dyn_eq_gain_db(&mut d, 1000.0, 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn texture_bands_past_the_high_band_are_ignored() {
+        let mut t = MultibandTexture::new(SR);
+        t.set_band(2, 0.5, -3.0);
+        t.set_band(3, 1.0, 6.0);
+        assert_eq!(t.band(2), Some((0.5, -3.0)));
+        assert_eq!(t.band(3), None);
+        assert_eq!(t.bands(), [(0.0, 0.0), (0.0, 0.0), (0.5, -3.0)]);
+    }
+}
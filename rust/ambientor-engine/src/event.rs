@@ -0,0 +1,113 @@
+//! Events scenes report back to the host.
+//!
+//! A scene that strikes a note, rolls thunder or moves on to a new section
+//! pushes a [`SceneEvent`] into its own [`EventQueue`]; hosts drain them with
+//! [`Engine::poll_event`](crate::Engine::poll_event) after each rendered block,
+//! e.g. to flash a visualizer in time with the music. Events are informative
+//! only: nobody has to poll, and an unread queue simply drops its oldest entries.
+//!
+//! Of the built-in scenes, thunderstorm, cave-drips and tape-loop report
+//! events; the others, and graph scenes loaded from scene files, never do.
+
+/// Events a scene's [`EventQueue`] holds before the oldest are dropped.
+pub const EVENT_QUEUE_CAPACITY: usize = 64;
+
+/// Something audible a scene just started.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SceneEvent {
+    /// A pitched note began: `pitch` in Hz, `amp` its velocity 0..1.
+    NoteTriggered { pitch: f32, amp: f32 },
+    /// A thunder roll began, `amp` its peak level 0..1.
+    Thunder { amp: f32 },
+    /// The scene moved on to section `n` (e.g. the tape loop's next chord).
+    SectionChanged(u32),
+}
+
+/// Fixed-capacity FIFO of [`SceneEvent`]s.
+///
+/// A plain array ring (so scenes holding one stay `Copy`) that never
+/// allocates; when full, the oldest event makes room for the newest.
+#[derive(Copy, Clone, Debug)]
+pub struct EventQueue {
+    events: [SceneEvent; EVENT_QUEUE_CAPACITY],
+    head: usize, // oldest event
+    len: usize,
+    dropped: u64,
+}
+
+impl Default for EventQueue {
+    fn default() -> Self { Self::new() }
+}
+
+impl EventQueue {
+    pub fn new() -> Self {
+        Self { events: [SceneEvent::SectionChanged(0); EVENT_QUEUE_CAPACITY], head: 0, len: 0, dropped: 0 }
+    }
+
+    #[inline]
+    pub fn push(&mut self, ev: SceneEvent) {
+        if self.len == EVENT_QUEUE_CAPACITY {
+            self.head = (self.head + 1) % EVENT_QUEUE_CAPACITY;
+            self.len -= 1;
+            self.dropped += 1;
+        }
+        self.events[(self.head + self.len) % EVENT_QUEUE_CAPACITY] = ev;
+        self.len += 1;
+    }
+
+    /// Oldest queued event.
+    #[inline]
+    pub fn pop(&mut self) -> Option<SceneEvent> {
+        if self.len == 0 { return None; }
+        let ev = self.events[self.head];
+        self.head = (self.head + 1) % EVENT_QUEUE_CAPACITY;
+        self.len -= 1;
+        Some(ev)
+    }
+
+    #[inline] pub fn len(&self) -> usize { self.len }
+    #[inline] pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Events lost to a full queue since it was created.
+    #[inline] pub fn dropped(&self) -> u64 { self.dropped }
+
+    #[inline] pub fn clear(&mut self) { self.len = 0; }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_come_out_in_order_across_the_wrap() {
+        let mut q = EventQueue::new();
+        let mut next = 0;
+        for round in 0..3 {
+            for i in 0..EVENT_QUEUE_CAPACITY - 5 { q.push(SceneEvent::SectionChanged((round * 100 + i) as u32)); }
+            while let Some(ev) = q.pop() {
+                assert_eq!(ev, SceneEvent::SectionChanged((round * 100 + next) as u32));
+                next += 1;
+            }
+            assert_eq!(next, EVENT_QUEUE_CAPACITY - 5);
+            next = 0;
+        }
+        assert_eq!(q.dropped(), 0);
+    }
+
+    #[test]
+    fn a_full_queue_drops_the_oldest() {
+        let mut q = EventQueue::new();
+        let extra = 10;
+        for i in 0..EVENT_QUEUE_CAPACITY + extra { q.push(SceneEvent::SectionChanged(i as u32)); }
+        assert_eq!(q.len(), EVENT_QUEUE_CAPACITY);
+        assert_eq!(q.dropped(), extra as u64);
+        let kept: Vec<SceneEvent> = core::iter::from_fn(|| q.pop()).collect();
+        let want: Vec<SceneEvent> = (extra..EVENT_QUEUE_CAPACITY + extra).map(|i| SceneEvent::SectionChanged(i as u32)).collect();
+        assert_eq!(kept, want);
+        assert!(q.is_empty());
+
+        q.push(SceneEvent::Thunder { amp: 0.5 });
+        q.clear();
+        assert_eq!((q.pop(), q.dropped()), (None, extra as u64));
+    }
+}
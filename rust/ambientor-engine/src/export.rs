@@ -0,0 +1,651 @@
+//! Offline export: float → 16-bit PCM quantization and WAV files.
+//!
+//! Truncating a float render straight to 16 bits turns quiet material (long
+//! reverb tails, fade-outs) into correlated, gritty distortion. [`Dither`] adds
+//! TPDF noise (±1 LSB, triangular) before rounding so the error becomes a
+//! constant, signal-independent hiss at about -96 dBFS, and can optionally
+//! shape that hiss towards high frequencies where it is less audible.
+//!
+//! [`render_loop`] turns any render into a seamlessly loopable file, and
+//! [`AudioFileWriter`] writes renders as 16-bit WAV or, with the `vorbis`
+//! feature, as Ogg Vorbis (roughly a tenth of the size for long renders).
+//! WAV output switches to RF64 past 4 GB and can carry Broadcast Wave metadata.
+
+use crate::nodes::Rng;
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::f32::consts::FRAC_PI_2;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Block size (frames) used by the offline helpers.
+pub const EXPORT_BLOCK_FRAMES: usize = 1024;
+
+/// How floats are rounded to 16-bit PCM.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DitherMode {
+    /// Plain rounding (only for material that is already 16-bit clean).
+    Off,
+    /// Triangular PDF dither, ±1 LSB.
+    #[default]
+    Tpdf,
+    /// TPDF plus first-order error-feedback noise shaping: less hiss in the
+    /// midrange, more near Nyquist.
+    Shaped,
+}
+
+impl DitherMode {
+    pub const ALL: [DitherMode; 3] = [DitherMode::Off, DitherMode::Tpdf, DitherMode::Shaped];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DitherMode::Off => "off",
+            DitherMode::Tpdf => "tpdf",
+            DitherMode::Shaped => "shaped",
+        }
+    }
+
+    /// Parse `off` / `tpdf` / `shaped` (case-insensitive).
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.name().eq_ignore_ascii_case(s.trim()))
+    }
+}
+
+/// Stateful float → i16 quantizer for interleaved audio.
+///
+/// Keep one instance per export so the noise-shaping error carries across blocks.
+#[derive(Clone, Debug)]
+pub struct Dither {
+    mode: DitherMode,
+    rng: Rng,
+    err: Vec<f32>, // last quantization error per channel, in LSB
+}
+
+impl Dither {
+    pub fn new(mode: DitherMode, channels: usize) -> Self {
+        Self { mode, rng: Rng::new(0x0D17_4E12), err: vec![0.0; channels.max(1)] }
+    }
+
+    #[inline] pub fn mode(&self) -> DitherMode { self.mode }
+    #[inline] pub fn channels(&self) -> usize { self.err.len() }
+
+    pub fn set_mode(&mut self, mode: DitherMode) {
+        self.mode = mode;
+        self.reset();
+    }
+
+    /// Change the channel count (clears the error state).
+    pub fn set_channels(&mut self, channels: usize) {
+        self.err = vec![0.0; channels.max(1)];
+    }
+
+    pub fn reset(&mut self) { self.err.fill(0.0); }
+
+    /// Quantize one sample of channel `ch` (input is clamped to [-1, 1]).
+    #[inline]
+    pub fn quantize(&mut self, ch: usize, x: f32) -> i16 {
+        let scale = f32::from(i16::MAX);
+        let x = if x.is_finite() { x.clamp(-1.0, 1.0) * scale } else { 0.0 };
+        let y = match self.mode {
+            DitherMode::Off => x.round(),
+            DitherMode::Tpdf => (x + self.tpdf()).round(),
+            DitherMode::Shaped => {
+                let n = self.err.len();
+                let e = &mut self.err[ch % n];
+                // subtract last sample's error: error spectrum gets a (1 - z⁻¹) highpass
+                let u = x - *e;
+                let d = self.rng.next01() - self.rng.next01();
+                let y = (u + d).round().clamp(-scale - 1.0, scale);
+                *e = y - u;
+                y
+            }
+        };
+        y.clamp(-scale - 1.0, scale) as i16
+    }
+
+    /// Quantize interleaved `input` into `out` (up to the shorter of the two).
+    pub fn quantize_interleaved(&mut self, input: &[f32], out: &mut [i16]) {
+        let ch = self.channels();
+        for (i, (y, &x)) in out.iter_mut().zip(input).enumerate() {
+            *y = self.quantize(i % ch, x);
+        }
+    }
+
+    /// Triangular noise in (-1, 1) LSB.
+    #[inline]
+    fn tpdf(&mut self) -> f32 { self.rng.next01() - self.rng.next01() }
+}
+
+/// Render `frames` frames in blocks: `render` fills interleaved blocks of
+/// `channels` channels and `sink` receives them (same contract as [`render_loop`]).
+pub fn render_blocks<E>(
+    channels: usize,
+    frames: usize,
+    mut render: impl FnMut(&mut [f32]),
+    mut sink: impl FnMut(&[f32]) -> Result<(), E>,
+) -> Result<(), E> {
+    let ch = channels.max(1);
+    let mut block = vec![0.0f32; EXPORT_BLOCK_FRAMES * ch];
+    let mut remaining = frames;
+    while remaining > 0 {
+        let n = remaining.min(EXPORT_BLOCK_FRAMES);
+        render(&mut block[..n * ch]);
+        sink(&block[..n * ch])?;
+        remaining -= n;
+    }
+    Ok(())
+}
+
+/// Render a seamless loop of `loop_frames` frames with a `fade_frames` crossfade.
+///
+/// Renders `loop_frames + fade_frames` frames in total and overlap-adds the
+/// extra tail onto the first `fade_frames`. The result is rotated so only that
+/// head has to be kept in memory: `sink` receives frames `fade..loop` as they
+/// are rendered, then the crossfade region, whose end runs straight back into
+/// the first frame. Played end-to-end the output has no seam.
+///
+/// `render` fills interleaved blocks of `channels` channels; `sink` gets them in
+/// output order. The crossfade is equal-power (sin/cos), which keeps the level
+/// steady for uncorrelated material like evolving ambience. `fade_frames` is
+/// capped at `loop_frames`.
+pub fn render_loop<E>(
+    channels: usize,
+    loop_frames: usize,
+    fade_frames: usize,
+    mut render: impl FnMut(&mut [f32]),
+    mut sink: impl FnMut(&[f32]) -> Result<(), E>,
+) -> Result<(), E> {
+    let ch = channels.max(1);
+    let fade = fade_frames.min(loop_frames);
+
+    let mut head = vec![0.0f32; fade * ch];
+    for block in head.chunks_mut(EXPORT_BLOCK_FRAMES * ch) {
+        render(block);
+    }
+
+    render_blocks(ch, loop_frames - fade, &mut render, &mut sink)?;
+
+    let mut block = vec![0.0f32; EXPORT_BLOCK_FRAMES * ch];
+
+    // tail fades out while the head fades in; ends where the body above starts
+    let mut done = 0;
+    while done < fade {
+        let n = (fade - done).min(EXPORT_BLOCK_FRAMES);
+        let tail = &mut block[..n * ch];
+        render(tail);
+        for (i, (t, h)) in tail.chunks_exact_mut(ch).zip(head[done * ch..].chunks_exact(ch)).enumerate() {
+            let x = (done + i) as f32 + 0.5;
+            let (fade_in, fade_out) = (FRAC_PI_2 * x / fade as f32).sin_cos();
+            for (t, &h) in t.iter_mut().zip(h) {
+                *t = *t * fade_out + h * fade_in;
+            }
+        }
+        sink(tail)?;
+        done += n;
+    }
+    Ok(())
+}
+
+/// Container/codec for [`AudioFileWriter`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// 16-bit PCM WAV, dithered.
+    #[default]
+    Wav,
+    /// Ogg Vorbis, quality-based VBR (feature `vorbis`).
+    OggVorbis,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 2] = [ExportFormat::Wav, ExportFormat::OggVorbis];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ExportFormat::Wav => "wav",
+            ExportFormat::OggVorbis => "ogg",
+        }
+    }
+
+    /// Parse `wav` / `ogg` (also `vorbis`), case-insensitive.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("vorbis") { return Some(ExportFormat::OggVorbis); }
+        Self::ALL.into_iter().find(|f| f.name().eq_ignore_ascii_case(s))
+    }
+
+    /// Guess the format from a file extension (`.wav`, `.ogg`, `.oga`).
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "wav" | "wave" => Some(ExportFormat::Wav),
+            "ogg" | "oga" => Some(ExportFormat::OggVorbis),
+            _ => None,
+        }
+    }
+
+    /// `false` if this build lacks the encoder (see the crate features).
+    pub fn is_available(self) -> bool {
+        match self {
+            ExportFormat::Wav => true,
+            ExportFormat::OggVorbis => cfg!(feature = "vorbis"),
+        }
+    }
+}
+
+/// Encoder settings for [`AudioFileWriter`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportOptions {
+    /// Quantization for 16-bit formats.
+    pub dither: DitherMode,
+    /// Quality for lossy formats, 0 (smallest) .. 1 (best); ignored by WAV.
+    pub quality: f32,
+    /// Broadcast Wave metadata for WAV output; ignored by other formats.
+    pub bext: Option<BextInfo>,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self { Self { dither: DitherMode::default(), quality: 0.6, bext: None } }
+}
+
+/// Writes rendered float audio to a file in any [`ExportFormat`].
+///
+/// Feed interleaved blocks to [`AudioFileWriter::write`], then call
+/// [`AudioFileWriter::finish`]; dropping the writer unfinished leaves an
+/// incomplete file. Each block is encoded and written as it arrives, so memory
+/// use does not grow with the length of the render.
+pub struct AudioFileWriter {
+    inner: WriterInner,
+}
+
+enum WriterInner {
+    Wav { wav: WavWriter, channels: u16, dither: Dither, pcm: Vec<i16> },
+    #[cfg(feature = "vorbis")]
+    Vorbis { enc: Box<vorbis_rs::VorbisEncoder<BufWriter<File>>>, planar: Vec<Vec<f32>> }, // boxed: the encoder state is large
+}
+
+impl AudioFileWriter {
+    /// Create `path` for `channels` channels at `sample_rate`. Fails with
+    /// `Unsupported` if `format` is not compiled in.
+    pub fn create(
+        path: impl AsRef<Path>,
+        format: ExportFormat,
+        sample_rate: u32,
+        channels: u16,
+        opts: &ExportOptions,
+    ) -> io::Result<Self> {
+        if !format.is_available() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("{} export needs the `vorbis` feature", format.name()),
+            ));
+        }
+        let channels = channels.max(1);
+        let inner = match format {
+            ExportFormat::Wav => WriterInner::Wav {
+                wav: WavWriter::create_with(path, sample_rate, channels, opts.bext.as_ref())?,
+                channels,
+                dither: Dither::new(opts.dither, usize::from(channels)),
+                pcm: Vec::new(),
+            },
+            #[cfg(feature = "vorbis")]
+            ExportFormat::OggVorbis => {
+                let file = BufWriter::new(File::create(path)?);
+                use std::num::{NonZeroU32, NonZeroU8};
+                use vorbis_rs::{VorbisBitrateManagementStrategy, VorbisEncoderBuilder};
+                let invalid = || io::Error::new(io::ErrorKind::InvalidInput, "Vorbis needs 1..255 channels and a non-zero rate");
+                let sr = NonZeroU32::new(sample_rate).ok_or_else(invalid)?;
+                let ch = u8::try_from(channels).ok().and_then(NonZeroU8::new).ok_or_else(invalid)?;
+                let enc = VorbisEncoderBuilder::new(sr, ch, file)
+                    .map_err(io::Error::other)?
+                    .bitrate_management_strategy(VorbisBitrateManagementStrategy::QualityVbr {
+                        target_quality: opts.quality.clamp(0.0, 1.0),
+                    })
+                    .build()
+                    .map_err(io::Error::other)?;
+                WriterInner::Vorbis { enc: Box::new(enc), planar: vec![Vec::new(); usize::from(channels)] }
+            }
+            #[cfg(not(feature = "vorbis"))]
+            ExportFormat::OggVorbis => unreachable!("checked by is_available"),
+        };
+        Ok(Self { inner })
+    }
+
+    /// Append interleaved frames (a trailing partial frame is ignored).
+    pub fn write(&mut self, interleaved: &[f32]) -> io::Result<()> {
+        match &mut self.inner {
+            WriterInner::Wav { wav, channels, dither, pcm } => {
+                let n = interleaved.len() / usize::from(*channels) * usize::from(*channels);
+                pcm.resize(n, 0);
+                dither.quantize_interleaved(&interleaved[..n], pcm);
+                wav.write_i16(pcm)
+            }
+            #[cfg(feature = "vorbis")]
+            WriterInner::Vorbis { enc, planar } => {
+                let ch = planar.len();
+                for (c, p) in planar.iter_mut().enumerate() {
+                    p.clear();
+                    p.extend(interleaved.chunks_exact(ch).map(|f| f[c]));
+                }
+                enc.encode_audio_block(planar.as_slice()).map_err(io::Error::other)
+            }
+        }
+    }
+
+    /// Flush everything and close the file.
+    pub fn finish(self) -> io::Result<()> {
+        match self.inner {
+            WriterInner::Wav { wav, .. } => wav.finish(),
+            #[cfg(feature = "vorbis")]
+            WriterInner::Vorbis { enc, .. } => enc.finish().map_err(io::Error::other)?.flush(),
+        }
+    }
+}
+
+/// Broadcast Wave (`bext`, EBU Tech 3285) metadata for archival renders.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BextInfo {
+    /// Free text, up to 256 bytes (longer text is cut).
+    pub description: String,
+    /// Who made the file, up to 32 bytes.
+    pub originator: String,
+    /// Originator's reference (e.g. scene name), up to 32 bytes.
+    pub originator_reference: String,
+    /// When the render started; written as a UTC date and time.
+    pub origination: SystemTime,
+}
+
+impl BextInfo {
+    /// `description` with originator "Ambientor", originating now.
+    pub fn new(description: impl Into<String>) -> Self {
+        Self {
+            description: description.into(),
+            originator: "Ambientor".into(),
+            originator_reference: String::new(),
+            origination: SystemTime::now(),
+        }
+    }
+
+    /// Size of the fixed part of the `bext` chunk (version 1, no coding history).
+    const CHUNK_BYTES: usize = 602;
+
+    fn to_chunk(&self) -> Vec<u8> {
+        let mut b = Vec::with_capacity(Self::CHUNK_BYTES);
+        let mut text = |s: &str, len: usize| {
+            let mut end = s.len().min(len);
+            while !s.is_char_boundary(end) { end -= 1; }
+            b.extend_from_slice(&s.as_bytes()[..end]);
+            b.resize(b.len() + len - end, 0);
+        };
+        let secs = self.origination.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let (y, m, d) = civil_from_days(secs / 86_400);
+        let t = secs % 86_400;
+        text(&self.description, 256);
+        text(&self.originator, 32);
+        text(&self.originator_reference, 32);
+        text(&format!("{y:04}-{m:02}-{d:02}"), 10);
+        text(&format!("{:02}:{:02}:{:02}", t / 3600, t / 60 % 60, t % 60), 8);
+        b.extend_from_slice(&0u64.to_le_bytes()); // time reference (samples since midnight)
+        b.extend_from_slice(&1u16.to_le_bytes()); // version
+        b.resize(Self::CHUNK_BYTES, 0); // UMID, loudness (v2 only), reserved
+        b
+    }
+}
+
+/// Gregorian `(year, month, day)` for a day count since 1970-01-01.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    // Howard Hinnant's days_from_civil, inverted
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + u64::from(m <= 2);
+    (y, m, d)
+}
+
+/// Body size of the `ds64` chunk, reserved as `JUNK` until a file needs RF64.
+const DS64_BYTES: u32 = 28;
+
+/// Streaming 16-bit PCM WAV writer.
+///
+/// Samples go straight to disk and the sizes are patched in
+/// [`WavWriter::finish`], so memory use stays constant however long the render.
+/// Files that outgrow the 4 GB RIFF limit are finished as RF64 (EBU Tech 3306):
+/// a `JUNK` chunk reserved after the header becomes the `ds64` size table.
+/// Optional [`BextInfo`] makes the file a Broadcast Wave.
+///
+/// A writer dropped without `finish` leaves a file whose header claims no data.
+pub struct WavWriter {
+    file: BufWriter<File>,
+    block_align: u64,
+    data_size_pos: u64,
+    data_bytes: u64,
+}
+
+impl WavWriter {
+    /// Create `path` and write a header for `channels` channels at `sample_rate`.
+    pub fn create(path: impl AsRef<Path>, sample_rate: u32, channels: u16) -> io::Result<Self> {
+        Self::create_with(path, sample_rate, channels, None)
+    }
+
+    /// [`WavWriter::create`] with an optional Broadcast Wave `bext` chunk.
+    pub fn create_with(
+        path: impl AsRef<Path>,
+        sample_rate: u32,
+        channels: u16,
+        bext: Option<&BextInfo>,
+    ) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let channels = channels.max(1);
+        let block_align = channels * 2;
+
+        file.write_all(b"RIFF")?;
+        file.write_all(&0u32.to_le_bytes())?; // patched in finish()
+        file.write_all(b"WAVE")?;
+
+        // placeholder for ds64 (RF64); readers skip JUNK
+        file.write_all(b"JUNK")?;
+        file.write_all(&DS64_BYTES.to_le_bytes())?;
+        file.write_all(&[0; DS64_BYTES as usize])?;
+
+        if let Some(bext) = bext {
+            file.write_all(b"bext")?;
+            file.write_all(&(BextInfo::CHUNK_BYTES as u32).to_le_bytes())?;
+            file.write_all(&bext.to_chunk())?;
+        }
+
+        file.write_all(b"fmt ")?;
+        file.write_all(&16u32.to_le_bytes())?; // chunk size
+        file.write_all(&1u16.to_le_bytes())?; // PCM
+        file.write_all(&channels.to_le_bytes())?;
+        file.write_all(&sample_rate.to_le_bytes())?;
+        file.write_all(&(sample_rate * u32::from(block_align)).to_le_bytes())?;
+        file.write_all(&block_align.to_le_bytes())?;
+        file.write_all(&16u16.to_le_bytes())?; // bits per sample
+
+        file.write_all(b"data")?;
+        let data_size_pos = file.stream_position()?;
+        file.write_all(&0u32.to_le_bytes())?; // patched in finish()
+        Ok(Self { file, block_align: u64::from(block_align), data_size_pos, data_bytes: 0 })
+    }
+
+    /// Append interleaved samples.
+    pub fn write_i16(&mut self, samples: &[i16]) -> io::Result<()> {
+        for s in samples {
+            self.file.write_all(&s.to_le_bytes())?;
+        }
+        self.data_bytes += 2 * samples.len() as u64;
+        Ok(())
+    }
+
+    /// Bytes of sample data written so far.
+    #[inline] pub fn data_bytes(&self) -> u64 { self.data_bytes }
+
+    /// Patch the header sizes (switching to RF64 past 4 GB) and close the file.
+    pub fn finish(mut self) -> io::Result<()> {
+        let riff_bytes = self.data_size_pos + 4 + self.data_bytes - 8;
+        match (u32::try_from(riff_bytes), u32::try_from(self.data_bytes)) {
+            (Ok(riff), Ok(data)) => {
+                self.file.seek(SeekFrom::Start(4))?;
+                self.file.write_all(&riff.to_le_bytes())?;
+                self.file.seek(SeekFrom::Start(self.data_size_pos))?;
+                self.file.write_all(&data.to_le_bytes())?;
+            }
+            _ => {
+                self.file.seek(SeekFrom::Start(0))?;
+                self.file.write_all(b"RF64")?;
+                self.file.write_all(&u32::MAX.to_le_bytes())?; // see ds64
+                self.file.seek(SeekFrom::Start(12))?;
+                self.file.write_all(b"ds64")?;
+                self.file.write_all(&DS64_BYTES.to_le_bytes())?;
+                self.file.write_all(&riff_bytes.to_le_bytes())?;
+                self.file.write_all(&self.data_bytes.to_le_bytes())?;
+                self.file.write_all(&(self.data_bytes / self.block_align).to_le_bytes())?;
+                self.file.write_all(&0u32.to_le_bytes())?; // no extra size table
+                self.file.seek(SeekFrom::Start(self.data_size_pos))?;
+                self.file.write_all(&u32::MAX.to_le_bytes())?;
+            }
+        }
+        self.file.flush()
+    }
+}
+
+/// Write interleaved 16-bit PCM as a canonical RIFF/WAVE file.
+pub fn write_wav_i16(path: impl AsRef<Path>, sample_rate: u32, channels: u16, data: &[i16]) -> io::Result<()> {
+    let mut w = WavWriter::create(path, sample_rate, channels)?;
+    w.write_i16(data)?;
+    w.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ambientor-export-{}-{name}", std::process::id()))
+    }
+
+    fn u16_at(b: &[u8], at: usize) -> u16 { u16::from_le_bytes([b[at], b[at + 1]]) }
+    fn u32_at(b: &[u8], at: usize) -> u32 { u32::from_le_bytes(b[at..at + 4].try_into().unwrap()) }
+    fn u64_at(b: &[u8], at: usize) -> u64 { u64::from_le_bytes(b[at..at + 8].try_into().unwrap()) }
+
+    /// Offset of the body of the first `id` chunk after the RIFF header.
+    fn chunk(b: &[u8], id: &[u8; 4]) -> Option<usize> {
+        let mut at = 12;
+        while at + 8 <= b.len() {
+            if &b[at..at + 4] == id { return Some(at + 8); }
+            at += 8 + u32_at(b, at + 4) as usize;
+        }
+        None
+    }
+
+    /// Render `frames` of a stereo ramp through an [`AudioFileWriter`] and read the file back.
+    fn render_wav(name: &str, frames: usize, opts: &ExportOptions) -> Vec<u8> {
+        let path = temp_path(name);
+        let mut w = AudioFileWriter::create(&path, ExportFormat::Wav, 48_000, 2, opts).unwrap();
+        let mut t = 0;
+        render_blocks(2, frames, |block| {
+            for x in block.iter_mut() { *x = (t % 200) as f32 / 400.0; t += 1; }
+        }, |block| w.write(block)).unwrap();
+        w.finish().unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn wav_header_matches_the_render() {
+        let frames = 3 * EXPORT_BLOCK_FRAMES + 17;
+        let b = render_wav("plain.wav", frames, &ExportOptions::default());
+        assert_eq!(&b[0..4], b"RIFF");
+        assert_eq!(&b[8..12], b"WAVE");
+        assert_eq!(u32_at(&b, 4) as usize, b.len() - 8);
+
+        let fmt = chunk(&b, b"fmt ").unwrap();
+        assert_eq!(u16_at(&b, fmt), 1); // PCM
+        assert_eq!(u16_at(&b, fmt + 2), 2);
+        assert_eq!(u32_at(&b, fmt + 4), 48_000);
+        assert_eq!(u32_at(&b, fmt + 8), 48_000 * 4);
+        assert_eq!(u16_at(&b, fmt + 12), 4);
+        assert_eq!(u16_at(&b, fmt + 14), 16);
+
+        let data = chunk(&b, b"data").unwrap();
+        assert_eq!(u32_at(&b, data - 4) as usize, frames * 4);
+        assert_eq!(b.len(), data + frames * 4);
+        assert!(chunk(&b, b"bext").is_none());
+    }
+
+    #[test]
+    fn bext_chunk_carries_the_metadata() {
+        let bext = BextInfo {
+            description: "overnight render".into(),
+            originator: "Ambientor".into(),
+            originator_reference: "drone".into(),
+            origination: UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000),
+        };
+        let opts = ExportOptions { bext: Some(bext), ..ExportOptions::default() };
+        let b = render_wav("bext.wav", 100, &opts);
+        let at = chunk(&b, b"bext").unwrap();
+        assert_eq!(u32_at(&b, at - 4) as usize, BextInfo::CHUNK_BYTES);
+        assert!(b[at..].starts_with(b"overnight render\0"));
+        assert_eq!(&b[at + 256..at + 265], b"Ambientor");
+        assert_eq!(&b[at + 288..at + 293], b"drone");
+        assert_eq!(&b[at + 320..at + 338], b"2023-11-1422:13:20");
+        assert_eq!(u16_at(&b, at + 346), 1);
+        let data = chunk(&b, b"data").unwrap();
+        assert_eq!(b.len(), data + 100 * 4);
+    }
+
+    #[test]
+    fn oversized_files_finish_as_rf64() {
+        let path = temp_path("rf64.wav");
+        let mut w = WavWriter::create(&path, 48_000, 2).unwrap();
+        w.write_i16(&[1, 2, 3, 4]).unwrap();
+        w.data_bytes = 5 << 30; // pretend: only the header is checked
+        w.finish().unwrap();
+        let b = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(&b[0..4], b"RF64");
+        assert_eq!(u32_at(&b, 4), u32::MAX);
+        let ds64 = chunk(&b, b"ds64").unwrap();
+        let data = chunk(&b, b"data").unwrap();
+        assert_eq!(u64_at(&b, ds64), data as u64 + (5 << 30) - 8);
+        assert_eq!(u64_at(&b, ds64 + 8), 5 << 30);
+        assert_eq!(u64_at(&b, ds64 + 16), (5 << 30) / 4);
+        assert_eq!(u32_at(&b, data - 4), u32::MAX);
+    }
+
+    #[test]
+    fn loop_render_has_the_loop_length() {
+        let (loop_frames, fade) = (2 * EXPORT_BLOCK_FRAMES + 5, 300);
+        let mut rendered = 0;
+        let mut out = Vec::new();
+        render_loop::<()>(2, loop_frames, fade, |b| { b.fill(1.0); rendered += b.len() / 2; }, |b| {
+            out.extend_from_slice(b);
+            Ok(())
+        }).unwrap();
+        assert_eq!(rendered, loop_frames + fade);
+        assert_eq!(out.len(), 2 * loop_frames);
+    }
+
+    #[cfg(feature = "vorbis")]
+    #[test]
+    fn vorbis_render_is_an_ogg_stream() {
+        let path = temp_path("render.ogg");
+        let mut w = AudioFileWriter::create(&path, ExportFormat::OggVorbis, 48_000, 2, &ExportOptions::default()).unwrap();
+        render_blocks(2, 48_000, |b| {
+            for (i, x) in b.iter_mut().enumerate() { *x = 0.3 * (i as f32 * 0.05).sin(); }
+        }, |b| w.write(b)).unwrap();
+        w.finish().unwrap();
+        let b = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(b.starts_with(b"OggS"));
+        assert!(b.windows(6).any(|w| w == b"vorbis"));
+        // far smaller than the 192 KB the same second takes as WAV
+        assert!(b.len() > 1000 && b.len() < 64_000, "{} bytes", b.len());
+    }
+}
@@ -0,0 +1,445 @@
+//! Tiny arithmetic expressions for control signals, e.g.
+//! `800 + 400*sin(0.05*t) + 200*noise1`.
+//!
+//! [`Expr::parse`] compiles the text once, at load time, into a fixed-size
+//! postfix op list; [`Expr::eval`] runs that list on a small stack array, so an
+//! `Expr` is `Copy` and evaluating it never allocates. Scenes evaluate
+//! expressions once per control block, not per sample.
+//!
+//! Syntax: numbers, `pi`, the variables of [`Var`], `+ - * /`, `^` (power,
+//! right-associative), unary minus, parentheses, and the functions
+//! `sin cos tan abs sqrt exp ln floor` (one argument), `min max` (two) and
+//! `clamp(x, lo, hi)`. Angles are in radians.
+
+use core::fmt;
+
+/// Longest compiled expression (ops).
+pub const MAX_OPS: usize = 64;
+/// Deepest evaluation stack an expression may need.
+pub const MAX_STACK: usize = 16;
+/// Number of independent noise variables (`noise1` .. `noise4`).
+pub const NOISE_VARS: usize = 4;
+
+/// Deepest parenthesis / call nesting accepted by the parser.
+const MAX_NESTING: usize = 32;
+
+/// Inputs an expression can read.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Var {
+    /// Seconds since the scene started.
+    Time,
+    /// Seconds the engine has been running; unlike `t`, it carries on across
+    /// scene swaps and reloads.
+    Elapsed,
+    /// Host tempo, BPM (120 unless the host sets it).
+    Tempo,
+    /// The scene's intensity, 0..1.
+    Intensity,
+    /// Smooth random wander, about -1..1; `noise1` is the slowest.
+    Noise(u8),
+}
+
+impl Var {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "t" => Some(Var::Time),
+            "elapsed" => Some(Var::Elapsed),
+            "bpm" => Some(Var::Tempo),
+            "intensity" => Some(Var::Intensity),
+            _ => {
+                let n: u8 = s.strip_prefix("noise")?.parse().ok()?;
+                (1..=NOISE_VARS as u8).contains(&n).then_some(Var::Noise(n - 1))
+            }
+        }
+    }
+}
+
+/// Values of every [`Var`] for one evaluation.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ExprInputs {
+    pub t: f32,
+    pub elapsed: f32,
+    pub bpm: f32,
+    pub intensity: f32,
+    pub noise: [f32; NOISE_VARS],
+}
+
+impl ExprInputs {
+    #[inline]
+    fn get(&self, v: Var) -> f32 {
+        match v {
+            Var::Time => self.t,
+            Var::Elapsed => self.elapsed,
+            Var::Tempo => self.bpm,
+            Var::Intensity => self.intensity,
+            Var::Noise(i) => self.noise[i as usize],
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Func {
+    Sin,
+    Cos,
+    Tan,
+    Abs,
+    Sqrt,
+    Exp,
+    Ln,
+    Floor,
+    Min,
+    Max,
+    Clamp,
+}
+
+impl Func {
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "sin" => Func::Sin,
+            "cos" => Func::Cos,
+            "tan" => Func::Tan,
+            "abs" => Func::Abs,
+            "sqrt" => Func::Sqrt,
+            "exp" => Func::Exp,
+            "ln" => Func::Ln,
+            "floor" => Func::Floor,
+            "min" => Func::Min,
+            "max" => Func::Max,
+            "clamp" => Func::Clamp,
+            _ => return None,
+        })
+    }
+
+    fn arity(self) -> usize {
+        match self {
+            Func::Min | Func::Max => 2,
+            Func::Clamp => 3,
+            _ => 1,
+        }
+    }
+
+    #[inline]
+    fn apply(self, a: &[f32]) -> f32 {
+        match self {
+            Func::Sin => a[0].sin(),
+            Func::Cos => a[0].cos(),
+            Func::Tan => a[0].tan(),
+            Func::Abs => a[0].abs(),
+            Func::Sqrt => a[0].sqrt(),
+            Func::Exp => a[0].exp(),
+            Func::Ln => a[0].ln(),
+            Func::Floor => a[0].floor(),
+            Func::Min => a[0].min(a[1]),
+            Func::Max => a[0].max(a[1]),
+            // not f32::clamp: that panics on lo > hi
+            Func::Clamp => a[0].max(a[1]).min(a[2]),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Op {
+    Num(f32),
+    Var(Var),
+    Neg,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    Call(Func),
+}
+
+/// Why an expression did not parse; `pos` is a byte offset into the text.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ExprError {
+    pub pos: usize,
+    pub msg: &'static str,
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{} at offset {}", self.msg, self.pos) }
+}
+
+impl std::error::Error for ExprError {}
+
+/// A compiled expression. The default evaluates to 0.
+#[derive(Copy, Clone)]
+pub struct Expr {
+    ops: [Op; MAX_OPS],
+    len: usize,
+}
+
+impl Default for Expr {
+    fn default() -> Self { Self::constant(0.0) }
+}
+
+impl fmt::Debug for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.debug_list().entries(&self.ops[..self.len]).finish() }
+}
+
+impl Expr {
+    /// Expression that always yields `x`.
+    pub fn constant(x: f32) -> Self {
+        let mut ops = [Op::Num(0.0); MAX_OPS];
+        ops[0] = Op::Num(x);
+        Self { ops, len: 1 }
+    }
+
+    /// Compile `text`.
+    pub fn parse(text: &str) -> Result<Self, ExprError> {
+        let mut p = Parser { src: text.as_bytes(), pos: 0, expr: Self { ops: [Op::Num(0.0); MAX_OPS], len: 0 }, depth: 0, nesting: 0 };
+        p.sum()?;
+        p.skip_ws();
+        if p.pos < p.src.len() { return Err(p.error("unexpected character")); }
+        Ok(p.expr)
+    }
+
+    /// `true` if the expression reads no variables.
+    pub fn is_constant(&self) -> bool { !self.ops[..self.len].iter().any(|op| matches!(op, Op::Var(_))) }
+
+    /// Evaluate against `x`; a non-finite result reads as 0.
+    pub fn eval(&self, x: &ExprInputs) -> f32 {
+        let mut st = [0.0f32; MAX_STACK];
+        let mut sp = 0;
+        for op in &self.ops[..self.len] {
+            match *op {
+                Op::Num(v) => { st[sp] = v; sp += 1; }
+                Op::Var(v) => { st[sp] = x.get(v); sp += 1; }
+                Op::Neg => st[sp - 1] = -st[sp - 1],
+                Op::Call(f) => {
+                    sp -= f.arity() - 1;
+                    st[sp - 1] = f.apply(&st[sp - 1..]);
+                }
+                bin => {
+                    sp -= 1;
+                    let (a, b) = (st[sp - 1], st[sp]);
+                    st[sp - 1] = match bin {
+                        Op::Add => a + b,
+                        Op::Sub => a - b,
+                        Op::Mul => a * b,
+                        Op::Div => a / b,
+                        _ => a.powf(b),
+                    };
+                }
+            }
+        }
+        let y = st[0];
+        if y.is_finite() { y } else { 0.0 }
+    }
+}
+
+/// Recursive-descent parser emitting postfix ops; tracks the stack depth the
+/// ops will need so evaluation can't overflow.
+struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+    expr: Expr,
+    depth: usize,
+    nesting: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, msg: &'static str) -> ExprError { ExprError { pos: self.pos, msg } }
+
+    fn skip_ws(&mut self) {
+        while self.src.get(self.pos).is_some_and(u8::is_ascii_whitespace) { self.pos += 1; }
+    }
+
+    /// Consume `c` (after whitespace) if it is next.
+    fn eat(&mut self, c: u8) -> bool {
+        self.skip_ws();
+        let hit = self.src.get(self.pos) == Some(&c);
+        if hit { self.pos += 1; }
+        hit
+    }
+
+    /// Append `op`, which pops `pops` values and pushes one.
+    fn emit(&mut self, op: Op, pops: usize) -> Result<(), ExprError> {
+        if self.expr.len == MAX_OPS { return Err(self.error("expression too long")); }
+        self.expr.ops[self.expr.len] = op;
+        self.expr.len += 1;
+        self.depth = self.depth + 1 - pops;
+        if self.depth > MAX_STACK { return Err(self.error("expression too deeply nested")); }
+        Ok(())
+    }
+
+    fn nest(&mut self) -> Result<(), ExprError> {
+        self.nesting += 1;
+        if self.nesting > MAX_NESTING { return Err(self.error("expression too deeply nested")); }
+        Ok(())
+    }
+
+    // sum := product (('+' | '-') product)*
+    fn sum(&mut self) -> Result<(), ExprError> {
+        self.product()?;
+        loop {
+            if self.eat(b'+') { self.product()?; self.emit(Op::Add, 2)?; }
+            else if self.eat(b'-') { self.product()?; self.emit(Op::Sub, 2)?; }
+            else { return Ok(()); }
+        }
+    }
+
+    // product := unary (('*' | '/') unary)*
+    fn product(&mut self) -> Result<(), ExprError> {
+        self.unary()?;
+        loop {
+            if self.eat(b'*') { self.unary()?; self.emit(Op::Mul, 2)?; }
+            else if self.eat(b'/') { self.unary()?; self.emit(Op::Div, 2)?; }
+            else { return Ok(()); }
+        }
+    }
+
+    // unary := '-' unary | power
+    fn unary(&mut self) -> Result<(), ExprError> {
+        if self.eat(b'-') {
+            self.nest()?;
+            self.unary()?;
+            self.nesting -= 1;
+            return self.emit(Op::Neg, 1);
+        }
+        self.power()
+    }
+
+    // power := atom ('^' unary)?
+    fn power(&mut self) -> Result<(), ExprError> {
+        self.atom()?;
+        if self.eat(b'^') {
+            self.nest()?;
+            self.unary()?;
+            self.nesting -= 1;
+            self.emit(Op::Pow, 2)?;
+        }
+        Ok(())
+    }
+
+    // atom := number | name | name '(' args ')' | '(' sum ')'
+    fn atom(&mut self) -> Result<(), ExprError> {
+        self.skip_ws();
+        let start = self.pos;
+        match self.src.get(self.pos) {
+            Some(b'(') => {
+                self.pos += 1;
+                self.nest()?;
+                self.sum()?;
+                self.nesting -= 1;
+                if !self.eat(b')') { return Err(self.error("expected ')'")); }
+                Ok(())
+            }
+            Some(c) if c.is_ascii_digit() || *c == b'.' => {
+                while self.src.get(self.pos).is_some_and(|c| c.is_ascii_digit() || *c == b'.') { self.pos += 1; }
+                // exponent: 1e3, 2.5e-2
+                if matches!(self.src.get(self.pos), Some(b'e' | b'E')) {
+                    let mut end = self.pos + 1;
+                    if matches!(self.src.get(end), Some(b'+' | b'-')) { end += 1; }
+                    if self.src.get(end).is_some_and(u8::is_ascii_digit) {
+                        self.pos = end;
+                        while self.src.get(self.pos).is_some_and(u8::is_ascii_digit) { self.pos += 1; }
+                    }
+                }
+                let text = core::str::from_utf8(&self.src[start..self.pos]).unwrap_or_default();
+                let v: f32 = text.parse().map_err(|_| ExprError { pos: start, msg: "invalid number" })?;
+                self.emit(Op::Num(v), 0)
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                while self.src.get(self.pos).is_some_and(|c| c.is_ascii_alphanumeric() || *c == b'_') { self.pos += 1; }
+                let name = core::str::from_utf8(&self.src[start..self.pos]).unwrap_or_default();
+                if self.eat(b'(') {
+                    let f = Func::parse(name).ok_or(ExprError { pos: start, msg: "unknown function" })?;
+                    self.nest()?;
+                    for i in 0..f.arity() {
+                        if i > 0 && !self.eat(b',') { return Err(self.error("expected ','")); }
+                        self.sum()?;
+                    }
+                    self.nesting -= 1;
+                    if !self.eat(b')') { return Err(self.error("expected ')'")); }
+                    return self.emit(Op::Call(f), f.arity());
+                }
+                if name == "pi" { return self.emit(Op::Num(core::f32::consts::PI), 0); }
+                let v = Var::parse(name).ok_or(ExprError { pos: start, msg: "unknown variable" })?;
+                self.emit(Op::Var(v), 0)
+            }
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(text: &str) -> f32 { Expr::parse(text).unwrap().eval(&ExprInputs::default()) }
+
+    fn err(text: &str) -> ExprError { Expr::parse(text).unwrap_err() }
+
+    #[test]
+    fn precedence_and_associativity() {
+        assert_eq!(eval("1 + 2 * 3"), 7.0);
+        assert_eq!(eval("(1 + 2) * 3"), 9.0);
+        assert_eq!(eval("8 / 4 / 2"), 1.0);
+        assert_eq!(eval("10 - 4 - 3"), 3.0);
+        assert_eq!(eval("2 * 3 ^ 2"), 18.0);
+        // `^` is right-associative: 2^(3^2)
+        assert_eq!(eval("2 ^ 3 ^ 2"), 512.0);
+        assert_eq!(eval("1.5e1 + 2.5e-1"), 15.25);
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_eq!(eval("-2 ^ 2"), -4.0);
+        assert_eq!(eval("(-2) ^ 2"), 4.0);
+        assert_eq!(eval("2 ^ -1"), 0.5);
+        assert_eq!(eval("--3"), 3.0);
+        assert_eq!(eval("4 * -2"), -8.0);
+    }
+
+    #[test]
+    fn functions_and_variables() {
+        let x = ExprInputs { t: 2.0, elapsed: 5.0, bpm: 90.0, intensity: 0.5, noise: [0.1, 0.2, 0.3, 0.4] };
+        let e = Expr::parse("t + elapsed + bpm + intensity + noise4").unwrap();
+        assert!(!e.is_constant());
+        assert!((e.eval(&x) - 97.9).abs() < 1e-4);
+        assert!(Expr::parse("sin(pi / 2) + 1").unwrap().is_constant());
+        assert!((eval("sin(pi / 2)") - 1.0).abs() < 1e-6);
+        assert_eq!(eval("min(3, max(1, 2))"), 2.0);
+        assert_eq!(eval("clamp(5, 0, 1)"), 1.0);
+        // lo > hi doesn't panic
+        assert_eq!(eval("clamp(5, 2, 1)"), 1.0);
+        // non-finite results read as 0
+        assert_eq!(eval("1 / 0"), 0.0);
+        assert_eq!(eval("ln(-1)"), 0.0);
+    }
+
+    #[test]
+    fn errors_report_position() {
+        assert_eq!(err("1 +"), ExprError { pos: 3, msg: "unexpected end of expression" });
+        assert_eq!(err("1 + )"), ExprError { pos: 4, msg: "unexpected character" });
+        assert_eq!(err("1 2"), ExprError { pos: 2, msg: "unexpected character" });
+        assert_eq!(err("(1 + 2"), ExprError { pos: 6, msg: "expected ')'" });
+        assert_eq!(err("2 * foo(1)"), ExprError { pos: 4, msg: "unknown function" });
+        assert_eq!(err("2 * bar"), ExprError { pos: 4, msg: "unknown variable" });
+        assert_eq!(err("noise5"), ExprError { pos: 0, msg: "unknown variable" });
+        assert_eq!(err("min(1 2)"), ExprError { pos: 6, msg: "expected ','" });
+        assert_eq!(err("1..2"), ExprError { pos: 0, msg: "invalid number" });
+        assert_eq!(err("1 +").to_string(), "unexpected end of expression at offset 3");
+    }
+
+    #[test]
+    fn nesting_and_length_limits() {
+        let deep = format!("{}1{}", "(".repeat(MAX_NESTING + 1), ")".repeat(MAX_NESTING + 1));
+        assert_eq!(err(&deep).msg, "expression too deeply nested");
+        let ok = format!("{}1{}", "(".repeat(MAX_NESTING), ")".repeat(MAX_NESTING));
+        assert_eq!(eval(&ok), 1.0);
+        assert_eq!(err(&"-".repeat(MAX_NESTING + 1)).msg, "expression too deeply nested");
+
+        // right-nested sums need one stack slot per level
+        let wide = format!("{}1{}", "1 + (".repeat(MAX_STACK), ")".repeat(MAX_STACK));
+        assert_eq!(err(&wide).msg, "expression too deeply nested");
+        let fits = format!("{}1{}", "1 + (".repeat(MAX_STACK - 1), ")".repeat(MAX_STACK - 1));
+        assert_eq!(eval(&fits), MAX_STACK as f32);
+
+        let long = vec!["1"; MAX_OPS / 2 + 1].join(" + ");
+        assert_eq!(err(&long).msg, "expression too long");
+    }
+}
@@ -0,0 +1,208 @@
+//! Radix-2 FFT and a magnitude-spectrum analyzer.
+//!
+//! Small and dependency-free: twiddles and the bit-reversal table are worked
+//! out once in [`Fft::new`], so transforms themselves never allocate and can
+//! run on the audio thread. [`SpectrumAnalyzer`] keeps the most recent output
+//! samples and turns them into a smoothed magnitude spectrum for visualizers.
+
+use core::f32::consts::TAU;
+
+/// Default analysis window of a [`SpectrumAnalyzer`] (samples).
+pub const SPECTRUM_FFT_SIZE: usize = 2048;
+/// Default [`SpectrumAnalyzer::set_smoothing`] factor.
+pub const SPECTRUM_SMOOTHING: f32 = 0.8;
+
+/// Power-of-two complex FFT of a fixed size.
+#[derive(Clone, Debug)]
+pub struct Fft {
+    n: usize,
+    cos: Vec<f32>, // cos(2πk/n), k < n/2
+    sin: Vec<f32>,
+    rev: Vec<u32>, // bit-reversed index of every slot
+}
+
+impl Fft {
+    /// `n` is rounded up to a power of two (at least 2).
+    pub fn new(n: usize) -> Self {
+        let n = n.max(2).next_power_of_two();
+        let bits = n.trailing_zeros();
+        let (cos, sin) = (0..n / 2).map(|k| {
+            let w = TAU * k as f32 / n as f32;
+            (w.cos(), w.sin())
+        }).unzip();
+        let rev = (0..n as u32).map(|i| i.reverse_bits() >> (32 - bits)).collect();
+        Self { n, cos, sin, rev }
+    }
+
+    /// Transform size.
+    #[inline] pub fn len(&self) -> usize { self.n }
+    #[inline] pub fn is_empty(&self) -> bool { false }
+
+    /// In-place forward transform (e^-jωt kernel, unscaled).
+    pub fn forward(&self, re: &mut [f32], im: &mut [f32]) { self.run(re, im, -1.0); }
+
+    /// In-place inverse transform, scaled by 1/n so `inverse(forward(x)) == x`.
+    pub fn inverse(&self, re: &mut [f32], im: &mut [f32]) {
+        self.run(re, im, 1.0);
+        let k = 1.0 / self.n as f32;
+        for (r, i) in re.iter_mut().zip(im.iter_mut()) {
+            *r *= k;
+            *i *= k;
+        }
+    }
+
+    fn run(&self, re: &mut [f32], im: &mut [f32], sign: f32) {
+        let n = self.n;
+        let (re, im) = (&mut re[..n], &mut im[..n]);
+        for i in 0..n {
+            let j = self.rev[i] as usize;
+            if j > i {
+                re.swap(i, j);
+                im.swap(i, j);
+            }
+        }
+        let mut half = 1;
+        while half < n {
+            let step = n / (2 * half);
+            for start in (0..n).step_by(2 * half) {
+                for k in 0..half {
+                    let (c, s) = (self.cos[k * step], sign * self.sin[k * step]);
+                    let (a, b) = (start + k, start + k + half);
+                    let tr = re[b] * c - im[b] * s;
+                    let ti = re[b] * s + im[b] * c;
+                    re[b] = re[a] - tr;
+                    im[b] = im[a] - ti;
+                    re[a] += tr;
+                    im[a] += ti;
+                }
+            }
+            half *= 2;
+        }
+    }
+}
+
+/// Smoothed magnitude spectrum of the most recent samples.
+///
+/// [`SpectrumAnalyzer::push`] is cheap enough to call for every output sample;
+/// the FFT only runs in [`SpectrumAnalyzer::update`], typically once per
+/// visualizer frame. Magnitudes are linear, Hann-windowed and scaled so a
+/// full-scale sine peaks near 1.0; between updates they rise at once and fall
+/// by the smoothing factor, like a meter's peak decay.
+#[derive(Clone, Debug)]
+pub struct SpectrumAnalyzer {
+    fft: Fft,
+    window: Vec<f32>,
+    ring: Vec<f32>, // last `n` samples, oldest at `pos`
+    pos: usize,
+    re: Vec<f32>,
+    im: Vec<f32>,
+    mags: Vec<f32>, // n/2 + 1 bins, DC .. Nyquist
+    smoothing: f32,
+}
+
+impl SpectrumAnalyzer {
+    /// Analyzer over the last `size` samples (a power of two, 64..16384).
+    pub fn new(size: usize) -> Self {
+        let fft = Fft::new(size.clamp(64, 16_384));
+        let n = fft.len();
+        let window = (0..n).map(|i| 0.5 - 0.5 * (TAU * i as f32 / n as f32).cos()).collect();
+        Self {
+            fft,
+            window,
+            ring: vec![0.0; n],
+            pos: 0,
+            re: vec![0.0; n],
+            im: vec![0.0; n],
+            mags: vec![0.0; n / 2 + 1],
+            smoothing: SPECTRUM_SMOOTHING,
+        }
+    }
+
+    /// Window size in samples.
+    #[inline] pub fn size(&self) -> usize { self.fft.len() }
+
+    /// Width of one bin of [`SpectrumAnalyzer::magnitudes`] at sample rate `sr`.
+    #[inline] pub fn bin_hz(&self, sr: f32) -> f32 { sr / self.fft.len() as f32 }
+
+    /// How much of the previous magnitude survives each update when the new
+    /// one is lower (0 = none, up to 0.99).
+    #[inline] pub fn set_smoothing(&mut self, k: f32) { self.smoothing = k.clamp(0.0, 0.99); }
+
+    #[inline]
+    pub fn push(&mut self, x: f32) {
+        self.ring[self.pos] = x;
+        self.pos = (self.pos + 1) % self.ring.len();
+    }
+
+    /// Forget the history and the smoothed spectrum.
+    pub fn clear(&mut self) {
+        self.ring.fill(0.0);
+        self.mags.fill(0.0);
+    }
+
+    /// Analyze the current window and return the smoothed magnitudes
+    /// (`size / 2 + 1` bins from DC to Nyquist).
+    pub fn update(&mut self) -> &[f32] {
+        let n = self.fft.len();
+        let (newer, older) = self.ring.split_at(self.pos);
+        for (i, x) in older.iter().chain(newer).enumerate() { self.re[i] = x * self.window[i]; }
+        self.im.fill(0.0);
+        self.fft.forward(&mut self.re, &mut self.im);
+        // a Hann-windowed sine of amplitude A peaks at A·n/4
+        let scale = 4.0 / n as f32;
+        for (k, m) in self.mags.iter_mut().enumerate() {
+            let v = scale * (self.re[k] * self.re[k] + self.im[k] * self.im[k]).sqrt();
+            *m = v.max(*m * self.smoothing);
+        }
+        &self.mags
+    }
+
+    /// Smoothed magnitudes from the last update.
+    #[inline] pub fn magnitudes(&self) -> &[f32] { &self.mags }
+
+    /// Update, then fold the spectrum into `out.len()` equal-width bands from
+    /// DC to Nyquist, each the peak of the bins it covers (with more bands
+    /// than bins, neighbouring bands repeat a bin).
+    pub fn fill_bands(&mut self, out: &mut [f32]) {
+        self.update();
+        let bins = self.mags.len();
+        let bands = out.len();
+        for (b, y) in out.iter_mut().enumerate() {
+            let lo = b * bins / bands;
+            let hi = ((b + 1) * bins / bands).max(lo + 1).min(bins);
+            *y = self.mags[lo..hi].iter().fold(0.0, |a: f32, &m| a.max(m));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverse_undoes_forward() {
+        let fft = Fft::new(256);
+        let x: Vec<f32> = (0..256).map(|i| ((i * 37 % 101) as f32 / 50.0) - 1.0).collect();
+        let (mut re, mut im) = (x.clone(), vec![0.0; 256]);
+        fft.forward(&mut re, &mut im);
+        fft.inverse(&mut re, &mut im);
+        for (a, b) in re.iter().zip(&x) {
+            assert!((a - b).abs() < 1e-5, "{a} vs {b}");
+        }
+        assert!(im.iter().all(|v| v.abs() < 1e-5));
+    }
+
+    #[test]
+    fn sine_lands_in_its_bin() {
+        let n = 128;
+        let fft = Fft::new(n);
+        let mut re: Vec<f32> = (0..n).map(|i| (std::f32::consts::TAU * 5.0 * i as f32 / n as f32).cos()).collect();
+        let mut im = vec![0.0; n];
+        fft.forward(&mut re, &mut im);
+        for k in 0..n {
+            let mag = re[k].hypot(im[k]);
+            let want = if k == 5 || k == n - 5 { n as f32 / 2.0 } else { 0.0 };
+            assert!((mag - want).abs() < 1e-3, "bin {k}: {mag}");
+        }
+    }
+}
@@ -2,32 +2,290 @@
 //!
 //! This module defines the minimal `Generator` trait and a lightweight `Engine<G>`
 //! wrapper that owns a generator (scene/voice), tracks sample rate and time, and
-//! produces one **mono** sample at a time with zero heap work per sample.
+//! produces one **mono** sample (or one stereo frame) at a time with zero heap work
+//! per sample.
 //!
 //! Design goals
 //! - No dynamic allocations in the audio thread
-//! - SR changes handled lazily (if the host reconfigures), with cheap branching
+//! - SR changes handled lazily (if the host reconfigures), with cheap branching,
+//!   and without restarting the scene's musical state where the scene allows it
 //! - Generic over the scene type, so scenes can be swapped without trait objects
+//! - Shared effects live in the engine's master section: generators hand back a
+//!   [`BusFrame`] (dry + reverb/delay sends) instead of running their own reverbs
+//! - Host context (rate, block size, clock, tempo, transport) reaches generators
+//!   as one [`RenderCtx`], so new host facts don't each need a new trait method
 
 /// Anything that can generate one sample at a time.
 pub trait Generator {
-    /// Called when the engine is (re)initialized or when the sample rate changes.
+    /// Called when the engine is (re)initialized. Restart from scratch at `sr`,
+    /// filter states included: the engine also resets a generator whose output
+    /// went NaN/Inf, and any state that kept the NaN would bring it back.
     fn reset(&mut self, sr: f32);
 
+    /// Called when the host renegotiates the sample rate mid-stream.
+    ///
+    /// Implementations should recompute rate-dependent coefficients while keeping
+    /// phases, envelope values, and reverb tails. The default falls back to a full
+    /// `reset`. Returns `true` if a full reset occurred.
+    #[inline]
+    fn set_sample_rate(&mut self, sr: f32) -> bool {
+        self.reset(sr);
+        true
+    }
+
     /// Generate the next mono sample. Implementations should assume the sample
     /// rate has been communicated via `reset`.
     fn next(&mut self) -> f32;
+
+    /// Generate the next stereo frame `(left, right)`.
+    ///
+    /// Mono generators can rely on the default, which duplicates `next()` to
+    /// both sides. Stereo scenes override this; `next()` stays their mono path.
+    #[inline]
+    fn next_stereo(&mut self) -> (f32, f32) {
+        let s = self.next();
+        (s, s)
+    }
+
+    /// Generate the next frame split into the dry mix and sends to the engine's
+    /// shared reverb and delay buses. The default sends nothing: the whole
+    /// `next_stereo()` frame is dry.
+    #[inline]
+    fn next_bus(&mut self) -> BusFrame {
+        let (l, r) = self.next_stereo();
+        BusFrame::dry(l, r)
+    }
+
+    /// Generate the next frame, given the engine's [`RenderCtx`].
+    ///
+    /// This is what the engine calls. The default is the compatibility path
+    /// for generators written against the bare-`sr` API: it hands the clock
+    /// to [`Generator::set_time`] and returns [`Generator::next_bus`].
+    /// Generators that follow tempo or transport override it.
+    #[inline]
+    fn render(&mut self, ctx: &RenderCtx) -> BusFrame {
+        self.set_time(ctx.time);
+        self.next_bus()
+    }
+
+    /// Room and echo settings this generator wants on the shared buses; the
+    /// engine applies them when the generator is installed.
+    #[inline]
+    fn bus_settings(&self) -> BusSettings {
+        BusSettings::default()
+    }
+
+    /// Extra gain (0..1) applied just before the generator's final ±1 clamp;
+    /// the engine's auto-trim lowers it while the clamp keeps engaging. The
+    /// default ignores it.
+    #[inline]
+    fn set_output_trim(&mut self, _trim: f32) {}
+
+    /// `true` while nothing is sounding and the next frames will be silent
+    /// until the generator's next event (e.g. between the drips of a sparse
+    /// scene). The default never idles; of the built-in scenes only
+    /// [`CaveDrips`](crate::scenes::cave_drips::CaveDrips) has silent stretches.
+    #[inline]
+    fn is_idle(&self) -> bool {
+        false
+    }
+
+    /// While idle, advance up to `max_frames` frames without rendering audio,
+    /// keeping timers and random streams exactly where rendering would have
+    /// left them. Stops at the frame where an event starts (that frame is
+    /// then rendered by the next `next_bus` call) and returns the frames
+    /// skipped. The default skips nothing.
+    #[inline]
+    fn skip_idle(&mut self, _max_frames: usize) -> usize {
+        0
+    }
+
+    /// The engine clock: seconds rendered since the engine was created (see
+    /// [`Engine::time`]), passed before every frame by the default
+    /// [`Generator::render`] and when the scene is swapped in. Unlike a scene's own
+    /// sample count it keeps running across scene swaps and resets, so scenes
+    /// can schedule long-form changes (bring in a layer after ten minutes,
+    /// thin out after forty). The default ignores it.
+    #[inline]
+    fn set_time(&mut self, _t: f64) {}
+
+    /// Oldest event the generator has reported since the last call (see
+    /// [`crate::event`]). The default reports none.
+    #[inline]
+    fn pop_event(&mut self) -> Option<SceneEvent> {
+        None
+    }
+
+    /// Index of this generator's analysis tap `name` (a modulator or other
+    /// internal signal, see [`crate::taps`]) for [`Generator::tap_value`].
+    /// The default has no taps.
+    #[inline]
+    fn tap_index(&self, _name: &str) -> Option<usize> {
+        None
+    }
+
+    /// Current value of the tap [`Generator::tap_index`] gave `index` for.
+    #[inline]
+    fn tap_value(&self, _index: usize) -> f32 {
+        0.0
+    }
+
+    /// Current value of host parameter `id` (see [`ParamId`]), or `None` if
+    /// this generator has no such parameter. The default has none.
+    #[inline]
+    fn param_value(&self, _id: ParamId) -> Option<f32> {
+        None
+    }
+
+    /// Set host parameter `id`; `false` if this generator has no such
+    /// parameter. The default has none.
+    #[inline]
+    fn set_param_value(&mut self, _id: ParamId, _value: f32) -> bool {
+        false
+    }
+
+    /// Return every host parameter to the generator's defaults, keeping phases
+    /// and other running state. The default does nothing.
+    #[inline]
+    fn reset_params(&mut self) {}
+
+    /// Calibrated loudness of this generator at its default settings (RMS
+    /// dBFS after the master bus, see [`measure_loudness_db`]), used by the
+    /// engine's auto-gain. `None` (the default) leaves the level alone.
+    #[inline]
+    fn loudness_db(&self) -> Option<f32> {
+        None
+    }
+}
+
+/// Default [`RenderCtx::tempo`] (BPM).
+pub const DEFAULT_TEMPO_BPM: f32 = 120.0;
+/// Tempo range accepted by [`Engine::set_tempo`] (BPM).
+pub const TEMPO_RANGE_BPM: (f32, f32) = (20.0, 400.0);
+
+/// Host transport state (see [`Engine::set_transport`]).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Transport {
+    /// Running; hosts without a transport never leave this state.
+    #[default]
+    Playing,
+    /// The host's transport is stopped (audio may still be rendered).
+    Stopped,
+}
+
+/// What a generator knows about the host while rendering a frame.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RenderCtx {
+    /// Sample rate, Hz.
+    pub sr: f32,
+    /// Frames in the host buffer being rendered; 0 if unknown.
+    pub block_len: u32,
+    /// Engine clock: seconds rendered since the engine was created.
+    pub time: f64,
+    /// Host tempo, BPM.
+    pub tempo: f32,
+    pub transport: Transport,
+}
+
+impl Default for RenderCtx {
+    fn default() -> Self {
+        Self { sr: 48_000.0, block_len: 0, time: 0.0, tempo: DEFAULT_TEMPO_BPM, transport: Transport::Playing }
+    }
+}
+
+/// One stereo frame as dry signal plus effect sends.
+///
+/// Sends are pre-scaled by the generator (a send of `x` with the default unity
+/// return adds `x` worth of signal to that effect's input).
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct BusFrame {
+    pub dry: (f32, f32),
+    pub reverb: (f32, f32),
+    pub delay: (f32, f32),
+}
+
+impl BusFrame {
+    /// Dry-only frame.
+    #[inline]
+    pub fn dry(l: f32, r: f32) -> Self { Self { dry: (l, r), ..Self::default() } }
+
+    /// `true` if no part is NaN or infinite.
+    #[inline]
+    pub fn is_finite(&self) -> bool {
+        [self.dry, self.reverb, self.delay].iter().all(|(l, r)| l.is_finite() && r.is_finite())
+    }
+
+    /// Every part multiplied by `g`.
+    #[inline]
+    pub fn scaled(self, g: f32) -> Self {
+        let s = |(l, r): (f32, f32)| (l * g, r * g);
+        Self { dry: s(self.dry), reverb: s(self.reverb), delay: s(self.delay) }
+    }
 }
 
+use crate::event::SceneEvent;
+use crate::log;
+use crate::master::{BusSettings, MasterBus};
+use crate::nodes::{ClipGuard, OnePoleSmoother, SilenceDetector};
+use ambientor_core::dsp::db_to_lin;
+use crate::params::{AbSlot, ParamId, ParamRamps, ParamSet};
+use crate::profile::{scope, CpuReport, Stage};
+use crate::taps::{TapFrame, Taps};
+use crate::waveform::WaveformHistory;
+
+/// Default soft-start length (seconds) applied on creation and after scene swaps.
+pub const DEFAULT_FADE_IN_S: f32 = 0.5;
+
+/// Default output level (dBFS peak) below which the engine counts as silent.
+pub const DEFAULT_SILENCE_DB: f32 = -90.0;
+/// Default time the output must stay below [`DEFAULT_SILENCE_DB`] before it is silent.
+pub const DEFAULT_SILENCE_HOLD_S: f32 = 5.0;
+
+/// Loudness (RMS dBFS) auto-gain brings every scene to by default.
+pub const AUTO_GAIN_TARGET_DB: f32 = -24.0;
+/// Largest boost or cut auto-gain applies, dB.
+pub const AUTO_GAIN_MAX_DB: f32 = 12.0;
+const AUTO_GAIN_GLIDE_MS: f32 = 300.0;
+
+/// Shortest time between scene resets after non-finite output (seconds); a
+/// scene that keeps producing NaN is muted in between instead of being
+/// reset on every frame.
+pub const NAN_RESET_HOLDOFF_S: f64 = 0.5;
+
 /// Lightweight realtime engine that owns a generator.
 ///
 /// The audio callback should call `next(sr)` for every output sample. If the
-/// `sr` reported by the host changes, the engine will call `reset(sr)` on the
-/// inner generator once and continue.
+/// `sr` reported by the host changes, the engine will call `set_sample_rate(sr)`
+/// on the inner generator once and continue.
+///
+/// Output starts with a short linear fade-in (see [`Engine::fade_in`]) so hosts
+/// that begin playback abruptly don't click into the drone, then passes through
+/// the stereo [`MasterBus`].
 pub struct Engine<G: Generator> {
     sr: f32,
-    t: f32,
+    t: f64, // f32 would stop advancing after a few minutes at audio rates
+    ctx: RenderCtx,
     gen: G,
+    master: MasterBus,
+    // soft start
+    fade_s: f32,
+    fade: f32,
+    fade_inc: f32,
+    silence: SilenceDetector,
+    clip: ClipGuard,
+    nan_resets: u64,
+    last_nan_reset: f64, // engine time of the last scene reset
+    skip_idle: bool,
+    ramps: ParamRamps,
+    ab: [Option<ParamSet>; 2],
+    ab_slot: AbSlot,
+    // loudness compensation across scenes
+    auto_gain: bool,
+    auto_gain_target_db: f32,
+    level: f32,
+    level_sm: OnePoleSmoother,
+    taps: Taps,
+    waveform: Option<WaveformHistory>, // see Engine::enable_waveform
 }
 
 impl<G: Generator> Engine<G> {
@@ -38,39 +296,650 @@ impl<G: Generator> Engine<G> {
         // `sr` will be set by the first `next(sr)` call, but we can initialize to sane defaults.
         let sr = 48_000.0;
         gen.reset(sr);
-        Self { sr, t: 0.0, gen }
+        let mut master = MasterBus::new(sr);
+        master.set_bus_settings(&gen.bus_settings());
+        let silence = SilenceDetector::new(DEFAULT_SILENCE_DB, DEFAULT_SILENCE_HOLD_S, sr);
+        let clip = ClipGuard::new(sr);
+        let mut level_sm = OnePoleSmoother::new_ms(AUTO_GAIN_GLIDE_MS, sr);
+        level_sm.reset(1.0);
+        let mut e = Self {
+            sr, t: 0.0, ctx: RenderCtx { sr, ..RenderCtx::default() }, gen, master, fade_s: DEFAULT_FADE_IN_S, fade: 0.0, fade_inc: 0.0, silence, clip, nan_resets: 0,
+            last_nan_reset: f64::NEG_INFINITY, skip_idle: false, ramps: ParamRamps::default(), ab: [None; 2], ab_slot: AbSlot::A,
+            auto_gain: false, auto_gain_target_db: AUTO_GAIN_TARGET_DB, level: 1.0, level_sm,
+            taps: Taps::new(),
+            waveform: None,
+        };
+        e.fade_in(DEFAULT_FADE_IN_S);
+        e
     }
 
-    /// Produce **one** mono sample at the given sample rate.
+    /// Produce **one** mono sample at the given sample rate: the mid of
+    /// [`Engine::next_stereo`], so the master bus applies in mono too.
     ///
-    /// - If `sr` differs from the current engine `sr`, we update and call `reset(sr)`.
-    /// - We track `t` (seconds) incrementally, in case scenes want to expose it later.
+    /// - If `sr` differs from the current engine `sr`, we update and call `set_sample_rate(sr)`.
+    /// - We track `t` (seconds) incrementally and hand it to the scene in its [`RenderCtx`].
     #[inline]
     pub fn next(&mut self, sr: f32) -> f32 {
+        let (l, r) = self.next_stereo(sr);
+        0.5 * (l + r)
+    }
+
+    /// Produce **one** stereo frame `(left, right)` at the given sample rate.
+    ///
+    /// Same SR/time handling as [`Engine::next`]; mono scenes come back duplicated
+    /// (before the master bus). The generator's sends pass through the shared
+    /// reverb/delay buses before the rest of the master chain.
+    ///
+    /// NaN/Inf never leave the engine: a non-finite scene frame resets the scene
+    /// to its default parameters (with a fresh fade-in, at most once per
+    /// [`NAN_RESET_HOLDOFF_S`]), a non-finite master output flushes the master
+    /// bus, and either way the frame is replaced by silence. Resets and
+    /// flushes are counted in [`Engine::nan_resets`].
+    ///
+    /// Frames where the scene's final clamp engaged are counted (see
+    /// [`Engine::clip_guard`]); with auto-trim on, sustained clipping lowers
+    /// the scene's output trim until it stops.
+    #[inline]
+    pub fn next_stereo(&mut self, sr: f32) -> (f32, f32) {
+        let g = self.advance(sr);
+        if self.ramps.is_active() { self.step_ramps(); }
+        let mut f = {
+            let _p = scope(Stage::Scene);
+            self.gen.render(&self.ctx)
+        };
+        if !f.is_finite() {
+            if self.t - self.last_nan_reset >= NAN_RESET_HOLDOFF_S { self.recover_scene(); }
+            f = BusFrame::default();
+        }
+        let was = self.clip.trim();
+        if self.clip.process(f.dry.0, f.dry.1) {
+            self.apply_trim(was);
+        }
+        let g = g * self.level_sm.process(self.level);
+        let (mut l, mut r) = self.master.process(f.scaled(g));
+        if !(l.is_finite() && r.is_finite()) {
+            self.master.clear();
+            self.nan_resets += 1;
+            log::warn(format_args!("non-finite master output, master bus flushed"));
+            (l, r) = (0.0, 0.0);
+        }
+        self.silence.process(l, r);
+        if self.taps.is_active() {
+            let frame = TapFrame {
+                scene: 0.5 * (f.dry.0 + f.dry.1),
+                pre_reverb: self.master.pre_reverb(),
+                post_filter: self.master.post_filter(),
+                output: 0.5 * (l + r),
+            };
+            self.record_taps(&frame);
+        }
+        if let Some(w) = &mut self.waveform { w.push(0.5 * (l + r)); }
+        (l, r)
+    }
+
+    /// On control-block boundaries, append `frame` and the scene's taps to
+    /// the subscribed taps' histories.
+    #[inline]
+    fn record_taps(&mut self, frame: &TapFrame) {
+        if self.taps.tick() {
+            let gen = &self.gen;
+            self.taps.record(frame, |i| gen.tap_value(i));
+        }
+    }
+
+    /// Offline renders: skip frames nobody can hear (see [`Engine::skip_idle`]).
+    /// Off by default.
+    #[inline] pub fn set_skip_idle(&mut self, on: bool) { self.skip_idle = on; }
+    #[inline] pub fn skips_idle(&self) -> bool { self.skip_idle }
+
+    /// With [`Engine::set_skip_idle`] on, and while the scene is idle
+    /// ([`Generator::is_idle`]) and the master is silent ([`MasterBus::is_idle`]),
+    /// advance up to `max_frames` silent frames without rendering them. The
+    /// caller writes that many frames of silence instead of calling
+    /// [`Engine::next_stereo`]. Returns 0 when there is anything to render.
+    ///
+    /// Time, the fade-in, and the clip and silence statistics advance as if
+    /// the frames had been rendered, so a render of a sparse scene comes out
+    /// the same, only faster.
+    pub fn skip_idle(&mut self, sr: f32, max_frames: usize) -> usize {
+        if !self.skip_idle || sr != self.sr || !self.master.is_idle() || !self.gen.is_idle() {
+            return 0;
+        }
+        let n = {
+            let _p = scope(Stage::Scene);
+            self.gen.skip_idle(max_frames)
+        };
+        for _ in 0..n {
+            self.advance(sr);
+            self.level_sm.process(self.level);
+            if self.ramps.is_active() { self.step_ramps(); }
+            let was = self.clip.trim();
+            if self.clip.process(0.0, 0.0) {
+                self.apply_trim(was);
+            }
+            self.silence.process(0.0, 0.0);
+            if self.taps.is_active() { self.record_taps(&TapFrame::default()); }
+            if let Some(w) = &mut self.waveform { w.push(0.0); }
+        }
+        n
+    }
+
+    /// Current value of host parameter `id`: master parameters from the master
+    /// bus, the rest from the scene (`None` if the scene doesn't have it).
+    pub fn param(&self, id: ParamId) -> Option<f32> {
+        let m = &self.master;
+        match id {
+            ParamId::Width => Some(m.width_percent()),
+            ParamId::Tone => Some(m.tone()),
+            ParamId::ReverbReturn => Some(m.reverb_return()),
+            ParamId::DelayReturn => Some(m.delay_return()),
+            ParamId::Distance => Some(m.listener_distance()),
+            ParamId::Occlusion => Some(m.occlusion()),
+            _ => self.gen.param_value(id),
+        }
+    }
+
+    /// Set host parameter `id` now, stopping any ramp on it. Returns `false`
+    /// if neither the master bus nor the scene has it, or `value` is NaN or
+    /// infinite (the parameter is left as it was).
+    pub fn set_param(&mut self, id: ParamId, value: f32) -> bool {
+        self.ramps.cancel(id);
+        self.apply_param(id, value)
+    }
+
+    /// Move host parameter `id` to `target` over `seconds`, on the engine's
+    /// control clock (see [`ParamRamps`]); `seconds <= 0` sets it at once.
+    /// Starts from the current value, so a new ramp picks up where a running
+    /// one is. Returns `false` if the parameter doesn't exist here, `target`
+    /// isn't finite, or too many ramps are running.
+    pub fn ramp_param(&mut self, id: ParamId, target: f32, seconds: f32) -> bool {
+        if !target.is_finite() { return false; }
+        let Some(from) = self.param(id) else { return false; };
+        let samples = (seconds.max(0.0) * self.sr) as u32;
+        if samples == 0 { return self.set_param(id, target); }
+        self.ramps.start(id, from, target, samples)
+    }
+
+    /// Value a running ramp on `id` is heading for.
+    #[inline] pub fn ramp_target(&self, id: ParamId) -> Option<f32> { self.ramps.target(id) }
+
+    /// Current value of every host parameter the master bus and scene have.
+    pub fn params(&self) -> ParamSet {
+        ParamId::ALL.into_iter().filter_map(|id| self.param(id).map(|v| (id, v))).collect()
+    }
+
+    /// Move every parameter in `set` to its value over `seconds` (see
+    /// [`Engine::ramp_param`]); ones that can't ramp (too many at once) are set
+    /// directly, and ones this engine lacks are skipped.
+    pub fn ramp_params(&mut self, set: &ParamSet, seconds: f32) {
+        for (id, v) in set.iter() {
+            if !self.ramp_param(id, v, seconds) { self.set_param(id, v); }
+        }
+    }
+
+    /// Morph from preset `from` to preset `to` over `seconds` (minutes-long
+    /// morphs are fine): parameters in `from` jump there first, then everything
+    /// in `to` ramps on the engine's control clock, levels and frequencies by
+    /// ratios. Parameters in neither keep their value; an empty `from` starts
+    /// from where the engine is. Setting a parameter directly takes it out of
+    /// the morph.
+    pub fn morph(&mut self, from: &ParamSet, to: &ParamSet, seconds: f32) {
+        for (id, v) in from.iter() { self.set_param(id, v); }
+        self.ramp_params(to, seconds);
+    }
+
+    /// Keep the current parameters ([`Engine::params`]) as snapshot `slot`,
+    /// which becomes the active one.
+    pub fn store_ab(&mut self, slot: AbSlot) {
+        self.ab[slot as usize] = Some(self.params());
+        self.ab_slot = slot;
+    }
+    #[inline] pub fn store_a(&mut self) { self.store_ab(AbSlot::A); }
+    #[inline] pub fn store_b(&mut self) { self.store_ab(AbSlot::B); }
+
+    /// Crossfade to snapshot `slot` over `seconds` (0 switches at once);
+    /// `false` if nothing was stored there.
+    pub fn recall_ab(&mut self, slot: AbSlot, seconds: f32) -> bool {
+        let Some(set) = self.ab[slot as usize] else { return false; };
+        self.ramp_params(&set, seconds);
+        self.ab_slot = slot;
+        true
+    }
+
+    /// Switch to the other A/B snapshot over `seconds`, to compare two
+    /// settings by ear; `false` if it is empty. Tweaks made since the last
+    /// store or switch are dropped unless stored first.
+    pub fn toggle_ab(&mut self, seconds: f32) -> bool { self.recall_ab(self.ab_slot.other(), seconds) }
+
+    /// Snapshot last stored or recalled.
+    #[inline] pub fn ab_slot(&self) -> AbSlot { self.ab_slot }
+    #[inline] pub fn has_ab(&self, slot: AbSlot) -> bool { self.ab[slot as usize].is_some() }
+
+    /// Return every host parameter to its default: the scene's own
+    /// ([`Generator::reset_params`]) and the master bus knobs (see
+    /// [`MasterBus::reset_params`]). Running ramps stop. Unlike a scene swap,
+    /// phases, tails and the fade state are kept, so the sound glides back
+    /// instead of restarting.
+    pub fn reset_params(&mut self) {
+        self.ramps.clear();
+        self.master.reset_params();
+        self.gen.reset_params();
+    }
+
+    fn apply_param(&mut self, id: ParamId, v: f32) -> bool {
+        if !v.is_finite() { return false; }
+        let m = &mut self.master;
+        match id {
+            ParamId::Width => m.set_width_percent(v),
+            ParamId::Tone => m.set_tone(v),
+            ParamId::ReverbReturn => m.set_reverb_return(v),
+            ParamId::DelayReturn => m.set_delay_return(v),
+            ParamId::Distance => m.set_listener_distance(v),
+            ParamId::Occlusion => m.set_occlusion(v),
+            _ => return self.gen.set_param_value(id, v),
+        }
+        true
+    }
+
+    fn step_ramps(&mut self) {
+        let mut ramps = self.ramps;
+        ramps.tick(|id, v| { self.apply_param(id, v); });
+        self.ramps = ramps;
+    }
+
+    /// Loudness compensation: scale each scene so its calibrated loudness
+    /// ([`Generator::loudness_db`]) lands on the auto-gain target, so switching
+    /// scenes doesn't jump in level. Changes glide over ~300 ms. Off by default.
+    pub fn set_auto_gain(&mut self, on: bool) {
+        self.auto_gain = on;
+        self.update_level();
+    }
+    #[inline] pub fn auto_gain(&self) -> bool { self.auto_gain }
+
+    /// Loudness auto-gain aims for, RMS dBFS (-48..0, default [`AUTO_GAIN_TARGET_DB`]).
+    pub fn set_auto_gain_target_db(&mut self, db: f32) {
+        self.auto_gain_target_db = log::clamp_param("auto-gain target", db, -48.0, 0.0);
+        self.update_level();
+    }
+    #[inline] pub fn auto_gain_target_db(&self) -> f32 { self.auto_gain_target_db }
+
+    /// Gain auto-gain applies to the current scene in dB (0 when off, or for
+    /// scenes without a calibration).
+    pub fn auto_gain_db(&self) -> f32 {
+        match (self.auto_gain, self.gen.loudness_db()) {
+            (true, Some(db)) => (self.auto_gain_target_db - db).clamp(-AUTO_GAIN_MAX_DB, AUTO_GAIN_MAX_DB),
+            _ => 0.0,
+        }
+    }
+
+    fn update_level(&mut self) { self.level = db_to_lin(self.auto_gain_db()); }
+
+    /// Scene produced NaN/Inf: restart it from clean state at its default
+    /// parameters (the settings that got it there may do it again) and fade
+    /// back in. Ramps on scene parameters stop.
+    #[cold]
+    fn recover_scene(&mut self) {
+        for id in ParamId::ALL.into_iter().filter(|id| !id.is_master()) { self.ramps.cancel(id); }
+        self.gen.reset_params();
+        self.gen.reset(self.sr);
+        self.last_nan_reset = self.t;
+        self.fade_in(self.fade_s);
+        self.nan_resets += 1;
+        log::warn(format_args!("non-finite scene output, scene reset"));
+        self.gen.set_output_trim(self.clip.trim());
+    }
+
+    /// Auto-trim moved: hand the new trim to the scene.
+    #[cold]
+    fn apply_trim(&mut self, was: f32) {
+        let trim = self.clip.trim();
+        self.gen.set_output_trim(trim);
+        if was >= 1.0 {
+            log::warn(format_args!("sustained clipping, auto-trim engaged"));
+        } else if trim >= 1.0 {
+            log::info(format_args!("auto-trim released"));
+        } else {
+            log::debug(format_args!("auto-trim {:.1} dB", self.clip.trim_db()));
+        }
+    }
+
+    /// Shared per-sample bookkeeping: lazy SR change, running time accumulator,
+    /// and the fade-in ramp. Returns the fade gain for this sample.
+    #[inline]
+    fn advance(&mut self, sr: f32) -> f32 {
         if sr != self.sr {
             self.sr = sr;
-            self.gen.reset(sr);
+            self.gen.set_sample_rate(sr);
+            self.master.set_sample_rate(sr);
+            self.silence.set_sample_rate(sr);
+            self.clip.set_sample_rate(sr);
+            self.level_sm.set_time_ms(AUTO_GAIN_GLIDE_MS, sr);
+            if let Some(w) = &mut self.waveform { w.set_sample_rate(sr); }
+            self.update_fade_inc();
+        }
+        self.t += 1.0 / f64::from(self.sr);
+        self.ctx.sr = self.sr;
+        self.ctx.time = self.t;
+        if self.fade < 1.0 {
+            self.fade = (self.fade + self.fade_inc).min(1.0);
+        }
+        self.fade
+    }
+
+    /// Start a fade-in from silence lasting `seconds`, and use that length for
+    /// subsequent scene swaps. `seconds <= 0` disables the soft start.
+    #[inline]
+    pub fn fade_in(&mut self, seconds: f32) {
+        self.fade_s = seconds.max(0.0);
+        self.fade = 0.0;
+        self.update_fade_inc();
+    }
+
+    /// Change the fade-in length without restarting the ramp. A fade already in
+    /// progress continues from its current level at the new speed.
+    #[inline]
+    pub fn set_fade_in(&mut self, seconds: f32) {
+        self.fade_s = seconds.max(0.0);
+        self.update_fade_inc();
+    }
+
+    #[inline]
+    fn update_fade_inc(&mut self) {
+        if self.fade_s <= 0.0 {
+            self.fade = 1.0;
+            self.fade_inc = 1.0;
+        } else {
+            self.fade_inc = 1.0 / (self.fade_s * self.sr);
         }
-        // maintain a running time accumulator (not currently exposed)
-        self.t += 1.0 / self.sr;
-        self.gen.next()
+    }
+
+    /// Communicate a (possibly unchanged) sample rate ahead of rendering.
+    ///
+    /// Confirming the current rate is a no-op; a real change rescales the
+    /// generator via [`Generator::set_sample_rate`]. Returns `true` if the
+    /// generator had to fall back to a full reset.
+    #[inline]
+    pub fn set_sample_rate(&mut self, sr: f32) -> bool {
+        let sr = sr.max(1.0);
+        if sr == self.sr {
+            return false;
+        }
+        self.sr = sr;
+        self.update_fade_inc();
+        self.master.set_sample_rate(sr);
+        self.silence.set_sample_rate(sr);
+        self.clip.set_sample_rate(sr);
+        self.level_sm.set_time_ms(AUTO_GAIN_GLIDE_MS, sr);
+        if let Some(w) = &mut self.waveform { w.set_sample_rate(sr); }
+        self.gen.set_sample_rate(sr)
     }
 
     /// Return the engine’s current sample rate.
     #[inline] pub fn sample_rate(&self) -> f32 { self.sr }
 
-    /// Return elapsed time (seconds) since this engine was created.
-    #[inline] pub fn time(&self) -> f32 { self.t }
+    /// Return rendered time (seconds) since this engine was created.
+    #[inline] pub fn time(&self) -> f64 { self.t }
+
+    /// Context the scene renders with (see [`Generator::render`]).
+    #[inline] pub fn render_ctx(&self) -> &RenderCtx { &self.ctx }
+
+    /// Host tempo in BPM, clamped to [`TEMPO_RANGE_BPM`] (default 120).
+    #[inline] pub fn set_tempo(&mut self, bpm: f32) { self.ctx.tempo = bpm.clamp(TEMPO_RANGE_BPM.0, TEMPO_RANGE_BPM.1); }
+    #[inline] pub fn tempo(&self) -> f32 { self.ctx.tempo }
+
+    /// Host transport state (default [`Transport::Playing`]).
+    #[inline] pub fn set_transport(&mut self, t: Transport) { self.ctx.transport = t; }
+    #[inline] pub fn transport(&self) -> Transport { self.ctx.transport }
+
+    /// Frames in the host buffer about to be rendered (0 = unknown). Hosts
+    /// that render in blocks set it before each block.
+    #[inline] pub fn set_block_len(&mut self, frames: u32) { self.ctx.block_len = frames; }
+
+    /// Soft-start length in seconds (see [`Engine::fade_in`]).
+    #[inline] pub fn fade_in_s(&self) -> f32 { self.fade_s }
 
     /// Replace the inner generator (scene) in a zero-allocation manner.
-    /// We call `reset(sr)` on the new scene.
+    /// We call `reset(sr)` on the new scene, adopt its bus settings, and restart
+    /// the fade-in. The shared reverb/delay tails carry across the swap.
+    /// Returns the previous scene, so a realtime caller can hand it to another
+    /// thread to free.
     #[inline]
-    pub fn swap_scene(&mut self, mut new_scene: G) {
+    pub fn swap_scene(&mut self, mut new_scene: G) -> G {
         new_scene.reset(self.sr);
-        self.gen = new_scene;
+        new_scene.set_time(self.t);
+        self.master.set_bus_settings(&new_scene.bus_settings());
+        let old = core::mem::replace(&mut self.gen, new_scene);
+        self.gen.set_output_trim(self.clip.trim());
+        let gen = &self.gen;
+        self.taps.rebind(|name| gen.tap_index(name));
+        self.silence.reset();
+        self.fade_in(self.fade_s);
+        // the fade-in covers the level change
+        self.update_level();
+        self.level_sm.reset(self.level);
+        old
     }
 
     /// Get a mutable reference to the inner generator for live parameter tweaks.
     #[inline]
     pub fn scene_mut(&mut self) -> &mut G { &mut self.gen }
+
+    /// Subscribe to analysis tap `name`: one of [`ENGINE_TAPS`](crate::taps::ENGINE_TAPS)
+    /// or a tap of the current scene (see [`crate::taps`]). Returns the slot
+    /// to read, or `None` for an unknown name or when all
+    /// [`MAX_TAPS`](crate::taps::MAX_TAPS) are in use. Scene taps follow scene
+    /// swaps by name.
+    pub fn subscribe_tap(&mut self, name: &str) -> Option<usize> {
+        let gen = &self.gen;
+        self.taps.subscribe(name, |n| gen.tap_index(n))
+    }
+
+    #[inline] pub fn unsubscribe_tap(&mut self, slot: usize) { self.taps.unsubscribe(slot); }
+
+    /// Move the values tap `slot` recorded since the last read into `out`,
+    /// oldest first, one per control block (see [`Engine::tap_rate`]).
+    /// Returns how many were written.
+    #[inline] pub fn read_tap(&mut self, slot: usize, out: &mut [f32]) -> usize { self.taps.read(slot, out) }
+
+    /// Values per second each subscribed tap records.
+    #[inline] pub fn tap_rate(&self) -> f32 { self.taps.rate(self.sr) }
+
+    /// Start recording a min/max [`WaveformHistory`] of the output (mono sum)
+    /// with `columns_per_s` columns over the last `seconds`, or stop with
+    /// `None`. Allocates; call it outside the audio callback.
+    pub fn enable_waveform(&mut self, columns: Option<(f32, f32)>) {
+        self.waveform = columns.map(|(per_s, seconds)| WaveformHistory::new(self.sr, per_s, seconds));
+    }
+
+    /// The output history, if [`Engine::enable_waveform`] turned it on.
+    #[inline] pub fn waveform(&self) -> Option<&WaveformHistory> { self.waveform.as_ref() }
+
+    /// Forget the recorded output history (it keeps recording).
+    #[inline]
+    pub fn clear_waveform(&mut self) {
+        if let Some(w) = &mut self.waveform { w.clear(); }
+    }
+
+    /// Oldest unread event from the scene (notes, thunder, section changes),
+    /// or `None` once drained. Hosts poll this after each rendered block.
+    #[inline] pub fn poll_event(&mut self) -> Option<SceneEvent> { self.gen.pop_event() }
+
+    /// Get a mutable reference to the master bus (width and other master settings).
+    #[inline]
+    pub fn master_mut(&mut self) -> &mut MasterBus { &mut self.master }
+
+    /// `true` once the output has stayed below the silence threshold for the
+    /// hold time (see [`Engine::silence_mut`]). Hosts on battery can suspend the
+    /// stream while this holds.
+    #[inline] pub fn is_silent(&self) -> bool { self.silence.is_silent() }
+
+    /// How many times a NaN/Inf was caught and the scene or master bus reset.
+    #[inline] pub fn nan_resets(&self) -> u64 { self.nan_resets }
+
+    /// Per-stage CPU time rendered on this thread since the last
+    /// [`Engine::reset_cpu_report`]: graph-scene nodes by family and the
+    /// master bus by effect (see [`crate::profile`]). The counters belong to
+    /// the thread, not the engine, so render one engine per thread while
+    /// measuring. All zeros unless built with the `profile` feature.
+    #[inline] pub fn cpu_report(&self) -> CpuReport { CpuReport::capture() }
+
+    /// Zero the CPU counters of the calling thread.
+    #[inline] pub fn reset_cpu_report(&self) { CpuReport::reset(); }
+
+    /// Silence detector settings (threshold and hold time).
+    #[inline]
+    pub fn silence_mut(&mut self) -> &mut SilenceDetector { &mut self.silence }
+
+    #[inline] pub fn silence(&self) -> &SilenceDetector { &self.silence }
+
+    #[inline] pub fn master(&self) -> &MasterBus { &self.master }
+
+    /// Clip statistics and auto-trim state for the scene's final clamp.
+    #[inline] pub fn clip_guard(&self) -> &ClipGuard { &self.clip }
+
+    /// Zero the clipped/total frame counters.
+    #[inline] pub fn reset_clip_stats(&mut self) { self.clip.reset_stats(); }
+
+    /// Let sustained clipping slowly lower the scene's output gain (restored
+    /// once it stops). Off by default; turning it off restores unity at once.
+    pub fn set_auto_trim(&mut self, on: bool) {
+        let was = self.clip.trim();
+        if self.clip.set_auto_trim(on) {
+            self.apply_trim(was);
+        }
+    }
+}
+
+/// RMS level in dBFS of `engine`'s output over `seconds`, ignoring the first
+/// `settle_s` (fade-in, reverb build-up). This is how the scenes'
+/// [`Generator::loudness_db`] calibrations are made (`ambientor-cli calibrate`);
+/// the engine keeps running, so measure a fresh one.
+pub fn measure_loudness_db<G: Generator>(engine: &mut Engine<G>, settle_s: f32, seconds: f32) -> f32 {
+    let sr = engine.sample_rate();
+    for _ in 0..(settle_s.max(0.0) * sr) as usize {
+        engine.next_stereo(sr);
+    }
+    let n = ((seconds.max(0.0) * sr) as usize).max(1);
+    let mut sum = 0.0f64;
+    for _ in 0..n {
+        let (l, r) = engine.next_stereo(sr);
+        sum += f64::from(l * l + r * r);
+    }
+    let rms = (sum / (2 * n) as f64).sqrt() as f32;
+    ambientor_core::dsp::lin_to_db(rms.max(1e-9))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Silent until its drive goes past 1, then NaN.
+    struct Unstable {
+        drive: f32,
+    }
+
+    impl Generator for Unstable {
+        fn reset(&mut self, _sr: f32) {}
+        fn next(&mut self) -> f32 { if self.drive > 1.0 { f32::NAN } else { 0.0 } }
+        fn param_value(&self, id: ParamId) -> Option<f32> { (id == ParamId::Drive).then_some(self.drive) }
+        fn set_param_value(&mut self, id: ParamId, v: f32) -> bool {
+            if id != ParamId::Drive { return false; }
+            self.drive = v;
+            true
+        }
+        fn reset_params(&mut self) { self.drive = 1.0; }
+    }
+
+    #[test]
+    fn non_finite_params_are_rejected() {
+        let mut e = Engine::new(Unstable { drive: 1.0 });
+        for v in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            assert!(!e.set_param(ParamId::Drive, v));
+            assert!(!e.ramp_param(ParamId::Drive, v, 1.0));
+            assert!(!e.set_param(ParamId::Width, v));
+        }
+        assert_eq!(e.param(ParamId::Drive), Some(1.0));
+        assert!(e.param(ParamId::Width).is_some_and(f32::is_finite));
+    }
+
+    #[test]
+    fn recovery_restores_default_params() {
+        let mut e = Engine::new(Unstable { drive: 1.0 });
+        assert!(e.set_param(ParamId::Drive, 2.0));
+        for _ in 0..48_000 {
+            let (l, r) = e.next_stereo(48_000.0);
+            assert!(l.is_finite() && r.is_finite());
+        }
+        assert_eq!(e.nan_resets(), 1);
+        assert_eq!(e.param(ParamId::Drive), Some(1.0));
+    }
+
+    #[test]
+    fn reset_params_restores_the_master_bus() {
+        let mut e = Engine::new(Unstable { drive: 1.0 });
+        let fresh = e.params();
+        let master: Vec<ParamId> = ParamId::ALL.into_iter().filter(|id| id.is_master()).collect();
+        for &id in &master { assert!(e.set_param(id, id.range().1), "{}", id.name()); }
+        assert!(master.iter().all(|&id| e.param(id) != fresh.get(id)));
+        e.reset_params();
+        for &id in &master { assert_eq!(e.param(id), fresh.get(id), "{}", id.name()); }
+    }
+
+    #[test]
+    fn recovery_is_rate_limited() {
+        // a scene that stays broken whatever its params
+        struct Broken;
+        impl Generator for Broken {
+            fn reset(&mut self, _sr: f32) {}
+            fn next(&mut self) -> f32 { f32::NAN }
+        }
+        let mut e = Engine::new(Broken);
+        for _ in 0..47_000 { e.next_stereo(48_000.0); }
+        assert_eq!(e.nan_resets(), 2, "one reset per {NAN_RESET_HOLDOFF_S} s");
+    }
+
+    #[test]
+    fn skipping_idle_frames_renders_the_same() {
+        const SR: f32 = 48_000.0;
+        let scene = || crate::scenes::Scene::by_name("cave-drips", SR).unwrap();
+        let (mut plain, mut skipping) = (Engine::new(scene()), Engine::new(scene()));
+        skipping.set_skip_idle(true);
+        let frames = 30 * SR as usize;
+
+        let (mut skip, mut skipped) = (0, 0);
+        for i in 0..frames {
+            let a = plain.next_stereo(SR);
+            if skip == 0 {
+                skip = skipping.skip_idle(SR, frames - i);
+                skipped += skip;
+            }
+            let b = if skip > 0 {
+                skip -= 1;
+                (0.0, 0.0)
+            } else {
+                skipping.next_stereo(SR)
+            };
+            assert_eq!(a, b, "frame {i}");
+        }
+        assert!(skipped > frames / 10, "only {skipped} of {frames} frames skipped");
+        assert_eq!(plain.time(), skipping.time());
+    }
+
+    #[test]
+    fn waveform_follows_the_output_until_reset() {
+        let mut e = Engine::new(crate::scenes::Scene::by_name("slow-drone", 48_000.0).unwrap());
+        assert!(e.waveform().is_none());
+        e.enable_waveform(Some((10.0, 1.0)));
+        let mut peak = 0.0f32;
+        for _ in 0..48_000 {
+            let (l, r) = e.next_stereo(48_000.0);
+            peak = peak.max((0.5 * (l + r)).abs());
+        }
+        let mut cols = [[0.0; 2]; 10];
+        assert_eq!(e.waveform().unwrap().fill(&mut cols), 10);
+        let widest = cols.iter().fold(0.0f32, |m, c| m.max(c[1]).max(-c[0]));
+        assert_eq!(widest, peak);
+        e.clear_waveform();
+        assert!(e.waveform().unwrap().is_empty());
+    }
 }
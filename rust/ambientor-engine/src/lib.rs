@@ -1,20 +1,75 @@
 //! Ambientor Engine — graph + building blocks + scenes.
 //!
 //! Crate layout:
+//! - [`bank`]   : several engines mixed into one output (per-engine gain/pan, shared limiter)
+//! - [`circadian`]: day/night keyframe schedule driving intensity and width
+//! - [`expr`]   : tiny arithmetic expressions for scene-file parameter modulation
+//! - [`command`]: lock-free control command queue into the audio thread
+//! - `convolution`: impulse-response reverb by partitioned FFT convolution (feature `convolution`)
+//! - [`event`]  : events scenes report back to the host (notes, thunder, sections)
+//! - [`fft`]    : radix-2 FFT and the smoothed spectrum analyzer behind visualizers
+//! - [`export`] : dithered 16-bit quantization and WAV writing for offline renders
 //! - [`graph`]  : `Generator` trait and `Engine<G>` wrapper
+//! - [`log`]    : diagnostics facade (warnings to stderr or a host-installed sink)
 //! - [`nodes`]  : oscillators, modulators, utility DSP nodes
+//! - [`params`] : numeric ids for host parameters and engine-side parameter ramps
+//! - [`profile`]: per-stage CPU attribution for benchmarks (timings need feature `profile`)
+//! - [`effects`]: insert effects (stereo width, …)
+//! - [`master`] : `MasterBus`, shared reverb/delay buses and the stereo chain after every scene
 //! - [`reverb`] : lightweight reverbs/diffusers (implemented separately)
+//! - [`routing`]: channel maps from the scene's stereo frame to device channels
 //! - [`scenes`] : musical scene graphs that implement `Generator`
+//! - [`sequencer`]: probability step sequencer + voice pool for generative melodies
+//! - `script`   : Rhai scripts driving the engine through the command queue (feature `scripting`)
+//! - [`spatial`]: multichannel (quad/5.1/7.1) spatializer with slow autopanning
+//! - `stream`   : render-ahead worker thread + lock-free ring (feature `stream`)
+//! - [`taps`]   : named internal signals (pre-reverb, post-filter, modulators) sampled for UIs
+//! - [`triggers`]: event sources (clock, Poisson, Euclidean, divider) for generative scenes
+//! - [`waveform`]: downsampled min/max output history for scrolling waveform displays
 //!
 //! The engine deliberately avoids heap allocations in the audio thread.
 //! Scenes are plain structs; parameters are simple floats with optional
 //! per-sample smoothing.
 
+pub mod bank;
+pub mod circadian;
+pub mod command;
+#[cfg(feature = "convolution")]
+pub mod convolution;
+pub mod effects;
+pub mod event;
+pub mod export;
+pub mod expr;
+pub mod fft;
 pub mod graph;
+pub mod log;
+pub mod master;
 pub mod nodes;
+pub mod params;
+pub mod profile;
 pub mod reverb;
+pub mod routing;
 pub mod scenes;
+#[cfg(feature = "scripting")]
+pub mod script;
+pub mod sequencer;
+pub mod spatial;
+#[cfg(feature = "stream")]
+pub mod stream;
+pub mod taps;
+pub mod triggers;
+pub mod waveform;
 
 // Re-export some commonly used items to make downstream imports ergonomic.
-pub use graph::{Engine, Generator};
-pub use nodes::{NoiseMod, Osc, Wave, Lfo, Mix2, PanLaw, OnePoleSmoother};
+pub use ambientor_core::dsp::{Scale, Tuning};
+pub use bank::EngineBank;
+pub use circadian::{Circadian, CircadianKey};
+pub use event::{EventQueue, SceneEvent, EVENT_QUEUE_CAPACITY};
+pub use graph::{BusFrame, Engine, Generator, RenderCtx, Transport, DEFAULT_FADE_IN_S, DEFAULT_SILENCE_DB, DEFAULT_SILENCE_HOLD_S, DEFAULT_TEMPO_BPM, TEMPO_RANGE_BPM};
+pub use master::{BusSettings, MasterBus};
+pub use params::{AbSlot, ParamId, ParamSet, RampCurve};
+pub use nodes::{AdditiveOsc, AutoPan, ChaosMap, ChaosMod, ControlClock, ControlRamp, Crackle, EnvFollower, Glide, NoiseMod, Osc, Wave, Lfo, LfoShape, Mix2, PanLaw, OnePoleSmoother, LayerPan, PinkNoise, RandomWalk, ResonatorBank, RingMod, Rng, SampleHold, SilenceDetector, SmoothNoise, SubBed, TiltNoise, UnisonOsc};
+pub use routing::{ChannelMap, ChannelSource};
+pub use sequencer::{NoteEvent, Step, StepSequencer, VoicePool};
+pub use taps::{ENGINE_TAPS, MAX_TAPS, TAP_HISTORY};
+pub use triggers::{Clock, ClockDivider, Euclid, PoissonTrigger, Trigger};
@@ -0,0 +1,116 @@
+//! Diagnostics facade: one process-wide sink for engine warnings.
+//!
+//! The engine reports things a host may want in its own logs — NaN/Inf
+//! recoveries, out-of-range parameters being clamped, clipping — through
+//! [`warn`], [`info`], etc. Without a sink installed messages go to stderr;
+//! hosts route them elsewhere with [`set_sink`] (the FFI forwards to a C
+//! callback).
+//!
+//! Messages are formatted into a fixed stack buffer (longer ones are
+//! truncated), so logging from the audio thread never allocates. The sink is
+//! only try-locked there: a message emitted while another thread is swapping
+//! the sink is dropped rather than blocking the callback.
+
+use std::fmt::{self, Write as _};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::RwLock;
+
+/// Longest message passed to a sink, in bytes.
+pub const MAX_MESSAGE_LEN: usize = 255;
+
+/// Message severity, most severe first.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error = 0,
+    Warn = 1,
+    Info = 2,
+    Debug = 3,
+}
+
+impl Level {
+    pub const ALL: [Level; 4] = [Level::Error, Level::Warn, Level::Info, Level::Debug];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+        }
+    }
+
+    /// Level for a numeric code (`0` = error .. `3` = debug); out of range → `None`.
+    pub fn from_u8(v: u8) -> Option<Self> { Self::ALL.get(v as usize).copied() }
+}
+
+/// Receives every message at or above the current [`max_level`].
+pub type Sink = Box<dyn Fn(Level, &str) + Send + Sync>;
+
+static SINK: RwLock<Option<Sink>> = RwLock::new(None);
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(Level::Info as u8);
+
+/// Install `sink` for all further messages (`None` → back to stderr).
+pub fn set_sink(sink: Option<Sink>) {
+    *SINK.write().unwrap_or_else(|e| e.into_inner()) = sink;
+}
+
+/// Drop messages less severe than `level` (default [`Level::Info`]).
+pub fn set_max_level(level: Level) { MAX_LEVEL.store(level as u8, Ordering::Relaxed); }
+
+/// Least severe level that is still delivered.
+pub fn max_level() -> Level { Level::from_u8(MAX_LEVEL.load(Ordering::Relaxed)).unwrap_or(Level::Debug) }
+
+/// `true` if a message at `level` would be delivered.
+#[inline]
+pub fn enabled(level: Level) -> bool { level as u8 <= MAX_LEVEL.load(Ordering::Relaxed) }
+
+/// Fixed-size message buffer; writes past the end are cut at a char boundary.
+struct MessageBuf {
+    buf: [u8; MAX_MESSAGE_LEN],
+    len: usize,
+}
+
+impl fmt::Write for MessageBuf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let room = MAX_MESSAGE_LEN - self.len;
+        let mut n = s.len().min(room);
+        while !s.is_char_boundary(n) { n -= 1; }
+        self.buf[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+        if n < s.len() { Err(fmt::Error) } else { Ok(()) }
+    }
+}
+
+/// Format and deliver one message.
+pub fn log(level: Level, args: fmt::Arguments<'_>) {
+    if !enabled(level) { return; }
+    let mut m = MessageBuf { buf: [0; MAX_MESSAGE_LEN], len: 0 };
+    let _ = m.write_fmt(args); // truncated on overflow
+    let msg = std::str::from_utf8(&m.buf[..m.len]).unwrap_or_default();
+    match SINK.try_read() {
+        Ok(guard) => match guard.as_ref() {
+            Some(sink) => sink(level, msg),
+            None => eprintln!("[{}] {msg}", level.name()),
+        },
+        Err(std::sync::TryLockError::Poisoned(guard)) => {
+            if let Some(sink) = guard.into_inner().as_ref() { sink(level, msg); }
+        }
+        Err(std::sync::TryLockError::WouldBlock) => {}
+    }
+}
+
+#[inline] pub fn error(args: fmt::Arguments<'_>) { log(Level::Error, args); }
+#[inline] pub fn warn(args: fmt::Arguments<'_>) { log(Level::Warn, args); }
+#[inline] pub fn info(args: fmt::Arguments<'_>) { log(Level::Info, args); }
+#[inline] pub fn debug(args: fmt::Arguments<'_>) { log(Level::Debug, args); }
+
+/// Clamp parameter `name` to `lo..=hi`, warning if `x` was outside.
+#[inline]
+pub fn clamp_param(name: &str, x: f32, lo: f32, hi: f32) -> f32 {
+    let y = x.clamp(lo, hi);
+    if y != x {
+        warn(format_args!("{name} {x} out of range {lo}..{hi}, clamped to {y}"));
+    }
+    y
+}
@@ -0,0 +1,354 @@
+//! Engine master section: shared effect buses and stereo processing between the
+//! scene and the host.
+//!
+//! The [`MasterBus`] is owned by [`Engine`](crate::graph::Engine) and applied to
+//! every rendered frame, whatever scene is loaded. Scenes (and their layers)
+//! render dry plus send levels ([`BusFrame`]); the master owns the single reverb
+//! and delay those sends feed, so N layers never need N reverbs.
+
+use crate::effects::{DynamicEq, Emitter, MultibandTexture, PingPongDelay, StereoWidth, ThreeBandEq, TiltEq};
+use crate::graph::BusFrame;
+use crate::log;
+use crate::nodes::{SubBed, SUB_BED_HZ};
+use crate::profile::{scope, Stage};
+use crate::reverb::ReverbLite;
+use ambientor_core::filters::{Crossover, DcBlock};
+
+/// Settings for the shared buses, chosen per scene (see
+/// [`Generator::bus_settings`](crate::graph::Generator::bus_settings)).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BusSettings {
+    /// Reverb size 0..1.
+    pub reverb_room: f32,
+    /// Reverb high-frequency damping 0..1 (1 = darkest).
+    pub reverb_damp: f32,
+    /// Seconds per echo bounce.
+    pub delay_time_s: f32,
+    /// Echo feedback 0..0.95.
+    pub delay_feedback: f32,
+    /// Low-pass in the echo feedback path (Hz).
+    pub delay_tone_hz: f32,
+    /// How much of the delay return is fed on into the reverb (0..1).
+    pub delay_to_reverb: f32,
+}
+
+impl Default for BusSettings {
+    fn default() -> Self {
+        Self {
+            reverb_room: 0.6,
+            reverb_damp: 0.4,
+            delay_time_s: 0.375,
+            delay_feedback: 0.4,
+            delay_tone_hz: 3000.0,
+            delay_to_reverb: 0.0,
+        }
+    }
+}
+
+/// Default cutoff of the master high-pass.
+pub const MASTER_HP_HZ: f32 = 25.0;
+/// Default frequency of the sub bed.
+pub const SUB_BED_DEFAULT_HZ: f32 = 35.0;
+/// Default crossover of the mono-bass stage.
+pub const MONO_BASS_HZ: f32 = 120.0;
+/// Default band of the dynamic EQ on the reverb and delay returns.
+pub const RETURN_DYN_EQ_HZ: f32 = 1500.0;
+
+/// Stereo master chain: (dry + (reverb return + delay return) → dynamic EQ) → width → mono
+/// bass → multiband texture → EQ → tilt → distance/occlusion → high-pass
+/// (+ sub bed) → host.
+#[derive(Clone, Debug)]
+pub struct MasterBus {
+    reverb: ReverbLite, // 100% wet
+    delay: PingPongDelay,
+    reverb_return: f32,
+    delay_return: f32,
+    delay_to_reverb: f32,
+    return_eq: DynamicEq, // off unless `return_eq_on`
+    return_eq_on: bool,
+    width: StereoWidth,
+    mono_bass: [Crossover; 2], // off unless `mono_bass_on`
+    mono_bass_on: bool,
+    texture: MultibandTexture,
+    eq: ThreeBandEq,
+    tilt: TiltEq,
+    emitter: Emitter,
+    // DC/subsonic guard: two one-poles per side (12 dB/oct)
+    hp: [DcBlock; 4],
+    hp_on: bool,
+    hp_hz: f32,
+    sub: SubBed, // only sounds while the high-pass is off
+    // last frame at two analysis points (mono), for `crate::taps`
+    tap_pre_reverb: f32,
+    tap_post_filter: f32,
+    sr: f32,
+}
+
+impl MasterBus {
+    pub fn new(sr: f32) -> Self {
+        let mut reverb = ReverbLite::new(sr);
+        reverb.set_mix(1.0);
+        let mut m = Self {
+            reverb,
+            delay: PingPongDelay::new(0.375, 0.4, sr),
+            reverb_return: 1.0,
+            delay_return: 1.0,
+            delay_to_reverb: 0.0,
+            return_eq: DynamicEq::new(RETURN_DYN_EQ_HZ, sr),
+            return_eq_on: false,
+            width: StereoWidth::new(sr),
+            mono_bass: [Crossover::new(MONO_BASS_HZ, sr); 2],
+            mono_bass_on: false,
+            texture: MultibandTexture::new(sr),
+            eq: ThreeBandEq::new(sr),
+            tilt: TiltEq::new(sr),
+            emitter: Emitter::new(sr),
+            hp: [DcBlock::new(MASTER_HP_HZ, sr); 4],
+            hp_on: true,
+            hp_hz: MASTER_HP_HZ,
+            sub: SubBed::new(SUB_BED_DEFAULT_HZ, sr),
+            tap_pre_reverb: 0.0,
+            tap_post_filter: 0.0,
+            sr,
+        };
+        m.set_bus_settings(&BusSettings::default());
+        m
+    }
+
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        self.reverb.set_sample_rate(sr);
+        self.delay.set_sample_rate(sr);
+        self.return_eq.set_sample_rate(sr);
+        self.width.set_sample_rate(sr);
+        for x in &mut self.mono_bass { x.set_sample_rate(sr); }
+        self.texture.set_sample_rate(sr);
+        self.eq.set_sample_rate(sr);
+        self.tilt.set_sample_rate(sr);
+        self.emitter.set_sample_rate(sr);
+        for f in &mut self.hp { f.set_sample_rate(sr); }
+        self.sub.set_sample_rate(sr);
+        self.sr = sr;
+    }
+
+    /// Apply a scene's room/echo settings (tails keep ringing).
+    pub fn set_bus_settings(&mut self, s: &BusSettings) {
+        self.reverb.set_room(s.reverb_room);
+        self.reverb.set_damp(s.reverb_damp);
+        self.delay.set_time(s.delay_time_s);
+        self.delay.set_feedback(s.delay_feedback);
+        self.delay.set_tone_hz(s.delay_tone_hz);
+        self.delay_to_reverb = s.delay_to_reverb.clamp(0.0, 1.0);
+    }
+
+    /// Flush every tail and filter state (reverb, delay, width, EQ, occlusion); settings are kept.
+    pub fn clear(&mut self) {
+        self.tap_pre_reverb = 0.0;
+        self.tap_post_filter = 0.0;
+        self.reverb.clear();
+        self.delay.clear();
+        self.return_eq.reset();
+        self.width.clear();
+        for x in &mut self.mono_bass { x.reset(); }
+        self.texture.reset();
+        self.eq.reset();
+        self.tilt.reset();
+        self.emitter.reset();
+        for f in &mut self.hp { f.reset(); }
+    }
+
+    /// Width, texture, EQ, tone, returns, distance and occlusion back to their
+    /// defaults (the room/echo settings, rolloff and reverb bypass are left alone).
+    pub fn reset_params(&mut self) {
+        self.reverb_return = 1.0;
+        self.delay_return = 1.0;
+        self.width.set_width_percent(100.0);
+        for band in 0..3 { self.texture.set_band(band, 0.0, 0.0); }
+        self.set_eq_db(0.0, 0.0, 0.0);
+        self.eq.set_mid_hz(1000.0);
+        self.tilt.set_tone(0.0);
+        self.emitter.set_distance(0.0);
+        self.emitter.set_occlusion(0.0);
+    }
+
+    /// Reverb return level (0..2, default 1 = as the scene sends it).
+    #[inline] pub fn set_reverb_return(&mut self, g: f32) { self.reverb_return = log::clamp_param("reverb return", g, 0.0, 2.0); }
+    #[inline] pub fn reverb_return(&self) -> f32 { self.reverb_return }
+
+    /// Switch the shared reverb off (its tail fades out and it stops using CPU)
+    /// or back on.
+    #[inline] pub fn set_reverb_bypass(&mut self, on: bool) { self.reverb.set_bypass(on); }
+    #[inline] pub fn reverb_bypass(&self) -> bool { self.reverb.bypass() }
+    /// Shimmer of the shared reverb: octave-up feedback of its tail (0..1, default 0).
+    #[inline] pub fn set_reverb_shimmer(&mut self, amount: f32) { self.reverb.set_shimmer(log::clamp_param("reverb shimmer", amount, 0.0, 1.0)); }
+    #[inline] pub fn reverb_shimmer(&self) -> f32 { self.reverb.shimmer() }
+    /// `true` while the reverb is idle (bypassed, or nothing sent to it lately).
+    #[inline] pub fn reverb_asleep(&self) -> bool { self.reverb.is_asleep() }
+
+    /// `true` while neither the reverb, the delay nor the sub bed (with the
+    /// high-pass off) makes anything audible.
+    #[inline]
+    pub fn is_idle(&self) -> bool {
+        self.reverb.is_asleep() && self.delay.is_idle() && (self.hp_on || !self.sub.is_active())
+    }
+
+    /// Dynamic EQ on the reverb and delay returns (see [`DynamicEq`]): the
+    /// band at `hz` (40..16000) is cut only while it rings above
+    /// `threshold_db` (-60..0 dBFS), taming the comb resonances long feedback
+    /// builds up without dulling the tail the rest of the time; off by default.
+    pub fn set_return_dyn_eq(&mut self, on: bool, hz: f32, threshold_db: f32) {
+        if on && !self.return_eq_on { self.return_eq.reset(); }
+        self.return_eq_on = on;
+        self.return_eq.set_freq_hz(log::clamp_param("return dynamic EQ", hz, 40.0, 16_000.0));
+        self.return_eq.set_threshold_db(log::clamp_param("return dynamic EQ threshold", threshold_db, -60.0, 0.0));
+    }
+    #[inline] pub fn return_dyn_eq_on(&self) -> bool { self.return_eq_on }
+    #[inline] pub fn return_dyn_eq(&self) -> &DynamicEq { &self.return_eq }
+
+    /// Delay return level (0..2, default 1).
+    #[inline] pub fn set_delay_return(&mut self, g: f32) { self.delay_return = log::clamp_param("delay return", g, 0.0, 2.0); }
+    #[inline] pub fn delay_return(&self) -> f32 { self.delay_return }
+
+    /// Stereo width in percent (0 = mono, 100 = unchanged, 200 = extra wide).
+    #[inline] pub fn set_width_percent(&mut self, pct: f32) { self.width.set_width_percent(pct); }
+    #[inline] pub fn width_percent(&self) -> f32 { 100.0 * self.width.width() }
+
+    /// Fold everything below `hz` (clamped to 40..300) to mono with an LR4
+    /// crossover, so wide pads keep a centered low end for vinyl cutting, club
+    /// systems, and a single subwoofer; off by default.
+    pub fn set_mono_bass(&mut self, on: bool, hz: f32) {
+        if on && !self.mono_bass_on { for x in &mut self.mono_bass { x.reset(); } }
+        self.mono_bass_on = on;
+        let hz = log::clamp_param("mono bass", hz, 40.0, 300.0);
+        for x in &mut self.mono_bass { x.set_freq_hz(hz); }
+    }
+    #[inline] pub fn mono_bass_on(&self) -> bool { self.mono_bass_on }
+    #[inline] pub fn mono_bass_hz(&self) -> f32 { self.mono_bass[0].freq_hz() }
+
+    /// Multiband texture: saturation `amount` (0..1) and `level_db` (±12) of
+    /// band 0 (low), 1 (mid) or 2 (high). All bands neutral = bypassed.
+    #[inline]
+    pub fn set_texture_band(&mut self, band: usize, amount: f32, level_db: f32) {
+        let amount = log::clamp_param("texture amount", amount, 0.0, 1.0);
+        let level_db = log::clamp_param("texture level", level_db, -12.0, 12.0);
+        self.texture.set_band(band, amount, level_db);
+    }
+    /// Multiband texture crossover frequencies (Hz, default 200 and 3000).
+    #[inline] pub fn set_texture_crossovers(&mut self, lo_hz: f32, hi_hz: f32) { self.texture.set_crossovers(lo_hz, hi_hz); }
+    #[inline] pub fn texture(&self) -> &MultibandTexture { &self.texture }
+
+    /// Output EQ band gains in dB (each ±12; all 0 = bypassed).
+    #[inline]
+    pub fn set_eq_db(&mut self, low: f32, mid: f32, high: f32) {
+        self.eq.set_low_db(low);
+        self.eq.set_mid_db(mid);
+        self.eq.set_high_db(high);
+    }
+
+    /// Center of the output EQ's mid band in Hz (100..10000, default 1000).
+    #[inline] pub fn set_eq_mid_hz(&mut self, hz: f32) { self.eq.set_mid_hz(hz); }
+
+    #[inline] pub fn eq(&self) -> &ThreeBandEq { &self.eq }
+
+    /// Tilt "tone": -1 = dark (good for night listening) .. 0 = flat .. +1 = bright.
+    #[inline] pub fn set_tone(&mut self, tone: f32) { self.tilt.set_tone(tone); }
+    #[inline] pub fn tone(&self) -> f32 { self.tilt.tone() }
+
+    /// Distance of the soundscape from the listener in meters: inverse-distance
+    /// gain between the rolloff's reference and max distance (default 0 m = unity).
+    #[inline] pub fn set_listener_distance(&mut self, m: f32) { self.emitter.set_distance(m); }
+    #[inline] pub fn listener_distance(&self) -> f32 { self.emitter.distance() }
+    /// Reference distance (unity gain, default 1 m) and max distance (default 100 m).
+    #[inline] pub fn set_distance_rolloff(&mut self, ref_m: f32, max_m: f32) { self.emitter.set_rolloff(ref_m, max_m); }
+    /// Occlusion 0 (clear) .. 1 (behind walls): low-pass plus up to 6 dB less level.
+    #[inline] pub fn set_occlusion(&mut self, x: f32) { self.emitter.set_occlusion(x); }
+    #[inline] pub fn occlusion(&self) -> f32 { self.emitter.occlusion() }
+    #[inline] pub fn emitter(&self) -> &Emitter { &self.emitter }
+
+    /// High-pass at the very end of the chain (on by default at [`MASTER_HP_HZ`]),
+    /// removing DC and subsonic build-up (e.g. from saturated saw/tri mixes)
+    /// that would push small speakers. `hz` is clamped to 10..80; switch it off
+    /// for full-range systems that should get everything down to DC.
+    pub fn set_highpass(&mut self, on: bool, hz: f32) {
+        if on && !self.hp_on { for f in &mut self.hp { f.reset(); } }
+        self.hp_on = on;
+        self.sub.set_open(!on);
+        self.hp_hz = log::clamp_param("high-pass", hz, 10.0, 80.0);
+        for f in &mut self.hp { f.set_cutoff_hz(self.hp_hz); }
+    }
+    #[inline] pub fn highpass_on(&self) -> bool { self.hp_on }
+    #[inline] pub fn highpass_hz(&self) -> f32 { self.hp_hz }
+
+    /// Sub bed: a mono 25–45 Hz sine swell (peak `level` 0..1, default 0 = off)
+    /// whose level rides a `swell_hz` sine LFO, added after the high-pass for
+    /// installations with subwoofers. It is gated behind the high-pass: it
+    /// only sounds while [`MasterBus::set_highpass`] has switched that off,
+    /// fading in and out with it.
+    pub fn set_sub_bed(&mut self, level: f32, hz: f32, swell_hz: f32) {
+        self.sub.set_level(log::clamp_param("sub bed level", level, 0.0, 1.0));
+        self.sub.set_freq(log::clamp_param("sub bed", hz, SUB_BED_HZ.0, SUB_BED_HZ.1));
+        self.sub.set_swell(log::clamp_param("sub bed swell", swell_hz, 0.005, 1.0), 0.6);
+    }
+    #[inline] pub fn sub_bed(&self) -> &SubBed { &self.sub }
+
+    /// Mono input of the shared reverb on the last frame (sends plus the
+    /// delay's feed), the `pre_reverb` analysis tap.
+    #[inline] pub fn pre_reverb(&self) -> f32 { self.tap_pre_reverb }
+
+    /// Mono mix right after the EQ and tone tilt on the last frame, the
+    /// `post_filter` analysis tap.
+    #[inline] pub fn post_filter(&self) -> f32 { self.tap_post_filter }
+
+    #[inline]
+    pub fn process(&mut self, f: BusFrame) -> (f32, f32) {
+        let (dl, dr) = {
+            let _p = scope(Stage::Delay);
+            self.delay.process(f.delay.0, f.delay.1)
+        };
+        let k = self.delay_to_reverb;
+        self.tap_pre_reverb = 0.5 * (f.reverb.0 + f.reverb.1 + k * (dl + dr));
+        let (rl, rr) = {
+            let _p = scope(Stage::Reverb);
+            self.reverb.process_stereo(f.reverb.0 + k * dl, f.reverb.1 + k * dr)
+        };
+        let (wl, wr) = (self.reverb_return * rl + self.delay_return * dl, self.reverb_return * rr + self.delay_return * dr);
+        let (wl, wr) = if self.return_eq_on { self.return_eq.process(wl, wr) } else { (wl, wr) };
+        let (l, r) = (f.dry.0 + wl, f.dry.1 + wr);
+        let (l, r) = {
+            let _p = scope(Stage::Width);
+            self.width.process(l, r)
+        };
+        let (l, r) = if self.mono_bass_on {
+            let _p = scope(Stage::Output);
+            let [a, b] = &mut self.mono_bass;
+            let ((ll, lh), (rl, rh)) = (a.split(l), b.split(r));
+            let low = 0.5 * (ll + rl);
+            (low + lh, low + rh)
+        } else {
+            (l, r)
+        };
+        let (l, r) = {
+            let _p = scope(Stage::Texture);
+            self.texture.process(l, r)
+        };
+        let (l, r) = {
+            let _p = scope(Stage::Eq);
+            self.eq.process(l, r)
+        };
+        let (l, r) = {
+            let _p = scope(Stage::Tilt);
+            self.tilt.process(l, r)
+        };
+        self.tap_post_filter = 0.5 * (l + r);
+        let (l, r) = {
+            let _p = scope(Stage::Spatial);
+            self.emitter.process(l, r)
+        };
+        let _p = scope(Stage::Output);
+        if !self.hp_on {
+            let s = self.sub.next(self.sr);
+            return (l + s, r + s);
+        }
+        let [a, b, c, d] = &mut self.hp;
+        (b.process(a.process(l)), d.process(c.process(r)))
+    }
+}
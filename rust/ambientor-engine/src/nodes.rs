@@ -5,22 +5,41 @@
 //!
 //! Contents:
 //! - `Wave`, `Osc`     : basic oscillators (Sine/Tri/Saw) with stable phase wrap
-//! - `Lfo`             : low-frequency oscillator (same core as `Osc`), for modulation
+//! - `UnisonOsc`       : up to 7 detuned, phase-randomized copies with stereo spread
+//! - `AdditiveOsc`     : sine partials with spectral tilt and slow per-partial drift
+//! - `Lfo`, `LfoShape` : low-frequency oscillator (sine/tri/saw/square/S&H/exp), for modulation
 //! - `NoiseMod`        : ultra-low-rate random modulator with slewed steps
+//! - `Rng`             : tiny seedable xorshift RNG for audio-thread randomness
+//! - `PinkNoise`       : seedable pink (−3 dB/oct) noise for beds and textures
+//! - `TiltNoise`       : noise with a variable slope, blue (+3) through white to brown (−6 dB/oct)
+//! - `SubBed`          : gated 25–45 Hz sine swell for systems with subwoofers
+//! - `Crackle`         : sparse random vinyl-style clicks with brightness and stereo spread
+//! - `ResonatorBank`   : parallel tuned modal resonators, excited by noise or strikes
+//! - `SampleHold`      : clocked random steps, optionally snapped to a scale
+//! - `RandomWalk`      : bounded Brownian drift
+//! - `SmoothNoise`     : seedable 1D value noise with octaves (organic wander)
+//! - `ChaosMod`        : Lorenz / logistic-map chaotic modulator, regular to fully chaotic
 //! - `OnePoleSmoother` : parameter smoothing
+//! - `EnvFollower`     : peak envelope follower with separate attack/release
+//! - `SilenceDetector` : flags output that has stayed below a threshold for a hold time
+//! - `ClipGuard`       : counts frames hitting a scene's ±1 clamp; optional auto-trim
+//! - `Glide`           : portamento — exponential pitch slew at a rate in semitones/s
 //! - `Mix2`            : lightweight stereo/mono mixer helpers
 //! - `PanLaw`          : constant-power panning helper
+//! - `AutoPan`         : `PanLaw` driven by an LFO or `NoiseMod` position source
+//! - `LayerPan`        : constant-power pan plus interaural delay and head shadow, for layers
+//! - `RingMod`         : ring / amplitude modulation by a carrier oscillator (DC offset for tremolo)
 //!
 //! Notes:
 //! - Frequency is **Hz**; methods expect the current **sample rate** when stepping.
 //! - These nodes are deliberately simple—higher-level scenes wire them together.
 
-use ambientor_core::dsp::{TAU};
+use ambientor_core::dsp::{db_to_lin, fill_sine, mix_in_place, quantize_to_scale, Scale, TAU};
 use ambientor_core::filters::{OnePoleLP};
 use core::fmt::Debug;
 
 /// Oscillator waveform.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Wave { Sine, Tri, Saw }
 
 /// Simple bandlimited-ish triangle (cheap) and naive saw (good enough for ambient).
@@ -60,24 +79,375 @@ impl Osc {
     #[inline] pub fn set_phase01(&mut self, p: f32) { self.phase = if p >= 1.0 { p - (p as i32 as f32) } else if p < 0.0 { 0.0 } else { p }; }
 }
 
-/// Low-frequency oscillator; identical to `Osc` but with convenience constructor.
+/// Maximum voices in a [`UnisonOsc`].
+pub const UNISON_MAX_VOICES: usize = 7;
+
+/// Unison ("supersaw") oscillator bank: `VOICES` copies of one waveform, detuned
+/// symmetrically around the base frequency and spread across the stereo field.
+///
+/// State is kept as parallel arrays (phase, increment, gains) and stepped
+/// voice-by-voice inside a sample, which keeps the inner loop SIMD-friendly;
+/// [`UnisonOsc::process_block`] renders whole buffers.
+#[derive(Copy, Clone, Debug)]
+pub struct UnisonOsc<const VOICES: usize> {
+    phase: [f32; VOICES],
+    ratio: [f32; VOICES], // per-voice frequency ratio from detune
+    gl: [f32; VOICES],
+    gr: [f32; VOICES],
+    freq: f32,
+    detune_cents: f32, // outermost voices sit at ±detune
+    spread: f32,       // 0 = all centered, 1 = outermost voices hard L/R
+    wave: Wave,
+    norm: f32,
+}
+
+impl<const VOICES: usize> UnisonOsc<VOICES> {
+    const VOICES_OK: () = assert!(VOICES >= 1 && VOICES <= UNISON_MAX_VOICES, "UnisonOsc supports 1..=7 voices");
+
+    /// `detune_cents`: spread of the outermost voices; phases are randomized from `seed`.
+    #[inline]
+    pub fn new(freq_hz: f32, wave: Wave, detune_cents: f32, seed: u32) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VOICES_OK;
+        let mut s = Self {
+            phase: [0.0; VOICES],
+            ratio: [1.0; VOICES],
+            gl: [0.0; VOICES],
+            gr: [0.0; VOICES],
+            freq: freq_hz.max(0.0),
+            detune_cents: detune_cents.max(0.0),
+            spread: 0.8,
+            wave,
+            norm: 1.0 / (VOICES as f32).sqrt(),
+        };
+        s.randomize_phases(seed);
+        s.update();
+        s
+    }
+
+    /// Voice position in [-1, 1] (0 for a single voice).
+    #[inline]
+    fn pos(i: usize) -> f32 {
+        if VOICES == 1 { 0.0 } else { 2.0 * i as f32 / (VOICES - 1) as f32 - 1.0 }
+    }
+
+    #[inline]
+    fn update(&mut self) {
+        for i in 0..VOICES {
+            let p = Self::pos(i);
+            self.ratio[i] = (self.detune_cents * p / 1200.0).exp2();
+            let (l, r) = PanLaw::gains(self.spread * p);
+            self.gl[i] = l * self.norm;
+            self.gr[i] = r * self.norm;
+        }
+    }
+
+    #[inline] pub fn set_freq(&mut self, hz: f32) { self.freq = hz.max(0.0); }
+    #[inline] pub fn set_wave(&mut self, w: Wave) { self.wave = w; }
+    #[inline] pub fn set_detune_cents(&mut self, c: f32) { self.detune_cents = c.max(0.0); self.update(); }
+    #[inline] pub fn set_spread(&mut self, s: f32) { self.spread = s.clamp(0.0, 1.0); self.update(); }
+
+    /// Scatter voice phases (avoids the phasey "all voices start together" attack).
+    #[inline]
+    pub fn randomize_phases(&mut self, seed: u32) {
+        let mut rng = Rng::new(seed);
+        for p in &mut self.phase { *p = rng.next01(); }
+    }
+
+    /// Advance one sample; returns `(left, right)`.
+    #[inline]
+    pub fn next_stereo(&mut self, sr: f32) -> (f32, f32) {
+        let base = self.freq / sr;
+        let (mut l, mut r) = (0.0, 0.0);
+        for i in 0..VOICES {
+            let p = (self.phase[i] + base * self.ratio[i]) % 1.0;
+            self.phase[i] = p;
+            let s = osc_sample(p, self.wave);
+            l += s * self.gl[i];
+            r += s * self.gr[i];
+        }
+        (l, r)
+    }
+
+    /// Mono sum of all voices for one sample.
+    #[inline]
+    pub fn next(&mut self, sr: f32) -> f32 {
+        let (l, r) = self.next_stereo(sr);
+        0.707 * (l + r)
+    }
+
+    /// Render a block into `left`/`right` (overwrites; renders `min(len)` frames).
+    ///
+    /// Runs voice-by-voice over the whole block, so each pass is a tight loop
+    /// with constant gains and increment that the compiler can vectorize.
+    #[inline]
+    pub fn process_block(&mut self, sr: f32, left: &mut [f32], right: &mut [f32]) {
+        let n = left.len().min(right.len());
+        let (left, right) = (&mut left[..n], &mut right[..n]);
+        left.fill(0.0);
+        right.fill(0.0);
+        let base = self.freq / sr;
+        for i in 0..VOICES {
+            let inc = base * self.ratio[i];
+            let (gl, gr) = (self.gl[i], self.gr[i]);
+            let mut p = self.phase[i];
+            for (yl, yr) in left.iter_mut().zip(right.iter_mut()) {
+                p = (p + inc) % 1.0;
+                let s = osc_sample(p, self.wave);
+                *yl += s * gl;
+                *yr += s * gr;
+            }
+            self.phase[i] = p;
+        }
+    }
+}
+
+/// Control block for [`AdditiveOsc`]: drift and gains update once per this many samples.
+const ADDITIVE_BLOCK: usize = 32;
+
+/// Additive oscillator: `PARTIALS` sine partials at `freq * ratio[i]`.
+///
+/// Each partial has its own amplitude; a global tilt (dB per octave above the
+/// fundamental) rolls the spectrum off, and a slow smooth-noise drift (cents)
+/// per partial keeps organ/glass drones alive. Partials at or above ~0.45·sr are
+/// muted. [`AdditiveOsc::process_block`] renders with the block sine fill
+/// (`dsp::fill_sine`) per partial; [`AdditiveOsc::next`] is the per-sample path.
+#[derive(Copy, Clone, Debug)]
+pub struct AdditiveOsc<const PARTIALS: usize> {
+    phase: [f32; PARTIALS], // radians, kept in [-π, π] by `fill_sine`
+    ratio: [f32; PARTIALS],
+    amp: [f32; PARTIALS],   // user amplitudes
+    gain: [f32; PARTIALS],  // amp * tilt * nyquist guard (cached)
+    drift: [SmoothNoise; PARTIALS],
+    drift_ratio: [f32; PARTIALS],
+    freq: f32,
+    tilt_db: f32,
+    drift_cents: f32,
+    ctrl: usize, // samples until next control update (per-sample path)
+    scratch: [f32; ADDITIVE_BLOCK],
+}
+
+impl<const PARTIALS: usize> AdditiveOsc<PARTIALS> {
+    /// Harmonic partials (1, 2, 3, …) at equal amplitude, -6 dB/oct tilt, no drift.
+    pub fn new(freq_hz: f32, seed: u32) -> Self {
+        let mut ratio = [1.0; PARTIALS];
+        let mut drift = [SmoothNoise::new(0.05, 2, seed); PARTIALS];
+        for i in 0..PARTIALS {
+            ratio[i] = (i + 1) as f32;
+            drift[i].set_seed(seed.wrapping_add(i as u32 * 0x9E37));
+        }
+        Self {
+            phase: [0.0; PARTIALS],
+            ratio,
+            amp: [1.0; PARTIALS],
+            gain: [0.0; PARTIALS],
+            drift,
+            drift_ratio: [1.0; PARTIALS],
+            freq: freq_hz.max(0.0),
+            tilt_db: -6.0,
+            drift_cents: 0.0,
+            ctrl: 0,
+            scratch: [0.0; ADDITIVE_BLOCK],
+        }
+    }
+
+    #[inline] pub fn set_freq(&mut self, hz: f32) { self.freq = hz.max(0.0); }
+
+    /// Amplitude of partial `i` (0..1).
+    #[inline]
+    pub fn set_partial_amp(&mut self, i: usize, a: f32) {
+        if i < PARTIALS { self.amp[i] = a.max(0.0); }
+    }
+
+    /// Frequency ratio of partial `i` (e.g. 2.76 for bell/glass inharmonics).
+    #[inline]
+    pub fn set_partial_ratio(&mut self, i: usize, r: f32) {
+        if i < PARTIALS { self.ratio[i] = r.max(0.0); }
+    }
+
+    /// Spectral tilt in dB per octave above the fundamental (negative = darker).
+    #[inline] pub fn set_tilt_db_per_oct(&mut self, db: f32) { self.tilt_db = db.clamp(-24.0, 12.0); }
+
+    /// Per-partial drift depth in cents and its wander rate in Hz.
+    #[inline]
+    pub fn set_drift(&mut self, cents: f32, rate_hz: f32) {
+        self.drift_cents = cents.max(0.0);
+        for d in &mut self.drift { d.set_rate(rate_hz); }
+    }
+
+    /// Recompute drift and gains for the next `ADDITIVE_BLOCK` samples.
+    #[inline]
+    fn control(&mut self, sr: f32) {
+        let ctrl_sr = sr / ADDITIVE_BLOCK as f32;
+        let norm = 1.0 / PARTIALS.max(1) as f32;
+        for i in 0..PARTIALS {
+            let c = self.drift_cents * self.drift[i].next(ctrl_sr);
+            self.drift_ratio[i] = (c / 1200.0).exp2();
+            let f = self.freq * self.ratio[i] * self.drift_ratio[i];
+            let tilt = if self.ratio[i] > 0.0 { db_to_lin(self.tilt_db * self.ratio[i].log2()) } else { 0.0 };
+            let alive = if f < 0.45 * sr { 1.0 } else { 0.0 };
+            self.gain[i] = self.amp[i] * tilt * alive * norm;
+        }
+    }
+
+    #[inline]
+    fn inc(&self, i: usize, sr: f32) -> f32 { TAU * self.freq * self.ratio[i] * self.drift_ratio[i] / sr }
+
+    /// Advance one sample and return the sum of partials.
+    #[inline]
+    pub fn next(&mut self, sr: f32) -> f32 {
+        if self.ctrl == 0 {
+            self.control(sr);
+            self.ctrl = ADDITIVE_BLOCK;
+        }
+        self.ctrl -= 1;
+        let mut y = 0.0;
+        for i in 0..PARTIALS {
+            if self.gain[i] > 0.0 { y += self.gain[i] * self.phase[i].sin(); }
+            let mut p = self.phase[i] + self.inc(i, sr);
+            if p > core::f32::consts::PI { p -= TAU; }
+            self.phase[i] = p;
+        }
+        y
+    }
+
+    /// Render a block into `out` (overwrites) with the block sine fill.
+    pub fn process_block(&mut self, sr: f32, out: &mut [f32]) {
+        for chunk in out.chunks_mut(ADDITIVE_BLOCK) {
+            self.control(sr);
+            self.ctrl = 0;
+            chunk.fill(0.0);
+            let n = chunk.len();
+            for i in 0..PARTIALS {
+                let inc = self.inc(i, sr);
+                if self.gain[i] > 0.0 {
+                    fill_sine(&mut self.scratch[..n], &mut self.phase[i], inc);
+                    mix_in_place(chunk, &self.scratch[..n], self.gain[i]);
+                } else {
+                    // keep silent partials phase-coherent
+                    self.phase[i] += inc * n as f32;
+                    self.phase[i] -= (self.phase[i] / TAU).round() * TAU;
+                }
+            }
+        }
+    }
+}
+
+/// LFO waveform.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LfoShape {
+    Sine,
+    Tri,
+    Saw,
+    /// Pulse; high for the first `pulse_width` of each cycle.
+    Square,
+    /// New random level at the start of every cycle, held until the next.
+    SampleHold,
+    /// Exponential rise over the cycle (slow start, fast finish), then reset.
+    ExpRise,
+    /// Exponential decay over the cycle (fast start, slow finish), then reset.
+    ExpFall,
+}
+
+/// Curvature of the exponential LFO ramps (larger = more bent).
+const LFO_EXP_K: f32 = 5.0;
+
+/// Low-frequency oscillator for modulation.
+///
+/// Besides the basic `Osc` shapes it offers square (with pulse width),
+/// sample-and-hold and exponential ramps, bipolar/unipolar output, retrigger,
+/// and a fixed phase offset so several LFOs in a scene can be deliberately staggered.
 #[derive(Copy, Clone, Debug)]
-pub struct Lfo(Osc);
+pub struct Lfo {
+    phase: f32,  // [0,1) running phase
+    rate: f32,   // Hz
+    shape: LfoShape,
+    pw: f32,     // pulse width for Square, (0,1)
+    offset: f32, // phase offset in [0,1)
+    unipolar: bool,
+    held: f32,   // current S&H level in [-1,1]
+    rng: Rng,
+}
+
 impl Lfo {
-    #[inline] pub fn sine(rate_hz: f32) -> Self { Self(Osc::new(rate_hz, Wave::Sine)) }
-    #[inline] pub fn tri(rate_hz: f32)  -> Self { Self(Osc::new(rate_hz, Wave::Tri))  }
-    #[inline] pub fn saw(rate_hz: f32)  -> Self { Self(Osc::new(rate_hz, Wave::Saw))  }
+    #[inline]
+    pub fn new(rate_hz: f32, shape: LfoShape) -> Self {
+        let mut rng = Rng::new(0x1F0_5EED);
+        let held = rng.next_bipolar();
+        Self { phase: 0.0, rate: rate_hz.max(0.0), shape, pw: 0.5, offset: 0.0, unipolar: false, held, rng }
+    }
+    #[inline] pub fn sine(rate_hz: f32) -> Self { Self::new(rate_hz, LfoShape::Sine) }
+    #[inline] pub fn tri(rate_hz: f32)  -> Self { Self::new(rate_hz, LfoShape::Tri)  }
+    #[inline] pub fn saw(rate_hz: f32)  -> Self { Self::new(rate_hz, LfoShape::Saw)  }
+    #[inline] pub fn square(rate_hz: f32, pulse_width: f32) -> Self {
+        let mut l = Self::new(rate_hz, LfoShape::Square);
+        l.set_pulse_width(pulse_width);
+        l
+    }
+    #[inline] pub fn sample_hold(rate_hz: f32, seed: u32) -> Self {
+        let mut l = Self::new(rate_hz, LfoShape::SampleHold);
+        l.set_seed(seed);
+        l
+    }
+
+    /// Shaped value in [-1,1] at phase `p` (offset already applied).
+    #[inline]
+    fn shape_at(&self, p: f32) -> f32 {
+        match self.shape {
+            LfoShape::Sine => osc_sample(p, Wave::Sine),
+            LfoShape::Tri  => osc_sample(p, Wave::Tri),
+            LfoShape::Saw  => osc_sample(p, Wave::Saw),
+            LfoShape::Square => if p < self.pw { 1.0 } else { -1.0 },
+            LfoShape::SampleHold => self.held,
+            LfoShape::ExpRise => 2.0 * ((LFO_EXP_K * p).exp() - 1.0) / (LFO_EXP_K.exp() - 1.0) - 1.0,
+            LfoShape::ExpFall => 2.0 * ((LFO_EXP_K * (1.0 - p)).exp() - 1.0) / (LFO_EXP_K.exp() - 1.0) - 1.0,
+        }
+    }
 
-    /// Next LFO value in **[-1,1]**.
-    #[inline] pub fn next_norm(&mut self, sr: f32) -> f32 { self.0.next(sr) }
+    /// Next LFO value in **[-1,1]** (ignores the unipolar setting).
+    #[inline]
+    pub fn next_norm(&mut self, sr: f32) -> f32 {
+        let prev = wrap01(self.phase + self.offset);
+        self.phase = (self.phase + self.rate / sr) % 1.0;
+        let p = wrap01(self.phase + self.offset);
+        if p < prev && self.shape == LfoShape::SampleHold {
+            self.held = self.rng.next_bipolar();
+        }
+        self.shape_at(p)
+    }
 
     /// Next LFO value remapped to **[0,1]**.
-    #[inline] pub fn next01(&mut self, sr: f32) -> f32 { 0.5 * (self.0.next(sr) + 1.0) }
+    #[inline] pub fn next01(&mut self, sr: f32) -> f32 { 0.5 * (self.next_norm(sr) + 1.0) }
+
+    /// Next LFO value honoring the polarity: [0,1] if unipolar, else [-1,1].
+    #[inline]
+    pub fn next(&mut self, sr: f32) -> f32 {
+        if self.unipolar { self.next01(sr) } else { self.next_norm(sr) }
+    }
+
+    #[inline] pub fn set_rate(&mut self, hz: f32) { self.rate = hz.max(0.0); }
+    #[inline] pub fn set_shape(&mut self, shape: LfoShape) { self.shape = shape; }
+    #[inline] pub fn set_pulse_width(&mut self, pw: f32) { self.pw = pw.clamp(0.01, 0.99); }
+    #[inline] pub fn set_unipolar(&mut self, on: bool) { self.unipolar = on; }
+    #[inline] pub fn set_seed(&mut self, seed: u32) { self.rng.reseed(seed); self.held = self.rng.next_bipolar(); }
+
+    /// Fixed phase offset in cycles (e.g. 0.25 = quarter cycle ahead).
+    #[inline] pub fn set_phase_offset(&mut self, p: f32) { self.offset = wrap01(p); }
+
+    /// Hard-set the running phase in [0,1).
+    #[inline] pub fn set_phase01(&mut self, p: f32) { self.phase = if p >= 1.0 { p - (p as i32 as f32) } else if p < 0.0 { 0.0 } else { p }; }
 
-    #[inline] pub fn set_rate(&mut self, hz: f32) { self.0.set_freq(hz); }
-    #[inline] pub fn set_phase01(&mut self, p: f32) { self.0.set_phase01(p); }
+    /// Restart the cycle (phase offset still applies); S&H picks a fresh level.
+    #[inline]
+    pub fn retrigger(&mut self) {
+        self.phase = 0.0;
+        if self.shape == LfoShape::SampleHold { self.held = self.rng.next_bipolar(); }
+    }
 }
 
+#[inline]
+fn wrap01(p: f32) -> f32 { p - p.floor() }
+
 /// Slowly changing random modulator (great for ambient drift).
 ///
 /// Every `period_s` seconds we choose a new random target in [low, high] and
@@ -109,6 +479,14 @@ impl NoiseMod {
 
     #[inline] pub fn reset_sr(&mut self, sr: f32) { self.lp.set_sample_rate(sr); }
 
+    /// Back to the state [`NoiseMod::new`] left it in (same first target).
+    #[inline]
+    pub fn reset(&mut self) {
+        self.t = 0.0;
+        self.lp.reset();
+        self.pick_target();
+    }
+
     #[inline]
     fn pick_target(&mut self) {
         // Simple LCG-ish RNG without pulling `rand` to the audio thread
@@ -136,41 +514,1538 @@ fn pseudo_rand01(x: f32) -> f32 {
     (n.fract() + 1.0).fract()
 }
 
-/// One-pole parameter smoother: y += (x - y) * (1 - a), with `a = exp(-1/(tau*sr))`.
+/// Tiny seedable xorshift32 RNG. Deterministic per seed, no allocation, no locks.
 #[derive(Copy, Clone, Debug)]
-pub struct OnePoleSmoother {
-    a: f32, // alpha (closer to 1 → slower)
-    y: f32,
+pub struct Rng {
+    state: u32,
 }
-impl OnePoleSmoother {
-    #[inline] pub fn new_ms(t_ms: f32, sr: f32) -> Self { Self { a: ambientor_core::dsp::one_pole_coeff_ms(t_ms, sr), y: 0.0 } }
-    #[inline] pub fn reset(&mut self, y0: f32) { self.y = y0; }
-    #[inline] pub fn set_time_ms(&mut self, t_ms: f32, sr: f32) { self.a = ambientor_core::dsp::one_pole_coeff_ms(t_ms, sr); }
-    #[inline] pub fn process(&mut self, x: f32) -> f32 { self.y += (x - self.y) * (1.0 - self.a); self.y }
-    #[inline] pub fn value(&self) -> f32 { self.y }
+impl Rng {
+    /// Seed `0` is remapped (xorshift would get stuck at zero).
+    #[inline] pub fn new(seed: u32) -> Self { Self { state: if seed == 0 { 0x9E37_79B9 } else { seed } } }
+    #[inline] pub fn reseed(&mut self, seed: u32) { *self = Self::new(seed); }
+
+    #[inline]
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    /// Uniform in [0, 1).
+    #[inline] pub fn next01(&mut self) -> f32 { (self.next_u32() >> 8) as f32 * (1.0 / 16_777_216.0) }
+
+    /// Uniform in [-1, 1).
+    #[inline] pub fn next_bipolar(&mut self) -> f32 { 2.0 * self.next01() - 1.0 }
 }
 
-/// Two-input mix utility with per-input gains (mono for now).
+/// Pink noise from white `Rng` output through Paul Kellet's "economy" filter
+/// (three poles, within ~0.5 dB of −3 dB/oct above ~40 Hz at 44.1–48 kHz).
+///
+/// Scaled to RMS ~0.2, so peaks stay within about [-1, 1].
 #[derive(Copy, Clone, Debug)]
-pub struct Mix2 {
-    g1: f32,
-    g2: f32,
+pub struct PinkNoise {
+    b: [f32; 3],
+    rng: Rng,
 }
-impl Mix2 {
-    #[inline] pub fn new(g1: f32, g2: f32) -> Self { Self { g1, g2 } }
-    #[inline] pub fn set(&mut self, g1: f32, g2: f32) { self.g1 = g1; self.g2 = g2; }
-    #[inline] pub fn run(&self, a: f32, b: f32) -> f32 { a * self.g1 + b * self.g2 }
+impl PinkNoise {
+    #[inline] pub fn new(seed: u32) -> Self { Self { b: [0.0; 3], rng: Rng::new(seed) } }
+
+    #[inline]
+    pub fn next_sample(&mut self) -> f32 {
+        let w = self.rng.next_bipolar();
+        self.b[0] = 0.99765 * self.b[0] + w * 0.099_046;
+        self.b[1] = 0.96300 * self.b[1] + w * 0.296_516_4;
+        self.b[2] = 0.57000 * self.b[2] + w * 1.052_691_3;
+        0.11 * (self.b[0] + self.b[1] + self.b[2] + w * 0.1848)
+    }
 }
 
-/// Constant-power panner helper.
+/// Slope range of a [`TiltNoise`] in dB/oct (blue .. brown).
+pub const TILT_NOISE_SLOPE: (f32, f32) = (-6.0, 3.0);
+
+/// Pole-zero sections in a [`TiltNoise`], two octaves apart from 20 Hz.
+const TILT_STAGES: usize = 5;
+/// Log-spaced points a [`TiltNoise`] samples its response at to set its level.
+const TILT_LEVEL_POINTS: usize = 24;
+
+/// Noise with a continuously variable spectral slope: +3 dB/oct (blue) through
+/// 0 (white) and −3 (pink) to −6 dB/oct (brown), so a scene can morph noise
+/// color with one parameter.
+///
+/// White `Rng` noise runs through a cascade of one-pole/one-zero sections
+/// spaced two octaves apart; each shelves its two octaves by the slope (the
+/// zero and pole trade places for rising slopes). The slope holds from
+/// ~20 Hz to ~10 kHz and flattens beyond. The output is scaled to the RMS of
+/// [`PinkNoise`] (~0.2) at every slope, so morphing color doesn't jump level.
 #[derive(Copy, Clone, Debug)]
-pub struct PanLaw;
-impl PanLaw {
-    /// Return (left, right) gains given `pan` in [-1..1], where -1 = hard left, +1 = hard right.
+pub struct TiltNoise {
+    rng: Rng,
+    slope: f32,
+    sr: f32,
+    zero: [f32; TILT_STAGES],
+    pole: [f32; TILT_STAGES],
+    x1: [f32; TILT_STAGES],
+    y1: [f32; TILT_STAGES],
+    gain: f32,
+}
+impl TiltNoise {
+    /// Pink (−3 dB/oct) to start with.
+    pub fn new(seed: u32, sr: f32) -> Self {
+        let mut n = Self {
+            rng: Rng::new(seed),
+            slope: -3.0,
+            sr: sr.max(1.0),
+            zero: [0.0; TILT_STAGES],
+            pole: [0.0; TILT_STAGES],
+            x1: [0.0; TILT_STAGES],
+            y1: [0.0; TILT_STAGES],
+            gain: 1.0,
+        };
+        n.update();
+        n
+    }
+
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        self.sr = sr.max(1.0);
+        self.update();
+    }
+
+    /// Spectral slope in dB/oct, clamped to [`TILT_NOISE_SLOPE`]. Recomputes
+    /// the sections (a few `exp`/`cos`), so call it at control rate.
+    pub fn set_slope(&mut self, db_per_oct: f32) {
+        let s = db_per_oct.clamp(TILT_NOISE_SLOPE.0, TILT_NOISE_SLOPE.1);
+        if s == self.slope { return; }
+        self.slope = s;
+        self.update();
+    }
+    #[inline] pub fn slope(&self) -> f32 { self.slope }
+
+    fn update(&mut self) {
+        // each section spans 2 octaves, so it steps the level by 2 × slope dB
+        let ratio = db_to_lin(2.0 * self.slope.abs());
+        let nyq = 0.49 * self.sr;
+        for i in 0..TILT_STAGES {
+            let lo = (20.0 * 4f32.powi(i as i32)).min(nyq);
+            let hi = (lo * ratio).min(nyq);
+            // falling: pole first, zero above it; rising: the other way round
+            let (p_hz, z_hz) = if self.slope < 0.0 { (lo, hi) } else { (hi, lo) };
+            self.pole[i] = (-TAU * p_hz / self.sr).exp();
+            self.zero[i] = (-TAU * z_hz / self.sr).exp();
+        }
+        // mean power gain over 0..nyquist (trapezoids between log-spaced points)
+        let (mut f0, mut m0) = (0.0, self.power_gain(0.0));
+        let mut area = 0.0;
+        for k in 0..TILT_LEVEL_POINTS {
+            let f = 10.0 * (0.5 * self.sr / 10.0).powf(k as f32 / (TILT_LEVEL_POINTS - 1) as f32);
+            let m = self.power_gain(f);
+            area += 0.5 * (m0 + m) * (f - f0);
+            (f0, m0) = (f, m);
+        }
+        // white bipolar noise has power 1/3; aim for RMS 0.2
+        self.gain = 0.2 / (area / (0.5 * self.sr) / 3.0).sqrt();
+    }
+
+    /// |H|² of the cascade at `hz`.
+    fn power_gain(&self, hz: f32) -> f32 {
+        // |1 - a·e^-jw|² = 1 - 2a·cos w + a²
+        let c = (TAU * hz / self.sr).cos();
+        (0..TILT_STAGES).fold(1.0, |g, i| {
+            let (z, p) = (self.zero[i], self.pole[i]);
+            g * (1.0 - 2.0 * z * c + z * z) / (1.0 - 2.0 * p * c + p * p)
+        })
+    }
+
     #[inline]
-    pub fn gains(pan: f32) -> (f32, f32) {
-        // constant power using sine/cosine taper
-        let p = (pan.clamp(-1.0, 1.0) + 1.0) * 0.25 * core::f32::consts::PI; // map to [0, π/2]
-        (p.cos(), p.sin())
+    pub fn next_sample(&mut self) -> f32 {
+        let mut x = self.rng.next_bipolar();
+        for i in 0..TILT_STAGES {
+            let y = x - self.zero[i] * self.x1[i] + self.pole[i] * self.y1[i];
+            self.x1[i] = x;
+            self.y1[i] = y;
+            x = y;
+        }
+        self.gain * x
+    }
+}
+
+/// Frequency range of a [`SubBed`] (Hz).
+pub const SUB_BED_HZ: (f32, f32) = (25.0, 45.0);
+
+/// Subsonic swell for installations with subwoofers: a 25–45 Hz sine whose
+/// level rises and falls with a slow sine LFO (`depth` 0 = steady, 1 = swells
+/// down to silence).
+///
+/// Silent until [`SubBed::set_open`] opens its gate; gate and level glide over
+/// ~0.5 s so neither clicks, and a closed, faded-out bed costs no `sin` calls.
+#[derive(Copy, Clone, Debug)]
+pub struct SubBed {
+    osc: Osc,
+    lfo: Lfo,
+    hz: f32,
+    level: f32,
+    depth: f32,
+    open: bool,
+    amp: OnePoleSmoother,
+}
+impl SubBed {
+    const GLIDE_MS: f32 = 500.0;
+
+    /// Closed, at `hz` with level 0; swelling at 0.05 Hz, depth 0.6.
+    pub fn new(hz: f32, sr: f32) -> Self {
+        let mut s = Self {
+            osc: Osc::new(SUB_BED_HZ.0, Wave::Sine),
+            lfo: Lfo::sine(0.05),
+            hz: SUB_BED_HZ.0,
+            level: 0.0,
+            depth: 0.6,
+            open: false,
+            amp: OnePoleSmoother::new_ms(Self::GLIDE_MS, sr),
+        };
+        s.lfo.set_phase01(0.75); // start at the bottom of a swell
+        s.set_freq(hz);
+        s
+    }
+
+    #[inline] pub fn set_sample_rate(&mut self, sr: f32) { self.amp.set_time_ms(Self::GLIDE_MS, sr); }
+
+    /// Sine frequency, clamped to [`SUB_BED_HZ`].
+    #[inline]
+    pub fn set_freq(&mut self, hz: f32) {
+        self.hz = hz.clamp(SUB_BED_HZ.0, SUB_BED_HZ.1);
+        self.osc.set_freq(self.hz);
+    }
+    #[inline] pub fn freq(&self) -> f32 { self.hz }
+
+    /// Peak level 0..1 (0 = off).
+    #[inline] pub fn set_level(&mut self, level: f32) { self.level = level.clamp(0.0, 1.0); }
+    #[inline] pub fn level(&self) -> f32 { self.level }
+
+    /// Swell LFO rate (0.005..1 Hz) and depth (0..1).
+    #[inline]
+    pub fn set_swell(&mut self, rate_hz: f32, depth: f32) {
+        self.lfo.set_rate(rate_hz.clamp(0.005, 1.0));
+        self.depth = depth.clamp(0.0, 1.0);
+    }
+
+    /// Open (fade in) or close (fade out) the gate.
+    #[inline] pub fn set_open(&mut self, open: bool) { self.open = open; }
+    #[inline] pub fn is_open(&self) -> bool { self.open }
+
+    /// `true` while the bed is (or is still fading) audible.
+    #[inline] pub fn is_active(&self) -> bool { (self.open && self.level > 0.0) || self.amp.value() > 1e-6 }
+
+    #[inline]
+    pub fn next(&mut self, sr: f32) -> f32 {
+        if !self.is_active() { return 0.0; }
+        let swell = 1.0 - self.depth * (1.0 - self.lfo.next01(sr));
+        let g = self.amp.process(if self.open { self.level * swell } else { 0.0 });
+        g * self.osc.next(sr)
+    }
+}
+
+/// Shortest and longest click of a [`Crackle`] (ms, decay time constant).
+const CRACKLE_CLICK_MS: (f32, f32) = (0.05, 0.8);
+
+/// Vinyl-style crackle for lo-fi beds: clicks at random moments (`density`
+/// per second on average), each a short decaying impulse of random polarity
+/// and level (mostly faint ticks, now and then a louder pop), panned at random
+/// within `spread`. `brightness` sets a low-pass from 800 Hz (dull dust) to
+/// 16 kHz (sharp ticks).
+///
+/// Driven by a seedable [`Rng`], so the same seed crackles the same way in
+/// every render.
+#[derive(Copy, Clone, Debug)]
+pub struct Crackle {
+    rng: Rng,
+    sr: f32,
+    density: f32,  // clicks per second
+    brightness: f32,
+    spread: f32,
+    env: f32,      // signed level of the current click
+    decay: f32,    // per-sample multiplier of `env`
+    gl: f32,
+    gr: f32,
+    lp: [OnePoleLP; 2],
+}
+impl Crackle {
+    /// 4 clicks/s, brightness 0.5, spread 0.5.
+    pub fn new(seed: u32, sr: f32) -> Self {
+        let sr = sr.max(1.0);
+        let mut c = Self {
+            rng: Rng::new(seed),
+            sr,
+            density: 4.0,
+            brightness: 0.5,
+            spread: 0.5,
+            env: 0.0,
+            decay: 0.0,
+            gl: 0.0,
+            gr: 0.0,
+            lp: [OnePoleLP::new(1000.0, sr); 2],
+        };
+        c.set_brightness(c.brightness);
+        c
+    }
+
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        self.sr = sr.max(1.0);
+        for f in &mut self.lp { f.set_sample_rate(self.sr); }
+        self.set_brightness(self.brightness);
+    }
+
+    /// Average clicks per second (0..200).
+    #[inline] pub fn set_density(&mut self, per_s: f32) { self.density = per_s.clamp(0.0, 200.0); }
+    #[inline] pub fn density(&self) -> f32 { self.density }
+
+    /// 0 (dull, 800 Hz) .. 1 (sharp, 16 kHz), log-spaced.
+    pub fn set_brightness(&mut self, b: f32) {
+        self.brightness = b.clamp(0.0, 1.0);
+        let hz = 800.0 * 20f32.powf(self.brightness);
+        for f in &mut self.lp { f.set_cutoff_hz(hz); }
+    }
+    #[inline] pub fn brightness(&self) -> f32 { self.brightness }
+
+    /// How far clicks scatter from the center: 0 = all centered, 1 = anywhere
+    /// from hard left to hard right.
+    #[inline] pub fn set_spread(&mut self, s: f32) { self.spread = s.clamp(0.0, 1.0); }
+    #[inline] pub fn spread(&self) -> f32 { self.spread }
+
+    pub fn set_seed(&mut self, seed: u32) { self.rng.reseed(seed); }
+
+    fn click(&mut self) {
+        // u⁴ skews toward quiet ticks with the odd loud pop
+        let u = self.rng.next01();
+        let level = 0.15 + 0.85 * u * u * u * u;
+        self.env = if self.rng.next01() < 0.5 { -level } else { level };
+        let ms = CRACKLE_CLICK_MS.0 + (CRACKLE_CLICK_MS.1 - CRACKLE_CLICK_MS.0) * self.rng.next01();
+        self.decay = ambientor_core::dsp::one_pole_coeff_ms(ms, self.sr);
+        (self.gl, self.gr) = PanLaw::gains(self.spread * self.rng.next_bipolar());
+    }
+
+    /// Next stereo frame.
+    #[inline]
+    pub fn next_stereo(&mut self) -> (f32, f32) {
+        if self.rng.next01() * self.sr < self.density { self.click(); }
+        let x = self.env;
+        self.env *= self.decay;
+        (self.lp[0].process(x * self.gl), self.lp[1].process(x * self.gr))
+    }
+}
+
+/// Decay range of a [`ResonatorBank`] resonator (seconds to fall 60 dB).
+pub const RESONATOR_DECAY_S: (f32, f32) = (0.01, 60.0);
+
+/// `N` parallel modal resonators (two-pole band-passes), each with its own
+/// frequency, decay and gain — the core of "blowing through pipes" textures.
+///
+/// Feed it noise through [`ResonatorBank::process`] for sustained, breathy
+/// tones, or [`ResonatorBank::strike`] it now and then for bell/pipe hits that
+/// ring out over the decay time. Each resonator has unity gain at its
+/// frequency, so broadband noise comes out far quieter (the longer the decay,
+/// the narrower the band it keeps): expect to add 20–40 dB of gain. Resonators
+/// tuned at or above ~0.45 × the sample rate are muted.
+///
+/// Coefficients are kept as parallel arrays, like [`UnisonOsc`]; changing a
+/// frequency or decay costs an `exp` and a `cos`, so do it at control rate.
+#[derive(Copy, Clone, Debug)]
+pub struct ResonatorBank<const N: usize> {
+    sr: f32,
+    hz: [f32; N],
+    decay_s: [f32; N],
+    gain: [f32; N],
+    a1: [f32; N],  // 2r·cos w
+    a2: [f32; N],  // -r²
+    b0: [f32; N],  // input scale for unity peak gain (0 = muted)
+    sin_w: [f32; N],
+    y1: [f32; N],
+    y2: [f32; N],
+    kick: f32,     // strike amplitude waiting for the next sample
+}
+
+impl<const N: usize> ResonatorBank<N> {
+    /// Tuned as a harmonic series on 110 Hz with 2 s decays
+    /// (see [`ResonatorBank::tune_harmonic`]).
+    pub fn new(sr: f32) -> Self {
+        let mut b = Self {
+            sr: sr.max(1.0),
+            hz: [110.0; N],
+            decay_s: [2.0; N],
+            gain: [1.0; N],
+            a1: [0.0; N],
+            a2: [0.0; N],
+            b0: [0.0; N],
+            sin_w: [0.0; N],
+            y1: [0.0; N],
+            y2: [0.0; N],
+            kick: 0.0,
+        };
+        b.tune_harmonic(110.0, 2.0);
+        b
+    }
+
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        self.sr = sr.max(1.0);
+        for i in 0..N { self.update(i); }
+    }
+
+    fn update(&mut self, i: usize) {
+        let w = TAU * self.hz[i] / self.sr;
+        // r^(decay·sr) = 10^(-60/20)
+        let r = (-6.907_755 / (self.decay_s[i] * self.sr)).exp();
+        self.a1[i] = 2.0 * r * w.cos();
+        self.a2[i] = -r * r;
+        self.sin_w[i] = w.sin();
+        // peak gain of 1/(1 - a1 z⁻¹ - a2 z⁻²) at w ≈ 1/((1-r)·2 sin w)
+        self.b0[i] = if self.hz[i] < 0.45 * self.sr { (1.0 - r) * 2.0 * self.sin_w[i] } else { 0.0 };
+    }
+
+    /// Set resonator `i` (out of range is ignored): frequency in Hz, decay in
+    /// seconds (clamped to [`RESONATOR_DECAY_S`]) and output gain (0..4).
+    pub fn set_resonator(&mut self, i: usize, hz: f32, decay_s: f32, gain: f32) {
+        if i >= N { return; }
+        self.hz[i] = hz.max(1.0);
+        self.decay_s[i] = decay_s.clamp(RESONATOR_DECAY_S.0, RESONATOR_DECAY_S.1);
+        self.gain[i] = gain.clamp(0.0, 4.0);
+        self.update(i);
+    }
+
+    #[inline] pub fn freq(&self, i: usize) -> Option<f32> { self.hz.get(i).copied() }
+    #[inline] pub fn decay_s(&self, i: usize) -> Option<f32> { self.decay_s.get(i).copied() }
+    #[inline] pub fn gain(&self, i: usize) -> Option<f32> { self.gain.get(i).copied() }
+
+    /// Pipe-like tuning: resonator `k` at `(k + 1) × fundamental_hz`, gain
+    /// `1 / (k + 1)`, all with `decay_s`.
+    pub fn tune_harmonic(&mut self, fundamental_hz: f32, decay_s: f32) {
+        for k in 0..N {
+            let n = (k + 1) as f32;
+            self.set_resonator(k, n * fundamental_hz, decay_s, 1.0 / n);
+        }
+    }
+
+    /// Ring every resonator at `amount` × its gain from the next sample on, as
+    /// if hit (adds to whatever is ringing already).
+    #[inline] pub fn strike(&mut self, amount: f32) { self.kick += amount; }
+
+    /// Silence all resonators; tuning is kept.
+    pub fn clear(&mut self) {
+        self.y1 = [0.0; N];
+        self.y2 = [0.0; N];
+        self.kick = 0.0;
+    }
+
+    /// Excite the bank with `x` and return the summed resonators.
+    #[inline]
+    pub fn process(&mut self, x: f32) -> f32 {
+        let kick = core::mem::take(&mut self.kick);
+        let mut out = 0.0;
+        for i in 0..N {
+            // an impulse of k·sin w rings at amplitude k: h[n] = r^n sin((n+1)w) / sin w
+            let k = if self.b0[i] > 0.0 { kick * self.sin_w[i] } else { 0.0 };
+            let y = self.b0[i] * x + k + self.a1[i] * self.y1[i] + self.a2[i] * self.y2[i];
+            self.y2[i] = self.y1[i];
+            self.y1[i] = y;
+            out += self.gain[i] * y;
+        }
+        out
+    }
+}
+
+/// Sample-and-hold: picks a new random value in [low, high] `rate_hz` times per
+/// second and holds it (hard steps).
+///
+/// With [`SampleHold::set_scale`] the range is read as **semitones** and each
+/// step snaps to the nearest scale degree — handy for stepped melodic motion.
+#[derive(Copy, Clone, Debug)]
+pub struct SampleHold {
+    low: f32,
+    high: f32,
+    rate: f32,
+    phase: f32,                    // [0,1) clock phase
+    value: f32,
+    scale: Option<Scale<'static>>,
+    rng: Rng,
+}
+
+impl SampleHold {
+    #[inline]
+    pub fn new(rate_hz: f32, low: f32, high: f32, seed: u32) -> Self {
+        let mut s = Self { low, high, rate: rate_hz.max(0.0), phase: 0.0, value: low, scale: None, rng: Rng::new(seed) };
+        s.step();
+        s
+    }
+
+    #[inline] pub fn set_rate(&mut self, hz: f32) { self.rate = hz.max(0.0); }
+    #[inline] pub fn set_range(&mut self, low: f32, high: f32) { self.low = low; self.high = high; }
+    #[inline] pub fn set_seed(&mut self, seed: u32) { self.rng.reseed(seed); }
+
+    /// Quantize steps to `scale` (values read as MIDI-style semitones), or `None` for free values.
+    #[inline] pub fn set_scale(&mut self, scale: Option<Scale<'static>>) { self.scale = scale; }
+
+    /// Force a new step now and restart the clock.
+    #[inline]
+    pub fn trigger(&mut self) {
+        self.phase = 0.0;
+        self.step();
+    }
+
+    #[inline]
+    fn step(&mut self) {
+        let v = self.low + (self.high - self.low) * self.rng.next01();
+        self.value = match self.scale {
+            Some(sc) => quantize_to_scale(v, &sc),
+            None => v,
+        };
+    }
+
+    /// Advance one sample; returns the held value.
+    #[inline]
+    pub fn next(&mut self, sr: f32) -> f32 {
+        self.phase += self.rate / sr;
+        if self.phase >= 1.0 {
+            self.phase -= self.phase.floor();
+            self.step();
+        }
+        self.value
+    }
+
+    #[inline] pub fn value(&self) -> f32 { self.value }
+}
+
+/// Bounded Brownian drift: the value takes random steps each sample (scaled so
+/// the spread grows like `speed * sqrt(t)`) and reflects off [low, high].
+///
+/// Smoother and less "goal-directed" than `NoiseMod`; good for pitch/cutoff wander.
+#[derive(Copy, Clone, Debug)]
+pub struct RandomWalk {
+    low: f32,
+    high: f32,
+    speed: f32, // units per sqrt(second)
+    value: f32,
+    rng: Rng,
+}
+
+impl RandomWalk {
+    /// Starts in the middle of the range.
+    #[inline]
+    pub fn new(low: f32, high: f32, speed: f32, seed: u32) -> Self {
+        Self { low, high, speed: speed.max(0.0), value: 0.5 * (low + high), rng: Rng::new(seed) }
+    }
+
+    #[inline] pub fn set_speed(&mut self, speed: f32) { self.speed = speed.max(0.0); }
+    #[inline] pub fn set_seed(&mut self, seed: u32) { self.rng.reseed(seed); }
+    #[inline]
+    pub fn set_range(&mut self, low: f32, high: f32) {
+        self.low = low;
+        self.high = high;
+        self.value = self.value.clamp(low.min(high), high.max(low));
+    }
+
+    /// Advance one sample; returns a value in [low, high].
+    #[inline]
+    pub fn next(&mut self, sr: f32) -> f32 {
+        // triangular noise (sum of two uniforms on ±1) has variance 2/3 → ×√1.5 for unit variance
+        let n = (self.rng.next_bipolar() + self.rng.next_bipolar()) * 1.224_744_9;
+        let mut v = self.value + n * self.speed / sr.sqrt();
+        // reflect at the bounds
+        if v > self.high { v = 2.0 * self.high - v; }
+        if v < self.low  { v = 2.0 * self.low - v; }
+        self.value = v.clamp(self.low.min(self.high), self.high.max(self.low));
+        self.value
+    }
+
+    #[inline] pub fn value(&self) -> f32 { self.value }
+}
+
+/// Integer hash → [-1, 1] (lowbias32). Used as the lattice for `SmoothNoise`.
+#[inline]
+fn hash_bipolar(mut x: u32) -> f32 {
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb_352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846c_a68b);
+    x ^= x >> 16;
+    (x >> 8) as f32 * (2.0 / 16_777_216.0) - 1.0
+}
+
+/// Maximum number of octaves summed by [`SmoothNoise`].
+pub const SMOOTH_NOISE_MAX_OCTAVES: u32 = 8;
+
+/// 1D smooth value noise (fractal sum of octaves), output roughly in **[-1, 1]**.
+///
+/// Random lattice values `rate_hz` apart are joined with a quintic fade, so the
+/// curve has continuous slope — no staircase corners like slewed targets. Extra
+/// octaves (each twice as fast, `persistence` times as loud) add finer wander.
+#[derive(Copy, Clone, Debug)]
+pub struct SmoothNoise {
+    rate: f32,
+    cell: u32,   // integer lattice position (octave 0)
+    frac: f32,   // [0,1) position within the cell
+    octaves: u32,
+    persistence: f32,
+    seed: u32,
+}
+
+impl SmoothNoise {
+    #[inline]
+    pub fn new(rate_hz: f32, octaves: u32, seed: u32) -> Self {
+        Self {
+            rate: rate_hz.max(0.0), cell: 0, frac: 0.0,
+            octaves: octaves.clamp(1, SMOOTH_NOISE_MAX_OCTAVES), persistence: 0.5,
+            seed: seed.wrapping_mul(0x9E37_79B9),
+        }
+    }
+
+    #[inline] pub fn set_rate(&mut self, hz: f32) { self.rate = hz.max(0.0); }
+    #[inline] pub fn set_octaves(&mut self, n: u32) { self.octaves = n.clamp(1, SMOOTH_NOISE_MAX_OCTAVES); }
+    /// Relative amplitude of each successive octave (0..1, default 0.5).
+    #[inline] pub fn set_persistence(&mut self, p: f32) { self.persistence = p.clamp(0.0, 1.0); }
+    /// Reseed and restart from the beginning of the curve.
+    #[inline]
+    pub fn set_seed(&mut self, seed: u32) {
+        self.seed = seed.wrapping_mul(0x9E37_79B9);
+        self.cell = 0;
+        self.frac = 0.0;
+    }
+
+    /// Value of one octave at lattice `cell + frac` (both already scaled).
+    #[inline]
+    fn octave(&self, cell: u32, frac: f32, oct: u32) -> f32 {
+        let key = self.seed ^ oct.wrapping_mul(0x6C8E_9CF5);
+        let a = hash_bipolar(cell ^ key);
+        let b = hash_bipolar(cell.wrapping_add(1) ^ key);
+        // quintic fade 6t^5 - 15t^4 + 10t^3
+        let t = frac * frac * frac * (frac * (frac * 6.0 - 15.0) + 10.0);
+        a + (b - a) * t
+    }
+
+    /// Advance one sample and return the noise value.
+    #[inline]
+    pub fn next(&mut self, sr: f32) -> f32 {
+        self.frac += self.rate / sr;
+        if self.frac >= 1.0 {
+            let whole = self.frac.floor();
+            self.cell = self.cell.wrapping_add(whole as u32);
+            self.frac -= whole;
+        }
+
+        let mut sum = 0.0;
+        let mut amp = 1.0;
+        let mut norm = 0.0;
+        for k in 0..self.octaves {
+            let scale = (1u32 << k) as f32;
+            let f = self.frac * scale;
+            let fi = f.floor();
+            let cell = (self.cell << k).wrapping_add(fi as u32);
+            sum += amp * self.octave(cell, f - fi, k);
+            norm += amp;
+            amp *= self.persistence;
+        }
+        if norm > 0.0 { sum / norm } else { 0.0 }
+    }
+
+    /// Like [`SmoothNoise::next`] but remapped to [low, high].
+    #[inline]
+    pub fn next_range(&mut self, low: f32, high: f32, sr: f32) -> f32 {
+        low + (high - low) * 0.5 * (self.next(sr) + 1.0)
+    }
+}
+
+/// Attractor behind a [`ChaosMod`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChaosMap {
+    /// Lorenz system: smooth swirls that flip unpredictably between two lobes.
+    Lorenz,
+    /// Logistic map `x ← r·x·(1 − x)`: a new value each cycle, joined smoothly.
+    Logistic,
+}
+
+/// Lorenz ρ at chaos 0 (a stable periodic orbit) and chaos 1 (the classic attractor).
+pub const CHAOS_LORENZ_RHO: (f32, f32) = (160.0, 28.0);
+/// Logistic-map r at chaos 0 (a period-4 cycle) and chaos 1 (fully chaotic).
+pub const CHAOS_LOGISTIC_R: (f32, f32) = (3.5, 4.0);
+
+/// Long-run mean of the logistic orbit at chaos 0, 1/32, …, 1 (measured over
+/// 200k steps). The orbit sits well above the middle of its range, more so in
+/// some periodic windows; the output is centered on this.
+const LOGISTIC_MEAN: [f32; 33] = [
+    0.6464, 0.6469, 0.6473, 0.6477, 0.6476, 0.6477, 0.6455, 0.6474, 0.6508, 0.6535, 0.6556,
+    0.6625, 0.6726, 0.6665, 0.6603, 0.6598, 0.6507, 0.6474, 0.6445, 0.6444, 0.6349, 0.5908,
+    0.5316, 0.5758, 0.6006, 0.5989, 0.5472, 0.5911, 0.5897, 0.5759, 0.5440, 0.5518, 0.5002,
+];
+/// Where the logistic orbit's farthest value from its mean lands: the quintic
+/// fade dwells on every value, so full-scale extremes would sit on the rails.
+const LOGISTIC_PEAK: f64 = 0.9;
+
+/// Lorenz RK4 step (system time units).
+const LORENZ_DT: f64 = 0.01;
+/// Lorenz steps per lobe revolution at ρ = 28; higher ρ circles faster, ~√(28/ρ).
+const LORENZ_STEPS_PER_CYCLE: f32 = 75.0;
+
+/// Chaotic modulation source, output in [-1, 1]: drift that never repeats,
+/// yet has the shape and memory that plain noise lacks.
+///
+/// `rate_hz` is roughly cycles per second (revolutions of the Lorenz attractor,
+/// or new logistic values). `chaos` (0..1) morphs from a regular, repeating
+/// pattern to full chaos (Lorenz ρ 160 → 28, logistic r 3.5 → 4); settings in
+/// between are mostly chaotic, with a few periodic windows.
+///
+/// The system runs at a fixed step size, so its shape doesn't depend on rate
+/// or sample rate; the output is interpolated between steps (linearly for
+/// Lorenz, with a quintic fade for the logistic map). The logistic output is
+/// centered on the orbit's long-run mean and peaks near ±0.9. The same seed
+/// gives the same curve in every render.
+#[derive(Copy, Clone, Debug)]
+pub struct ChaosMod {
+    map: ChaosMap,
+    rate: f32,
+    chaos: f32,
+    k: f64,      // Lorenz ρ or logistic r
+    center: f64, // logistic: orbit mean, mapped to 0
+    scale: f64,  // logistic: orbit → output gain
+    steps_per_cycle: f32,
+    frac: f32,   // [0,1) position between `prev` and `cur`
+    prev: f32,
+    cur: f32,
+    s: [f64; 3], // Lorenz (x, y, z); logistic x in s[0]
+    seed: u32,
+}
+
+impl ChaosMod {
+    pub fn new(map: ChaosMap, rate_hz: f32, chaos: f32, seed: u32) -> Self {
+        let mut c = Self {
+            map, rate: rate_hz.max(0.0), chaos: 0.0, k: 0.0, center: 0.0, scale: 1.0, steps_per_cycle: 1.0,
+            frac: 0.0, prev: 0.0, cur: 0.0, s: [0.0; 3], seed,
+        };
+        c.set_chaos(chaos);
+        c.restart();
+        c
+    }
+
+    /// Switch attractor (restarts it from the seed).
+    pub fn set_map(&mut self, map: ChaosMap) {
+        if map == self.map { return; }
+        self.map = map;
+        self.set_chaos(self.chaos);
+        self.restart();
+    }
+
+    #[inline] pub fn set_rate(&mut self, hz: f32) { self.rate = hz.max(0.0); }
+
+    /// 0 = regular, 1 = fully chaotic. Changing it moves the running system
+    /// onto the new attractor without a jump.
+    pub fn set_chaos(&mut self, chaos: f32) {
+        self.chaos = chaos.clamp(0.0, 1.0);
+        let (lo, hi) = match self.map {
+            ChaosMap::Lorenz => CHAOS_LORENZ_RHO,
+            ChaosMap::Logistic => CHAOS_LOGISTIC_R,
+        };
+        let k = lo + (hi - lo) * self.chaos;
+        self.k = f64::from(k);
+        if self.map == ChaosMap::Logistic {
+            // the orbit stays within [r·m·(1 − m), m], m = r/4
+            let pos = self.chaos * (LOGISTIC_MEAN.len() - 1) as f32;
+            let i = (pos as usize).min(LOGISTIC_MEAN.len() - 2);
+            let mean = LOGISTIC_MEAN[i] + (LOGISTIC_MEAN[i + 1] - LOGISTIC_MEAN[i]) * (pos - i as f32);
+            let (hi, mean) = (0.25 * self.k, f64::from(mean));
+            let lo = self.k * hi * (1.0 - hi);
+            self.center = mean;
+            self.scale = LOGISTIC_PEAK / (hi - mean).max(mean - lo);
+        }
+        self.steps_per_cycle = match self.map {
+            ChaosMap::Lorenz => LORENZ_STEPS_PER_CYCLE * (28.0 / k).sqrt(),
+            ChaosMap::Logistic => 1.0,
+        };
+    }
+
+    #[inline] pub fn chaos(&self) -> f32 { self.chaos }
+
+    /// Reseed and restart.
+    pub fn set_seed(&mut self, seed: u32) {
+        self.seed = seed;
+        self.restart();
+    }
+
+    /// Start from a seed-dependent point and run onto the attractor.
+    fn restart(&mut self) {
+        let h = f64::from(hash_bipolar(self.seed.wrapping_mul(0x9E37_79B9)));
+        let warmup = match self.map {
+            ChaosMap::Lorenz => {
+                self.s = [1.0 + 5.0 * h, 1.0, 20.0];
+                2000
+            }
+            ChaosMap::Logistic => {
+                self.s = [0.5 + 0.4 * h, 0.0, 0.0];
+                64
+            }
+        };
+        for _ in 0..warmup { self.step(); }
+        self.step();
+        self.frac = 0.0;
+    }
+
+    /// Advance the system one step.
+    fn step(&mut self) {
+        match self.map {
+            ChaosMap::Lorenz => {
+                let rho = self.k;
+                let f = |[x, y, z]: [f64; 3]| [10.0 * (y - x), x * (rho - z) - y, x * y - 8.0 / 3.0 * z];
+                let at = |s: [f64; 3], d: [f64; 3], h: f64| [s[0] + h * d[0], s[1] + h * d[1], s[2] + h * d[2]];
+                let s = self.s;
+                let k1 = f(s);
+                let k2 = f(at(s, k1, 0.5 * LORENZ_DT));
+                let k3 = f(at(s, k2, 0.5 * LORENZ_DT));
+                let k4 = f(at(s, k3, LORENZ_DT));
+                for i in 0..3 { self.s[i] += LORENZ_DT / 6.0 * (k1[i] + 2.0 * (k2[i] + k3[i]) + k4[i]); }
+            }
+            ChaosMap::Logistic => {
+                let x = self.k * self.s[0] * (1.0 - self.s[0]);
+                // rounding can land on the fixed point 0 (or leave 0..1); nudge back
+                self.s[0] = if x > 1e-9 && x < 1.0 { x } else { 0.3 };
+            }
+        }
+        self.prev = self.cur;
+        self.cur = match self.map {
+            // the attractor spans about ±2.5× its fixed points' |x|
+            ChaosMap::Lorenz => (self.s[0] / (2.5 * (8.0 / 3.0 * (self.k - 1.0)).sqrt())).clamp(-1.0, 1.0) as f32,
+            ChaosMap::Logistic => ((self.s[0] - self.center) * self.scale).clamp(-1.0, 1.0) as f32,
+        };
+    }
+
+    /// Advance one sample and return the value in [-1, 1].
+    #[inline]
+    pub fn next(&mut self, sr: f32) -> f32 {
+        self.frac += self.rate * self.steps_per_cycle / sr;
+        if self.frac >= 1.0 {
+            let whole = self.frac.floor();
+            // very high rates skip ahead at most a few steps per sample
+            for _ in 0..(whole as u32).min(16) { self.step(); }
+            self.frac -= whole;
+        }
+        let t = match self.map {
+            ChaosMap::Lorenz => self.frac,
+            ChaosMap::Logistic => {
+                let f = self.frac;
+                f * f * f * (f * (f * 6.0 - 15.0) + 10.0)
+            }
+        };
+        self.prev + (self.cur - self.prev) * t
+    }
+
+    /// Like [`ChaosMod::next`] but remapped to [low, high].
+    #[inline]
+    pub fn next_range(&mut self, low: f32, high: f32, sr: f32) -> f32 {
+        low + (high - low) * 0.5 * (self.next(sr) + 1.0)
+    }
+}
+
+/// One-pole parameter smoother: y += (x - y) * (1 - a), with `a = exp(-1/(tau*sr))`.
+#[derive(Copy, Clone, Debug)]
+pub struct OnePoleSmoother {
+    a: f32, // alpha (closer to 1 → slower)
+    y: f32,
+}
+impl OnePoleSmoother {
+    #[inline] pub fn new_ms(t_ms: f32, sr: f32) -> Self { Self { a: ambientor_core::dsp::one_pole_coeff_ms(t_ms, sr), y: 0.0 } }
+    #[inline] pub fn reset(&mut self, y0: f32) { self.y = y0; }
+    #[inline] pub fn set_time_ms(&mut self, t_ms: f32, sr: f32) { self.a = ambientor_core::dsp::one_pole_coeff_ms(t_ms, sr); }
+    #[inline] pub fn process(&mut self, x: f32) -> f32 { self.y += (x - self.y) * (1.0 - self.a); self.y }
+    #[inline] pub fn value(&self) -> f32 { self.y }
+}
+
+/// Samples per control-rate tick. Scenes run slow modulators and recompute
+/// pitch/filter coefficients once per block, and interpolate per sample in
+/// between, instead of redoing `exp`/`tan` math every sample.
+pub const CONTROL_BLOCK: u32 = 32;
+
+/// Counts samples down to the next control-rate tick.
+#[derive(Copy, Clone, Debug)]
+pub struct ControlClock {
+    block: u32,
+    left: u32,
+}
+impl Default for ControlClock {
+    fn default() -> Self { Self::new(CONTROL_BLOCK) }
+}
+impl ControlClock {
+    #[inline] pub fn new(block: u32) -> Self { Self { block: block.max(1), left: 0 } }
+    /// Call once per sample; `true` on the first sample of every block
+    /// (including the first call after [`ControlClock::reset`]).
+    #[inline]
+    pub fn tick(&mut self) -> bool {
+        let due = self.left == 0;
+        if due { self.left = self.block; }
+        self.left -= 1;
+        due
+    }
+    /// Make the next [`ControlClock::tick`] a control tick.
+    #[inline] pub fn reset(&mut self) { self.left = 0; }
+    #[inline] pub fn block(&self) -> u32 { self.block }
+    /// Control ticks per second at audio rate `sr`.
+    #[inline] pub fn rate(&self, sr: f32) -> f32 { sr / self.block as f32 }
+}
+
+/// Per-sample linear interpolation between control-rate values: each
+/// [`ControlRamp::set_target`] glides from the previous target over one block.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ControlRamp {
+    value: f32,
+    target: f32,
+    step: f32,
+    left: u32,
+}
+impl ControlRamp {
+    #[inline] pub fn new(x: f32) -> Self { Self { value: x, target: x, step: 0.0, left: 0 } }
+    /// Head for `target`, arriving after `samples` calls to [`ControlRamp::next_sample`].
+    #[inline]
+    pub fn set_target(&mut self, target: f32, samples: u32) {
+        self.value = self.target; // land exactly on the last target first
+        self.target = target;
+        self.left = samples.max(1);
+        self.step = (target - self.value) / self.left as f32;
+    }
+    /// Jump to `x` without gliding.
+    #[inline] pub fn snap(&mut self, x: f32) { *self = Self::new(x); }
+    #[inline]
+    pub fn next_sample(&mut self) -> f32 {
+        if self.left > 0 {
+            self.left -= 1;
+            self.value = if self.left == 0 { self.target } else { self.value + self.step };
+        }
+        self.value
+    }
+    #[inline] pub fn value(&self) -> f32 { self.value }
+    #[inline] pub fn target(&self) -> f32 { self.target }
+}
+
+/// Peak envelope follower: rises with the `attack` time constant, falls with
+/// `release` (both ms, ~63% time). Tracks `|x|`.
+#[derive(Copy, Clone, Debug)]
+pub struct EnvFollower {
+    att_ms: f32,
+    rel_ms: f32,
+    att: f32,
+    rel: f32,
+    y: f32,
+}
+impl EnvFollower {
+    pub fn new(attack_ms: f32, release_ms: f32, sr: f32) -> Self {
+        let mut s = Self { att_ms: 0.0, rel_ms: 0.0, att: 0.0, rel: 0.0, y: 0.0 };
+        s.set_times(attack_ms, release_ms, sr);
+        s
+    }
+
+    #[inline]
+    pub fn set_times(&mut self, attack_ms: f32, release_ms: f32, sr: f32) {
+        self.att_ms = attack_ms.max(0.1);
+        self.rel_ms = release_ms.max(0.1);
+        self.set_sample_rate(sr);
+    }
+
+    #[inline]
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        self.att = ambientor_core::dsp::one_pole_coeff_ms(self.att_ms, sr);
+        self.rel = ambientor_core::dsp::one_pole_coeff_ms(self.rel_ms, sr);
+    }
+
+    #[inline]
+    pub fn process(&mut self, x: f32) -> f32 {
+        let x = x.abs();
+        let a = if x > self.y { self.att } else { self.rel };
+        self.y = x + (self.y - x) * a;
+        self.y
+    }
+
+    #[inline] pub fn reset(&mut self) { self.y = 0.0; }
+    #[inline] pub fn value(&self) -> f32 { self.y }
+}
+
+/// Silence detector for power saving.
+///
+/// Reports silence once every sample has stayed below `threshold_db` (peak)
+/// for `hold_s` seconds; any louder sample clears it immediately. Counting is
+/// per frame, so a host can check it after each block.
+#[derive(Copy, Clone, Debug)]
+pub struct SilenceDetector {
+    threshold_db: f32,
+    threshold: f32, // linear
+    hold_s: f32,
+    hold: u32,      // frames
+    quiet: u32,     // frames below threshold so far (saturates at `hold`)
+}
+impl SilenceDetector {
+    pub fn new(threshold_db: f32, hold_s: f32, sr: f32) -> Self {
+        let mut s = Self { threshold_db: 0.0, threshold: 0.0, hold_s: 0.0, hold: 0, quiet: 0 };
+        s.set_threshold_db(threshold_db);
+        s.set_hold_s(hold_s, sr);
+        s
+    }
+
+    /// Peak level (dBFS) under which output counts as silent, -140..0.
+    #[inline]
+    pub fn set_threshold_db(&mut self, db: f32) {
+        self.threshold_db = db.clamp(-140.0, 0.0);
+        self.threshold = db_to_lin(self.threshold_db);
+    }
+    #[inline] pub fn threshold_db(&self) -> f32 { self.threshold_db }
+
+    /// How long output must stay below the threshold (seconds, at least one frame).
+    #[inline]
+    pub fn set_hold_s(&mut self, s: f32, sr: f32) {
+        self.hold_s = s.max(0.0);
+        self.set_sample_rate(sr);
+    }
+    #[inline] pub fn hold_s(&self) -> f32 { self.hold_s }
+
+    #[inline]
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        self.hold = ((self.hold_s * sr.max(1.0)) as u32).max(1);
+        self.quiet = self.quiet.min(self.hold);
+    }
+
+    /// Feed one stereo frame; returns `true` while silent.
+    #[inline]
+    pub fn process(&mut self, l: f32, r: f32) -> bool {
+        // `!(x < t)` so NaN counts as loud
+        if !(l.abs() < self.threshold && r.abs() < self.threshold) {
+            self.quiet = 0;
+        } else if self.quiet < self.hold {
+            self.quiet += 1;
+        }
+        self.is_silent()
+    }
+
+    #[inline] pub fn is_silent(&self) -> bool { self.quiet >= self.hold }
+    #[inline] pub fn reset(&mut self) { self.quiet = 0; }
+}
+
+/// Length of one clip-analysis window (seconds).
+const CLIP_WINDOW_S: f32 = 0.1;
+/// Share of clipped frames in a window that counts as sustained clipping.
+const CLIP_SUSTAINED: f32 = 0.01;
+/// Clip-free time before a lowered trim starts to recover (seconds).
+const CLIP_RELEASE_S: f32 = 3.0;
+/// Trim steps per window: down while clipping, back up once released.
+const TRIM_DOWN_DB: f32 = -0.5;
+const TRIM_UP_DB: f32 = 0.1;
+/// Lowest auto-trim.
+const TRIM_FLOOR_DB: f32 = -12.0;
+
+/// Clip statistics and auto-trim for a scene's final ±1 clamp.
+///
+/// Feed it the clamped dry frame: any channel at full scale counts as a
+/// clipped frame. With auto-trim on, a 100 ms window in which more
+/// than 1% of frames clipped lowers the trim by 0.5 dB (down to -12 dB); after
+/// 3 s without sustained clipping it climbs back 1 dB/s to unity. Apply
+/// [`ClipGuard::trim`] before the clamp whenever `process` reports a change.
+#[derive(Copy, Clone, Debug)]
+pub struct ClipGuard {
+    auto_trim: bool,
+    trim: f32,
+    window: u32,  // frames per window
+    release: u32, // clip-free frames before recovering
+    pos: u32,     // frames into the current window
+    hits: u32,    // clipped frames in the current window
+    calm: u32,    // frames since the last sustained clipping (saturates at `release`)
+    clipped: u64,
+    total: u64,
+}
+
+impl ClipGuard {
+    pub fn new(sr: f32) -> Self {
+        let mut g = Self { auto_trim: false, trim: 1.0, window: 1, release: 1, pos: 0, hits: 0, calm: 0, clipped: 0, total: 0 };
+        g.set_sample_rate(sr);
+        g
+    }
+
+    #[inline]
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        let sr = sr.max(1.0);
+        self.window = ((CLIP_WINDOW_S * sr) as u32).max(1);
+        self.release = (CLIP_RELEASE_S * sr) as u32;
+        self.pos = self.pos.min(self.window - 1);
+        self.calm = self.calm.min(self.release);
+    }
+
+    /// Turn auto-trim on or off; off snaps the trim back to unity.
+    /// Returns `true` if the trim changed.
+    pub fn set_auto_trim(&mut self, on: bool) -> bool {
+        self.auto_trim = on;
+        let old = self.trim;
+        if !on { self.trim = 1.0; }
+        self.trim != old
+    }
+    #[inline] pub fn auto_trim(&self) -> bool { self.auto_trim }
+
+    /// Feed one clamped stereo frame; returns `true` when the trim changed.
+    #[inline]
+    pub fn process(&mut self, l: f32, r: f32) -> bool {
+        self.total += 1;
+        if !(l.abs() < 1.0 && r.abs() < 1.0) {
+            self.clipped += 1;
+            self.hits += 1;
+        }
+        self.pos += 1;
+        if self.pos < self.window { return false; }
+        self.end_window()
+    }
+
+    #[cold]
+    fn end_window(&mut self) -> bool {
+        let sustained = self.hits as f32 > CLIP_SUSTAINED * self.window as f32;
+        self.pos = 0;
+        self.hits = 0;
+        if sustained {
+            self.calm = 0;
+        } else {
+            self.calm = (self.calm + self.window).min(self.release);
+        }
+        if !self.auto_trim { return false; }
+        let old = self.trim;
+        if sustained {
+            self.trim = (self.trim * db_to_lin(TRIM_DOWN_DB)).max(db_to_lin(TRIM_FLOOR_DB));
+        } else if self.calm >= self.release {
+            self.trim = (self.trim * db_to_lin(TRIM_UP_DB)).min(1.0);
+        }
+        self.trim != old
+    }
+
+    /// Gain to apply before the clamp (1 unless auto-trim is holding it down).
+    #[inline] pub fn trim(&self) -> f32 { self.trim }
+    #[inline] pub fn trim_db(&self) -> f32 { 20.0 * self.trim.log10() }
+
+    /// Frames that hit the clamp since creation or the last [`ClipGuard::reset_stats`].
+    #[inline] pub fn clipped_frames(&self) -> u64 { self.clipped }
+    /// Frames seen over the same span.
+    #[inline] pub fn total_frames(&self) -> u64 { self.total }
+
+    pub fn reset_stats(&mut self) {
+        self.clipped = 0;
+        self.total = 0;
+    }
+}
+
+/// Portamento between pitches.
+///
+/// Slews in the **log-frequency** domain at a fixed rate (semitones per second),
+/// so every interval takes time proportional to its size and the swell sounds
+/// even. Feed it target frequencies (Hz) or MIDI notes and read Hz back.
+#[derive(Copy, Clone, Debug)]
+pub struct Glide {
+    cur: f32,    // current pitch in semitones (MIDI-like, 69 = 440 Hz)
+    target: f32,
+    rate: f32,   // semitones per second; 0 = instant
+}
+
+impl Glide {
+    /// Start at `hz` with a glide rate in semitones per second.
+    #[inline]
+    pub fn new(hz: f32, semitones_per_s: f32) -> Self {
+        let p = Self::hz_to_semi(hz);
+        Self { cur: p, target: p, rate: semitones_per_s.max(0.0) }
+    }
+
+    #[inline]
+    fn hz_to_semi(hz: f32) -> f32 { 69.0 + 12.0 * (hz.max(1e-3) / 440.0).log2() }
+
+    #[inline]
+    fn semi_to_hz(semi: f32) -> f32 { 440.0 * ((semi - 69.0) / 12.0).exp2() }
+
+    #[inline] pub fn set_rate_semitones(&mut self, st_per_s: f32) { self.rate = st_per_s.max(0.0); }
+    #[inline] pub fn set_rate_cents(&mut self, cents_per_s: f32) { self.rate = (cents_per_s / 100.0).max(0.0); }
+
+    /// Glide towards `hz`.
+    #[inline] pub fn set_target_hz(&mut self, hz: f32) { self.target = Self::hz_to_semi(hz); }
+    /// Glide towards a (fractional) MIDI note (12-TET, A4 = 440).
+    #[inline] pub fn set_target_note(&mut self, note: f32) { self.target = note; }
+
+    /// Jump to `hz` immediately.
+    #[inline]
+    pub fn snap_hz(&mut self, hz: f32) {
+        self.cur = Self::hz_to_semi(hz);
+        self.target = self.cur;
+    }
+
+    /// `true` while still moving.
+    #[inline] pub fn is_gliding(&self) -> bool { self.cur != self.target }
+
+    /// Advance one sample; returns the current frequency in Hz.
+    #[inline]
+    pub fn next(&mut self, sr: f32) -> f32 {
+        let d = self.target - self.cur;
+        if d != 0.0 {
+            let step = if self.rate > 0.0 { self.rate / sr } else { f32::INFINITY };
+            self.cur = if d.abs() <= step { self.target } else { self.cur + step * d.signum() };
+        }
+        Self::semi_to_hz(self.cur)
+    }
+}
+
+/// Two-input mix utility with per-input gains (mono for now).
+#[derive(Copy, Clone, Debug)]
+pub struct Mix2 {
+    g1: f32,
+    g2: f32,
+}
+impl Mix2 {
+    #[inline] pub fn new(g1: f32, g2: f32) -> Self { Self { g1, g2 } }
+    #[inline] pub fn set(&mut self, g1: f32, g2: f32) { self.g1 = g1; self.g2 = g2; }
+    #[inline] pub fn run(&self, a: f32, b: f32) -> f32 { a * self.g1 + b * self.g2 }
+}
+
+/// Constant-power panner helper.
+#[derive(Copy, Clone, Debug)]
+pub struct PanLaw;
+impl PanLaw {
+    /// Return (left, right) gains given `pan` in [-1..1], where -1 = hard left, +1 = hard right.
+    #[inline]
+    pub fn gains(pan: f32) -> (f32, f32) {
+        // constant power using sine/cosine taper
+        let p = (pan.clamp(-1.0, 1.0) + 1.0) * 0.25 * core::f32::consts::PI; // map to [0, π/2]
+        (p.cos(), p.sin())
+    }
+}
+
+/// Position source driving an [`AutoPan`].
+#[derive(Copy, Clone, Debug)]
+pub enum PanSource {
+    /// Periodic sweep.
+    Lfo(Lfo),
+    /// Wandering position (targets in [-1, 1], slewed).
+    Noise(NoiseMod),
+}
+
+/// Slowly moving constant-power panner: `pan = center + depth * source`.
+#[derive(Copy, Clone, Debug)]
+pub struct AutoPan {
+    src: PanSource,
+    center: f32,
+    depth: f32,
+}
+impl AutoPan {
+    /// Sine-LFO sweep at `rate_hz` with `depth` in [0, 1].
+    #[inline]
+    pub fn lfo(rate_hz: f32, depth: f32) -> Self {
+        Self { src: PanSource::Lfo(Lfo::sine(rate_hz)), center: 0.0, depth: depth.clamp(0.0, 1.0) }
+    }
+
+    /// Random drift: a new position every `period_s`, slewed by a very low cutoff.
+    #[inline]
+    pub fn noise(period_s: f32, depth: f32, sr: f32) -> Self {
+        let nm = NoiseMod::new(-1.0, 1.0, period_s, 0.1, sr);
+        Self { src: PanSource::Noise(nm), center: 0.0, depth: depth.clamp(0.0, 1.0) }
+    }
+
+    #[inline] pub fn set_center(&mut self, c: f32) { self.center = c.clamp(-1.0, 1.0); }
+    #[inline] pub fn set_depth(&mut self, d: f32)  { self.depth = d.clamp(0.0, 1.0); }
+
+    #[inline]
+    pub fn reset_sr(&mut self, sr: f32) {
+        if let PanSource::Noise(nm) = &mut self.src { nm.reset_sr(sr); }
+    }
+
+    /// Restart the sweep or drift from where a new panner starts.
+    #[inline]
+    pub fn reset(&mut self) {
+        match &mut self.src {
+            PanSource::Lfo(l) => l.set_phase01(0.0),
+            PanSource::Noise(n) => n.reset(),
+        }
+    }
+
+    /// Current pan position in [-1, 1], advanced by one sample.
+    #[inline]
+    pub fn next_pos(&mut self, sr: f32) -> f32 {
+        let m = match &mut self.src {
+            PanSource::Lfo(l) => l.next_norm(sr),
+            PanSource::Noise(n) => n.next(sr),
+        };
+        (self.center + self.depth * m).clamp(-1.0, 1.0)
+    }
+
+    /// Pan mono `x` and return `(left, right)`.
+    #[inline]
+    pub fn process(&mut self, x: f32, sr: f32) -> (f32, f32) {
+        let (gl, gr) = PanLaw::gains(self.next_pos(sr));
+        (x * gl, x * gr)
+    }
+}
+
+/// Largest interaural delay of a [`LayerPan`] (ms, at hard left/right).
+pub const LAYER_PAN_ITD_MS: f32 = 0.66;
+/// Far-side low-pass of a [`LayerPan`] at hard left/right (Hz).
+const LAYER_PAN_SHADOW_HZ: f32 = 5000.0;
+const LAYER_PAN_BUF: usize = 128; // > 1 ms at 96 kHz
+
+/// Mono → stereo placement that sounds like a position rather than a level
+/// difference: constant-power gains ([`PanLaw`]), plus the far ear hearing
+/// the source up to [`LAYER_PAN_ITD_MS`] later and a little duller (a gentle
+/// low-pass closing to ~5 kHz at hard pan, standing in for the head's shadow).
+///
+/// Centered, both sides get the unfiltered, undelayed input at -3 dB, exactly
+/// like [`PanLaw`]. Position changes glide over ~30 ms, so the delay never
+/// jumps (a moving source only bends pitch a hair).
+#[derive(Copy, Clone, Debug)]
+pub struct LayerPan {
+    sr: f32,
+    pan: f32,
+    pan_sm: OnePoleSmoother,
+    buf: [f32; LAYER_PAN_BUF],
+    w: usize,
+    shadow: OnePoleLP,
+    shadow_amt: f32, // |pan| the shadow cutoff was set for
+}
+impl LayerPan {
+    const GLIDE_MS: f32 = 30.0;
+
+    pub fn new(pan: f32, sr: f32) -> Self {
+        let sr = sr.max(1.0);
+        let mut p = Self {
+            sr,
+            pan: 0.0,
+            pan_sm: OnePoleSmoother::new_ms(Self::GLIDE_MS, sr),
+            buf: [0.0; LAYER_PAN_BUF],
+            w: 0,
+            shadow: OnePoleLP::new(20_000.0, sr),
+            shadow_amt: 0.0,
+        };
+        p.set_pan(pan);
+        p.pan_sm.reset(p.pan);
+        p
+    }
+
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        self.sr = sr.max(1.0);
+        self.pan_sm.set_time_ms(Self::GLIDE_MS, self.sr);
+        self.shadow.set_sample_rate(self.sr);
+    }
+
+    /// -1 (hard left) .. 0 (center) .. +1 (hard right).
+    #[inline] pub fn set_pan(&mut self, pan: f32) { self.pan = pan.clamp(-1.0, 1.0); }
+    #[inline] pub fn pan(&self) -> f32 { self.pan }
+
+    pub fn reset(&mut self) {
+        self.buf.fill(0.0);
+        self.shadow.reset();
+        self.pan_sm.reset(self.pan);
+    }
+
+    /// Place mono `x`; returns `(left, right)`.
+    #[inline]
+    pub fn process(&mut self, x: f32) -> (f32, f32) {
+        let pan = self.pan_sm.process(self.pan);
+        let (gl, gr) = PanLaw::gains(pan);
+        self.buf[self.w] = x;
+        let amt = pan.abs();
+        if (amt - self.shadow_amt).abs() > 1e-4 {
+            self.shadow_amt = amt;
+            self.shadow.set_cutoff_hz(20_000.0 * (LAYER_PAN_SHADOW_HZ / 20_000.0).powf(amt));
+        }
+        // far side: fractional read `d` samples back, then the shadow (kept
+        // running while centered, so leaving the center doesn't click)
+        let d = (amt * LAYER_PAN_ITD_MS * 0.001 * self.sr).min((LAYER_PAN_BUF - 2) as f32);
+        let i = d as usize;
+        let frac = d - i as f32;
+        let a = self.buf[(self.w + LAYER_PAN_BUF - i) % LAYER_PAN_BUF];
+        let b = self.buf[(self.w + LAYER_PAN_BUF - i - 1) % LAYER_PAN_BUF];
+        let shadowed = self.shadow.process(a + (b - a) * frac);
+        let far = if amt < 1e-4 { x } else { shadowed };
+        self.w = (self.w + 1) % LAYER_PAN_BUF;
+        if pan >= 0.0 { (gl * far, gr * x) } else { (gl * x, gr * far) }
+    }
+}
+
+/// Ring / amplitude modulator: the input times a carrier `dc + depth · osc`,
+/// blended with the dry input by `mix`.
+///
+/// With `dc` 0 and `depth` 1 (the default) it is a classic ring modulator:
+/// input and carrier both vanish, leaving their sum and difference tones,
+/// which turns pads metallic and bell-like. With `dc = 1 - depth` the carrier
+/// stays at or below unity and it becomes tremolo (`depth` 0.5 swings the
+/// level all the way down to silence); a 0 Hz carrier is a fixed gain.
+/// Depth, offset and mix glide over ~20 ms, so control-rate changes don't zip.
+#[derive(Copy, Clone, Debug)]
+pub struct RingMod {
+    osc: Osc,
+    freq: f32,
+    depth: f32,
+    dc: f32,
+    mix: f32,
+    depth_sm: OnePoleSmoother,
+    dc_sm: OnePoleSmoother,
+    mix_sm: OnePoleSmoother,
+}
+impl RingMod {
+    const GLIDE_MS: f32 = 20.0;
+
+    /// Sine carrier at `freq_hz`: depth 1, no offset, fully wet.
+    pub fn new(freq_hz: f32, sr: f32) -> Self {
+        let sm = OnePoleSmoother::new_ms(Self::GLIDE_MS, sr);
+        let mut r = Self {
+            osc: Osc::new(0.0, Wave::Sine),
+            freq: 0.0,
+            depth: 1.0,
+            dc: 0.0,
+            mix: 1.0,
+            depth_sm: sm,
+            dc_sm: sm,
+            mix_sm: sm,
+        };
+        r.set_freq(freq_hz);
+        r.snap();
+        r
+    }
+
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        for s in [&mut self.depth_sm, &mut self.dc_sm, &mut self.mix_sm] { s.set_time_ms(Self::GLIDE_MS, sr); }
+    }
+
+    /// Carrier frequency in Hz (0 = a constant carrier).
+    #[inline]
+    pub fn set_freq(&mut self, hz: f32) {
+        self.freq = hz.max(0.0);
+        self.osc.set_freq(self.freq);
+    }
+    #[inline] pub fn freq(&self) -> f32 { self.freq }
+    #[inline] pub fn set_wave(&mut self, w: Wave) { self.osc.set_wave(w); }
+
+    /// Carrier amplitude 0..1.
+    #[inline] pub fn set_depth(&mut self, d: f32) { self.depth = d.clamp(0.0, 1.0); }
+    #[inline] pub fn depth(&self) -> f32 { self.depth }
+
+    /// Carrier DC offset 0..1 (0 = ring modulation, `1 - depth` = tremolo).
+    #[inline] pub fn set_dc(&mut self, dc: f32) { self.dc = dc.clamp(0.0, 1.0); }
+    #[inline] pub fn dc(&self) -> f32 { self.dc }
+
+    /// Dry/wet balance 0..1.
+    #[inline] pub fn set_mix(&mut self, m: f32) { self.mix = m.clamp(0.0, 1.0); }
+    #[inline] pub fn mix(&self) -> f32 { self.mix }
+
+    /// Jump straight to the current depth, offset and mix (no glide).
+    pub fn snap(&mut self) {
+        self.depth_sm.reset(self.depth);
+        self.dc_sm.reset(self.dc);
+        self.mix_sm.reset(self.mix);
+    }
+
+    #[inline]
+    pub fn process(&mut self, x: f32, sr: f32) -> f32 {
+        let carrier = self.dc_sm.process(self.dc) + self.depth_sm.process(self.depth) * self.osc.next(sr);
+        x + self.mix_sm.process(self.mix) * (x * carrier - x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SR: f32 = 48_000.0;
+
+    #[test]
+    fn auto_pan_reset_restarts_the_motion() {
+        for fresh in [AutoPan::lfo(0.5, 0.8), AutoPan::noise(0.5, 0.8, SR)] {
+            let mut p = fresh;
+            let first: Vec<f32> = (0..SR as usize).map(|_| p.next_pos(SR)).collect();
+            for _ in 0..12_345 { p.next_pos(SR); }
+            p.reset();
+            let again: Vec<f32> = (0..SR as usize).map(|_| p.next_pos(SR)).collect();
+            assert_eq!(first, again);
+        }
+    }
+
+    #[test]
+    fn logistic_chaos_is_centered_and_off_the_rails() {
+        for i in 0..=20 {
+            let chaos = i as f32 / 20.0;
+            let mut c = ChaosMod::new(ChaosMap::Logistic, 1000.0, chaos, 7);
+            let n = 10 * SR as usize; // 10k logistic values
+            let (mut sum, mut railed, mut peak) = (0.0f64, 0, 0.0f32);
+            for _ in 0..n {
+                let y = c.next(SR);
+                sum += f64::from(y);
+                if y.abs() > 0.95 { railed += 1; }
+                peak = peak.max(y.abs());
+            }
+            let mean = sum / n as f64;
+            assert!(mean.abs() < 0.1, "chaos {chaos}: mean {mean:.3}");
+            assert!(railed < n / 100, "chaos {chaos}: {railed} of {n} samples past ±0.95");
+            assert!(peak > 0.6, "chaos {chaos}: peak {peak}");
+        }
+    }
+
+    #[test]
+    fn tilt_noise_holds_its_slope_and_level() {
+        for slope in [-6.0, -4.5, -3.0, 0.0, 1.5, 3.0] {
+            let mut n = TiltNoise::new(11, SR);
+            n.set_slope(slope);
+            // 80 Hz .. 5.12 kHz, six octaves inside the tilted range
+            let tilt = 10.0 * (n.power_gain(5120.0) / n.power_gain(80.0)).log10() / 6.0;
+            assert!((tilt - slope).abs() < 0.5, "slope {slope}: {tilt:.2} dB/oct");
+
+            let len = 4 * SR as usize;
+            let rms = ((0..len).map(|_| n.next_sample().powi(2)).sum::<f32>() / len as f32).sqrt();
+            assert!((0.18..0.22).contains(&rms), "slope {slope}: rms {rms}");
+        }
+    }
+
+    #[test]
+    fn crackle_clicks_at_its_density_within_level() {
+        for density in [1.0, 20.0, 150.0] {
+            let mut c = Crackle::new(5, SR);
+            c.set_density(density);
+            c.set_brightness(1.0);
+            c.set_spread(0.0);
+            let secs = 60.0;
+            let (mut clicks, mut peak) = (0, 0.0f32);
+            for _ in 0..(secs * SR) as usize {
+                let before = c.env.abs();
+                let (l, r) = c.next_stereo();
+                if c.env.abs() > before { clicks += 1; }
+                assert_eq!(l, r, "spread 0 keeps clicks centered");
+                peak = peak.max(l.abs());
+            }
+            // Poisson count: within ~4 standard deviations of the mean
+            let mean = density * secs;
+            assert!((clicks as f32 - mean).abs() < 4.0 * mean.sqrt() + 2.0, "density {density}: {clicks} clicks");
+            assert!(peak <= 1.0 && peak > 0.1, "density {density}: peak {peak}");
+        }
+
+        let mut c = Crackle::new(5, SR);
+        c.set_density(0.0);
+        assert!((0..SR as usize).all(|_| c.next_stereo() == (0.0, 0.0)));
+    }
+
+    #[test]
+    fn resonators_have_unity_peak_and_their_decay() {
+        for hz in [55.0, 440.0, 5000.0] {
+            let mut b = ResonatorBank::<1>::new(SR);
+            b.set_resonator(0, hz, 0.5, 1.0);
+            // a sine at the resonator's frequency comes out at its own level
+            let w = TAU * hz / SR;
+            let y: Vec<f32> = (0..3 * SR as usize).map(|i| b.process((w * i as f32).sin())).collect();
+            let peak = y[2 * SR as usize..].iter().fold(0.0f32, |m, v| m.max(v.abs()));
+            assert!((0.9..1.1).contains(&peak), "{hz} Hz: peak {peak}");
+
+            // a strike rings at its amount and falls 60 dB over the decay
+            b.clear();
+            b.strike(0.5);
+            let y: Vec<f32> = (0..SR as usize).map(|_| b.process(0.0)).collect();
+            let peak_at = |t: f32| {
+                let i = (t * SR) as usize;
+                y[i..i + 2_000].iter().fold(0.0f32, |m, v| m.max(v.abs()))
+            };
+            let (start, later) = (peak_at(0.0), peak_at(0.5));
+            assert!((0.45..0.55).contains(&start), "{hz} Hz: strike {start}");
+            let db = 20.0 * (later / start).log10();
+            assert!((-63.0..-57.0).contains(&db), "{hz} Hz: {db:.1} dB after the decay time");
+        }
+
+        // tuned near nyquist: muted, strikes included
+        let mut b = ResonatorBank::<1>::new(SR);
+        b.set_resonator(0, 0.46 * SR, 1.0, 1.0);
+        b.strike(1.0);
+        assert!((0..1000).all(|i| b.process(if i % 2 == 0 { 1.0 } else { -1.0 }) == 0.0));
     }
 }
@@ -0,0 +1,453 @@
+//! Host-addressable parameters and engine-side ramps.
+//!
+//! Every knob a host can turn has a stable numeric [`ParamId`] (shared with
+//! the C API), so it can be set, read back, or ramped without one function per
+//! parameter. [`ParamRamps`] runs scheduled ramps inside the engine at control
+//! rate: a host that only updates at its frame rate (e.g. 60 Hz) hands over
+//! the target and the time, and the engine moves there smoothly instead of in
+//! audible steps.
+
+use crate::nodes::CONTROL_BLOCK;
+use core::fmt;
+
+/// Parameter ids. The numbers are part of the C API: append, never renumber.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ParamId {
+    /// Scene intensity 0..1.
+    Intensity = 0,
+    /// Scene output gain 0..1.
+    SceneGain = 1,
+    /// Master stereo width, percent.
+    Width = 2,
+    /// Master tilt tone -1..1.
+    Tone = 3,
+    ReverbReturn = 4,
+    DelayReturn = 5,
+    /// Listener distance, meters.
+    Distance = 6,
+    /// Occlusion 0..1.
+    Occlusion = 7,
+    /// Slow drone: base cutoff, Hz.
+    CutBase = 8,
+    /// Slow drone: cutoff modulation span, Hz.
+    CutSpan = 9,
+    /// Slow drone: saturation drive.
+    Drive = 10,
+    /// Slow drone: unison detune, cents.
+    Detune = 11,
+    /// Risset: partial stretch 0..0.15.
+    Inharmonicity = 12,
+    /// Risset: speed of slow motion.
+    MotionRate = 13,
+    /// Thunderstorm: rumbles per minute.
+    RumbleRate = 14,
+    /// Thunderstorm: duck depth, dB.
+    DuckDepth = 15,
+    /// Cave drips: drips per minute.
+    DripDensity = 16,
+    /// Cave drips: wetness 0..1.
+    Wetness = 17,
+    /// Binaural: carrier, Hz.
+    CarrierHz = 18,
+    /// Binaural: beat, Hz.
+    BeatHz = 19,
+    /// Binaural: noise bed level 0..1.
+    NoiseBed = 20,
+    /// Tape loop: degrade 0..1.
+    Degrade = 21,
+    /// Tape loop: wow depth 0..1.
+    Wow = 22,
+    /// Every scene: time warp on all LFO and drift rates, 0.1..10 (1 = as designed).
+    TimeWarp = 23,
+}
+
+/// How a ramp moves between its endpoints.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RampCurve {
+    /// Straight line in the parameter's own units.
+    Linear,
+    /// Straight line in dB (gains) or octaves (frequencies): equal ratios per
+    /// unit of time, which sounds even. Ramps to or from 0 run down to -80 dB
+    /// and land on 0 exactly.
+    Log,
+}
+
+/// Floor for [`RampCurve::Log`] ramps through 0 (-80 dB).
+const LOG_FLOOR: f32 = 1e-4;
+
+/// Number of parameter ids.
+pub const PARAM_COUNT: usize = 24;
+
+impl ParamId {
+    pub const ALL: [ParamId; PARAM_COUNT] = [
+        ParamId::Intensity, ParamId::SceneGain, ParamId::Width, ParamId::Tone,
+        ParamId::ReverbReturn, ParamId::DelayReturn, ParamId::Distance, ParamId::Occlusion,
+        ParamId::CutBase, ParamId::CutSpan, ParamId::Drive, ParamId::Detune,
+        ParamId::Inharmonicity, ParamId::MotionRate, ParamId::RumbleRate, ParamId::DuckDepth,
+        ParamId::DripDensity, ParamId::Wetness, ParamId::CarrierHz, ParamId::BeatHz,
+        ParamId::NoiseBed, ParamId::Degrade, ParamId::Wow, ParamId::TimeWarp,
+    ];
+
+    pub fn from_u32(id: u32) -> Option<Self> { Self::ALL.get(id as usize).copied() }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ParamId::Intensity => "intensity",
+            ParamId::SceneGain => "scene_gain",
+            ParamId::Width => "width",
+            ParamId::Tone => "tone",
+            ParamId::ReverbReturn => "reverb_return",
+            ParamId::DelayReturn => "delay_return",
+            ParamId::Distance => "distance",
+            ParamId::Occlusion => "occlusion",
+            ParamId::CutBase => "cut_base",
+            ParamId::CutSpan => "cut_span",
+            ParamId::Drive => "drive",
+            ParamId::Detune => "detune",
+            ParamId::Inharmonicity => "inharmonicity",
+            ParamId::MotionRate => "motion_rate",
+            ParamId::RumbleRate => "rumble_rate",
+            ParamId::DuckDepth => "duck_depth",
+            ParamId::DripDensity => "drip_density",
+            ParamId::Wetness => "wetness",
+            ParamId::CarrierHz => "carrier_hz",
+            ParamId::BeatHz => "beat_hz",
+            ParamId::NoiseBed => "noise_bed",
+            ParamId::Degrade => "degrade",
+            ParamId::Wow => "wow",
+            ParamId::TimeWarp => "time_warp",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> { Self::ALL.into_iter().find(|p| p.name() == s) }
+
+    /// What the parameter does, with its unit, for help listings.
+    pub fn description(self) -> &'static str {
+        match self {
+            ParamId::Intensity => "scene intensity: density, brightness and level (0.5 = as designed)",
+            ParamId::SceneGain => "scene output gain",
+            ParamId::Width => "master stereo width, %",
+            ParamId::Tone => "master tilt tone, -1 dark .. +1 bright",
+            ParamId::ReverbReturn => "shared reverb return level",
+            ParamId::DelayReturn => "shared delay return level",
+            ParamId::Distance => "listener distance, m",
+            ParamId::Occlusion => "occlusion (muffling) 0..1",
+            ParamId::CutBase => "base filter cutoff, Hz",
+            ParamId::CutSpan => "cutoff modulation span, Hz",
+            ParamId::Drive => "saturation drive",
+            ParamId::Detune => "detune depth, cents",
+            ParamId::Inharmonicity => "partial stretch",
+            ParamId::MotionRate => "speed of the slow motion",
+            ParamId::RumbleRate => "thunder rumbles per minute",
+            ParamId::DuckDepth => "rain ducking under thunder, dB",
+            ParamId::DripDensity => "drips per minute",
+            ParamId::Wetness => "echo and reverb amount 0..1",
+            ParamId::CarrierHz => "carrier tone, Hz",
+            ParamId::BeatHz => "beat frequency, Hz",
+            ParamId::NoiseBed => "noise bed level 0..1",
+            ParamId::Degrade => "tape wear speed 0..1",
+            ParamId::Wow => "tape wow depth 0..1",
+            ParamId::TimeWarp => "time warp on every LFO and drift rate (1 = as designed)",
+        }
+    }
+
+    /// `true` for parameters that belong to the master bus rather than the scene.
+    pub fn is_master(self) -> bool {
+        matches!(
+            self,
+            ParamId::Width | ParamId::Tone | ParamId::ReverbReturn | ParamId::DelayReturn | ParamId::Distance | ParamId::Occlusion
+        )
+    }
+
+    /// Curve [`ParamRamps`] uses: log for levels and frequencies, linear otherwise.
+    pub fn curve(self) -> RampCurve {
+        match self {
+            ParamId::SceneGain | ParamId::ReverbReturn | ParamId::DelayReturn | ParamId::NoiseBed
+            | ParamId::CutBase | ParamId::CutSpan | ParamId::CarrierHz | ParamId::BeatHz
+            | ParamId::TimeWarp => RampCurve::Log,
+            _ => RampCurve::Linear,
+        }
+    }
+
+    /// Musically safe `(min, max)`: values in here suit the scene's character.
+    /// Narrower than what the setters accept; used for random variations.
+    pub fn range(self) -> (f32, f32) {
+        match self {
+            ParamId::Intensity => (0.2, 0.8),
+            ParamId::SceneGain => (0.0, 1.0),
+            ParamId::Width => (50.0, 150.0),
+            ParamId::Tone => (-0.5, 0.5),
+            ParamId::ReverbReturn | ParamId::DelayReturn => (0.5, 1.5),
+            ParamId::Distance => (0.0, 20.0),
+            ParamId::Occlusion => (0.0, 0.5),
+            ParamId::CutBase => (300.0, 2500.0),
+            ParamId::CutSpan => (100.0, 1500.0),
+            ParamId::Drive => (0.5, 2.0),
+            ParamId::Detune => (0.0, 10.0),
+            ParamId::Inharmonicity => (0.0, 0.08),
+            ParamId::MotionRate => (0.3, 3.0),
+            ParamId::RumbleRate => (0.5, 4.0),
+            ParamId::DuckDepth => (0.0, 9.0),
+            ParamId::DripDensity => (4.0, 40.0),
+            ParamId::Wetness => (0.3, 0.9),
+            ParamId::CarrierHz => (100.0, 400.0),
+            ParamId::BeatHz => (4.0, 14.0),
+            ParamId::NoiseBed => (0.0, 0.4),
+            ParamId::Degrade => (0.1, 0.8),
+            ParamId::Wow => (0.1, 0.9),
+            ParamId::TimeWarp => (0.5, 2.0),
+        }
+    }
+}
+
+/// A value for some of the [`ParamId`]s, e.g. a scene's defaults.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ParamSet {
+    values: [Option<f32>; PARAM_COUNT],
+}
+
+impl ParamSet {
+    #[inline] pub fn get(&self, id: ParamId) -> Option<f32> { self.values[id as usize] }
+    #[inline] pub fn set(&mut self, id: ParamId, v: f32) { self.values[id as usize] = Some(v); }
+    #[inline] pub fn remove(&mut self, id: ParamId) { self.values[id as usize] = None; }
+
+    /// Number of parameters with a value.
+    pub fn len(&self) -> usize { self.values.iter().flatten().count() }
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// `(id, value)` pairs in id order.
+    pub fn iter(&self) -> impl Iterator<Item = (ParamId, f32)> + '_ {
+        ParamId::ALL.into_iter().filter_map(|id| self.get(id).map(|v| (id, v)))
+    }
+
+    /// Parse a preset written as `name=value` pairs separated by commas, e.g.
+    /// `intensity=0.3,tone=-0.4,width=120` (names as in [`ParamId::name`]; the
+    /// format [`ParamSet`]'s `Display` writes). `None` on an unknown name or a
+    /// bad number.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut s = Self::default();
+        for pair in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (name, value) = pair.split_once('=')?;
+            let id = ParamId::parse(name.trim())?;
+            let v: f32 = value.trim().parse().ok()?;
+            if !v.is_finite() { return None; }
+            s.set(id, v);
+        }
+        Some(s)
+    }
+}
+
+impl fmt::Display for ParamSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (id, v)) in self.iter().enumerate() {
+            if i > 0 { f.write_str(",")?; }
+            write!(f, "{}={v}", id.name())?;
+        }
+        Ok(())
+    }
+}
+
+/// One of the engine's two parameter snapshots (see `Engine::toggle_ab`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AbSlot {
+    A,
+    B,
+}
+
+impl AbSlot {
+    /// The other slot.
+    #[inline]
+    pub fn other(self) -> Self {
+        match self {
+            AbSlot::A => AbSlot::B,
+            AbSlot::B => AbSlot::A,
+        }
+    }
+}
+
+impl FromIterator<(ParamId, f32)> for ParamSet {
+    fn from_iter<I: IntoIterator<Item = (ParamId, f32)>>(iter: I) -> Self {
+        let mut s = Self::default();
+        for (id, v) in iter { s.set(id, v); }
+        s
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Ramp {
+    id: ParamId,
+    log: bool,
+    from: f32, // in ramp space (ln for log ramps)
+    to: f32,
+    target: f32,
+    total: u32, // samples
+    done: u32,
+}
+
+impl Ramp {
+    #[inline]
+    fn value(&self) -> f32 {
+        if self.done >= self.total { return self.target; }
+        let x = self.from + (self.to - self.from) * (self.done as f32 / self.total as f32);
+        if self.log { x.exp() } else { x }
+    }
+}
+
+/// Most ramps running at once.
+pub const MAX_RAMPS: usize = 16;
+
+/// Scheduled parameter ramps, stepped every [`CONTROL_BLOCK`] samples.
+#[derive(Copy, Clone, Debug)]
+pub struct ParamRamps {
+    ramps: [Option<Ramp>; MAX_RAMPS],
+    active: usize,
+    left: u32, // samples to the next step
+}
+
+impl Default for ParamRamps {
+    fn default() -> Self { Self { ramps: [None; MAX_RAMPS], active: 0, left: 0 } }
+}
+
+impl ParamRamps {
+    /// Ramp `id` from `from` to `target` over `samples`, replacing any ramp
+    /// already running on it. Returns `false` (nothing scheduled) if all
+    /// [`MAX_RAMPS`] slots are busy with other parameters.
+    pub fn start(&mut self, id: ParamId, from: f32, target: f32, samples: u32) -> bool {
+        let log = id.curve() == RampCurve::Log && from >= 0.0 && target >= 0.0;
+        let space = |x: f32| if log { x.max(LOG_FLOOR).ln() } else { x };
+        let ramp = Ramp { id, log, from: space(from), to: space(target), target, total: samples.max(1), done: 0 };
+        let slot = match self.ramps.iter().position(|r| r.is_some_and(|r| r.id == id)) {
+            Some(i) => i,
+            None => match self.ramps.iter().position(Option::is_none) {
+                Some(i) => {
+                    self.active += 1;
+                    i
+                }
+                None => return false,
+            },
+        };
+        self.ramps[slot] = Some(ramp);
+        true
+    }
+
+    /// Stop the ramp on `id` where it is (e.g. because the host set the value directly).
+    pub fn cancel(&mut self, id: ParamId) {
+        for r in &mut self.ramps {
+            if r.is_some_and(|r| r.id == id) {
+                *r = None;
+                self.active -= 1;
+            }
+        }
+    }
+
+    /// Stop every ramp.
+    pub fn clear(&mut self) { *self = Self::default(); }
+
+    #[inline] pub fn is_active(&self) -> bool { self.active > 0 }
+
+    /// Value ramp `id` is heading for, if one is running.
+    pub fn target(&self, id: ParamId) -> Option<f32> {
+        self.ramps.iter().flatten().find(|r| r.id == id).map(|r| r.target)
+    }
+
+    /// Call once per sample while [`ParamRamps::is_active`]; on the first sample
+    /// of every control block, hands each ramp's new value to `apply` (finished
+    /// ramps deliver their exact target once, then end).
+    #[inline]
+    pub fn tick(&mut self, mut apply: impl FnMut(ParamId, f32)) {
+        if self.left > 0 {
+            self.left -= 1;
+            return;
+        }
+        self.left = CONTROL_BLOCK - 1;
+        for slot in &mut self.ramps {
+            let Some(r) = slot else { continue; };
+            r.done = r.done.saturating_add(CONTROL_BLOCK).min(r.total);
+            apply(r.id, r.value());
+            if r.done >= r.total {
+                *slot = None;
+                self.active -= 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every value the ramps deliver until they are done.
+    fn run(r: &mut ParamRamps) -> Vec<(ParamId, f32)> {
+        let mut out = Vec::new();
+        while r.is_active() { r.tick(|id, v| out.push((id, v))); }
+        out
+    }
+
+    #[test]
+    fn linear_ramps_step_evenly_to_the_exact_target() {
+        let mut r = ParamRamps::default();
+        assert!(r.start(ParamId::Tone, -1.0, 0.3, 8 * CONTROL_BLOCK));
+        assert_eq!(r.target(ParamId::Tone), Some(0.3));
+        let v: Vec<f32> = run(&mut r).into_iter().map(|(_, v)| v).collect();
+        assert_eq!(v.len(), 8);
+        for (k, x) in v.iter().enumerate() {
+            let want = -1.0 + 1.3 * (k + 1) as f32 / 8.0;
+            assert!((x - want).abs() < 1e-5, "step {k}: {x} vs {want}");
+        }
+        assert_eq!(v[7], 0.3);
+        assert_eq!(r.target(ParamId::Tone), None);
+    }
+
+    #[test]
+    fn log_ramps_step_in_equal_ratios() {
+        let mut r = ParamRamps::default();
+        r.start(ParamId::CutBase, 100.0, 1600.0, 4 * CONTROL_BLOCK);
+        let v: Vec<f32> = run(&mut r).into_iter().map(|(_, v)| v).collect();
+        for (x, want) in v.iter().zip([200.0, 400.0, 800.0, 1600.0]) {
+            assert!((x / want - 1.0).abs() < 1e-4, "{x} vs {want}");
+        }
+        assert_eq!(v[3], 1600.0);
+
+        // through 0: from -80 dB up, and landing on 0 exactly on the way down
+        r.start(ParamId::SceneGain, 0.0, 1.0, 4 * CONTROL_BLOCK);
+        let v: Vec<f32> = run(&mut r).into_iter().map(|(_, v)| v).collect();
+        assert!((v[0] / 1e-3 - 1.0).abs() < 1e-3 && v[3] == 1.0, "{v:?}");
+        r.start(ParamId::SceneGain, 1.0, 0.0, 4 * CONTROL_BLOCK);
+        let v: Vec<f32> = run(&mut r).into_iter().map(|(_, v)| v).collect();
+        assert!((v[2] / 1e-3 - 1.0).abs() < 1e-3 && v[3] == 0.0, "{v:?}");
+    }
+
+    #[test]
+    fn full_slots_refuse_new_parameters_but_retarget_running_ones() {
+        let mut r = ParamRamps::default();
+        for &id in &ParamId::ALL[..MAX_RAMPS] { assert!(r.start(id, 0.0, 1.0, 1000)); }
+        let spare = ParamId::ALL[MAX_RAMPS];
+        assert!(!r.start(spare, 0.0, 1.0, 1000));
+        assert_eq!(r.target(spare), None);
+
+        // same parameter: replaces its ramp in place
+        assert!(r.start(ParamId::ALL[0], 0.0, 0.5, 1000));
+        assert_eq!(r.target(ParamId::ALL[0]), Some(0.5));
+
+        // a cancelled ramp frees its slot
+        r.cancel(ParamId::ALL[1]);
+        assert!(r.start(spare, 0.0, 1.0, 1000));
+        assert_eq!(run(&mut r).iter().filter(|(id, _)| *id == ParamId::ALL[1]).count(), 0);
+    }
+
+    #[test]
+    fn cancel_stops_a_ramp_where_it_is() {
+        let mut r = ParamRamps::default();
+        r.start(ParamId::Width, 0.0, 100.0, 10 * CONTROL_BLOCK);
+        let mut last = None;
+        for _ in 0..3 * CONTROL_BLOCK { r.tick(|_, v| last = Some(v)); }
+        r.cancel(ParamId::Width);
+        assert!(!r.is_active());
+        assert_eq!(r.target(ParamId::Width), None);
+        assert!(last.is_some_and(|v| (v - 30.0).abs() < 1e-4), "{last:?}");
+        // cancelling nothing is harmless
+        r.cancel(ParamId::Width);
+        assert!(run(&mut r).is_empty());
+    }
+}
@@ -0,0 +1,208 @@
+//! CPU attribution for diagnostic renders (feature `profile`).
+//!
+//! Hot paths wrap their stages in [`scope`]; with the feature enabled each
+//! scope adds its elapsed time to a per-thread counter, and
+//! [`Engine::cpu_report`](crate::graph::Engine::cpu_report) turns the counters
+//! into a breakdown. Without the feature `scope` compiles to nothing.
+//!
+//! Scopes nest, and time is exclusive: a graph scene's nodes are timed inside
+//! the scene's scope, and what they take is not counted again as `scene`.
+//! Built-in scenes are timed as a whole; graph scenes by node family; the
+//! master bus by effect.
+//!
+//! Counters are thread-local: read the report on the thread that rendered.
+//! Timing every sample costs far more than the DSP itself, so only use this
+//! for offline benchmarks (e.g. `ambientor-cli bench`), never for playback.
+
+/// Processing stages that time is attributed to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// Scene rendering not covered by a node stage: a whole built-in scene,
+    /// or a graph scene's routing, expressions and output mix.
+    Scene,
+    /// Graph nodes: oscillators and noise.
+    Sources,
+    /// Graph nodes: LFOs, drifts and chaos modulators.
+    Modulators,
+    /// Graph nodes: filters and crossovers.
+    Filters,
+    /// Graph nodes: gain, saturation, ring modulation, frequency shift,
+    /// exciter and auto-wah.
+    NodeFx,
+    /// Shared reverb bus.
+    Reverb,
+    /// Shared delay bus.
+    Delay,
+    /// Stereo width.
+    Width,
+    /// Multiband texture.
+    Texture,
+    /// Output 3-band EQ.
+    Eq,
+    /// Tilt tone control.
+    Tilt,
+    /// Distance and occlusion.
+    Spatial,
+    /// Mono bass, output high-pass and sub bed.
+    Output,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 13] = [
+        Stage::Scene, Stage::Sources, Stage::Modulators, Stage::Filters, Stage::NodeFx,
+        Stage::Reverb, Stage::Delay, Stage::Width, Stage::Texture, Stage::Eq, Stage::Tilt,
+        Stage::Spatial, Stage::Output,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Scene => "scene",
+            Stage::Sources => "sources",
+            Stage::Modulators => "mods",
+            Stage::Filters => "filters",
+            Stage::NodeFx => "node-fx",
+            Stage::Reverb => "reverb",
+            Stage::Delay => "delay",
+            Stage::Width => "width",
+            Stage::Texture => "texture",
+            Stage::Eq => "eq",
+            Stage::Tilt => "tilt",
+            Stage::Spatial => "spatial",
+            Stage::Output => "output",
+        }
+    }
+}
+
+const STAGES: usize = Stage::ALL.len();
+
+#[cfg(feature = "profile")]
+mod imp {
+    use super::{Stage, STAGES};
+    use std::cell::Cell;
+    use std::time::Instant;
+
+    thread_local! {
+        static NANOS: Cell<[u64; STAGES]> = const { Cell::new([0; STAGES]) };
+        /// Time taken so far by scopes nested in the innermost open one.
+        static NESTED: Cell<u64> = const { Cell::new(0) };
+    }
+
+    /// Times one stage until dropped.
+    pub struct Scope {
+        stage: Stage,
+        t0: Instant,
+        outer_nested: u64,
+    }
+
+    impl Drop for Scope {
+        #[inline]
+        fn drop(&mut self) {
+            let dt = self.t0.elapsed().as_nanos() as u64;
+            let nested = NESTED.with(|c| c.replace(self.outer_nested + dt));
+            NANOS.with(|n| {
+                let mut v = n.get();
+                v[self.stage as usize] += dt.saturating_sub(nested);
+                n.set(v);
+            });
+        }
+    }
+
+    #[inline]
+    pub fn scope(stage: Stage) -> Scope {
+        let outer_nested = NESTED.with(|c| c.replace(0));
+        Scope { stage, t0: Instant::now(), outer_nested }
+    }
+
+    pub fn nanos() -> [u64; STAGES] { NANOS.with(Cell::get) }
+
+    pub fn reset() {
+        NANOS.with(|n| n.set([0; STAGES]));
+        NESTED.with(|c| c.set(0));
+    }
+}
+
+#[cfg(not(feature = "profile"))]
+mod imp {
+    use super::{Stage, STAGES};
+
+    /// No-op without the `profile` feature.
+    pub struct Scope;
+
+    #[inline(always)]
+    pub fn scope(_stage: Stage) -> Scope { Scope }
+
+    pub fn nanos() -> [u64; STAGES] { [0; STAGES] }
+
+    pub fn reset() {}
+}
+
+pub use imp::{scope, Scope};
+
+/// `true` if this build collects timings.
+pub const ENABLED: bool = cfg!(feature = "profile");
+
+/// Time spent per stage since the last reset, on this thread.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct CpuReport {
+    nanos: [u64; STAGES],
+}
+
+impl CpuReport {
+    /// Snapshot the calling thread's counters.
+    pub fn capture() -> Self { Self { nanos: imp::nanos() } }
+
+    /// Zero the calling thread's counters.
+    pub fn reset() { imp::reset(); }
+
+    /// Nanoseconds attributed to `stage`.
+    #[inline] pub fn nanos(&self, stage: Stage) -> u64 { self.nanos[stage as usize] }
+
+    /// Total attributed nanoseconds.
+    pub fn total_nanos(&self) -> u64 { self.nanos.iter().sum() }
+
+    /// `(stage, nanoseconds, share 0..1)` for every stage, in [`Stage::ALL`] order.
+    pub fn entries(&self) -> impl Iterator<Item = (Stage, u64, f32)> + '_ {
+        let total = self.total_nanos().max(1) as f32;
+        Stage::ALL.into_iter().map(move |s| (s, self.nanos(s), self.nanos(s) as f32 / total))
+    }
+}
+
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn nested_scopes_are_counted_once() {
+        CpuReport::reset();
+        {
+            let _outer = scope(Stage::Scene);
+            let _inner = scope(Stage::Filters);
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let r = CpuReport::capture();
+        assert!(r.nanos(Stage::Filters) >= 20_000_000);
+        assert!(r.nanos(Stage::Scene) < 5_000_000, "{r:?}");
+    }
+
+    #[test]
+    fn graph_nodes_and_master_effects_get_their_own_stages() {
+        use crate::graph::{Engine, Generator};
+        use crate::nodes::Wave;
+        use crate::scenes::graph_scene::{FilterMode, GraphScene, NodeKind};
+        let mut g = GraphScene::new("test", 48_000.0);
+        let osc = g.add_node(NodeKind::Osc { wave: Wave::Saw, note: None }).unwrap();
+        let lp = g.add_node(NodeKind::Filter { mode: FilterMode::Lowpass }).unwrap();
+        g.connect(osc, lp, 1.0).unwrap();
+        g.connect_output(lp, 1.0, 0.0).unwrap();
+        g.reset(48_000.0);
+        let mut e = Engine::new(g);
+        e.reset_cpu_report();
+        for _ in 0..4_800 { e.next_stereo(48_000.0); }
+        let r = e.cpu_report();
+        for stage in [Stage::Scene, Stage::Sources, Stage::Filters, Stage::Reverb, Stage::Texture, Stage::Output] {
+            assert!(r.nanos(stage) > 0, "{}: {r:?}", stage.name());
+        }
+        assert_eq!(r.nanos(Stage::Modulators), 0);
+    }
+}
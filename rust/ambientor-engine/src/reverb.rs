@@ -1,4 +1,4 @@
-//! Lightweight mono reverb (no heap, realtime-safe).
+//! Lightweight reverb (no heap, realtime-safe).
 //!
 //! Design
 //! - Simple “Schroeder-ish” structure: 2 short all-passes → 4 LP-combs in parallel → 2 all-passes.
@@ -6,28 +6,67 @@
 //! - Tunable `room` (feedback), `damp` (HF damping in feedback), `mix` (dry/wet).
 //!
 //! This is intentionally modest in CPU and memory while still giving a pleasant wash
-//! for ambient drones. Output is **mono**; the CLI duplicates it to device channels.
+//! for ambient drones. `process` is mono in/out; `process_stereo` feeds the tank the
+//! mono sum and returns decorrelated left/right tails. The `*_block` variants
+//! run each stage over a chunk of [`BLOCK`] samples at a time (same
+//! processing, tighter loops).
+//!
+//! Sleep: once both the input and the tail have stayed below [`SLEEP_LEVEL`]
+//! for [`SLEEP_AFTER_S`], the tank stops running and only the dry path is
+//! computed, until the input comes back. [`ReverbLite::set_bypass`] (or a mix
+//! of 0) fades the wet path out over [`FADE_MS`], flushes the tail, and sleeps
+//! the same way, so dry scenes cost next to nothing.
+//!
+//! Shimmer: [`ReverbLite::set_shimmer`] feeds the tail back into the tank
+//! through a [`PitchShifter`] an octave up, so every pass through the loop
+//! climbs an octave and the tail blooms upward. While it is on, the block
+//! methods process frame by frame.
 
 use core::fmt::Debug;
+use crate::effects::PitchShifter;
 use ambientor_core::dsp::{kill_denormals};
-use ambientor_core::filters::OnePoleLP;
+use ambientor_core::filters::{OnePoleHP, OnePoleLP};
 
 /// Fixed sizes for delay lines (compile-time, stack-allocated inside the struct).
 const MAX_PRE_AP: usize   = 2048;   // ~43 ms @ 48k
 const MAX_TANK:   usize   = 34000;  // ~0.708 s @ 48k
 const MAX_POST_AP: usize  = 4096;   // ~85 ms @ 48k
+/// Chunk size of the block methods (stack scratch, no heap).
+pub const BLOCK: usize = 64;
+/// Input and tail level (linear, about -100 dB) below which the tank may sleep.
+pub const SLEEP_LEVEL: f32 = 1e-5;
+/// How long input and tail must stay below [`SLEEP_LEVEL`] before sleeping
+/// (longer than the longest path through the tank).
+pub const SLEEP_AFTER_S: f32 = 0.5;
+/// Wet fade when bypassing or un-bypassing.
+pub const FADE_MS: f32 = 20.0;
+/// Transposition of the shimmer loop (semitones).
+pub const SHIMMER_SEMITONES: f32 = 12.0;
+/// Loop gain of the shimmer feedback at amount 1 (kept well under the level
+/// at which the octave-up loop would build up).
+const SHIMMER_GAIN: f32 = 0.35;
+/// High-pass in the shimmer loop: an octave up of (near) DC is still (near)
+/// DC, so without it the lows would circulate and build up.
+const SHIMMER_HP_HZ: f32 = 250.0;
+/// Low-pass in the shimmer loop, so octaves stacking up fade out before
+/// they reach (and alias around) Nyquist.
+const SHIMMER_LP_HZ: f32 = 9000.0;
 
-#[derive(Copy, Clone, Debug)]
-struct DelayLine<const N: usize> {
-    buf: [f32; N],
+/// Fixed-capacity circular delay line (shared with other engine modules). The
+/// buffer lives on the heap: a reverb's lines alone are over half a megabyte,
+/// too much to keep inline in the engine or move through the stack.
+#[derive(Clone, Debug)]
+pub(crate) struct DelayLine<const N: usize> {
+    buf: Box<[f32]>, // N samples
     i: usize,
     len: usize,
 }
 impl<const N: usize> DelayLine<N> {
-    #[inline] fn new() -> Self { Self { buf: [0.0; N], i: 0, len: N.min(1) } }
-    #[inline] fn set_len(&mut self, len: usize) { self.len = len.max(1).min(N); if self.i >= self.len { self.i = 0; } }
-    #[inline] fn read(&self) -> f32 { self.buf[self.i] }
-    #[inline] fn write_advance(&mut self, x: f32) {
+    #[inline] pub(crate) fn new() -> Self { Self { buf: vec![0.0; N].into_boxed_slice(), i: 0, len: N.min(1) } }
+    #[inline] pub(crate) fn set_len(&mut self, len: usize) { self.len = len.max(1).min(N); if self.i >= self.len { self.i = 0; } }
+    #[inline] pub(crate) fn clear(&mut self) { self.buf.fill(0.0); self.i = 0; }
+    #[inline] pub(crate) fn read(&self) -> f32 { self.buf[self.i] }
+    #[inline] pub(crate) fn write_advance(&mut self, x: f32) {
         self.buf[self.i] = x;
         self.i += 1;
         if self.i >= self.len { self.i = 0; }
@@ -36,25 +75,30 @@ impl<const N: usize> DelayLine<N> {
 
 /// Simple all-pass: y = -g*x + d + g*y_prev_path, with a single delay.
 /// Canonical “feedforward + feedback” all-pass.
-#[derive(Copy, Clone, Debug)]
-struct Allpass<const N: usize> {
+#[derive(Clone, Debug)]
+pub(crate) struct Allpass<const N: usize> {
     d: DelayLine<N>,
     g: f32,
 }
 impl<const N: usize> Allpass<N> {
-    #[inline] fn new(g: f32) -> Self { Self { d: DelayLine::new(), g } }
-    #[inline] fn set_len(&mut self, len: usize) { self.d.set_len(len); }
-    #[inline] fn set_g(&mut self, g: f32) { self.g = g.clamp(-0.999, 0.999); }
-    #[inline] fn process(&mut self, x: f32) -> f32 {
+    #[inline] pub(crate) fn new(g: f32) -> Self { Self { d: DelayLine::new(), g } }
+    #[inline] pub(crate) fn set_len(&mut self, len: usize) { self.d.set_len(len); }
+    #[inline] pub(crate) fn clear(&mut self) { self.d.clear(); }
+    #[inline] pub(crate) fn set_g(&mut self, g: f32) { self.g = g.clamp(-0.999, 0.999); }
+    #[inline] pub(crate) fn process(&mut self, x: f32) -> f32 {
         let z = self.d.read();
         let y = z - self.g * x;
         self.d.write_advance(x + self.g * y);
         kill_denormals(y)
     }
+    /// [`Allpass::process`] over `io` in place.
+    #[inline] pub(crate) fn process_block(&mut self, io: &mut [f32]) {
+        for x in io { *x = self.process(*x); }
+    }
 }
 
 /// Feedback comb with an LP filter inside the feedback path (for damping).
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 struct CombLp<const N: usize> {
     d: DelayLine<N>,
     fb: f32,
@@ -63,6 +107,7 @@ struct CombLp<const N: usize> {
 impl<const N: usize> CombLp<N> {
     #[inline] fn new(sr: f32) -> Self { Self { d: DelayLine::new(), fb: 0.7, lp: OnePoleLP::new(8000.0, sr) } }
     #[inline] fn set_len(&mut self, len: usize) { self.d.set_len(len); }
+    #[inline] fn clear(&mut self) { self.d.clear(); self.lp.reset(); }
     #[inline] fn set_feedback(&mut self, fb: f32) { self.fb = fb.clamp(0.0, 0.99); }
     #[inline] fn set_damp_cut(&mut self, hz: f32) { self.lp.set_cutoff_hz(hz); }
     #[inline] fn set_sr(&mut self, sr: f32) { self.lp.set_sample_rate(sr); }
@@ -73,10 +118,14 @@ impl<const N: usize> CombLp<N> {
         self.d.write_advance(x + self.fb * z_damped);
         kill_denormals(y)
     }
+    /// Add the comb output for each of `input` into `acc`.
+    #[inline] fn process_add(&mut self, input: &[f32], acc: &mut [f32]) {
+        for (x, a) in input.iter().zip(acc.iter_mut()) { *a += self.process(*x); }
+    }
 }
 
-/// Mono reverb with small footprint.
-#[derive(Copy, Clone, Debug)]
+/// Reverb with small footprint (single tank, mono or stereo output).
+#[derive(Clone, Debug)]
 pub struct ReverbLite {
     sr: f32,
     // pre-diffusion
@@ -95,6 +144,19 @@ pub struct ReverbLite {
     damp: f32,  // 0..1 → mapped to comb LP cutoff
     mix:  f32,  // 0..1 (wet)
     pre_delay_samps: usize,
+    // sleep / bypass
+    bypass: bool,
+    asleep: bool,
+    wet_gain: f32, // bypass fade, 0..1
+    fade_step: f32,
+    quiet: u32,    // samples of silent input and tail so far
+    sleep_after: u32,
+    // shimmer: octave-up feedback of the tail into the tank
+    shimmer: f32,
+    shifter: PitchShifter,
+    shim_hp: OnePoleHP,
+    shim_lp: OnePoleLP,
+    shim_fb: f32,  // shifted tail of the previous frame
 }
 impl ReverbLite {
     #[inline]
@@ -113,13 +175,52 @@ impl ReverbLite {
             damp: 0.4,
             mix:  0.25,
             pre_delay_samps: 0,
+            bypass: false,
+            asleep: true,
+            wet_gain: 1.0,
+            fade_step: 1.0,
+            quiet: 0,
+            sleep_after: 0,
+            shimmer: 0.0,
+            shifter: PitchShifter::new(SHIMMER_SEMITONES, sr),
+            shim_hp: OnePoleHP::new(SHIMMER_HP_HZ, sr),
+            shim_lp: OnePoleLP::new(SHIMMER_LP_HZ, sr),
+            shim_fb: 0.0,
         };
         s.reset(sr);
         s
     }
 
+    /// Rescale to `sr` and flush the tank (starts from silence, asleep).
     #[inline]
     pub fn reset(&mut self, sr: f32) {
+        self.set_sample_rate(sr);
+        self.flush();
+        self.wet_gain = if self.wet_off() { 0.0 } else { 1.0 };
+    }
+
+    /// Empty every delay line and go to sleep.
+    fn flush(&mut self) {
+        self.ap1.clear(); self.ap2.clear();
+        for c in [&mut self.c1, &mut self.c2, &mut self.c3, &mut self.c4] {
+            c.clear();
+        }
+        self.ap3.clear(); self.ap4.clear();
+        self.shifter.clear();
+        self.shim_hp.reset();
+        self.shim_lp.reset();
+        self.shim_fb = 0.0;
+        self.asleep = true;
+        self.quiet = 0;
+    }
+
+    /// Flush the tank at the current sample rate.
+    #[inline] pub fn clear(&mut self) { self.reset(self.sr); }
+
+    /// Rescale delay lengths and damping to `sr`, keeping whatever tail is in
+    /// the tank (lines that shrink simply wrap their read position).
+    #[inline]
+    pub fn set_sample_rate(&mut self, sr: f32) {
         self.sr = sr.max(1.0);
         // Set allpass/comb lengths (in samples) relative to SR.
         // Keep within compile-time maximums.
@@ -141,6 +242,11 @@ impl ReverbLite {
 
         // Default pre-delay ~ 12 ms
         self.pre_delay_samps = (self.sr * 0.012) as usize;
+        self.sleep_after = (SLEEP_AFTER_S * self.sr) as u32;
+        self.fade_step = 1.0 / (FADE_MS * 0.001 * self.sr).max(1.0);
+        self.shifter.set_sample_rate(self.sr);
+        self.shim_hp.set_sample_rate(self.sr);
+        self.shim_lp.set_sample_rate(self.sr);
         self.update_params();
     }
 
@@ -159,13 +265,74 @@ impl ReverbLite {
     #[inline] pub fn set_damp(&mut self, v: f32) { self.damp = v; self.update_params(); }
     #[inline] pub fn set_mix(&mut self, v: f32)  { self.mix  = v; self.update_params(); }
 
+    /// Shimmer amount 0 (off, default) .. 1: how much of the tail is fed back
+    /// an octave up. Turning it off lets the shifted part of the tail die away.
+    #[inline]
+    pub fn set_shimmer(&mut self, amount: f32) {
+        self.shimmer = amount.clamp(0.0, 1.0);
+        if self.shimmer == 0.0 { self.shim_fb = 0.0; }
+    }
+    #[inline] pub fn shimmer(&self) -> f32 { self.shimmer }
+    /// The shimmer loop's pitch shifter (e.g. to change its interval or window).
+    #[inline] pub fn shifter_mut(&mut self) -> &mut PitchShifter { &mut self.shifter }
+
+    /// Turn the wet path off (fade out, flush the tail, sleep) or back on
+    /// (fade in). The dry path, `(1 - mix) × input`, is unaffected.
+    #[inline] pub fn set_bypass(&mut self, on: bool) { self.bypass = on; }
+    #[inline] pub fn bypass(&self) -> bool { self.bypass }
+    /// `true` while the tank is not running (see the module docs).
+    #[inline] pub fn is_asleep(&self) -> bool { self.asleep }
+
+    /// Tail sample → what the shimmer loop feeds back into the tank next frame.
+    #[inline]
+    fn shimmer_feedback(&mut self, wet: f32) -> f32 {
+        let band = self.shim_lp.process(self.shim_hp.process(wet));
+        kill_denormals(SHIMMER_GAIN * self.shimmer * self.shifter.process(band))
+    }
+
+    #[inline] fn wet_off(&self) -> bool { self.bypass || self.mix <= 0.0 }
+
+    /// While asleep: wake for input at `level`, unless the wet path is off.
+    #[inline]
+    fn wake(&mut self, level: f32) -> bool {
+        if self.wet_off() || level < SLEEP_LEVEL { return false; }
+        self.asleep = false;
+        self.quiet = 0;
+        true
+    }
+
+    /// Advance the bypass fade one sample; flushes once faded out.
+    #[inline]
+    fn next_wet_gain(&mut self) -> f32 {
+        if self.wet_off() {
+            self.wet_gain = (self.wet_gain - self.fade_step).max(0.0);
+            if self.wet_gain <= 0.0 && !self.asleep { self.flush(); }
+        } else if self.wet_gain < 1.0 {
+            self.wet_gain = (self.wet_gain + self.fade_step).min(1.0);
+        }
+        self.wet_gain
+    }
+
+    /// Count `n` samples with input peak `input` and tail peak `tail` towards sleep.
+    #[inline]
+    fn track(&mut self, input: f32, tail: f32, n: usize) {
+        if input < SLEEP_LEVEL && tail < SLEEP_LEVEL {
+            self.quiet = self.quiet.saturating_add(n as u32);
+            if self.quiet >= self.sleep_after { self.asleep = true; }
+        } else {
+            self.quiet = 0;
+        }
+    }
+
     /// Process one mono sample; returns the reverberated (dry+wet) sample.
     #[inline]
     pub fn process(&mut self, x: f32) -> f32 {
+        let dry = (1.0 - self.mix) * x;
+        if self.asleep && !self.wake(x.abs()) { return kill_denormals(dry); }
         // Optional pre-delay: we approximate by pushing zeros before starting the tank
         // when the scene first runs. For simplicity in a streaming context, we model
         // it as two short APs acting as a diffuser (already set up above).
-        let pre = self.ap2.process(self.ap1.process(x));
+        let pre = self.ap2.process(self.ap1.process(x + self.shim_fb));
 
         // Parallel combs
         let y1 = self.c1.process(pre);
@@ -179,8 +346,190 @@ impl ReverbLite {
 
         // Mix
         let wet = post;
-        let dry = x;
-        let y = (1.0 - self.mix) * dry + self.mix * wet;
-        kill_denormals(y)
+        if self.shimmer > 0.0 { self.shim_fb = self.shimmer_feedback(wet); }
+        self.track(x.abs(), wet.abs(), 1);
+        let g = self.next_wet_gain();
+        kill_denormals(dry + self.mix * g * wet)
+    }
+
+    /// [`ReverbLite::process`] over `io` in place.
+    pub fn process_block(&mut self, io: &mut [f32]) {
+        if self.shimmer > 0.0 {
+            for x in io { *x = self.process(*x); }
+            return;
+        }
+        for chunk in io.chunks_mut(BLOCK) {
+            let n = chunk.len();
+            let dry = 1.0 - self.mix;
+            let peak = chunk.iter().fold(0.0f32, |m, x| m.max(x.abs()));
+            if self.asleep && !self.wake(peak) {
+                for x in chunk.iter_mut() { *x = kill_denormals(dry * *x); }
+                continue;
+            }
+            let mut pre = [0.0; BLOCK];
+            let pre = &mut pre[..n];
+            pre.copy_from_slice(chunk);
+            self.ap1.process_block(pre);
+            self.ap2.process_block(pre);
+
+            let mut sum = [0.0; BLOCK];
+            let sum = &mut sum[..n];
+            for c in [&mut self.c1, &mut self.c2, &mut self.c3, &mut self.c4] {
+                c.process_add(pre, sum);
+            }
+            for s in sum.iter_mut() { *s *= 0.25; }
+            self.ap3.process_block(sum);
+            self.ap4.process_block(sum);
+
+            self.track(peak, sum.iter().fold(0.0f32, |m, w| m.max(w.abs())), n);
+            for (x, w) in chunk.iter_mut().zip(sum.iter()) {
+                let g = self.next_wet_gain();
+                *x = kill_denormals(dry * *x + self.mix * g * *w);
+            }
+        }
+    }
+
+    /// Process one stereo frame; returns `(left, right)` dry+wet.
+    ///
+    /// The tank is fed the mono sum; the wet sides tap different comb pairs and
+    /// use one post all-pass each, so the tail comes out decorrelated.
+    #[inline]
+    pub fn process_stereo(&mut self, l: f32, r: f32) -> (f32, f32) {
+        let dry = 1.0 - self.mix;
+        if self.asleep && !self.wake(l.abs().max(r.abs())) {
+            return (kill_denormals(dry * l), kill_denormals(dry * r));
+        }
+        let pre = self.ap2.process(self.ap1.process(0.5 * (l + r) + self.shim_fb));
+
+        let y1 = self.c1.process(pre);
+        let y2 = self.c2.process(pre);
+        let y3 = self.c3.process(pre);
+        let y4 = self.c4.process(pre);
+
+        // two uncorrelated combs per side: 0.354 ≈ 0.25 * √2 keeps the mono tail level
+        let wet_l = self.ap3.process(0.354 * (y1 + y3));
+        let wet_r = self.ap4.process(0.354 * (y2 + y4));
+        if self.shimmer > 0.0 {
+            self.shim_fb = self.shimmer_feedback(0.5 * (wet_l + wet_r));
+        }
+
+        self.track(l.abs().max(r.abs()), wet_l.abs().max(wet_r.abs()), 1);
+        let wet = self.mix * self.next_wet_gain();
+        (
+            kill_denormals(dry * l + wet * wet_l),
+            kill_denormals(dry * r + wet * wet_r),
+        )
+    }
+
+    /// [`ReverbLite::process_stereo`] over `left`/`right` in place
+    /// (`min(len)` frames).
+    pub fn process_stereo_block(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let n = left.len().min(right.len());
+        if self.shimmer > 0.0 {
+            for (l, r) in left[..n].iter_mut().zip(right[..n].iter_mut()) {
+                (*l, *r) = self.process_stereo(*l, *r);
+            }
+            return;
+        }
+        for (cl, cr) in left[..n].chunks_mut(BLOCK).zip(right[..n].chunks_mut(BLOCK)) {
+            let n = cl.len();
+            let dry = 1.0 - self.mix;
+            let peak = cl.iter().chain(cr.iter()).fold(0.0f32, |m, x| m.max(x.abs()));
+            if self.asleep && !self.wake(peak) {
+                for x in cl.iter_mut().chain(cr.iter_mut()) { *x = kill_denormals(dry * *x); }
+                continue;
+            }
+            let mut pre = [0.0; BLOCK];
+            let pre = &mut pre[..n];
+            for ((p, l), r) in pre.iter_mut().zip(cl.iter()).zip(cr.iter()) { *p = 0.5 * (l + r); }
+            self.ap1.process_block(pre);
+            self.ap2.process_block(pre);
+
+            let (mut wl, mut wr) = ([0.0; BLOCK], [0.0; BLOCK]);
+            let (wl, wr) = (&mut wl[..n], &mut wr[..n]);
+            self.c1.process_add(pre, wl);
+            self.c2.process_add(pre, wr);
+            self.c3.process_add(pre, wl);
+            self.c4.process_add(pre, wr);
+            for w in wl.iter_mut().chain(wr.iter_mut()) { *w *= 0.354; }
+            self.ap3.process_block(wl);
+            self.ap4.process_block(wr);
+
+            self.track(peak, wl.iter().chain(wr.iter()).fold(0.0f32, |m, w| m.max(w.abs())), n);
+            for i in 0..n {
+                let wet = self.mix * self.next_wet_gain();
+                cl[i] = kill_denormals(dry * cl[i] + wet * wl[i]);
+                cr[i] = kill_denormals(dry * cr[i] + wet * wr[i]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Half a second of noise, then enough silence for the tank to fall asleep,
+    /// then another burst to wake it.
+    fn burst_signal(sr: f32, seed: u32) -> Vec<f32> {
+        let mut state = seed;
+        let half = (0.5 * sr) as usize;
+        let mut out = vec![0.0; 44 * half];
+        for i in (0..half).chain(42 * half..43 * half) {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            out[i] = 0.5 * ((state >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0);
+        }
+        out
+    }
+
+    fn max_diff(a: &[f32], b: &[f32]) -> f32 {
+        a.iter().zip(b).fold(0.0f32, |m, (x, y)| m.max((x - y).abs()))
+    }
+
+    fn reverb(shimmer: f32) -> ReverbLite {
+        let mut r = ReverbLite::new(48_000.0);
+        r.set_mix(0.4);
+        r.set_shimmer(shimmer);
+        r
+    }
+
+    #[test]
+    fn block_matches_per_sample() {
+        for shimmer in [0.0, 0.5] {
+            let input = burst_signal(48_000.0, 1);
+            let (mut a, mut b) = (reverb(shimmer), reverb(shimmer));
+            let per_sample: Vec<f32> = input.iter().map(|&x| a.process(x)).collect();
+            let mut block = input.clone();
+            // odd chunk lengths so blocks straddle the BLOCK boundaries
+            for chunk in block.chunks_mut(3 * BLOCK / 2 + 7) { b.process_block(chunk); }
+            assert!(max_diff(&per_sample, &block) < 1e-4, "shimmer {shimmer}");
+        }
+    }
+
+    #[test]
+    fn stereo_block_matches_per_sample() {
+        for shimmer in [0.0, 0.5] {
+            let left = burst_signal(48_000.0, 1);
+            let right = burst_signal(48_000.0, 2);
+            let (mut a, mut b) = (reverb(shimmer), reverb(shimmer));
+            let (per_l, per_r): (Vec<f32>, Vec<f32>) =
+                left.iter().zip(&right).map(|(&l, &r)| a.process_stereo(l, r)).unzip();
+            let (mut bl, mut br) = (left.clone(), right.clone());
+            for (cl, cr) in bl.chunks_mut(3 * BLOCK / 2 + 7).zip(br.chunks_mut(3 * BLOCK / 2 + 7)) {
+                b.process_stereo_block(cl, cr);
+            }
+            assert!(max_diff(&per_l, &bl) < 1e-4, "shimmer {shimmer}");
+            assert!(max_diff(&per_r, &br) < 1e-4, "shimmer {shimmer}");
+        }
+    }
+
+    #[test]
+    fn block_path_sleeps_through_silence() {
+        let mut r = reverb(0.0);
+        let mut buf = burst_signal(48_000.0, 3);
+        buf.truncate(42 * (0.5 * 48_000.0) as usize);
+        r.process_block(&mut buf);
+        assert!(r.is_asleep());
+        assert!(buf[buf.len() - BLOCK..].iter().all(|&x| x == 0.0));
     }
 }
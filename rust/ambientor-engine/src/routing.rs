@@ -0,0 +1,137 @@
+//! Output channel routing.
+//!
+//! Scenes render a stereo frame (mono scenes duplicate it). A [`ChannelMap`]
+//! decides what each **device** channel receives, so a host can e.g. send the
+//! scene only to channels 3–4 of an 8-channel interface and leave the rest silent.
+//!
+//! The map is a fixed-size table (no heap), cheap to copy into audio callbacks.
+
+/// Maximum number of device channels a [`ChannelMap`] can address.
+pub const MAX_CHANNELS: usize = 32;
+
+/// What a single device channel receives.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChannelSource {
+    /// Nothing (written as 0.0).
+    Silent,
+    /// Left side of the scene frame.
+    Left,
+    /// Right side of the scene frame.
+    Right,
+    /// Mono sum `(L + R) / 2`.
+    Mono,
+}
+
+impl ChannelSource {
+    /// Parse a single token: `L`, `R`, `M` (mono), or `-` / `0` (silent).
+    pub fn parse(tok: &str) -> Option<Self> {
+        match tok.trim().to_ascii_lowercase().as_str() {
+            "l" | "left" => Some(Self::Left),
+            "r" | "right" => Some(Self::Right),
+            "m" | "mono" => Some(Self::Mono),
+            "-" | "0" | "x" | "off" => Some(Self::Silent),
+            _ => None,
+        }
+    }
+}
+
+/// Per-device-channel routing table.
+///
+/// Channels past [`ChannelMap::channels`] are silent.
+#[derive(Copy, Clone, Debug)]
+pub struct ChannelMap {
+    src: [ChannelSource; MAX_CHANNELS],
+    len: usize,
+}
+
+impl ChannelMap {
+    /// All `channels` silent; fill in with [`ChannelMap::set`].
+    #[inline]
+    pub fn silent(channels: usize) -> Self {
+        Self { src: [ChannelSource::Silent; MAX_CHANNELS], len: channels.min(MAX_CHANNELS) }
+    }
+
+    /// Default layout: stereo pairs repeated across the device (L, R, L, R, …).
+    /// A single-channel device gets the mono sum. For mono scenes this is the
+    /// same as duplicating the sample to every channel. `None` for more than
+    /// [`MAX_CHANNELS`] channels, which the map cannot address.
+    #[inline]
+    pub fn stereo_pairs(channels: usize) -> Option<Self> {
+        if channels > MAX_CHANNELS { return None; }
+        let mut m = Self::silent(channels);
+        if m.len == 1 {
+            m.src[0] = ChannelSource::Mono;
+            return Some(m);
+        }
+        for (i, s) in m.src.iter_mut().take(m.len).enumerate() {
+            *s = if i % 2 == 0 { ChannelSource::Left } else { ChannelSource::Right };
+        }
+        Some(m)
+    }
+
+    /// Parse a comma-separated list, one token per device channel, e.g. `-,-,L,R`.
+    /// See [`ChannelSource::parse`] for tokens.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut m = Self::silent(0);
+        for tok in spec.split(',') {
+            if m.len >= MAX_CHANNELS {
+                return None;
+            }
+            m.src[m.len] = ChannelSource::parse(tok)?;
+            m.len += 1;
+        }
+        Some(m)
+    }
+
+    /// Route device channel `ch` from `src`. Grows the map if `ch` is past the end.
+    #[inline]
+    pub fn set(&mut self, ch: usize, src: ChannelSource) {
+        if ch < MAX_CHANNELS {
+            self.src[ch] = src;
+            self.len = self.len.max(ch + 1);
+        }
+    }
+
+    /// Number of device channels described by this map.
+    #[inline] pub fn channels(&self) -> usize { self.len }
+
+    /// Source of device channel `ch` (silent past the end).
+    #[inline]
+    pub fn source(&self, ch: usize) -> ChannelSource {
+        if ch < self.len { self.src[ch] } else { ChannelSource::Silent }
+    }
+
+    /// Sample for device channel `ch` given the scene frame `(l, r)`.
+    #[inline]
+    pub fn route(&self, ch: usize, l: f32, r: f32) -> f32 {
+        match self.source(ch) {
+            ChannelSource::Silent => 0.0,
+            ChannelSource::Left => l,
+            ChannelSource::Right => r,
+            ChannelSource::Mono => 0.5 * (l + r),
+        }
+    }
+
+    /// Write one interleaved frame (`frame.len()` = device channels).
+    #[inline]
+    pub fn write_frame(&self, frame: &mut [f32], l: f32, r: f32) {
+        for (ch, y) in frame.iter_mut().enumerate() {
+            *y = self.route(ch, l, r);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stereo_pairs_cover_every_channel_or_refuse() {
+        let m = ChannelMap::stereo_pairs(MAX_CHANNELS).unwrap();
+        assert_eq!(m.channels(), MAX_CHANNELS);
+        assert_eq!(m.source(MAX_CHANNELS - 2), ChannelSource::Left);
+        assert_eq!(m.source(MAX_CHANNELS - 1), ChannelSource::Right);
+        assert_eq!(ChannelMap::stereo_pairs(1).unwrap().source(0), ChannelSource::Mono);
+        assert!(ChannelMap::stereo_pairs(MAX_CHANNELS + 1).is_none());
+    }
+}
@@ -0,0 +1,187 @@
+//! "binaural": two steady sine tones a few Hz apart, one per ear.
+
+use crate::params::ParamId;
+use crate::graph::Generator;
+use crate::scenes::intensity::Intensity;
+use crate::scenes::TIME_WARP_RANGE;
+use crate::nodes::{OnePoleSmoother, PinkNoise};
+use ambientor_core::dsp::Tuning;
+use ambientor_core::filters::OnePoleLP;
+
+const TAU64: f64 = core::f64::consts::TAU;
+/// Noise-bed low-pass at default intensity.
+const BED_TONE_HZ: f32 = 2500.0;
+/// Starting values of the host parameters (see [`BinauralBeat::DEFAULTS`]).
+const DEFAULT_GAIN: f32 = 0.5;
+const DEFAULT_CARRIER_HZ: f32 = 200.0;
+const DEFAULT_BEAT_HZ: f32 = 10.0;
+const DEFAULT_BED: f32 = 0.0;
+
+/// Binaural beat generator for sleep/focus use:
+/// - left ear at `carrier − beat/2`, right ear at `carrier + beat/2`, so the
+///   perceived beat equals the offset exactly,
+/// - optional soft pink-noise bed (off by default).
+///
+/// Phases are accumulated in `f64` so the offset stays precise over hours of
+/// playback. Needs stereo (headphones); the mono `next()` sums both tones, which
+/// turns the effect into an audible amplitude beat instead.
+#[derive(Copy, Clone, Debug)]
+pub struct BinauralBeat {
+    phase_l: f64, // radians
+    phase_r: f64,
+    noise_l: PinkNoise,
+    noise_r: PinkNoise,
+    bed_lp_l: OnePoleLP,
+    bed_lp_r: OnePoleLP,
+    // parameters
+    sr: f32,
+    tuning: Tuning, // not used by the tones (given in Hz); kept across scene swaps
+    carrier_hz: f32,
+    beat_hz: f32,
+    bed: f32,
+    out_gain: f32,
+    trim: f32, // from Generator::set_output_trim
+    // smoothed controls
+    carrier_sm: OnePoleSmoother,
+    beat_sm: OnePoleSmoother,
+    bed_sm: OnePoleSmoother,
+    gain_sm: OnePoleSmoother,
+    intensity: Intensity,
+    warp: f32, // time warp on slow motion rates
+}
+
+impl BinauralBeat {
+    /// Host parameters as [`BinauralBeat::new`] sets them (intensity aside).
+    pub const DEFAULTS: [(ParamId, f32); 4] = [
+        (ParamId::SceneGain, DEFAULT_GAIN),
+        (ParamId::CarrierHz, DEFAULT_CARRIER_HZ),
+        (ParamId::BeatHz, DEFAULT_BEAT_HZ),
+        (ParamId::NoiseBed, DEFAULT_BED),
+    ];
+
+    pub fn new(sr: f32) -> Self {
+        let mut s = Self {
+            phase_l: 0.0,
+            phase_r: 0.0,
+            noise_l: PinkNoise::new(0xB1A1),
+            noise_r: PinkNoise::new(0x5EED),
+            bed_lp_l: OnePoleLP::new(BED_TONE_HZ, sr),
+            bed_lp_r: OnePoleLP::new(BED_TONE_HZ, sr),
+            sr,
+            tuning: Tuning::equal(),
+            carrier_hz: DEFAULT_CARRIER_HZ,
+            beat_hz: DEFAULT_BEAT_HZ, // alpha
+            bed: DEFAULT_BED,
+            out_gain: DEFAULT_GAIN,
+            trim: 1.0,
+            carrier_sm: OnePoleSmoother::new_ms(200.0, sr),
+            beat_sm: OnePoleSmoother::new_ms(200.0, sr),
+            bed_sm: OnePoleSmoother::new_ms(50.0, sr),
+            gain_sm: OnePoleSmoother::new_ms(30.0, sr),
+            intensity: Intensity::new(sr),
+            warp: 1.0,
+        };
+        s.snap_controls();
+        s
+    }
+
+    #[inline] pub fn set_gain(&mut self, g: f32) { if g.is_finite() { self.out_gain = g.clamp(0.0, 1.0); } }
+    #[inline] pub fn gain(&self) -> f32 { self.out_gain }
+
+    /// Center frequency between the ears (40..1000 Hz, default 200).
+    #[inline] pub fn set_carrier_hz(&mut self, hz: f32) { if hz.is_finite() { self.carrier_hz = hz.clamp(40.0, 1000.0); } }
+    #[inline] pub fn carrier_hz(&self) -> f32 { self.carrier_hz }
+
+    /// Beat rate = frequency offset between the ears (0.5..40 Hz, default 10).
+    #[inline] pub fn set_beat_hz(&mut self, hz: f32) { if hz.is_finite() { self.beat_hz = hz.clamp(0.5, 40.0); } }
+    #[inline] pub fn beat_hz(&self) -> f32 { self.beat_hz }
+
+    /// Level of the pink-noise bed, 0 (off) .. 1.
+    #[inline] pub fn set_noise_bed(&mut self, level: f32) { if level.is_finite() { self.bed = level.clamp(0.0, 1.0); } }
+    #[inline] pub fn noise_bed(&self) -> f32 { self.bed }
+
+    /// Intensity 0..1 (0.5 = default): noise-bed brightness and level. The
+    /// tones themselves are left alone so the beat stays exact.
+    #[inline] pub fn set_intensity(&mut self, x: f32) { self.intensity.set(x); }
+    #[inline] pub fn intensity(&self) -> f32 { self.intensity.target() }
+
+    /// Time warp 0.1..10 (see [`Scene::set_time_warp`](crate::scenes::Scene::set_time_warp)):
+    /// the tones have no slow motion, so this is only stored.
+    #[inline] pub fn set_time_warp(&mut self, w: f32) { if w.is_finite() { self.warp = w.clamp(TIME_WARP_RANGE.0, TIME_WARP_RANGE.1); } }
+    #[inline] pub fn time_warp(&self) -> f32 { self.warp }
+
+    #[inline] pub fn set_tuning(&mut self, t: Tuning) { self.tuning = t; }
+    #[inline] pub fn tuning(&self) -> &Tuning { &self.tuning }
+
+    fn snap_controls(&mut self) {
+        self.carrier_sm.reset(self.carrier_hz);
+        self.beat_sm.reset(self.beat_hz);
+        self.bed_sm.reset(self.bed);
+        self.gain_sm.reset(self.out_gain);
+        self.intensity.snap();
+    }
+}
+
+impl Generator for BinauralBeat {
+    #[inline]
+    fn reset(&mut self, sr: f32) {
+        self.set_sample_rate(sr);
+        self.phase_l = 0.0;
+        self.phase_r = 0.0;
+        self.bed_lp_l.reset();
+        self.bed_lp_r.reset();
+        self.snap_controls();
+    }
+
+    #[inline]
+    fn set_sample_rate(&mut self, sr: f32) -> bool {
+        self.sr = sr.max(1.0);
+        self.bed_lp_l.set_sample_rate(self.sr);
+        self.bed_lp_r.set_sample_rate(self.sr);
+        self.carrier_sm.set_time_ms(200.0, self.sr);
+        self.beat_sm.set_time_ms(200.0, self.sr);
+        self.bed_sm.set_time_ms(50.0, self.sr);
+        self.gain_sm.set_time_ms(30.0, self.sr);
+        self.intensity.set_sample_rate(self.sr);
+        false
+    }
+
+    #[inline]
+    fn next(&mut self) -> f32 {
+        let (l, r) = self.next_stereo();
+        0.5 * (l + r)
+    }
+
+    #[inline]
+    fn next_stereo(&mut self) -> (f32, f32) {
+        let carrier = f64::from(self.carrier_sm.process(self.carrier_hz));
+        let half_beat = 0.5 * f64::from(self.beat_sm.process(self.beat_hz));
+        let k = TAU64 / f64::from(self.sr);
+
+        self.phase_l = (self.phase_l + (carrier - half_beat) * k) % TAU64;
+        self.phase_r = (self.phase_r + (carrier + half_beat) * k) % TAU64;
+        let tone_l = self.phase_l.sin() as f32;
+        let tone_r = self.phase_r.sin() as f32;
+
+        // Independent (decorrelated) noise per ear, softened so it sits under the tones
+        if self.intensity.tick() {
+            let cut = BED_TONE_HZ * self.intensity.brightness();
+            self.bed_lp_l.set_cutoff_hz(cut);
+            self.bed_lp_r.set_cutoff_hz(cut);
+        }
+        let bed = self.bed_sm.process(self.bed);
+        let (nl, nr) = if bed > 0.0 {
+            (self.bed_lp_l.process(self.noise_l.next_sample()), self.bed_lp_r.process(self.noise_r.next_sample()))
+        } else {
+            (0.0, 0.0)
+        };
+
+        let g = self.gain_sm.process(self.out_gain * self.trim * self.intensity.level());
+        let l = 0.6 * tone_l + 0.8 * bed * nl;
+        let r = 0.6 * tone_r + 0.8 * bed * nr;
+        ((l * g).clamp(-1.0, 1.0), (r * g).clamp(-1.0, 1.0))
+    }
+
+    #[inline]
+    fn set_output_trim(&mut self, trim: f32) { self.trim = trim.clamp(0.0, 1.0); }
+}
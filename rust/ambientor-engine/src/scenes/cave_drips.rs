@@ -0,0 +1,256 @@
+//! "cave-drips": sparse pitched drips into long, dark echoes.
+
+use crate::event::{EventQueue, SceneEvent};
+use crate::params::ParamId;
+use crate::graph::{BusFrame, Generator};
+use crate::master::BusSettings;
+use crate::scenes::intensity::Intensity;
+use crate::scenes::TIME_WARP_RANGE;
+use crate::nodes::{Glide, OnePoleSmoother, Osc, PanLaw, Rng, Wave};
+use crate::triggers::{PoissonTrigger, Trigger};
+use ambientor_core::dsp::{Scale, Tuning};
+use ambientor_core::envelopes::ArExp;
+
+/// Concurrent drip voices.
+const VOICES: usize = 4;
+/// Lowest drip pitch (C5).
+const DRIP_ROOT_NOTE: f32 = 72.0;
+/// Feedback low-pass of the echo.
+const ECHO_TONE_HZ: f32 = 2200.0;
+/// Starting values of the host parameters (see [`CaveDrips::DEFAULTS`]).
+const DEFAULT_GAIN: f32 = 0.8;
+const DEFAULT_DENSITY: f32 = 12.0;
+const DEFAULT_WET: f32 = 0.6;
+
+/// One drip: a sine blip that "plinks" up into pitch under a short envelope.
+#[derive(Copy, Clone, Debug)]
+struct Drip {
+    osc: Osc,
+    glide: Glide,
+    env: ArExp,
+    gl: f32,
+    gr: f32,
+}
+
+/// Sparse water drops in a cavern:
+/// - Poisson-timed sine blips, pitched on a pentatonic set (through the tuning),
+///   each with a quick upward pitch flick and a random position,
+/// - sends to the engine's ping-pong echo (darkening repeats) and a very
+///   long, dark reverb.
+#[derive(Copy, Clone)]
+pub struct CaveDrips {
+    drips: [Drip; VOICES],
+    next_voice: usize,
+    trig: PoissonTrigger,
+    rng: Rng,
+    // parameters
+    sr: f32,
+    tuning: Tuning,
+    density: f32, // drips per minute
+    wet: f32,     // 0..1
+    out_gain: f32,
+    trim: f32, // from Generator::set_output_trim
+    gain_sm: OnePoleSmoother,
+    intensity: Intensity,
+    warp: f32, // time warp on slow motion rates
+    spawned: bool, // this frame's events already ran in `skip_idle`
+    events: EventQueue,
+}
+impl core::fmt::Debug for CaveDrips {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CaveDrips")
+            .field("sr", &self.sr)
+            .field("density", &self.density)
+            .field("wet", &self.wet)
+            .finish()
+    }
+}
+
+impl CaveDrips {
+    /// Host parameters as [`CaveDrips::new`] sets them (intensity aside).
+    pub const DEFAULTS: [(ParamId, f32); 3] = [
+        (ParamId::SceneGain, DEFAULT_GAIN),
+        (ParamId::DripDensity, DEFAULT_DENSITY),
+        (ParamId::Wetness, DEFAULT_WET),
+    ];
+
+    pub fn new(sr: f32) -> Self {
+        let d = Drip {
+            osc: Osc::new(440.0, Wave::Sine),
+            glide: Glide::new(440.0, 240.0),
+            env: ArExp::new(0.8, 90.0, sr),
+            gl: 0.0,
+            gr: 0.0,
+        };
+        let mut s = Self {
+            drips: [d; VOICES],
+            next_voice: 0,
+            trig: PoissonTrigger::new(12.0, 0xD21B),
+            rng: Rng::new(0xCA7E),
+            sr,
+            tuning: Tuning::equal(),
+            density: DEFAULT_DENSITY,
+            wet: DEFAULT_WET,
+            out_gain: DEFAULT_GAIN,
+            trim: 1.0,
+            gain_sm: OnePoleSmoother::new_ms(30.0, sr),
+            intensity: Intensity::new(sr),
+            warp: 1.0,
+            spawned: false,
+            events: EventQueue::new(),
+        };
+        s.gain_sm.reset(s.out_gain);
+        s
+    }
+
+    #[inline] pub fn set_gain(&mut self, g: f32) { if g.is_finite() { self.out_gain = g.clamp(0.0, 1.0); } }
+    #[inline] pub fn gain(&self) -> f32 { self.out_gain }
+
+    /// Mean drips per minute (0..240).
+    #[inline]
+    pub fn set_density(&mut self, per_min: f32) {
+        if !per_min.is_finite() { return; }
+        self.density = per_min.clamp(0.0, 240.0);
+        self.trig.set_density(self.density * self.intensity.density());
+    }
+    #[inline] pub fn density(&self) -> f32 { self.density }
+
+    /// Echo + reverb amount, 0 = dry drips .. 1 = mostly cave.
+    #[inline] pub fn set_wetness(&mut self, w: f32) { if w.is_finite() { self.wet = w.clamp(0.0, 1.0); } }
+    #[inline] pub fn wetness(&self) -> f32 { self.wet }
+
+    /// Intensity 0..1 (0.5 = default): drip density and level.
+    #[inline] pub fn set_intensity(&mut self, x: f32) { self.intensity.set(x); }
+    #[inline] pub fn intensity(&self) -> f32 { self.intensity.target() }
+
+    /// Time warp 0.1..10 (see [`Scene::set_time_warp`](crate::scenes::Scene::set_time_warp)):
+    /// drips keep the density set for them, so this is only stored.
+    #[inline] pub fn set_time_warp(&mut self, w: f32) { if w.is_finite() { self.warp = w.clamp(TIME_WARP_RANGE.0, TIME_WARP_RANGE.1); } }
+    #[inline] pub fn time_warp(&self) -> f32 { self.warp }
+
+    /// Push the current intensity into the drip rate.
+    fn apply_intensity(&mut self) {
+        self.trig.set_density(self.density * self.intensity.density());
+    }
+
+    #[inline] pub fn set_tuning(&mut self, t: Tuning) { self.tuning = t; }
+    #[inline] pub fn tuning(&self) -> &Tuning { &self.tuning }
+
+    fn spawn(&mut self) {
+        let degree = (self.rng.next_u32() % 10) as i32; // two octaves of pentatonic
+        let note = DRIP_ROOT_NOTE + Scale::PENTATONIC.degree_to_semitones(degree);
+        let hz = self.tuning.note_to_hz(note);
+        let (gl, gr) = PanLaw::gains(0.8 * self.rng.next_bipolar());
+        let vel = 0.4 + 0.6 * self.rng.next01();
+        let decay = 50.0 + 130.0 * self.rng.next01();
+
+        let d = &mut self.drips[self.next_voice];
+        d.glide.snap_hz(hz * 0.8); // starts ~4 semitones flat and flicks up
+        d.glide.set_target_hz(hz);
+        d.osc.set_phase01(0.0);
+        d.env.set_params(0.8, decay);
+        d.env.trigger();
+        d.gl = gl * vel;
+        d.gr = gr * vel;
+        self.events.push(SceneEvent::NoteTriggered { pitch: hz, amp: vel });
+        self.next_voice = (self.next_voice + 1) % VOICES;
+    }
+}
+
+impl Generator for CaveDrips {
+    #[inline]
+    fn reset(&mut self, sr: f32) {
+        self.set_sample_rate(sr);
+        self.spawned = false;
+        self.events.clear();
+        self.gain_sm.reset(self.out_gain);
+        self.intensity.snap();
+        self.apply_intensity();
+    }
+
+    #[inline]
+    fn set_sample_rate(&mut self, sr: f32) -> bool {
+        self.sr = sr.max(1.0);
+        for d in &mut self.drips { d.env.set_sr(self.sr); }
+        self.gain_sm.set_time_ms(30.0, self.sr);
+        self.intensity.set_sample_rate(self.sr);
+        false
+    }
+
+    #[inline]
+    fn next(&mut self) -> f32 {
+        let (l, r) = self.next_stereo();
+        0.5 * (l + r)
+    }
+
+    /// Dry frame only; the engine adds the room from [`Generator::next_bus`].
+    #[inline]
+    fn next_stereo(&mut self) -> (f32, f32) { self.next_bus().dry }
+
+    #[inline]
+    fn next_bus(&mut self) -> BusFrame {
+        let sr = self.sr;
+        if self.spawned {
+            self.spawned = false;
+        } else {
+            if self.intensity.tick() { self.apply_intensity(); }
+            if self.trig.tick(sr) { self.spawn(); }
+        }
+
+        let (mut l, mut r) = (0.0, 0.0);
+        for d in &mut self.drips {
+            let e = d.env.next();
+            if e > 0.0 {
+                d.osc.set_freq(d.glide.next(sr));
+                let x = d.osc.next(sr) * e;
+                l += x * d.gl;
+                r += x * d.gr;
+            }
+        }
+
+        let g = self.gain_sm.process(self.out_gain * self.trim * self.intensity.level());
+        let (l, r) = (l * g, r * g);
+        let dry = 1.0 - 0.6 * self.wet;
+        BusFrame {
+            dry: ((dry * l).clamp(-1.0, 1.0), (dry * r).clamp(-1.0, 1.0)),
+            reverb: (1.2 * self.wet * l, 1.2 * self.wet * r),
+            delay: (0.6 * self.wet * l, 0.6 * self.wet * r),
+        }
+    }
+
+    /// Long, dark echoes that wash on into a very long reverb.
+    fn bus_settings(&self) -> BusSettings {
+        BusSettings {
+            reverb_room: 1.0,
+            reverb_damp: 0.9,
+            delay_time_s: 0.43,
+            delay_feedback: 0.55,
+            delay_tone_hz: ECHO_TONE_HZ,
+            delay_to_reverb: 1.0,
+        }
+    }
+
+    #[inline]
+    fn set_output_trim(&mut self, trim: f32) { self.trim = trim.clamp(0.0, 1.0); }
+
+    #[inline] fn pop_event(&mut self) -> Option<SceneEvent> { self.events.pop() }
+
+    /// Idle between drips, once every voice has died away.
+    #[inline]
+    fn is_idle(&self) -> bool { !self.spawned && self.drips.iter().all(|d| d.env.is_idle()) }
+
+    /// Run only the drip timer (and the smoothing it would have seen) until
+    /// the next drip.
+    fn skip_idle(&mut self, max_frames: usize) -> usize {
+        for i in 0..max_frames {
+            if self.intensity.tick() { self.apply_intensity(); }
+            if self.trig.tick(self.sr) {
+                self.spawn();
+                self.spawned = true;
+                return i;
+            }
+            self.gain_sm.process(self.out_gain * self.trim * self.intensity.level());
+        }
+        max_frames
+    }
+}
@@ -0,0 +1,389 @@
+//! Declarative scene files (feature `scene-file`).
+//!
+//! A TOML file describes nodes, the connections between them, modulation
+//! routings, and the output stage; [`SceneDesc::compile`] turns it into a
+//! [`GraphScene`] at load time. Hosts reload by compiling the file again and
+//! swapping the result in (see `Engine::swap_scene`).
+//!
+//! ```toml
+//! name = "glass-pad"
+//! gain = 0.5          # output level 0..1
+//! reverb_send = 0.35  # share of the mix sent to the engine reverb
+//! delay_send = 0.1
+//!
+//! [bus]               # optional: engine reverb/delay character
+//! reverb_room = 0.9
+//!
+//! [[node]]
+//! id = "osc"
+//! type = "osc"        # osc, noise, lfo, drift, lowpass, highpass, bandpass, notch,
+//!                     # xover-low, xover-high, gain, saturate, ringmod, freqshift, exciter,
+//!                     # autowah, chaos
+//! wave = "saw"
+//! note = 45           # or freq = 110.0
+//! level = 0.4
+//!
+//! [[node]]
+//! id = "wobble"
+//! type = "lfo"
+//! shape = "sine"
+//! rate = 0.05
+//!
+//! [[node]]
+//! id = "lp"
+//! type = "lowpass"
+//! cutoff = 900.0
+//!
+//! [[connect]]
+//! from = "osc"
+//! to = "lp"
+//!
+//! [[connect]]
+//! from = "lp"
+//! to = "out"          # the scene output; optional `pan` -1..1
+//!
+//! [[mod]]
+//! source = "wobble"
+//! target = "lp.cutoff"
+//! depth = 400.0
+//! ```
+//!
+//! Any node parameter may be a string instead of a number: an expression (see
+//! [`crate::expr`]) of `t` (seconds), `elapsed` (seconds of engine run time,
+//! which carries on across scene reloads), `bpm` (host tempo), `intensity`, and
+//! `noise1`..`noise4`, e.g. `cutoff = "800 + 400*sin(0.05*t) + 200*noise1"`,
+//! `rate = "bpm / 240"` (one cycle per 4/4 bar), or a layer that fades in over
+//! a minute after ten: `level = "clamp((elapsed - 600) / 60, 0, 1)"`.
+//!
+//! Nodes may be listed in any order; they run in dependency order, and a
+//! connection or modulation that would close a loop is an error.
+
+use crate::effects::WahDirection;
+use crate::expr::Expr;
+use crate::master::BusSettings;
+use crate::nodes::{ChaosMap, LfoShape, Wave};
+use crate::scenes::graph_scene::{CrossoverBand, FilterMode, GraphError, GraphScene, NodeId, NodeKind, Param};
+use serde::Deserialize;
+use std::fmt;
+use std::path::Path;
+
+/// Id that names the scene output in `[[connect]]` tables.
+pub const OUTPUT_ID: &str = "out";
+
+/// Why a scene file could not be loaded.
+#[derive(Debug)]
+pub enum SceneFileError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    /// Parsed, but describes something the graph can't build.
+    Invalid(String),
+}
+
+impl fmt::Display for SceneFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneFileError::Io(e) => write!(f, "cannot read scene file: {e}"),
+            SceneFileError::Parse(e) => write!(f, "invalid scene file: {e}"),
+            SceneFileError::Invalid(msg) => write!(f, "invalid scene: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for SceneFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SceneFileError::Io(e) => Some(e),
+            SceneFileError::Parse(e) => Some(e),
+            SceneFileError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SceneFileError {
+    fn from(e: std::io::Error) -> Self { SceneFileError::Io(e) }
+}
+
+impl From<toml::de::Error> for SceneFileError {
+    fn from(e: toml::de::Error) -> Self { SceneFileError::Parse(e) }
+}
+
+/// Top level of a scene file.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SceneDesc {
+    pub name: Option<String>,
+    pub gain: Option<f32>,
+    pub reverb_send: Option<f32>,
+    pub delay_send: Option<f32>,
+    pub bus: Option<BusDesc>,
+    #[serde(default, rename = "node")]
+    pub nodes: Vec<NodeDesc>,
+    #[serde(default, rename = "connect")]
+    pub connections: Vec<ConnectionDesc>,
+    #[serde(default, rename = "mod")]
+    pub mods: Vec<ModDesc>,
+}
+
+/// `[bus]`: overrides for the engine's shared reverb/delay (see [`BusSettings`]).
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BusDesc {
+    pub reverb_room: Option<f32>,
+    pub reverb_damp: Option<f32>,
+    pub delay_time_s: Option<f32>,
+    pub delay_feedback: Option<f32>,
+    pub delay_tone_hz: Option<f32>,
+    pub delay_to_reverb: Option<f32>,
+}
+
+/// `[[node]]`: one node and its base parameters.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NodeDesc {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Oscillator (or ring-mod carrier) waveform: sine, tri, saw.
+    pub wave: Option<String>,
+    /// LFO shape: sine, tri, saw, square, sample-hold, exp-rise, exp-fall.
+    pub shape: Option<String>,
+    /// Oscillator pitch as a (fractional) MIDI note, through the scene tuning.
+    pub note: Option<f32>,
+    pub seed: Option<u32>,
+    /// Drift octaves (1..8).
+    pub octaves: Option<u32>,
+    /// Chaos attractor: lorenz, logistic.
+    pub map: Option<String>,
+    /// Auto-wah sweep as the input gets louder: up (default), down.
+    pub direction: Option<String>,
+    pub freq: Option<ParamValue>,
+    pub detune: Option<ParamValue>,
+    pub rate: Option<ParamValue>,
+    pub cutoff: Option<ParamValue>,
+    pub q: Option<ParamValue>,
+    pub drive: Option<ParamValue>,
+    pub depth: Option<ParamValue>,
+    pub dc: Option<ParamValue>,
+    pub mix: Option<ParamValue>,
+    pub chaos: Option<ParamValue>,
+    pub shift: Option<ParamValue>,
+    pub feedback: Option<ParamValue>,
+    pub amount: Option<ParamValue>,
+    pub range: Option<ParamValue>,
+    pub sensitivity: Option<ParamValue>,
+    pub level: Option<ParamValue>,
+}
+
+impl NodeDesc {
+    fn param(&self, p: Param) -> Option<&ParamValue> {
+        match p {
+            Param::Freq => self.freq.as_ref(),
+            Param::Detune => self.detune.as_ref(),
+            Param::Rate => self.rate.as_ref(),
+            Param::Cutoff => self.cutoff.as_ref(),
+            Param::Q => self.q.as_ref(),
+            Param::Drive => self.drive.as_ref(),
+            Param::Depth => self.depth.as_ref(),
+            Param::Dc => self.dc.as_ref(),
+            Param::Mix => self.mix.as_ref(),
+            Param::Chaos => self.chaos.as_ref(),
+            Param::Shift => self.shift.as_ref(),
+            Param::Feedback => self.feedback.as_ref(),
+            Param::Amount => self.amount.as_ref(),
+            Param::Range => self.range.as_ref(),
+            Param::Sensitivity => self.sensitivity.as_ref(),
+            Param::Level => self.level.as_ref(),
+        }
+    }
+}
+
+/// A node parameter: a fixed value or an expression string.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum ParamValue {
+    Value(f32),
+    Expr(String),
+}
+
+/// `[[connect]]`: audio from one node into another, or into `out`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectionDesc {
+    pub from: String,
+    pub to: String,
+    #[serde(default = "unity")]
+    pub gain: f32,
+    /// Output connections only: -1 (left) .. +1 (right).
+    #[serde(default)]
+    pub pan: f32,
+}
+
+/// `[[mod]]`: `depth × source` added to `target` (`"node.param"`); for a
+/// `.rate` target, depth is in octaves (see [`GraphScene::modulate`]).
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModDesc {
+    pub source: String,
+    pub target: String,
+    pub depth: f32,
+}
+
+fn unity() -> f32 { 1.0 }
+
+fn parse_wave(s: &str) -> Option<Wave> {
+    match s {
+        "sine" => Some(Wave::Sine),
+        "tri" | "triangle" => Some(Wave::Tri),
+        "saw" => Some(Wave::Saw),
+        _ => None,
+    }
+}
+
+fn parse_shape(s: &str) -> Option<LfoShape> {
+    match s {
+        "sine" => Some(LfoShape::Sine),
+        "tri" | "triangle" => Some(LfoShape::Tri),
+        "saw" => Some(LfoShape::Saw),
+        "square" => Some(LfoShape::Square),
+        "sample-hold" | "sh" => Some(LfoShape::SampleHold),
+        "exp-rise" => Some(LfoShape::ExpRise),
+        "exp-fall" => Some(LfoShape::ExpFall),
+        _ => None,
+    }
+}
+
+fn parse_map(s: &str) -> Option<ChaosMap> {
+    match s {
+        "lorenz" => Some(ChaosMap::Lorenz),
+        "logistic" => Some(ChaosMap::Logistic),
+        _ => None,
+    }
+}
+
+fn parse_direction(s: &str) -> Option<WahDirection> {
+    match s {
+        "up" => Some(WahDirection::Up),
+        "down" => Some(WahDirection::Down),
+        _ => None,
+    }
+}
+
+fn invalid(msg: impl Into<String>) -> SceneFileError { SceneFileError::Invalid(msg.into()) }
+
+impl SceneDesc {
+    /// Parse TOML text.
+    pub fn parse(text: &str) -> Result<Self, SceneFileError> { Ok(toml::from_str(text)?) }
+
+    /// Read and parse a scene file.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, SceneFileError> { Self::parse(&std::fs::read_to_string(path)?) }
+
+    /// Build the scene at `sr`.
+    pub fn compile(&self, sr: f32) -> Result<GraphScene, SceneFileError> {
+        let mut g = GraphScene::new(self.name.as_deref().unwrap_or("graph"), sr);
+        let mut ids: Vec<&str> = Vec::with_capacity(self.nodes.len());
+        for n in &self.nodes {
+            if n.id == OUTPUT_ID || ids.contains(&n.id.as_str()) {
+                return Err(invalid(format!("duplicate or reserved node id '{}'", n.id)));
+            }
+            let id = g.add_node(node_kind(n)?).map_err(|e| graph_error(e, &ids))?;
+            for p in Param::ALL {
+                let no_param = |_| invalid(format!("node '{}' ({}) has no parameter '{}'", n.id, n.kind, p.name()));
+                match n.param(p) {
+                    Some(ParamValue::Value(v)) => g.set_param(id, p, *v).map_err(no_param)?,
+                    Some(ParamValue::Expr(text)) => {
+                        let e = Expr::parse(text).map_err(|e| invalid(format!("node '{}' {}: {e}", n.id, p.name())))?;
+                        g.set_param_expr(id, p, e).map_err(no_param)?;
+                    }
+                    None => {}
+                }
+            }
+            ids.push(&n.id);
+        }
+        let lookup = |name: &str| ids.iter().position(|&i| i == name).ok_or_else(|| invalid(format!("unknown node '{name}'")));
+        for c in &self.connections {
+            let from = lookup(&c.from)?;
+            let r = if c.to == OUTPUT_ID { g.connect_output(from, c.gain, c.pan) } else { g.connect(from, lookup(&c.to)?, c.gain) };
+            r.map_err(|e| graph_error(e, &ids))?;
+        }
+        for m in &self.mods {
+            let (node, param) = m.target.split_once('.').ok_or_else(|| invalid(format!("mod target '{}' is not node.param", m.target)))?;
+            let param = Param::parse(param).ok_or_else(|| invalid(format!("unknown parameter in '{}'", m.target)))?;
+            g.modulate(lookup(&m.source)?, lookup(node)?, param, m.depth).map_err(|e| graph_error(e, &ids))?;
+        }
+
+        if let Some(x) = self.gain { g.set_gain(x); }
+        g.set_sends(self.reverb_send.unwrap_or(0.25), self.delay_send.unwrap_or(0.0));
+        if let Some(b) = &self.bus {
+            let d = BusSettings::default();
+            g.set_bus(BusSettings {
+                reverb_room: b.reverb_room.unwrap_or(d.reverb_room),
+                reverb_damp: b.reverb_damp.unwrap_or(d.reverb_damp),
+                delay_time_s: b.delay_time_s.unwrap_or(d.delay_time_s),
+                delay_feedback: b.delay_feedback.unwrap_or(d.delay_feedback),
+                delay_tone_hz: b.delay_tone_hz.unwrap_or(d.delay_tone_hz),
+                delay_to_reverb: b.delay_to_reverb.unwrap_or(d.delay_to_reverb),
+            });
+        }
+        g.mark_defaults();
+        Ok(g)
+    }
+}
+
+fn node_kind(n: &NodeDesc) -> Result<NodeKind, SceneFileError> {
+    let seed = n.seed.unwrap_or(1);
+    let filter = |mode| Ok(NodeKind::Filter { mode });
+    let wave = || {
+        let w = n.wave.as_deref().unwrap_or("sine");
+        parse_wave(w).ok_or_else(|| invalid(format!("node '{}': unknown wave '{w}'", n.id)))
+    };
+    match n.kind.as_str() {
+        "osc" => Ok(NodeKind::Osc { wave: wave()?, note: n.note }),
+        "noise" => Ok(NodeKind::Noise { seed }),
+        "lfo" => {
+            let s = n.shape.as_deref().unwrap_or("sine");
+            let shape = parse_shape(s).ok_or_else(|| invalid(format!("node '{}': unknown shape '{s}'", n.id)))?;
+            Ok(NodeKind::Lfo { shape, seed })
+        }
+        "drift" => Ok(NodeKind::Drift { octaves: n.octaves.unwrap_or(2), seed }),
+        "chaos" => {
+            let m = n.map.as_deref().unwrap_or("lorenz");
+            let map = parse_map(m).ok_or_else(|| invalid(format!("node '{}': unknown map '{m}'", n.id)))?;
+            Ok(NodeKind::Chaos { map, seed })
+        }
+        "lowpass" => filter(FilterMode::Lowpass),
+        "highpass" => filter(FilterMode::Highpass),
+        "bandpass" => filter(FilterMode::Bandpass),
+        "notch" => filter(FilterMode::Notch),
+        "xover-low" => Ok(NodeKind::Crossover { band: CrossoverBand::Low }),
+        "xover-high" => Ok(NodeKind::Crossover { band: CrossoverBand::High }),
+        "gain" => Ok(NodeKind::Gain),
+        "saturate" => Ok(NodeKind::Saturate),
+        "ringmod" => Ok(NodeKind::RingMod { wave: wave()? }),
+        "freqshift" => Ok(NodeKind::FreqShift),
+        "exciter" => Ok(NodeKind::Exciter),
+        "autowah" => {
+            let d = n.direction.as_deref().unwrap_or("up");
+            let direction = parse_direction(d).ok_or_else(|| invalid(format!("node '{}': unknown direction '{d}'", n.id)))?;
+            Ok(NodeKind::AutoWah { direction })
+        }
+        other => Err(invalid(format!("node '{}': unknown type '{other}'", n.id))),
+    }
+}
+
+/// `e` with node ids spelled out.
+fn graph_error(e: GraphError, ids: &[&str]) -> SceneFileError {
+    let name = |n: NodeId| ids.get(n).copied().unwrap_or("?");
+    invalid(match e {
+        GraphError::Full => "too many nodes, connections, outputs, or mods".to_string(),
+        GraphError::NoNode(n) => format!("unknown node '{}'", name(n)),
+        GraphError::NoParam(n, p) => format!("node '{}' has no parameter '{}'", name(n), p.name()),
+        GraphError::NoInput(n) => format!("node '{}' takes no input", name(n)),
+        GraphError::Cycle { from, to } => format!("'{}' → '{}' would create a cycle", name(from), name(to)),
+        GraphError::NotFinite => "value is not a finite number".to_string(),
+    })
+}
+
+/// Read and compile a scene file at `sr`.
+pub fn load(path: impl AsRef<Path>, sr: f32) -> Result<GraphScene, SceneFileError> {
+    SceneDesc::read(path)?.compile(sr)
+}
@@ -0,0 +1,1213 @@
+//! "graph": a scene wired at runtime from nodes, connections, and modulation
+//! routings instead of hand-written Rust — usually compiled from a scene file.
+//!
+//! Storage is a fixed-capacity arena of node slots, so a `GraphScene` is
+//! `Copy` and renders without touching the heap, like the built-in scenes.
+//! Nodes, connections, and modulations can be added and removed at any time
+//! (an editor rewires a live graph through the same calls a scene file uses);
+//! each edit re-sorts the nodes topologically, so every node runs after the
+//! nodes it reads, and edits that would close a cycle are refused.
+//!
+//! Parameters are control-rate: expressions and modulation sums are worked
+//! out once every [`CONTROL_BLOCK`] samples, and pitch, rate, drive, level,
+//! and filter coefficients glide linearly to the new values across the block,
+//! so modulation stays smooth without per-sample `exp`/`tan` math. A
+//! parameter can also follow an [`Expr`] of time, intensity, and noise.
+
+use crate::effects::{AutoWah, Exciter, FreqShifter, WahDirection, WAH_RANGE_HZ};
+use crate::expr::{Expr, ExprInputs, NOISE_VARS};
+use crate::graph::{BusFrame, Generator, RenderCtx, DEFAULT_TEMPO_BPM};
+use crate::master::BusSettings;
+use crate::profile::{scope, Stage};
+use crate::nodes::{ChaosMap, ChaosMod, ControlClock, ControlRamp, Lfo, LfoShape, OnePoleSmoother, Osc, PinkNoise, RingMod, SmoothNoise, Wave};
+use crate::scenes::intensity::{Intensity, INTENSITY_DEFAULT};
+use crate::scenes::TIME_WARP_RANGE;
+use ambientor_core::dsp::{saturate, Tuning};
+use ambientor_core::filters::{Crossover, SvfMode, SvfTpt};
+use core::fmt;
+
+/// Node slots per scene.
+pub const MAX_NODES: usize = 32;
+/// Node-to-node connections per scene.
+pub const MAX_CONNECTIONS: usize = 64;
+/// Node-to-output connections per scene.
+pub const MAX_OUTPUTS: usize = 16;
+/// Modulation routings per scene.
+pub const MAX_MODS: usize = 32;
+/// Expression-driven parameters per scene.
+pub const MAX_EXPRS: usize = 16;
+/// Largest depth of a [`Param::Rate`] modulation (octaves per unit of source).
+pub const MAX_RATE_MOD_OCT: f32 = 4.0;
+pub use crate::nodes::CONTROL_BLOCK;
+/// Longest scene name kept (bytes; longer names are cut).
+pub const MAX_NAME_LEN: usize = 32;
+
+/// Slot of a node, as returned by [`GraphScene::add_node`]. Slots of removed
+/// nodes are reused.
+pub type NodeId = usize;
+
+/// Node parameters that can be set and modulated. Not every node has every one
+/// (see [`NodeKind::has_param`]).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Param {
+    /// Oscillator or ring-mod carrier frequency (Hz).
+    Freq,
+    /// Oscillator detune (cents).
+    Detune,
+    /// LFO / drift rate (Hz). Modulation of it is exponential: depth is in
+    /// octaves, so one LFO can sweep another's rate without stalling it.
+    Rate,
+    /// Filter cutoff, crossover or exciter frequency, or the bottom of an
+    /// auto-wah sweep (Hz).
+    Cutoff,
+    /// Filter or auto-wah resonance.
+    Q,
+    /// Saturation or exciter drive.
+    Drive,
+    /// Ring-mod carrier amplitude (0..1).
+    Depth,
+    /// Ring-mod carrier DC offset (0..1).
+    Dc,
+    /// Ring-mod, frequency-shifter or auto-wah dry/wet balance (0..1).
+    Mix,
+    /// Chaos-source amount: 0 regular .. 1 fully chaotic.
+    Chaos,
+    /// Frequency shift (Hz, negative = down).
+    Shift,
+    /// Frequency-shifter feedback (0..0.9).
+    Feedback,
+    /// Exciter harmonics mixed back in (0..1).
+    Amount,
+    /// Auto-wah sweep width above `cutoff` (octaves).
+    Range,
+    /// Auto-wah sensitivity: 0 sweeps fully at 0 dBFS .. 1 at -40 dBFS.
+    Sensitivity,
+    /// Output multiplier; every node has it.
+    Level,
+}
+
+const PARAMS: usize = Param::ALL.len();
+
+impl Param {
+    pub const ALL: [Param; 16] = [
+        Param::Freq,
+        Param::Detune,
+        Param::Rate,
+        Param::Cutoff,
+        Param::Q,
+        Param::Drive,
+        Param::Depth,
+        Param::Dc,
+        Param::Mix,
+        Param::Chaos,
+        Param::Shift,
+        Param::Feedback,
+        Param::Amount,
+        Param::Range,
+        Param::Sensitivity,
+        Param::Level,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Param::Freq => "freq",
+            Param::Detune => "detune",
+            Param::Rate => "rate",
+            Param::Cutoff => "cutoff",
+            Param::Q => "q",
+            Param::Drive => "drive",
+            Param::Depth => "depth",
+            Param::Dc => "dc",
+            Param::Mix => "mix",
+            Param::Chaos => "chaos",
+            Param::Shift => "shift",
+            Param::Feedback => "feedback",
+            Param::Amount => "amount",
+            Param::Range => "range",
+            Param::Sensitivity => "sensitivity",
+            Param::Level => "level",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> { Self::ALL.into_iter().find(|p| p.name() == s) }
+}
+
+/// Response of a [`NodeKind::Filter`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FilterMode {
+    Lowpass,
+    Highpass,
+    Bandpass,
+    Notch,
+}
+
+/// Band of a [`NodeKind::Crossover`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CrossoverBand {
+    Low,
+    High,
+}
+
+/// What a node does. Audio nodes sum their inputs first; sources ignore inputs.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum NodeKind {
+    /// Oscillator at `freq` Hz, or at MIDI `note` through the scene tuning.
+    Osc { wave: Wave, note: Option<f32> },
+    /// Pink noise.
+    Noise { seed: u32 },
+    /// LFO at `rate` Hz, -1..1; meant as a modulation source.
+    Lfo { shape: LfoShape, seed: u32 },
+    /// Smooth random wander at `rate` Hz, about -1..1; meant as a modulation source.
+    Drift { octaves: u32, seed: u32 },
+    /// Chaotic attractor at `rate` cycles/s, -1..1 (see [`ChaosMod`]); meant
+    /// as a modulation source.
+    Chaos { map: ChaosMap, seed: u32 },
+    /// State-variable filter (cutoff follows the scene's intensity brightness).
+    Filter { mode: FilterMode },
+    /// One band of an LR4 crossover at `cutoff` Hz. A low and a high node fed
+    /// the same signal at the same cutoff sum back flat, so bands can be
+    /// processed apart (e.g. saturate only the lows) and remixed.
+    Crossover { band: CrossoverBand },
+    /// Inputs times `level` (a mixer / VCA).
+    Gain,
+    /// Soft saturation by `drive`.
+    Saturate,
+    /// Inputs times a `wave` carrier at `freq` Hz (see [`RingMod`]): ring
+    /// modulation, or tremolo with `dc = 1 - depth`.
+    RingMod { wave: Wave },
+    /// Inputs moved up (or down) by `shift` Hz, every partial by the same
+    /// amount (see [`FreqShifter`]): metallic drones, or with `mix` 0.5 and
+    /// a sub-Hz shift, endless barberpole phasing.
+    FreqShift,
+    /// Inputs with harmonics of everything above `cutoff` Hz added by
+    /// `amount` (see [`Exciter`]); starts at 3 kHz, drive 10, amount 0.5.
+    /// Air for dark pads at the same level.
+    Exciter,
+    /// Resonant band-pass swept by the inputs' level (see [`AutoWah`]):
+    /// from `cutoff` up `range` octaves as they get louder (or down from the
+    /// top with [`WahDirection::Down`]). Starts at 300 Hz, 3.3 octaves, Q 3,
+    /// sensitivity 0.5. Movement for rain, bells and other hits.
+    AutoWah { direction: WahDirection },
+}
+
+impl NodeKind {
+    /// `true` if nodes of this kind have `p`.
+    pub fn has_param(&self, p: Param) -> bool {
+        match self {
+            NodeKind::Osc { .. } => matches!(p, Param::Freq | Param::Detune | Param::Level),
+            NodeKind::Noise { .. } | NodeKind::Gain => p == Param::Level,
+            NodeKind::Lfo { .. } | NodeKind::Drift { .. } => matches!(p, Param::Rate | Param::Level),
+            NodeKind::Chaos { .. } => matches!(p, Param::Rate | Param::Chaos | Param::Level),
+            NodeKind::Filter { .. } => matches!(p, Param::Cutoff | Param::Q | Param::Level),
+            NodeKind::Crossover { .. } => matches!(p, Param::Cutoff | Param::Level),
+            NodeKind::Saturate => matches!(p, Param::Drive | Param::Level),
+            NodeKind::RingMod { .. } => matches!(p, Param::Freq | Param::Depth | Param::Dc | Param::Mix | Param::Level),
+            NodeKind::FreqShift => matches!(p, Param::Shift | Param::Feedback | Param::Mix | Param::Level),
+            NodeKind::Exciter => matches!(p, Param::Cutoff | Param::Drive | Param::Amount | Param::Level),
+            NodeKind::AutoWah { .. } => {
+                matches!(p, Param::Cutoff | Param::Range | Param::Q | Param::Sensitivity | Param::Mix | Param::Level)
+            }
+        }
+    }
+
+    /// Where [`crate::profile`] counts the node's processing time.
+    fn stage(&self) -> Stage {
+        match self {
+            NodeKind::Osc { .. } | NodeKind::Noise { .. } => Stage::Sources,
+            NodeKind::Lfo { .. } | NodeKind::Drift { .. } | NodeKind::Chaos { .. } => Stage::Modulators,
+            NodeKind::Filter { .. } | NodeKind::Crossover { .. } => Stage::Filters,
+            NodeKind::Gain
+            | NodeKind::Saturate
+            | NodeKind::RingMod { .. }
+            | NodeKind::FreqShift
+            | NodeKind::Exciter
+            | NodeKind::AutoWah { .. } => Stage::NodeFx,
+        }
+    }
+
+    /// `true` if the node processes its summed inputs.
+    pub fn takes_input(&self) -> bool {
+        matches!(
+            self,
+            NodeKind::Filter { .. }
+                | NodeKind::Crossover { .. }
+                | NodeKind::Gain
+                | NodeKind::Saturate
+                | NodeKind::RingMod { .. }
+                | NodeKind::FreqShift
+                | NodeKind::Exciter
+                | NodeKind::AutoWah { .. }
+        )
+    }
+}
+
+/// Why a graph edit was refused.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GraphError {
+    /// The node, connection, output, or modulation table is full.
+    Full,
+    /// No node with this id.
+    NoNode(NodeId),
+    /// The node has no such parameter.
+    NoParam(NodeId, Param),
+    /// The target node ignores audio input.
+    NoInput(NodeId),
+    /// `from` → `to` would close a feedback loop.
+    Cycle { from: NodeId, to: NodeId },
+    /// A value, gain, pan or depth is NaN or infinite.
+    NotFinite,
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::Full => write!(f, "graph capacity exceeded"),
+            GraphError::NoNode(n) => write!(f, "no node #{n}"),
+            GraphError::NoParam(n, p) => write!(f, "node #{n} has no parameter '{}'", p.name()),
+            GraphError::NoInput(n) => write!(f, "node #{n} takes no input"),
+            GraphError::Cycle { from, to } => write!(f, "node #{from} → node #{to} would create a cycle"),
+            GraphError::NotFinite => write!(f, "value is not a finite number"),
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+/// Running state for a node.
+#[derive(Copy, Clone, Debug)]
+enum State {
+    Osc(Osc),
+    Noise(PinkNoise),
+    Lfo(Lfo),
+    Drift(SmoothNoise),
+    Chaos(ChaosMod),
+    Filter(SvfTpt, SvfMode),
+    Crossover(Crossover, CrossoverBand),
+    RingMod(RingMod),
+    FreqShift(FreqShifter),
+    Exciter(Exciter),
+    AutoWah(AutoWah),
+    Plain,
+}
+
+impl State {
+    fn new(kind: &NodeKind, sr: f32) -> Self {
+        match *kind {
+            NodeKind::Osc { wave, .. } => State::Osc(Osc::new(110.0, wave)),
+            NodeKind::Noise { seed } => State::Noise(PinkNoise::new(seed)),
+            NodeKind::Lfo { shape, seed } => {
+                let mut l = Lfo::new(0.1, shape);
+                l.set_seed(seed);
+                State::Lfo(l)
+            }
+            NodeKind::Drift { octaves, seed } => State::Drift(SmoothNoise::new(0.1, octaves, seed)),
+            NodeKind::Chaos { map, seed } => State::Chaos(ChaosMod::new(map, 0.1, 1.0, seed)),
+            NodeKind::Filter { mode } => {
+                let mode = match mode {
+                    FilterMode::Lowpass => SvfMode::Lowpass,
+                    FilterMode::Highpass => SvfMode::Highpass,
+                    FilterMode::Bandpass => SvfMode::Bandpass,
+                    FilterMode::Notch => SvfMode::Notch,
+                };
+                State::Filter(SvfTpt::new(1000.0, 0.707, sr), mode)
+            }
+            NodeKind::Crossover { band } => State::Crossover(Crossover::new(1000.0, sr), band),
+            NodeKind::RingMod { wave } => {
+                let mut r = RingMod::new(110.0, sr);
+                r.set_wave(wave);
+                State::RingMod(r)
+            }
+            NodeKind::FreqShift => State::FreqShift(FreqShifter::new(0.0, sr)),
+            NodeKind::Exciter => State::Exciter(Exciter::new(sr)),
+            NodeKind::AutoWah { direction } => {
+                let mut w = AutoWah::new(sr);
+                w.set_direction(direction);
+                State::AutoWah(w)
+            }
+            NodeKind::Gain | NodeKind::Saturate => State::Plain,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Node {
+    kind: NodeKind,
+    base: [f32; PARAMS],
+    default: [f32; PARAMS], // base restored by `reset_params`
+    state: State,
+    // per-sample glides between control ticks
+    speed: ControlRamp, // oscillator Hz (detune applied), carrier Hz, or LFO/drift rate
+    drive: ControlRamp,
+    level: ControlRamp,
+}
+
+impl Node {
+    /// Set (possibly modulated) parameters at a control tick; they glide
+    /// there over `samples`.
+    #[inline]
+    fn control(&mut self, p: &[f32; PARAMS], brightness: f32, sr: f32, samples: u32) {
+        let speed = match self.kind {
+            NodeKind::Osc { .. } => {
+                let cents = p[Param::Detune as usize];
+                p[Param::Freq as usize] * (core::f32::consts::LN_2 * cents / 1200.0).exp()
+            }
+            NodeKind::RingMod { .. } => p[Param::Freq as usize],
+            _ => p[Param::Rate as usize],
+        };
+        match &mut self.state {
+            State::Filter(f, _) => {
+                f.set_cutoff_smoothed(Self::cutoff(p, brightness, sr), samples);
+                f.set_q(p[Param::Q as usize].max(0.1));
+            }
+            // fixed crossover points: no brightness tracking
+            State::Crossover(x, _) => x.set_freq_hz(p[Param::Cutoff as usize].clamp(20.0, 0.45 * sr)),
+            // depth, offset, and mix glide inside the ring modulator
+            State::RingMod(r) => {
+                r.set_depth(p[Param::Depth as usize]);
+                r.set_dc(p[Param::Dc as usize]);
+                r.set_mix(p[Param::Mix as usize]);
+            }
+            State::Chaos(c) => c.set_chaos(p[Param::Chaos as usize]),
+            // the carrier phase runs on, so shift steps don't click
+            State::FreqShift(f) => {
+                f.set_shift_hz(p[Param::Shift as usize]);
+                f.set_feedback(p[Param::Feedback as usize]);
+                f.set_mix(p[Param::Mix as usize]);
+            }
+            // like the crossover, a fixed frequency
+            State::Exciter(e) => {
+                e.set_freq_hz(p[Param::Cutoff as usize]);
+                e.set_drive(p[Param::Drive as usize]);
+                e.set_amount(p[Param::Amount as usize]);
+            }
+            // the follower moves the cutoff inside the range, no brightness tracking
+            State::AutoWah(w) => {
+                let lo = p[Param::Cutoff as usize];
+                w.set_range(lo, lo * p[Param::Range as usize].clamp(0.0, 10.0).exp2());
+                w.set_q(p[Param::Q as usize]);
+                w.set_sensitivity(p[Param::Sensitivity as usize]);
+                w.set_mix(p[Param::Mix as usize]);
+            }
+            _ => {}
+        }
+        self.speed.set_target(speed, samples);
+        self.drive.set_target(p[Param::Drive as usize], samples);
+        self.level.set_target(p[Param::Level as usize], samples);
+    }
+
+    #[inline]
+    fn cutoff(p: &[f32; PARAMS], brightness: f32, sr: f32) -> f32 {
+        (p[Param::Cutoff as usize] * brightness).clamp(20.0, 0.45 * sr)
+    }
+
+    /// Like [`Node::control`], but jump straight to `p`.
+    fn snap(&mut self, p: &[f32; PARAMS], brightness: f32, sr: f32) {
+        self.control(p, brightness, sr, 1);
+        match &mut self.state {
+            State::Filter(f, _) => f.set_cutoff_hz(Self::cutoff(p, brightness, sr)),
+            State::RingMod(r) => r.snap(),
+            _ => {}
+        }
+        self.speed.snap(self.speed.target());
+        self.drive.snap(self.drive.target());
+        self.level.snap(self.level.target());
+    }
+
+    #[inline]
+    fn process(&mut self, x: f32, sr: f32) -> f32 {
+        let speed = self.speed.next_sample();
+        let y = match &mut self.state {
+            State::Osc(o) => {
+                o.set_freq(speed);
+                o.next(sr)
+            }
+            State::Noise(n) => n.next_sample(),
+            State::Lfo(l) => {
+                l.set_rate(speed);
+                l.next_norm(sr)
+            }
+            State::Drift(d) => {
+                d.set_rate(speed);
+                d.next(sr)
+            }
+            State::Chaos(c) => {
+                c.set_rate(speed);
+                c.next(sr)
+            }
+            State::Filter(f, mode) => f.process(x, *mode),
+            State::Crossover(c, band) => match (c.split(x), *band) {
+                ((lo, _), CrossoverBand::Low) => lo,
+                ((_, hi), CrossoverBand::High) => hi,
+            },
+            State::RingMod(r) => {
+                r.set_freq(speed);
+                r.process(x, sr)
+            }
+            State::FreqShift(f) => f.process(x),
+            State::Exciter(e) => e.process(x),
+            State::AutoWah(w) => w.process(x),
+            State::Plain => match self.kind {
+                NodeKind::Saturate => saturate(x, self.drive.next_sample()),
+                _ => x,
+            },
+        };
+        y * self.level.next_sample()
+    }
+}
+
+/// Audio from `from` into `to`.
+#[derive(Copy, Clone, Debug, Default)]
+struct Connection {
+    from: NodeId,
+    to: NodeId,
+    gain: f32,
+}
+
+/// `from` to the scene output, panned.
+#[derive(Copy, Clone, Debug, Default)]
+struct Output {
+    from: NodeId,
+    gain_l: f32,
+    gain_r: f32,
+}
+
+/// `depth × source` added to `param` of `target`.
+#[derive(Copy, Clone, Debug)]
+struct Modulation {
+    source: NodeId,
+    target: NodeId,
+    param: Param,
+    depth: f32,
+}
+
+impl Default for Modulation {
+    fn default() -> Self { Self { source: 0, target: 0, param: Param::Level, depth: 0.0 } }
+}
+
+/// `param` of `node` set from `expr` every control block.
+#[derive(Copy, Clone, Debug)]
+struct ParamExpr {
+    node: NodeId,
+    param: Param,
+    expr: Expr,
+}
+
+impl Default for ParamExpr {
+    fn default() -> Self { Self { node: 0, param: Param::Level, expr: Expr::default() } }
+}
+
+/// Rates of the `noise1` .. `noise4` expression variables (Hz).
+const EXPR_NOISE_HZ: [f32; NOISE_VARS] = [0.05, 0.1, 0.2, 0.4];
+
+fn expr_noise() -> [SmoothNoise; NOISE_VARS] {
+    core::array::from_fn(|i| SmoothNoise::new(EXPR_NOISE_HZ[i], 3, 0x6e6f_6973 + i as u32))
+}
+
+/// A scene built from up to [`MAX_NODES`] nodes.
+///
+/// Every sample, in dependency order, each node adds its modulations to its base parameters, sums
+/// its audio inputs (each scaled by the connection gain), and produces one
+/// mono value. Output connections pan node signals into the stereo dry mix;
+/// fixed shares of that mix go to the engine's reverb and delay.
+#[derive(Copy, Clone)]
+pub struct GraphScene {
+    name: [u8; MAX_NAME_LEN],
+    name_len: usize,
+    nodes: [Option<Node>; MAX_NODES],
+    len: usize,
+    order: [NodeId; MAX_NODES], // live nodes, sources before their readers
+    conns: [Connection; MAX_CONNECTIONS], // sorted by `to`
+    n_conns: usize,
+    conn_range: [(usize, usize); MAX_NODES],
+    mods: [Modulation; MAX_MODS], // sorted by `target`
+    n_mods: usize,
+    mod_range: [(usize, usize); MAX_NODES],
+    outputs: [Output; MAX_OUTPUTS],
+    n_outputs: usize,
+    exprs: [ParamExpr; MAX_EXPRS],
+    n_exprs: usize,
+    expr_noise: [SmoothNoise; NOISE_VARS],
+    ctrl: ControlClock,
+    frames: u64,     // since reset, for `t`
+    elapsed: f64,    // engine clock, for `elapsed`
+    bpm: f32,        // host tempo, for `bpm`
+    outs: [f32; MAX_NODES], // last value of every node
+    // output stage
+    sr: f32,
+    tuning: Tuning,
+    reverb_send: f32,
+    delay_send: f32,
+    bus: BusSettings,
+    out_gain: f32,
+    default_gain: f32,
+    trim: f32, // from Generator::set_output_trim
+    gain_sm: OnePoleSmoother,
+    intensity: Intensity,
+    warp: f32, // time warp on every modulator rate
+}
+
+impl fmt::Debug for GraphScene {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GraphScene")
+            .field("name", &self.name())
+            .field("nodes", &self.len)
+            .field("sr", &self.sr)
+            .finish()
+    }
+}
+
+impl GraphScene {
+    /// Empty scene (silent until nodes are connected to the output).
+    pub fn new(name: &str, sr: f32) -> Self {
+        let mut s = Self {
+            name: [0; MAX_NAME_LEN],
+            name_len: 0,
+            nodes: [None; MAX_NODES],
+            len: 0,
+            order: [0; MAX_NODES],
+            conns: [Connection::default(); MAX_CONNECTIONS],
+            n_conns: 0,
+            conn_range: [(0, 0); MAX_NODES],
+            mods: [Modulation::default(); MAX_MODS],
+            n_mods: 0,
+            mod_range: [(0, 0); MAX_NODES],
+            outputs: [Output::default(); MAX_OUTPUTS],
+            n_outputs: 0,
+            exprs: [ParamExpr::default(); MAX_EXPRS],
+            n_exprs: 0,
+            expr_noise: expr_noise(),
+            ctrl: ControlClock::default(),
+            frames: 0,
+            elapsed: 0.0,
+            bpm: DEFAULT_TEMPO_BPM,
+            outs: [0.0; MAX_NODES],
+            sr: sr.max(1.0),
+            tuning: Tuning::equal(),
+            reverb_send: 0.25,
+            delay_send: 0.0,
+            bus: BusSettings::default(),
+            out_gain: 0.5,
+            default_gain: 0.5,
+            trim: 1.0,
+            gain_sm: OnePoleSmoother::new_ms(30.0, sr),
+            intensity: Intensity::new(sr / CONTROL_BLOCK as f32),
+            warp: 1.0,
+        };
+        s.set_name(name);
+        s.gain_sm.reset(s.out_gain);
+        s
+    }
+
+    /// Scene name (from the scene file), cut to [`MAX_NAME_LEN`] bytes.
+    pub fn name(&self) -> &str { std::str::from_utf8(&self.name[..self.name_len]).unwrap_or_default() }
+
+    pub fn set_name(&mut self, name: &str) {
+        let mut n = name.len().min(MAX_NAME_LEN);
+        while !name.is_char_boundary(n) { n -= 1; }
+        self.name[..n].copy_from_slice(&name.as_bytes()[..n]);
+        self.name_len = n;
+    }
+
+    /// Number of nodes.
+    #[inline] pub fn len(&self) -> usize { self.len }
+    #[inline] pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Add a node with default parameters in the first free slot; returns its id.
+    pub fn add_node(&mut self, kind: NodeKind) -> Result<NodeId, GraphError> {
+        let id = self.nodes.iter().position(Option::is_none).ok_or(GraphError::Full)?;
+        let mut base = [0.0; PARAMS];
+        base[Param::Freq as usize] = 110.0;
+        base[Param::Rate as usize] = 0.1;
+        base[Param::Cutoff as usize] = 1000.0;
+        base[Param::Q as usize] = 0.707;
+        base[Param::Drive as usize] = 1.0;
+        base[Param::Depth as usize] = 1.0;
+        base[Param::Mix as usize] = 1.0;
+        base[Param::Chaos as usize] = 1.0;
+        base[Param::Level as usize] = 1.0;
+        if let NodeKind::Osc { note: Some(n), .. } = kind {
+            base[Param::Freq as usize] = self.tuning.note_to_hz(n);
+        }
+        if kind == NodeKind::Exciter {
+            base[Param::Cutoff as usize] = 3000.0;
+            base[Param::Drive as usize] = 10.0;
+            base[Param::Amount as usize] = 0.5;
+        }
+        if let NodeKind::AutoWah { .. } = kind {
+            base[Param::Cutoff as usize] = WAH_RANGE_HZ.0;
+            base[Param::Range as usize] = (WAH_RANGE_HZ.1 / WAH_RANGE_HZ.0).log2();
+            base[Param::Q as usize] = 3.0;
+            base[Param::Sensitivity as usize] = 0.5;
+        }
+        let mut node = Node {
+            kind,
+            base,
+            default: base,
+            state: State::new(&kind, self.sr),
+            speed: ControlRamp::default(),
+            drive: ControlRamp::default(),
+            level: ControlRamp::default(),
+        };
+        let mut p = base;
+        p[Param::Rate as usize] *= self.warp;
+        node.snap(&p, self.intensity.brightness(), self.sr);
+        self.nodes[id] = Some(node);
+        self.outs[id] = 0.0;
+        self.len += 1;
+        self.reorder();
+        Ok(id)
+    }
+
+    /// Remove `node` and every connection, output, and modulation touching it.
+    pub fn remove_node(&mut self, node: NodeId) -> Result<(), GraphError> {
+        self.node(node)?;
+        self.nodes[node] = None;
+        self.outs[node] = 0.0;
+        self.len -= 1;
+        retain(&mut self.conns, &mut self.n_conns, |c| c.from != node && c.to != node);
+        retain(&mut self.mods, &mut self.n_mods, |m| m.source != node && m.target != node);
+        retain(&mut self.outputs, &mut self.n_outputs, |o| o.from != node);
+        retain(&mut self.exprs, &mut self.n_exprs, |e| e.node != node);
+        self.reindex();
+        Ok(())
+    }
+
+    /// Kind of `node`, if there is one.
+    pub fn kind(&self, node: NodeId) -> Option<NodeKind> { self.node(node).ok().map(|n| n.kind) }
+
+    /// Live nodes as `(id, kind)`, by slot.
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, NodeKind)> + '_ {
+        self.nodes.iter().enumerate().filter_map(|(i, n)| n.as_ref().map(|n| (i, n.kind)))
+    }
+
+    /// Node-to-node connections as `(from, to, gain)`.
+    pub fn connections(&self) -> impl Iterator<Item = (NodeId, NodeId, f32)> + '_ {
+        self.conns[..self.n_conns].iter().map(|c| (c.from, c.to, c.gain))
+    }
+
+    /// Modulation routings as `(source, target, param, depth)`.
+    pub fn modulations(&self) -> impl Iterator<Item = (NodeId, NodeId, Param, f32)> + '_ {
+        self.mods[..self.n_mods].iter().map(|m| (m.source, m.target, m.param, m.depth))
+    }
+
+    /// Live nodes in evaluation order.
+    pub fn order(&self) -> &[NodeId] { &self.order[..self.len] }
+
+    fn node(&self, id: NodeId) -> Result<&Node, GraphError> {
+        self.nodes.get(id).and_then(Option::as_ref).ok_or(GraphError::NoNode(id))
+    }
+
+    /// Set the base (unmodulated) value of `param` on `node`, replacing any
+    /// expression driving it.
+    pub fn set_param(&mut self, node: NodeId, param: Param, value: f32) -> Result<(), GraphError> {
+        if !self.node(node)?.kind.has_param(param) { return Err(GraphError::NoParam(node, param)); }
+        if !value.is_finite() { return Err(GraphError::NotFinite); }
+        self.clear_param_expr(node, param);
+        if let Some(n) = self.nodes[node].as_mut() { n.base[param as usize] = value; }
+        Ok(())
+    }
+
+    /// Drive the base value of `param` on `node` from `expr`, evaluated every
+    /// [`CONTROL_BLOCK`] samples (modulations still add on top).
+    pub fn set_param_expr(&mut self, node: NodeId, param: Param, expr: Expr) -> Result<(), GraphError> {
+        if !self.node(node)?.kind.has_param(param) { return Err(GraphError::NoParam(node, param)); }
+        let e = ParamExpr { node, param, expr };
+        if let Some(slot) = self.exprs[..self.n_exprs].iter_mut().find(|x| x.node == node && x.param == param) {
+            *slot = e;
+        } else {
+            if self.n_exprs == MAX_EXPRS { return Err(GraphError::Full); }
+            self.exprs[self.n_exprs] = e;
+            self.n_exprs += 1;
+        }
+        self.ctrl.reset(); // take effect on the next sample
+        Ok(())
+    }
+
+    /// Stop driving `param` of `node` from an expression (it keeps its last
+    /// value); `false` if none was set.
+    pub fn clear_param_expr(&mut self, node: NodeId, param: Param) -> bool {
+        let n = self.n_exprs;
+        retain(&mut self.exprs, &mut self.n_exprs, |e| e.node != node || e.param != param);
+        self.n_exprs != n
+    }
+
+    /// Base value of `param` on `node`, if it has one.
+    pub fn param(&self, node: NodeId, param: Param) -> Option<f32> {
+        let n = self.node(node).ok()?;
+        n.kind.has_param(param).then(|| n.base[param as usize])
+    }
+
+    /// Feed `from`'s output into `to`'s input, scaled by `gain`; an existing
+    /// `from` → `to` connection just takes the new gain.
+    pub fn connect(&mut self, from: NodeId, to: NodeId, gain: f32) -> Result<(), GraphError> {
+        self.node(from)?;
+        if !self.node(to)?.kind.takes_input() { return Err(GraphError::NoInput(to)); }
+        if !gain.is_finite() { return Err(GraphError::NotFinite); }
+        if let Some(c) = self.conns[..self.n_conns].iter_mut().find(|c| c.from == from && c.to == to) {
+            c.gain = gain;
+            return Ok(());
+        }
+        if self.n_conns == MAX_CONNECTIONS { return Err(GraphError::Full); }
+        self.conns[self.n_conns] = Connection { from, to, gain };
+        self.n_conns += 1;
+        if !self.reorder() {
+            self.n_conns -= 1;
+            return Err(GraphError::Cycle { from, to });
+        }
+        self.reindex();
+        Ok(())
+    }
+
+    /// Remove the `from` → `to` connection; `false` if there was none.
+    pub fn disconnect(&mut self, from: NodeId, to: NodeId) -> bool {
+        let n = self.n_conns;
+        retain(&mut self.conns, &mut self.n_conns, |c| c.from != from || c.to != to);
+        if self.n_conns == n { return false; }
+        self.reindex();
+        true
+    }
+
+    /// Send `from` to the scene output at `gain`, panned -1 (left) .. +1 (right)
+    /// with constant power. A node already at the output is re-panned.
+    pub fn connect_output(&mut self, from: NodeId, gain: f32, pan: f32) -> Result<(), GraphError> {
+        self.node(from)?;
+        if !(gain.is_finite() && pan.is_finite()) { return Err(GraphError::NotFinite); }
+        let theta = (pan.clamp(-1.0, 1.0) + 1.0) * core::f32::consts::FRAC_PI_4;
+        // √2 keeps a centered node at `gain` on each side
+        let g = gain * core::f32::consts::SQRT_2;
+        let out = Output { from, gain_l: g * theta.cos(), gain_r: g * theta.sin() };
+        if let Some(o) = self.outputs[..self.n_outputs].iter_mut().find(|o| o.from == from) {
+            *o = out;
+            return Ok(());
+        }
+        if self.n_outputs == MAX_OUTPUTS { return Err(GraphError::Full); }
+        self.outputs[self.n_outputs] = out;
+        self.n_outputs += 1;
+        Ok(())
+    }
+
+    /// Take `from` off the scene output; `false` if it was not connected.
+    pub fn disconnect_output(&mut self, from: NodeId) -> bool {
+        let n = self.n_outputs;
+        retain(&mut self.outputs, &mut self.n_outputs, |o| o.from != from);
+        self.n_outputs != n
+    }
+
+    /// Add `depth × source` to `param` of `target` every sample; an existing
+    /// routing between the same pair and parameter just takes the new depth.
+    ///
+    /// [`Param::Rate`] is the exception: it is scaled by `2^(depth × source)`,
+    /// with depth limited to ±[`MAX_RATE_MOD_OCT`], so an LFO modulating
+    /// another LFO's rate bends it both ways and never through zero.
+    pub fn modulate(&mut self, source: NodeId, target: NodeId, param: Param, depth: f32) -> Result<(), GraphError> {
+        self.node(source)?;
+        if !depth.is_finite() { return Err(GraphError::NotFinite); }
+        let depth = if param == Param::Rate { depth.clamp(-MAX_RATE_MOD_OCT, MAX_RATE_MOD_OCT) } else { depth };
+        if !self.node(target)?.kind.has_param(param) { return Err(GraphError::NoParam(target, param)); }
+        let same = |m: &Modulation| m.source == source && m.target == target && m.param == param;
+        if let Some(m) = self.mods[..self.n_mods].iter_mut().find(|m| same(m)) {
+            m.depth = depth;
+            return Ok(());
+        }
+        if self.n_mods == MAX_MODS { return Err(GraphError::Full); }
+        self.mods[self.n_mods] = Modulation { source, target, param, depth };
+        self.n_mods += 1;
+        if !self.reorder() {
+            self.n_mods -= 1;
+            return Err(GraphError::Cycle { from: source, to: target });
+        }
+        self.reindex();
+        Ok(())
+    }
+
+    /// Remove the `source` → `target.param` routing; `false` if there was none.
+    pub fn unmodulate(&mut self, source: NodeId, target: NodeId, param: Param) -> bool {
+        let n = self.n_mods;
+        retain(&mut self.mods, &mut self.n_mods, |m| m.source != source || m.target != target || m.param != param);
+        if self.n_mods == n { return false; }
+        self.reindex();
+        true
+    }
+
+    /// Topologically sort the live nodes over connections and modulations
+    /// (Kahn's algorithm, ties by slot). `false` if the edges form a cycle, in
+    /// which case the previous order is kept.
+    fn reorder(&mut self) -> bool {
+        let mut pending = [0usize; MAX_NODES]; // unsorted inputs per node
+        for c in &self.conns[..self.n_conns] { pending[c.to] += 1; }
+        for m in &self.mods[..self.n_mods] { pending[m.target] += 1; }
+        let mut order = [0; MAX_NODES];
+        let mut n = 0;
+        for (i, slot) in self.nodes.iter().enumerate() {
+            if slot.is_some() && pending[i] == 0 {
+                order[n] = i;
+                n += 1;
+            }
+        }
+        let mut next = 0;
+        while next < n {
+            let u = order[next];
+            next += 1;
+            let conns = self.conns[..self.n_conns].iter().filter(|c| c.from == u).map(|c| c.to);
+            let mods = self.mods[..self.n_mods].iter().filter(|m| m.source == u).map(|m| m.target);
+            for v in conns.chain(mods) {
+                pending[v] -= 1;
+                if pending[v] == 0 {
+                    order[n] = v;
+                    n += 1;
+                }
+            }
+        }
+        if n != self.len { return false; }
+        self.order = order;
+        true
+    }
+
+    /// Re-sort the connection and modulation tables by target and rebuild the
+    /// per-node ranges and evaluation order.
+    fn reindex(&mut self) {
+        self.conns[..self.n_conns].sort_unstable_by_key(|c| c.to);
+        self.conn_range = Self::ranges(self.conns[..self.n_conns].iter().map(|c| c.to));
+        self.mods[..self.n_mods].sort_unstable_by_key(|m| m.target);
+        self.mod_range = Self::ranges(self.mods[..self.n_mods].iter().map(|m| m.target));
+        self.reorder();
+    }
+
+    /// `(start, end)` per node into a table sorted by node.
+    fn ranges(keys: impl Iterator<Item = NodeId>) -> [(usize, usize); MAX_NODES] {
+        let mut r = [(0, 0); MAX_NODES];
+        for (i, k) in keys.enumerate() {
+            if r[k].1 == r[k].0 { r[k] = (i, i); }
+            r[k].1 = i + 1;
+        }
+        r
+    }
+
+    /// Shares of the dry mix sent to the engine reverb and delay (0..1 each).
+    pub fn set_sends(&mut self, reverb: f32, delay: f32) {
+        if !(reverb.is_finite() && delay.is_finite()) { return; }
+        self.reverb_send = reverb.clamp(0.0, 1.0);
+        self.delay_send = delay.clamp(0.0, 1.0);
+    }
+
+    /// Room and echo settings applied when the scene is installed.
+    pub fn set_bus(&mut self, s: BusSettings) { self.bus = s; }
+
+    #[inline] pub fn set_gain(&mut self, g: f32) { if g.is_finite() { self.out_gain = g.clamp(0.0, 1.0); } }
+    #[inline] pub fn gain(&self) -> f32 { self.out_gain }
+    /// Gain [`GraphScene::reset_params`] restores.
+    #[inline] pub fn default_gain(&self) -> f32 { self.default_gain }
+
+    /// Keep the current node parameters and gain as the scene's defaults (done
+    /// once a scene file is compiled); [`GraphScene::reset_params`] returns to them.
+    pub fn mark_defaults(&mut self) {
+        for n in self.nodes.iter_mut().flatten() { n.default = n.base; }
+        self.default_gain = self.out_gain;
+    }
+
+    /// Return node parameters, gain and intensity to their defaults. Phases,
+    /// filter states and parameter expressions are kept; note oscillators follow
+    /// the current tuning.
+    pub fn reset_params(&mut self) {
+        for n in self.nodes.iter_mut().flatten() { n.base = n.default; }
+        self.out_gain = self.default_gain;
+        self.intensity.set(INTENSITY_DEFAULT);
+        self.warp = 1.0;
+        self.set_tuning(self.tuning);
+    }
+
+    /// Intensity 0..1 (0.5 = default): output level and filter brightness.
+    #[inline] pub fn set_intensity(&mut self, x: f32) { self.intensity.set(x); }
+    #[inline] pub fn intensity(&self) -> f32 { self.intensity.target() }
+
+    /// Time warp 0.1..10 (see [`Scene::set_time_warp`](crate::scenes::Scene::set_time_warp)):
+    /// multiplies the rate of every LFO, drift, and chaos node and of the
+    /// expression noises. `t` in expressions stays real time.
+    #[inline] pub fn set_time_warp(&mut self, w: f32) { if w.is_finite() { self.warp = w.clamp(TIME_WARP_RANGE.0, TIME_WARP_RANGE.1); } }
+    #[inline] pub fn time_warp(&self) -> f32 { self.warp }
+
+    /// Retune: oscillators given as notes move with the tuning.
+    pub fn set_tuning(&mut self, t: Tuning) {
+        self.tuning = t;
+        for n in self.nodes.iter_mut().flatten() {
+            if let NodeKind::Osc { note: Some(note), .. } = n.kind {
+                n.base[Param::Freq as usize] = t.note_to_hz(note);
+            }
+        }
+    }
+    #[inline] pub fn tuning(&self) -> &Tuning { &self.tuning }
+
+    /// Jump every node to its base parameters (after a reset or rate change).
+    fn snap_all(&mut self) {
+        let (brightness, sr) = (self.intensity.brightness(), self.sr);
+        for n in self.nodes.iter_mut().flatten() {
+            let mut p = n.base;
+            p[Param::Rate as usize] *= self.warp;
+            n.snap(&p, brightness, sr);
+        }
+    }
+
+    /// Evaluate every parameter expression into the node's base value.
+    fn update_exprs(&mut self) {
+        let noise_sr = self.ctrl.rate(self.sr) / self.warp;
+        let mut inputs = ExprInputs {
+            t: (self.frames as f64 / f64::from(self.sr)) as f32,
+            elapsed: self.elapsed as f32,
+            bpm: self.bpm,
+            intensity: self.intensity.target(),
+            ..ExprInputs::default()
+        };
+        for (v, n) in inputs.noise.iter_mut().zip(&mut self.expr_noise) { *v = n.next(noise_sr); }
+        for e in &self.exprs[..self.n_exprs] {
+            if let Some(n) = self.nodes[e.node].as_mut() { n.base[e.param as usize] = e.expr.eval(&inputs); }
+        }
+    }
+
+    /// Control tick: expressions, intensity, and every node's modulated
+    /// parameters, in evaluation order so modulators read this block's values.
+    fn control(&mut self) {
+        if self.n_exprs > 0 { self.update_exprs(); }
+        self.intensity.tick();
+        let (brightness, sr, block) = (self.intensity.brightness(), self.sr, self.ctrl.block());
+        for &i in &self.order[..self.len] {
+            let Some(node) = self.nodes[i].as_mut() else { continue; };
+            let mut p = node.base;
+            let (m0, m1) = self.mod_range[i];
+            p[Param::Rate as usize] *= self.warp;
+            let mut rate_oct = 0.0;
+            for m in &self.mods[m0..m1] {
+                let v = m.depth * self.outs[m.source];
+                if m.param == Param::Rate { rate_oct += v; } else { p[m.param as usize] += v; }
+            }
+            if rate_oct != 0.0 {
+                let r = &mut p[Param::Rate as usize];
+                *r *= rate_oct.clamp(-MAX_RATE_MOD_OCT, MAX_RATE_MOD_OCT).exp2();
+            }
+            node.control(&p, brightness, sr, block);
+        }
+    }
+
+    /// Run every node once; returns the panned stereo mix.
+    #[inline]
+    fn tick(&mut self) -> (f32, f32) {
+        if self.ctrl.tick() { self.control(); }
+        self.frames += 1;
+        let sr = self.sr;
+        for &i in &self.order[..self.len] {
+            let Some(node) = self.nodes[i].as_mut() else { continue; };
+            let (c0, c1) = self.conn_range[i];
+            let x: f32 = self.conns[c0..c1].iter().map(|c| c.gain * self.outs[c.from]).sum();
+            let _p = scope(node.kind.stage());
+            self.outs[i] = node.process(x, sr);
+        }
+        let (mut l, mut r) = (0.0, 0.0);
+        for o in &self.outputs[..self.n_outputs] {
+            let y = self.outs[o.from];
+            l += o.gain_l * y;
+            r += o.gain_r * y;
+        }
+        (l, r)
+    }
+}
+
+/// Keep the first `*n` items that pass `keep`, compacted in order.
+fn retain<T: Copy>(items: &mut [T], n: &mut usize, keep: impl Fn(&T) -> bool) {
+    let mut w = 0;
+    for r in 0..*n {
+        if keep(&items[r]) {
+            items[w] = items[r];
+            w += 1;
+        }
+    }
+    *n = w;
+}
+
+impl Generator for GraphScene {
+    fn reset(&mut self, sr: f32) {
+        self.sr = sr.max(1.0);
+        for n in self.nodes.iter_mut().flatten() {
+            n.state = State::new(&n.kind, self.sr);
+        }
+        self.outs = [0.0; MAX_NODES];
+        self.expr_noise = expr_noise();
+        self.ctrl.reset();
+        self.frames = 0;
+        self.gain_sm.set_time_ms(30.0, self.sr);
+        self.gain_sm.reset(self.out_gain);
+        self.intensity.set_sample_rate(self.ctrl.rate(self.sr));
+        self.intensity.snap();
+        self.snap_all();
+    }
+
+    /// Rescale filters and smoothing; phases and filter state carry on.
+    fn set_sample_rate(&mut self, sr: f32) -> bool {
+        self.sr = sr.max(1.0);
+        for n in self.nodes.iter_mut().flatten() {
+            match &mut n.state {
+                State::Filter(f, _) => f.set_sample_rate(self.sr),
+                State::FreqShift(f) => f.set_sample_rate(self.sr),
+                State::Exciter(e) => e.set_sample_rate(self.sr),
+                State::AutoWah(w) => w.set_sample_rate(self.sr),
+                _ => {}
+            }
+        }
+        self.gain_sm.set_time_ms(30.0, self.sr);
+        self.intensity.set_sample_rate(self.ctrl.rate(self.sr));
+        self.snap_all();
+        false
+    }
+
+    #[inline]
+    fn next(&mut self) -> f32 {
+        let (l, r) = self.next_stereo();
+        0.5 * (l + r)
+    }
+
+    #[inline]
+    fn next_stereo(&mut self) -> (f32, f32) { self.next_bus().dry }
+
+    #[inline]
+    fn next_bus(&mut self) -> BusFrame {
+        let (l, r) = self.tick();
+        let g = self.gain_sm.process(self.out_gain * self.trim * self.intensity.level());
+        let (l, r) = (l * g, r * g);
+        let dry = 1.0 - self.reverb_send;
+        BusFrame {
+            dry: ((dry * l).clamp(-1.0, 1.0), (dry * r).clamp(-1.0, 1.0)),
+            reverb: (self.reverb_send * l, self.reverb_send * r),
+            delay: (self.delay_send * l, self.delay_send * r),
+        }
+    }
+
+    #[inline]
+    fn bus_settings(&self) -> BusSettings { self.bus }
+
+    #[inline]
+    fn set_output_trim(&mut self, trim: f32) { self.trim = trim.clamp(0.0, 1.0); }
+
+    #[inline]
+    fn render(&mut self, ctx: &RenderCtx) -> BusFrame {
+        self.elapsed = ctx.time;
+        self.bpm = ctx.tempo;
+        self.next_bus()
+    }
+
+    #[inline]
+    fn set_time(&mut self, t: f64) { self.elapsed = t; }
+
+    /// Every node's output is a tap named `node.<id>` (modulators included),
+    /// read once per control block.
+    fn tap_index(&self, name: &str) -> Option<usize> {
+        let id: usize = name.strip_prefix("node.")?.parse().ok()?;
+        self.nodes.get(id)?.as_ref().map(|_| id)
+    }
+
+    #[inline]
+    fn tap_value(&self, index: usize) -> f32 { self.outs.get(index).copied().unwrap_or(0.0) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SR: f32 = 48_000.0;
+
+    /// Amplitude of the `hz` component of `x` (one DFT bin).
+    fn tone_level(x: &[f32], hz: f32) -> f32 {
+        let (mut re, mut im) = (0.0f64, 0.0f64);
+        for (i, &v) in x.iter().enumerate() {
+            let w = core::f64::consts::TAU * f64::from(hz) * i as f64 / f64::from(SR);
+            re += f64::from(v) * w.cos();
+            im += f64::from(v) * w.sin();
+        }
+        (2.0 * (re * re + im * im).sqrt() / x.len() as f64) as f32
+    }
+
+    /// `osc` (1 kHz sine) → `node` → out; one second of the left channel.
+    fn render_through(kind: NodeKind, params: &[(Param, f32)]) -> Vec<f32> {
+        let mut g = GraphScene::new("test", SR);
+        let osc = g.add_node(NodeKind::Osc { wave: Wave::Sine, note: None }).unwrap();
+        g.set_param(osc, Param::Freq, 1000.0).unwrap();
+        let fx = g.add_node(kind).unwrap();
+        for &(p, v) in params { g.set_param(fx, p, v).unwrap(); }
+        g.connect(osc, fx, 1.0).unwrap();
+        g.connect_output(fx, 1.0, 0.0).unwrap();
+        g.reset(SR);
+        (0..SR as usize).map(|_| g.next_stereo().0).skip(4_800).collect()
+    }
+
+    #[test]
+    fn nodes_run_after_their_inputs_and_modulators() {
+        // added sink first, so slot order is the reverse of signal order
+        let mut g = GraphScene::new("test", SR);
+        let vca = g.add_node(NodeKind::Gain).unwrap();
+        let lp = g.add_node(NodeKind::Filter { mode: FilterMode::Lowpass }).unwrap();
+        let osc = g.add_node(NodeKind::Osc { wave: Wave::Saw, note: None }).unwrap();
+        let lfo = g.add_node(NodeKind::Lfo { shape: LfoShape::Sine, seed: 1 }).unwrap();
+        g.connect(lp, vca, 1.0).unwrap();
+        g.connect(osc, lp, 1.0).unwrap();
+        g.modulate(lfo, osc, Param::Freq, 5.0).unwrap();
+        let at = |n: NodeId| g.order().iter().position(|&m| m == n).unwrap();
+        assert_eq!(g.order().len(), 4);
+        assert!(at(lfo) < at(osc) && at(osc) < at(lp) && at(lp) < at(vca), "{:?}", g.order());
+    }
+
+    #[test]
+    fn cycles_are_refused_and_leave_the_graph_as_it_was() {
+        let mut g = GraphScene::new("test", SR);
+        let a = g.add_node(NodeKind::Gain).unwrap();
+        let b = g.add_node(NodeKind::Saturate).unwrap();
+        let c = g.add_node(NodeKind::Filter { mode: FilterMode::Lowpass }).unwrap();
+        g.connect(a, b, 1.0).unwrap();
+        g.connect(b, c, 1.0).unwrap();
+        let order = g.order().to_vec();
+
+        assert_eq!(g.connect(c, a, 1.0), Err(GraphError::Cycle { from: c, to: a }));
+        assert_eq!(g.connect(a, a, 1.0), Err(GraphError::Cycle { from: a, to: a }));
+        // a modulation closes a loop just as well
+        assert_eq!(g.modulate(c, a, Param::Level, 0.5), Err(GraphError::Cycle { from: c, to: a }));
+        assert_eq!(g.connections().count(), 2);
+        assert_eq!(g.modulations().count(), 0);
+        assert_eq!(g.order(), &order[..]);
+
+        // once the loop is broken the same edge is fine
+        assert!(g.disconnect(a, b));
+        g.connect(c, a, 1.0).unwrap();
+        let at = |n: NodeId| g.order().iter().position(|&m| m == n).unwrap();
+        assert!(at(b) < at(c) && at(c) < at(a), "{:?}", g.order());
+    }
+
+    #[test]
+    fn reset_params_returns_to_the_marked_defaults() {
+        let mut g = GraphScene::new("test", SR);
+        let osc = g.add_node(NodeKind::Osc { wave: Wave::Sine, note: None }).unwrap();
+        g.set_param(osc, Param::Freq, 220.0).unwrap();
+        g.set_gain(0.6);
+        g.mark_defaults();
+
+        g.set_param(osc, Param::Freq, 880.0).unwrap();
+        g.set_gain(0.2);
+        g.set_intensity(0.9);
+        g.set_time_warp(3.0);
+        g.reset_params();
+        assert_eq!(g.param(osc, Param::Freq), Some(220.0));
+        assert_eq!((g.gain(), g.default_gain()), (0.6, 0.6));
+        assert_eq!((g.intensity(), g.time_warp()), (INTENSITY_DEFAULT, 1.0));
+    }
+
+    #[test]
+    fn freqshift_node_shifts_its_input() {
+        assert!(!NodeKind::FreqShift.has_param(Param::Freq));
+        let y = render_through(NodeKind::FreqShift, &[(Param::Shift, 100.0)]);
+        let (moved, left) = (tone_level(&y, 1100.0), tone_level(&y, 1000.0));
+        assert!(moved > 0.05 && left < 0.02 * moved, "1.1 kHz at {moved}, 1 kHz at {left}");
+    }
+
+    #[test]
+    fn exciter_node_adds_harmonics() {
+        let plain = render_through(NodeKind::Gain, &[]);
+        let y = render_through(NodeKind::Exciter, &[(Param::Cutoff, 1000.0), (Param::Amount, 1.0)]);
+        // third harmonic of the 1 kHz input, not in the plain signal
+        assert!(tone_level(&y, 3000.0) > 10.0 * tone_level(&plain, 3000.0).max(1e-5));
+    }
+}
@@ -0,0 +1,88 @@
+//! Shared "intensity" meta-parameter for scenes.
+//!
+//! One normalized control (0..1) that each scene maps onto its own density,
+//! brightness, and level, so hosts can dial a whole soundscape up or down.
+//! 0.5 is the scene as designed.
+
+use crate::nodes::OnePoleSmoother;
+use ambientor_core::dsp::db_to_lin;
+
+/// Neutral intensity: every derived factor is 1.
+pub const INTENSITY_DEFAULT: f32 = 0.5;
+
+/// Time constant for intensity moves; slow enough to be a "dial", not a switch.
+const INTENSITY_SMOOTH_MS: f32 = 400.0;
+
+/// Smoothed intensity with cached mappings:
+/// - `level()`     : −18 dB at 0, unity at 0.5, +3 dB at 1,
+/// - `brightness()`: cutoff factor 0.5 .. 1 .. 2,
+/// - `density()`   : event-rate factor 0.25 .. 1 .. 4.
+///
+/// Call [`Intensity::tick`] at the rate given to `new`/`set_sample_rate`
+/// (audio or control rate); it reports whether the value is still moving, so
+/// scenes only re-derive their parameters during a change.
+#[derive(Copy, Clone, Debug)]
+pub struct Intensity {
+    target: f32,
+    sm: OnePoleSmoother,
+    value: f32,
+    level: f32,
+    brightness: f32,
+    density: f32,
+}
+
+impl Intensity {
+    pub fn new(rate: f32) -> Self {
+        let mut s = Self {
+            target: INTENSITY_DEFAULT,
+            sm: OnePoleSmoother::new_ms(INTENSITY_SMOOTH_MS, rate),
+            value: INTENSITY_DEFAULT,
+            level: 1.0,
+            brightness: 1.0,
+            density: 1.0,
+        };
+        s.snap();
+        s
+    }
+
+    #[inline] pub fn set_sample_rate(&mut self, rate: f32) { self.sm.set_time_ms(INTENSITY_SMOOTH_MS, rate); }
+
+    /// New target in 0..1; the value glides there. NaN and ±inf are ignored.
+    #[inline] pub fn set(&mut self, x: f32) { if x.is_finite() { self.target = x.clamp(0.0, 1.0); } }
+    #[inline] pub fn target(&self) -> f32 { self.target }
+
+    /// Current (smoothed) intensity.
+    #[inline] pub fn value(&self) -> f32 { self.value }
+
+    /// Jump to the target without gliding.
+    #[inline]
+    pub fn snap(&mut self) {
+        self.sm.reset(self.target);
+        self.update(self.target);
+    }
+
+    /// Advance the glide one step; `true` while the value is still moving.
+    #[inline]
+    pub fn tick(&mut self) -> bool {
+        if self.value == self.target { return false; }
+        let v = self.sm.process(self.target);
+        if (v - self.target).abs() < 1e-4 {
+            self.snap();
+        } else {
+            self.update(v);
+        }
+        true
+    }
+
+    #[inline] pub fn level(&self) -> f32 { self.level }
+    #[inline] pub fn brightness(&self) -> f32 { self.brightness }
+    #[inline] pub fn density(&self) -> f32 { self.density }
+
+    fn update(&mut self, v: f32) {
+        self.value = v;
+        let db = if v < 0.5 { -36.0 * (0.5 - v) } else { 6.0 * (v - 0.5) };
+        self.level = db_to_lin(db);
+        self.brightness = (2.0 * v - 1.0).exp2();
+        self.density = (4.0 * v - 2.0).exp2();
+    }
+}
@@ -0,0 +1,683 @@
+//! Musical scenes that implement the realtime [`Generator`](crate::graph::Generator) trait.
+//!
+//! Scenes produce a mono sample via `next()` and may override `next_stereo()` for
+//! true stereo; hosts route the frame to device channels. Keep scenes
+//! allocation-free and cheap per sample.
+//!
+//! Each scene is its own struct in a submodule; [`Scene`] wraps them in one
+//! `Copy` enum so hosts can hold any scene in a single `Engine<Scene>` and swap
+//! by name at runtime.
+//!
+//! - [`slow_drone`]: `SlowDrone`, two detuned oscillators under slow drift
+//! - [`risset`]    : `RissetDrone`, additive partials with a wandering centroid
+//! - [`thunderstorm`]: `Thunderstorm`, rain bed with distant rumbles
+//! - [`cave_drips`]: `CaveDrips`, sparse pitched drips into long echoes
+//! - [`binaural`]  : `BinauralBeat`, binaural beat tones with an optional noise bed
+//! - [`tape_loop`] : `TapeLoop`, a chord pad on a wearing tape loop
+//! - [`graph_scene`]: `GraphScene`, nodes wired at runtime (e.g. from a scene file)
+//! - `file`        : TOML scene descriptions compiled into a `GraphScene` (feature `scene-file`)
+//!
+//! Every scene also takes an [`intensity`] meta-parameter (0..1, 0.5 = as
+//! designed) that it maps onto its own density, brightness, and level, and a
+//! time warp ([`TIME_WARP_RANGE`], 1 = as designed) that multiplies the rate
+//! of all its slow motion, so the whole soundscape evolves faster or slower.
+
+pub mod binaural;
+pub mod cave_drips;
+#[cfg(feature = "scene-file")]
+pub mod file;
+pub mod graph_scene;
+pub mod intensity;
+pub mod risset;
+pub mod slow_drone;
+pub mod tape_loop;
+pub mod thunderstorm;
+
+pub use binaural::BinauralBeat;
+pub use cave_drips::CaveDrips;
+pub use graph_scene::GraphScene;
+pub use intensity::{Intensity, INTENSITY_DEFAULT};
+pub use risset::RissetDrone;
+pub use slow_drone::SlowDrone;
+pub use tape_loop::TapeLoop;
+pub use thunderstorm::Thunderstorm;
+
+use crate::event::SceneEvent;
+use crate::graph::{BusFrame, Generator, RenderCtx};
+use crate::master::BusSettings;
+use crate::nodes::Rng;
+use crate::params::{ParamId, ParamSet, RampCurve};
+use ambientor_core::dsp::Tuning;
+
+/// Range of [`Scene::set_time_warp`].
+pub const TIME_WARP_RANGE: (f32, f32) = (0.1, 10.0);
+
+/// Names accepted by [`Scene::by_name`], in listing order.
+pub const SCENE_NAMES: &[&str] = &["slow-drone", "risset", "thunderstorm", "cave-drips", "binaural", "tape-loop"];
+
+/// One-line description of each built-in scene (same order as [`SCENE_NAMES`]).
+pub const SCENE_DESCRIPTIONS: [&str; 6] = [
+    "two detuned oscillators under slow filter and detune drift",
+    "Risset-style additive drone with a wandering spectral centroid",
+    "rain bed with distant thunder rumbles that duck it",
+    "sparse pitched drips into long echoes",
+    "binaural beat tones with an optional noise bed (headphones)",
+    "a chord pad on a short tape loop that wears out pass by pass",
+];
+
+/// Loudness of each built-in scene (same order as [`SCENE_NAMES`]) at default
+/// settings: RMS dBFS after the master bus over a minute, as printed by
+/// `ambientor-cli calibrate`. Auto-gain levels scenes from these; re-measure
+/// after changing a scene's levels.
+const LOUDNESS_DB: [f32; 6] = [-21.9, -20.3, -26.4, -28.1, -13.6, -22.1];
+
+/// Any built-in scene.
+///
+/// Parameter setters apply to scenes that have the parameter and are no-ops
+/// elsewhere, so hosts can forward controls without matching on the scene.
+// The built-in scenes are 1-4 KB each and held by value; the graph scene
+// (~28 KB) and the tape loop's tape live on the heap.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug)]
+pub enum Scene {
+    SlowDrone(SlowDrone),
+    Risset(RissetDrone),
+    Thunderstorm(Thunderstorm),
+    CaveDrips(CaveDrips),
+    Binaural(BinauralBeat),
+    TapeLoop(TapeLoop),
+    Graph(Box<GraphScene>),
+}
+
+impl Scene {
+    /// The default slow evolving drone.
+    pub fn slow_drone(sr: f32) -> Self { Self::SlowDrone(SlowDrone::new(sr)) }
+
+    /// Risset-style additive drone.
+    pub fn risset(sr: f32) -> Self { Self::Risset(RissetDrone::new(sr)) }
+
+    /// Rain with distant thunder.
+    pub fn thunderstorm(sr: f32) -> Self { Self::Thunderstorm(Thunderstorm::new(sr)) }
+
+    /// Sparse drips in a cavern.
+    pub fn cave_drips(sr: f32) -> Self { Self::CaveDrips(CaveDrips::new(sr)) }
+
+    /// Binaural beat tones (headphones).
+    pub fn binaural(sr: f32) -> Self { Self::Binaural(BinauralBeat::new(sr)) }
+
+    /// Decaying tape-loop pad.
+    pub fn tape_loop(sr: f32) -> Self { Self::TapeLoop(TapeLoop::new(sr)) }
+
+    /// Build a scene from its name (see [`SCENE_NAMES`]); case-insensitive,
+    /// `_` and `-` are interchangeable.
+    pub fn by_name(name: &str, sr: f32) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "slow-drone" | "drone" => Some(Self::slow_drone(sr)),
+            "risset" => Some(Self::risset(sr)),
+            "thunderstorm" | "storm" => Some(Self::thunderstorm(sr)),
+            "cave-drips" | "cave" => Some(Self::cave_drips(sr)),
+            "binaural" | "binaural-beat" => Some(Self::binaural(sr)),
+            "tape-loop" | "tape" => Some(Self::tape_loop(sr)),
+            _ => None,
+        }
+    }
+
+    /// Canonical name of this scene (a graph scene's own name).
+    pub fn name(&self) -> &str {
+        match self {
+            Self::SlowDrone(_) => "slow-drone",
+            Self::Risset(_) => "risset",
+            Self::Thunderstorm(_) => "thunderstorm",
+            Self::CaveDrips(_) => "cave-drips",
+            Self::Binaural(_) => "binaural",
+            Self::TapeLoop(_) => "tape-loop",
+            Self::Graph(s) => s.name(),
+        }
+    }
+
+    /// Host parameters of this scene at their defaults: what a fresh scene of
+    /// the same kind starts with (for graph scenes, the values from the scene
+    /// file). See [`Generator::reset_params`].
+    pub fn defaults(&self) -> ParamSet {
+        let table: &[(ParamId, f32)] = match self {
+            Self::SlowDrone(_) => &SlowDrone::DEFAULTS,
+            Self::Risset(_) => &RissetDrone::DEFAULTS,
+            Self::Thunderstorm(_) => &Thunderstorm::DEFAULTS,
+            Self::CaveDrips(_) => &CaveDrips::DEFAULTS,
+            Self::Binaural(_) => &BinauralBeat::DEFAULTS,
+            Self::TapeLoop(_) => &TapeLoop::DEFAULTS,
+            Self::Graph(s) => &[(ParamId::SceneGain, s.default_gain())],
+        };
+        let mut d: ParamSet = table.iter().copied().collect();
+        d.set(ParamId::Intensity, INTENSITY_DEFAULT);
+        d.set(ParamId::TimeWarp, 1.0);
+        d
+    }
+
+    /// A random variation: move each scene parameter (intensity included,
+    /// output gain left alone) by up to `amount` (0..1) of its
+    /// [`ParamId::range`], staying inside that range. Frequencies and levels
+    /// move by ratios. The same `seed` and starting point give the same result.
+    pub fn randomize(&mut self, seed: u32, amount: f32) {
+        let amount = amount.clamp(0.0, 1.0);
+        let mut rng = Rng::new(seed);
+        for id in ParamId::ALL {
+            if id == ParamId::SceneGain { continue; }
+            let Some(v) = self.param_value(id) else { continue; };
+            let (lo, hi) = id.range();
+            let r = amount * rng.next_bipolar();
+            let x = if id.curve() == RampCurve::Log && lo > 0.0 {
+                (v.max(lo).ln() + r * (hi / lo).ln()).exp()
+            } else {
+                v + r * (hi - lo)
+            };
+            self.set_param_value(id, x.clamp(lo, hi));
+        }
+    }
+
+    // --- shared parameters -----------------------------------------------------
+
+    #[inline]
+    pub fn set_gain(&mut self, g: f32) {
+        match self {
+            Self::SlowDrone(s) => s.set_gain(g),
+            Self::Risset(s) => s.set_gain(g),
+            Self::Thunderstorm(s) => s.set_gain(g),
+            Self::CaveDrips(s) => s.set_gain(g),
+            Self::Binaural(s) => s.set_gain(g),
+            Self::TapeLoop(s) => s.set_gain(g),
+            Self::Graph(s) => s.set_gain(g),
+        }
+    }
+
+    #[inline]
+    pub fn set_tuning(&mut self, t: Tuning) {
+        match self {
+            Self::SlowDrone(s) => s.set_tuning(t),
+            Self::Risset(s) => s.set_tuning(t),
+            Self::Thunderstorm(s) => s.set_tuning(t),
+            Self::CaveDrips(s) => s.set_tuning(t),
+            Self::Binaural(s) => s.set_tuning(t),
+            Self::TapeLoop(s) => s.set_tuning(t),
+            Self::Graph(s) => s.set_tuning(t),
+        }
+    }
+
+    /// Overall intensity 0..1 (0.5 = default); see [`Intensity`].
+    #[inline]
+    pub fn set_intensity(&mut self, x: f32) {
+        match self {
+            Self::SlowDrone(s) => s.set_intensity(x),
+            Self::Risset(s) => s.set_intensity(x),
+            Self::Thunderstorm(s) => s.set_intensity(x),
+            Self::CaveDrips(s) => s.set_intensity(x),
+            Self::Binaural(s) => s.set_intensity(x),
+            Self::TapeLoop(s) => s.set_intensity(x),
+            Self::Graph(s) => s.set_intensity(x),
+        }
+    }
+
+    /// Target intensity (0.5 unless changed).
+    #[inline]
+    pub fn intensity(&self) -> f32 {
+        match self {
+            Self::SlowDrone(s) => s.intensity(),
+            Self::Risset(s) => s.intensity(),
+            Self::Thunderstorm(s) => s.intensity(),
+            Self::CaveDrips(s) => s.intensity(),
+            Self::Binaural(s) => s.intensity(),
+            Self::TapeLoop(s) => s.intensity(),
+            Self::Graph(s) => s.intensity(),
+        }
+    }
+
+    /// Multiply the rate of every LFO and drift in the scene by `w`
+    /// ([`TIME_WARP_RANGE`], 1 = as designed). Scenes whose motion is all
+    /// event timing or pitch character (cave drips, binaural) keep the value
+    /// but sound the same.
+    #[inline]
+    pub fn set_time_warp(&mut self, w: f32) {
+        match self {
+            Self::SlowDrone(s) => s.set_time_warp(w),
+            Self::Risset(s) => s.set_time_warp(w),
+            Self::Thunderstorm(s) => s.set_time_warp(w),
+            Self::CaveDrips(s) => s.set_time_warp(w),
+            Self::Binaural(s) => s.set_time_warp(w),
+            Self::TapeLoop(s) => s.set_time_warp(w),
+            Self::Graph(s) => s.set_time_warp(w),
+        }
+    }
+
+    /// Current time warp (1 unless changed).
+    #[inline]
+    pub fn time_warp(&self) -> f32 {
+        match self {
+            Self::SlowDrone(s) => s.time_warp(),
+            Self::Risset(s) => s.time_warp(),
+            Self::Thunderstorm(s) => s.time_warp(),
+            Self::CaveDrips(s) => s.time_warp(),
+            Self::Binaural(s) => s.time_warp(),
+            Self::TapeLoop(s) => s.time_warp(),
+            Self::Graph(s) => s.time_warp(),
+        }
+    }
+
+    #[inline]
+    pub fn tuning(&self) -> &Tuning {
+        match self {
+            Self::SlowDrone(s) => s.tuning(),
+            Self::Risset(s) => s.tuning(),
+            Self::Thunderstorm(s) => s.tuning(),
+            Self::CaveDrips(s) => s.tuning(),
+            Self::Binaural(s) => s.tuning(),
+            Self::TapeLoop(s) => s.tuning(),
+            Self::Graph(s) => s.tuning(),
+        }
+    }
+
+    // --- scene-specific parameters (no-op where absent) ---------------------------
+
+    #[inline]
+    pub fn set_cut_base(&mut self, hz: f32) {
+        if let Self::SlowDrone(s) = self { s.set_cut_base(hz); }
+    }
+
+    #[inline]
+    pub fn set_cut_span(&mut self, hz: f32) {
+        if let Self::SlowDrone(s) = self { s.set_cut_span(hz); }
+    }
+
+    #[inline]
+    pub fn set_drive(&mut self, d: f32) {
+        if let Self::SlowDrone(s) = self { s.set_drive(d); }
+    }
+
+    #[inline]
+    pub fn set_detune_cents(&mut self, c: f32) {
+        if let Self::SlowDrone(s) = self { s.set_detune_cents(c); }
+    }
+
+    #[inline]
+    pub fn set_inharmonicity(&mut self, x: f32) {
+        if let Self::Risset(s) = self { s.set_inharmonicity(x); }
+    }
+
+    #[inline]
+    pub fn set_motion_rate(&mut self, m: f32) {
+        if let Self::Risset(s) = self { s.set_motion_rate(m); }
+    }
+
+    #[inline]
+    pub fn set_storm_intensity(&mut self, x: f32) {
+        if let Self::Thunderstorm(s) = self { s.set_intensity(x); }
+    }
+
+    #[inline]
+    pub fn set_rumble_rate(&mut self, per_min: f32) {
+        if let Self::Thunderstorm(s) = self { s.set_rumble_rate(per_min); }
+    }
+
+    /// Sidechain ducking depth (dB) of the bed under event layers.
+    #[inline]
+    pub fn set_duck_depth_db(&mut self, db: f32) {
+        if let Self::Thunderstorm(s) = self { s.set_duck_depth_db(db); }
+    }
+
+    #[inline]
+    pub fn set_drip_density(&mut self, per_min: f32) {
+        if let Self::CaveDrips(s) = self { s.set_density(per_min); }
+    }
+
+    #[inline]
+    pub fn set_wetness(&mut self, w: f32) {
+        if let Self::CaveDrips(s) = self { s.set_wetness(w); }
+    }
+
+    #[inline]
+    pub fn set_carrier_hz(&mut self, hz: f32) {
+        if let Self::Binaural(s) = self { s.set_carrier_hz(hz); }
+    }
+
+    #[inline]
+    pub fn set_beat_hz(&mut self, hz: f32) {
+        if let Self::Binaural(s) = self { s.set_beat_hz(hz); }
+    }
+
+    #[inline]
+    pub fn set_noise_bed(&mut self, level: f32) {
+        if let Self::Binaural(s) = self { s.set_noise_bed(level); }
+    }
+
+    #[inline]
+    pub fn set_degrade(&mut self, d: f32) {
+        if let Self::TapeLoop(s) = self { s.set_degrade(d); }
+    }
+
+    #[inline]
+    pub fn set_wow(&mut self, w: f32) {
+        if let Self::TapeLoop(s) = self { s.set_wow(w); }
+    }
+}
+
+impl From<GraphScene> for Scene {
+    fn from(g: GraphScene) -> Self { Self::Graph(Box::new(g)) }
+}
+
+impl Generator for Scene {
+    #[inline]
+    fn reset(&mut self, sr: f32) {
+        match self {
+            Self::SlowDrone(s) => s.reset(sr),
+            Self::Risset(s) => s.reset(sr),
+            Self::Thunderstorm(s) => s.reset(sr),
+            Self::CaveDrips(s) => s.reset(sr),
+            Self::Binaural(s) => s.reset(sr),
+            Self::TapeLoop(s) => s.reset(sr),
+            Self::Graph(s) => s.reset(sr),
+        }
+    }
+
+    #[inline]
+    fn set_sample_rate(&mut self, sr: f32) -> bool {
+        match self {
+            Self::SlowDrone(s) => s.set_sample_rate(sr),
+            Self::Risset(s) => s.set_sample_rate(sr),
+            Self::Thunderstorm(s) => s.set_sample_rate(sr),
+            Self::CaveDrips(s) => s.set_sample_rate(sr),
+            Self::Binaural(s) => s.set_sample_rate(sr),
+            Self::TapeLoop(s) => s.set_sample_rate(sr),
+            Self::Graph(s) => s.set_sample_rate(sr),
+        }
+    }
+
+    #[inline]
+    fn next(&mut self) -> f32 {
+        match self {
+            Self::SlowDrone(s) => s.next(),
+            Self::Risset(s) => s.next(),
+            Self::Thunderstorm(s) => s.next(),
+            Self::CaveDrips(s) => s.next(),
+            Self::Binaural(s) => s.next(),
+            Self::TapeLoop(s) => s.next(),
+            Self::Graph(s) => s.next(),
+        }
+    }
+
+    #[inline]
+    fn next_stereo(&mut self) -> (f32, f32) {
+        match self {
+            Self::SlowDrone(s) => s.next_stereo(),
+            Self::Risset(s) => s.next_stereo(),
+            Self::Thunderstorm(s) => s.next_stereo(),
+            Self::CaveDrips(s) => s.next_stereo(),
+            Self::Binaural(s) => s.next_stereo(),
+            Self::TapeLoop(s) => s.next_stereo(),
+            Self::Graph(s) => s.next_stereo(),
+        }
+    }
+
+    #[inline]
+    fn next_bus(&mut self) -> BusFrame {
+        match self {
+            Self::SlowDrone(s) => s.next_bus(),
+            Self::Risset(s) => s.next_bus(),
+            Self::Thunderstorm(s) => s.next_bus(),
+            Self::CaveDrips(s) => s.next_bus(),
+            Self::Binaural(s) => s.next_bus(),
+            Self::TapeLoop(s) => s.next_bus(),
+            Self::Graph(s) => s.next_bus(),
+        }
+    }
+
+    #[inline]
+    fn bus_settings(&self) -> BusSettings {
+        match self {
+            Self::SlowDrone(s) => s.bus_settings(),
+            Self::Risset(s) => s.bus_settings(),
+            Self::Thunderstorm(s) => s.bus_settings(),
+            Self::CaveDrips(s) => s.bus_settings(),
+            Self::Binaural(s) => s.bus_settings(),
+            Self::TapeLoop(s) => s.bus_settings(),
+            Self::Graph(s) => s.bus_settings(),
+        }
+    }
+
+    #[inline]
+    fn set_output_trim(&mut self, trim: f32) {
+        match self {
+            Self::SlowDrone(s) => s.set_output_trim(trim),
+            Self::Risset(s) => s.set_output_trim(trim),
+            Self::Thunderstorm(s) => s.set_output_trim(trim),
+            Self::CaveDrips(s) => s.set_output_trim(trim),
+            Self::Binaural(s) => s.set_output_trim(trim),
+            Self::TapeLoop(s) => s.set_output_trim(trim),
+            Self::Graph(s) => s.set_output_trim(trim),
+        }
+    }
+    #[inline]
+    fn is_idle(&self) -> bool {
+        match self {
+            Self::SlowDrone(s) => s.is_idle(),
+            Self::Risset(s) => s.is_idle(),
+            Self::Thunderstorm(s) => s.is_idle(),
+            Self::CaveDrips(s) => s.is_idle(),
+            Self::Binaural(s) => s.is_idle(),
+            Self::TapeLoop(s) => s.is_idle(),
+            Self::Graph(s) => s.is_idle(),
+        }
+    }
+
+    #[inline]
+    fn skip_idle(&mut self, max_frames: usize) -> usize {
+        match self {
+            Self::SlowDrone(s) => s.skip_idle(max_frames),
+            Self::Risset(s) => s.skip_idle(max_frames),
+            Self::Thunderstorm(s) => s.skip_idle(max_frames),
+            Self::CaveDrips(s) => s.skip_idle(max_frames),
+            Self::Binaural(s) => s.skip_idle(max_frames),
+            Self::TapeLoop(s) => s.skip_idle(max_frames),
+            Self::Graph(s) => s.skip_idle(max_frames),
+        }
+    }
+    #[inline]
+    fn render(&mut self, ctx: &RenderCtx) -> BusFrame {
+        match self {
+            Self::SlowDrone(s) => s.render(ctx),
+            Self::Risset(s) => s.render(ctx),
+            Self::Thunderstorm(s) => s.render(ctx),
+            Self::CaveDrips(s) => s.render(ctx),
+            Self::Binaural(s) => s.render(ctx),
+            Self::TapeLoop(s) => s.render(ctx),
+            Self::Graph(s) => s.render(ctx),
+        }
+    }
+
+    #[inline]
+    fn set_time(&mut self, t: f64) {
+        match self {
+            Self::SlowDrone(s) => s.set_time(t),
+            Self::Risset(s) => s.set_time(t),
+            Self::Thunderstorm(s) => s.set_time(t),
+            Self::CaveDrips(s) => s.set_time(t),
+            Self::Binaural(s) => s.set_time(t),
+            Self::TapeLoop(s) => s.set_time(t),
+            Self::Graph(s) => s.set_time(t),
+        }
+    }
+
+    #[inline]
+    fn pop_event(&mut self) -> Option<SceneEvent> {
+        match self {
+            Self::SlowDrone(s) => s.pop_event(),
+            Self::Risset(s) => s.pop_event(),
+            Self::Thunderstorm(s) => s.pop_event(),
+            Self::CaveDrips(s) => s.pop_event(),
+            Self::Binaural(s) => s.pop_event(),
+            Self::TapeLoop(s) => s.pop_event(),
+            Self::Graph(s) => s.pop_event(),
+        }
+    }
+
+    #[inline]
+    fn tap_index(&self, name: &str) -> Option<usize> {
+        match self {
+            Self::SlowDrone(s) => s.tap_index(name),
+            Self::Risset(s) => s.tap_index(name),
+            Self::Thunderstorm(s) => s.tap_index(name),
+            Self::CaveDrips(s) => s.tap_index(name),
+            Self::Binaural(s) => s.tap_index(name),
+            Self::TapeLoop(s) => s.tap_index(name),
+            Self::Graph(s) => s.tap_index(name),
+        }
+    }
+
+    #[inline]
+    fn tap_value(&self, index: usize) -> f32 {
+        match self {
+            Self::SlowDrone(s) => s.tap_value(index),
+            Self::Risset(s) => s.tap_value(index),
+            Self::Thunderstorm(s) => s.tap_value(index),
+            Self::CaveDrips(s) => s.tap_value(index),
+            Self::Binaural(s) => s.tap_value(index),
+            Self::TapeLoop(s) => s.tap_value(index),
+            Self::Graph(s) => s.tap_value(index),
+        }
+    }
+
+    fn param_value(&self, id: ParamId) -> Option<f32> {
+        Some(match (self, id) {
+            (s, ParamId::Intensity) => s.intensity(),
+            (s, ParamId::TimeWarp) => s.time_warp(),
+            (Self::SlowDrone(s), ParamId::SceneGain) => s.gain(),
+            (Self::Risset(s), ParamId::SceneGain) => s.gain(),
+            (Self::Thunderstorm(s), ParamId::SceneGain) => s.gain(),
+            (Self::CaveDrips(s), ParamId::SceneGain) => s.gain(),
+            (Self::Binaural(s), ParamId::SceneGain) => s.gain(),
+            (Self::TapeLoop(s), ParamId::SceneGain) => s.gain(),
+            (Self::Graph(s), ParamId::SceneGain) => s.gain(),
+            (Self::SlowDrone(s), ParamId::CutBase) => s.cut_base(),
+            (Self::SlowDrone(s), ParamId::CutSpan) => s.cut_span(),
+            (Self::SlowDrone(s), ParamId::Drive) => s.drive(),
+            (Self::SlowDrone(s), ParamId::Detune) => s.detune_cents(),
+            (Self::Risset(s), ParamId::Inharmonicity) => s.inharmonicity(),
+            (Self::Risset(s), ParamId::MotionRate) => s.motion_rate(),
+            (Self::Thunderstorm(s), ParamId::RumbleRate) => s.rumble_rate(),
+            (Self::Thunderstorm(s), ParamId::DuckDepth) => s.duck_depth_db(),
+            (Self::CaveDrips(s), ParamId::DripDensity) => s.density(),
+            (Self::CaveDrips(s), ParamId::Wetness) => s.wetness(),
+            (Self::Binaural(s), ParamId::CarrierHz) => s.carrier_hz(),
+            (Self::Binaural(s), ParamId::BeatHz) => s.beat_hz(),
+            (Self::Binaural(s), ParamId::NoiseBed) => s.noise_bed(),
+            (Self::TapeLoop(s), ParamId::Degrade) => s.degrade(),
+            (Self::TapeLoop(s), ParamId::Wow) => s.wow(),
+            _ => return None,
+        })
+    }
+
+    fn set_param_value(&mut self, id: ParamId, v: f32) -> bool {
+        if self.param_value(id).is_none() { return false; }
+        match id {
+            ParamId::Intensity => self.set_intensity(v),
+            ParamId::SceneGain => self.set_gain(v),
+            ParamId::CutBase => self.set_cut_base(v),
+            ParamId::CutSpan => self.set_cut_span(v),
+            ParamId::Drive => self.set_drive(v),
+            ParamId::Detune => self.set_detune_cents(v),
+            ParamId::Inharmonicity => self.set_inharmonicity(v),
+            ParamId::MotionRate => self.set_motion_rate(v),
+            ParamId::RumbleRate => self.set_rumble_rate(v),
+            ParamId::DuckDepth => self.set_duck_depth_db(v),
+            ParamId::DripDensity => self.set_drip_density(v),
+            ParamId::Wetness => self.set_wetness(v),
+            ParamId::CarrierHz => self.set_carrier_hz(v),
+            ParamId::BeatHz => self.set_beat_hz(v),
+            ParamId::NoiseBed => self.set_noise_bed(v),
+            ParamId::Degrade => self.set_degrade(v),
+            ParamId::Wow => self.set_wow(v),
+            ParamId::TimeWarp => self.set_time_warp(v),
+            _ => return false,
+        }
+        true
+    }
+
+    fn reset_params(&mut self) {
+        if let Self::Graph(s) = self {
+            s.reset_params();
+            return;
+        }
+        for (id, v) in self.defaults().iter() { self.set_param_value(id, v); }
+    }
+
+    fn loudness_db(&self) -> Option<f32> {
+        let i = match self {
+            Self::SlowDrone(_) => 0,
+            Self::Risset(_) => 1,
+            Self::Thunderstorm(_) => 2,
+            Self::CaveDrips(_) => 3,
+            Self::Binaural(_) => 4,
+            Self::TapeLoop(_) => 5,
+            Self::Graph(_) => return None,
+        };
+        Some(LOUDNESS_DB[i])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{measure_loudness_db, Engine};
+
+    #[test]
+    fn setters_ignore_non_finite_values() {
+        for &name in SCENE_NAMES {
+            let mut s = Scene::by_name(name, 48_000.0).unwrap();
+            for id in ParamId::ALL {
+                let Some(before) = s.param_value(id) else { continue };
+                for v in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+                    s.set_param_value(id, v);
+                    assert_eq!(s.param_value(id), Some(before), "{name} {}", id.name());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reset_params_restores_the_defaults_table() {
+        for &name in SCENE_NAMES {
+            let mut s = Scene::by_name(name, 48_000.0).unwrap();
+            let defaults = s.defaults();
+            let ids: Vec<ParamId> = ParamId::ALL.into_iter().filter(|&id| s.param_value(id).is_some()).collect();
+            // the table covers every parameter and matches a fresh scene
+            for &id in &ids {
+                assert_eq!(s.param_value(id), defaults.get(id), "{name} {}", id.name());
+            }
+            for &id in &ids { s.set_param_value(id, id.range().1); }
+            assert!(ids.iter().any(|&id| s.param_value(id) != defaults.get(id)), "{name}");
+            s.reset_params();
+            for &id in &ids {
+                let (got, want) = (s.param_value(id).unwrap(), defaults.get(id).unwrap());
+                assert!((got - want).abs() <= 1e-4 * want.abs().max(1.0), "{name} {}: {got} vs {want}", id.name());
+            }
+        }
+    }
+
+    #[test]
+    fn loudness_table_matches_the_scenes() {
+        // as `ambientor-cli calibrate` measures them
+        for &name in SCENE_NAMES {
+            let scene = Scene::by_name(name, 48_000.0).unwrap();
+            let want = scene.loudness_db().unwrap();
+            let mut engine = Engine::new(scene);
+            engine.set_sample_rate(48_000.0);
+            let got = measure_loudness_db(&mut engine, 5.0, 60.0);
+            assert!((got - want).abs() < 0.5, "{name}: measured {got:.1} dB, table says {want:.1}");
+        }
+    }
+
+    #[test]
+    fn tape_loop_reports_its_rate_change_reset() {
+        let mut s = Scene::tape_loop(48_000.0);
+        for _ in 0..48_000 { s.next_stereo(); }
+        assert!(s.set_sample_rate(44_100.0));
+    }
+}
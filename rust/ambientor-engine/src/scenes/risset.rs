@@ -0,0 +1,253 @@
+//! "risset": evolving harmonic drone on additive partials.
+
+use crate::params::ParamId;
+use crate::graph::{BusFrame, Generator};
+use crate::scenes::intensity::Intensity;
+use crate::scenes::TIME_WARP_RANGE;
+use crate::nodes::{AdditiveOsc, ControlClock, Lfo, OnePoleSmoother, SmoothNoise};
+use ambientor_core::dsp::Tuning;
+
+const PARTIALS: usize = 16;
+/// Fundamental (A1; 55 Hz in standard tuning).
+const RISSET_ROOT_NOTE: f32 = 33.0;
+/// Partial amplitudes are recomputed, and the partials rendered ahead, once
+/// per this many samples.
+const CTRL_BLOCK: u32 = 64;
+/// Share sent to the engine's reverb (the rest stays dry).
+const REVERB_SEND: f32 = 0.25;
+/// Starting values of the host parameters (see [`RissetDrone::DEFAULTS`]).
+const DEFAULT_GAIN: f32 = 0.9;
+const DEFAULT_INHARM: f32 = 0.0;
+const DEFAULT_MOTION: f32 = 1.0;
+
+/// Additive drone in the spirit of Risset's harmonic arpeggios:
+/// - 16 sine partials over a low fundamental,
+/// - a spectral "window" (in octaves) whose center — the centroid — wanders slowly,
+/// - per-partial swell LFOs, staggered in phase, so partials crossfade in and out,
+/// - stretched (inharmonic) partials on demand,
+/// - a send to the engine reverb for space.
+///
+/// In stereo the two sides run their own partial drift, so the beating differs
+/// left and right. The partials are rendered a control block at a time with
+/// [`AdditiveOsc::process_block`] and played out per frame.
+#[derive(Copy, Clone)]
+pub struct RissetDrone {
+    osc_l: AdditiveOsc<PARTIALS>,
+    osc_r: AdditiveOsc<PARTIALS>,
+    block_l: [f32; CTRL_BLOCK as usize], // this control block's partial sums
+    block_r: [f32; CTRL_BLOCK as usize],
+    pos: usize, // next frame in the blocks
+    swells: [Lfo; PARTIALS],
+    centroid_lfo: Lfo,
+    centroid_drift: SmoothNoise,
+    // parameters
+    sr: f32,
+    tuning: Tuning,
+    inharm: f32,  // partial stretch exponent, 0 = harmonic
+    motion: f32,  // multiplier on all modulation rates
+    window: f32,  // spectral window width (octaves)
+    out_gain: f32,
+    trim: f32, // from Generator::set_output_trim
+    // state
+    gain_sm: OnePoleSmoother,
+    intensity: Intensity,
+    warp: f32, // time warp on slow motion rates
+    ctrl: ControlClock,
+}
+impl core::fmt::Debug for RissetDrone {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RissetDrone")
+            .field("sr", &self.sr)
+            .field("inharm", &self.inharm)
+            .field("motion", &self.motion)
+            .finish()
+    }
+}
+
+impl RissetDrone {
+    /// Host parameters as [`RissetDrone::new`] sets them (intensity aside).
+    pub const DEFAULTS: [(ParamId, f32); 3] = [
+        (ParamId::SceneGain, DEFAULT_GAIN),
+        (ParamId::Inharmonicity, DEFAULT_INHARM),
+        (ParamId::MotionRate, DEFAULT_MOTION),
+    ];
+
+    pub fn new(sr: f32) -> Self {
+        let tuning = Tuning::equal();
+        let root = tuning.note_to_hz(RISSET_ROOT_NOTE);
+        let mut osc_l = AdditiveOsc::new(root, 0x51);
+        let mut osc_r = AdditiveOsc::new(root, 0xA7);
+        for o in [&mut osc_l, &mut osc_r] {
+            o.set_tilt_db_per_oct(-3.0);
+            o.set_drift(4.0, 0.07);
+        }
+        let mut s = Self {
+            osc_l,
+            osc_r,
+            block_l: [0.0; CTRL_BLOCK as usize],
+            block_r: [0.0; CTRL_BLOCK as usize],
+            pos: 0,
+            swells: [Lfo::sine(0.01); PARTIALS],
+            centroid_lfo: Lfo::sine(0.004), // ~4 min sweep
+            centroid_drift: SmoothNoise::new(0.02, 2, 0x5EED),
+            sr,
+            tuning,
+            inharm: DEFAULT_INHARM,
+            motion: DEFAULT_MOTION,
+            window: 1.1,
+            out_gain: DEFAULT_GAIN,
+            trim: 1.0,
+            gain_sm: OnePoleSmoother::new_ms(30.0, sr),
+            intensity: Intensity::new(sr / CTRL_BLOCK as f32),
+            warp: 1.0,
+            ctrl: ControlClock::new(CTRL_BLOCK),
+        };
+        for (i, l) in s.swells.iter_mut().enumerate() {
+            // golden-ratio spacing keeps the swells from lining up
+            l.set_phase_offset((i as f32 * 0.618_034).fract());
+        }
+        s.update_rates();
+        s.update_partials();
+        s.gain_sm.reset(s.out_gain);
+        s
+    }
+
+    #[inline] pub fn set_gain(&mut self, g: f32) { if g.is_finite() { self.out_gain = g.clamp(0.0, 1.0); } }
+    #[inline] pub fn gain(&self) -> f32 { self.out_gain }
+
+    /// Stretch partials away from the harmonic series: ratio = n^(1 + x), x in [0, 0.15].
+    #[inline]
+    pub fn set_inharmonicity(&mut self, x: f32) {
+        if !x.is_finite() { return; }
+        self.inharm = x.clamp(0.0, 0.15);
+        self.update_partials();
+    }
+    #[inline] pub fn inharmonicity(&self) -> f32 { self.inharm }
+
+    /// Speed of all slow motion (swells and centroid), 1.0 = default, 0.1..10.
+    #[inline]
+    pub fn set_motion_rate(&mut self, m: f32) {
+        if !m.is_finite() { return; }
+        self.motion = m.clamp(0.1, 10.0);
+        self.update_rates();
+    }
+    #[inline] pub fn motion_rate(&self) -> f32 { self.motion }
+
+    /// Intensity 0..1 (0.5 = default): centroid height (brightness), spectral
+    /// window width (how many partials sound at once), and level.
+    #[inline] pub fn set_intensity(&mut self, x: f32) { self.intensity.set(x); }
+    #[inline] pub fn intensity(&self) -> f32 { self.intensity.target() }
+
+    /// Time warp 0.1..10 (see [`Scene::set_time_warp`](crate::scenes::Scene::set_time_warp)):
+    /// multiplies the motion rate.
+    #[inline]
+    pub fn set_time_warp(&mut self, w: f32) {
+        if !w.is_finite() { return; }
+        self.warp = w.clamp(TIME_WARP_RANGE.0, TIME_WARP_RANGE.1);
+        self.update_rates();
+    }
+    #[inline] pub fn time_warp(&self) -> f32 { self.warp }
+
+    #[inline]
+    pub fn set_tuning(&mut self, t: Tuning) {
+        self.tuning = t;
+        let root = t.note_to_hz(RISSET_ROOT_NOTE);
+        self.osc_l.set_freq(root);
+        self.osc_r.set_freq(root);
+    }
+    #[inline] pub fn tuning(&self) -> &Tuning { &self.tuning }
+
+    fn update_rates(&mut self) {
+        let speed = self.motion * self.warp;
+        for (i, l) in self.swells.iter_mut().enumerate() {
+            let spread = (i as f32 * 0.381_966).fract(); // 0..1, decorrelated per partial
+            l.set_rate(speed * (0.006 + 0.012 * spread));
+        }
+        self.centroid_lfo.set_rate(speed * 0.004);
+        self.centroid_drift.set_rate(speed * 0.02);
+    }
+
+    fn update_partials(&mut self) {
+        for i in 0..PARTIALS {
+            let r = ((i + 1) as f32).powf(1.0 + self.inharm);
+            self.osc_l.set_partial_ratio(i, r);
+            self.osc_r.set_partial_ratio(i, r);
+        }
+    }
+
+    /// Move the centroid and swells, then set partial amplitudes (control rate).
+    #[inline]
+    fn control(&mut self) {
+        let csr = self.ctrl.rate(self.sr);
+        self.intensity.tick();
+        let x = self.intensity.value();
+        // centroid in octaves above the fundamental, ~0.3..3.3 (±0.8 oct with intensity)
+        let c = 1.8 + 1.1 * self.centroid_lfo.next_norm(csr) + 0.4 * self.centroid_drift.next(csr)
+            + 1.6 * (x - 0.5);
+        let window = self.window * (0.75 + 0.5 * x);
+        let inv_w2 = 1.0 / (2.0 * window * window);
+        for i in 0..PARTIALS {
+            let oct = ((i + 1) as f32).log2() * (1.0 + self.inharm);
+            let d = oct - c;
+            let win = (-d * d * inv_w2).exp();
+            let swell = self.swells[i].next01(csr);
+            let a = win * (0.25 + 0.75 * swell);
+            self.osc_l.set_partial_amp(i, a);
+            self.osc_r.set_partial_amp(i, a);
+        }
+    }
+}
+
+impl Generator for RissetDrone {
+    #[inline]
+    fn reset(&mut self, sr: f32) {
+        self.set_sample_rate(sr);
+        for l in &mut self.swells { l.set_phase01(0.0); }
+        self.centroid_lfo.set_phase01(0.0);
+        self.gain_sm.reset(self.out_gain);
+        self.intensity.snap();
+        self.ctrl.reset();
+    }
+
+    #[inline]
+    fn set_sample_rate(&mut self, sr: f32) -> bool {
+        self.sr = sr.max(1.0);
+        self.gain_sm.set_time_ms(30.0, self.sr);
+        self.intensity.set_sample_rate(self.ctrl.rate(self.sr));
+        false
+    }
+
+    #[inline]
+    fn next(&mut self) -> f32 {
+        let (l, r) = self.next_stereo();
+        0.5 * (l + r)
+    }
+
+    /// Dry frame only; the engine adds the room from [`Generator::next_bus`].
+    #[inline]
+    fn next_stereo(&mut self) -> (f32, f32) { self.next_bus().dry }
+
+    #[inline]
+    fn next_bus(&mut self) -> BusFrame {
+        if self.ctrl.tick() {
+            self.control();
+            self.osc_l.process_block(self.sr, &mut self.block_l);
+            self.osc_r.process_block(self.sr, &mut self.block_r);
+            self.pos = 0;
+        }
+        let (l, r) = (self.block_l[self.pos], self.block_r[self.pos]);
+        self.pos += 1;
+        // partial sums are normalized by the partial count; bring them back up
+        let g = self.gain_sm.process(self.out_gain * self.trim * self.intensity.level());
+        let (l, r) = (4.0 * l * g, 4.0 * r * g);
+        let dry = 1.0 - REVERB_SEND;
+        BusFrame {
+            dry: ((dry * l).clamp(-1.0, 1.0), (dry * r).clamp(-1.0, 1.0)),
+            reverb: (REVERB_SEND * l, REVERB_SEND * r),
+            delay: (0.0, 0.0),
+        }
+    }
+
+    #[inline]
+    fn set_output_trim(&mut self, trim: f32) { self.trim = trim.clamp(0.0, 1.0); }
+}
@@ -0,0 +1,279 @@
+//! "slow-drone": two detuned oscillators under slow filter/detune drift.
+
+use crate::params::ParamId;
+use crate::graph::{BusFrame, Generator};
+use crate::scenes::intensity::Intensity;
+use crate::scenes::TIME_WARP_RANGE;
+use crate::nodes::{AutoPan, ControlClock, ControlRamp, Osc, Lfo, NoiseMod, Wave, OnePoleSmoother};
+use ambientor_core::filters::OnePoleLP;
+use ambientor_core::dsp::{saturate, Tuning};
+
+/// Root of the slow drone (A2; 110 Hz in standard tuning).
+const DRONE_ROOT_NOTE: f32 = 45.0;
+/// Share of the drone sent to the engine's reverb (the rest stays dry).
+const REVERB_SEND: f32 = 0.25;
+/// Starting values of the host parameters (see [`SlowDrone::DEFAULTS`]).
+const DEFAULT_GAIN: f32 = 0.33;
+const DEFAULT_CUT_BASE: f32 = 900.0;
+const DEFAULT_CUT_SPAN: f32 = 600.0;
+const DEFAULT_DRIVE: f32 = 0.9;
+const DEFAULT_DETUNE_CENTS: f32 = 3.0;
+
+/// The starter scene, a **slow evolving drone**:
+/// - Two oscillators (tri + saw) near a musical interval,
+/// - Very slow drift in cutoff and detune,
+/// - Gentle low-pass tone control,
+/// - Mild saturation,
+/// - A quarter of the signal sent to the engine reverb for space.
+///
+/// In stereo, each oscillator layer is slowly auto-panned (one by an LFO, one by
+/// random drift) so the drone breathes across the field.
+///
+/// Cutoff and detune move at control rate ([`ControlClock`]); oscillator
+/// frequencies are interpolated per sample in between.
+#[derive(Copy, Clone)]
+pub struct SlowDrone {
+    // tone sources
+    osc_a: Osc,
+    osc_b: Osc,
+    // motion
+    lfo_cut: Lfo,
+    drift_detune: NoiseMod, // runs at control rate
+    ctrl: ControlClock,
+    freq_a: ControlRamp,
+    freq_b: ControlRamp,
+    pan_a: AutoPan,
+    pan_b: AutoPan,
+    // tone shaping (lp_r only used by the stereo path)
+    lp: OnePoleLP,
+    lp_r: OnePoleLP,
+    // output stage
+    // parameters
+    sr: f32,
+    tuning: Tuning,
+    root_hz: f32, // DRONE_ROOT_NOTE through `tuning`
+    base_cut: f32,
+    cut_span: f32,
+    detune_cents: f32,
+    drive: f32,
+    out_gain: f32,
+    trim: f32, // from Generator::set_output_trim
+    // smoothed controls
+    gain_sm: OnePoleSmoother,
+    intensity: Intensity,
+    warp: f32, // time warp on slow motion rates
+    motion: [f32; 3], // last control block's values of `TAPS`
+}
+impl core::fmt::Debug for SlowDrone {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SlowDrone")
+            .field("sr", &self.sr)
+            .finish()
+    }
+}
+
+impl SlowDrone {
+    /// Host parameters as [`SlowDrone::new`] sets them (intensity aside).
+    pub const DEFAULTS: [(ParamId, f32); 5] = [
+        (ParamId::SceneGain, DEFAULT_GAIN),
+        (ParamId::CutBase, DEFAULT_CUT_BASE),
+        (ParamId::CutSpan, DEFAULT_CUT_SPAN),
+        (ParamId::Drive, DEFAULT_DRIVE),
+        (ParamId::Detune, DEFAULT_DETUNE_CENTS),
+    ];
+
+    /// Analysis taps (see [`crate::taps`]): the cutoff LFO (0..1), the
+    /// filter cutoff it drives (Hz) and the detune drift (cents).
+    pub const TAPS: [&'static str; 3] = ["lfo", "cutoff", "drift"];
+
+    /// Construct the default “slow_drone” scene. Safe defaults for 44.1–48 kHz.
+    pub fn new(sr: f32) -> Self {
+        let mut s = Self {
+            // Sources (rough A2 + sub/5th; adjust by ear)
+            osc_a: Osc::new(110.0, Wave::Tri),
+            osc_b: Osc::new(110.0 * 0.498, Wave::Saw),
+            // Motion
+            lfo_cut: Lfo::sine(0.05), // ~20 s period
+            drift_detune: NoiseMod::new(-6.0, 6.0, 7.5, 0.25, ControlClock::default().rate(sr)), // ±6 cents target, pick every ~7.5 s
+            ctrl: ControlClock::default(),
+            freq_a: ControlRamp::new(110.0),
+            freq_b: ControlRamp::new(110.0 * 0.498),
+            pan_a: AutoPan::lfo(0.031, 0.6),        // ~32 s sweep
+            pan_b: AutoPan::noise(11.0, 0.7, sr),   // new spot every ~11 s
+            // Tone shaping
+            lp: OnePoleLP::new(900.0, sr),
+            lp_r: OnePoleLP::new(900.0, sr),
+            // Space
+            // Params
+            sr,
+            tuning: Tuning::equal(),
+            root_hz: Tuning::equal().note_to_hz(DRONE_ROOT_NOTE),
+            base_cut: DEFAULT_CUT_BASE,
+            cut_span: DEFAULT_CUT_SPAN,
+            detune_cents: DEFAULT_DETUNE_CENTS, // depth of LFO on detune (additional to noise drift)
+            drive: DEFAULT_DRIVE,
+            out_gain: DEFAULT_GAIN,
+            trim: 1.0,
+            gain_sm: OnePoleSmoother::new_ms(30.0, sr),
+            intensity: Intensity::new(ControlClock::default().rate(sr)),
+            warp: 1.0,
+            motion: [0.0; 3],
+        };
+        s.gain_sm.reset(s.out_gain);
+        s
+    }
+
+    /// Tweakers (optional use at runtime from host if you expose a control UI)
+    #[inline] pub fn set_cut_base(&mut self, hz: f32) { if hz.is_finite() { self.base_cut = hz.max(50.0); } }
+    #[inline] pub fn set_cut_span(&mut self, hz: f32) { if hz.is_finite() { self.cut_span = hz.max(0.0); } }
+    #[inline] pub fn set_drive(&mut self, d: f32)     { if d.is_finite() { self.drive = d.clamp(0.1, 5.0); } }
+    #[inline] pub fn set_gain(&mut self, g: f32)      { if g.is_finite() { self.out_gain = g.clamp(0.0, 1.0); } }
+    #[inline] pub fn set_detune_cents(&mut self, c: f32) { if c.is_finite() { self.detune_cents = c.clamp(0.0, 25.0); } }
+    #[inline] pub fn cut_base(&self) -> f32 { self.base_cut }
+    #[inline] pub fn cut_span(&self) -> f32 { self.cut_span }
+    #[inline] pub fn drive(&self) -> f32 { self.drive }
+    #[inline] pub fn gain(&self) -> f32 { self.out_gain }
+    #[inline] pub fn detune_cents(&self) -> f32 { self.detune_cents }
+
+    /// Intensity 0..1 (0.5 = default): filter brightness and level.
+    #[inline] pub fn set_intensity(&mut self, x: f32) { self.intensity.set(x); }
+    #[inline] pub fn intensity(&self) -> f32 { self.intensity.target() }
+
+    /// Time warp 0.1..10 (see [`Scene::set_time_warp`](crate::scenes::Scene::set_time_warp)):
+    /// speed of the cutoff LFO, detune drift, and auto-pans.
+    #[inline]
+    pub fn set_time_warp(&mut self, w: f32) {
+        if !w.is_finite() { return; }
+        self.warp = w.clamp(TIME_WARP_RANGE.0, TIME_WARP_RANGE.1);
+        self.rescale_motion();
+    }
+    #[inline] pub fn time_warp(&self) -> f32 { self.warp }
+
+    /// Retune the drone (reference pitch and/or ratio table).
+    #[inline]
+    pub fn set_tuning(&mut self, t: Tuning) {
+        self.tuning = t;
+        self.root_hz = t.note_to_hz(DRONE_ROOT_NOTE);
+    }
+    #[inline] pub fn tuning(&self) -> &Tuning { &self.tuning }
+
+    #[inline]
+    fn cents_to_ratio(c: f32) -> f32 {
+        // 1200 cents = 2x; ratio = 2^(c/1200)
+        (core::f32::consts::LN_2 * (c / 1200.0)).exp()
+    }
+
+    /// Rate the modulators step at: the sample rate slowed by the time warp,
+    /// so they run `warp` times faster.
+    #[inline] fn motion_sr(&self) -> f32 { self.sr / self.warp }
+
+    /// Retune the modulators that keep their own sample rate.
+    fn rescale_motion(&mut self) {
+        self.drift_detune.reset_sr(self.ctrl.rate(self.motion_sr()));
+        self.pan_b.reset_sr(self.motion_sr());
+    }
+
+    /// Advance the slow modulators one control block and push cutoff/detune
+    /// into the nodes; oscillator frequencies glide there over the block.
+    #[inline]
+    fn modulate(&mut self) {
+        let sr = self.ctrl.rate(self.motion_sr());
+
+        // Evolving cutoff: base ± span via very slow LFO, scaled by intensity
+        self.intensity.tick();
+        let lfo01 = self.lfo_cut.next01(sr); // 0..1
+        let cut = (self.base_cut + (lfo01 - 0.5) * 2.0 * self.cut_span) * self.intensity.brightness();
+        let block = self.ctrl.block();
+        self.lp.set_cutoff_smoothed(cut.max(80.0), block);
+        self.lp_r.set_cutoff_smoothed(cut.max(80.0), block);
+
+        // Very slow detune drift (in cents) + subtle LFO detune
+        let drift_cents = self.drift_detune.next(sr);            // in [-6, +6] by design
+        self.motion = [lfo01, cut.max(80.0), drift_cents];
+        let lfo_cents   = (lfo01 - 0.5) * 2.0 * self.detune_cents;
+        let ratio_a = Self::cents_to_ratio(drift_cents + 0.5 * lfo_cents);
+        let ratio_b = Self::cents_to_ratio(-drift_cents + lfo_cents);
+
+        let n = self.ctrl.block();
+        self.freq_a.set_target(self.root_hz * ratio_a, n);
+        self.freq_b.set_target(self.root_hz * 0.498 * ratio_b, n);
+    }
+}
+
+impl Generator for SlowDrone {
+    #[inline]
+    fn reset(&mut self, sr: f32) {
+        self.set_sample_rate(sr);
+        self.lfo_cut.set_rate(0.05);
+        self.lfo_cut.set_phase01(0.0);
+        self.osc_a.set_phase01(0.0);
+        self.osc_b.set_phase01(0.0);
+        self.pan_a.reset();
+        self.pan_b.reset();
+        self.lp.reset();
+        self.lp_r.reset();
+        self.gain_sm.reset(self.out_gain);
+        self.intensity.snap();
+        self.ctrl.reset();
+        self.modulate();
+        self.freq_a.snap(self.freq_a.target());
+        self.freq_b.snap(self.freq_b.target());
+    }
+
+    /// Rescale filters, drift, and smoothing; phases and tails carry on.
+    #[inline]
+    fn set_sample_rate(&mut self, sr: f32) -> bool {
+        self.sr = sr.max(1.0);
+        self.lp.set_sample_rate(self.sr);
+        self.lp_r.set_sample_rate(self.sr);
+        self.rescale_motion();
+        self.gain_sm.set_time_ms(30.0, self.sr);
+        self.intensity.set_sample_rate(self.ctrl.rate(self.sr));
+        false
+    }
+
+    #[inline]
+    fn next(&mut self) -> f32 {
+        let (l, r) = self.next_stereo();
+        0.5 * (l + r)
+    }
+
+    /// Dry frame only; the engine adds the room from [`Generator::next_bus`].
+    #[inline]
+    fn next_stereo(&mut self) -> (f32, f32) { self.next_bus().dry }
+
+    #[inline]
+    fn tap_index(&self, name: &str) -> Option<usize> { Self::TAPS.iter().position(|&t| t == name) }
+    #[inline]
+    fn tap_value(&self, index: usize) -> f32 { self.motion.get(index).copied().unwrap_or(0.0) }
+
+    #[inline]
+    fn next_bus(&mut self) -> BusFrame {
+        let sr = self.sr;
+        if self.ctrl.tick() { self.modulate(); }
+        self.osc_a.set_freq(self.freq_a.next_sample());
+        self.osc_b.set_freq(self.freq_b.next_sample());
+
+        // Auto-panned layers (constant power), summed per side
+        let msr = self.motion_sr();
+        let (al, ar) = self.pan_a.process(self.osc_a.next(sr), msr);
+        let (bl, br) = self.pan_b.process(self.osc_b.next(sr), msr);
+
+        // Tone + very light saturation per side; √2 restores the center level
+        let l = saturate(self.lp.process(0.707 * (al + bl)), self.drive);
+        let r = saturate(self.lp_r.process(0.707 * (ar + br)), self.drive);
+
+        // Smooth output gain to avoid clicks on runtime tweaks
+        let g = self.gain_sm.process(self.out_gain * self.trim * self.intensity.level());
+        let (l, r) = (l * g, r * g);
+        let dry = 1.0 - REVERB_SEND;
+        BusFrame {
+            dry: ((dry * l).clamp(-1.0, 1.0), (dry * r).clamp(-1.0, 1.0)),
+            reverb: (REVERB_SEND * l, REVERB_SEND * r),
+            delay: (0.0, 0.0),
+        }
+    }
+
+    #[inline]
+    fn set_output_trim(&mut self, trim: f32) { self.trim = trim.clamp(0.0, 1.0); }
+}
@@ -0,0 +1,377 @@
+//! "tape-loop": a chord pad on a short tape loop that wears out pass by pass.
+
+use crate::event::{EventQueue, SceneEvent};
+use crate::params::ParamId;
+use crate::graph::{BusFrame, Generator};
+use crate::master::BusSettings;
+use crate::scenes::intensity::Intensity;
+use crate::scenes::TIME_WARP_RANGE;
+use crate::nodes::{Lfo, OnePoleSmoother, Rng, SmoothNoise, UnisonOsc, Wave};
+use crate::triggers::{PoissonTrigger, Trigger};
+use ambientor_core::dsp::{saturate, Tuning};
+use ambientor_core::filters::{OnePoleHP, OnePoleLP};
+
+/// Tape capacity in samples (~2.7 s @ 48k). Stored as 16-bit to keep the scene small.
+const TAPE_MAX: usize = 1 << 17;
+const TAPE_SCALE: f32 = 32_767.0;
+/// Nominal loop length; shorter at high sample rates if the tape runs out.
+const TAPE_LOOP_S: f32 = 2.6;
+/// Fade at both ends of each recording, so the splice does not click.
+const SPLICE_FADE_S: f32 = 0.3;
+/// Pad voices (one chord tone each).
+const PAD_VOICES: usize = 4;
+/// Pad low-pass at default intensity.
+const PAD_TONE_HZ: f32 = 1600.0;
+/// Chord root for the pad (C3).
+const PAD_ROOT_NOTE: f32 = 48.0;
+/// Chord tones in semitones above the root; a new chord is recorded once the
+/// loop has worn away.
+const CHORDS: [[f32; PAD_VOICES]; 4] = [
+    [0.0, 7.0, 15.0, 22.0],  // Cm9 (no 9th, open)
+    [-4.0, 3.0, 10.0, 14.0], // Abmaj7
+    [-7.0, 0.0, 7.0, 14.0],  // Fsus2
+    [-2.0, 5.0, 10.0, 15.0], // Bbsus4
+];
+/// Re-record when a whole pass peaks below this.
+const WORN_OUT_PEAK: f32 = 0.05;
+/// Base playback-head offset behind the write head, leaving room for wow.
+const HEAD_GAP_S: f32 = 0.02;
+/// Share sent to the engine's reverb (the rest stays dry).
+const REVERB_SEND: f32 = 0.35;
+/// Starting values of the host parameters (see [`TapeLoop::DEFAULTS`]).
+const DEFAULT_GAIN: f32 = 0.8;
+const DEFAULT_DEGRADE: f32 = 0.4;
+const DEFAULT_WOW: f32 = 0.5;
+
+/// A decaying tape loop, in the spirit of "disintegration loops":
+/// - a detuned chord pad is recorded once onto a short mono tape loop,
+/// - every pass re-writes the tape through a low-pass that closes a little further,
+///   gentle saturation and a small level loss, plus random "flakes" (dropouts)
+///   that become more frequent as the tape wears,
+/// - playback heads wobble (slow wow + fast flutter + random drift) via a
+///   modulated fractional read, slightly differently per side,
+/// - when the loop has faded away, the next chord is recorded.
+#[derive(Clone)]
+pub struct TapeLoop {
+    tape: Box<[i16]>,
+    loop_len: usize,
+    head: usize,
+    recording: bool,
+    pass: u32,
+    pass_peak: f32,
+    chord: usize,
+    // pad (recorded)
+    pad: [UnisonOsc<3>; PAD_VOICES],
+    pad_lp: OnePoleLP,
+    // wear
+    wear_lp: OnePoleLP,
+    wear_cut: f32,
+    flake_trig: PoissonTrigger,
+    flake_left: usize, // samples of dropout still to write
+    flake_depth: f32,
+    // playback
+    wow: Lfo,
+    flutter: Lfo,
+    drift: SmoothNoise,
+    hp_l: OnePoleHP,
+    hp_r: OnePoleHP,
+    rng: Rng,
+    // parameters
+    sr: f32,
+    tuning: Tuning,
+    degrade: f32,   // 0..1 wear speed
+    wow_depth: f32, // 0..1
+    out_gain: f32,
+    trim: f32, // from Generator::set_output_trim
+    // smoothed controls
+    gain_sm: OnePoleSmoother,
+    intensity: Intensity,
+    warp: f32, // time warp on slow motion rates
+    events: EventQueue,
+}
+impl core::fmt::Debug for TapeLoop {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TapeLoop")
+            .field("sr", &self.sr)
+            .field("pass", &self.pass)
+            .field("degrade", &self.degrade)
+            .field("wow", &self.wow_depth)
+            .finish()
+    }
+}
+
+impl TapeLoop {
+    /// Host parameters as [`TapeLoop::new`] sets them (intensity aside).
+    pub const DEFAULTS: [(ParamId, f32); 3] = [
+        (ParamId::SceneGain, DEFAULT_GAIN),
+        (ParamId::Degrade, DEFAULT_DEGRADE),
+        (ParamId::Wow, DEFAULT_WOW),
+    ];
+
+    pub fn new(sr: f32) -> Self {
+        let mut s = Self {
+            tape: vec![0; TAPE_MAX].into_boxed_slice(),
+            loop_len: 1,
+            head: 0,
+            recording: true,
+            pass: 0,
+            pass_peak: 0.0,
+            chord: 0,
+            pad: [UnisonOsc::new(130.0, Wave::Saw, 9.0, 0x7A9E); PAD_VOICES],
+            pad_lp: OnePoleLP::new(PAD_TONE_HZ, sr),
+            wear_lp: OnePoleLP::new(12_000.0, sr),
+            wear_cut: 12_000.0,
+            flake_trig: PoissonTrigger::new(0.0, 0xF1A4),
+            flake_left: 0,
+            flake_depth: 0.0,
+            wow: Lfo::sine(0.45),
+            flutter: Lfo::sine(6.5),
+            drift: SmoothNoise::new(0.3, 2, 0x0D21),
+            hp_l: OnePoleHP::new(60.0, sr),
+            hp_r: OnePoleHP::new(60.0, sr),
+            rng: Rng::new(0x7A9E),
+            sr,
+            tuning: Tuning::equal(),
+            degrade: DEFAULT_DEGRADE,
+            wow_depth: DEFAULT_WOW,
+            out_gain: DEFAULT_GAIN,
+            trim: 1.0,
+            gain_sm: OnePoleSmoother::new_ms(30.0, sr),
+            intensity: Intensity::new(sr),
+            warp: 1.0,
+            events: EventQueue::new(),
+        };
+        for (i, v) in s.pad.iter_mut().enumerate() {
+            v.randomize_phases(0x7A9E + i as u32);
+        }
+        s.start_recording();
+        s.gain_sm.reset(s.out_gain);
+        s
+    }
+
+    #[inline] pub fn set_gain(&mut self, g: f32) { if g.is_finite() { self.out_gain = g.clamp(0.0, 1.0); } }
+    #[inline] pub fn gain(&self) -> f32 { self.out_gain }
+
+    /// How fast the tape wears: 0 (barely) .. 1 (falls apart in a few dozen passes).
+    #[inline] pub fn set_degrade(&mut self, d: f32) { if d.is_finite() { self.degrade = d.clamp(0.0, 1.0); } }
+    #[inline] pub fn degrade(&self) -> f32 { self.degrade }
+
+    /// Wow/flutter depth, 0 (stable) .. 1 (seasick).
+    #[inline] pub fn set_wow(&mut self, w: f32) { if w.is_finite() { self.wow_depth = w.clamp(0.0, 1.0); } }
+    #[inline] pub fn wow(&self) -> f32 { self.wow_depth }
+
+    /// Intensity 0..1 (0.5 = default): pad brightness and level. Brightness
+    /// shapes what gets recorded, so it shows up from the next chord on.
+    #[inline] pub fn set_intensity(&mut self, x: f32) { self.intensity.set(x); }
+    #[inline] pub fn intensity(&self) -> f32 { self.intensity.target() }
+
+    /// Time warp 0.1..10 (see [`Scene::set_time_warp`](crate::scenes::Scene::set_time_warp)):
+    /// speed of the wow, flutter, and drift on the playback heads.
+    #[inline] pub fn set_time_warp(&mut self, w: f32) { if w.is_finite() { self.warp = w.clamp(TIME_WARP_RANGE.0, TIME_WARP_RANGE.1); } }
+    #[inline] pub fn time_warp(&self) -> f32 { self.warp }
+
+    /// Retune the pad; takes effect with the next recorded chord.
+    #[inline] pub fn set_tuning(&mut self, t: Tuning) { self.tuning = t; }
+    #[inline] pub fn tuning(&self) -> &Tuning { &self.tuning }
+
+    /// Begin recording the current chord over a fresh loop.
+    fn start_recording(&mut self) {
+        let len = (TAPE_LOOP_S * self.sr) as usize;
+        self.loop_len = len.clamp(1, TAPE_MAX);
+        self.head = 0;
+        self.recording = true;
+        self.pass = 0;
+        self.pass_peak = 0.0;
+        self.wear_cut = 12_000.0;
+        self.wear_lp.set_cutoff_hz(self.wear_cut);
+        self.flake_left = 0;
+        self.events.push(SceneEvent::SectionChanged(self.chord as u32));
+        for (v, st) in self.pad.iter_mut().zip(CHORDS[self.chord]) {
+            let hz = self.tuning.note_to_hz(PAD_ROOT_NOTE + st);
+            v.set_freq(hz);
+            self.events.push(SceneEvent::NoteTriggered { pitch: hz, amp: 1.0 / PAD_VOICES as f32 });
+        }
+    }
+
+    /// Called when the write head wraps: wear the tape a little more.
+    fn end_of_pass(&mut self) {
+        if self.recording {
+            self.recording = false;
+        } else if self.pass_peak < WORN_OUT_PEAK {
+            self.chord = (self.chord + 1) % CHORDS.len();
+            self.start_recording();
+            return;
+        }
+        self.pass += 1;
+        self.pass_peak = 0.0;
+        self.wear_cut = (self.wear_cut * (1.0 - 0.1 * self.degrade)).max(250.0);
+        self.wear_lp.set_cutoff_hz(self.wear_cut);
+        // flakes per minute grow as the tape wears
+        let worn = (self.pass as f32 * self.degrade).min(60.0);
+        self.flake_trig.set_density(self.degrade * (6.0 + 3.0 * worn));
+    }
+
+    /// Splice fade for a recording position (0 at both ends of the loop).
+    #[inline]
+    fn splice_gain(&self, i: usize) -> f32 {
+        let fade = (SPLICE_FADE_S * self.sr).max(1.0);
+        let edge = i.min(self.loop_len - 1 - i) as f32;
+        let x = (edge / fade).min(1.0);
+        x * x * (3.0 - 2.0 * x)
+    }
+
+    /// Linear-interpolated read `delay` samples behind the write head.
+    #[inline]
+    fn read_behind(&self, delay: f32) -> f32 {
+        let len = self.loop_len as f32;
+        let mut pos = self.head as f32 - delay;
+        while pos < 0.0 { pos += len; }
+        let i0 = (pos as usize).min(self.loop_len - 1);
+        let i1 = if i0 + 1 >= self.loop_len { 0 } else { i0 + 1 };
+        let frac = pos - i0 as f32;
+        let (a, b) = (self.tape_at(i0), self.tape_at(i1));
+        a + (b - a) * frac
+    }
+
+    /// Rate the head wobble runs at: time warp makes it faster or slower.
+    #[inline] fn motion_sr(&self) -> f32 { self.sr / self.warp }
+
+    #[inline] fn tape_at(&self, i: usize) -> f32 { f32::from(self.tape[i]) * (1.0 / TAPE_SCALE) }
+
+    /// Write (record or wear) at the head, then return the playback pair.
+    #[inline]
+    fn step(&mut self) -> (f32, f32) {
+        let sr = self.sr;
+        let i = self.head;
+
+        let x = if self.recording {
+            let mut pad = 0.0;
+            for v in &mut self.pad { pad += v.next(sr); }
+            0.3 * self.pad_lp.process(pad) * self.splice_gain(i)
+        } else {
+            // wear: darken, saturate gently (≈ unity for small signals), lose level
+            let loss = 1.0 - 0.004 * self.degrade;
+            let mut y = saturate(self.wear_lp.process(self.tape_at(i)) * loss, 1.4) * (1.0 / 1.4);
+            if self.flake_trig.tick(sr) {
+                let n = ((0.01 + 0.05 * self.rng.next01()) * sr) as usize;
+                self.flake_left = n;
+                self.flake_depth = 0.5 + 0.5 * self.rng.next01();
+            }
+            if self.flake_left > 0 {
+                self.flake_left -= 1;
+                y *= 1.0 - self.flake_depth;
+            }
+            y
+        };
+        self.tape[i] = (x.clamp(-1.0, 1.0) * TAPE_SCALE) as i16;
+        self.pass_peak = self.pass_peak.max(x.abs());
+
+        // Wobbling playback heads (delay in samples behind the write head)
+        let msr = self.motion_sr();
+        let wow = self.wow.next(msr);
+        let flutter = self.flutter.next(msr);
+        let drift = self.drift.next(msr);
+        let depth = self.wow_depth * sr;
+        let base = HEAD_GAP_S * sr;
+        let d_l = base + depth * (0.006 * wow + 0.0004 * flutter + 0.004 * drift);
+        let d_r = base + depth * (0.006 * wow - 0.0004 * flutter - 0.004 * drift);
+        let out = (self.read_behind(d_l.max(1.0)), self.read_behind(d_r.max(1.0)));
+
+        self.head += 1;
+        if self.head >= self.loop_len {
+            self.head = 0;
+            self.end_of_pass();
+        }
+        out
+    }
+}
+
+impl Generator for TapeLoop {
+    #[inline]
+    fn reset(&mut self, sr: f32) {
+        self.set_sample_rate(sr);
+        self.tape.fill(0);
+        self.chord = 0;
+        self.events.clear();
+        self.start_recording();
+        self.wow.set_phase01(0.0);
+        self.flutter.set_phase01(0.0);
+        self.pad_lp.reset();
+        self.wear_lp.reset();
+        self.hp_l.reset();
+        self.hp_r.reset();
+        self.gain_sm.reset(self.out_gain);
+        self.intensity.snap();
+    }
+
+    /// Rescale filters; the loop itself is wiped and re-recorded at the new
+    /// rate, which counts as a reset.
+    #[inline]
+    fn set_sample_rate(&mut self, sr: f32) -> bool {
+        self.sr = sr.max(1.0);
+        self.pad_lp.set_sample_rate(self.sr);
+        self.wear_lp.set_sample_rate(self.sr);
+        self.hp_l.set_sample_rate(self.sr);
+        self.hp_r.set_sample_rate(self.sr);
+        self.gain_sm.set_time_ms(30.0, self.sr);
+        self.intensity.set_sample_rate(self.sr);
+        self.tape.fill(0);
+        self.start_recording();
+        true
+    }
+
+    #[inline]
+    fn next(&mut self) -> f32 {
+        let (l, r) = self.next_stereo();
+        0.5 * (l + r)
+    }
+
+    /// Dry frame only; the engine adds the room from [`Generator::next_bus`].
+    #[inline]
+    fn next_stereo(&mut self) -> (f32, f32) { self.next_bus().dry }
+
+    #[inline] fn pop_event(&mut self) -> Option<SceneEvent> { self.events.pop() }
+
+    #[inline]
+    fn next_bus(&mut self) -> BusFrame {
+        if self.intensity.tick() { self.pad_lp.set_cutoff_hz(PAD_TONE_HZ * self.intensity.brightness()); }
+        let (l, r) = self.step();
+        let g = self.gain_sm.process(self.out_gain * self.trim * self.intensity.level());
+        let (l, r) = (self.hp_l.process(l) * g, self.hp_r.process(r) * g);
+        let dry = 1.0 - REVERB_SEND;
+        BusFrame {
+            dry: ((dry * l).clamp(-1.0, 1.0), (dry * r).clamp(-1.0, 1.0)),
+            reverb: (REVERB_SEND * l, REVERB_SEND * r),
+            delay: (0.0, 0.0),
+        }
+    }
+
+    /// A mid-sized, slightly dark room.
+    fn bus_settings(&self) -> BusSettings {
+        BusSettings { reverb_room: 0.85, reverb_damp: 0.6, ..BusSettings::default() }
+    }
+
+    #[inline]
+    fn set_output_trim(&mut self, trim: f32) { self.trim = trim.clamp(0.0, 1.0); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_warp_speeds_up_the_wobble() {
+        let sr = 48_000.0;
+        let (mut plain, mut fast) = (TapeLoop::new(sr), TapeLoop::new(sr));
+        fast.set_time_warp(2.0);
+        for _ in 0..24_000 {
+            plain.next_stereo();
+            fast.next_stereo();
+        }
+        // the 0.45 Hz wow after half a second: a quarter turn at 1×, nearly a half at 2×
+        let wow = |s: &TapeLoop| s.wow.clone().next(sr);
+        let at = |turns: f32| (core::f32::consts::TAU * turns).sin();
+        assert!((wow(&plain) - at(0.225)).abs() < 0.02, "{}", wow(&plain));
+        assert!((wow(&fast) - at(0.45)).abs() < 0.02, "{}", wow(&fast));
+    }
+}
@@ -0,0 +1,349 @@
+//! "thunderstorm": rain texture with occasional distant rumbles.
+
+use crate::effects::Ducker;
+use crate::event::{EventQueue, SceneEvent};
+use crate::params::ParamId;
+use crate::graph::{BusFrame, Generator};
+use crate::master::BusSettings;
+use crate::scenes::intensity::Intensity;
+use crate::scenes::TIME_WARP_RANGE;
+use crate::nodes::{OnePoleSmoother, PanLaw, Rng, SmoothNoise};
+use crate::triggers::{PoissonTrigger, Trigger};
+use ambientor_core::dsp::Tuning;
+use ambientor_core::envelopes::{ArExp, Breakpoint, BreakpointEnv};
+use ambientor_core::filters::{OnePoleHP, OnePoleLP};
+
+/// Concurrent rain drop voices.
+const DROPS: usize = 6;
+/// Rate of the rain gusts (Hz).
+const GUST_HZ: f32 = 0.08;
+/// Starting values of the host parameters (see [`Thunderstorm::DEFAULTS`]).
+const DEFAULT_GAIN: f32 = 0.8;
+const DEFAULT_RUMBLES_PER_MIN: f32 = 1.5;
+const DEFAULT_DUCK_DB: f32 = 4.0;
+
+/// One rain drop: a short noise tick through a random band, panned.
+#[derive(Copy, Clone, Debug)]
+struct Drop {
+    env: ArExp,
+    hp: OnePoleHP,
+    lp: OnePoleLP,
+    gl: f32,
+    gr: f32,
+}
+
+/// Stereo rain: band-limited hiss with slow gusts plus Poisson-timed drop ticks.
+#[derive(Copy, Clone, Debug)]
+pub struct Rain {
+    hiss_hp: [OnePoleHP; 2],
+    hiss_lp: [OnePoleLP; 2],
+    gust: SmoothNoise,
+    drops: [Drop; DROPS],
+    next_drop: usize,
+    trig: PoissonTrigger,
+    rng: Rng,
+    sr: f32,
+    density: f32, // 0..1
+}
+
+impl Rain {
+    pub fn new(sr: f32, seed: u32) -> Self {
+        let d = Drop {
+            env: ArExp::new(0.3, 9.0, sr),
+            hp: OnePoleHP::new(1500.0, sr),
+            lp: OnePoleLP::new(4000.0, sr),
+            gl: 0.0,
+            gr: 0.0,
+        };
+        let mut s = Self {
+            hiss_hp: [OnePoleHP::new(500.0, sr); 2],
+            hiss_lp: [OnePoleLP::new(6500.0, sr); 2],
+            gust: SmoothNoise::new(GUST_HZ, 2, seed ^ 0x6057),
+            drops: [d; DROPS],
+            next_drop: 0,
+            trig: PoissonTrigger::new(0.0, seed ^ 0xD809),
+            rng: Rng::new(seed),
+            sr,
+            density: 0.5,
+        };
+        s.set_density(0.5);
+        s
+    }
+
+    /// Speed up (or slow down) the gusts by `w`.
+    #[inline] pub fn set_time_warp(&mut self, w: f32) { if w.is_finite() { self.gust.set_rate(GUST_HZ * w); } }
+
+    /// 0 = drizzle .. 1 = downpour (drop rate and hiss level).
+    #[inline]
+    pub fn set_density(&mut self, d: f32) {
+        if !d.is_finite() { return; }
+        self.density = d.clamp(0.0, 1.0);
+        self.trig.set_density(300.0 + 5000.0 * self.density);
+    }
+
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        self.sr = sr.max(1.0);
+        for f in &mut self.hiss_hp { f.set_sample_rate(self.sr); }
+        for f in &mut self.hiss_lp { f.set_sample_rate(self.sr); }
+        for d in &mut self.drops {
+            d.env.set_sr(self.sr);
+            d.hp.set_sample_rate(self.sr);
+            d.lp.set_sample_rate(self.sr);
+        }
+    }
+
+    /// Clear the hiss and drop filters.
+    pub fn clear(&mut self) {
+        for f in &mut self.hiss_hp { f.reset(); }
+        for f in &mut self.hiss_lp { f.reset(); }
+        for d in &mut self.drops {
+            d.hp.reset();
+            d.lp.reset();
+        }
+    }
+
+    #[inline]
+    fn spawn_drop(&mut self) {
+        let pan = self.rng.next_bipolar();
+        let band = 1200.0 + 3800.0 * self.rng.next01();
+        let vel = 0.3 + 0.7 * self.rng.next01();
+        let d = &mut self.drops[self.next_drop];
+        d.hp.set_cutoff_hz(band);
+        d.lp.set_cutoff_hz(band * 1.6);
+        let (gl, gr) = PanLaw::gains(pan);
+        d.gl = gl * vel;
+        d.gr = gr * vel;
+        d.env.trigger();
+        self.next_drop = (self.next_drop + 1) % DROPS;
+    }
+
+    /// One stereo frame of rain.
+    #[inline]
+    pub fn next_stereo(&mut self) -> (f32, f32) {
+        if self.trig.tick(self.sr) { self.spawn_drop(); }
+
+        let gust = 0.75 + 0.25 * self.gust.next(self.sr);
+        let level = (0.05 + 0.2 * self.density) * gust;
+        let hl = self.hiss_lp[0].process(self.hiss_hp[0].process(self.rng.next_bipolar()));
+        let hr = self.hiss_lp[1].process(self.hiss_hp[1].process(self.rng.next_bipolar()));
+        let (mut l, mut r) = (hl * level, hr * level);
+
+        for i in 0..DROPS {
+            let n = self.rng.next_bipolar();
+            let d = &mut self.drops[i];
+            let e = d.env.next();
+            if e > 0.0 {
+                let x = 0.4 * d.lp.process(d.hp.process(n)) * e;
+                l += x * d.gl;
+                r += x * d.gr;
+            }
+        }
+        (l, r)
+    }
+}
+
+/// Rain plus distant thunder:
+/// - a [`Rain`] bed whose density follows the storm intensity,
+/// - rumble events (Poisson, events/minute) — deep low-passed noise shaped by a
+///   slow breakpoint envelope with a randomized rise and long decay,
+/// - the rain ducks a few dB under each rumble and recovers slowly,
+/// - a send to a large, dark engine reverb, fed mostly by the rumbles.
+#[derive(Copy, Clone)]
+pub struct Thunderstorm {
+    rain: Rain,
+    rumble_trig: PoissonTrigger,
+    rumble_env: BreakpointEnv<4>,
+    rumble_lp1: OnePoleLP,
+    rumble_lp2: OnePoleLP,
+    rumble_gl: f32,
+    rumble_gr: f32,
+    duck: Ducker, // rumble → rain
+    rng: Rng,
+    // parameters
+    sr: f32,
+    tuning: Tuning, // unpitched; kept so tuning survives scene swaps
+    rumble_per_min: f32,
+    out_gain: f32,
+    trim: f32, // from Generator::set_output_trim
+    gain_sm: OnePoleSmoother,
+    intensity: Intensity,
+    warp: f32, // time warp on slow motion rates
+    events: EventQueue,
+}
+impl core::fmt::Debug for Thunderstorm {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Thunderstorm")
+            .field("sr", &self.sr)
+            .field("intensity", &self.intensity.target())
+            .field("rumble_per_min", &self.rumble_per_min)
+            .finish()
+    }
+}
+
+impl Thunderstorm {
+    /// Host parameters as [`Thunderstorm::new`] sets them (intensity aside).
+    pub const DEFAULTS: [(ParamId, f32); 3] = [
+        (ParamId::SceneGain, DEFAULT_GAIN),
+        (ParamId::RumbleRate, DEFAULT_RUMBLES_PER_MIN),
+        (ParamId::DuckDepth, DEFAULT_DUCK_DB),
+    ];
+
+    pub fn new(sr: f32) -> Self {
+        let mut s = Self {
+            rain: Rain::new(sr, 0x7A11),
+            rumble_trig: PoissonTrigger::new(1.5, 0x7B0D),
+            rumble_env: BreakpointEnv::new(),
+            rumble_lp1: OnePoleLP::new(120.0, sr),
+            rumble_lp2: OnePoleLP::new(120.0, sr),
+            rumble_gl: 0.707,
+            rumble_gr: 0.707,
+            duck: Ducker::new(DEFAULT_DUCK_DB, 1500.0, sr),
+            rng: Rng::new(0x5702),
+            sr,
+            tuning: Tuning::equal(),
+            rumble_per_min: DEFAULT_RUMBLES_PER_MIN,
+            out_gain: DEFAULT_GAIN,
+            trim: 1.0,
+            gain_sm: OnePoleSmoother::new_ms(30.0, sr),
+            intensity: Intensity::new(sr),
+            warp: 1.0,
+            events: EventQueue::new(),
+        };
+        s.rain.set_density(s.intensity.value());
+        s.gain_sm.reset(s.out_gain);
+        s
+    }
+
+    #[inline] pub fn set_gain(&mut self, g: f32) { if g.is_finite() { self.out_gain = g.clamp(0.0, 1.0); } }
+    #[inline] pub fn gain(&self) -> f32 { self.out_gain }
+
+    /// Storm intensity 0..1 (0.5 = default): rain density, rumble loudness, and
+    /// level. Changes glide rather than jump.
+    #[inline] pub fn set_intensity(&mut self, x: f32) { self.intensity.set(x); }
+    #[inline] pub fn intensity(&self) -> f32 { self.intensity.target() }
+
+    /// Time warp 0.1..10 (see [`Scene::set_time_warp`](crate::scenes::Scene::set_time_warp)):
+    /// speed of the rain gusts; rumbles keep their own rate.
+    #[inline]
+    pub fn set_time_warp(&mut self, w: f32) {
+        if !w.is_finite() { return; }
+        self.warp = w.clamp(TIME_WARP_RANGE.0, TIME_WARP_RANGE.1);
+        self.rain.set_time_warp(self.warp);
+    }
+    #[inline] pub fn time_warp(&self) -> f32 { self.warp }
+
+    /// Mean number of rumbles per minute (0 = none, up to 20).
+    #[inline]
+    pub fn set_rumble_rate(&mut self, per_min: f32) {
+        if !per_min.is_finite() { return; }
+        self.rumble_per_min = per_min.clamp(0.0, 20.0);
+        self.rumble_trig.set_density(self.rumble_per_min);
+    }
+    #[inline] pub fn rumble_rate(&self) -> f32 { self.rumble_per_min }
+
+    /// How far the rain dips under a rumble, in dB (0 = off, up to 24; default 4).
+    #[inline] pub fn set_duck_depth_db(&mut self, db: f32) { if db.is_finite() { self.duck.set_depth_db(db); } }
+    #[inline] pub fn duck_depth_db(&self) -> f32 { self.duck.depth_db() }
+
+    #[inline] pub fn set_tuning(&mut self, t: Tuning) { self.tuning = t; }
+    #[inline] pub fn tuning(&self) -> &Tuning { &self.tuning }
+
+    /// Start a rumble: random distance (cutoff/level), position, and shape.
+    fn start_rumble(&mut self) {
+        let near = self.rng.next01();
+        let cut = 50.0 + 130.0 * near;
+        self.rumble_lp1.set_cutoff_hz(cut);
+        self.rumble_lp2.set_cutoff_hz(cut);
+        let (gl, gr) = PanLaw::gains(0.7 * self.rng.next_bipolar());
+        self.rumble_gl = gl;
+        self.rumble_gr = gr;
+
+        let peak = (0.4 + 0.6 * near) * (0.5 + 0.5 * self.intensity.value());
+        let rise = 0.6 + 2.5 * self.rng.next01();
+        let roll = 1.5 + 3.0 * self.rng.next01();
+        let tail = 4.0 + 8.0 * self.rng.next01();
+        self.rumble_env = BreakpointEnv::from_points(&[
+            Breakpoint::new(rise, peak, 0.4),
+            Breakpoint::new(roll, 0.55 * peak, -0.2),
+            Breakpoint::new(tail, 0.0, 0.6),
+        ]);
+        self.rumble_env.trigger();
+        self.events.push(SceneEvent::Thunder { amp: peak });
+    }
+}
+
+impl Generator for Thunderstorm {
+    #[inline]
+    fn reset(&mut self, sr: f32) {
+        self.set_sample_rate(sr);
+        self.rain.clear();
+        self.rumble_lp1.reset();
+        self.rumble_lp2.reset();
+        self.rumble_env.reset();
+        self.duck.reset();
+        self.events.clear();
+        self.gain_sm.reset(self.out_gain);
+        self.intensity.snap();
+        self.rain.set_density(self.intensity.value());
+    }
+
+    #[inline]
+    fn set_sample_rate(&mut self, sr: f32) -> bool {
+        self.sr = sr.max(1.0);
+        self.rain.set_sample_rate(self.sr);
+        self.rumble_lp1.set_sample_rate(self.sr);
+        self.rumble_lp2.set_sample_rate(self.sr);
+        self.duck.set_sample_rate(self.sr);
+        self.gain_sm.set_time_ms(30.0, self.sr);
+        self.intensity.set_sample_rate(self.sr);
+        false
+    }
+
+    #[inline]
+    fn next(&mut self) -> f32 {
+        let (l, r) = self.next_stereo();
+        0.5 * (l + r)
+    }
+
+    /// Dry frame only; the engine adds the room from [`Generator::next_bus`].
+    #[inline]
+    fn next_stereo(&mut self) -> (f32, f32) { self.next_bus().dry }
+
+    #[inline] fn pop_event(&mut self) -> Option<SceneEvent> { self.events.pop() }
+
+    #[inline]
+    fn next_bus(&mut self) -> BusFrame {
+        if self.intensity.tick() { self.rain.set_density(self.intensity.value()); }
+        if self.rumble_trig.tick(self.sr) { self.start_rumble(); }
+
+        let (rl, rr) = self.rain.next_stereo();
+
+        // two cascaded one-poles: steep enough to keep only the rumble; ×12 restores level
+        let e = self.rumble_env.next(self.sr);
+        let rum = if self.rumble_env.is_running() || e > 0.0 {
+            12.0 * e * self.rumble_lp2.process(self.rumble_lp1.process(self.rng.next_bipolar()))
+        } else {
+            0.0
+        };
+        let (ul, ur) = (rum * self.rumble_gl, rum * self.rumble_gr);
+        let (rl, rr) = self.duck.process(rl, rr, rum);
+
+        let g = self.gain_sm.process(self.out_gain * self.trim * self.intensity.level());
+        let l = rl + 0.35 * ul;
+        let r = rr + 0.35 * ur;
+        BusFrame {
+            dry: ((l * g).clamp(-1.0, 1.0), (r * g).clamp(-1.0, 1.0)),
+            // the reverb gets the rumble mostly, a little rain for air
+            reverb: ((ul + 0.15 * rl) * g, (ur + 0.15 * rr) * g),
+            delay: (0.0, 0.0),
+        }
+    }
+
+    /// One large, dark room.
+    fn bus_settings(&self) -> BusSettings {
+        BusSettings { reverb_room: 0.95, reverb_damp: 0.85, ..BusSettings::default() }
+    }
+
+    #[inline]
+    fn set_output_trim(&mut self, trim: f32) { self.trim = trim.clamp(0.0, 1.0); }
+}
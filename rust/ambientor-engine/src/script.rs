@@ -0,0 +1,218 @@
+//! Rhai scripting for generative logic (feature `scripting`).
+//!
+//! A script runs on its own thread at control rate (every
+//! [`DEFAULT_PERIOD`] by default) and changes the engine only by pushing
+//! [`Command`]s into a [`command_queue`](crate::command::command_queue), so a
+//! slow or stuck script can't stall the audio callback.
+//!
+//! ```rhai
+//! // top level: runs once at load
+//! set_intensity(0.4);
+//!
+//! // optional: runs once after the top level; `this` is a map kept between calls
+//! fn init() { this.step = 0; }
+//!
+//! // runs every period; t = seconds since the script started
+//! fn tick(t) {
+//!     this.step += 1;
+//!     if this.step % 50 == 0 {
+//!         set_param("lp.cutoff", rand_range(400.0, 1600.0));
+//!     }
+//!     set_width(100 + 40 * sin(0.1 * t));
+//! }
+//! ```
+//!
+//! Functions available to scripts: `set_intensity`, `set_width`, `set_tone`,
+//! `set_reverb_return`, `set_delay_return`, `set_tuning_ref`, `fade_in`,
+//! `set_reverb_bypass(bool)` (see [`Command`]), `set_param("node.param", value)` for graph scenes,
+//! `rand()` (0..1), `rand_range(lo, hi)`, and `log(message)`.
+
+use crate::command::{Command, CommandSender, NodeNames};
+use crate::log;
+use crate::nodes::Rng;
+use crate::scenes::graph_scene::Param;
+use rhai::{CallFnOptions, Dynamic, EvalAltResult, ImmutableString, Map, Scope, AST};
+use std::fmt;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How often `tick` runs by default.
+pub const DEFAULT_PERIOD: Duration = Duration::from_millis(100);
+/// Operations one call may take before it is aborted (runaway-loop guard).
+pub const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// Why a script could not be loaded or failed while running.
+#[derive(Debug)]
+pub enum ScriptError {
+    Io(std::io::Error),
+    Parse(String),
+    Runtime(String),
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptError::Io(e) => write!(f, "cannot read script: {e}"),
+            ScriptError::Parse(msg) => write!(f, "invalid script: {msg}"),
+            ScriptError::Runtime(msg) => write!(f, "script error: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ScriptError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ScriptError {
+    fn from(e: std::io::Error) -> Self { ScriptError::Io(e) }
+}
+
+impl From<Box<EvalAltResult>> for ScriptError {
+    fn from(e: Box<EvalAltResult>) -> Self { ScriptError::Runtime(e.to_string()) }
+}
+
+type Sender = Arc<Mutex<CommandSender>>;
+
+fn send(tx: &Sender, cmd: Command) {
+    let ok = tx.lock().unwrap_or_else(|e| e.into_inner()).push(cmd);
+    if !ok { log::warn(format_args!("script: command queue full, dropped {cmd:?}")); }
+}
+
+/// Register `name(x)` for both float and integer `x`, pushing `make(x)`.
+fn register_num(engine: &mut rhai::Engine, tx: &Sender, name: &str, make: fn(f32) -> Command) {
+    let t = Arc::clone(tx);
+    engine.register_fn(name, move |x: f64| send(&t, make(x as f32)));
+    let t = Arc::clone(tx);
+    engine.register_fn(name, move |x: i64| send(&t, make(x as f32)));
+}
+
+/// `"node.param"` → a [`Command::SetParam`] for `value`.
+fn param_command(names: &NodeNames, target: &str, value: f32) -> Result<Command, Box<EvalAltResult>> {
+    let (node, param) = target.split_once('.').ok_or_else(|| format!("'{target}' is not node.param"))?;
+    let param = Param::parse(param).ok_or_else(|| format!("unknown parameter in '{target}'"))?;
+    let names = names.read().unwrap_or_else(|e| e.into_inner());
+    let node = names.iter().position(|n| n == node).ok_or_else(|| format!("unknown node '{node}'"))?;
+    Ok(Command::SetParam { node, param, value })
+}
+
+/// A compiled script plus the state kept between its calls.
+pub struct ScriptHost {
+    engine: rhai::Engine,
+    ast: AST,
+    scope: Scope<'static>,
+    this: Dynamic,
+    has_tick: bool,
+}
+
+impl ScriptHost {
+    /// Compile `source`, then run its top level and `init()` once. Commands go
+    /// to `tx`; `names` resolves `set_param` targets.
+    pub fn new(source: &str, tx: CommandSender, names: NodeNames) -> Result<Self, ScriptError> {
+        let tx: Sender = Arc::new(Mutex::new(tx));
+        let mut engine = rhai::Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+
+        register_num(&mut engine, &tx, "set_intensity", Command::SetIntensity);
+        register_num(&mut engine, &tx, "set_width", Command::SetWidth);
+        register_num(&mut engine, &tx, "set_tone", Command::SetTone);
+        register_num(&mut engine, &tx, "set_reverb_return", Command::SetReverbReturn);
+        register_num(&mut engine, &tx, "set_delay_return", Command::SetDelayReturn);
+        register_num(&mut engine, &tx, "set_tuning_ref", Command::SetTuningRef);
+        register_num(&mut engine, &tx, "fade_in", Command::FadeIn);
+        let t = Arc::clone(&tx);
+        engine.register_fn("set_reverb_bypass", move |on: bool| send(&t, Command::SetReverbBypass(on)));
+        let (t, n) = (Arc::clone(&tx), Arc::clone(&names));
+        engine.register_fn("set_param", move |target: ImmutableString, v: f64| -> Result<(), Box<EvalAltResult>> {
+            send(&t, param_command(&n, &target, v as f32)?);
+            Ok(())
+        });
+        let (t, n) = (Arc::clone(&tx), Arc::clone(&names));
+        engine.register_fn("set_param", move |target: ImmutableString, v: i64| -> Result<(), Box<EvalAltResult>> {
+            send(&t, param_command(&n, &target, v as f32)?);
+            Ok(())
+        });
+
+        let rng = Arc::new(Mutex::new(Rng::new(0x7363_7269)));
+        let r = Arc::clone(&rng);
+        engine.register_fn("rand", move || f64::from(r.lock().unwrap_or_else(|e| e.into_inner()).next01()));
+        let r = Arc::clone(&rng);
+        engine.register_fn("rand_range", move |lo: f64, hi: f64| {
+            lo + (hi - lo) * f64::from(r.lock().unwrap_or_else(|e| e.into_inner()).next01())
+        });
+        engine.register_fn("log", |msg: ImmutableString| log::info(format_args!("[script] {msg}")));
+
+        let ast = engine.compile(source).map_err(|e| ScriptError::Parse(e.to_string()))?;
+        let has_fn = |name: &str, arity: usize| ast.iter_functions().any(|f| f.name == name && f.params.len() == arity);
+        let (has_init, has_tick) = (has_fn("init", 0), has_fn("tick", 1));
+
+        let mut host = Self { engine, ast, scope: Scope::new(), this: Dynamic::from_map(Map::new()), has_tick };
+        host.engine.run_ast_with_scope(&mut host.scope, &host.ast)?;
+        if has_init { host.call("init", ())?; }
+        Ok(host)
+    }
+
+    /// Read and load a script file (see [`ScriptHost::new`]).
+    pub fn load(path: impl AsRef<Path>, tx: CommandSender, names: NodeNames) -> Result<Self, ScriptError> {
+        Self::new(&std::fs::read_to_string(path)?, tx, names)
+    }
+
+    fn call(&mut self, name: &str, args: impl rhai::FuncArgs) -> Result<(), ScriptError> {
+        let opts = CallFnOptions::new().eval_ast(false).rewind_scope(true).bind_this_ptr(&mut self.this);
+        let _ = self.engine.call_fn_with_options::<Dynamic>(opts, &mut self.scope, &self.ast, name, args)?;
+        Ok(())
+    }
+
+    /// `true` if the script defines `tick(t)`.
+    #[inline] pub fn has_tick(&self) -> bool { self.has_tick }
+
+    /// Run `tick(t)` once (no-op without one); `t` in seconds.
+    pub fn tick(&mut self, t: f64) -> Result<(), ScriptError> {
+        if !self.has_tick { return Ok(()); }
+        self.call("tick", (t,))
+    }
+
+    /// Run `tick` every `period` on a new thread until the returned handle is
+    /// dropped. Errors are logged (once until the message changes) and the
+    /// script keeps running.
+    pub fn spawn(mut self, period: Duration) -> std::io::Result<ScriptRunner> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&stop);
+        let handle = thread::Builder::new().name("ambientor-script".into()).spawn(move || {
+            let t0 = Instant::now();
+            let mut last_error = String::new();
+            while !flag.load(Ordering::Relaxed) {
+                thread::sleep(period);
+                match self.tick(t0.elapsed().as_secs_f64()) {
+                    Ok(()) => last_error.clear(),
+                    Err(e) => {
+                        let msg = e.to_string();
+                        if msg != last_error { log::warn(format_args!("{msg}")); }
+                        last_error = msg;
+                    }
+                }
+            }
+        })?;
+        Ok(ScriptRunner { stop, handle: Some(handle) })
+    }
+}
+
+/// A script running on its own thread; dropping it stops the thread.
+pub struct ScriptRunner {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Drop for ScriptRunner {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(h) = self.handle.take() { let _ = h.join(); }
+    }
+}
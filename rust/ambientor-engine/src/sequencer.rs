@@ -0,0 +1,220 @@
+//! Probability-based generative step sequencer and a small voice pool.
+//!
+//! A [`StepSequencer`] walks its steps on a tempo [`Clock`]; each step fires with
+//! its own probability and picks a pitch as a scale degree above a root note.
+//! Steps can be humanized with a [`Jitter`], which delays each note by a small
+//! random amount. The resulting [`NoteEvent`]s are played by a [`VoicePool`] of simple
+//! oscillator + AR-envelope voices (oldest voice is stolen when full), pitched
+//! through the pool's [`Tuning`].
+//!
+//! Everything is fixed-size (const generics), so a scene can own a sequencer
+//! and pool by value with no heap use on the audio thread.
+
+use ambientor_core::dsp::{Scale, Tuning};
+use ambientor_core::envelopes::ArExp;
+
+use crate::nodes::{Osc, Rng, Wave};
+use crate::triggers::{Clock, Jitter, Trigger};
+
+/// One sequencer step.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Step {
+    /// Scale degree above the root (wraps into higher/lower octaves).
+    pub degree: i32,
+    /// Chance the step plays, 0..1.
+    pub prob: f32,
+    /// Velocity 0..1.
+    pub vel: f32,
+}
+
+impl Step {
+    #[inline] pub const fn new(degree: i32, prob: f32, vel: f32) -> Self { Self { degree, prob, vel } }
+    /// A step that never plays.
+    #[inline] pub const fn rest() -> Self { Self { degree: 0, prob: 0.0, vel: 0.0 } }
+}
+
+/// A note produced by the sequencer.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NoteEvent {
+    /// MIDI note number (fractional for microtonal scales).
+    pub note: f32,
+    pub vel: f32,
+}
+
+/// Step sequencer with up to `N` steps, per-step probability, and scale-degree pitches.
+#[derive(Copy, Clone, Debug)]
+pub struct StepSequencer<const N: usize> {
+    steps: [Step; N],
+    len: usize,
+    pos: usize,
+    clock: Clock,
+    rng: Rng,
+    scale: Scale<'static>,
+    root: f32,
+    jitter: Jitter,
+    pending: Option<(u32, NoteEvent)>, // jitter-delayed note, samples left
+}
+
+impl<const N: usize> StepSequencer<N> {
+    /// Empty pattern (all rests) at `bpm` quarter notes, `div` steps per beat.
+    #[inline]
+    pub fn new(bpm: f32, div: u32, seed: u32) -> Self {
+        Self {
+            steps: [Step::rest(); N],
+            len: N,
+            pos: 0,
+            clock: Clock::new(bpm / 60.0 * div.max(1) as f32),
+            rng: Rng::new(seed),
+            scale: Scale::PENTATONIC,
+            root: 57.0, // A3
+            jitter: Jitter::new(0.0, seed ^ 0x6a17),
+            pending: None,
+        }
+    }
+
+    /// Set the step rate from tempo: `bpm` beats per minute, `div` steps per beat.
+    #[inline] pub fn set_tempo(&mut self, bpm: f32, div: u32) { self.clock.set_rate(bpm / 60.0 * div.max(1) as f32); }
+    #[inline] pub fn set_root(&mut self, midi_note: f32) { self.root = midi_note; }
+    #[inline]
+    pub fn set_seed(&mut self, seed: u32) {
+        self.rng.reseed(seed);
+        self.jitter.set_seed(seed ^ 0x6a17);
+    }
+
+    /// Humanize: delay each note by a random 0..2×`ms` (bell-shaped around
+    /// `ms`; see [`Jitter`]). 0 (the default) plays on the grid.
+    #[inline] pub fn set_jitter_ms(&mut self, ms: f32) { self.jitter.set_depth_ms(ms); }
+
+    /// Scale for step degrees (default major pentatonic).
+    #[inline] pub fn set_scale(&mut self, scale: Scale<'static>) { self.scale = scale; }
+
+    /// Active pattern length (1..=N).
+    #[inline] pub fn set_len(&mut self, len: usize) { self.len = len.clamp(1, N.max(1)); self.pos %= self.len; }
+
+    #[inline]
+    pub fn set_step(&mut self, i: usize, step: Step) {
+        if i < N { self.steps[i] = step; }
+    }
+
+    /// Restart from step 0 on the next tick.
+    #[inline] pub fn reset(&mut self) { self.pos = 0; self.clock.reset(); self.pending = None; }
+
+    /// Scale degree → MIDI note relative to the root.
+    #[inline]
+    pub fn degree_to_note(&self, degree: i32) -> f32 {
+        self.root + self.scale.degree_to_semitones(degree)
+    }
+
+    /// Advance one sample; returns a note when a step fires and passes its
+    /// probability (after its jitter delay, if any).
+    #[inline]
+    pub fn tick(&mut self, sr: f32) -> Option<NoteEvent> {
+        let due = match &mut self.pending {
+            Some((left, ev)) if *left <= 1 => {
+                let ev = *ev;
+                self.pending = None;
+                Some(ev)
+            }
+            Some((left, _)) => {
+                *left -= 1;
+                None
+            }
+            None => None,
+        };
+        let Some(ev) = self.step(sr) else { return due; };
+        let delay = self.jitter.next_delay(sr);
+        if delay == 0 && due.is_none() { return Some(ev); }
+        // a note still waiting when the next one comes plays now
+        match self.pending.replace((delay.max(1), ev)) {
+            Some((_, old)) => Some(old),
+            None => due,
+        }
+    }
+
+    /// Next step on the grid, if it plays.
+    #[inline]
+    fn step(&mut self, sr: f32) -> Option<NoteEvent> {
+        if N == 0 || !self.clock.tick(sr) { return None; }
+        let step = self.steps[self.pos];
+        self.pos = (self.pos + 1) % self.len;
+        if step.prob <= 0.0 || self.rng.next01() >= step.prob { return None; }
+        Some(NoteEvent { note: self.degree_to_note(step.degree), vel: step.vel.clamp(0.0, 1.0) })
+    }
+}
+
+/// One pool voice: oscillator through an AR envelope.
+#[derive(Copy, Clone, Debug)]
+struct Voice {
+    osc: Osc,
+    env: ArExp,
+    vel: f32,
+    age: u32,
+}
+
+/// Fixed pool of `V` voices for sequencer notes.
+#[derive(Copy, Clone, Debug)]
+pub struct VoicePool<const V: usize> {
+    voices: [Voice; V],
+    clock: u32, // note counter for voice ages
+    tuning: Tuning,
+}
+
+impl<const V: usize> VoicePool<V> {
+    /// Voices with attack/release in ms (RC time constants) and waveform `wave`.
+    #[inline]
+    pub fn new(atk_ms: f32, rel_ms: f32, wave: Wave, sr: f32) -> Self {
+        let v = Voice { osc: Osc::new(220.0, wave), env: ArExp::new(atk_ms, rel_ms, sr), vel: 0.0, age: 0 };
+        Self { voices: [v; V], clock: 0, tuning: Tuning::equal() }
+    }
+
+    #[inline]
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        for v in &mut self.voices { v.env.set_sr(sr); }
+    }
+
+    /// Tuning for new notes (sounding voices keep their pitch).
+    #[inline] pub fn set_tuning(&mut self, t: Tuning) { self.tuning = t; }
+
+    #[inline]
+    pub fn set_envelope(&mut self, atk_ms: f32, rel_ms: f32) {
+        for v in &mut self.voices { v.env.set_params(atk_ms, rel_ms); }
+    }
+
+    /// Start a note on a free voice, or steal the oldest one. A voice
+    /// triggered earlier in the same sample is busy even while still at 0.
+    #[inline]
+    pub fn note_on(&mut self, ev: NoteEvent) {
+        if V == 0 { return; }
+        self.clock = self.clock.wrapping_add(1);
+        let idx = self.voices.iter().position(|v| v.env.is_idle()).unwrap_or_else(|| {
+            let mut oldest = 0;
+            for (i, v) in self.voices.iter().enumerate() {
+                if self.clock.wrapping_sub(v.age) > self.clock.wrapping_sub(self.voices[oldest].age) { oldest = i; }
+            }
+            oldest
+        });
+        let v = &mut self.voices[idx];
+        v.osc.set_freq(self.tuning.note_to_hz(ev.note));
+        v.osc.set_phase01(0.0);
+        v.vel = ev.vel;
+        v.age = self.clock;
+        v.env.trigger();
+    }
+
+    /// Number of voices currently sounding.
+    #[inline]
+    pub fn active(&self) -> usize { self.voices.iter().filter(|v| !v.env.is_idle()).count() }
+
+    /// Mix all voices for one sample (unnormalized; scale by the caller).
+    #[inline]
+    pub fn next(&mut self, sr: f32) -> f32 {
+        let mut sum = 0.0;
+        for v in &mut self.voices {
+            let e = v.env.next();
+            if e > 0.0 {
+                sum += v.osc.next(sr) * e * v.vel;
+            }
+        }
+        sum
+    }
+}
@@ -0,0 +1,381 @@
+//! Spatialization: multichannel rooms (quad, 5.1, 7.1) and headphone binaural.
+//!
+//! The [`Spatializer`] takes the scene's stereo frame and produces one output per
+//! speaker:
+//! - The left/right sides become two virtual sources that slowly **orbit** the
+//!   room, panned between adjacent speakers with a constant-power law.
+//! - A share of the mono sum is spread to every speaker as a diffuse bed.
+//! - Every full-range channel runs through its own short all-pass pair so the
+//!   outputs are **decorrelated** (no phantom center collapse in large rooms).
+//! - The LFE channel (5.1/7.1) receives a low-passed mono sum.
+//!
+//! [`Binaural`] is an HRTF-less headphone approximation of the same idea: the two
+//! sources orbit the listener's head, each rendered with an interaural time
+//! difference (fractional delay), a level difference, and a head-shadow low-pass
+//! on the far ear.
+//!
+//! Fixed-size state, no heap; pan gains are refreshed every few samples since the
+//! orbit moves at sub-Hz rates.
+
+use ambientor_core::dsp::TAU;
+use ambientor_core::filters::OnePoleLP;
+
+use crate::nodes::PanLaw;
+use crate::reverb::Allpass;
+
+/// Largest supported layout (7.1).
+pub const MAX_SPATIAL_CHANNELS: usize = 8;
+
+const DECOR_LEN: usize = 1024; // ~21 ms @ 48k, plenty for the taps below
+const CONTROL_INTERVAL: u32 = 32;
+
+/// Per-channel decorrelation all-pass lengths (samples @ 48 kHz), mutually prime-ish.
+const DECOR_TAPS: [(f32, f32); MAX_SPATIAL_CHANNELS] = [
+    (113.0, 337.0),
+    (149.0, 373.0),
+    (181.0, 409.0),
+    (211.0, 443.0),
+    (241.0, 487.0),
+    (269.0, 521.0),
+    (307.0, 563.0),
+    (331.0, 599.0),
+];
+
+/// Supported speaker layouts, channels in WAV/SMPTE order.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SpeakerLayout {
+    /// FL, FR, RL, RR
+    Quad,
+    /// FL, FR, C, LFE, SL, SR
+    Surround51,
+    /// FL, FR, C, LFE, BL, BR, SL, SR
+    Surround71,
+}
+
+impl SpeakerLayout {
+    /// Layout for a device channel count (4, 6, or 8).
+    pub fn from_channels(channels: usize) -> Option<Self> {
+        match channels {
+            4 => Some(Self::Quad),
+            6 => Some(Self::Surround51),
+            8 => Some(Self::Surround71),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn channels(self) -> usize {
+        self.azimuths().len()
+    }
+
+    /// Speaker azimuth in degrees (clockwise from front) per channel; `None` = LFE.
+    pub fn azimuths(self) -> &'static [Option<f32>] {
+        match self {
+            Self::Quad => &[Some(-45.0), Some(45.0), Some(-135.0), Some(135.0)],
+            Self::Surround51 => &[Some(-30.0), Some(30.0), Some(0.0), None, Some(-110.0), Some(110.0)],
+            Self::Surround71 => &[
+                Some(-30.0), Some(30.0), Some(0.0), None,
+                Some(-150.0), Some(150.0), Some(-90.0), Some(90.0),
+            ],
+        }
+    }
+}
+
+/// Stereo scene → N decorrelated speaker feeds with slow autopanning.
+#[derive(Clone, Debug)]
+pub struct Spatializer {
+    layout: SpeakerLayout,
+    sr: f32,
+    // full-range speakers sorted by azimuth (radians in [0, τ))
+    ring_ch: [usize; MAX_SPATIAL_CHANNELS],
+    ring_az: [f32; MAX_SPATIAL_CHANNELS],
+    ring_len: usize,
+    lfe_ch: Option<usize>,
+    lfe_lp: OnePoleLP,
+    // decorrelation
+    ap1: [Allpass<DECOR_LEN>; MAX_SPATIAL_CHANNELS],
+    ap2: [Allpass<DECOR_LEN>; MAX_SPATIAL_CHANNELS],
+    // motion
+    orbit: f32, // [0,1) turns
+    orbit_hz: f32,
+    spread: f32,
+    diffuse: f32,
+    // control-rate pan gains per channel for the L and R sources
+    gains_l: [f32; MAX_SPATIAL_CHANNELS],
+    gains_r: [f32; MAX_SPATIAL_CHANNELS],
+    ctl: u32,
+}
+
+impl Spatializer {
+    pub fn new(layout: SpeakerLayout, sr: f32) -> Self {
+        let mut s = Self {
+            layout,
+            sr: sr.max(1.0),
+            ring_ch: [0; MAX_SPATIAL_CHANNELS],
+            ring_az: [0.0; MAX_SPATIAL_CHANNELS],
+            ring_len: 0,
+            lfe_ch: None,
+            lfe_lp: OnePoleLP::new(120.0, sr),
+            ap1: core::array::from_fn(|_| Allpass::new(0.5)),
+            ap2: core::array::from_fn(|_| Allpass::new(0.5)),
+            orbit: 0.0,
+            orbit_hz: 0.01, // one turn every ~100 s
+            spread: 0.5,
+            diffuse: 0.3,
+            gains_l: [0.0; MAX_SPATIAL_CHANNELS],
+            gains_r: [0.0; MAX_SPATIAL_CHANNELS],
+            ctl: 0,
+        };
+        s.set_layout(layout);
+        s
+    }
+
+    /// Switch speaker layout (rebuilds the ring; keeps motion parameters).
+    pub fn set_layout(&mut self, layout: SpeakerLayout) {
+        self.layout = layout;
+        self.ring_len = 0;
+        self.lfe_ch = None;
+        for (ch, az) in layout.azimuths().iter().enumerate() {
+            match az {
+                Some(deg) => {
+                    // insertion sort by wrapped azimuth
+                    let a = deg.to_radians().rem_euclid(TAU);
+                    let mut k = self.ring_len;
+                    while k > 0 && self.ring_az[k - 1] > a {
+                        self.ring_az[k] = self.ring_az[k - 1];
+                        self.ring_ch[k] = self.ring_ch[k - 1];
+                        k -= 1;
+                    }
+                    self.ring_az[k] = a;
+                    self.ring_ch[k] = ch;
+                    self.ring_len += 1;
+                }
+                None => self.lfe_ch = Some(ch),
+            }
+        }
+        self.set_sample_rate(self.sr);
+        self.ctl = 0;
+    }
+
+    #[inline] pub fn layout(&self) -> SpeakerLayout { self.layout }
+    #[inline] pub fn channels(&self) -> usize { self.layout.channels() }
+
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        self.sr = sr.max(1.0);
+        self.lfe_lp.set_sample_rate(self.sr);
+        let scale = self.sr / 48_000.0;
+        for (c, (t1, t2)) in DECOR_TAPS.iter().enumerate() {
+            self.ap1[c].set_len((t1 * scale) as usize);
+            self.ap2[c].set_len((t2 * scale) as usize);
+        }
+    }
+
+    /// Orbit speed of the virtual sources (Hz; full turns per second).
+    #[inline] pub fn set_orbit_hz(&mut self, hz: f32) { self.orbit_hz = hz.clamp(-1.0, 1.0); }
+    /// Angular separation of the L/R sources, 0 (mono point) .. 1 (±90°).
+    #[inline] pub fn set_spread(&mut self, s: f32) { self.spread = s.clamp(0.0, 1.0); }
+    /// Share of the mono sum sent to every speaker as a decorrelated bed, 0..1.
+    #[inline] pub fn set_diffuse(&mut self, d: f32) { self.diffuse = d.clamp(0.0, 1.0); }
+
+    /// Pan a source at azimuth `az` (radians) between its two nearest speakers.
+    fn pan(&self, az: f32, gains: &mut [f32; MAX_SPATIAL_CHANNELS]) {
+        gains.fill(0.0);
+        let n = self.ring_len;
+        if n == 0 { return; }
+        if n == 1 { gains[self.ring_ch[0]] = 1.0; return; }
+
+        let a = az.rem_euclid(TAU);
+        for k in 0..n {
+            let a0 = self.ring_az[k];
+            let a1 = if k + 1 < n { self.ring_az[k + 1] } else { self.ring_az[0] + TAU };
+            let aa = if a < a0 { a + TAU } else { a };
+            if aa >= a0 && aa < a1 {
+                let frac = (aa - a0) / (a1 - a0);
+                let (g0, g1) = PanLaw::gains(2.0 * frac - 1.0);
+                gains[self.ring_ch[k]] = g0;
+                gains[self.ring_ch[(k + 1) % n]] = g1;
+                return;
+            }
+        }
+    }
+
+    fn update_gains(&mut self) {
+        let center = self.orbit * TAU;
+        let half = self.spread * core::f32::consts::FRAC_PI_2;
+        let mut gl = [0.0; MAX_SPATIAL_CHANNELS];
+        let mut gr = [0.0; MAX_SPATIAL_CHANNELS];
+        self.pan(center - half, &mut gl);
+        self.pan(center + half, &mut gr);
+        self.gains_l = gl;
+        self.gains_r = gr;
+    }
+
+    /// Process one stereo frame into `out` (length >= [`Spatializer::channels`]).
+    #[inline]
+    pub fn process(&mut self, l: f32, r: f32, out: &mut [f32]) {
+        if self.ctl == 0 {
+            self.update_gains();
+            self.ctl = CONTROL_INTERVAL;
+        }
+        self.ctl -= 1;
+        self.orbit = (self.orbit + self.orbit_hz / self.sr).rem_euclid(1.0);
+
+        let mono = 0.5 * (l + r);
+        let direct = 1.0 - self.diffuse;
+        let bed = self.diffuse * mono / (self.ring_len.max(1) as f32).sqrt();
+
+        for (c, y) in out.iter_mut().take(self.channels()).enumerate() {
+            if self.lfe_ch == Some(c) {
+                *y = self.lfe_lp.process(mono);
+                continue;
+            }
+            let x = direct * (self.gains_l[c] * l + self.gains_r[c] * r) + bed;
+            *y = self.ap2[c].process(self.ap1[c].process(x));
+        }
+    }
+}
+
+// ------------------------------------ Binaural ------------------------------------
+
+/// Head radius (m) and speed of sound (m/s) for the Woodworth ITD model.
+const HEAD_RADIUS: f32 = 0.0875;
+const SPEED_OF_SOUND: f32 = 343.0;
+const ITD_MAX: usize = 128; // > 0.66 ms max ITD even at 192 kHz
+const EAR_OPEN_HZ: f32 = 18_000.0;
+
+/// Small fractional delay line (linear interpolation) for interaural delays.
+#[derive(Copy, Clone, Debug)]
+struct FracDelay<const N: usize> {
+    buf: [f32; N],
+    w: usize,
+}
+impl<const N: usize> FracDelay<N> {
+    #[inline] fn new() -> Self { Self { buf: [0.0; N], w: 0 } }
+
+    /// Write `x`, then return the input delayed by `d` samples (0 ≤ d < N-1).
+    #[inline]
+    fn process(&mut self, x: f32, d: f32) -> f32 {
+        self.buf[self.w] = x;
+        let mut pos = self.w as f32 - d.clamp(0.0, (N - 2) as f32);
+        if pos < 0.0 { pos += N as f32; }
+        let i0 = pos as usize % N;
+        let i1 = (i0 + 1) % N;
+        let frac = pos - pos.floor();
+        self.w = (self.w + 1) % N;
+        self.buf[i0] + (self.buf[i1] - self.buf[i0]) * frac
+    }
+}
+
+/// One ear's path for one source: ITD delay, ILD gain, head-shadow low-pass.
+/// Delay and gain are ramped linearly between control-rate targets.
+#[derive(Copy, Clone, Debug)]
+struct EarPath {
+    d: FracDelay<ITD_MAX>,
+    lp: OnePoleLP,
+    delay: f32,
+    delay_inc: f32,
+    gain: f32,
+    gain_inc: f32,
+}
+impl EarPath {
+    fn new(sr: f32) -> Self {
+        Self { d: FracDelay::new(), lp: OnePoleLP::new(EAR_OPEN_HZ, sr), delay: 0.0, delay_inc: 0.0, gain: 1.0, gain_inc: 0.0 }
+    }
+
+    fn retarget(&mut self, delay: f32, gain: f32, cut_hz: f32) {
+        let n = CONTROL_INTERVAL as f32;
+        self.delay_inc = (delay - self.delay) / n;
+        self.gain_inc = (gain - self.gain) / n;
+        self.lp.set_cutoff_smoothed(cut_hz, CONTROL_INTERVAL);
+    }
+
+    #[inline]
+    fn process(&mut self, x: f32) -> f32 {
+        self.delay += self.delay_inc;
+        self.gain += self.gain_inc;
+        let y = self.d.process(x, self.delay);
+        self.lp.process(y) * self.gain
+    }
+}
+
+/// Headphone binaural approximation with two slowly orbiting sources.
+///
+/// The scene's left and right sides are placed at `center ∓ width·90°` around the
+/// head, and `center` turns at `orbit_hz`. No HRTF data is needed.
+#[derive(Copy, Clone, Debug)]
+pub struct Binaural {
+    sr: f32,
+    orbit: f32, // [0,1) turns
+    orbit_hz: f32,
+    width: f32,
+    // [source L/R][ear L/R]
+    paths: [[EarPath; 2]; 2],
+    ctl: u32,
+}
+
+impl Binaural {
+    pub fn new(sr: f32) -> Self {
+        let sr = sr.max(1.0);
+        Self {
+            sr,
+            orbit: 0.0,
+            orbit_hz: 0.01,
+            width: 0.5,
+            paths: [[EarPath::new(sr); 2]; 2],
+            ctl: 0,
+        }
+    }
+
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        self.sr = sr.max(1.0);
+        for p in self.paths.iter_mut().flatten() {
+            p.lp.set_sample_rate(self.sr);
+        }
+        self.ctl = 0;
+    }
+
+    /// Orbit speed (turns per second) of the source pair around the head.
+    #[inline] pub fn set_orbit_hz(&mut self, hz: f32) { self.orbit_hz = hz.clamp(-1.0, 1.0); }
+    /// Source separation: 0 (both in the same spot) .. 1 (±90°, ear to ear).
+    #[inline] pub fn set_width(&mut self, w: f32) { self.width = w.clamp(0.0, 1.0); }
+
+    /// Retarget both ears for a source at azimuth `az` (radians, clockwise from front).
+    fn place(&mut self, src: usize, az: f32) {
+        let lat = az.sin();             // +1 = right ear side
+        let rear = (-az.cos()).max(0.0); // 0 in front .. 1 directly behind
+        let side = lat.abs();
+
+        // Woodworth ITD on the lateral angle (folds front/back onto the same delay).
+        let theta = side.asin();
+        let itd = HEAD_RADIUS / SPEED_OF_SOUND * (theta + side) * self.sr;
+
+        let near_gain = 1.0 + 0.1 * side;
+        let far_gain = 1.0 - 0.45 * side;
+        let rear_cut = 1.0 - 0.5 * rear;
+        let near_cut = EAR_OPEN_HZ * rear_cut;
+        let far_cut = (EAR_OPEN_HZ - 15_500.0 * side) * rear_cut;
+
+        let (near, far) = if lat >= 0.0 { (1, 0) } else { (0, 1) };
+        self.paths[src][near].retarget(0.0, near_gain, near_cut);
+        self.paths[src][far].retarget(itd, far_gain, far_cut);
+    }
+
+    /// Process one stereo frame; returns the binaural `(left, right)` pair.
+    #[inline]
+    pub fn process(&mut self, l: f32, r: f32) -> (f32, f32) {
+        if self.ctl == 0 {
+            let center = self.orbit * TAU;
+            let half = self.width * core::f32::consts::FRAC_PI_2;
+            self.place(0, center - half);
+            self.place(1, center + half);
+            self.ctl = CONTROL_INTERVAL;
+        }
+        self.ctl -= 1;
+        self.orbit = (self.orbit + self.orbit_hz / self.sr).rem_euclid(1.0);
+
+        let [pl, pr] = &mut self.paths;
+        let out_l = pl[0].process(l) + pr[0].process(r);
+        let out_r = pl[1].process(l) + pr[1].process(r);
+        (0.5 * out_l, 0.5 * out_r)
+    }
+}
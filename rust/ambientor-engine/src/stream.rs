@@ -0,0 +1,340 @@
+//! Render-ahead streaming (feature `stream`).
+//!
+//! A worker thread renders the engine in blocks into a lock-free
+//! single-producer/single-consumer ring of interleaved stereo frames, and the
+//! audio callback only copies out of the ring. Heavy scenes then only have to
+//! keep up *on average* instead of inside every callback deadline, at the cost
+//! of up to `ahead_frames` of latency on parameter changes.
+//!
+//! Contents:
+//! - `ring`, `RingProducer`, `RingConsumer`: SPSC f32 ring (atomics only, no locks)
+//! - `RenderAhead`: owns the worker thread and the consumer side
+//!
+//! The engine sits (boxed) behind a `Mutex` that only the worker (once per
+//! block) and control code lock — never the audio callback. Control-rate work
+//! that must follow the rendered audio (automation, command queues) goes in a
+//! per-block hook run by the worker. All buffers are allocated up front; the
+//! worker and the callback never allocate.
+
+use crate::graph::{Engine, Generator};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle, Thread};
+use std::time::Duration;
+
+/// Default worker block size in frames.
+pub const DEFAULT_BLOCK_FRAMES: usize = 256;
+/// Default render-ahead depth in frames (~85 ms @ 48k).
+pub const DEFAULT_AHEAD_FRAMES: usize = 4096;
+
+struct Ring {
+    buf: Box<[AtomicU32]>, // f32 bits
+    head: AtomicUsize,     // total samples written
+    tail: AtomicUsize,     // total samples read
+}
+
+/// Create an SPSC ring holding up to `capacity` samples.
+pub fn ring(capacity: usize) -> (RingProducer, RingConsumer) {
+    let ring = Arc::new(Ring {
+        buf: (0..capacity.max(1)).map(|_| AtomicU32::new(0)).collect(),
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0),
+    });
+    (RingProducer { ring: Arc::clone(&ring) }, RingConsumer { ring })
+}
+
+/// Writing side of a [`ring`]; move it to the producer thread.
+pub struct RingProducer {
+    ring: Arc<Ring>,
+}
+
+impl RingProducer {
+    #[inline] pub fn capacity(&self) -> usize { self.ring.buf.len() }
+
+    /// Samples that can be pushed right now.
+    #[inline]
+    pub fn free(&self) -> usize {
+        let tail = self.ring.tail.load(Ordering::Acquire);
+        let head = self.ring.head.load(Ordering::Relaxed);
+        self.capacity() - head.wrapping_sub(tail)
+    }
+
+    /// Push as many samples of `xs` as fit; returns how many were written.
+    pub fn push_slice(&mut self, xs: &[f32]) -> usize {
+        let cap = self.capacity();
+        let head = self.ring.head.load(Ordering::Relaxed);
+        let n = xs.len().min(self.free());
+        for (i, &x) in xs[..n].iter().enumerate() {
+            self.ring.buf[head.wrapping_add(i) % cap].store(x.to_bits(), Ordering::Relaxed);
+        }
+        self.ring.head.store(head.wrapping_add(n), Ordering::Release);
+        n
+    }
+}
+
+/// Reading side of a [`ring`]; safe to use from the audio callback.
+pub struct RingConsumer {
+    ring: Arc<Ring>,
+}
+
+impl RingConsumer {
+    #[inline] pub fn capacity(&self) -> usize { self.ring.buf.len() }
+
+    /// Samples ready to pop.
+    #[inline]
+    pub fn available(&self) -> usize {
+        let head = self.ring.head.load(Ordering::Acquire);
+        let tail = self.ring.tail.load(Ordering::Relaxed);
+        head.wrapping_sub(tail)
+    }
+
+    /// Pop up to `out.len()` samples; returns how many were read.
+    pub fn pop_slice(&mut self, out: &mut [f32]) -> usize {
+        let cap = self.capacity();
+        let tail = self.ring.tail.load(Ordering::Relaxed);
+        let n = out.len().min(self.available());
+        for (i, y) in out[..n].iter_mut().enumerate() {
+            *y = f32::from_bits(self.ring.buf[tail.wrapping_add(i) % cap].load(Ordering::Relaxed));
+        }
+        self.ring.tail.store(tail.wrapping_add(n), Ordering::Release);
+        n
+    }
+}
+
+/// State shared between the worker and [`RenderAhead`].
+struct Shared {
+    stop: AtomicBool,
+    silent: AtomicBool,
+}
+
+/// An [`Engine`] rendered ahead of time by a worker thread.
+///
+/// Read frames with [`RenderAhead::read_interleaved`] or
+/// [`RenderAhead::next_frame`] from the audio callback; change parameters
+/// through [`RenderAhead::engine`] from anywhere else. If the worker falls
+/// behind, missing frames are filled with silence and counted as underruns.
+pub struct RenderAhead<G: Generator + Send + 'static> {
+    engine: Arc<Mutex<Box<Engine<G>>>>,
+    ring: RingConsumer,
+    shared: Arc<Shared>,
+    worker: Option<JoinHandle<()>>,
+    thread: Thread,
+    underrun_frames: u64,
+}
+
+fn lock<G: Generator>(m: &Mutex<Box<Engine<G>>>) -> MutexGuard<'_, Box<Engine<G>>> {
+    // a panic elsewhere must not silence the stream; the engine state is still usable
+    m.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl<G: Generator + Send + 'static> RenderAhead<G> {
+    /// Start a worker that keeps up to `ahead_frames` stereo frames rendered,
+    /// in blocks of `block_frames`. The engine renders at its current sample
+    /// rate (see [`Engine::set_sample_rate`]).
+    pub fn new(engine: Box<Engine<G>>, ahead_frames: usize, block_frames: usize) -> std::io::Result<Self> {
+        Self::with_hook(engine, ahead_frames, block_frames, |_, _| {})
+    }
+
+    /// Like [`RenderAhead::new`], calling `hook(engine, block_s)` on the worker
+    /// before each block, so control-rate updates land in step with the audio
+    /// they shape rather than `ahead_frames` early.
+    pub fn with_hook(
+        engine: Box<Engine<G>>,
+        ahead_frames: usize,
+        block_frames: usize,
+        mut hook: impl FnMut(&mut Engine<G>, f64) + Send + 'static,
+    ) -> std::io::Result<Self> {
+        let block_frames = block_frames.max(1);
+        let ahead_frames = ahead_frames.max(2 * block_frames);
+        let (mut prod, cons) = ring(2 * ahead_frames);
+        let engine = Arc::new(Mutex::new(engine));
+        let shared = Arc::new(Shared { stop: AtomicBool::new(false), silent: AtomicBool::new(false) });
+
+        let (w_engine, w_shared) = (Arc::clone(&engine), Arc::clone(&shared));
+        let worker = thread::Builder::new()
+            .name("ambientor-render".into())
+            .spawn(move || {
+                let mut block = vec![0.0f32; 2 * block_frames];
+                while !w_shared.stop.load(Ordering::Acquire) {
+                    if prod.free() < block.len() {
+                        thread::park_timeout(Duration::from_millis(2));
+                        continue;
+                    }
+                    {
+                        let mut e = lock(&w_engine);
+                        let sr = e.sample_rate();
+                        hook(&mut e, block_frames as f64 / f64::from(sr));
+                        e.set_block_len(block_frames as u32);
+                        for f in block.chunks_exact_mut(2) {
+                            let (l, r) = e.next_stereo(sr);
+                            f[0] = l;
+                            f[1] = r;
+                        }
+                        w_shared.silent.store(e.is_silent(), Ordering::Relaxed);
+                    }
+                    prod.push_slice(&block);
+                }
+            })?;
+        let thread = worker.thread().clone();
+        Ok(Self { engine, ring: cons, shared, worker: Some(worker), thread, underrun_frames: 0 })
+    }
+
+    /// Lock the engine for parameter changes. Never call this from the audio callback.
+    #[inline] pub fn engine(&self) -> MutexGuard<'_, Box<Engine<G>>> { lock(&self.engine) }
+
+    /// Fill interleaved stereo `out` (`[l0, r0, l1, r1, ...]`) from the ring,
+    /// padding with silence if the worker fell behind. Only whole frames are
+    /// taken, so a trailing odd sample is silence too. Returns frames taken
+    /// from the ring.
+    pub fn read_interleaved(&mut self, out: &mut [f32]) -> usize {
+        let whole = out.len() & !1;
+        let n = self.ring.pop_slice(&mut out[..whole]);
+        out[n..].fill(0.0);
+        let frames = n / 2;
+        self.underrun_frames += (out.len() / 2 - frames) as u64;
+        if self.ring.available() < self.ring.capacity() / 2 {
+            self.thread.unpark();
+        }
+        frames
+    }
+
+    /// Pop one stereo frame (silence on underrun).
+    #[inline]
+    pub fn next_frame(&mut self) -> (f32, f32) {
+        let mut f = [0.0f32; 2];
+        self.read_interleaved(&mut f);
+        (f[0], f[1])
+    }
+
+    /// Frames rendered and waiting in the ring.
+    #[inline] pub fn buffered_frames(&self) -> usize { self.ring.available() / 2 }
+
+    /// Total frames replaced by silence because the worker was late.
+    #[inline] pub fn underrun_frames(&self) -> u64 { self.underrun_frames }
+
+    /// Engine silence state as of the last rendered block (see [`Engine::is_silent`]).
+    #[inline] pub fn is_silent(&self) -> bool { self.shared.silent.load(Ordering::Relaxed) }
+}
+
+impl<G: Generator + Send + 'static> Drop for RenderAhead<G> {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::Release);
+        self.thread.unpark();
+        if let Some(w) = self.worker.take() {
+            let _ = w.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring_wraps_around() {
+        let (mut tx, mut rx) = ring(5);
+        let mut out = [0.0; 3];
+        for k in 0..10 {
+            let xs = [k as f32, k as f32 + 0.25, k as f32 + 0.5];
+            assert_eq!(tx.push_slice(&xs), 3);
+            assert_eq!(rx.pop_slice(&mut out), 3);
+            assert_eq!(out, xs, "round {k}");
+        }
+        // one write straddling the end of the buffer
+        assert_eq!(tx.push_slice(&[1.0, 2.0, 3.0, 4.0, 5.0]), 5);
+        let mut out = [0.0; 5];
+        assert_eq!(rx.pop_slice(&mut out), 5);
+        assert_eq!(out, [1.0, 2.0, 3.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    fn ring_full_and_empty() {
+        let (mut tx, mut rx) = ring(4);
+        let mut out = [9.0; 6];
+        assert_eq!((rx.available(), tx.free()), (0, 4));
+        assert_eq!(rx.pop_slice(&mut out), 0);
+        assert_eq!(out, [9.0; 6], "empty pop wrote");
+
+        assert_eq!(tx.push_slice(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]), 4);
+        assert_eq!((rx.available(), tx.free()), (4, 0));
+        assert_eq!(tx.push_slice(&[7.0]), 0);
+
+        assert_eq!(rx.pop_slice(&mut out[..1]), 1);
+        assert_eq!(tx.push_slice(&[7.0, 8.0]), 1);
+        assert_eq!(rx.pop_slice(&mut out), 4);
+        assert_eq!(out[..4], [2.0, 3.0, 4.0, 7.0]);
+        assert_eq!((rx.available(), tx.free()), (0, 4));
+
+        let (tx, rx) = ring(0);
+        assert_eq!((tx.capacity(), rx.capacity()), (1, 1));
+    }
+
+    /// A sine on the left, silence on the right.
+    struct LeftTone {
+        phase: f32,
+    }
+
+    impl Generator for LeftTone {
+        fn reset(&mut self, _sr: f32) { self.phase = 0.0; }
+        fn next(&mut self) -> f32 { self.next_stereo().0 }
+        fn next_stereo(&mut self) -> (f32, f32) {
+            self.phase = (self.phase + 0.01) % 1.0;
+            (0.5 * (core::f32::consts::TAU * self.phase).sin(), 0.0)
+        }
+    }
+
+    fn left_tone(ahead_frames: usize) -> RenderAhead<LeftTone> {
+        let ra = RenderAhead::new(Box::new(Engine::new(LeftTone { phase: 0.0 })), ahead_frames, 64).unwrap();
+        while ra.buffered_frames() < ahead_frames { thread::sleep(Duration::from_millis(1)); }
+        ra
+    }
+
+    #[test]
+    fn odd_length_reads_keep_channels_in_place() {
+        let mut ra = left_tone(1024);
+        let (mut left, mut right) = (0.0, 0.0);
+        for len in [3, 5, 1, 7, 4, 9].into_iter().cycle().take(60) {
+            let mut out = vec![1.0; len];
+            assert_eq!(ra.read_interleaved(&mut out), len / 2);
+            if len % 2 == 1 { assert_eq!(out[len - 1], 0.0, "odd sample of a {len}-sample read"); }
+            for f in out.chunks_exact(2) {
+                left += f[0] * f[0];
+                right += f[1] * f[1];
+            }
+        }
+        // still fading in, but only ever on the left
+        assert!(left > 0.0 && right <= 1e-6 * left, "left {left}, right {right}");
+        assert_eq!(ra.underrun_frames(), 0);
+    }
+
+    #[test]
+    fn underruns_pad_with_silence() {
+        let mut ra = left_tone(256);
+        // hold the engine so the worker can't refill
+        let engine = Arc::clone(&ra.engine);
+        let held = lock(&engine);
+        let buffered = ra.buffered_frames();
+        let mut out = vec![1.0; 2 * (buffered + 10)];
+        assert_eq!(ra.read_interleaved(&mut out), buffered);
+        assert!(out[2 * buffered..].iter().all(|&x| x == 0.0));
+        assert_eq!(ra.underrun_frames(), 10);
+        assert_eq!(ra.next_frame(), (0.0, 0.0));
+        assert_eq!(ra.underrun_frames(), 11);
+        drop(held);
+    }
+
+    #[test]
+    fn hook_runs_before_every_block() {
+        let blocks = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::clone(&blocks);
+        let mut engine = Box::new(Engine::new(LeftTone { phase: 0.0 }));
+        engine.set_sample_rate(48_000.0);
+        let ra = RenderAhead::with_hook(engine, 512, 64, move |_, block_s| {
+            assert_eq!(block_s, 64.0 / 48_000.0);
+            counted.fetch_add(1, Ordering::Relaxed);
+        }).unwrap();
+        while ra.buffered_frames() < 512 { thread::sleep(Duration::from_millis(1)); }
+        // the worker has parked with the ring full
+        assert_eq!(blocks.load(Ordering::Relaxed), 512 / 64);
+    }
+}
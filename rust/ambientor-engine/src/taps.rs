@@ -0,0 +1,160 @@
+//! Analysis taps: internal signals sampled at control rate for companion UIs.
+//!
+//! A host subscribes to a tap by name ([`Engine::subscribe_tap`](crate::Engine::subscribe_tap))
+//! and gets a slot. Once per control block ([`CONTROL_BLOCK`] samples) the
+//! engine appends the tapped signal's current value to the slot's history,
+//! which the host drains with [`Engine::read_tap`](crate::Engine::read_tap),
+//! e.g. once per UI frame for an oscilloscope or modulation display.
+//!
+//! Engine taps, there with every scene ([`ENGINE_TAPS`], all mono):
+//! - `scene`      : the scene's dry mix, before the master bus
+//! - `pre_reverb` : the shared reverb's input
+//! - `post_filter`: the master mix right after the EQ and tone tilt
+//! - `output`     : the engine output
+//!
+//! Scenes add their own through [`Generator::tap_index`](crate::Generator::tap_index):
+//! the slow drone's modulators, a graph scene's node outputs. Unsubscribed
+//! taps cost nothing; subscribed ones a few stores per control block.
+
+use crate::nodes::{ControlClock, CONTROL_BLOCK};
+
+/// Taps that can be subscribed at once.
+pub const MAX_TAPS: usize = 8;
+/// Values a tap keeps until read (about 0.7 s at 48 kHz); older ones are dropped.
+pub const TAP_HISTORY: usize = 1024;
+/// Taps the engine itself provides, whatever the scene.
+pub const ENGINE_TAPS: [&str; 4] = ["scene", "pre_reverb", "post_filter", "output"];
+
+/// Where a subscribed tap reads from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Source {
+    Scene,
+    PreReverb,
+    PostFilter,
+    Output,
+    /// A generator tap; `None` while the current scene doesn't have it.
+    Generator(Option<usize>),
+}
+
+impl Source {
+    fn engine(name: &str) -> Option<Self> {
+        Some(match name {
+            "scene" => Self::Scene,
+            "pre_reverb" => Self::PreReverb,
+            "post_filter" => Self::PostFilter,
+            "output" => Self::Output,
+            _ => return None,
+        })
+    }
+}
+
+/// The engine's own tap points on one frame.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TapFrame {
+    pub scene: f32,
+    pub pre_reverb: f32,
+    pub post_filter: f32,
+    pub output: f32,
+}
+
+struct Slot {
+    name: String,
+    source: Source,
+    ring: Box<[f32]>, // TAP_HISTORY values, oldest at `head`
+    head: usize,
+    len: usize,
+}
+
+/// Subscribed taps and their histories. Subscribing allocates; recording
+/// and reading don't.
+pub struct Taps {
+    slots: [Option<Slot>; MAX_TAPS],
+    clock: ControlClock,
+    active: usize,
+}
+
+impl Default for Taps {
+    fn default() -> Self { Self::new() }
+}
+
+impl core::fmt::Debug for Taps {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.slots.iter().flatten().map(|s| &s.name)).finish()
+    }
+}
+
+impl Taps {
+    pub fn new() -> Self {
+        Self { slots: Default::default(), clock: ControlClock::new(CONTROL_BLOCK), active: 0 }
+    }
+
+    /// `true` while any tap is subscribed.
+    #[inline] pub fn is_active(&self) -> bool { self.active > 0 }
+
+    /// Control blocks per second at `sr`: the rate tap values arrive at.
+    #[inline] pub fn rate(&self, sr: f32) -> f32 { self.clock.rate(sr) }
+
+    /// Subscribe to `name`: an engine tap, else whatever `scene_tap` resolves
+    /// it to. Returns the slot, or `None` for an unknown name or when all
+    /// [`MAX_TAPS`] slots are taken.
+    pub fn subscribe(&mut self, name: &str, scene_tap: impl Fn(&str) -> Option<usize>) -> Option<usize> {
+        let source = match Source::engine(name) {
+            Some(s) => s,
+            None => Source::Generator(Some(scene_tap(name)?)),
+        };
+        let i = self.slots.iter().position(Option::is_none)?;
+        self.slots[i] = Some(Slot {
+            name: name.to_owned(),
+            source,
+            ring: vec![0.0; TAP_HISTORY].into_boxed_slice(),
+            head: 0,
+            len: 0,
+        });
+        self.active += 1;
+        Some(i)
+    }
+
+    pub fn unsubscribe(&mut self, slot: usize) {
+        if let Some(s) = self.slots.get_mut(slot) {
+            if s.take().is_some() { self.active -= 1; }
+        }
+    }
+
+    /// Re-resolve scene taps after a scene swap; ones the new scene lacks
+    /// read 0 until a scene that has them comes back.
+    pub fn rebind(&mut self, scene_tap: impl Fn(&str) -> Option<usize>) {
+        for s in self.slots.iter_mut().flatten() {
+            if let Source::Generator(_) = s.source { s.source = Source::Generator(scene_tap(&s.name)); }
+        }
+    }
+
+    /// Call once per frame; `true` on the frames whose values should be
+    /// [`Taps::record`]ed.
+    #[inline] pub fn tick(&mut self) -> bool { self.clock.tick() }
+
+    /// Append the current value of every subscribed tap.
+    pub fn record(&mut self, frame: &TapFrame, scene_tap: impl Fn(usize) -> f32) {
+        for s in self.slots.iter_mut().flatten() {
+            let v = match s.source {
+                Source::Scene => frame.scene,
+                Source::PreReverb => frame.pre_reverb,
+                Source::PostFilter => frame.post_filter,
+                Source::Output => frame.output,
+                Source::Generator(i) => i.map_or(0.0, &scene_tap),
+            };
+            s.ring[(s.head + s.len) % TAP_HISTORY] = v;
+            if s.len < TAP_HISTORY { s.len += 1; } else { s.head = (s.head + 1) % TAP_HISTORY; }
+        }
+    }
+
+    /// Move up to `out.len()` unread values of `slot` into `out`, oldest
+    /// first; returns how many. 0 for an empty or unknown slot.
+    pub fn read(&mut self, slot: usize, out: &mut [f32]) -> usize {
+        let Some(Some(s)) = self.slots.get_mut(slot) else { return 0; };
+        let n = out.len().min(s.len);
+        for (k, y) in out[..n].iter_mut().enumerate() { *y = s.ring[(s.head + k) % TAP_HISTORY]; }
+        s.head = (s.head + n) % TAP_HISTORY;
+        s.len -= n;
+        n
+    }
+}
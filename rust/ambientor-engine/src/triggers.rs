@@ -0,0 +1,229 @@
+//! Gate/trigger sources for generative events.
+//!
+//! Scenes with discrete events (droplets, bells, clicks) ask a [`Trigger`]
+//! once per sample whether something should happen now, instead of keeping
+//! ad-hoc timers of their own.
+//!
+//! Contents:
+//! - `Trigger`      : the source trait (`tick(sr) -> bool`)
+//! - `Clock`        : steady pulses at a rate in Hz
+//! - `PoissonTrigger`: random events with a mean density in events/minute
+//! - `Euclid`       : Euclidean rhythm (k pulses spread over n steps) on a clock
+//! - `ClockDivider` : fires on every n-th event of another trigger
+//! - `Jitter`       : bounded random timing offsets ("humanize")
+//! - `Jittered`     : delays each event of another trigger by a `Jitter` offset
+//!
+//! All sources are allocation-free and cheap to step per sample.
+
+use crate::nodes::Rng;
+
+/// A per-sample event source.
+pub trait Trigger {
+    /// Advance one sample; `true` if an event fires on this sample.
+    fn tick(&mut self, sr: f32) -> bool;
+
+    /// Restart the pattern/clock from its beginning.
+    fn reset(&mut self) {}
+}
+
+/// Steady clock: fires `rate_hz` times per second (first tick on the first sample).
+#[derive(Copy, Clone, Debug)]
+pub struct Clock {
+    rate: f32,
+    phase: f32, // [0,1)
+    armed: bool,
+}
+
+impl Clock {
+    #[inline] pub fn new(rate_hz: f32) -> Self { Self { rate: rate_hz.max(0.0), phase: 0.0, armed: true } }
+    #[inline] pub fn bpm(bpm: f32) -> Self { Self::new(bpm / 60.0) }
+    #[inline] pub fn set_rate(&mut self, hz: f32) { self.rate = hz.max(0.0); }
+}
+
+impl Trigger for Clock {
+    #[inline]
+    fn tick(&mut self, sr: f32) -> bool {
+        if self.armed {
+            self.armed = false;
+            return true;
+        }
+        self.phase += self.rate / sr;
+        if self.phase >= 1.0 {
+            self.phase -= self.phase.floor();
+            return true;
+        }
+        false
+    }
+
+    #[inline] fn reset(&mut self) { self.phase = 0.0; self.armed = true; }
+}
+
+/// Random events with mean `density` per minute (exponential gaps, no memory).
+#[derive(Copy, Clone, Debug)]
+pub struct PoissonTrigger {
+    per_min: f32,
+    rng: Rng,
+}
+
+impl PoissonTrigger {
+    #[inline] pub fn new(events_per_min: f32, seed: u32) -> Self { Self { per_min: events_per_min.max(0.0), rng: Rng::new(seed) } }
+    #[inline] pub fn set_density(&mut self, events_per_min: f32) { self.per_min = events_per_min.max(0.0); }
+    #[inline] pub fn set_seed(&mut self, seed: u32) { self.rng.reseed(seed); }
+}
+
+impl Trigger for PoissonTrigger {
+    #[inline]
+    fn tick(&mut self, sr: f32) -> bool {
+        // per-sample probability; fine while density << sr
+        let p = self.per_min / (60.0 * sr);
+        self.rng.next01() < p
+    }
+}
+
+/// Maximum steps in a Euclidean pattern.
+pub const EUCLID_MAX_STEPS: u32 = 64;
+
+/// Euclidean rhythm: `pulses` hits spread as evenly as possible over `steps`,
+/// rotated by `rotation`, stepping at `rate_hz` steps per second.
+#[derive(Copy, Clone, Debug)]
+pub struct Euclid {
+    clock: Clock,
+    steps: u32,
+    pulses: u32,
+    rotation: u32,
+    pos: u32, // next step index
+}
+
+impl Euclid {
+    #[inline]
+    pub fn new(steps: u32, pulses: u32, rate_hz: f32) -> Self {
+        let steps = steps.clamp(1, EUCLID_MAX_STEPS);
+        Self { clock: Clock::new(rate_hz), steps, pulses: pulses.min(steps), rotation: 0, pos: 0 }
+    }
+
+    #[inline]
+    pub fn set_pattern(&mut self, steps: u32, pulses: u32) {
+        self.steps = steps.clamp(1, EUCLID_MAX_STEPS);
+        self.pulses = pulses.min(self.steps);
+        self.pos %= self.steps;
+    }
+    #[inline] pub fn set_rotation(&mut self, r: u32) { self.rotation = r; }
+    #[inline] pub fn set_rate(&mut self, hz: f32) { self.clock.set_rate(hz); }
+
+    /// Whether step `i` (0-based, before rotation) is a hit.
+    #[inline]
+    pub fn is_hit(&self, i: u32) -> bool {
+        // bucket form of Bjorklund: hit where the running sum of `pulses` wraps `steps`
+        let j = (i % self.steps + self.rotation % self.steps) % self.steps; // no overflow for any rotation
+        (j * self.pulses) % self.steps < self.pulses
+    }
+}
+
+impl Trigger for Euclid {
+    #[inline]
+    fn tick(&mut self, sr: f32) -> bool {
+        if !self.clock.tick(sr) { return false; }
+        let hit = self.is_hit(self.pos);
+        self.pos = (self.pos + 1) % self.steps;
+        hit
+    }
+
+    #[inline] fn reset(&mut self) { self.clock.reset(); self.pos = 0; }
+}
+
+/// Passes through every `div`-th event of `src` (the first one included).
+#[derive(Copy, Clone, Debug)]
+pub struct ClockDivider<T: Trigger> {
+    src: T,
+    div: u32,
+    count: u32,
+}
+
+impl<T: Trigger> ClockDivider<T> {
+    #[inline] pub fn new(src: T, div: u32) -> Self { Self { src, div: div.max(1), count: 0 } }
+    #[inline] pub fn set_div(&mut self, div: u32) { self.div = div.max(1); self.count %= self.div; }
+    #[inline] pub fn inner_mut(&mut self) -> &mut T { &mut self.src }
+}
+
+impl<T: Trigger> Trigger for ClockDivider<T> {
+    #[inline]
+    fn tick(&mut self, sr: f32) -> bool {
+        if !self.src.tick(sr) { return false; }
+        let fire = self.count == 0;
+        self.count = (self.count + 1) % self.div;
+        fire
+    }
+
+    #[inline] fn reset(&mut self) { self.src.reset(); self.count = 0; }
+}
+
+/// Bounded, bell-shaped random timing offsets: the mean of three uniform
+/// draws, so offsets cluster near 0 (like a player's timing) but never pass
+/// ±`depth_ms`. Same seed, same sequence.
+#[derive(Copy, Clone, Debug)]
+pub struct Jitter {
+    depth_ms: f32,
+    rng: Rng,
+}
+
+impl Jitter {
+    #[inline] pub fn new(depth_ms: f32, seed: u32) -> Self { Self { depth_ms: depth_ms.max(0.0), rng: Rng::new(seed) } }
+    #[inline] pub fn set_depth_ms(&mut self, ms: f32) { self.depth_ms = ms.max(0.0); }
+    #[inline] pub fn depth_ms(&self) -> f32 { self.depth_ms }
+    #[inline] pub fn set_seed(&mut self, seed: u32) { self.rng.reseed(seed); }
+
+    /// Next offset in ms, within ±depth.
+    #[inline]
+    pub fn next_ms(&mut self) -> f32 {
+        let r = self.rng.next_bipolar() + self.rng.next_bipolar() + self.rng.next_bipolar();
+        self.depth_ms * r * (1.0 / 3.0)
+    }
+
+    /// Next offset as a delay in samples, 0..2×depth: events can't fire early,
+    /// so the offset is centred on a latency of `depth_ms`.
+    #[inline]
+    pub fn next_delay(&mut self, sr: f32) -> u32 {
+        ((self.depth_ms + self.next_ms()) * 0.001 * sr).max(0.0) as u32
+    }
+}
+
+/// Events waiting in a [`Jittered`] trigger at once; more fire without delay.
+const JITTER_PENDING: usize = 4;
+
+/// Fires each event of `src` a [`Jitter`] delay later, so clocked patterns
+/// don't land machine-perfectly. With depth 0 it passes events straight through.
+#[derive(Copy, Clone, Debug)]
+pub struct Jittered<T: Trigger> {
+    src: T,
+    jitter: Jitter,
+    pending: [u32; JITTER_PENDING], // samples left; 0 = free slot
+}
+
+impl<T: Trigger> Jittered<T> {
+    #[inline] pub fn new(src: T, depth_ms: f32, seed: u32) -> Self { Self { src, jitter: Jitter::new(depth_ms, seed), pending: [0; JITTER_PENDING] } }
+    #[inline] pub fn jitter_mut(&mut self) -> &mut Jitter { &mut self.jitter }
+    #[inline] pub fn inner_mut(&mut self) -> &mut T { &mut self.src }
+}
+
+impl<T: Trigger> Trigger for Jittered<T> {
+    #[inline]
+    fn tick(&mut self, sr: f32) -> bool {
+        let mut fire = false;
+        for p in &mut self.pending {
+            if *p > 0 {
+                *p -= 1;
+                fire |= *p == 0;
+            }
+        }
+        if self.src.tick(sr) {
+            let d = self.jitter.next_delay(sr);
+            match self.pending.iter_mut().find(|p| **p == 0) {
+                Some(slot) if d > 0 => *slot = d,
+                _ => fire = true,
+            }
+        }
+        fire
+    }
+
+    #[inline] fn reset(&mut self) { self.src.reset(); self.pending = [0; JITTER_PENDING]; }
+}
@@ -0,0 +1,141 @@
+//! Downsampled min/max history of the output for scrolling waveform displays.
+//!
+//! [`WaveformHistory`] folds every `sr / columns_per_s` samples into one
+//! column (its lowest and highest sample) and keeps the most recent columns
+//! in a ring, so a GUI can draw the last minute of audio without touching the
+//! samples itself. Storage is sized in [`WaveformHistory::new`]; pushing and
+//! reading never allocate. [`Engine::enable_waveform`](crate::graph::Engine::enable_waveform)
+//! keeps one of the engine's output.
+
+/// Default columns per second of a [`WaveformHistory`].
+pub const WAVEFORM_COLUMNS_PER_S: f32 = 10.0;
+/// Default length of a [`WaveformHistory`] (seconds).
+pub const WAVEFORM_HISTORY_S: f32 = 60.0;
+
+/// Ring of per-column `[min, max]` peaks.
+#[derive(Clone, Debug)]
+pub struct WaveformHistory {
+    cols: Vec<[f32; 2]>, // oldest at `pos` once full
+    pos: usize,
+    filled: usize,
+    columns_per_s: f32,
+    per_col: u32, // samples per column
+    left: u32,    // samples still to fold into the current column
+    lo: f32,
+    hi: f32,
+}
+
+impl WaveformHistory {
+    /// `columns_per_s` columns per second (0.1..1000) over the last
+    /// `seconds` (at least one column), at sample rate `sr`.
+    pub fn new(sr: f32, columns_per_s: f32, seconds: f32) -> Self {
+        let columns_per_s = columns_per_s.clamp(0.1, 1000.0);
+        let n = ((columns_per_s * seconds.max(0.0)).round() as usize).max(1);
+        let mut w = Self {
+            cols: vec![[0.0; 2]; n],
+            pos: 0,
+            filled: 0,
+            columns_per_s,
+            per_col: 1,
+            left: u32::MAX, // set_sample_rate trims it to one column
+            lo: f32::INFINITY,
+            hi: f32::NEG_INFINITY,
+        };
+        w.set_sample_rate(sr);
+        w
+    }
+
+    /// Keep the history; later columns span the same time at the new rate.
+    pub fn set_sample_rate(&mut self, sr: f32) {
+        self.per_col = ((sr.max(1.0) / self.columns_per_s).round() as u32).max(1);
+        self.left = self.left.min(self.per_col);
+    }
+
+    /// Columns the history holds when full.
+    #[inline] pub fn capacity(&self) -> usize { self.cols.len() }
+
+    /// Columns recorded so far (up to [`WaveformHistory::capacity`]).
+    #[inline] pub fn len(&self) -> usize { self.filled }
+    #[inline] pub fn is_empty(&self) -> bool { self.filled == 0 }
+
+    #[inline] pub fn columns_per_s(&self) -> f32 { self.columns_per_s }
+
+    #[inline]
+    pub fn push(&mut self, x: f32) {
+        self.lo = self.lo.min(x);
+        self.hi = self.hi.max(x);
+        self.left -= 1;
+        if self.left == 0 {
+            self.cols[self.pos] = [self.lo, self.hi];
+            self.pos = (self.pos + 1) % self.cols.len();
+            self.filled = (self.filled + 1).min(self.cols.len());
+            self.left = self.per_col;
+            self.lo = f32::INFINITY;
+            self.hi = f32::NEG_INFINITY;
+        }
+    }
+
+    /// Forget every column.
+    pub fn clear(&mut self) {
+        self.pos = 0;
+        self.filled = 0;
+        self.left = self.per_col;
+        self.lo = f32::INFINITY;
+        self.hi = f32::NEG_INFINITY;
+    }
+
+    /// Copy the newest `out.len()` columns (fewer if not recorded yet) into
+    /// `out`, oldest first; returns how many.
+    pub fn fill(&self, out: &mut [[f32; 2]]) -> usize {
+        let n = out.len().min(self.filled);
+        let cap = self.cols.len();
+        let start = (self.pos + cap - n) % cap;
+        for (k, y) in out[..n].iter_mut().enumerate() { *y = self.cols[(start + k) % cap]; }
+        n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_hold_each_stretch_min_and_max() {
+        // 100 samples per column, 5 columns
+        let mut w = WaveformHistory::new(1000.0, 10.0, 0.5);
+        assert_eq!(w.capacity(), 5);
+        for i in 0..250 { w.push(if i < 100 { 0.1 * (i % 3) as f32 } else { -0.5 }); }
+        let mut out = [[9.0; 2]; 5];
+        assert_eq!(w.fill(&mut out), 2); // the third column is still open
+        assert_eq!(&out[..2], &[[0.0, 0.2], [-0.5, -0.5]]);
+    }
+
+    #[test]
+    fn the_ring_keeps_the_newest_columns() {
+        let mut w = WaveformHistory::new(1000.0, 10.0, 0.5);
+        for col in 0..8 {
+            for _ in 0..100 { w.push(col as f32); }
+        }
+        assert_eq!(w.len(), 5);
+        let mut out = [[0.0; 2]; 3];
+        assert_eq!(w.fill(&mut out), 3);
+        assert_eq!(out, [[5.0, 5.0], [6.0, 6.0], [7.0, 7.0]]);
+
+        w.clear();
+        assert!(w.is_empty());
+        assert_eq!(w.fill(&mut out), 0);
+    }
+
+    #[test]
+    fn columns_keep_their_length_in_time_across_rates() {
+        let mut w = WaveformHistory::new(1000.0, 10.0, 1.0);
+        w.set_sample_rate(2000.0);
+        // the open column was sized at 1 kHz; the next one spans as long at 2 kHz
+        for _ in 0..100 { w.push(1.0); }
+        assert_eq!(w.len(), 1);
+        for _ in 0..199 { w.push(1.0); }
+        assert_eq!(w.len(), 1);
+        w.push(1.0);
+        assert_eq!(w.len(), 2);
+    }
+}
@@ -11,15 +11,85 @@
 //
 // Threading:
 //   - All functions are NOT thread-safe; call them all from the same audio thread.
+//   - Engines from ambientor_create_buffered additionally own a render thread;
+//     the same single-caller rule applies to the handle itself.
 
 #ifdef __cplusplus
 extern "C" {
 #endif
 
+#include <stdbool.h>
 #include <stdint.h>
 #include <stddef.h>
 
 typedef struct AmbientorEngine AmbientorEngine; // Opaque handle
+typedef struct AmbientorBank AmbientorBank;     // Opaque handle (engine mixer)
+
+// Silence notification (see ambientor_set_silence_callback).
+typedef void (*AmbientorSilenceCallback)(void* user_data, bool silent);
+
+// Diagnostics sink (see ambientor_set_log_callback); level is AMBIENTOR_LOG_*.
+typedef void (*AmbientorLogCallback)(void* user_data, uint8_t level, const char* message);
+
+// Channel-map source codes for ambientor_set_channel_map.
+#define AMBIENTOR_CH_SILENT 0
+#define AMBIENTOR_CH_LEFT   1
+#define AMBIENTOR_CH_RIGHT  2
+#define AMBIENTOR_CH_MONO   3
+
+// Dither modes for ambientor_set_dither.
+#define AMBIENTOR_DITHER_OFF    0
+#define AMBIENTOR_DITHER_TPDF   1
+#define AMBIENTOR_DITHER_SHAPED 2
+
+// File formats for ambientor_render_to_file / ambientor_render_loop_to_file.
+#define AMBIENTOR_FORMAT_WAV 0
+#define AMBIENTOR_FORMAT_OGG 1
+
+// Log levels for ambientor_set_log_level and AmbientorLogCallback.
+#define AMBIENTOR_LOG_ERROR 0
+#define AMBIENTOR_LOG_WARN  1
+#define AMBIENTOR_LOG_INFO  2
+#define AMBIENTOR_LOG_DEBUG 3
+
+// Parameter ids for ambientor_set_param_ramped / ambientor_get_param.
+#define AMBIENTOR_PARAM_INTENSITY     0
+#define AMBIENTOR_PARAM_SCENE_GAIN    1
+#define AMBIENTOR_PARAM_WIDTH         2
+#define AMBIENTOR_PARAM_TONE          3
+#define AMBIENTOR_PARAM_REVERB_RETURN 4
+#define AMBIENTOR_PARAM_DELAY_RETURN  5
+#define AMBIENTOR_PARAM_DISTANCE      6
+#define AMBIENTOR_PARAM_OCCLUSION     7
+#define AMBIENTOR_PARAM_CUT_BASE      8
+#define AMBIENTOR_PARAM_CUT_SPAN      9
+#define AMBIENTOR_PARAM_DRIVE         10
+#define AMBIENTOR_PARAM_DETUNE        11
+#define AMBIENTOR_PARAM_INHARMONICITY 12
+#define AMBIENTOR_PARAM_MOTION_RATE   13
+#define AMBIENTOR_PARAM_RUMBLE_RATE   14
+#define AMBIENTOR_PARAM_DUCK_DEPTH    15
+#define AMBIENTOR_PARAM_DRIP_DENSITY  16
+#define AMBIENTOR_PARAM_WETNESS       17
+#define AMBIENTOR_PARAM_CARRIER_HZ    18
+#define AMBIENTOR_PARAM_BEAT_HZ       19
+#define AMBIENTOR_PARAM_NOISE_BED     20
+#define AMBIENTOR_PARAM_DEGRADE       21
+#define AMBIENTOR_PARAM_WOW           22
+#define AMBIENTOR_PARAM_TIME_WARP     23
+
+// Event kinds for AmbientorEvent.kind (see ambientor_poll_event).
+#define AMBIENTOR_EVENT_NOTE    0
+#define AMBIENTOR_EVENT_THUNDER 1
+#define AMBIENTOR_EVENT_SECTION 2
+
+// A scene event; fields the kind has no use for are zero.
+typedef struct AmbientorEvent {
+    uint32_t kind;    // AMBIENTOR_EVENT_*
+    float    pitch;   // note pitch in Hz
+    float    amp;     // note velocity or thunder peak, 0..1
+    uint32_t section; // new section number
+} AmbientorEvent;
 
 // --- Lifecycle ---------------------------------------------------------------
 
@@ -31,7 +101,120 @@ typedef struct AmbientorEngine AmbientorEngine; // Opaque handle
 AmbientorEngine* ambientor_create(float sample_rate);
 
 /**
- * Destroy an engine previously created by ambientor_create.
+ * Create an engine whose audio is rendered ahead by a worker thread into a
+ * lock-free ring; the render calls then only copy. Use it for heavy scenes or
+ * tight callback deadlines. Parameter changes take effect after the buffered
+ * audio (about ahead_frames / sample_rate seconds).
+ * @param sample_rate   device sample rate in Hz
+ * @param ahead_frames  render-ahead depth in frames (0 = default 4096)
+ * @return non-null handle on success; NULL if the worker cannot start
+ */
+AmbientorEngine* ambientor_create_buffered(float sample_rate, uint32_t ahead_frames);
+
+/**
+ * Frames a buffered engine had to fill with silence because its worker fell
+ * behind (always 0 for engines from ambientor_create).
+ */
+uint64_t ambientor_get_underrun_frames(const AmbientorEngine* engine);
+
+/**
+ * Diagnostic counter: how many times the engine caught NaN/Inf in rendered audio,
+ * replaced it with silence, and reset the offending scene or master bus state
+ * (normally 0; a growing count points at a bad parameter value).
+ */
+uint64_t ambientor_get_nan_resets(AmbientorEngine* engine);
+
+/**
+ * Clip statistics: frames in which the scene's final +/-1 clamp engaged, and
+ * all frames checked, since creation or the last ambientor_reset_clip_stats.
+ * @param clipped_frames  out (may be NULL)
+ * @param total_frames    out (may be NULL)
+ */
+void ambientor_get_clip_stats(AmbientorEngine* engine, uint64_t* clipped_frames, uint64_t* total_frames);
+
+/** Zero the clip counters. */
+void ambientor_reset_clip_stats(AmbientorEngine* engine);
+
+/**
+ * Smoothed magnitude spectrum of the last 2048 rendered samples (mono sum,
+ * before the output gain) for visualizers: fills `bins` with `n` equal-width
+ * bands from DC to Nyquist, each the peak linear magnitude in its range (a
+ * full-scale sine reads about 1.0). Magnitudes rise at once and decay between
+ * calls, so call it at a steady rate, e.g. once per UI frame. Returns the
+ * number of bands written.
+ */
+uint32_t ambientor_get_spectrum(AmbientorEngine* engine, float* bins, uint32_t n);
+
+/**
+ * Min/max history of the engine's output (mono sum, before the IR reverb and
+ * the output gain) for scrolling waveform displays: 10 columns per second over
+ * the last 60 s, cleared by ambientor_reset.
+ * Writes the newest `len` columns (fewer until that much has been rendered)
+ * into `out`, oldest first, as min, max pairs; `out` must hold 2 * len
+ * floats. Returns the number of columns written.
+ */
+uint32_t ambientor_get_waveform_history(AmbientorEngine* engine, float* out, uint32_t len);
+
+/**
+ * Pop the oldest unread scene event into `*out`: a note struck (cave drips,
+ * the tape loop's new chord), a thunder roll, or a new section (the tape
+ * loop's next chord). Returns false once none are left; poll after each
+ * render call until it does. Each scene keeps the last 64 events, dropping
+ * older unread ones. With render-ahead enabled, events arrive up to the
+ * render-ahead time early.
+ */
+bool ambientor_poll_event(AmbientorEngine* engine, AmbientorEvent* out);
+
+/**
+ * Analysis taps: internal signals sampled once per control block (32 frames)
+ * for oscilloscope and modulation displays. Engine taps (mono, any scene):
+ *   "scene"       the scene's dry mix, before the master bus
+ *   "pre_reverb"  the shared reverb's input
+ *   "post_filter" the master mix right after the EQ and tone tilt
+ *   "output"      the engine output
+ * Scene taps: slow drone "lfo" (0..1), "cutoff" (Hz), "drift" (cents);
+ * graph scenes "node.<id>" for every node. Scene taps follow scene swaps by
+ * name and read 0 while the scene lacks them.
+ * @return slot for ambientor_tap_read, or -1 for an unknown name or when all
+ *         8 slots are taken
+ */
+int32_t ambientor_tap_subscribe(AmbientorEngine* engine, const char* name);
+
+/** Free a slot from ambientor_tap_subscribe. */
+void ambientor_tap_unsubscribe(AmbientorEngine* engine, int32_t slot);
+
+/**
+ * Move up to `n` values the tap recorded since the last read into `out`,
+ * oldest first. Each slot keeps the last 1024 unread values. Returns how many
+ * were written.
+ */
+uint32_t ambientor_tap_read(AmbientorEngine* engine, int32_t slot, float* out, uint32_t n);
+
+/** Values per second each tap records (sample rate / 32). */
+float ambientor_tap_rate(AmbientorEngine* engine);
+
+/**
+ * Auto-trim: when the clamp keeps engaging (over 1% of frames in a 100 ms
+ * window) the scene's output gain drops 0.5 dB at a time, down to -12 dB;
+ * after 3 s without sustained clipping it recovers at 1 dB/s. Off by default.
+ */
+void ambientor_set_auto_trim(AmbientorEngine* engine, bool enabled);
+
+/** Current auto-trim in dB (0 = none, down to -12). */
+float ambientor_get_auto_trim_db(AmbientorEngine* engine);
+
+/**
+ * Level every built-in scene to the same loudness (off by default) using
+ * per-scene calibrations (at most +/-12 dB), so switching scenes doesn't jump
+ * in level. Scenes loaded from files are not adjusted.
+ */
+void ambientor_set_auto_gain(AmbientorEngine* engine, bool enabled);
+
+/** Loudness auto-gain aims for, RMS dBFS (-48..0, default -24). */
+void ambientor_set_auto_gain_target_db(AmbientorEngine* engine, float db);
+
+/**
+ * Destroy an engine previously created by ambientor_create or ambientor_create_buffered.
  */
 void ambientor_destroy(AmbientorEngine* engine);
 
@@ -41,16 +224,43 @@ void ambientor_destroy(AmbientorEngine* engine);
  */
 void ambientor_reset(AmbientorEngine* engine, float sample_rate);
 
+/**
+ * Set the sample rate without restarting the scene. Confirming the current rate
+ * is a no-op; a real change rescales coefficients while keeping phases,
+ * envelopes, and reverb tails. Safe to call on every device (re)configuration.
+ *
+ * @return true if the scene had to fall back to a full reset
+ */
+bool ambientor_set_sample_rate(AmbientorEngine* engine, float sample_rate);
+
+/**
+ * Return the engine's current sample rate in Hz (0.0f on a NULL handle).
+ */
+float ambientor_get_sample_rate(const AmbientorEngine* engine);
+
+// --- Scenes ------------------------------------------------------------------
+
+/**
+ * Switch to a built-in scene by name: "slow-drone" (default), "risset",
+ * "thunderstorm", "cave-drips", "binaural", or "tape-loop".
+ * The new scene starts with a soft fade-in; the current tuning, intensity,
+ * and time warp carry over.
+ * @return false for NULL/unknown names (the current scene keeps playing)
+ */
+bool ambientor_set_scene(AmbientorEngine* engine, const char* name);
+
 // --- Rendering ---------------------------------------------------------------
 
 /**
  * Render `frames` of audio into an interleaved f32 buffer with `channels` channels.
- * The internal generator is mono; the sample is duplicated to all channels.
+ * Channels are filled through the channel map (see ambientor_set_channel_map);
+ * by default stereo pairs repeat across the device, so mono scenes are duplicated.
+ * Without a map at most 32 channels can be addressed; more is an error.
  *
  * @param engine           engine handle
  * @param out_interleaved  non-null pointer to output buffer (frames * channels floats)
  * @param frames           number of frames to render
- * @param channels         channel count (1..N)
+ * @param channels         channel count (1..32 without a channel map)
  * @return frames rendered (0 on error)
  */
 uint32_t ambientor_render_interleaved_f32(
@@ -60,6 +270,203 @@ uint32_t ambientor_render_interleaved_f32(
     uint32_t channels
 );
 
+/**
+ * Render `frames` of audio into an interleaved f64 buffer with `channels` channels.
+ * Same behavior as ambientor_render_interleaved_f32, converted internally.
+ *
+ * @param engine           engine handle
+ * @param out_interleaved  non-null pointer to output buffer (frames * channels doubles)
+ * @param frames           number of frames to render
+ * @param channels         channel count (1..32 without a channel map)
+ * @return frames rendered (0 on error)
+ */
+uint32_t ambientor_render_interleaved_f64(
+    AmbientorEngine* engine,
+    double* out_interleaved,
+    uint32_t frames,
+    uint32_t channels
+);
+
+/**
+ * Render `frames` of audio into an interleaved 16-bit PCM buffer, for export to
+ * 16-bit files. Same routing as ambientor_render_interleaved_f32; samples are
+ * clamped and quantized with the dither chosen by ambientor_set_dither.
+ *
+ * @param engine           engine handle
+ * @param out_interleaved  non-null pointer to output buffer (frames * channels int16s)
+ * @param frames           number of frames to render
+ * @param channels         channel count (1..32 without a channel map)
+ * @return frames rendered (0 on error)
+ */
+uint32_t ambientor_render_interleaved_i16(
+    AmbientorEngine* engine,
+    int16_t* out_interleaved,
+    uint32_t frames,
+    uint32_t channels
+);
+
+/**
+ * Render a seamlessly loopable clip for offline export: `loop_frames +
+ * fade_frames` are rendered and the extra tail is crossfaded (equal-power) into
+ * the start, so the clip repeats without a seam. Dithered like
+ * ambientor_render_interleaved_i16. Allocates; do not call from an audio callback.
+ *
+ * @param engine           engine handle
+ * @param out_interleaved  non-null pointer to output buffer (loop_frames * channels int16s)
+ * @param loop_frames      clip length in frames
+ * @param fade_frames      crossfade length in frames (capped at loop_frames)
+ * @param channels         channel count (1..32 without a channel map)
+ * @return frames written (0 on error)
+ */
+uint32_t ambientor_render_loop_i16(
+    AmbientorEngine* engine,
+    int16_t* out_interleaved,
+    uint32_t loop_frames,
+    uint32_t fade_frames,
+    uint32_t channels
+);
+
+/**
+ * Quantization used by ambientor_render_interleaved_i16:
+ * AMBIENTOR_DITHER_TPDF (default) adds ±1 LSB triangular noise so quiet tails
+ * fade into a steady hiss instead of distorting; AMBIENTOR_DITHER_SHAPED also
+ * pushes that hiss towards high frequencies; AMBIENTOR_DITHER_OFF just rounds.
+ * Unknown codes are ignored.
+ */
+void ambientor_set_dither(AmbientorEngine* engine, uint8_t mode);
+
+/**
+ * Render `frames` frames offline straight into a file. WAV is 16-bit, dithered
+ * per ambientor_set_dither; Ogg Vorbis (built with the `vorbis` feature) uses
+ * `quality`. Blocks for the whole render; never call it from an audio callback.
+ *
+ * @param engine    engine handle
+ * @param path      UTF-8 output path
+ * @param frames    number of frames to render
+ * @param channels  channel count, routed like the interleaved render calls
+ * @param format    AMBIENTOR_FORMAT_WAV or AMBIENTOR_FORMAT_OGG
+ * @param quality   0 (smallest) .. 1 (best); ignored for WAV
+ * @return false on bad arguments, I/O errors, or a format this build lacks
+ */
+bool ambientor_render_to_file(
+    AmbientorEngine* engine,
+    const char* path,
+    uint64_t frames,
+    uint32_t channels,
+    uint8_t format,
+    float quality
+);
+
+/**
+ * Like ambientor_render_to_file, but the file loops seamlessly: `loop_frames +
+ * fade_frames` are rendered and the extra tail is crossfaded into the start.
+ */
+bool ambientor_render_loop_to_file(
+    AmbientorEngine* engine,
+    const char* path,
+    uint64_t loop_frames,
+    uint64_t fade_frames,
+    uint32_t channels,
+    uint8_t format,
+    float quality
+);
+
+/**
+ * Turn WAV file renders into Broadcast Wave files: a `bext` chunk with
+ * `description` (UTF-8, up to 256 bytes), the scene name, and the origination
+ * date/time (UTC). NULL or "" turns it off. WAV renders past 4 GB are written
+ * as RF64 either way.
+ */
+void ambientor_set_export_description(AmbientorEngine* engine, const char* description);
+
+/**
+ * @return true if this build can write `format` (AMBIENTOR_FORMAT_*)
+ */
+bool ambientor_export_format_available(uint8_t format);
+
+/**
+ * Render `frames` of stereo audio into two planar f32 buffers.
+ * Stereo scenes render true stereo; mono scenes are duplicated to both sides.
+ *
+ * @param engine  engine handle
+ * @param left    non-null pointer to `frames` floats (left channel)
+ * @param right   non-null pointer to `frames` floats (right channel)
+ * @param frames  number of frames to render
+ * @return frames rendered (0 on error)
+ */
+uint32_t ambientor_render_stereo_f32(
+    AmbientorEngine* engine,
+    float* left,
+    float* right,
+    uint32_t frames
+);
+
+/**
+ * Render `frames` of multichannel audio for a speaker layout chosen by `channels`:
+ *   4 = quad (FL FR RL RR)
+ *   6 = 5.1  (FL FR C LFE SL SR)
+ *   8 = 7.1  (FL FR C LFE BL BR SL SR)
+ * Each speaker gets a decorrelated feed and the scene's left/right sides slowly
+ * orbit the room. The channel map does not apply to this call.
+ *
+ * @return frames rendered (0 on error or unsupported channel count)
+ */
+uint32_t ambientor_render_spatial_f32(
+    AmbientorEngine* engine,
+    float* out_interleaved,
+    uint32_t frames,
+    uint32_t channels
+);
+
+/**
+ * Configure the multichannel spatializer.
+ * @param orbit_hz  orbit speed of the virtual sources in turns/second (default 0.01)
+ * @param spread    L/R source separation, 0 (point) .. 1 (+-90 degrees) (default 0.5)
+ * @param diffuse   share of the decorrelated bed sent to every speaker, 0..1 (default 0.3)
+ */
+void ambientor_set_spatial_params(AmbientorEngine* engine, float orbit_hz, float spread, float diffuse);
+
+/**
+ * Route the scene output to device channels for the interleaved render calls.
+ *
+ * @param sources  one AMBIENTOR_CH_* code per device channel; channels past
+ *                 `count` stay silent. NULL or count == 0 restores the default
+ *                 L/R pairs.
+ * @param count    number of entries in `sources` (max 32)
+ * @return false on an unknown code or count > 32
+ *
+ * Example: send only to channels 3-4 of an 8-channel interface:
+ *   const uint8_t map[] = { 0, 0, AMBIENTOR_CH_LEFT, AMBIENTOR_CH_RIGHT };
+ *   ambientor_set_channel_map(engine, map, 4);
+ */
+bool ambientor_set_channel_map(AmbientorEngine* engine, const uint8_t* sources, uint32_t count);
+
+// --- Silence detection -------------------------------------------------------
+
+/**
+ * Return true once the output has stayed below the silence threshold for the
+ * hold time (defaults: -90 dBFS peak for 5 s), e.g. when the scene gain is at 0.
+ * Battery-powered hosts can suspend the stream while this holds. Note that a
+ * suspended engine does not render, so un-suspend when you change parameters.
+ */
+bool ambientor_is_silent(const AmbientorEngine* engine);
+
+/**
+ * Configure the silence detector.
+ * @param threshold_db  peak level in dBFS under which output counts as silent (-140 .. 0)
+ * @param hold_s        how long the output must stay below it, in seconds
+ * Non-finite values leave that setting unchanged.
+ */
+void ambientor_set_silence_params(AmbientorEngine* engine, float threshold_db, float hold_s);
+
+/**
+ * Register a callback fired when the output enters (silent = true) or leaves
+ * (silent = false) silence. It runs on the render thread at the end of the
+ * render call that saw the change, so keep it short and non-blocking.
+ * Pass NULL to remove it.
+ */
+void ambientor_set_silence_callback(AmbientorEngine* engine, AmbientorSilenceCallback callback, void* user_data);
+
 // --- Global gain -------------------------------------------------------------
 
 /**
@@ -68,6 +475,221 @@ uint32_t ambientor_render_interleaved_f32(
  */
 void ambientor_set_gain(AmbientorEngine* engine, float gain);
 
+/**
+ * Set the soft-start (fade-in) length in milliseconds, applied on creation and
+ * after scene swaps (default 500 ms). A fade already in progress continues at
+ * the new speed; 0 disables the fade.
+ */
+void ambientor_set_fadein_ms(AmbientorEngine* engine, float ms);
+
+/**
+ * Set the overall scene intensity 0..1 (default 0.5 = the scene as designed).
+ * Each scene maps it onto its own density, brightness, and level, so one
+ * control dials the whole soundscape up or down. Changes glide smoothly.
+ */
+void ambientor_set_intensity(AmbientorEngine* engine, float intensity);
+
+/**
+ * Set the host tempo in BPM (20..400, default 120) for scenes that follow it;
+ * scene-file expressions read it as `bpm`.
+ */
+void ambientor_set_tempo(AmbientorEngine* engine, float bpm);
+
+/** Report the host transport state (default playing) to scenes that follow it. */
+void ambientor_set_transport(AmbientorEngine* engine, bool playing);
+
+// --- Master bus --------------------------------------------------------------
+
+/**
+ * Set master stereo width in percent (0..200): 0 = mono, 100 = unchanged,
+ * 200 = extra wide (adds a short decorrelation delay so mono material spreads too).
+ */
+void ambientor_set_stereo_width(AmbientorEngine* engine, float percent);
+
+/**
+ * Set the master output EQ, in dB per band (each -12 .. +12, default 0):
+ * a low shelf at 150 Hz, a peaking mid band (see ambientor_set_eq_mid_hz), and
+ * a high shelf at 6 kHz. Use it to fit the output to small or boomy speakers.
+ * All bands at 0 bypass the EQ.
+ */
+void ambientor_set_eq(AmbientorEngine* engine, float low_db, float mid_db, float high_db);
+
+/** Set the center of the master EQ's mid band in Hz (100 .. 10000, default 1000). */
+void ambientor_set_eq_mid_hz(AmbientorEngine* engine, float hz);
+
+/**
+ * Set the master tone (tilt EQ around 800 Hz), independent of the scene:
+ * -1 = darkest (lows +6 dB, highs -6 dB) .. 0 = flat (default) .. +1 = brightest.
+ * Handy for quieter, warmer playback at night.
+ */
+void ambientor_set_tone(AmbientorEngine* engine, float tone);
+
+/**
+ * Place the soundscape `meters` from the listener (game-engine style): unity
+ * gain up to the reference distance, ref / distance beyond it, no further drop
+ * past the max distance (see ambientor_set_distance_rolloff). Default 0 m.
+ * Changes glide over ~50 ms, so calling once per game frame is fine.
+ */
+void ambientor_set_listener_distance(AmbientorEngine* engine, float meters);
+
+/** Reference distance (default 1 m) and max distance (default 100 m) for the rolloff. */
+void ambientor_set_distance_rolloff(AmbientorEngine* engine, float ref_m, float max_m);
+
+/**
+ * Occlusion 0 (clear line of sight, default) .. 1 (behind walls): a low-pass
+ * closing down to 400 Hz plus up to 6 dB less level. Glides over ~50 ms.
+ */
+void ambientor_set_occlusion(AmbientorEngine* engine, float occlusion);
+
+/**
+ * High-pass at the end of the master chain, removing DC and subsonic rumble
+ * that would push small speakers (12 dB/oct). On by default at 25 Hz; `hz` is
+ * clamped to 10..80. Turn it off for full-range systems with subwoofers.
+ */
+void ambientor_set_master_highpass(AmbientorEngine* engine, bool enabled, float hz);
+
+/**
+ * Sub bed for installations with subwoofers: a mono 25–45 Hz sine swell at
+ * peak `level` (0..1, default 0 = off) whose level rises and falls with a
+ * `swell_hz` LFO (0.005..1). Gated behind the master high-pass: it only
+ * sounds (fading in over ~0.5 s) while that is disabled.
+ */
+void ambientor_set_sub_bed(AmbientorEngine* engine, float level, float hz, float swell_hz);
+
+/**
+ * Mono bass: everything below `hz` (clamped to 40..300, typically 120) is
+ * folded to mono through a Linkwitz–Riley (LR4) crossover, keeping the low
+ * end centered for single subwoofers and vinyl. Off by default.
+ */
+void ambientor_set_mono_bass(AmbientorEngine* engine, bool enabled, float hz);
+
+/**
+ * Multiband texture: the master is split into low / mid / high bands (LR4
+ * crossovers), each with its own saturation `amount` (0..1; denser, not
+ * louder) and `level_db` (±12). `band` is 0 (low), 1 (mid) or 2 (high).
+ * Bypassed while every band is at amount 0 and 0 dB (the default).
+ */
+void ambientor_set_texture_band(AmbientorEngine* engine, uint32_t band, float amount, float level_db);
+
+/**
+ * Crossover frequencies of the multiband texture in Hz (each 40..16000,
+ * default 200 and 3000; swapped if given backwards).
+ */
+void ambientor_set_texture_crossovers(AmbientorEngine* engine, float lo_hz, float hi_hz);
+
+/**
+ * Run the output through an impulse-response reverb (partitioned FFT
+ * convolution) loaded from the WAV file at `path`: mono or stereo, 8/16/24/32-bit
+ * PCM or float, any sample rate, cut at 12 s. `mix` blends dry (0) and wet (1);
+ * a peak limiter (-1 dBFS ceiling) after the reverb catches the peaks it adds.
+ * The wet signal lags by 512 samples. Loading allocates: call it off the audio
+ * thread or between renders. A NULL `path` removes the reverb. Returns false
+ * (keeping the current reverb) if the file can't be read.
+ */
+bool ambientor_load_ir(AmbientorEngine* engine, const char* path, float mix);
+
+/** Wet/dry balance of the impulse-response reverb (0..1); no-op without one. */
+void ambientor_set_ir_mix(AmbientorEngine* engine, float mix);
+
+/**
+ * Move parameter `id` (AMBIENTOR_PARAM_*) to `target` over `ms` milliseconds,
+ * ramped inside the engine (updated every 32 samples), so hosts that only
+ * update at frame rate avoid zipper noise. Gains and frequencies ramp linearly
+ * in dB / octaves, other parameters linearly. ms <= 0 sets the value at once.
+ * A new ramp on the same parameter continues from the current value.
+ * Scene-specific ids (CUT_BASE .. WOW) only apply to their scene.
+ * @return false for an unknown id, a parameter the current scene lacks, or
+ *         more than 16 ramps at once
+ */
+bool ambientor_set_param_ramped(AmbientorEngine* engine, uint32_t id, float target, float ms);
+
+/**
+ * Current value of parameter `id` (mid-ramp values included).
+ * @return false (value untouched) for an unknown id or a parameter the
+ *         current scene lacks
+ */
+bool ambientor_get_param(AmbientorEngine* engine, uint32_t id, float* value);
+
+/**
+ * Return all parameters to the current scene's defaults ("reset knobs"):
+ * scene parameters, intensity, width, tone, EQ, reverb/delay returns,
+ * distance and occlusion. Stops running ramps. Playback is not interrupted:
+ * phases and reverb/delay tails are kept and the sound glides back.
+ */
+void ambientor_reset_params(AmbientorEngine* engine);
+
+/**
+ * Morph from preset `from` to preset `to` over `minutes` (e.g. a "bright
+ * morning" to "dark evening" preset across an afternoon). Presets are
+ * comma-separated name=value lists, e.g. "intensity=0.7,tone=0.3,width=120";
+ * names are the AMBIENTOR_PARAM_* names in lower case ("scene_gain",
+ * "cut_base", ...). Parameters in `from` jump there at once, then everything
+ * in `to` glides (gains and frequencies by ratio). `from` may be NULL or "" to
+ * start from the current values. Setting a parameter directly takes it out of
+ * the morph.
+ * @return false (nothing changed) if a preset names an unknown parameter or
+ *         has a bad number
+ */
+bool ambientor_morph(AmbientorEngine* engine, const char* from, const char* to, float minutes);
+
+/**
+ * A/B compare: store the current parameters as snapshot A or B, then switch
+ * between them by ear with ambientor_toggle_ab. Storing makes that snapshot
+ * the active one. Snapshots hold the same parameters as ambientor_get_param.
+ */
+void ambientor_store_a(AmbientorEngine* engine);
+void ambientor_store_b(AmbientorEngine* engine);
+
+/**
+ * Crossfade every parameter to the other snapshot over `crossfade_ms`
+ * (0 = instant). Changes made since the last store or toggle are dropped
+ * unless stored first.
+ * @return false if the other snapshot was never stored
+ */
+bool ambientor_toggle_ab(AmbientorEngine* engine, float crossfade_ms);
+
+/** Active snapshot: 0 = A, 1 = B (-1 for a null engine). */
+int32_t ambientor_ab_slot(AmbientorEngine* engine);
+
+/**
+ * Set the return level of the engine's shared reverb bus (0..2, default 1).
+ * Scenes send to one reverb owned by the master section; 0 leaves them dry.
+ * Room size and damping follow the loaded scene.
+ */
+void ambientor_set_reverb_return(AmbientorEngine* engine, float level);
+
+/**
+ * Switch the shared reverb bus off (true) or back on (false, default).
+ * Bypassing fades the tail out over ~20 ms, then the reverb stops using CPU.
+ * The reverb also idles by itself while nothing is sent to it.
+ */
+void ambientor_set_reverb_bypass(AmbientorEngine* engine, bool bypass);
+
+/**
+ * Set the return level of the engine's shared ping-pong delay bus (0..2, default 1).
+ * Only scenes with echoes ("cave-drips") send to it.
+ */
+void ambientor_set_delay_return(AmbientorEngine* engine, float level);
+
+// --- Tuning ------------------------------------------------------------------
+
+/**
+ * Set the reference pitch in Hz (A4 in 12-TET, or degree 0 of a ratio table),
+ * e.g. 432.0. Default 440. Non-positive values are ignored.
+ */
+void ambientor_set_tuning_ref(AmbientorEngine* engine, float hz);
+
+/**
+ * Use a non-equal temperament given as frequency ratios per scale degree.
+ *
+ * @param ratios  ascending ratios, normally starting at 1.0 (max 128 entries);
+ *                NULL or count == 0 restores 12-TET
+ * @param count   number of entries in `ratios`
+ * @param period  ratio at which the table repeats (2.0 = octave)
+ * @return false if the table is invalid (tuning unchanged)
+ */
+bool ambientor_set_tuning_ratios(AmbientorEngine* engine, const float* ratios, uint32_t count, float period);
+
 // --- Scene parameter helpers -------------------------------------------------
 
 /** Set base low-pass cutoff (Hz). */
@@ -85,6 +707,154 @@ void ambientor_scene_set_out_gain(AmbientorEngine* engine, float gain);
 /** Set detune depth (in cents) for slow drift + LFO. */
 void ambientor_scene_set_detune_cents(AmbientorEngine* engine, float cents);
 
+/** "risset" scene: stretch partials away from harmonic (0 .. 0.15). */
+void ambientor_scene_set_inharmonicity(AmbientorEngine* engine, float amount);
+
+/** "risset" scene: speed multiplier for the slow motion (1.0 = default, 0.1 .. 10). */
+void ambientor_scene_set_motion_rate(AmbientorEngine* engine, float rate);
+
+/** "thunderstorm" scene: storm intensity 0..1 (rain density, rumble loudness). */
+void ambientor_scene_set_storm_intensity(AmbientorEngine* engine, float intensity);
+
+/** "thunderstorm" scene: mean rumbles per minute (0 .. 20, default 1.5). */
+void ambientor_scene_set_rumble_rate(AmbientorEngine* engine, float per_min);
+
+/**
+ * Sidechain ducking: how far the bed layer dips under event layers, in dB
+ * (0 = off .. 24). "thunderstorm": rain under rumbles (default 4 dB, 1.5 s release).
+ */
+void ambientor_scene_set_duck_depth(AmbientorEngine* engine, float db);
+
+/** "cave-drips" scene: mean drips per minute (0 .. 240, default 12). */
+void ambientor_scene_set_drip_density(AmbientorEngine* engine, float per_min);
+
+/** "cave-drips" scene: echo + reverb wetness 0..1 (default 0.6). */
+void ambientor_scene_set_wetness(AmbientorEngine* engine, float wet);
+
+/**
+ * "binaural" scene: carrier frequency in Hz (40 .. 1000, default 200).
+ * The left ear plays carrier - beat/2, the right ear carrier + beat/2.
+ */
+void ambientor_scene_set_carrier_hz(AmbientorEngine* engine, float hz);
+
+/** "binaural" scene: beat rate in Hz (0.5 .. 40, default 10). Needs stereo playback. */
+void ambientor_scene_set_beat_hz(AmbientorEngine* engine, float hz);
+
+/** "binaural" scene: pink-noise bed level 0 (off, default) .. 1. */
+void ambientor_scene_set_noise_bed(AmbientorEngine* engine, float level);
+
+/** "tape-loop" scene: wear speed 0 (barely) .. 1 (falls apart quickly) (default 0.4). */
+void ambientor_scene_set_degrade(AmbientorEngine* engine, float amount);
+
+/** "tape-loop" scene: wow/flutter depth 0..1 (default 0.5). */
+void ambientor_scene_set_wow(AmbientorEngine* engine, float depth);
+
+// --- Introspection -----------------------------------------------------------
+
+/**
+ * Name of the current scene (e.g. "slow-drone"): a static string, never free it.
+ * @return "" for a NULL handle
+ */
+const char* ambientor_get_scene_name(AmbientorEngine* engine);
+
+/**
+ * Seconds of audio handed to the host since creation (buffered engines exclude
+ * audio still waiting in the ring). 0 for a NULL handle.
+ */
+double ambientor_get_time_seconds(AmbientorEngine* engine);
+
+/**
+ * Write the engine's current settings as a JSON object (scene, sample rate,
+ * time, gain, intensity, tuning, master bus, silence detector, clip stats, export settings,
+ * diagnostics) into `buf`, snprintf-style: at most len - 1 bytes plus a NUL.
+ * Locks a buffered engine briefly; don't call it from the audio callback.
+ *
+ * @param buf  destination (may be NULL when len == 0 to query the size)
+ * @param len  size of `buf` in bytes
+ * @return full JSON length in bytes without the NUL; if >= len, the output was
+ *         cut and a buffer of return value + 1 bytes is needed
+ */
+size_t ambientor_dump_params_json(AmbientorEngine* engine, char* buf, size_t len);
+
+// --- Engine bank -------------------------------------------------------------
+//
+// Several engines, each with its own scene, mixed into one output with
+// per-engine gain and pan and a shared limiter. Useful for crossfading
+// between soundscapes (e.g. per game area). Same threading rules as engines.
+
+/**
+ * Create an empty bank (up to 16 engines; limiter ceiling -1 dBFS).
+ * @return non-null handle; free it with ambientor_bank_destroy
+ */
+AmbientorBank* ambientor_bank_create(float sample_rate);
+
+/** Destroy a bank and every engine in it. */
+void ambientor_bank_destroy(AmbientorBank* bank);
+
+/**
+ * Add an engine playing scene `name` (as for ambientor_set_scene) at full
+ * gain, centered.
+ * @return its id (0..15), or -1 for an unknown name or a full bank
+ */
+int32_t ambientor_bank_add(AmbientorBank* bank, const char* name);
+
+/** Remove engine `id` (the id may be reused). @return false for an unknown id */
+bool ambientor_bank_remove(AmbientorBank* bank, uint32_t id);
+
+/**
+ * Ramp engine `id` to linear `gain` (0..4) over `ramp_ms` (0 = jump). An
+ * engine faded to 0 stops rendering (no CPU) until faded back in, and then
+ * resumes where it stopped.
+ * @return false for an unknown id
+ */
+bool ambientor_bank_set_gain(AmbientorBank* bank, uint32_t id, float gain, float ramp_ms);
+
+/**
+ * Balance engine `id` from -1 (left channel only) through 0 (unchanged) to
+ * +1 (right channel only). @return false for an unknown id
+ */
+bool ambientor_bank_set_pan(AmbientorBank* bank, uint32_t id, float pan);
+
+/** Scene intensity (0..1) of engine `id`. @return false for an unknown id */
+bool ambientor_bank_set_intensity(AmbientorBank* bank, uint32_t id, float intensity);
+
+/** Limiter ceiling in dBFS (-24..0, default -1) and release in ms (default 250). */
+void ambientor_bank_set_limiter(AmbientorBank* bank, float ceiling_db, float release_ms);
+
+/** Change the sample rate of the bank and all its engines. */
+void ambientor_bank_set_sample_rate(AmbientorBank* bank, float sample_rate);
+
+/**
+ * Render `frames` of the mix into an interleaved f32 buffer; stereo pairs
+ * repeat across `channels` (1..32).
+ * @return frames rendered (0 on error)
+ */
+uint32_t ambientor_bank_render_interleaved_f32(
+    AmbientorBank* bank,
+    float* out_interleaved,
+    uint32_t frames,
+    uint32_t channels
+);
+
+// --- Logging -----------------------------------------------------------------
+
+/**
+ * Route engine diagnostics (NaN/Inf recoveries, clamped or ignored parameters,
+ * ...) to `callback` instead of stderr. Process-wide, not per engine.
+ * The callback may run on any thread that renders or sets parameters,
+ * including the audio thread, so it must be quick and must not call back into
+ * the engine. `message` is only valid during the call.
+ * @param callback  NULL to go back to stderr
+ * @param user_data passed through to every call
+ */
+void ambientor_set_log_callback(AmbientorLogCallback callback, void* user_data);
+
+/**
+ * Only deliver messages at `level` or more severe (default AMBIENTOR_LOG_INFO).
+ * @return false for an unknown level
+ */
+bool ambientor_set_log_level(uint8_t level);
+
 #ifdef __cplusplus
 } // extern "C"
 #endif
@@ -6,15 +6,168 @@
 //! ABI notes
 //! - All functions are `extern "C"` and `#[no_mangle]`.
 //! - Opaque handle type: `AmbientorEngine` (heap-allocated; you own/delete it).
-//! - Render path produces **mono** internally and duplicates to N channels.
+//! - Interleaved render routes the scene's stereo frame to N channels through a
+//!   channel map (default: L/R pairs; mono scenes end up duplicated everywhere);
+//!   `ambientor_render_stereo_f32` renders true stereo into two planar buffers.
+//!
+//! - `AmbientorBank` mixes several engines (one scene each) into one output,
+//!   for hosts that crossfade between soundscapes (`ambientor_bank_*`).
 //!
 //! Threading
 //! - The object is NOT thread-safe; call all functions from the same audio thread.
+//!
+//! Pointers
+//! - Every function takes raw pointers from C and checks them for null before
+//!   use; beyond that, callers must pass handles from the matching `*_create`
+//!   that haven't been destroyed, NUL-terminated strings, and buffers at least
+//!   as long as the lengths they pass with them. The functions stay safe
+//!   `extern "C"` (as seen from C), so clippy's `not_unsafe_ptr_arg_deref` is
+//!   allowed for the whole crate instead of on each of them.
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
 
-use ambientor_engine::{Engine};
-use ambientor_engine::scenes::Scene;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::fmt::Write as _;
+use std::ops::{Deref, DerefMut};
+use std::sync::{MutexGuard, OnceLock};
+
+use ambientor_engine::{log, AbSlot, Engine, EngineBank, ParamId, ParamSet, SceneEvent, Transport, DEFAULT_TEMPO_BPM};
+use ambientor_engine::convolution::ConvolutionReverb;
+use ambientor_engine::fft::{SpectrumAnalyzer, SPECTRUM_FFT_SIZE};
+use ambientor_engine::effects::{Limiter, TEXTURE_XOVER_HZ};
+use ambientor_engine::export::{self, AudioFileWriter, BextInfo, Dither, DitherMode, ExportFormat, ExportOptions};
+use ambientor_engine::master::{MASTER_HP_HZ, MONO_BASS_HZ, SUB_BED_DEFAULT_HZ};
+use ambientor_engine::routing::{ChannelMap, ChannelSource, MAX_CHANNELS};
+use ambientor_engine::scenes::{Scene, SCENE_NAMES};
+use ambientor_engine::spatial::{SpeakerLayout, Spatializer, MAX_SPATIAL_CHANNELS};
+use ambientor_engine::stream::{RenderAhead, DEFAULT_AHEAD_FRAMES, DEFAULT_BLOCK_FRAMES};
+use ambientor_engine::waveform::{WAVEFORM_COLUMNS_PER_S, WAVEFORM_HISTORY_S};
 use ambientor_engine::Generator;
 
+/// Channel-map source codes for `ambientor_set_channel_map`.
+pub const AMBIENTOR_CH_SILENT: u8 = 0;
+pub const AMBIENTOR_CH_LEFT: u8 = 1;
+pub const AMBIENTOR_CH_RIGHT: u8 = 2;
+pub const AMBIENTOR_CH_MONO: u8 = 3;
+
+/// Dither modes for `ambientor_set_dither`.
+pub const AMBIENTOR_DITHER_OFF: u8 = 0;
+pub const AMBIENTOR_DITHER_TPDF: u8 = 1;
+pub const AMBIENTOR_DITHER_SHAPED: u8 = 2;
+
+/// File formats for `ambientor_render_to_file` / `ambientor_render_loop_to_file`.
+pub const AMBIENTOR_FORMAT_WAV: u8 = 0;
+pub const AMBIENTOR_FORMAT_OGG: u8 = 1;
+
+/// Log levels for `ambientor_set_log_level` and the log callback.
+pub const AMBIENTOR_LOG_ERROR: u8 = 0;
+pub const AMBIENTOR_LOG_WARN: u8 = 1;
+pub const AMBIENTOR_LOG_INFO: u8 = 2;
+pub const AMBIENTOR_LOG_DEBUG: u8 = 3;
+
+/// Parameter ids for `ambientor_set_param_ramped` / `ambientor_get_param`
+/// (same numbering as `ambientor_engine::ParamId`).
+pub const AMBIENTOR_PARAM_INTENSITY: u32 = 0;
+pub const AMBIENTOR_PARAM_SCENE_GAIN: u32 = 1;
+pub const AMBIENTOR_PARAM_WIDTH: u32 = 2;
+pub const AMBIENTOR_PARAM_TONE: u32 = 3;
+pub const AMBIENTOR_PARAM_REVERB_RETURN: u32 = 4;
+pub const AMBIENTOR_PARAM_DELAY_RETURN: u32 = 5;
+pub const AMBIENTOR_PARAM_DISTANCE: u32 = 6;
+pub const AMBIENTOR_PARAM_OCCLUSION: u32 = 7;
+pub const AMBIENTOR_PARAM_CUT_BASE: u32 = 8;
+pub const AMBIENTOR_PARAM_CUT_SPAN: u32 = 9;
+pub const AMBIENTOR_PARAM_DRIVE: u32 = 10;
+pub const AMBIENTOR_PARAM_DETUNE: u32 = 11;
+pub const AMBIENTOR_PARAM_INHARMONICITY: u32 = 12;
+pub const AMBIENTOR_PARAM_MOTION_RATE: u32 = 13;
+pub const AMBIENTOR_PARAM_RUMBLE_RATE: u32 = 14;
+pub const AMBIENTOR_PARAM_DUCK_DEPTH: u32 = 15;
+pub const AMBIENTOR_PARAM_DRIP_DENSITY: u32 = 16;
+pub const AMBIENTOR_PARAM_WETNESS: u32 = 17;
+pub const AMBIENTOR_PARAM_CARRIER_HZ: u32 = 18;
+pub const AMBIENTOR_PARAM_BEAT_HZ: u32 = 19;
+pub const AMBIENTOR_PARAM_NOISE_BED: u32 = 20;
+pub const AMBIENTOR_PARAM_DEGRADE: u32 = 21;
+pub const AMBIENTOR_PARAM_WOW: u32 = 22;
+pub const AMBIENTOR_PARAM_TIME_WARP: u32 = 23;
+
+/// Event kinds in `AmbientorEvent::kind` (see `ambientor_poll_event`).
+pub const AMBIENTOR_EVENT_NOTE: u32 = 0;
+pub const AMBIENTOR_EVENT_THUNDER: u32 = 1;
+pub const AMBIENTOR_EVENT_SECTION: u32 = 2;
+
+/// One scene event as `ambientor_poll_event` reports it. Fields a kind has
+/// no use for are zero.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AmbientorEvent {
+    /// `AMBIENTOR_EVENT_*`.
+    pub kind: u32,
+    /// Note pitch in Hz.
+    pub pitch: f32,
+    /// Note velocity or thunder peak, 0..1.
+    pub amp: f32,
+    /// New section number.
+    pub section: u32,
+}
+
+impl From<SceneEvent> for AmbientorEvent {
+    fn from(ev: SceneEvent) -> Self {
+        match ev {
+            SceneEvent::NoteTriggered { pitch, amp } => Self { kind: AMBIENTOR_EVENT_NOTE, pitch, amp, section: 0 },
+            SceneEvent::Thunder { amp } => Self { kind: AMBIENTOR_EVENT_THUNDER, amp, ..Self::default() },
+            SceneEvent::SectionChanged(n) => Self { kind: AMBIENTOR_EVENT_SECTION, section: n, ..Self::default() },
+        }
+    }
+}
+
+fn export_format(code: u8) -> Option<ExportFormat> {
+    match code {
+        AMBIENTOR_FORMAT_WAV => Some(ExportFormat::Wav),
+        AMBIENTOR_FORMAT_OGG => Some(ExportFormat::OggVorbis),
+        _ => None,
+    }
+}
+
+
+/// Called from the render thread when the output enters (`silent == true`) or
+/// leaves silence. See `ambientor_set_silence_callback`.
+pub type AmbientorSilenceCallback = Option<extern "C" fn(user_data: *mut c_void, silent: bool)>;
+
+/// Receives engine diagnostics (`AMBIENTOR_LOG_*` level, NUL-terminated UTF-8
+/// message). See `ambientor_set_log_callback`.
+pub type AmbientorLogCallback = Option<extern "C" fn(user_data: *mut c_void, level: u8, message: *const c_char)>;
+
+/// Where the engine runs: inline in the render calls, or on a render-ahead worker.
+enum Inner {
+    Direct(Box<Engine<Scene>>),
+    Buffered(RenderAhead<Scene>),
+}
+
+/// Mutable access to the engine either way (locks it when buffered).
+enum EngineRef<'a> {
+    Direct(&'a mut Engine<Scene>),
+    Locked(MutexGuard<'a, Box<Engine<Scene>>>),
+}
+
+impl Deref for EngineRef<'_> {
+    type Target = Engine<Scene>;
+    fn deref(&self) -> &Engine<Scene> {
+        match self {
+            Self::Direct(e) => e,
+            Self::Locked(g) => g,
+        }
+    }
+}
+
+impl DerefMut for EngineRef<'_> {
+    fn deref_mut(&mut self) -> &mut Engine<Scene> {
+        match self {
+            Self::Direct(e) => e,
+            Self::Locked(g) => g,
+        }
+    }
+}
 
 /// Opaque engine wrapper we hand to C.
 ///
@@ -25,18 +178,242 @@ use ambientor_engine::Generator;
 pub struct AmbientorEngine {
     sr: f32,
     gain: f32,
-    inner: Engine<Scene>,
+    inner: Inner,
+    // `None` → default L/R pairs for whatever channel count the host renders.
+    map: Option<ChannelMap>,
+    // multichannel render path (`ambientor_render_spatial_f32`)
+    spatial: Spatializer,
+    // silence notifications (fired after a render call when the state flips)
+    silence_cb: AmbientorSilenceCallback,
+    silence_user: *mut c_void,
+    was_silent: bool,
+    // quantizer for `ambientor_render_interleaved_i16` (error state spans calls)
+    dither: Dither,
+    // Broadcast Wave description for file renders (`None` → plain WAV)
+    export_description: Option<String>,
+    // impulse-response reverb on the output (`ambientor_load_ir`), limited
+    // after it: the wet tail can peak well above the engine's output
+    ir: Option<Box<ConvolutionReverb>>,
+    ir_limiter: Limiter,
+    // recent output for `ambientor_get_spectrum`
+    spectrum: SpectrumAnalyzer,
 }
 
 impl AmbientorEngine {
     fn new(sr: f32) -> Self {
         let sr = sr.max(1.0);
+        Self::with_inner(sr, Inner::Direct(Self::default_engine(sr)))
+    }
+
+    /// Engine with the default scene, already at `sr`.
+    fn default_engine(sr: f32) -> Box<Engine<Scene>> {
         let scene = Scene::slow_drone(sr);
-        let mut e = Engine::new(scene);
+        let mut e = Box::new(Engine::new(scene));
         // ensure scene got the exact SR we want
+        e.set_sample_rate(sr);
         e.scene_mut().reset(sr);
-        Self { sr, gain: 1.0, inner: e }
+        e.enable_waveform(Some((WAVEFORM_COLUMNS_PER_S, WAVEFORM_HISTORY_S)));
+        e
+    }
+
+    fn with_inner(sr: f32, inner: Inner) -> Self {
+        Self {
+            sr,
+            gain: 1.0,
+            inner,
+            map: None,
+            spatial: Spatializer::new(SpeakerLayout::Quad, sr),
+            silence_cb: None,
+            silence_user: std::ptr::null_mut(),
+            was_silent: false,
+            dither: Dither::new(DitherMode::Tpdf, 2),
+            export_description: None,
+            ir: None,
+            ir_limiter: Limiter::new(sr),
+            spectrum: SpectrumAnalyzer::new(SPECTRUM_FFT_SIZE),
+        }
+    }
+
+    /// Engine for parameter changes (locked while the worker is not rendering).
+    fn engine(&mut self) -> EngineRef<'_> {
+        match &mut self.inner {
+            Inner::Direct(e) => EngineRef::Direct(e),
+            Inner::Buffered(b) => EngineRef::Locked(b.engine()),
+        }
+    }
+
+    /// Tell a directly rendered engine the size of the host buffer about to be
+    /// rendered (render-ahead engines report their own block size).
+    fn begin_block(&mut self, frames: u32) {
+        if let Inner::Direct(e) = &mut self.inner { e.set_block_len(frames); }
+    }
+
+    /// Next stereo frame for the render calls.
+    #[inline]
+    fn next_frame(&mut self) -> (f32, f32) {
+        let (l, r) = match &mut self.inner {
+            Inner::Direct(e) => e.next_stereo(self.sr),
+            Inner::Buffered(b) => b.next_frame(),
+        };
+        let (l, r) = match self.ir.as_deref_mut() {
+            Some(c) => {
+                let (l, r) = c.process(l, r);
+                self.ir_limiter.process(l, r)
+            }
+            None => (l, r),
+        };
+        self.spectrum.push(0.5 * (l + r));
+        (l, r)
+    }
+
+    fn is_silent(&self) -> bool {
+        match &self.inner {
+            Inner::Direct(e) => e.is_silent(),
+            Inner::Buffered(b) => b.is_silent(),
+        }
+    }
+
+    /// Render `frames` (looped with a `fade` crossfade if given) straight into a file.
+    fn render_to_file(
+        &mut self,
+        path: &CStr,
+        frames: usize,
+        fade: Option<usize>,
+        channels: usize,
+        format: ExportFormat,
+        quality: f32,
+    ) -> std::io::Result<()> {
+        let path = path.to_str().map_err(|_| std::io::ErrorKind::InvalidInput)?;
+        let ch = u16::try_from(channels).map_err(|_| std::io::ErrorKind::InvalidInput)?;
+        let bext = self.export_description.clone().map(|d| BextInfo {
+            originator_reference: self.engine().scene_mut().name().to_string(),
+            ..BextInfo::new(d)
+        });
+        let opts = ExportOptions {
+            dither: self.dither.mode(),
+            quality: if quality.is_finite() { quality } else { 0.6 },
+            bext,
+        };
+        let mut writer = AudioFileWriter::create(path, format, self.sr as u32, ch, &opts)?;
+
+        let map = self.map.or_else(|| ChannelMap::stereo_pairs(channels)).ok_or(std::io::ErrorKind::InvalidInput)?;
+        let render = |block: &mut [f32]| {
+            for frame in block.chunks_exact_mut(channels) {
+                let (l, r) = self.next_frame();
+                map.write_frame(frame, l * self.gain, r * self.gain);
+            }
+        };
+        let sink = |block: &[f32]| writer.write(block);
+        match fade {
+            Some(fade) => export::render_loop(channels, frames, fade, render, sink)?,
+            None => export::render_blocks(channels, frames, render, sink)?,
+        }
+        self.notify_silence();
+        writer.finish()
+    }
+
+    /// Seconds of audio handed out so far (rendered time minus what is still buffered).
+    fn time_s(&mut self) -> f64 {
+        let ahead = match &self.inner {
+            Inner::Direct(_) => 0,
+            Inner::Buffered(b) => b.buffered_frames(),
+        };
+        let sr = f64::from(self.sr);
+        (self.engine().time() - ahead as f64 / sr).max(0.0)
+    }
+
+    /// Current settings as a JSON object (see `ambientor_dump_params_json`).
+    fn params_json(&mut self) -> String {
+        let (sr, gain, dither, time_s) = (self.sr, self.gain, self.dither.mode().name(), self.time_s());
+        let (buffered, underruns) = match &self.inner {
+            Inner::Direct(_) => (false, 0),
+            Inner::Buffered(b) => (true, b.underrun_frames()),
+        };
+        let description = self.export_description.as_deref().map_or("null".to_string(), json_string);
+        let mut eng = self.engine();
+        let scene = eng.scene_mut();
+        let (name, intensity, tuning_ref) = (json_string(scene.name()), scene.intensity(), scene.tuning().ref_hz());
+        let time_warp = scene.time_warp();
+        let m = eng.master();
+        let (low, mid, high) = m.eq().gains_db();
+        let s = eng.silence();
+        let c = eng.clip_guard();
+
+        let mut j = String::with_capacity(768);
+        let _ = write!(j, "{{\"scene\":{},\"sample_rate\":{sr},\"time_s\":{time_s:.3},", name);
+        let _ = write!(
+            j,
+            "\"gain\":{gain},\"intensity\":{intensity},\"time_warp\":{time_warp},\"tempo_bpm\":{},\"playing\":{},\"tuning_ref_hz\":{tuning_ref},\"fade_in_s\":{},",
+            eng.tempo(), eng.transport() == Transport::Playing, eng.fade_in_s(),
+        );
+        let _ = write!(
+            j,
+            "\"auto_gain\":{{\"enabled\":{},\"target_db\":{},\"gain_db\":{:.2}}},",
+            eng.auto_gain(), eng.auto_gain_target_db(), eng.auto_gain_db(),
+        );
+        let _ = write!(
+            j,
+            "\"master\":{{\"width_pct\":{},\"eq_db\":[{low},{mid},{high}],\"eq_mid_hz\":{},\"tone\":{},\"reverb_return\":{},\"reverb_bypass\":{},\"delay_return\":{},\"distance_m\":{},\"occlusion\":{},\"highpass\":{{\"enabled\":{},\"hz\":{}}},\"sub_bed\":{{\"level\":{},\"hz\":{}}},\"mono_bass\":{{\"enabled\":{},\"hz\":{}}},",
+            m.width_percent(), m.eq().mid_hz(), m.tone(), m.reverb_return(), m.reverb_bypass(), m.delay_return(),
+            m.listener_distance(), m.occlusion(), m.highpass_on(), m.highpass_hz(), m.sub_bed().level(), m.sub_bed().freq(),
+            m.mono_bass_on(), m.mono_bass_hz(),
+        );
+        let t = m.texture();
+        let ((lo_hz, hi_hz), [b0, b1, b2]) = (t.crossovers(), t.bands());
+        let _ = write!(
+            j,
+            "\"texture\":{{\"crossovers_hz\":[{lo_hz},{hi_hz}],\"amount\":[{},{},{}],\"level_db\":[{},{},{}]}}}},",
+            b0.0, b1.0, b2.0, b0.1, b1.1, b2.1,
+        );
+        let _ = write!(
+            j,
+            "\"silence\":{{\"threshold_db\":{},\"hold_s\":{},\"silent\":{}}},",
+            s.threshold_db(), s.hold_s(), eng.is_silent(),
+        );
+        let _ = write!(
+            j,
+            "\"clip\":{{\"clipped_frames\":{},\"total_frames\":{},\"auto_trim\":{},\"trim_db\":{:.2}}},",
+            c.clipped_frames(), c.total_frames(), c.auto_trim(), c.trim_db(),
+        );
+        let _ = write!(
+            j,
+            "\"export\":{{\"dither\":\"{dither}\",\"description\":{description}}},\"buffered\":{buffered},\"underrun_frames\":{underruns},\"nan_resets\":{}}}",
+            eng.nan_resets(),
+        );
+        j
+    }
+
+    /// Fire the silence callback if the detector changed state since the last render.
+    fn notify_silence(&mut self) {
+        let silent = self.is_silent();
+        if silent != self.was_silent {
+            self.was_silent = silent;
+            if let Some(cb) = self.silence_cb { cb(self.silence_user, silent); }
+        }
+    }
+}
+
+/// `x` if finite, otherwise `fallback` (with a warning naming the setter).
+fn finite_or(what: &str, x: f32, fallback: f32) -> f32 {
+    if x.is_finite() { return x; }
+    log::warn(format_args!("{what}: ignoring non-finite value {x}, using {fallback}"));
+    fallback
+}
+
+/// `s` as a quoted JSON string.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => { let _ = write!(out, "\\u{:04x}", c as u32); }
+            c => out.push(c),
+        }
     }
+    out.push('"');
+    out
 }
 
 // --- Creation / destruction -------------------------------------------------------
@@ -52,7 +429,184 @@ pub extern "C" fn ambientor_create(sample_rate: f32) -> *mut AmbientorEngine {
     }
 }
 
-/// Destroy an engine previously returned by `ambientor_create`.
+/// Create an engine (default scene) rendered ahead of the audio callback by a
+/// worker thread, `ahead_frames` deep (0 = 4096). Render calls then only copy
+/// from a lock-free ring; parameter changes take effect after the buffered audio
+/// and briefly lock the engine against the worker. Returns null if the worker
+/// thread cannot be started.
+#[no_mangle]
+pub extern "C" fn ambientor_create_buffered(sample_rate: f32, ahead_frames: u32) -> *mut AmbientorEngine {
+    let sr = sample_rate.max(1.0);
+    let ahead = if ahead_frames == 0 { DEFAULT_AHEAD_FRAMES } else { ahead_frames as usize };
+    let engine = AmbientorEngine::default_engine(sr);
+    let Ok(worker) = RenderAhead::new(engine, ahead, DEFAULT_BLOCK_FRAMES) else { return std::ptr::null_mut(); };
+    Box::into_raw(Box::new(AmbientorEngine::with_inner(sr, Inner::Buffered(worker))))
+}
+
+/// Frames a buffered engine had to replace with silence because its worker fell
+/// behind (always 0 for engines from `ambientor_create`).
+#[no_mangle]
+pub extern "C" fn ambientor_get_underrun_frames(engine: *const AmbientorEngine) -> u64 {
+    if engine.is_null() { return 0; }
+    let e = unsafe { &*engine };
+    match &e.inner {
+        Inner::Direct(_) => 0,
+        Inner::Buffered(b) => b.underrun_frames(),
+    }
+}
+
+/// How many times the engine caught NaN/Inf in its output and reset the scene or
+/// master bus (diagnostic; normally 0).
+#[no_mangle]
+pub extern "C" fn ambientor_get_nan_resets(engine: *mut AmbientorEngine) -> u64 {
+    if engine.is_null() { return 0; }
+    let e = unsafe { &mut *engine };
+    e.engine().nan_resets()
+}
+
+/// Frames in which the scene's final ±1 clamp engaged, and all frames checked,
+/// since creation or the last `ambientor_reset_clip_stats`. Either pointer may be null.
+#[no_mangle]
+pub extern "C" fn ambientor_get_clip_stats(engine: *mut AmbientorEngine, clipped_frames: *mut u64, total_frames: *mut u64) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    let eng = e.engine();
+    let c = eng.clip_guard();
+    if !clipped_frames.is_null() { unsafe { *clipped_frames = c.clipped_frames(); } }
+    if !total_frames.is_null() { unsafe { *total_frames = c.total_frames(); } }
+}
+
+/// Zero the clip counters.
+#[no_mangle]
+pub extern "C" fn ambientor_reset_clip_stats(engine: *mut AmbientorEngine) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    e.engine().reset_clip_stats();
+}
+
+/// Smoothed magnitude spectrum of the last 2048 rendered samples (mono sum,
+/// before the output gain), for visualizers: `n` equal-width bands from DC
+/// to Nyquist, each the peak linear magnitude in its range (a full-scale sine
+/// reads about 1.0). Magnitudes rise at once and decay between calls, so call
+/// it at a steady rate (e.g. once per UI frame). Returns the bands written.
+#[no_mangle]
+pub extern "C" fn ambientor_get_spectrum(engine: *mut AmbientorEngine, bins: *mut f32, n: u32) -> u32 {
+    if engine.is_null() || bins.is_null() || n == 0 { return 0; }
+    let e = unsafe { &mut *engine };
+    let out = unsafe { std::slice::from_raw_parts_mut(bins, n as usize) };
+    e.spectrum.fill_bands(out);
+    n
+}
+
+/// Min/max history of the engine's output (mono sum, before the IR reverb
+/// and the output gain) for scrolling waveform displays: 10 columns per second
+/// over the last 60 s, cleared by `ambientor_reset`. Writes the newest `len`
+/// columns (fewer until that much has been rendered) into `out`, oldest first,
+/// as `min, max` pairs, so `out` must hold `2 * len` floats. Returns the
+/// columns written.
+#[no_mangle]
+pub extern "C" fn ambientor_get_waveform_history(engine: *mut AmbientorEngine, out: *mut f32, len: u32) -> u32 {
+    if engine.is_null() || out.is_null() || len == 0 { return 0; }
+    let e = unsafe { &mut *engine };
+    let out = unsafe { std::slice::from_raw_parts_mut(out.cast::<[f32; 2]>(), len as usize) };
+    e.engine().waveform().map_or(0, |w| w.fill(out)) as u32
+}
+
+/// Pop the oldest unread scene event (a note struck, thunder, a new section)
+/// into `*out`; `false` once there are none left. Poll after each render call
+/// until it returns `false`. Each scene keeps the last 64; older unread events
+/// are dropped. With render-ahead enabled events arrive up to the render-ahead
+/// time early.
+#[no_mangle]
+pub extern "C" fn ambientor_poll_event(engine: *mut AmbientorEngine, out: *mut AmbientorEvent) -> bool {
+    if engine.is_null() || out.is_null() { return false; }
+    let e = unsafe { &mut *engine };
+    match e.engine().poll_event() {
+        Some(ev) => {
+            unsafe { *out = ev.into() };
+            true
+        }
+        None => false,
+    }
+}
+
+/// Subscribe to the analysis tap `name` (NUL-terminated UTF-8): "scene",
+/// "pre_reverb", "post_filter", "output", or a tap of the current scene
+/// (slow drone: "lfo", "cutoff", "drift"; graph scenes: "node.<id>").
+/// Returns the slot for `ambientor_tap_read`, or -1 for an unknown name or
+/// when all 8 slots are taken.
+#[no_mangle]
+pub extern "C" fn ambientor_tap_subscribe(engine: *mut AmbientorEngine, name: *const c_char) -> i32 {
+    if engine.is_null() || name.is_null() { return -1; }
+    let e = unsafe { &mut *engine };
+    let Ok(name) = unsafe { CStr::from_ptr(name) }.to_str() else { return -1; };
+    e.engine().subscribe_tap(name).map_or(-1, |slot| slot as i32)
+}
+
+/// Free a tap slot from `ambientor_tap_subscribe`.
+#[no_mangle]
+pub extern "C" fn ambientor_tap_unsubscribe(engine: *mut AmbientorEngine, slot: i32) {
+    if engine.is_null() || slot < 0 { return; }
+    let e = unsafe { &mut *engine };
+    e.engine().unsubscribe_tap(slot as usize);
+}
+
+/// Move up to `n` values tap `slot` recorded since the last read into `out`,
+/// oldest first (one per control block, see `ambientor_tap_rate`). Returns
+/// how many were written.
+#[no_mangle]
+pub extern "C" fn ambientor_tap_read(engine: *mut AmbientorEngine, slot: i32, out: *mut f32, n: u32) -> u32 {
+    if engine.is_null() || out.is_null() || slot < 0 || n == 0 { return 0; }
+    let e = unsafe { &mut *engine };
+    let out = unsafe { std::slice::from_raw_parts_mut(out, n as usize) };
+    e.engine().read_tap(slot as usize, out) as u32
+}
+
+/// Values per second each subscribed tap records (sample rate / 32).
+#[no_mangle]
+pub extern "C" fn ambientor_tap_rate(engine: *mut AmbientorEngine) -> f32 {
+    if engine.is_null() { return 0.0; }
+    let e = unsafe { &mut *engine };
+    e.engine().tap_rate()
+}
+
+/// Let sustained clipping slowly lower the scene's output gain, restoring it
+/// once the clipping stops (off by default).
+#[no_mangle]
+pub extern "C" fn ambientor_set_auto_trim(engine: *mut AmbientorEngine, enabled: bool) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    e.engine().set_auto_trim(enabled);
+}
+
+/// Current auto-trim in dB (0 = none, down to -12).
+#[no_mangle]
+pub extern "C" fn ambientor_get_auto_trim_db(engine: *mut AmbientorEngine) -> f32 {
+    if engine.is_null() { return 0.0; }
+    let e = unsafe { &mut *engine };
+    e.engine().clip_guard().trim_db()
+}
+
+/// Level every built-in scene to the same loudness (off by default), so
+/// switching e.g. from slow-drone to thunderstorm doesn't jump in level.
+/// Uses per-scene calibrations; scene files are left as they are.
+#[no_mangle]
+pub extern "C" fn ambientor_set_auto_gain(engine: *mut AmbientorEngine, enabled: bool) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    e.engine().set_auto_gain(enabled);
+}
+
+/// Loudness auto-gain aims for, RMS dBFS (-48..0, default -24).
+#[no_mangle]
+pub extern "C" fn ambientor_set_auto_gain_target_db(engine: *mut AmbientorEngine, db: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    let db = finite_or("ambientor_set_auto_gain_target_db", db, -24.0);
+    e.engine().set_auto_gain_target_db(db);
+}
+
+/// Destroy an engine previously returned by `ambientor_create` or `ambientor_create_buffered`.
 #[no_mangle]
 pub extern "C" fn ambientor_destroy(engine: *mut AmbientorEngine) {
     if !engine.is_null() {
@@ -66,13 +620,71 @@ pub extern "C" fn ambientor_reset(engine: *mut AmbientorEngine, sample_rate: f32
     if engine.is_null() { return; }
     let e = unsafe { &mut *engine };
     e.sr = sample_rate.max(1.0);
-    e.inner.scene_mut().reset(e.sr);
+    let sr = e.sr;
+    let mut eng = e.engine();
+    eng.set_sample_rate(sr);
+    eng.scene_mut().reset(sr);
+    eng.clear_waveform();
+    drop(eng);
+    e.spatial.set_sample_rate(sr);
+    e.ir_limiter.set_sample_rate(sr);
+    e.ir_limiter.reset();
+    if let Some(c) = e.ir.as_deref_mut() { c.set_sample_rate(sr); }
+}
+
+/// Set the sample rate without wiping scene evolution when nothing changed.
+///
+/// Unlike `ambientor_reset`, confirming the current rate is a no-op and a real
+/// change rescales coefficients while keeping phases, envelopes, and reverb tails.
+/// Returns `true` only if the scene had to fall back to a full reset.
+#[no_mangle]
+pub extern "C" fn ambientor_set_sample_rate(engine: *mut AmbientorEngine, sample_rate: f32) -> bool {
+    if engine.is_null() { return false; }
+    let e = unsafe { &mut *engine };
+    e.sr = sample_rate.max(1.0);
+    e.spatial.set_sample_rate(e.sr);
+    e.ir_limiter.set_sample_rate(e.sr);
+    let sr = e.sr;
+    if let Some(c) = e.ir.as_deref_mut() { c.set_sample_rate(sr); }
+    e.engine().set_sample_rate(sr)
+}
+
+/// Return the engine's current sample rate (0.0 on a null handle).
+#[no_mangle]
+pub extern "C" fn ambientor_get_sample_rate(engine: *const AmbientorEngine) -> f32 {
+    if engine.is_null() { return 0.0; }
+    let e = unsafe { &*engine };
+    e.sr
+}
+
+// --- Scenes ----------------------------------------------------------------------
+
+/// Switch to the scene named `name` (e.g. "slow-drone", "thunderstorm"), with a fresh
+/// soft start. The current tuning, intensity, and time warp carry over. Returns `false` for a null or
+/// unknown name (the current scene keeps playing).
+#[no_mangle]
+pub extern "C" fn ambientor_set_scene(engine: *mut AmbientorEngine, name: *const c_char) -> bool {
+    if engine.is_null() || name.is_null() { return false; }
+    let e = unsafe { &mut *engine };
+    let Ok(name) = unsafe { CStr::from_ptr(name) }.to_str() else { return false; };
+    let Some(mut scene) = Scene::by_name(name, e.sr) else { return false; };
+    let mut eng = e.engine();
+    let cur = eng.scene_mut();
+    scene.set_tuning(*cur.tuning());
+    scene.set_intensity(cur.intensity());
+    scene.set_time_warp(cur.time_warp());
+    let old = eng.swap_scene(scene);
+    drop(eng); // free the old scene outside the render-ahead lock
+    drop(old);
+    true
 }
 
 // --- Rendering -------------------------------------------------------------------
 
 /// Render `frames` of audio into an interleaved f32 buffer with `channels` channels.
-/// The internal generator is mono; the sample is duplicated to all channels.
+/// Channels are filled through the channel map (see `ambientor_set_channel_map`);
+/// by default stereo pairs repeat across the device, so mono scenes are duplicated.
+/// Without a map at most 32 channels can be addressed; more is an error.
 ///
 /// Returns the number of frames rendered (0 on error).
 #[no_mangle]
@@ -86,69 +698,1084 @@ pub extern "C" fn ambientor_render_interleaved_f32(
         return 0;
     }
     let e = unsafe { &mut *engine };
+    e.begin_block(frames);
+    let out = unsafe { std::slice::from_raw_parts_mut(out_interleaved, (frames as usize) * (channels as usize)) };
+
+    let ch = channels as usize;
+    let Some(map) = e.map.or_else(|| ChannelMap::stereo_pairs(ch)) else { return 0 };
+
+    for frame in out.chunks_exact_mut(ch) {
+        let (l, r) = e.next_frame();
+        map.write_frame(frame, l * e.gain, r * e.gain);
+    }
+    e.notify_silence();
+    frames
+}
+
+/// Render `frames` of audio into an interleaved **f64** buffer with `channels` channels.
+/// Same behavior as `ambientor_render_interleaved_f32`; samples are widened as they
+/// are written, so f64 hosts need no extra conversion pass.
+///
+/// Returns the number of frames rendered (0 on error).
+#[no_mangle]
+pub extern "C" fn ambientor_render_interleaved_f64(
+    engine: *mut AmbientorEngine,
+    out_interleaved: *mut f64,
+    frames: u32,
+    channels: u32,
+) -> u32 {
+    if engine.is_null() || out_interleaved.is_null() || frames == 0 || channels == 0 {
+        return 0;
+    }
+    let e = unsafe { &mut *engine };
+    e.begin_block(frames);
     let out = unsafe { std::slice::from_raw_parts_mut(out_interleaved, (frames as usize) * (channels as usize)) };
 
-    let sr = e.sr;
     let ch = channels as usize;
+    let Some(map) = e.map.or_else(|| ChannelMap::stereo_pairs(ch)) else { return 0 };
 
-    // Generate samples
-    let mut idx = 0usize;
-    for _ in 0..(frames as usize) {
-        let s = e.inner.next(sr) * e.gain;
-        for _c in 0..ch {
-            out[idx] = s;
-            idx += 1;
+    for frame in out.chunks_exact_mut(ch) {
+        let (l, r) = e.next_frame();
+        for (c, y) in frame.iter_mut().enumerate() {
+            *y = f64::from(map.route(c, l * e.gain, r * e.gain));
         }
     }
+    e.notify_silence();
     frames
 }
 
-// --- Scene parameter helpers ------------------------------------------------------
+/// Render `frames` of audio into an interleaved **16-bit PCM** buffer with `channels`
+/// channels, for export to 16-bit files. Same routing as the f32 call; samples are
+/// clamped and quantized with the dither set by `ambientor_set_dither` (TPDF by default).
+///
+/// Returns the number of frames rendered (0 on error).
+#[no_mangle]
+pub extern "C" fn ambientor_render_interleaved_i16(
+    engine: *mut AmbientorEngine,
+    out_interleaved: *mut i16,
+    frames: u32,
+    channels: u32,
+) -> u32 {
+    if engine.is_null() || out_interleaved.is_null() || frames == 0 || channels == 0 {
+        return 0;
+    }
+    let e = unsafe { &mut *engine };
+    e.begin_block(frames);
+    let out = unsafe { std::slice::from_raw_parts_mut(out_interleaved, (frames as usize) * (channels as usize)) };
 
-/// Set overall output gain (0..1 suggested). Values are clamped to [0, +inf).
+    let ch = channels as usize;
+    let Some(map) = e.map.or_else(|| ChannelMap::stereo_pairs(ch)) else { return 0 };
+    if e.dither.channels() != ch { e.dither.set_channels(ch); }
+
+    for frame in out.chunks_exact_mut(ch) {
+        let (l, r) = e.next_frame();
+        for (c, y) in frame.iter_mut().enumerate() {
+            *y = e.dither.quantize(c, map.route(c, l * e.gain, r * e.gain));
+        }
+    }
+    e.notify_silence();
+    frames
+}
+
+/// Render a seamlessly loopable clip of `loop_frames` frames into an interleaved
+/// 16-bit buffer (`loop_frames * channels` samples): `loop_frames + fade_frames`
+/// are rendered and the extra tail is crossfaded into the start, so the clip can
+/// repeat without a seam. Dithered like `ambientor_render_interleaved_i16`.
+///
+/// Offline use only: allocates and renders the whole clip in one call.
+/// Returns the number of frames written (0 on error).
 #[no_mangle]
-pub extern "C" fn ambientor_set_gain(engine: *mut AmbientorEngine, gain: f32) {
+pub extern "C" fn ambientor_render_loop_i16(
+    engine: *mut AmbientorEngine,
+    out_interleaved: *mut i16,
+    loop_frames: u32,
+    fade_frames: u32,
+    channels: u32,
+) -> u32 {
+    if engine.is_null() || out_interleaved.is_null() || loop_frames == 0 || channels == 0 {
+        return 0;
+    }
+    let e = unsafe { &mut *engine };
+    let out = unsafe { std::slice::from_raw_parts_mut(out_interleaved, (loop_frames as usize) * (channels as usize)) };
+
+    let ch = channels as usize;
+    let Some(map) = e.map.or_else(|| ChannelMap::stereo_pairs(ch)) else { return 0 };
+    if e.dither.channels() != ch { e.dither.set_channels(ch); }
+
+    let mut dither = e.dither.clone();
+    let mut written = 0;
+    let res = export::render_loop(
+        ch,
+        loop_frames as usize,
+        fade_frames as usize,
+        |block| {
+            for frame in block.chunks_exact_mut(ch) {
+                let (l, r) = e.next_frame();
+                map.write_frame(frame, l * e.gain, r * e.gain);
+            }
+        },
+        |block| {
+            dither.quantize_interleaved(block, &mut out[written..written + block.len()]);
+            written += block.len();
+            Ok::<(), ()>(())
+        },
+    );
+    e.dither = dither;
+    e.notify_silence();
+    if res.is_ok() { loop_frames } else { 0 }
+}
+
+/// Choose how `ambientor_render_interleaved_i16` quantizes: `AMBIENTOR_DITHER_OFF`,
+/// `_TPDF` (default) or `_SHAPED` (TPDF plus noise shaping). Unknown codes are ignored.
+#[no_mangle]
+pub extern "C" fn ambientor_set_dither(engine: *mut AmbientorEngine, mode: u8) {
     if engine.is_null() { return; }
     let e = unsafe { &mut *engine };
-    e.gain = if gain.is_finite() { gain.max(0.0) } else { 1.0 };
+    let mode = match mode {
+        AMBIENTOR_DITHER_OFF => DitherMode::Off,
+        AMBIENTOR_DITHER_TPDF => DitherMode::Tpdf,
+        AMBIENTOR_DITHER_SHAPED => DitherMode::Shaped,
+        _ => return,
+    };
+    e.dither.set_mode(mode);
 }
 
-/// Set the base low-pass cutoff (Hz) for the scene.
+/// Render `frames` frames offline straight into the file at `path` (UTF-8), in
+/// `format` (`AMBIENTOR_FORMAT_*`). WAV is 16-bit with the dither chosen by
+/// `ambientor_set_dither`; `quality` (0..1) applies to lossy formats.
+///
+/// Blocks the caller for the whole render; never call it from an audio callback.
+/// Returns `false` on bad arguments, I/O errors, or a format this build lacks.
 #[no_mangle]
-pub extern "C" fn ambientor_scene_set_cut_base(engine: *mut AmbientorEngine, hz: f32) {
+pub extern "C" fn ambientor_render_to_file(
+    engine: *mut AmbientorEngine,
+    path: *const c_char,
+    frames: u64,
+    channels: u32,
+    format: u8,
+    quality: f32,
+) -> bool {
+    if engine.is_null() || path.is_null() || channels == 0 { return false; }
+    let e = unsafe { &mut *engine };
+    let path = unsafe { CStr::from_ptr(path) };
+    let Some(format) = export_format(format) else { return false; };
+    e.render_to_file(path, frames as usize, None, channels as usize, format, quality).is_ok()
+}
+
+/// Like `ambientor_render_to_file`, but the file loops seamlessly: `loop_frames +
+/// fade_frames` are rendered and the extra tail is crossfaded into the start.
+#[no_mangle]
+pub extern "C" fn ambientor_render_loop_to_file(
+    engine: *mut AmbientorEngine,
+    path: *const c_char,
+    loop_frames: u64,
+    fade_frames: u64,
+    channels: u32,
+    format: u8,
+    quality: f32,
+) -> bool {
+    if engine.is_null() || path.is_null() || channels == 0 { return false; }
+    let e = unsafe { &mut *engine };
+    let path = unsafe { CStr::from_ptr(path) };
+    let Some(format) = export_format(format) else { return false; };
+    e.render_to_file(path, loop_frames as usize, Some(fade_frames as usize), channels as usize, format, quality)
+        .is_ok()
+}
+
+/// Make WAV files from `ambientor_render_to_file` / `ambientor_render_loop_to_file`
+/// Broadcast Wave files: a `bext` chunk with `description` (UTF-8, up to 256 bytes),
+/// the scene name, and the origination date/time (UTC). NULL or "" turns it off.
+#[no_mangle]
+pub extern "C" fn ambientor_set_export_description(engine: *mut AmbientorEngine, description: *const c_char) {
     if engine.is_null() { return; }
     let e = unsafe { &mut *engine };
-    e.inner.scene_mut().set_cut_base(hz);
+    e.export_description = if description.is_null() {
+        None
+    } else {
+        let d = unsafe { CStr::from_ptr(description) }.to_string_lossy();
+        (!d.is_empty()).then(|| d.into_owned())
+    };
 }
 
-/// Set the modulation span (Hz) around the base cutoff.
+/// `true` if this build can write `format` (`AMBIENTOR_FORMAT_*`); Ogg Vorbis
+/// needs the engine's `vorbis` feature.
 #[no_mangle]
-pub extern "C" fn ambientor_scene_set_cut_span(engine: *mut AmbientorEngine, hz: f32) {
+pub extern "C" fn ambientor_export_format_available(format: u8) -> bool {
+    export_format(format).is_some_and(ExportFormat::is_available)
+}
+
+/// Render `frames` of stereo audio into two planar f32 buffers (`left`, `right`).
+/// Stereo scenes render true stereo; mono scenes are duplicated to both sides.
+///
+/// Returns the number of frames rendered (0 on error).
+#[no_mangle]
+pub extern "C" fn ambientor_render_stereo_f32(
+    engine: *mut AmbientorEngine,
+    left: *mut f32,
+    right: *mut f32,
+    frames: u32,
+) -> u32 {
+    if engine.is_null() || left.is_null() || right.is_null() || frames == 0 {
+        return 0;
+    }
+    let e = unsafe { &mut *engine };
+    e.begin_block(frames);
+    let left = unsafe { std::slice::from_raw_parts_mut(left, frames as usize) };
+    let right = unsafe { std::slice::from_raw_parts_mut(right, frames as usize) };
+
+    for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+        let (yl, yr) = e.next_frame();
+        *l = yl * e.gain;
+        *r = yr * e.gain;
+    }
+    e.notify_silence();
+    frames
+}
+
+/// Set the soft-start length (milliseconds) used on creation and after scene
+/// swaps. A fade already in progress continues at the new speed; `0` disables it.
+#[no_mangle]
+pub extern "C" fn ambientor_set_fadein_ms(engine: *mut AmbientorEngine, ms: f32) {
     if engine.is_null() { return; }
     let e = unsafe { &mut *engine };
-    e.inner.scene_mut().set_cut_span(hz);
+    let ms = finite_or("ambientor_set_fadein_ms", ms, 0.0).max(0.0);
+    e.engine().set_fade_in(ms * 0.001);
 }
 
-/// Set drive (saturation intensity), clamped internally to [0.1, 5.0].
+/// Set the scene intensity (0..1, default 0.5): one control that each scene maps
+/// onto its density, brightness, and level. Changes glide over a few hundred ms.
 #[no_mangle]
-pub extern "C" fn ambientor_scene_set_drive(engine: *mut AmbientorEngine, drive: f32) {
+pub extern "C" fn ambientor_set_intensity(engine: *mut AmbientorEngine, intensity: f32) {
     if engine.is_null() { return; }
     let e = unsafe { &mut *engine };
-    e.inner.scene_mut().set_drive(drive);
+    if intensity.is_finite() { e.engine().scene_mut().set_intensity(intensity); }
 }
 
-/// Set scene output gain (pre-FFI gain smoothing).
+/// Set the host tempo in BPM (20..400, default 120), for scenes that follow it
+/// (scene-file expressions read it as `bpm`).
 #[no_mangle]
-pub extern "C" fn ambientor_scene_set_out_gain(engine: *mut AmbientorEngine, gain: f32) {
+pub extern "C" fn ambientor_set_tempo(engine: *mut AmbientorEngine, bpm: f32) {
     if engine.is_null() { return; }
     let e = unsafe { &mut *engine };
-    e.inner.scene_mut().set_gain(gain);
+    let bpm = finite_or("ambientor_set_tempo", bpm, DEFAULT_TEMPO_BPM);
+    e.engine().set_tempo(bpm);
 }
 
-/// Set detune depth (in cents) for slow drift + LFO.
+/// Report the host transport state (default playing) to scenes that follow it.
 #[no_mangle]
-pub extern "C" fn ambientor_scene_set_detune_cents(engine: *mut AmbientorEngine, cents: f32) {
+pub extern "C" fn ambientor_set_transport(engine: *mut AmbientorEngine, playing: bool) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    e.engine().set_transport(if playing { Transport::Playing } else { Transport::Stopped });
+}
+
+/// `true` once the output has stayed below the silence threshold for the hold
+/// time (defaults: -90 dBFS for 5 s). Battery-powered hosts can suspend the
+/// stream while this holds; rendering again resumes exactly where it left off.
+#[no_mangle]
+pub extern "C" fn ambientor_is_silent(engine: *const AmbientorEngine) -> bool {
+    if engine.is_null() { return false; }
+    let e = unsafe { &*engine };
+    e.is_silent()
+}
+
+/// Configure the silence detector: peak threshold in dBFS (-140..0) and how
+/// long (seconds) the output must stay below it. Non-finite values are ignored.
+#[no_mangle]
+pub extern "C" fn ambientor_set_silence_params(engine: *mut AmbientorEngine, threshold_db: f32, hold_s: f32) {
     if engine.is_null() { return; }
     let e = unsafe { &mut *engine };
-    e.inner.scene_mut().set_detune_cents(cents);
+    let sr = e.sr;
+    let mut eng = e.engine();
+    let det = eng.silence_mut();
+    if threshold_db.is_finite() { det.set_threshold_db(threshold_db); }
+    if hold_s.is_finite() { det.set_hold_s(hold_s, sr); }
+}
+
+/// Register `callback` to be told when the output enters or leaves silence.
+/// It runs on the render thread at the end of the render call that saw the
+/// change, with `user_data` passed through. Pass a null callback to remove it.
+#[no_mangle]
+pub extern "C" fn ambientor_set_silence_callback(
+    engine: *mut AmbientorEngine,
+    callback: AmbientorSilenceCallback,
+    user_data: *mut c_void,
+) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    e.silence_cb = callback;
+    e.silence_user = user_data;
+    e.was_silent = e.is_silent();
+}
+
+/// Render `frames` of multichannel audio for a speaker layout chosen by `channels`:
+/// 4 = quad (FL FR RL RR), 6 = 5.1 (FL FR C LFE SL SR), 8 = 7.1 (FL FR C LFE BL BR SL SR).
+///
+/// Each speaker gets a decorrelated feed; the scene's left/right sides orbit the
+/// room slowly (see `ambientor_set_spatial_params`). The channel map is not used.
+///
+/// Returns the number of frames rendered (0 on error or unsupported `channels`).
+#[no_mangle]
+pub extern "C" fn ambientor_render_spatial_f32(
+    engine: *mut AmbientorEngine,
+    out_interleaved: *mut f32,
+    frames: u32,
+    channels: u32,
+) -> u32 {
+    if engine.is_null() || out_interleaved.is_null() || frames == 0 {
+        return 0;
+    }
+    let Some(layout) = SpeakerLayout::from_channels(channels as usize) else { return 0; };
+    let e = unsafe { &mut *engine };
+    e.begin_block(frames);
+    let out = unsafe { std::slice::from_raw_parts_mut(out_interleaved, (frames as usize) * (channels as usize)) };
+
+    if e.spatial.layout() != layout {
+        e.spatial.set_layout(layout);
+    }
+    let mut tmp = [0.0f32; MAX_SPATIAL_CHANNELS];
+    for frame in out.chunks_exact_mut(channels as usize) {
+        let (l, r) = e.next_frame();
+        e.spatial.process(l * e.gain, r * e.gain, &mut tmp);
+        frame.copy_from_slice(&tmp[..frame.len()]);
+    }
+    e.notify_silence();
+    frames
+}
+
+/// Configure the multichannel spatializer:
+/// - `orbit_hz`: orbit speed of the virtual sources in turns/second (e.g. 0.01)
+/// - `spread`  : L/R source separation, 0 (point) .. 1 (±90°)
+/// - `diffuse` : share of the decorrelated bed sent to every speaker, 0..1
+#[no_mangle]
+pub extern "C" fn ambientor_set_spatial_params(
+    engine: *mut AmbientorEngine,
+    orbit_hz: f32,
+    spread: f32,
+    diffuse: f32,
+) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    e.spatial.set_orbit_hz(orbit_hz);
+    e.spatial.set_spread(spread);
+    e.spatial.set_diffuse(diffuse);
+}
+
+/// Route the scene output to device channels for the interleaved render calls.
+///
+/// `sources[i]` is one of `AMBIENTOR_CH_*` for device channel `i`; channels past
+/// `count` stay silent. Pass `count == 0` (or a null `sources`) to restore the
+/// default L/R pairs. Returns `false` on an unknown code or `count > 32`.
+#[no_mangle]
+pub extern "C" fn ambientor_set_channel_map(
+    engine: *mut AmbientorEngine,
+    sources: *const u8,
+    count: u32,
+) -> bool {
+    if engine.is_null() { return false; }
+    let e = unsafe { &mut *engine };
+    if sources.is_null() || count == 0 {
+        e.map = None;
+        return true;
+    }
+    if count as usize > MAX_CHANNELS { return false; }
+    let codes = unsafe { std::slice::from_raw_parts(sources, count as usize) };
+
+    let mut map = ChannelMap::silent(codes.len());
+    for (ch, &code) in codes.iter().enumerate() {
+        let src = match code {
+            AMBIENTOR_CH_SILENT => ChannelSource::Silent,
+            AMBIENTOR_CH_LEFT => ChannelSource::Left,
+            AMBIENTOR_CH_RIGHT => ChannelSource::Right,
+            AMBIENTOR_CH_MONO => ChannelSource::Mono,
+            _ => return false,
+        };
+        map.set(ch, src);
+    }
+    e.map = Some(map);
+    true
+}
+
+// --- Tuning ----------------------------------------------------------------------
+
+/// Set the reference pitch (A4 in 12-TET, degree 0 of a ratio table), e.g. 432 Hz.
+/// Non-positive or non-finite values are ignored.
+#[no_mangle]
+pub extern "C" fn ambientor_set_tuning_ref(engine: *mut AmbientorEngine, hz: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    let mut t = *e.engine().scene_mut().tuning();
+    t.set_ref_hz(hz);
+    e.engine().scene_mut().set_tuning(t);
+}
+
+/// Use a ratio table (one ratio per scale degree, repeating at `period`, e.g. 2.0).
+/// Null `ratios` or `count == 0` restores 12-TET. Returns `false` on an invalid table.
+#[no_mangle]
+pub extern "C" fn ambientor_set_tuning_ratios(
+    engine: *mut AmbientorEngine,
+    ratios: *const f32,
+    count: u32,
+    period: f32,
+) -> bool {
+    if engine.is_null() { return false; }
+    let e = unsafe { &mut *engine };
+    let mut t = *e.engine().scene_mut().tuning();
+    if ratios.is_null() || count == 0 {
+        t.set_equal();
+    } else {
+        let table = unsafe { std::slice::from_raw_parts(ratios, count as usize) };
+        if !t.set_ratios(table, period) { return false; }
+    }
+    e.engine().scene_mut().set_tuning(t);
+    true
+}
+
+// --- Scene parameter helpers ------------------------------------------------------
+
+/// Set overall output gain (0..1 suggested). Values are clamped to [0, +inf).
+#[no_mangle]
+pub extern "C" fn ambientor_set_gain(engine: *mut AmbientorEngine, gain: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    e.gain = finite_or("ambientor_set_gain", gain, 1.0).max(0.0);
+}
+
+/// Set master stereo width in percent: 0 = mono, 100 = unchanged, 200 = extra wide.
+#[no_mangle]
+pub extern "C" fn ambientor_set_stereo_width(engine: *mut AmbientorEngine, percent: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    let pct = finite_or("ambientor_set_stereo_width", percent, 100.0);
+    e.engine().master_mut().set_width_percent(pct);
+}
+
+/// Set the master output EQ band gains in dB (each -12..+12; all 0 = flat).
+#[no_mangle]
+pub extern "C" fn ambientor_set_eq(engine: *mut AmbientorEngine, low_db: f32, mid_db: f32, high_db: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    let db = |x: f32| finite_or("ambientor_set_eq", x, 0.0);
+    e.engine().master_mut().set_eq_db(db(low_db), db(mid_db), db(high_db));
+}
+
+/// Set the center frequency (Hz) of the master EQ's mid band.
+#[no_mangle]
+pub extern "C" fn ambientor_set_eq_mid_hz(engine: *mut AmbientorEngine, hz: f32) {
+    if engine.is_null() || !hz.is_finite() { return; }
+    let e = unsafe { &mut *engine };
+    e.engine().master_mut().set_eq_mid_hz(hz);
+}
+
+/// Set the master tilt "tone": -1 = dark .. 0 = flat .. +1 = bright.
+#[no_mangle]
+pub extern "C" fn ambientor_set_tone(engine: *mut AmbientorEngine, tone: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    let t = finite_or("ambientor_set_tone", tone, 0.0);
+    e.engine().master_mut().set_tone(t);
+}
+
+/// Place the soundscape `meters` from the listener: inverse-distance gain
+/// between the rolloff's reference and max distance (see
+/// `ambientor_set_distance_rolloff`). Glides over ~50 ms.
+#[no_mangle]
+pub extern "C" fn ambientor_set_listener_distance(engine: *mut AmbientorEngine, meters: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    let m = finite_or("ambientor_set_listener_distance", meters, 0.0);
+    e.engine().master_mut().set_listener_distance(m);
+}
+
+/// Reference distance (unity gain up to here, default 1 m) and max distance
+/// (no further attenuation past it, default 100 m).
+#[no_mangle]
+pub extern "C" fn ambientor_set_distance_rolloff(engine: *mut AmbientorEngine, ref_m: f32, max_m: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    let ref_m = finite_or("ambientor_set_distance_rolloff", ref_m, 1.0);
+    let max_m = finite_or("ambientor_set_distance_rolloff", max_m, 100.0);
+    e.engine().master_mut().set_distance_rolloff(ref_m, max_m);
+}
+
+/// Occlusion 0 (clear line of sight) .. 1 (behind walls): closes a low-pass
+/// down to 400 Hz and lowers the level by up to 6 dB. Glides over ~50 ms.
+#[no_mangle]
+pub extern "C" fn ambientor_set_occlusion(engine: *mut AmbientorEngine, occlusion: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    let x = finite_or("ambientor_set_occlusion", occlusion, 0.0);
+    e.engine().master_mut().set_occlusion(x);
+}
+
+/// Master high-pass (12 dB/oct, on by default at 25 Hz) guarding small
+/// speakers against DC and subsonic build-up. `hz` is clamped to 10..80;
+/// disable it for full-range systems with subwoofers.
+#[no_mangle]
+pub extern "C" fn ambientor_set_master_highpass(engine: *mut AmbientorEngine, enabled: bool, hz: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    let hz = finite_or("ambientor_set_master_highpass", hz, MASTER_HP_HZ);
+    e.engine().master_mut().set_highpass(enabled, hz);
+}
+
+/// Sub bed for installations with subwoofers: a mono 25–45 Hz sine swell at
+/// peak `level` (0..1, 0 = off) whose level rides a `swell_hz` LFO. Only
+/// audible while the master high-pass is disabled.
+#[no_mangle]
+pub extern "C" fn ambientor_set_sub_bed(engine: *mut AmbientorEngine, level: f32, hz: f32, swell_hz: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    let level = finite_or("ambientor_set_sub_bed", level, 0.0);
+    let hz = finite_or("ambientor_set_sub_bed", hz, SUB_BED_DEFAULT_HZ);
+    let swell_hz = finite_or("ambientor_set_sub_bed", swell_hz, 0.05);
+    e.engine().master_mut().set_sub_bed(level, hz, swell_hz);
+}
+
+/// Fold the master below `hz` (40..300, typically 120) to mono through an
+/// LR4 crossover; off by default.
+#[no_mangle]
+pub extern "C" fn ambientor_set_mono_bass(engine: *mut AmbientorEngine, enabled: bool, hz: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    let hz = finite_or("ambientor_set_mono_bass", hz, MONO_BASS_HZ);
+    e.engine().master_mut().set_mono_bass(enabled, hz);
+}
+
+/// Multiband texture: saturation `amount` (0..1) and `level_db` (±12) of
+/// `band` 0 (low), 1 (mid) or 2 (high); other bands are ignored.
+#[no_mangle]
+pub extern "C" fn ambientor_set_texture_band(engine: *mut AmbientorEngine, band: u32, amount: f32, level_db: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    let amount = finite_or("ambientor_set_texture_band", amount, 0.0);
+    let level_db = finite_or("ambientor_set_texture_band", level_db, 0.0);
+    e.engine().master_mut().set_texture_band(band as usize, amount, level_db);
+}
+
+/// Multiband texture crossover frequencies in Hz (default 200 and 3000).
+#[no_mangle]
+pub extern "C" fn ambientor_set_texture_crossovers(engine: *mut AmbientorEngine, lo_hz: f32, hi_hz: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    let lo_hz = finite_or("ambientor_set_texture_crossovers", lo_hz, TEXTURE_XOVER_HZ.0);
+    let hi_hz = finite_or("ambientor_set_texture_crossovers", hi_hz, TEXTURE_XOVER_HZ.1);
+    e.engine().master_mut().set_texture_crossovers(lo_hz, hi_hz);
+}
+
+/// Run the output through an impulse-response reverb loaded from the WAV
+/// file at `path` (mono or stereo, any sample rate; cut at 12 s), blended at
+/// `mix` (0 dry .. 1 wet), then through a peak limiter (-1 dBFS ceiling), as the
+/// reverb can raise peaks. The wet signal lags by 512 samples. Loading reads
+/// the file and allocates, so call it off the audio thread or between renders.
+/// A null `path` removes the reverb. Returns `false` (and keeps the current
+/// reverb) if the file can't be read.
+#[no_mangle]
+pub extern "C" fn ambientor_load_ir(engine: *mut AmbientorEngine, path: *const c_char, mix: f32) -> bool {
+    if engine.is_null() { return false; }
+    let e = unsafe { &mut *engine };
+    if path.is_null() {
+        e.ir = None;
+        return true;
+    }
+    let Ok(path) = unsafe { CStr::from_ptr(path) }.to_str() else { return false; };
+    match ConvolutionReverb::load(path, e.sr) {
+        Ok(mut c) => {
+            c.set_mix(finite_or("ambientor_load_ir", mix, 0.3));
+            e.ir = Some(Box::new(c));
+            e.ir_limiter.reset();
+            true
+        }
+        Err(err) => {
+            log::warn(format_args!("{path}: {err}"));
+            false
+        }
+    }
+}
+
+/// Wet/dry balance of the impulse-response reverb (0..1); no-op without one.
+#[no_mangle]
+pub extern "C" fn ambientor_set_ir_mix(engine: *mut AmbientorEngine, mix: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    let mix = finite_or("ambientor_set_ir_mix", mix, 0.3);
+    if let Some(c) = e.ir.as_deref_mut() { c.set_mix(mix); }
+}
+
+/// Move parameter `id` (`AMBIENTOR_PARAM_*`) to `target` over `ms`
+/// milliseconds inside the engine, updated every 32 samples, so hosts that
+/// only send changes at frame rate don't get zipper noise. Levels and
+/// frequencies ramp linearly in dB/octaves, everything else linearly; `ms <= 0`
+/// sets the value at once. A new ramp on the same parameter starts from where
+/// the old one got to. Returns `false` for an unknown id, a parameter the
+/// current scene doesn't have, or too many ramps (16) at once.
+#[no_mangle]
+pub extern "C" fn ambientor_set_param_ramped(engine: *mut AmbientorEngine, id: u32, target: f32, ms: f32) -> bool {
+    if engine.is_null() || !target.is_finite() { return false; }
+    let e = unsafe { &mut *engine };
+    let Some(id) = ParamId::from_u32(id) else { return false; };
+    let ms = finite_or("ambientor_set_param_ramped", ms, 0.0);
+    e.engine().ramp_param(id, target, ms * 0.001)
+}
+
+/// Current value of parameter `id` into `*value` (mid-ramp values included).
+/// Returns `false` (and leaves `*value` alone) for an unknown id or a
+/// parameter the current scene doesn't have.
+#[no_mangle]
+pub extern "C" fn ambientor_get_param(engine: *mut AmbientorEngine, id: u32, value: *mut f32) -> bool {
+    if engine.is_null() || value.is_null() { return false; }
+    let e = unsafe { &mut *engine };
+    let Some(id) = ParamId::from_u32(id) else { return false; };
+    let Some(v) = e.engine().param(id) else { return false; };
+    unsafe { *value = v; }
+    true
+}
+
+/// Return every parameter to its default for the current scene (what a fresh
+/// engine with this scene starts with): scene parameters, intensity, width,
+/// tone, EQ, returns, distance and occlusion. Running ramps stop. Phases and
+/// reverb/delay tails are kept, so playback carries on and the sound glides
+/// back.
+#[no_mangle]
+pub extern "C" fn ambientor_reset_params(engine: *mut AmbientorEngine) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    e.engine().reset_params();
+}
+
+/// Parse a preset string for `ambientor_morph`; null reads as empty.
+fn preset_arg(spec: *const c_char) -> Option<ParamSet> {
+    if spec.is_null() { return Some(ParamSet::default()); }
+    ParamSet::parse(unsafe { CStr::from_ptr(spec) }.to_str().ok()?)
+}
+
+/// Morph from preset `from` to preset `to` over `minutes`. Presets are
+/// `name=value` lists such as "intensity=0.7,tone=0.3,width=120" (names as in
+/// `AMBIENTOR_PARAM_*`, lower case). Parameters in `from` jump there first;
+/// null or "" starts from the current values. Returns `false` (nothing
+/// changed) if either preset doesn't parse.
+#[no_mangle]
+pub extern "C" fn ambientor_morph(
+    engine: *mut AmbientorEngine,
+    from: *const c_char,
+    to: *const c_char,
+    minutes: f32,
+) -> bool {
+    if engine.is_null() { return false; }
+    let e = unsafe { &mut *engine };
+    let (Some(from), Some(to)) = (preset_arg(from), preset_arg(to)) else { return false; };
+    let minutes = finite_or("ambientor_morph", minutes, 0.0);
+    e.engine().morph(&from, &to, minutes * 60.0);
+    true
+}
+
+/// Keep the current parameters as snapshot A (which becomes the active one).
+#[no_mangle]
+pub extern "C" fn ambientor_store_a(engine: *mut AmbientorEngine) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    e.engine().store_a();
+}
+
+/// Keep the current parameters as snapshot B (which becomes the active one).
+#[no_mangle]
+pub extern "C" fn ambientor_store_b(engine: *mut AmbientorEngine) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    e.engine().store_b();
+}
+
+/// Crossfade to the other A/B snapshot over `crossfade_ms` (0 = switch at
+/// once). Returns `false` if that snapshot was never stored.
+#[no_mangle]
+pub extern "C" fn ambientor_toggle_ab(engine: *mut AmbientorEngine, crossfade_ms: f32) -> bool {
+    if engine.is_null() { return false; }
+    let e = unsafe { &mut *engine };
+    let ms = finite_or("ambientor_toggle_ab", crossfade_ms, 0.0);
+    e.engine().toggle_ab(ms * 0.001)
+}
+
+/// Active A/B snapshot: 0 = A, 1 = B (-1 for a null engine).
+#[no_mangle]
+pub extern "C" fn ambientor_ab_slot(engine: *mut AmbientorEngine) -> i32 {
+    if engine.is_null() { return -1; }
+    let e = unsafe { &mut *engine };
+    match e.engine().ab_slot() {
+        AbSlot::A => 0,
+        AbSlot::B => 1,
+    }
+}
+
+/// Set the shared reverb return level (0..2, 1 = as the scene sends it).
+#[no_mangle]
+pub extern "C" fn ambientor_set_reverb_return(engine: *mut AmbientorEngine, level: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    let g = finite_or("ambientor_set_reverb_return", level, 1.0);
+    e.engine().master_mut().set_reverb_return(g);
+}
+
+/// Switch the shared reverb off (its tail fades out, then it stops using CPU)
+/// or back on.
+#[no_mangle]
+pub extern "C" fn ambientor_set_reverb_bypass(engine: *mut AmbientorEngine, bypass: bool) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    e.engine().master_mut().set_reverb_bypass(bypass);
+}
+
+/// Set the shared delay return level (0..2, 1 = as the scene sends it).
+#[no_mangle]
+pub extern "C" fn ambientor_set_delay_return(engine: *mut AmbientorEngine, level: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    let g = finite_or("ambientor_set_delay_return", level, 1.0);
+    e.engine().master_mut().set_delay_return(g);
+}
+
+/// Set the base low-pass cutoff (Hz) for the scene.
+#[no_mangle]
+pub extern "C" fn ambientor_scene_set_cut_base(engine: *mut AmbientorEngine, hz: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    e.engine().scene_mut().set_cut_base(hz);
+}
+
+/// Set the modulation span (Hz) around the base cutoff.
+#[no_mangle]
+pub extern "C" fn ambientor_scene_set_cut_span(engine: *mut AmbientorEngine, hz: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    e.engine().scene_mut().set_cut_span(hz);
+}
+
+/// Set drive (saturation intensity), clamped internally to [0.1, 5.0].
+#[no_mangle]
+pub extern "C" fn ambientor_scene_set_drive(engine: *mut AmbientorEngine, drive: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    e.engine().scene_mut().set_drive(drive);
+}
+
+/// Set scene output gain (pre-FFI gain smoothing).
+#[no_mangle]
+pub extern "C" fn ambientor_scene_set_out_gain(engine: *mut AmbientorEngine, gain: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    e.engine().scene_mut().set_gain(gain);
+}
+
+/// Set detune depth (in cents) for slow drift + LFO.
+#[no_mangle]
+pub extern "C" fn ambientor_scene_set_detune_cents(engine: *mut AmbientorEngine, cents: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    e.engine().scene_mut().set_detune_cents(cents);
+}
+
+/// Set partial stretch (0 = harmonic .. 0.15) for the additive "risset" scene.
+#[no_mangle]
+pub extern "C" fn ambientor_scene_set_inharmonicity(engine: *mut AmbientorEngine, amount: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    if amount.is_finite() { e.engine().scene_mut().set_inharmonicity(amount); }
+}
+
+/// Set the speed multiplier of slow motion (1.0 = default) for the "risset" scene.
+#[no_mangle]
+pub extern "C" fn ambientor_scene_set_motion_rate(engine: *mut AmbientorEngine, rate: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    if rate.is_finite() { e.engine().scene_mut().set_motion_rate(rate); }
+}
+
+/// Set storm intensity (0..1: rain density, rumble loudness) for the "thunderstorm" scene.
+#[no_mangle]
+pub extern "C" fn ambientor_scene_set_storm_intensity(engine: *mut AmbientorEngine, intensity: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    if intensity.is_finite() { e.engine().scene_mut().set_storm_intensity(intensity); }
+}
+
+/// Set the mean number of rumbles per minute (0..20) for the "thunderstorm" scene.
+#[no_mangle]
+pub extern "C" fn ambientor_scene_set_rumble_rate(engine: *mut AmbientorEngine, per_min: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    if per_min.is_finite() { e.engine().scene_mut().set_rumble_rate(per_min); }
+}
+
+/// Set how far the bed layer ducks under event layers, in dB (0 = off, up to 24).
+/// Currently used by "thunderstorm" (rain under rumbles).
+#[no_mangle]
+pub extern "C" fn ambientor_scene_set_duck_depth(engine: *mut AmbientorEngine, db: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    if db.is_finite() { e.engine().scene_mut().set_duck_depth_db(db); }
+}
+
+/// Set the mean drips per minute (0..240) for the "cave-drips" scene.
+#[no_mangle]
+pub extern "C" fn ambientor_scene_set_drip_density(engine: *mut AmbientorEngine, per_min: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    if per_min.is_finite() { e.engine().scene_mut().set_drip_density(per_min); }
+}
+
+/// Set echo/reverb wetness (0..1) for the "cave-drips" scene.
+#[no_mangle]
+pub extern "C" fn ambientor_scene_set_wetness(engine: *mut AmbientorEngine, wet: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    if wet.is_finite() { e.engine().scene_mut().set_wetness(wet); }
+}
+
+/// Set the carrier (center) frequency in Hz (40..1000) for the "binaural" scene.
+#[no_mangle]
+pub extern "C" fn ambientor_scene_set_carrier_hz(engine: *mut AmbientorEngine, hz: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    if hz.is_finite() { e.engine().scene_mut().set_carrier_hz(hz); }
+}
+
+/// Set the beat rate in Hz (0.5..40) for the "binaural" scene.
+#[no_mangle]
+pub extern "C" fn ambientor_scene_set_beat_hz(engine: *mut AmbientorEngine, hz: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    if hz.is_finite() { e.engine().scene_mut().set_beat_hz(hz); }
+}
+
+/// Set the pink-noise bed level (0..1) for the "binaural" scene.
+#[no_mangle]
+pub extern "C" fn ambientor_scene_set_noise_bed(engine: *mut AmbientorEngine, level: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    if level.is_finite() { e.engine().scene_mut().set_noise_bed(level); }
+}
+
+/// Set how fast the tape wears (0..1) for the "tape-loop" scene.
+#[no_mangle]
+pub extern "C" fn ambientor_scene_set_degrade(engine: *mut AmbientorEngine, amount: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    if amount.is_finite() { e.engine().scene_mut().set_degrade(amount); }
+}
+
+/// Set wow/flutter depth (0..1) for the "tape-loop" scene.
+#[no_mangle]
+pub extern "C" fn ambientor_scene_set_wow(engine: *mut AmbientorEngine, depth: f32) {
+    if engine.is_null() { return; }
+    let e = unsafe { &mut *engine };
+    if depth.is_finite() { e.engine().scene_mut().set_wow(depth); }
+}
+
+// --- Introspection ---------------------------------------------------------------
+
+/// Name of the current scene (e.g. "slow-drone") as a static NUL-terminated string;
+/// never free it. Returns "" for a null handle or a scene that is not built in.
+#[no_mangle]
+pub extern "C" fn ambientor_get_scene_name(engine: *mut AmbientorEngine) -> *const c_char {
+    static NAMES: OnceLock<Vec<CString>> = OnceLock::new();
+    if engine.is_null() { return c"".as_ptr(); }
+    let e = unsafe { &mut *engine };
+    let mut eng = e.engine();
+    let name = eng.scene_mut().name();
+    NAMES
+        .get_or_init(|| SCENE_NAMES.iter().filter_map(|n| CString::new(*n).ok()).collect())
+        .iter()
+        .find(|c| c.to_bytes() == name.as_bytes())
+        .map_or(c"".as_ptr(), |c| c.as_ptr())
+}
+
+/// Seconds of audio handed to the host since the engine was created (buffered
+/// engines exclude audio still waiting in the ring). 0 for a null handle.
+#[no_mangle]
+pub extern "C" fn ambientor_get_time_seconds(engine: *mut AmbientorEngine) -> f64 {
+    if engine.is_null() { return 0.0; }
+    let e = unsafe { &mut *engine };
+    e.time_s()
+}
+
+/// Write a JSON object with the engine's current settings (scene, sample rate, time,
+/// gain, intensity, tuning, master bus, silence detector, diagnostics) into `buf`.
+///
+/// Works like `snprintf`: at most `len - 1` bytes plus a NUL are written, and the
+/// return value is the full length (without NUL), so `len` too small → call again
+/// with a bigger buffer; `buf == NULL, len == 0` just queries the size.
+/// Locks a buffered engine briefly; don't call it from the audio callback.
+#[no_mangle]
+pub extern "C" fn ambientor_dump_params_json(engine: *mut AmbientorEngine, buf: *mut c_char, len: usize) -> usize {
+    if engine.is_null() { return 0; }
+    let e = unsafe { &mut *engine };
+    let json = e.params_json();
+    if !buf.is_null() && len > 0 {
+        let n = json.len().min(len - 1);
+        let out = unsafe { std::slice::from_raw_parts_mut(buf.cast::<u8>(), len) };
+        out[..n].copy_from_slice(&json.as_bytes()[..n]);
+        out[n] = 0;
+    }
+    json.len()
+}
+
+// --- Engine bank -----------------------------------------------------------------
+
+/// Several engines mixed into one output (see `ambientor_bank_create`).
+pub struct AmbientorBank {
+    bank: EngineBank<Scene>,
+}
+
+/// Create an empty bank: up to 16 engines, each with its own scene, mixed with
+/// per-engine gain and pan through a shared limiter (-1 dBFS ceiling).
+#[no_mangle]
+pub extern "C" fn ambientor_bank_create(sample_rate: f32) -> *mut AmbientorBank {
+    Box::into_raw(Box::new(AmbientorBank { bank: EngineBank::new(sample_rate) }))
+}
+
+/// Destroy a bank and every engine in it.
+#[no_mangle]
+pub extern "C" fn ambientor_bank_destroy(bank: *mut AmbientorBank) {
+    if !bank.is_null() {
+        unsafe { drop(Box::from_raw(bank)); }
+    }
+}
+
+/// Add an engine playing scene `name` at full gain, centered. Returns its id
+/// (0..15), or -1 for a null/unknown name or a full bank.
+#[no_mangle]
+pub extern "C" fn ambientor_bank_add(bank: *mut AmbientorBank, name: *const c_char) -> i32 {
+    if bank.is_null() || name.is_null() { return -1; }
+    let b = unsafe { &mut *bank };
+    let Ok(name) = unsafe { CStr::from_ptr(name) }.to_str() else { return -1; };
+    let sr = b.bank.sample_rate();
+    let Some(scene) = Scene::by_name(name, sr) else { return -1; };
+    let mut e = Engine::new(scene);
+    e.set_sample_rate(sr);
+    e.scene_mut().reset(sr);
+    b.bank.add(e).map_or(-1, |id| id as i32)
+}
+
+/// Remove engine `id`; its id may be handed out again. Returns `false` for an unknown id.
+#[no_mangle]
+pub extern "C" fn ambientor_bank_remove(bank: *mut AmbientorBank, id: u32) -> bool {
+    if bank.is_null() { return false; }
+    let b = unsafe { &mut *bank };
+    b.bank.remove(id as usize).is_some()
+}
+
+/// Ramp engine `id` to linear `gain` (0..4) over `ramp_ms` (0 jumps). Engines
+/// faded to 0 stop rendering until faded back in. Returns `false` for an unknown id.
+#[no_mangle]
+pub extern "C" fn ambientor_bank_set_gain(bank: *mut AmbientorBank, id: u32, gain: f32, ramp_ms: f32) -> bool {
+    if bank.is_null() { return false; }
+    let b = unsafe { &mut *bank };
+    let gain = finite_or("ambientor_bank_set_gain", gain, 1.0);
+    let ramp_ms = finite_or("ambientor_bank_set_gain", ramp_ms, 0.0);
+    b.bank.set_gain(id as usize, gain, ramp_ms * 0.001)
+}
+
+/// Balance engine `id` between -1 (left only) and +1 (right only); 0 leaves
+/// its stereo image unchanged. Returns `false` for an unknown id.
+#[no_mangle]
+pub extern "C" fn ambientor_bank_set_pan(bank: *mut AmbientorBank, id: u32, pan: f32) -> bool {
+    if bank.is_null() { return false; }
+    let b = unsafe { &mut *bank };
+    b.bank.set_pan(id as usize, finite_or("ambientor_bank_set_pan", pan, 0.0))
+}
+
+/// Set engine `id`'s scene intensity (0..1). Returns `false` for an unknown id.
+#[no_mangle]
+pub extern "C" fn ambientor_bank_set_intensity(bank: *mut AmbientorBank, id: u32, intensity: f32) -> bool {
+    if bank.is_null() { return false; }
+    let b = unsafe { &mut *bank };
+    let Some(e) = b.bank.engine_mut(id as usize) else { return false; };
+    if intensity.is_finite() { e.scene_mut().set_intensity(intensity); }
+    true
+}
+
+/// Ceiling (dBFS, -24..0) and release (ms) of the limiter on the mix.
+#[no_mangle]
+pub extern "C" fn ambientor_bank_set_limiter(bank: *mut AmbientorBank, ceiling_db: f32, release_ms: f32) {
+    if bank.is_null() { return; }
+    let b = unsafe { &mut *bank };
+    let sr = b.bank.sample_rate();
+    let lim = b.bank.limiter_mut();
+    lim.set_ceiling_db(finite_or("ambientor_bank_set_limiter", ceiling_db, -1.0));
+    lim.set_release_ms(finite_or("ambientor_bank_set_limiter", release_ms, 250.0), sr);
+}
+
+/// Change the sample rate of the bank and all its engines.
+#[no_mangle]
+pub extern "C" fn ambientor_bank_set_sample_rate(bank: *mut AmbientorBank, sample_rate: f32) {
+    if bank.is_null() { return; }
+    let b = unsafe { &mut *bank };
+    b.bank.set_sample_rate(sample_rate);
+}
+
+/// Render `frames` of the mix into an interleaved f32 buffer, as stereo pairs
+/// repeated across `channels` (1..32). Returns the number of frames rendered (0 on error).
+#[no_mangle]
+pub extern "C" fn ambientor_bank_render_interleaved_f32(
+    bank: *mut AmbientorBank,
+    out_interleaved: *mut f32,
+    frames: u32,
+    channels: u32,
+) -> u32 {
+    if bank.is_null() || out_interleaved.is_null() || frames == 0 || channels == 0 {
+        return 0;
+    }
+    let b = unsafe { &mut *bank };
+    let out = unsafe { std::slice::from_raw_parts_mut(out_interleaved, (frames as usize) * (channels as usize)) };
+    let ch = channels as usize;
+    let Some(map) = ChannelMap::stereo_pairs(ch) else { return 0 };
+    for frame in out.chunks_exact_mut(ch) {
+        let (l, r) = b.bank.next_stereo();
+        map.write_frame(frame, l, r);
+    }
+    frames
+}
+
+// --- Logging ---------------------------------------------------------------------
+
+/// Host `user_data` carried into the log sink; the host promises it may be
+/// used from whichever thread logs.
+struct LogUserData(*mut c_void);
+unsafe impl Send for LogUserData {}
+unsafe impl Sync for LogUserData {}
+
+impl LogUserData {
+    // a method, so closures capture the whole (Send) wrapper rather than the raw field
+    fn get(&self) -> *mut c_void { self.0 }
+}
+
+/// Route engine diagnostics (NaN/Inf recoveries, clamped or ignored parameters,
+/// …) to `callback` instead of stderr, with `user_data` passed through. The
+/// callback is process-wide, may run on any thread that renders or sets
+/// parameters (including the audio thread), and must not call back into the
+/// engine. The message pointer is only valid during the call. Pass a null
+/// callback to go back to stderr.
+#[no_mangle]
+pub extern "C" fn ambientor_set_log_callback(callback: AmbientorLogCallback, user_data: *mut c_void) {
+    let Some(cb) = callback else {
+        log::set_sink(None);
+        return;
+    };
+    let user = LogUserData(user_data);
+    log::set_sink(Some(Box::new(move |level: log::Level, msg: &str| {
+        let mut buf = [0u8; log::MAX_MESSAGE_LEN + 1];
+        for (d, &s) in buf.iter_mut().zip(msg.as_bytes()) {
+            *d = if s == 0 { b' ' } else { s };
+        }
+        cb(user.get(), level as u8, buf.as_ptr().cast());
+    })));
+}
+
+/// Only deliver messages at `level` or more severe (`AMBIENTOR_LOG_ERROR` ..
+/// `AMBIENTOR_LOG_DEBUG`; default `AMBIENTOR_LOG_INFO`). Returns `false` for
+/// an unknown level.
+#[no_mangle]
+pub extern "C" fn ambientor_set_log_level(level: u8) -> bool {
+    let Some(level) = log::Level::from_u8(level) else { return false; };
+    log::set_max_level(level);
+    true
 }